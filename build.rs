@@ -0,0 +1,13 @@
+// build.rs
+// Generates the gRPC service from proto/ct_scout.proto, using the bundled protoc
+// so builds don't need one installed
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let protoc = protoc_bin_vendored::protoc_bin_path()?;
+    // SAFETY: build scripts are single-threaded
+    unsafe { std::env::set_var("PROTOC", protoc) };
+    println!("cargo:rerun-if-changed=proto/ct_scout.proto");
+    tonic_prost_build::configure()
+        .compile_protos(&["proto/ct_scout.proto"], &["proto"])?;
+    Ok(())
+}
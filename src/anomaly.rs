@@ -0,0 +1,197 @@
+// src/anomaly.rs
+//! Match-rate anomaly alerts
+//!
+//! Matches are counted per program in hourly buckets. When the current hour's
+//! count climbs past `spike_multiple` times the program's average over the
+//! previous `baseline_hours`, an alert goes to the webhook: a sudden burst of
+//! new certificates for one org usually means an infrastructure rollout.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::config::AnomalyConfig;
+use crate::metrics;
+use crate::notifier::Notifier;
+
+/// A program whose matches this hour far exceed its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateSpike {
+    pub program: String,
+    pub matches: u64,  // So far this hour
+    pub baseline: f64,  // Average matches per hour before it
+}
+
+/// Hourly match counts per program
+pub struct MatchRateTracker {
+    spike_multiple: f64,
+    baseline_hours: u64,
+    min_matches: u64,
+    /// Hours before this one have no data, so they don't count towards the baseline
+    started_hour: u64,
+    programs: HashMap<String, ProgramRate>,
+}
+
+#[derive(Default)]
+struct ProgramRate {
+    /// (hour, matches), oldest first; hours without matches are absent
+    hours: VecDeque<(u64, u64)>,
+    /// Hour an alert was last raised, so a spike alerts only once
+    alerted_hour: Option<u64>,
+}
+
+impl MatchRateTracker {
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self {
+            spike_multiple: config.spike_multiple,
+            baseline_hours: config.baseline_hours.max(1),
+            min_matches: config.min_matches,
+            started_hour: current_hour(),
+            programs: HashMap::new(),
+        }
+    }
+
+    /// Count a match for `program`, returning a spike the first time this hour crosses the threshold
+    pub fn record(&mut self, program: &str) -> Option<RateSpike> {
+        self.record_at(program, current_hour())
+    }
+
+    fn record_at(&mut self, program: &str, hour: u64) -> Option<RateSpike> {
+        let rate = self.programs.entry(program.to_string()).or_default();
+
+        while rate.hours.front().is_some_and(|(h, _)| h + self.baseline_hours < hour) {
+            rate.hours.pop_front();
+        }
+        let matches = match rate.hours.back_mut() {
+            Some((h, n)) if *h == hour => {
+                *n += 1;
+                *n
+            }
+            _ => {
+                rate.hours.push_back((hour, 1));
+                1
+            }
+        };
+
+        // Nothing to compare against during the first hour
+        let history = hour.saturating_sub(self.started_hour).min(self.baseline_hours);
+        if history == 0 || rate.alerted_hour == Some(hour) || matches < self.min_matches {
+            return None;
+        }
+
+        let past: u64 = rate
+            .hours
+            .iter()
+            .filter(|(h, _)| *h < hour && h + history >= hour)
+            .map(|(_, n)| n)
+            .sum();
+        let baseline = past as f64 / history as f64;
+        if (matches as f64) <= baseline * self.spike_multiple {
+            return None;
+        }
+
+        rate.alerted_hour = Some(hour);
+        Some(RateSpike {
+            program: program.to_string(),
+            matches,
+            baseline,
+        })
+    }
+}
+
+/// Tracks match rates and sends spike alerts through the webhook notifier
+pub struct AnomalyAlerter {
+    tracker: Mutex<MatchRateTracker>,
+    notifier: Notifier,
+}
+
+impl AnomalyAlerter {
+    pub fn new(config: &AnomalyConfig, notifier: Notifier) -> Self {
+        Self {
+            tracker: Mutex::new(MatchRateTracker::new(config)),
+            notifier,
+        }
+    }
+
+    /// Count a match for `program`; a spike is reported in the background
+    pub fn record(&self, program: &str) {
+        let Some(spike) = self.tracker.lock().unwrap().record(program) else {
+            return;
+        };
+
+        warn!(
+            "Match rate spike for {}: {} matches this hour vs. {:.1}/hour baseline",
+            spike.program, spike.matches, spike.baseline
+        );
+        metrics::MATCH_RATE_SPIKES_TOTAL
+            .with_label_values(&[&spike.program])
+            .inc();
+
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifier.notify_rate_spike(&spike).await {
+                warn!("Failed to send match rate alert: {:#}", e);
+            }
+        });
+    }
+}
+
+fn current_hour() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 3600)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(started_hour: u64) -> MatchRateTracker {
+        let mut tracker = MatchRateTracker::new(&AnomalyConfig {
+            spike_multiple: 3.0,
+            baseline_hours: 4,
+            min_matches: 5,
+        });
+        tracker.started_hour = started_hour;
+        tracker
+    }
+
+    fn record_n(tracker: &mut MatchRateTracker, program: &str, hour: u64, n: u64) -> Vec<RateSpike> {
+        (0..n).filter_map(|_| tracker.record_at(program, hour)).collect()
+    }
+
+    #[test]
+    fn test_alerts_once_when_rate_spikes() {
+        let mut tracker = tracker(100);
+
+        // First hour only builds the baseline
+        assert!(record_n(&mut tracker, "Acme", 100, 4).is_empty());
+        // 2 per hour on average over hours 100..=103
+        assert!(record_n(&mut tracker, "Acme", 103, 4).is_empty());
+        assert!(record_n(&mut tracker, "Other", 103, 4).is_empty());
+
+        // Past 3x the baseline at the 7th match, then quiet for the rest of the hour
+        let spikes = record_n(&mut tracker, "Acme", 104, 20);
+        assert_eq!(
+            spikes,
+            vec![RateSpike { program: "Acme".to_string(), matches: 7, baseline: 2.0 }]
+        );
+    }
+
+    #[test]
+    fn test_min_matches_and_window() {
+        let mut tracker = tracker(100);
+
+        // Far above the empty baseline, but too few to matter
+        assert!(record_n(&mut tracker, "Acme", 101, 4).is_empty());
+
+        // Hour 101 has left the 4 hour window by hour 106
+        let spikes = record_n(&mut tracker, "Acme", 106, 5);
+        assert_eq!(
+            spikes,
+            vec![RateSpike { program: "Acme".to_string(), matches: 5, baseline: 0.0 }]
+        );
+    }
+}
@@ -0,0 +1,616 @@
+// src/api.rs
+//! Optional REST API for integrations
+//!
+//! Every request needs `Authorization: Bearer <token>`; `GET /stream` also
+//! accepts `?token=<token>` for clients that can't set headers (such as a
+//! browser's `EventSource`).
+//!
+//! - `GET /stream`: Server-Sent Events, one `match` event per match as it is found
+//! - `GET /matches`: stored matches (needs `[database]`), filtered by the
+//!   `domain`, `program`, `since`, `until`, `limit` and `offset` query parameters
+//! - `GET /stats`: the same JSON as the stats export file
+//! - `GET /watchlist`: the live watchlist, laid out like `ct-scout export --json`
+//! - `POST /watchlist/{kind}` with `{"pattern": "..."}`: watch a global domain,
+//!   host, IP or CIDR (`kind` is `domains`, `hosts`, `ips` or `cidrs`)
+//! - `DELETE /watchlist/{kind}/{pattern}`: stop watching it (CIDRs may keep their `/`)
+//! - `GET /watchlist/snapshots`: watchlist copies taken before each platform sync
+//! - `POST /watchlist/rollback` with `{"snapshot": <id>}` (or `{}` for the newest):
+//!   put the watchlist back as it was in that snapshot
+//! - `POST /logs/pause`, `/logs/resume` and `/logs/reset` with `{"log": "<url>"}`:
+//!   stop or restart polling a log, or clear its failure history
+//! - `POST /sync`: sync bug bounty platforms now instead of at the next interval
+//! - `POST /state/save`: write the log positions out now
+//!
+//! Watchlist edits and paused logs apply to the running instance only; they
+//! are not written back to the config file.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{Method, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, broadcast};
+use tracing::{debug, info, warn};
+
+use crate::ct_log::LogHealthTracker;
+use crate::database::{DatabaseBackend, MatchQuery};
+use crate::output::OutputHandler;
+use crate::platforms::PlatformSyncManager;
+use crate::state::StateBackend;
+use crate::stats::StatsCollector;
+use crate::types::MatchResult;
+use crate::watchlist::{EntryKind, Watchlist};
+
+/// Matches returned when the request sets no `limit`
+const DEFAULT_MATCH_LIMIT: i64 = 100;
+
+/// Upper bound on `limit`, so one request can't pull the whole table
+const MAX_MATCH_LIMIT: i64 = 1000;
+
+/// Matches buffered per `/stream` client; a client further behind skips ahead
+const STREAM_BUFFER: usize = 256;
+
+/// What the handlers work on
+#[derive(Clone)]
+pub struct ApiState {
+    pub db: Option<Arc<dyn DatabaseBackend>>,
+    pub stats: StatsCollector,
+    pub watchlist: Arc<Mutex<Watchlist>>,
+    pub stream: Arc<MatchStream>,
+    pub health: Arc<LogHealthTracker>,
+    pub state: Arc<dyn StateBackend>,
+    pub platform_sync: Option<Arc<PlatformSyncManager>>,  // None = no platforms configured
+    pub token: Arc<str>,
+}
+
+/// Output handler fanning matches out to `/stream` and gRPC `WatchMatches` clients
+pub struct MatchStream {
+    tx: broadcast::Sender<Arc<MatchResult>>,
+}
+
+impl MatchStream {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(STREAM_BUFFER);
+        Self { tx }
+    }
+
+    /// Receive every match emitted from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<MatchResult>> {
+        self.tx.subscribe()
+    }
+
+    /// Clients currently subscribed
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for MatchStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OutputHandler for MatchStream {
+    fn name(&self) -> &'static str {
+        "stream"
+    }
+
+    async fn emit_match(&self, result: &MatchResult) -> Result<()> {
+        // No subscribers is fine; the match is simply not streamed
+        let _ = self.tx.send(Arc::new(result.clone()));
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Bind the listener up front so a bad address fails start-up
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!("Serving the REST API on http://{}", listener.local_addr()?);
+    Ok(listener)
+}
+
+/// Answer requests until the task is dropped
+pub async fn serve(listener: TcpListener, state: ApiState) {
+    if let Err(e) = axum::serve(listener, router(state)).await {
+        warn!("REST API stopped: {}", e);
+    }
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/stream", get(match_stream))
+        .route("/matches", get(matches))
+        .route("/stats", get(stats))
+        .route("/watchlist", get(watchlist))
+        .route("/watchlist/snapshots", get(watchlist_snapshots))
+        .route("/watchlist/rollback", post(rollback_watchlist))
+        .route("/watchlist/{kind}", post(add_entry))
+        .route("/watchlist/{kind}/{*pattern}", delete(remove_entry))
+        .route("/logs/{action}", post(control_log))
+        .route("/sync", post(sync_platforms))
+        .route("/state/save", post(save_state))
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state)
+}
+
+/// JSON `{"error": ...}` with a status code
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn authenticate(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    // Query strings end up in access logs and browser history, so only the
+    // read-only stream (for EventSource, which can't set headers) takes one
+    let query_allowed = request.method() == Method::GET && request.uri().path() == "/stream";
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            request
+                .uri()
+                .query()
+                .filter(|_| query_allowed)?
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        });
+    match presented {
+        Some(token) if constant_time_eq(token.as_bytes(), state.token.as_bytes()) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "missing or invalid bearer token".to_string()).into_response(),
+    }
+}
+
+/// Compare without returning early, so response times don't leak the token
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn match_stream(State(state): State<ApiState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.stream.subscribe();
+    let events = stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(result) => {
+                    let event = Event::default().event("match").json_data(&*result);
+                    match event {
+                        Ok(event) => return Some((Ok(event), rx)),
+                        Err(e) => debug!("Failed to encode streamed match: {}", e),
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    let event = Event::default().event("lagged").data(skipped.to_string());
+                    return Some((Ok(event), rx));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[derive(Deserialize)]
+struct MatchParams {
+    domain: Option<String>,
+    program: Option<String>,
+    since: Option<u64>,  // Unix timestamp
+    until: Option<u64>,  // Unix timestamp
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+async fn matches(State(state): State<ApiState>, Query(params): Query<MatchParams>) -> Result<Json<Value>, ApiError> {
+    let Some(ref db) = state.db else {
+        return Err(ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "matches are only stored with [database] enabled".to_string(),
+        ));
+    };
+
+    let query = MatchQuery {
+        domain_pattern: params.domain,
+        since: params.since,
+        until: params.until,
+        program_name: params.program,
+        limit: Some(params.limit.unwrap_or(DEFAULT_MATCH_LIMIT).clamp(1, MAX_MATCH_LIMIT)),
+        offset: params.offset,
+    };
+    let results = db
+        .get_matches(query)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    Ok(Json(json!(results)))
+}
+
+async fn stats(State(state): State<ApiState>) -> Json<Value> {
+    Json(state.stats.to_json())
+}
+
+async fn watchlist(State(state): State<ApiState>) -> Json<Value> {
+    Json(state.watchlist.lock().await.export_to_json())
+}
+
+#[derive(Deserialize)]
+struct EntryBody {
+    pattern: String,
+}
+
+async fn add_entry(
+    State(state): State<ApiState>,
+    Path(kind): Path<String>,
+    Json(body): Json<EntryBody>,
+) -> Result<StatusCode, ApiError> {
+    let kind: EntryKind = kind.parse().map_err(bad_request)?;
+    let added = state
+        .watchlist
+        .lock()
+        .await
+        .add_global(kind, &body.pattern)
+        .map_err(bad_request)?;
+    if added {
+        info!("REST API: now watching {:?} {}", kind, body.pattern);
+        Ok(StatusCode::CREATED)
+    } else {
+        Ok(StatusCode::OK)
+    }
+}
+
+async fn remove_entry(
+    State(state): State<ApiState>,
+    Path((kind, pattern)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let kind: EntryKind = kind.parse().map_err(bad_request)?;
+    let removed = state
+        .watchlist
+        .lock()
+        .await
+        .remove_global(kind, &pattern)
+        .map_err(bad_request)?;
+    if removed {
+        info!("REST API: stopped watching {:?} {}", kind, pattern);
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError(StatusCode::NOT_FOUND, format!("{} is not in the watchlist", pattern)))
+    }
+}
+
+async fn watchlist_snapshots(State(state): State<ApiState>) -> Result<Json<Value>, ApiError> {
+    let sync = platform_sync(&state)?;
+    Ok(Json(json!(sync.snapshots().await)))
+}
+
+#[derive(Deserialize)]
+struct RollbackBody {
+    snapshot: Option<u64>,  // None = newest
+}
+
+async fn rollback_watchlist(
+    State(state): State<ApiState>,
+    Json(body): Json<RollbackBody>,
+) -> Result<Json<Value>, ApiError> {
+    let sync = platform_sync(&state)?;
+    let restored = sync
+        .rollback(body.snapshot)
+        .await
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, format!("{:#}", e)))?;
+    info!("REST API: watchlist rolled back to snapshot {}", restored.id);
+    Ok(Json(json!(restored)))
+}
+
+#[derive(Deserialize)]
+struct LogBody {
+    log: String,
+}
+
+async fn control_log(
+    State(state): State<ApiState>,
+    Path(action): Path<String>,
+    Json(body): Json<LogBody>,
+) -> Result<StatusCode, ApiError> {
+    let changed = match action.as_str() {
+        "pause" => state.health.pause(&body.log).await,
+        "resume" => state.health.resume(&body.log).await,
+        "reset" => {
+            state.health.reset_log(&body.log).await;
+            true
+        }
+        _ => {
+            return Err(ApiError(
+                StatusCode::NOT_FOUND,
+                format!("unknown action {:?} (expected pause, resume or reset)", action),
+            ));
+        }
+    };
+    if changed {
+        info!("REST API: {} {}", action, body.log);
+    }
+    Ok(if changed { StatusCode::NO_CONTENT } else { StatusCode::OK })
+}
+
+async fn sync_platforms(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    platform_sync(&state)?.request_sync();
+    Ok(StatusCode::ACCEPTED)
+}
+
+fn platform_sync(state: &ApiState) -> Result<&PlatformSyncManager, ApiError> {
+    state
+        .platform_sync
+        .as_deref()
+        .ok_or_else(|| ApiError(StatusCode::SERVICE_UNAVAILABLE, "no platforms are enabled".to_string()))
+}
+
+async fn save_state(State(state): State<ApiState>) -> Result<StatusCode, ApiError> {
+    state
+        .state
+        .save()
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    info!("REST API: state saved");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn bad_request(e: anyhow::Error) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, format!("{:#}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StateManager;
+    use reqwest::Client;
+    use tempfile::TempDir;
+
+    async fn start() -> (String, ApiState, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let state_manager = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        let stats = StatsCollector::new();
+        stats.increment_processed();
+        let state = ApiState {
+            db: None,
+            stats,
+            watchlist: Arc::new(Mutex::new(Watchlist::default())),
+            stream: Arc::new(MatchStream::new()),
+            health: Arc::new(LogHealthTracker::default()),
+            state: Arc::new(state_manager),
+            platform_sync: None,
+            token: Arc::from("secret"),
+        };
+        let listener = bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, state.clone()));
+        (base, state, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let (base, _, _dir) = start().await;
+        let client = Client::new();
+
+        let response = client.get(format!("{}/stats", base)).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client.get(format!("{}/stats", base)).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), 401);
+
+        let response = client.get(format!("{}/stats", base)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["total_processed"], 1);
+
+        // No database, no match history
+        let response = client.get(format!("{}/matches", base)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), 503);
+
+        // A token in the query string only opens the stream
+        let response = client.get(format!("{}/stats?token=secret", base)).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client.post(format!("{}/state/save?token=secret", base)).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+    }
+
+    #[tokio::test]
+    async fn test_edit_watchlist() {
+        let (base, state, _dir) = start().await;
+        let client = Client::new();
+        let add = |kind: &str, pattern: &str| {
+            client
+                .post(format!("{}/watchlist/{}", base, kind))
+                .bearer_auth("secret")
+                .json(&json!({ "pattern": pattern }))
+                .send()
+        };
+
+        assert_eq!(add("domains", "*.Example.com").await.unwrap().status(), 201);
+        assert_eq!(add("domains", "*.example.com").await.unwrap().status(), 200);
+        assert_eq!(add("cidrs", "10.0.0.0/8").await.unwrap().status(), 201);
+        assert_eq!(add("cidrs", "not a range").await.unwrap().status(), 400);
+        assert_eq!(add("programs", "acme").await.unwrap().status(), 400);
+        assert!(state.watchlist.lock().await.matches_domain("api.example.com"));
+
+        let response = client
+            .delete(format!("{}/watchlist/cidrs/10.0.0.0/8", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+        let response = client
+            .delete(format!("{}/watchlist/hosts/www.example.com", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        let body: Value = client
+            .get(format!("{}/watchlist", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["watchlist"]["domains"], json!(["*.example.com"]));
+        assert_eq!(body["watchlist"]["cidrs"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_stream_pushes_matches() {
+        let (base, state, _dir) = start().await;
+        let stream = state.stream;
+        let mut response = Client::new()
+            .get(format!("{}/stream?token=secret", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        // Wait for the handler to subscribe before emitting
+        while stream.subscriber_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let data = crate::types::CertData {
+            all_domains: Some(vec!["api.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(7),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let result = MatchResult::from_cert_data("api.example.com".to_string(), &data, None, None);
+        stream.emit_match(&result).await.unwrap();
+
+        let chunk = String::from_utf8(response.chunk().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(chunk.starts_with("event: match\ndata: {"));
+        assert!(chunk.contains("\"matched_domain\":\"api.example.com\""));
+    }
+
+    #[tokio::test]
+    async fn test_control_endpoints() {
+        let (base, state, dir) = start().await;
+        let client = Client::new();
+        let log = json!({ "log": "https://ct.example.com/log/" });
+        let control = |path: &str| {
+            client
+                .post(format!("{}{}", base, path))
+                .bearer_auth("secret")
+                .json(&log)
+                .send()
+        };
+
+        assert_eq!(control("/logs/pause").await.unwrap().status(), 204);
+        assert_eq!(control("/logs/pause").await.unwrap().status(), 200);
+        assert!(!state.health.should_poll("https://ct.example.com/log/").await);
+        assert_eq!(control("/logs/resume").await.unwrap().status(), 204);
+        assert!(state.health.should_poll("https://ct.example.com/log/").await);
+        assert_eq!(control("/logs/reset").await.unwrap().status(), 204);
+        assert_eq!(control("/logs/restart").await.unwrap().status(), 404);
+
+        // No platforms configured
+        assert_eq!(control("/sync").await.unwrap().status(), 503);
+        assert_eq!(control("/watchlist/rollback").await.unwrap().status(), 503);
+
+        state.state.update_index("https://ct.example.com/log/", 42).await;
+        assert_eq!(control("/state/save").await.unwrap().status(), 204);
+        let saved = std::fs::read_to_string(dir.path().join("state.toml")).unwrap();
+        assert!(saved.contains("42"));
+    }
+
+    /// Platform returning a program that matches every domain
+    struct JunkPlatform;
+
+    #[async_trait]
+    impl crate::platforms::PlatformAPI for JunkPlatform {
+        fn name(&self) -> &str {
+            "Junk"
+        }
+
+        async fn fetch_programs_with_options(
+            &self,
+            _options: crate::platforms::FetchOptions,
+        ) -> Result<Vec<crate::platforms::Program>> {
+            Ok(vec![crate::platforms::Program {
+                id: "1".to_string(),
+                name: "Junk".to_string(),
+                handle: "junk".to_string(),
+                domains: vec!["*".to_string()],
+                hosts: vec![],
+                in_scope: true,
+                platform: "Junk".to_string(),
+            }])
+        }
+
+        async fn test_connection(&self) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watchlist_rollback() {
+        let (_, mut state, _dir) = start().await;
+        state.watchlist.lock().await.add_domain_to_program("*.example.com", "Acme", None);
+        let sync = Arc::new(PlatformSyncManager::new(vec![Box::new(JunkPlatform)], state.watchlist.clone(), 24));
+        state.platform_sync = Some(sync.clone());
+        let listener = bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, state.clone()));
+        let client = Client::new();
+        let rollback = |body: Value| {
+            client
+                .post(format!("{}/watchlist/rollback", base))
+                .bearer_auth("secret")
+                .json(&body)
+                .send()
+        };
+
+        // Nothing synced yet, so nothing to go back to
+        assert_eq!(rollback(json!({})).await.unwrap().status(), 404);
+
+        sync.sync_all_platforms().await;
+        assert_eq!(state.watchlist.lock().await.programs().len(), 2);
+
+        let snapshots: Value = client
+            .get(format!("{}/watchlist/snapshots", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(snapshots[0]["reason"], "before Junk sync");
+        assert_eq!(snapshots[0]["programs"], 1);
+
+        assert_eq!(rollback(json!({ "snapshot": 7 })).await.unwrap().status(), 404);
+        let response = rollback(json!({ "snapshot": snapshots[0]["id"] })).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(state.watchlist.lock().await.programs().len(), 1);
+        assert_eq!(sync.snapshots().await.len(), 2);
+    }
+}
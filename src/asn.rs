@@ -0,0 +1,163 @@
+// src/asn.rs
+//! Prefixes announced by watched AS numbers
+//!
+//! `asns` watchlist entries match any address inside a prefix the AS
+//! currently announces in BGP. The prefix lists are fetched from RIPEstat's
+//! announced-prefixes endpoint at startup and then refreshed periodically,
+//! since announcements change over time.
+
+use anyhow::{Context, Result};
+use ipnet::IpNet;
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::AsnConfig;
+use crate::watchlist::Watchlist;
+
+#[derive(Deserialize)]
+struct AnnouncedPrefixes {
+    data: AnnouncedPrefixesData,
+}
+
+#[derive(Deserialize)]
+struct AnnouncedPrefixesData {
+    prefixes: Vec<AnnouncedPrefix>,
+}
+
+#[derive(Deserialize)]
+struct AnnouncedPrefix {
+    prefix: String,
+}
+
+pub struct AsnRefresher {
+    client: Client,
+    source_url: String,
+    interval: Duration,
+}
+
+impl AsnRefresher {
+    pub fn new(config: &AsnConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(crate::ct_log::http::DEFAULT_USER_AGENT)
+            .build()
+            .context("Failed to build ASN HTTP client")?;
+        Ok(Self {
+            client,
+            source_url: config.source_url.trim_end_matches('/').to_string(),
+            interval: Duration::from_secs(config.refresh_hours.max(1) * 3600),
+        })
+    }
+
+    /// Refresh now and then every interval until the task is aborted
+    pub fn spawn(self, watchlist: Arc<Mutex<Watchlist>>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            loop {
+                ticker.tick().await;
+                self.refresh(&watchlist).await;
+            }
+        })
+    }
+
+    /// Fetch the prefixes of every watched AS into the watchlist
+    ///
+    /// An AS whose lookup fails keeps the prefixes from the last refresh.
+    pub async fn refresh(&self, watchlist: &Mutex<Watchlist>) {
+        let mut asns: Vec<u32> = watchlist.lock().await.all_asns().copied().collect();
+        asns.sort_unstable();
+        asns.dedup();
+
+        for asn in asns {
+            match self.announced_prefixes(asn).await {
+                Ok(prefixes) => {
+                    info!("AS{} announces {} prefixes", asn, prefixes.len());
+                    watchlist.lock().await.asn_prefixes.insert(asn, prefixes);
+                }
+                Err(e) => warn!("Failed to refresh prefixes of AS{}: {:#}", asn, e),
+            }
+        }
+    }
+
+    async fn announced_prefixes(&self, asn: u32) -> Result<Vec<IpNet>> {
+        let url = format!("{}/data/announced-prefixes/data.json", self.source_url);
+        let response: AnnouncedPrefixes = self
+            .client
+            .get(&url)
+            .query(&[("resource", format!("AS{}", asn))])
+            .send()
+            .await
+            .context("Failed to reach BGP data source")?
+            .error_for_status()
+            .context("BGP data source rejected the request")?
+            .json()
+            .await
+            .context("Unexpected response from BGP data source")?;
+
+        Ok(response
+            .data
+            .prefixes
+            .iter()
+            .filter_map(|prefix| prefix.prefix.parse().ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProgramConfig, WatchlistConfig};
+    use crate::types::MatchType;
+    use std::net::IpAddr;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_refresh_matches_announced_prefixes() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/data/announced-prefixes/data.json"))
+            .and(query_param("resource", "AS64500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {
+                    "prefixes": [
+                        { "prefix": "198.51.100.0/24", "timelines": [] },
+                        { "prefix": "2001:db8::/32", "timelines": [] }
+                    ]
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let programs = vec![ProgramConfig {
+            name: "Acme".to_string(),
+            asns: vec!["AS64500".to_string()],
+            ..Default::default()
+        }];
+        let watchlist = Mutex::new(Watchlist::from_config(&WatchlistConfig::default(), &programs).unwrap());
+        let ip: IpAddr = "198.51.100.7".parse().unwrap();
+        assert!(!watchlist.lock().await.matches_ip(&ip));
+
+        let refresher = AsnRefresher::new(&AsnConfig {
+            source_url: server.uri(),
+            ..Default::default()
+        })
+        .unwrap();
+        refresher.refresh(&watchlist).await;
+
+        let watchlist = watchlist.lock().await;
+        assert!(watchlist.matches_ip(&ip));
+        assert!(watchlist.matches_ip(&"2001:db8::1".parse().unwrap()));
+        assert!(!watchlist.matches_ip(&"203.0.113.1".parse().unwrap()));
+        assert_eq!(
+            watchlist.rule_for_ip(&ip),
+            Some(("AS64500 (198.51.100.0/24)".to_string(), MatchType::Asn))
+        );
+        assert_eq!(watchlist.program_for_ip(&ip).map(|p| p.name.as_str()), Some("Acme"));
+    }
+}
@@ -1,18 +1,50 @@
 // src/cert_parser.rs
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use x509_parser::der_parser::oid::Oid;
 use x509_parser::extensions::ParsedExtension;
 use x509_parser::prelude::*;
+use x509_parser::public_key::PublicKey;
+
+use crate::types::{ChainCert, DistinguishedName, PkiEndpoints};
 
 /// Parsed certificate with extracted metadata
 #[derive(Debug, Clone)]
 pub struct ParsedCert {
     pub domains: Vec<String>,
+    pub ip_addresses: Vec<String>,
     pub not_before: Option<u64>,
     pub not_after: Option<u64>,
     pub fingerprint: String,
     pub issuer: Option<String>,
+    /// Subject name attributes
+    pub subject: DistinguishedName,
+    /// Issuer name attributes
+    pub issuer_dn: DistinguishedName,
+    /// Intermediate (and root) certificates submitted with the entry, issuer first
+    pub issuer_chain: Vec<ChainCert>,
+    /// CRL, OCSP and CA Issuers URLs
+    pub pki_endpoints: PkiEndpoints,
+    /// Serial number (hex)
+    pub serial_number: String,
+    /// Subject Key Identifier (hex)
+    pub subject_key_id: Option<String>,
+    /// Authority Key Identifier (hex), links the cert to its issuing CA key
+    pub authority_key_id: Option<String>,
+    /// Public key algorithm (RSA, EC, DSA, Ed25519, ...)
+    pub key_algorithm: Option<String>,
+    /// Public key size in bits
+    pub key_bits: Option<u32>,
+    /// Named curve for EC keys (P-256, P-384, ...)
+    pub key_curve: Option<String>,
+    /// Signature algorithm (e.g. sha256WithRSAEncryption)
+    pub signature_algorithm: Option<String>,
     pub is_precert: bool,
+    /// When the CT log added the entry (MerkleTreeLeaf timestamp, ms since epoch)
+    pub logged_at_ms: Option<u64>,
+    /// Raw DER encoding of the leaf certificate
+    pub der: Vec<u8>,
 }
 
 /// Certificate parser for extracting domains and metadata
@@ -32,57 +64,154 @@ impl CertificateParser {
         let der_bytes = base64::engine::general_purpose::STANDARD.decode(base64_der)
             .context("Failed to decode base64 certificate")?;
 
-        // Calculate SHA-256 fingerprint
-        let fingerprint = {
-            let mut hasher = Sha256::new();
-            hasher.update(&der_bytes);
-            hex::encode(hasher.finalize())
-        };
+        Self::extract_full_cert_from_der(&der_bytes, false) // parse_full is for regular certs
+    }
 
-        // Parse X.509 certificate
-        let (_, cert) = X509Certificate::from_der(&der_bytes)
-            .map_err(|e| anyhow::anyhow!("Failed to parse X.509 certificate: {:?}", e))?;
+    /// PEM-encode a DER certificate
+    pub fn to_pem(der: &[u8]) -> String {
+        use base64::Engine;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(der);
 
-        // Extract domains from Subject Alternative Name extension
-        let mut domains = Vec::new();
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        for line in encoded.as_bytes().chunks(64) {
+            // base64 output is ASCII
+            pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        pem
+    }
+
+    /// Convert an iPAddress SAN (4 or 16 raw bytes) to an IP address
+    fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+        match bytes.len() {
+            4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+            16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+            _ => None,
+        }
+    }
+
+    /// Serial number as lowercase hex (leading zero bytes stripped by DER)
+    fn extract_serial(cert: &TbsCertificate) -> String {
+        hex::encode(cert.raw_serial())
+    }
+
+    /// Subject and Authority Key Identifiers as lowercase hex
+    fn extract_key_ids(cert: &TbsCertificate) -> (Option<String>, Option<String>) {
+        let mut subject_key_id = None;
+        let mut authority_key_id = None;
 
-        // Check SAN extension (OID 2.5.29.17)
         for ext in cert.extensions() {
-            if let ParsedExtension::SubjectAlternativeName(san) = ext.parsed_extension() {
-                for general_name in &san.general_names {
-                    if let GeneralName::DNSName(dns_name) = general_name {
-                        domains.push(dns_name.to_string());
+            match ext.parsed_extension() {
+                ParsedExtension::SubjectKeyIdentifier(ski) => {
+                    subject_key_id = Some(hex::encode(ski.0));
+                }
+                ParsedExtension::AuthorityKeyIdentifier(aki) => {
+                    authority_key_id = aki.key_identifier.as_ref().map(|id| hex::encode(id.0));
+                }
+                _ => {}
+            }
+        }
+
+        (subject_key_id, authority_key_id)
+    }
+
+    /// CRL distribution point and Authority Information Access URLs
+    fn extract_pki_endpoints(cert: &TbsCertificate) -> PkiEndpoints {
+        let mut endpoints = PkiEndpoints::default();
+
+        for ext in cert.extensions() {
+            match ext.parsed_extension() {
+                ParsedExtension::CRLDistributionPoints(points) => {
+                    for point in points.iter() {
+                        if let Some(DistributionPointName::FullName(names)) = &point.distribution_point {
+                            endpoints.crl.extend(names.iter().filter_map(Self::uri));
+                        }
+                    }
+                }
+                ParsedExtension::AuthorityInfoAccess(aia) => {
+                    for desc in aia.iter() {
+                        let Some(url) = Self::uri(&desc.access_location) else {
+                            continue;
+                        };
+                        if desc.access_method == oid_registry::OID_PKIX_ACCESS_DESCRIPTOR_OCSP {
+                            endpoints.ocsp.push(url);
+                        } else if desc.access_method == oid_registry::OID_PKIX_ACCESS_DESCRIPTOR_CA_ISSUERS {
+                            endpoints.ca_issuers.push(url);
+                        }
                     }
                 }
+                _ => {}
             }
         }
 
-        // Fallback: Extract Common Name (CN) from subject if no SAN
-        if domains.is_empty() {
-            if let Some(cn) = Self::extract_cn(&cert) {
-                domains.push(cn);
+        endpoints
+    }
+
+    fn uri(name: &GeneralName) -> Option<String> {
+        match name {
+            GeneralName::URI(uri) => Some(uri.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Public key algorithm, size in bits and curve (EC keys only)
+    fn extract_key_info(cert: &TbsCertificate) -> (Option<String>, Option<u32>, Option<String>) {
+        let spki = cert.public_key();
+        let algorithm = &spki.algorithm.algorithm;
+
+        match spki.parsed() {
+            Ok(PublicKey::RSA(rsa)) => (Some("RSA".to_string()), Self::rsa_bits(rsa.modulus), None),
+            Ok(PublicKey::EC(point)) => {
+                let curve = spki
+                    .algorithm
+                    .parameters
+                    .as_ref()
+                    .and_then(|params| params.as_oid().ok())
+                    .map(|oid| Self::curve_name(&oid));
+                (Some("EC".to_string()), Some(point.key_size() as u32), curve)
+            }
+            Ok(PublicKey::DSA(y)) => (Some("DSA".to_string()), Some(y.len() as u32 * 8), None),
+            Ok(key @ (PublicKey::GostR3410(_) | PublicKey::GostR3410_2012(_))) => {
+                (Some("GOST".to_string()), Some(key.key_size() as u32), None)
+            }
+            _ if *algorithm == oid_registry::OID_SIG_ED25519 => {
+                (Some("Ed25519".to_string()), Some(256), None)
             }
+            _ => (Some(Self::oid_name(algorithm)), None, None),
         }
+    }
 
-        // Extract validity period
-        let not_before = Some(cert.validity().not_before.timestamp() as u64);
-        let not_after = Some(cert.validity().not_after.timestamp() as u64);
+    /// RSA modulus size in bits, ignoring DER sign padding
+    fn rsa_bits(modulus: &[u8]) -> Option<u32> {
+        let start = modulus.iter().position(|&b| b != 0)?;
+        let significant = &modulus[start..];
+        Some(significant.len() as u32 * 8 - significant[0].leading_zeros())
+    }
 
-        // Extract issuer
-        let issuer = Self::extract_issuer(&cert);
+    /// Common name for the NIST curves, registry short name otherwise
+    fn curve_name(oid: &Oid) -> String {
+        if *oid == oid_registry::OID_EC_P256 {
+            "P-256".to_string()
+        } else if *oid == oid_registry::OID_NIST_EC_P384 {
+            "P-384".to_string()
+        } else if *oid == oid_registry::OID_NIST_EC_P521 {
+            "P-521".to_string()
+        } else {
+            Self::oid_name(oid)
+        }
+    }
 
-        Ok(ParsedCert {
-            domains,
-            not_before,
-            not_after,
-            fingerprint,
-            issuer,
-            is_precert: false, // parse_full is for regular certs
-        })
+    /// Registry short name for an OID, or its dotted form if unknown
+    fn oid_name(oid: &Oid) -> String {
+        oid2sn(oid, oid_registry())
+            .map(str::to_string)
+            .unwrap_or_else(|_| oid.to_id_string())
     }
 
     /// Extract Common Name (CN) from certificate subject
-    fn extract_cn(cert: &X509Certificate) -> Option<String> {
+    #[allow(clippy::collapsible_if)]
+    fn extract_cn(cert: &TbsCertificate) -> Option<String> {
         for rdn in cert.subject().iter() {
             for attr in rdn.iter() {
                 if attr.attr_type() == &oid_registry::OID_X509_COMMON_NAME {
@@ -95,8 +224,25 @@ impl CertificateParser {
         None
     }
 
+    /// Pick out CN, O, OU and C (first value of each) from a subject or issuer name
+    fn extract_dn(name: &X509Name) -> DistinguishedName {
+        DistinguishedName {
+            common_name: Self::first_value(name.iter_common_name()),
+            organization: Self::first_value(name.iter_organization()),
+            organizational_unit: Self::first_value(name.iter_organizational_unit()),
+            country: Self::first_value(name.iter_country()),
+        }
+    }
+
+    fn first_value<'a>(
+        mut values: impl Iterator<Item = &'a AttributeTypeAndValue<'a>>,
+    ) -> Option<String> {
+        values.find_map(|attr| attr.as_str().ok()).map(str::to_string)
+    }
+
     /// Extract issuer from certificate
-    fn extract_issuer(cert: &X509Certificate) -> Option<String> {
+    #[allow(clippy::collapsible_if)]
+    fn extract_issuer(cert: &TbsCertificate) -> Option<String> {
         // Try to get CN from issuer
         for rdn in cert.issuer().iter() {
             for attr in rdn.iter() {
@@ -113,7 +259,8 @@ impl CertificateParser {
     }
 
     /// Parse CT log entry (handles both x509_entry and precert_entry types)
-    /// For precerts, uses extra_data which contains full certificate (more reliable than TBSCertificate)
+    /// For precerts, uses extra_data which contains full certificate (more reliable than TBSCertificate),
+    /// falling back to the TBSCertificate in leaf_input when extra_data is missing or truncated
     /// Returns full certificate metadata including validity dates and fingerprint
     ///
     /// # Arguments
@@ -134,7 +281,9 @@ impl CertificateParser {
         // Check entry type at bytes 10-11 (big-endian u16)
         let entry_type = ((leaf_bytes[10] as u16) << 8) | (leaf_bytes[11] as u16);
 
-        match entry_type {
+        let extra_bytes = base64::engine::general_purpose::STANDARD.decode(base64_extra_data);
+
+        let mut parsed = match entry_type {
             0 => {
                 // x509_entry: Certificate is in leaf_input
                 if leaf_bytes.len() < 15 {
@@ -156,34 +305,81 @@ impl CertificateParser {
                     anyhow::bail!("Precertificate parsing disabled");
                 }
 
-                // Parse from extra_data (contains full precertificate)
+                // Prefer the full precertificate from extra_data
                 // extra_data format: 3-byte length + full X.509 precert + chain
-
-                let extra_bytes = base64::engine::general_purpose::STANDARD.decode(base64_extra_data)
-                    .context("Failed to decode base64 extra_data")?;
-
-                if extra_bytes.len() < 3 {
-                    anyhow::bail!("extra_data too short for precert_entry");
+                // Some logs return sparse extra_data; fall back to the TBSCertificate in leaf_input
+                let from_extra_data = extra_bytes
+                    .as_deref()
+                    .ok()
+                    .and_then(Self::split_u24_prefixed)
+                    .map(|(precert_der, _)| Self::extract_full_cert_from_der(precert_der, true));
+
+                match from_extra_data {
+                    Some(Ok(parsed)) => Ok(parsed),
+                    _ => Self::parse_precert_tbs(&leaf_bytes),
                 }
-
-                // Read 3-byte big-endian length
-                let precert_len = ((extra_bytes[0] as usize) << 16)
-                    | ((extra_bytes[1] as usize) << 8)
-                    | (extra_bytes[2] as usize);
-
-                if extra_bytes.len() < 3 + precert_len {
-                    anyhow::bail!("extra_data truncated: expected {} bytes", 3 + precert_len);
-                }
-
-                // Extract precertificate DER (full X.509 certificate with poison extension)
-                let precert_der = &extra_bytes[3..3 + precert_len];
-
-                Self::extract_full_cert_from_der(precert_der, true)
             }
             _ => {
                 anyhow::bail!("Unknown entry type: {}", entry_type);
             }
+        }?;
+
+        parsed.logged_at_ms = Self::leaf_timestamp_ms(&leaf_bytes);
+        if let Ok(ref extra_bytes) = extra_bytes {
+            parsed.issuer_chain = Self::parse_chain(extra_bytes, parsed.is_precert).unwrap_or_default();
         }
+        Ok(parsed)
+    }
+
+    /// Parse the certificate chain from extra_data
+    ///
+    /// x509_entry: `certificate_chain<0..2^24-1>`
+    /// precert_entry: `pre_certificate` followed by `certificate_chain<0..2^24-1>`
+    /// Each chain element is itself a 3-byte length + DER certificate.
+    fn parse_chain(extra_bytes: &[u8], is_precert: bool) -> Option<Vec<ChainCert>> {
+        let mut rest = extra_bytes;
+        if is_precert {
+            rest = Self::split_u24_prefixed(rest)?.1;
+        }
+
+        let (mut chain_bytes, _) = Self::split_u24_prefixed(rest)?;
+        let mut chain = Vec::new();
+        while let Some((der, next)) = Self::split_u24_prefixed(chain_bytes) {
+            let name = X509Certificate::from_der(der)
+                .map(|(_, cert)| cert.subject().to_string())
+                .unwrap_or_default();
+            chain.push(ChainCert {
+                name,
+                fingerprint: hex::encode(Sha256::digest(der)),
+            });
+            chain_bytes = next;
+        }
+
+        Some(chain)
+    }
+
+    /// Split a 3-byte big-endian length prefixed field off the front of `bytes`
+    fn split_u24_prefixed(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+        let header = bytes.get(..3)?;
+        let len = ((header[0] as usize) << 16) | ((header[1] as usize) << 8) | (header[2] as usize);
+        let body = bytes.get(3..3 + len)?;
+        Some((body, &bytes[3 + len..]))
+    }
+
+    /// Log timestamp (ms) of a base64 `leaf_input`, without parsing the certificate
+    pub fn leaf_input_timestamp_ms(base64_leaf_input: &str) -> Option<u64> {
+        use base64::Engine;
+        let leaf_bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64_leaf_input)
+            .ok()?;
+        Self::leaf_timestamp_ms(&leaf_bytes)
+    }
+
+    /// Read the timestamp from a MerkleTreeLeaf header
+    /// Layout: version (1 byte), leaf_type (1 byte), timestamp (8 bytes, big-endian ms)
+    fn leaf_timestamp_ms(leaf_bytes: &[u8]) -> Option<u64> {
+        let bytes: [u8; 8] = leaf_bytes.get(2..10)?.try_into().ok()?;
+        Some(u64::from_be_bytes(bytes))
     }
 
     /// Legacy function for backward compatibility - parses with precerts enabled by default
@@ -194,6 +390,33 @@ impl CertificateParser {
 
     /// Extract full certificate metadata from DER-encoded certificate
     fn extract_full_cert_from_der(der_bytes: &[u8], is_precert: bool) -> Result<ParsedCert> {
+        // Parse X.509 certificate
+        let (_, cert) = X509Certificate::from_der(der_bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to parse certificate from DER: {:?}", e))?;
+
+        Ok(Self::extract_from_tbs(&cert.tbs_certificate, der_bytes, is_precert))
+    }
+
+    /// Parse the TBSCertificate embedded in a precert_entry leaf
+    /// Layout after entry_type: issuer_key_hash (32 bytes), 3-byte length + TBSCertificate DER
+    fn parse_precert_tbs(leaf_bytes: &[u8]) -> Result<ParsedCert> {
+        let (tbs_der, _) = leaf_bytes
+            .get(44..)
+            .and_then(Self::split_u24_prefixed)
+            .context("precert_entry leaf_input too short for TBSCertificate")?;
+
+        let (_, tbs) = TbsCertificate::from_der(tbs_der)
+            .map_err(|e| anyhow::anyhow!("Failed to parse precert TBSCertificate: {:?}", e))?;
+
+        let mut parsed = Self::extract_from_tbs(&tbs, tbs_der, true);
+        // No signed certificate to hand out; the fingerprint covers the TBS only
+        parsed.der.clear();
+        Ok(parsed)
+    }
+
+    /// Extract metadata from a (TBS) certificate; `der_bytes` is what gets fingerprinted
+    #[allow(clippy::collapsible_if)]
+    fn extract_from_tbs(cert: &TbsCertificate, der_bytes: &[u8], is_precert: bool) -> ParsedCert {
         // Calculate SHA-256 fingerprint
         let fingerprint = {
             let mut hasher = Sha256::new();
@@ -201,18 +424,21 @@ impl CertificateParser {
             hex::encode(hasher.finalize())
         };
 
-        // Parse X.509 certificate
-        let (_, cert) = X509Certificate::from_der(der_bytes)
-            .map_err(|e| anyhow::anyhow!("Failed to parse certificate from DER: {:?}", e))?;
-
-        // Extract domains from SAN
+        // Extract domains and IPs from SAN
         let mut domains = Vec::new();
+        let mut ip_addresses = Vec::new();
 
         for ext in cert.extensions() {
             if let ParsedExtension::SubjectAlternativeName(san) = ext.parsed_extension() {
                 for general_name in &san.general_names {
-                    if let GeneralName::DNSName(dns_name) = general_name {
-                        domains.push(dns_name.to_string());
+                    match general_name {
+                        GeneralName::DNSName(dns_name) => domains.push(dns_name.to_string()),
+                        GeneralName::IPAddress(bytes) => {
+                            if let Some(ip) = Self::ip_from_bytes(bytes) {
+                                ip_addresses.push(ip.to_string());
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -220,7 +446,7 @@ impl CertificateParser {
 
         // Fallback to CN if no SAN
         if domains.is_empty() {
-            if let Some(cn) = Self::extract_cn(&cert) {
+            if let Some(cn) = Self::extract_cn(cert) {
                 domains.push(cn);
             }
         }
@@ -230,16 +456,41 @@ impl CertificateParser {
         let not_after = Some(cert.validity().not_after.timestamp() as u64);
 
         // Extract issuer
-        let issuer = Self::extract_issuer(&cert);
+        let issuer = Self::extract_issuer(cert);
+        let subject = Self::extract_dn(cert.subject());
+        let issuer_dn = Self::extract_dn(cert.issuer());
+
+        // Extract serial number and key identifiers
+        let serial_number = Self::extract_serial(cert);
+        let (subject_key_id, authority_key_id) = Self::extract_key_ids(cert);
+        let pki_endpoints = Self::extract_pki_endpoints(cert);
+
+        // Extract key and signature algorithms (the TBS repeats the outer signature algorithm)
+        let (key_algorithm, key_bits, key_curve) = Self::extract_key_info(cert);
+        let signature_algorithm = Some(Self::oid_name(&cert.signature.algorithm));
 
-        Ok(ParsedCert {
+        ParsedCert {
             domains,
+            ip_addresses,
             not_before,
             not_after,
             fingerprint,
             issuer,
+            subject,
+            issuer_dn,
+            issuer_chain: Vec::new(),
+            pki_endpoints,
+            serial_number,
+            subject_key_id,
+            authority_key_id,
+            key_algorithm,
+            key_bits,
+            key_curve,
+            signature_algorithm,
             is_precert,
-        })
+            logged_at_ms: None,
+            der: der_bytes.to_vec(),
+        }
     }
 }
 
@@ -247,6 +498,150 @@ impl CertificateParser {
 mod tests {
     use super::*;
 
+    /// Self-signed P-256 cert: CN/SAN test.example.com, SAN IP 192.0.2.1, serial 0x0a1b2c3d,
+    /// CRL distribution point and OCSP/CA Issuers AIA entries under pki.example.internal
+    const TEST_CERT: &str = "MIICSTCCAe+gAwIBAgIEChssPTAKBggqhkjOPQQDAjAbMRkwFwYDVQQDDBB0ZXN0LmV4YW1wbGUuY29tMB4XDTI2MTAxNjE1Mjg0NloXDTM2MTAxMzE1Mjg0NlowGzEZMBcGA1UEAwwQdGVzdC5leGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABA/bM+L0NyaPK9wlTj0ySz/1Bvy1izl1oQjn0qCkQaXXZQyxafmJEYGvT1+kP4RDK8ZnaQ5B2BYA6VGS/RexhXajggEfMIIBGzAdBgNVHQ4EFgQUNACNSCO03UfFgWtgyvrKRWJeHf4wDwYDVR0TAQH/BAUwAwEB/zAhBgNVHREEGjAYghB0ZXN0LmV4YW1wbGUuY29thwTAAAIBMB8GA1UdIwQYMBaAFDQAjUgjtN1HxYFrYMr6ykViXh3+MDcGA1UdHwQwMC4wLKAqoCiGJmh0dHA6Ly9jcmwucGtpLmV4YW1wbGUuaW50ZXJuYWwvY2EuY3JsMGwGCCsGAQUFBwEBBGAwXjAsBggrBgEFBQcwAYYgaHR0cDovL29jc3AucGtpLmV4YW1wbGUuaW50ZXJuYWwwLgYIKwYBBQUHMAKGImh0dHA6Ly9wa2kuZXhhbXBsZS5pbnRlcm5hbC9jYS5jcnQwCgYIKoZIzj0EAwIDSAAwRQIhAPlVz0ysBmgyTWxj7ubHnrhtbcQ/qZqJ1c0cJBJDSR7zAiBDznj9SS+ad2rHBQlHdj2hQuGdgAytXwt4ojqlcrD3iA==";
+
+    #[test]
+    fn test_parse_full_certificate() {
+        let parsed = CertificateParser::parse_full(TEST_CERT).unwrap();
+
+        assert_eq!(parsed.domains, vec!["test.example.com"]);
+        assert_eq!(parsed.ip_addresses, vec!["192.0.2.1"]);
+        assert_eq!(parsed.serial_number, "0a1b2c3d");
+        assert_eq!(
+            parsed.subject_key_id.as_deref(),
+            Some("34008d4823b4dd47c5816b60cafaca45625e1dfe")
+        );
+        // Self-signed, so the AKI points back at its own key
+        assert_eq!(parsed.authority_key_id, parsed.subject_key_id);
+    }
+
+    #[test]
+    fn test_parse_pki_endpoints() {
+        let parsed = CertificateParser::parse_full(TEST_CERT).unwrap();
+
+        assert_eq!(parsed.pki_endpoints.crl, vec!["http://crl.pki.example.internal/ca.crl"]);
+        assert_eq!(parsed.pki_endpoints.ocsp, vec!["http://ocsp.pki.example.internal"]);
+        assert_eq!(parsed.pki_endpoints.ca_issuers, vec!["http://pki.example.internal/ca.crt"]);
+    }
+
+    #[test]
+    fn test_parse_key_and_signature_algorithms() {
+        let parsed = CertificateParser::parse_full(TEST_CERT).unwrap();
+
+        assert_eq!(parsed.key_algorithm.as_deref(), Some("EC"));
+        assert_eq!(parsed.key_bits, Some(256));
+        assert_eq!(parsed.key_curve.as_deref(), Some("P-256"));
+        assert_eq!(parsed.signature_algorithm.as_deref(), Some("ecdsa-with-SHA256"));
+    }
+
+    #[test]
+    fn test_parse_subject_and_issuer_names() {
+        let parsed = CertificateParser::parse_full(TEST_CERT).unwrap();
+
+        assert_eq!(parsed.subject.common_name.as_deref(), Some("test.example.com"));
+        assert_eq!(parsed.subject.organization, None);
+        assert_eq!(parsed.issuer_dn, parsed.subject);
+    }
+
+    #[test]
+    fn test_rsa_bits() {
+        // 1024-bit modulus with the DER sign byte
+        let mut modulus = vec![0x00, 0xc1];
+        modulus.extend_from_slice(&[0xff; 127]);
+        assert_eq!(CertificateParser::rsa_bits(&modulus), Some(1024));
+
+        // Odd sizes count only significant bits
+        let mut modulus = vec![0x7f];
+        modulus.extend_from_slice(&[0xff; 255]);
+        assert_eq!(CertificateParser::rsa_bits(&modulus), Some(2047));
+
+        assert_eq!(CertificateParser::rsa_bits(&[0, 0]), None);
+    }
+
+    /// Prefix `body` with its 3-byte big-endian length
+    fn u24_prefixed(body: &[u8]) -> Vec<u8> {
+        let mut out = (body.len() as u32).to_be_bytes()[1..].to_vec();
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn test_parse_log_entry_chain() {
+        use base64::Engine;
+        let engine = base64::engine::general_purpose::STANDARD;
+        let der = engine.decode(TEST_CERT).unwrap();
+
+        // MerkleTreeLeaf: version, leaf_type, timestamp, entry_type 0, cert, extensions
+        let mut leaf = vec![0u8, 0u8];
+        leaf.extend_from_slice(&1_700_000_000_000u64.to_be_bytes());
+        leaf.extend_from_slice(&[0, 0]);
+        leaf.extend_from_slice(&u24_prefixed(&der));
+        leaf.extend_from_slice(&[0, 0]);
+
+        // Chain of one (self-signed test cert standing in for the issuer)
+        let extra = u24_prefixed(&u24_prefixed(&der));
+
+        let parsed = CertificateParser::parse_log_entry(
+            &engine.encode(&leaf),
+            &engine.encode(&extra),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.issuer_chain.len(), 1);
+        assert_eq!(parsed.issuer_chain[0].name, "CN=test.example.com");
+        assert_eq!(parsed.issuer_chain[0].fingerprint, parsed.fingerprint);
+
+        // Precert extra_data carries the precert ahead of the chain
+        let precert_extra = [u24_prefixed(&der), extra].concat();
+        let chain = CertificateParser::parse_chain(&precert_extra, true).unwrap();
+        assert_eq!(chain.len(), 1);
+
+        // Truncated chains are ignored rather than failing the entry
+        assert!(CertificateParser::parse_chain(&[0, 0, 9, 1], false).is_none());
+    }
+
+    #[test]
+    fn test_to_pem() {
+        let parsed = CertificateParser::parse_full(TEST_CERT).unwrap();
+        let pem = CertificateParser::to_pem(&parsed.der);
+
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines.first(), Some(&"-----BEGIN CERTIFICATE-----"));
+        assert_eq!(lines.last(), Some(&"-----END CERTIFICATE-----"));
+        assert!(lines.iter().all(|line| line.len() <= 64));
+        assert_eq!(lines[1..lines.len() - 1].concat(), TEST_CERT);
+    }
+
+    #[test]
+    fn test_precert_falls_back_to_tbs() {
+        use base64::Engine;
+        let engine = base64::engine::general_purpose::STANDARD;
+        let der = engine.decode(TEST_CERT).unwrap();
+        let (_, cert) = X509Certificate::from_der(&der).unwrap();
+
+        // precert_entry leaf: header, entry_type 1, issuer_key_hash, TBSCertificate, extensions
+        let mut leaf = vec![0u8, 0u8];
+        leaf.extend_from_slice(&1_700_000_000_000u64.to_be_bytes());
+        leaf.extend_from_slice(&[0, 1]);
+        leaf.extend_from_slice(&[0xab; 32]);
+        leaf.extend_from_slice(&u24_prefixed(cert.tbs_certificate.as_ref()));
+        leaf.extend_from_slice(&[0, 0]);
+        let leaf = engine.encode(&leaf);
+
+        for extra_data in ["", "AAAB"] {
+            let parsed = CertificateParser::parse_log_entry(&leaf, extra_data, true).unwrap();
+            assert!(parsed.is_precert);
+            assert_eq!(parsed.domains, vec!["test.example.com"]);
+            assert_eq!(parsed.serial_number, "0a1b2c3d");
+            assert!(parsed.der.is_empty());
+        }
+
+        assert!(CertificateParser::parse_log_entry(&leaf, "", false).is_err());
+    }
+
     #[test]
     fn test_parse_empty_certificate() {
         // Invalid base64
@@ -260,4 +655,36 @@ mod tests {
         let short_input = base64::engine::general_purpose::STANDARD.encode(b"short");
         assert!(CertificateParser::parse_leaf_input(&short_input).is_err());
     }
+
+    #[test]
+    fn test_ip_from_bytes() {
+        assert_eq!(
+            CertificateParser::ip_from_bytes(&[192, 0, 2, 1]),
+            Some("192.0.2.1".parse().unwrap())
+        );
+
+        let mut v6 = [0u8; 16];
+        v6[0] = 0x20;
+        v6[1] = 0x01;
+        v6[2] = 0x0d;
+        v6[3] = 0xb8;
+        v6[15] = 1;
+        assert_eq!(
+            CertificateParser::ip_from_bytes(&v6),
+            Some("2001:db8::1".parse().unwrap())
+        );
+
+        // Name constraints use address+mask (8/32 bytes), not a single IP
+        assert_eq!(CertificateParser::ip_from_bytes(&[10, 0, 0, 0, 255, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_leaf_timestamp() {
+        let mut leaf = vec![0u8, 0u8];
+        leaf.extend_from_slice(&1_700_000_000_123u64.to_be_bytes());
+        leaf.extend_from_slice(&[0, 0]);
+
+        assert_eq!(CertificateParser::leaf_timestamp_ms(&leaf), Some(1_700_000_000_123));
+        assert_eq!(CertificateParser::leaf_timestamp_ms(&leaf[..9]), None);
+    }
 }
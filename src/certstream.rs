@@ -0,0 +1,86 @@
+// src/certstream.rs
+//! certstream WebSocket input
+//!
+//! Follows any certstream-compatible server (certstream-server, certstream-server-go,
+//! self-hosted or public) and feeds its `certificate_update` messages into the same
+//! pipeline as the CT log monitors, alongside or instead of polling logs directly.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::config::CertstreamConfig;
+use crate::ct_log::pipeline::CertSender;
+use crate::ct_log::replay::parse_line;
+use crate::ct_log::source::CertSource;
+
+/// certstream server as a certificate source
+pub struct CertstreamSource(pub CertstreamConfig);
+
+#[async_trait]
+impl CertSource for CertstreamSource {
+    fn name(&self) -> String {
+        format!("certstream {}", self.0.url)
+    }
+
+    async fn run(self: Box<Self>, cert_tx: CertSender, shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        run(self.0, cert_tx, shutdown_rx).await;
+        Ok(())
+    }
+}
+
+/// Stream certificates from `config.url` until shutdown, reconnecting after
+/// `reconnect_delay_secs` whenever the connection fails or closes
+pub async fn run(config: CertstreamConfig, cert_tx: CertSender, mut shutdown_rx: watch::Receiver<bool>) {
+    let delay = Duration::from_secs(config.reconnect_delay_secs.max(1));
+
+    loop {
+        tokio::select! {
+            result = stream(&config.url, &cert_tx) => match result {
+                Ok(()) => warn!("certstream server {} closed the connection", config.url),
+                Err(e) => warn!("certstream connection to {} failed: {:#}", config.url, e),
+            },
+            _ = shutdown_rx.changed() => break,
+        }
+
+        if cert_tx.is_closed() {
+            break;
+        }
+        info!("Reconnecting to certstream in {}s", delay.as_secs());
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown_rx.changed() => break,
+        }
+    }
+
+    debug!("certstream input stopped");
+}
+
+/// One connection: forward certificates until the server closes it
+async fn stream(url: &str, cert_tx: &CertSender) -> Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .context("Failed to connect")?;
+    info!("Connected to certstream server {}", url);
+
+    while let Some(message) = socket.next().await {
+        let text = match message.context("Failed to read message")? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        match parse_line(&text) {
+            Ok(Some(data)) => {
+                cert_tx.send(data).await?;
+            }
+            Ok(None) => {}
+            Err(e) => debug!("Skipping certstream message: {:#}", e),
+        }
+    }
+    Ok(())
+}
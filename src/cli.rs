@@ -1,19 +1,48 @@
 // src/cli.rs
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// CT-Scout: Certificate Transparency Log Monitor
 ///
 /// Monitor Certificate Transparency logs for domains matching your watchlist.
 /// Supports multiple output formats and notification methods.
+///
+/// Without a subcommand, ct-scout behaves like `ct-scout run`.
 #[derive(Parser, Debug, Clone)]
 #[command(name = "ct-scout")]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    // ===== Input & Configuration =====
-    /// Path to TOML config file
-    #[arg(short = 'c', long = "config", default_value = "config.toml")]
-    pub config: String,
+    /// Path to TOML config file; repeat to layer files (later ones override earlier ones)
+    #[arg(short = 'c', long = "config", default_value = "config.toml", global = true)]
+    pub config: Vec<String>,
+
+    /// Override a config value, e.g. --set ct_logs.poll_interval_secs=5 (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    pub set: Vec<String>,
+
+    /// Verbose logging (set log level to debug)
+    #[arg(short = 'v', long = "verbose", global = true)]
+    pub verbose: bool,
+
+    /// Quiet logging (set log level to warn)
+    #[arg(short = 'q', long = "quiet", global = true)]
+    pub quiet: bool,
 
+    /// Monitoring options when no subcommand is given
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Options for continuous monitoring (`run`, `scan` and `replay`)
+#[derive(Args, Debug, Clone)]
+pub struct RunArgs {
+    // ===== Input & Configuration =====
     /// Watch config file for changes and reload
     #[arg(short = 'w', long = "watch-config")]
     pub watch_config: bool,
@@ -22,6 +51,15 @@ pub struct Cli {
     #[arg(short = 'r', long = "root-domains")]
     pub root_domains: Option<String>,
 
+    /// Emit only matches whose registrable root domain was never seen before,
+    /// as the root itself; seen roots are kept in this file across runs
+    #[arg(long = "new-roots", value_name = "FILE")]
+    pub new_roots: Option<String>,
+
+    /// Emit only matches on short-lived certificates (see [output] short_lived_days)
+    #[arg(long = "short-lived-only")]
+    pub short_lived_only: bool,
+
     // ===== Output Format =====
     /// Output matches in JSONL format to stdout
     #[arg(short = 'j', long = "json")]
@@ -31,10 +69,23 @@ pub struct Cli {
     #[arg(long = "csv")]
     pub csv: bool,
 
+    /// Print only matched hostnames, each once and one per line, for piping into
+    /// other tools (e.g. `ct-scout --pipe | httpx`); logs and stats go to stderr
+    #[arg(short = 'p', long = "pipe")]
+    pub pipe: bool,
+
     /// Suppress all stdout output (webhook only mode)
     #[arg(short = 's', long = "silent")]
     pub silent: bool,
 
+    /// Include the full leaf certificate PEM in JSON and webhook output
+    #[arg(long = "include-pem")]
+    pub include_pem: bool,
+
+    /// How much of each match human-readable output shows
+    #[arg(long = "detail", value_enum, default_value_t = HumanDetail::Normal)]
+    pub detail: HumanDetail,
+
     // ===== Output Destination =====
     /// Write output to file instead of stdout
     #[arg(short = 'o', long = "output")]
@@ -91,34 +142,401 @@ pub struct Cli {
     #[arg(long = "stats-interval", default_value = "10")]
     pub stats_interval: u64,
 
+    /// Write a JSON stats snapshot (with per-log and per-program counts) to this file every interval
+    #[arg(long = "stats-file", value_name = "PATH")]
+    pub stats_file: Option<String>,
+
     /// Disable progress indicator
     #[arg(long = "no-progress")]
     pub no_progress: bool,
 
-    // ===== Logging =====
-    /// Verbose logging (set log level to debug)
-    #[arg(short = 'v', long = "verbose")]
-    pub verbose: bool,
-
-    /// Quiet logging (set log level to warn)
-    #[arg(short = 'q', long = "quiet")]
-    pub quiet: bool,
-
-    // ===== Utility Commands =====
-    /// Export current scope (config + platforms) to TOML format and exit
-    #[arg(long = "export-scope")]
+    /// Full-screen dashboard of log health, throughput and recent matches
+    /// (console logs are hidden; set logging.file to keep them)
+    #[arg(long = "tui")]
+    pub tui: bool,
+
+    // ===== Start Position =====
+    /// Start each log at the first entry logged since this time instead of the saved index,
+    /// e.g. "2024-06-01T00:00:00Z" or "7d" (ago)
+    #[arg(long = "since", value_parser = parse_since)]
+    pub since: Option<DateTime<Utc>>,
+
+    // ===== Stop Conditions =====
+    /// Stop monitoring after this long, e.g. "90m" or "2h" (units: s, m, h, d)
+    #[arg(long = "run-for", value_parser = parse_duration)]
+    pub run_for: Option<Duration>,
+
+    /// Stop monitoring once this many matches have been found
+    #[arg(long = "max-matches")]
+    pub max_matches: Option<u64>,
+
+    // ===== Deprecated =====
+    /// Same as `ct-scout export` (kept for existing scripts; honours --json and --output)
+    #[arg(long = "export-scope", hide = true)]
     pub export_scope: bool,
 
-    /// Dry-run platform sync: show what programs would be synced without actually syncing
-    #[arg(long = "dry-run-sync")]
+    /// Same as `ct-scout sync --dry-run` (kept for existing scripts)
+    #[arg(long = "dry-run-sync", hide = true)]
     pub dry_run_sync: bool,
 }
 
+/// Subcommands (default: `run`)
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Continuously monitor all configured logs (the default)
+    Run(RunArgs),
+
+    /// Scan a fixed entry range of a single log through the normal pipeline, then exit
+    Scan(ScanArgs),
+
+    /// Run captured certificates (certstream or ct-scout JSONL) through the pipeline, then exit
+    Replay(ReplayArgs),
+
+    /// Search matches stored in the database
+    Query(QueryArgs),
+
+    /// Fetch programs from the configured bug bounty platforms and print their scope
+    Sync(SyncArgs),
+
+    /// Export the current scope (config + platforms) as TOML
+    Export(ExportArgs),
+
+    /// Check the config file and watchlist, then exit
+    Validate,
+
+    /// Write a commented starter config to the --config path
+    Init(InitArgs),
+
+    /// Summarize saved log progress, stored matches and health (safe while `run` is active)
+    Status,
+
+    /// Inspect CT logs
+    Logs {
+        #[command(subcommand)]
+        command: LogsCommand,
+    },
+
+    /// Manage saved per-log progress
+    State {
+        #[command(subcommand)]
+        command: StateCommand,
+    },
+
+    /// List or roll back to watchlist snapshots taken before platform syncs
+    Watchlist {
+        #[command(subcommand)]
+        command: WatchlistCommand,
+    },
+
+    /// Measure watchlist matching speed on a list of names and show the busiest patterns
+    Bench(BenchArgs),
+
+    /// Check which watchlist entries and programs match the given domains or IPs
+    TestMatch {
+        /// Domains or IP addresses to test
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+
+    /// Print the JSON Schema of an output payload
+    Schema {
+        /// Payload to describe
+        #[arg(value_enum, default_value = "match")]
+        payload: SchemaPayload,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+    /// CT log URL
+    #[arg(long = "log")]
+    pub log: String,
+
+    /// First entry index to scan
+    #[arg(long = "start")]
+    pub start: u64,
+
+    /// Last entry index to scan (inclusive, capped at the current tree size)
+    #[arg(long = "end")]
+    pub end: u64,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReplayArgs {
+    /// JSONL file to read ("-" or omitted: stdin)
+    #[arg(value_name = "FILE")]
+    pub input: Option<String>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct QueryArgs {
+    /// Only matches whose domain contains this text (SQL LIKE pattern)
+    #[arg(long = "domain")]
+    pub domain: Option<String>,
+
+    /// Only matches for this program
+    #[arg(long = "program")]
+    pub program: Option<String>,
+
+    /// Only matches from the last N hours
+    #[arg(long = "since-hours")]
+    pub since_hours: Option<u64>,
+
+    /// Maximum number of matches to print
+    #[arg(long = "limit", default_value = "100")]
+    pub limit: i64,
+
+    /// Print matches as JSONL instead of human-readable text
+    #[arg(short = 'j', long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SyncArgs {
+    /// Only list programs without fetching their scope details
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExportArgs {
+    /// Write the export to a file instead of stdout
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+
+    /// Export as JSON instead of TOML
+    #[arg(short = 'j', long = "json")]
+    pub json: bool,
+
+    /// Only export the config scope, without syncing platform programs
+    #[arg(long = "no-sync")]
+    pub no_sync: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// File with one domain or IP per line (e.g. names from a previous run)
+    #[arg(long = "domains", value_name = "FILE")]
+    pub domains: String,
+
+    /// Times to match the whole file; more passes give steadier numbers
+    #[arg(long = "passes", default_value = "3")]
+    pub passes: u32,
+
+    /// Number of hot patterns to list
+    #[arg(long = "top", default_value = "10")]
+    pub top: usize,
+
+    /// Include programs from the enabled platforms (syncs them first)
+    #[arg(long = "sync")]
+    pub sync: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct InitArgs {
+    /// Domain to watch, e.g. "*.example.com" (repeatable)
+    #[arg(long = "domain")]
+    pub domains: Vec<String>,
+
+    /// Webhook URL for match notifications
+    #[arg(long = "webhook-url")]
+    pub webhook_url: Option<String>,
+
+    /// PostgreSQL URL for storing matches
+    #[arg(long = "database-url")]
+    pub database_url: Option<String>,
+
+    /// Redis URL for publishing matches
+    #[arg(long = "redis-url")]
+    pub redis_url: Option<String>,
+
+    /// HackerOne API username
+    #[arg(long = "hackerone-username", requires = "hackerone_token")]
+    pub hackerone_username: Option<String>,
+
+    /// HackerOne API token
+    #[arg(long = "hackerone-token", requires = "hackerone_username")]
+    pub hackerone_token: Option<String>,
+
+    /// Intigriti API token
+    #[arg(long = "intigriti-token")]
+    pub intigriti_token: Option<String>,
+
+    /// Don't prompt; unset values become commented examples
+    #[arg(short = 'y', long = "non-interactive")]
+    pub non_interactive: bool,
+
+    /// Overwrite an existing config file
+    #[arg(long = "force")]
+    pub force: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum LogsCommand {
+    /// Show the logs that would be monitored, with their saved index and lag
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum StateCommand {
+    /// Copy per-log indices and gaps between the TOML state file and the database
+    Migrate(MigrateArgs),
+
+    /// Import log positions saved by certspotter or gungnir into the configured state
+    Import(ImportArgs),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum WatchlistCommand {
+    /// List the kept snapshots, newest first
+    Snapshots,
+
+    /// Have the running instance put its watchlist back as it was in a snapshot
+    Rollback {
+        /// Snapshot to restore (default: the newest)
+        #[arg(long = "id")]
+        id: Option<u64>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MigrateArgs {
+    /// Backend to copy the state into (the other one is the source)
+    #[arg(long = "to", value_enum)]
+    pub to: StateStore,
+
+    /// Replace indices the destination has already advanced past
+    #[arg(long = "overwrite")]
+    pub overwrite: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ImportArgs {
+    /// Tool that wrote the positions
+    #[arg(long = "from", value_enum)]
+    pub from: ImportSource,
+
+    /// certspotter state directory, or gungnir position file
+    pub path: PathBuf,
+
+    /// Replace indices the saved state has already advanced past
+    #[arg(long = "overwrite")]
+    pub overwrite: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// State directory (e.g. ~/.certspotter) with a `logs/<log ID>/state.json` per log
+    Certspotter,
+    /// JSON object mapping each log URL to the next entry index
+    Gungnir,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HumanDetail {
+    /// Just the matched domain, one per line, for piping into other tools
+    Minimal,
+    /// Domain, program, rule, SANs and enrichment (default)
+    #[default]
+    Normal,
+    /// Normal plus issuer, validity, fingerprint, CT log and precert flag
+    Full,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaPayload {
+    /// A match, as written by --json, the API streams and Redis
+    Match,
+    /// The body POSTed to [webhook]
+    Webhook,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateStore {
+    /// The `ct_log_state` table
+    Db,
+    /// The `state_file` TOML file
+    Toml,
+}
+
 impl Cli {
+    /// Monitoring options, if the command monitors logs
+    pub fn run_args(&self) -> Option<&RunArgs> {
+        match self.command {
+            None => Some(&self.run),
+            Some(Command::Run(ref run)) => Some(run),
+            Some(Command::Scan(ref scan)) => Some(&scan.run),
+            Some(Command::Replay(ref replay)) => Some(&replay.run),
+            Some(_) => None,
+        }
+    }
+
+    /// Validate flag combinations and return errors for invalid usage
+    pub fn validate(&self) -> anyhow::Result<()> {
+        // Verbose and quiet are mutually exclusive
+        if self.verbose && self.quiet {
+            anyhow::bail!("Cannot specify both --verbose and --quiet");
+        }
+
+        if let Some(run) = self.run_args() {
+            run.validate()?;
+        }
+
+        // Scan range must not be empty
+        if let Some(Command::Scan(ref scan)) = self.command
+            && scan.start > scan.end
+        {
+            anyhow::bail!(
+                "scan --start ({}) must not be greater than --end ({})",
+                scan.start,
+                scan.end
+            );
+        }
+
+        // Scans take explicit indices
+        if let Some(Command::Scan(ref scan)) = self.command
+            && scan.run.since.is_some()
+        {
+            anyhow::bail!("--since cannot be used with scan; use --start/--end");
+        }
+
+        if let Some(Command::Replay(ref replay)) = self.command
+            && replay.run.since.is_some()
+        {
+            anyhow::bail!("--since cannot be used with replay");
+        }
+
+        Ok(())
+    }
+
+    /// Config files in merge order
+    pub fn config_paths(&self) -> Vec<PathBuf> {
+        self.config.iter().map(PathBuf::from).collect()
+    }
+
+    /// Determine log level based on verbose/quiet flags
+    pub fn log_level(&self) -> &str {
+        if self.verbose {
+            "debug"
+        } else if self.quiet {
+            "warn"
+        } else {
+            "info"
+        }
+    }
+}
+
+impl RunArgs {
     /// Validate flag combinations and return errors for invalid usage
     pub fn validate(&self) -> anyhow::Result<()> {
         // Cannot specify multiple output formats
-        let format_count = [self.json, self.csv, self.silent]
+        let format_count = [self.json, self.csv, self.pipe, self.silent]
             .iter()
             .filter(|&&x| x)
             .count();
@@ -126,10 +544,15 @@ impl Cli {
         if format_count > 1 {
             anyhow::bail!(
                 "Cannot specify multiple output formats. \
-                Choose one of: --json, --csv, or --silent"
+                Choose one of: --json, --csv, --pipe or --silent"
             );
         }
 
+        // The dashboard takes over the terminal that --pipe writes to
+        if self.pipe && self.tui {
+            anyhow::bail!("Cannot use --pipe with --tui");
+        }
+
         // Silent mode requires some output (webhook)
         if self.silent && self.no_webhook {
             anyhow::bail!(
@@ -143,11 +566,6 @@ impl Cli {
             anyhow::bail!("--stats-interval must be greater than 0");
         }
 
-        // Verbose and quiet are mutually exclusive
-        if self.verbose && self.quiet {
-            anyhow::bail!("Cannot specify both --verbose and --quiet");
-        }
-
         // Stats and no-stats are mutually exclusive
         if self.stats && self.no_stats {
             anyhow::bail!("Cannot specify both --stats and --no-stats");
@@ -163,6 +581,14 @@ impl Cli {
             anyhow::bail!("Cannot specify both --require-redis and --no-require-redis");
         }
 
+        // Stop conditions that are met immediately are almost certainly typos
+        if self.run_for.is_some_and(|d| d.is_zero()) {
+            anyhow::bail!("--run-for must be greater than 0");
+        }
+        if self.max_matches == Some(0) {
+            anyhow::bail!("--max-matches must be greater than 0");
+        }
+
         Ok(())
     }
 
@@ -172,6 +598,8 @@ impl Cli {
             OutputFormat::Json
         } else if self.csv {
             OutputFormat::Csv
+        } else if self.pipe {
+            OutputFormat::Pipe
         } else if self.silent {
             OutputFormat::Silent
         } else {
@@ -181,19 +609,37 @@ impl Cli {
 
     /// Check if progress indicator should be enabled
     pub fn should_show_progress(&self) -> bool {
-        !self.no_progress && !self.json && !self.csv && !self.silent
+        !self.no_progress && !self.json && !self.csv && !self.pipe && !self.silent && !self.tui
     }
+}
 
-    /// Determine log level based on verbose/quiet flags
-    pub fn log_level(&self) -> &str {
-        if self.verbose {
-            "debug"
-        } else if self.quiet {
-            "warn"
-        } else {
-            "info"
-        }
+/// Parse a duration like "45s", "30m", "2h" or "1d" (bare numbers are seconds)
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (expected e.g. 30m or 2h)", value))?;
+    let multiplier = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        other => return Err(format!("unknown duration unit '{}' (use s, m, h or d)", other)),
+    };
+    Ok(Duration::from_secs(amount * multiplier))
+}
+
+/// Parse an RFC 3339 timestamp, or a duration ("7d", "12h") meaning that long ago
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value.trim()) {
+        return Ok(timestamp.with_timezone(&Utc));
     }
+    let ago = parse_duration(value)
+        .map_err(|_| format!("invalid time '{}' (expected e.g. 2024-06-01T00:00:00Z or 7d)", value))?;
+    let ago = chrono::Duration::from_std(ago).map_err(|e| e.to_string())?;
+    Ok(Utc::now() - ago)
 }
 
 /// Output format selection
@@ -205,48 +651,60 @@ pub enum OutputFormat {
     Json,
     /// CSV format
     Csv,
+    /// Unique plain hostnames, one per line
+    Pipe,
     /// No stdout output
     Silent,
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_default_config_path() {
         let cli = Cli::parse_from(&["ct-scout"]);
-        assert_eq!(cli.config, "config.toml");
+        assert_eq!(cli.config, vec!["config.toml"]);
     }
 
     #[test]
     fn test_custom_config_path() {
         let cli = Cli::parse_from(&["ct-scout", "--config", "custom.toml"]);
-        assert_eq!(cli.config, "custom.toml");
+        assert_eq!(cli.config, vec!["custom.toml"]);
     }
 
     #[test]
     fn test_json_output_format() {
         let cli = Cli::parse_from(&["ct-scout", "--json"]);
-        assert_eq!(cli.output_format(), OutputFormat::Json);
+        assert_eq!(cli.run.output_format(), OutputFormat::Json);
     }
 
     #[test]
     fn test_csv_output_format() {
         let cli = Cli::parse_from(&["ct-scout", "--csv"]);
-        assert_eq!(cli.output_format(), OutputFormat::Csv);
+        assert_eq!(cli.run.output_format(), OutputFormat::Csv);
+    }
+
+    #[test]
+    fn test_pipe_output_format() {
+        let cli = Cli::parse_from(&["ct-scout", "--pipe"]);
+        assert_eq!(cli.run.output_format(), OutputFormat::Pipe);
+        assert!(!cli.run.should_show_progress());
+        assert!(Cli::parse_from(&["ct-scout", "--pipe", "--json"]).validate().is_err());
+        assert!(Cli::parse_from(&["ct-scout", "--pipe", "--tui"]).validate().is_err());
     }
 
     #[test]
     fn test_silent_output_format() {
         let cli = Cli::parse_from(&["ct-scout", "--silent"]);
-        assert_eq!(cli.output_format(), OutputFormat::Silent);
+        assert_eq!(cli.run.output_format(), OutputFormat::Silent);
     }
 
     #[test]
     fn test_default_is_human() {
         let cli = Cli::parse_from(&["ct-scout"]);
-        assert_eq!(cli.output_format(), OutputFormat::Human);
+        assert_eq!(cli.run.output_format(), OutputFormat::Human);
     }
 
     #[test]
@@ -276,13 +734,13 @@ mod tests {
     #[test]
     fn test_progress_disabled_for_json() {
         let cli = Cli::parse_from(&["ct-scout", "--json"]);
-        assert!(!cli.should_show_progress());
+        assert!(!cli.run.should_show_progress());
     }
 
     #[test]
     fn test_progress_enabled_by_default() {
         let cli = Cli::parse_from(&["ct-scout"]);
-        assert!(cli.should_show_progress());
+        assert!(cli.run.should_show_progress());
     }
 
     #[test]
@@ -303,6 +761,70 @@ mod tests {
         assert_eq!(cli.log_level(), "info");
     }
 
+    #[test]
+    fn test_scan_command() {
+        let cli = Cli::parse_from(&[
+            "ct-scout",
+            "scan", "--json", "--log", "https://ct.example.com/log/", "--start", "100", "--end", "200",
+        ]);
+        assert!(cli.validate().is_ok());
+        match cli.command {
+            Some(Command::Scan(scan)) => {
+                assert!(scan.run.json);
+                assert_eq!(scan.log, "https://ct.example.com/log/");
+                assert_eq!((scan.start, scan.end), (100, 200));
+            }
+            _ => panic!("expected scan command"),
+        }
+    }
+
+    #[test]
+    fn test_replay_command() {
+        let cli = Cli::parse_from(&["ct-scout", "replay", "captured.jsonl", "--json", "--no-webhook"]);
+        assert!(cli.validate().is_ok());
+        match cli.command {
+            Some(Command::Replay(ref replay)) => {
+                assert_eq!(replay.input.as_deref(), Some("captured.jsonl"));
+                assert!(replay.run.no_webhook);
+            }
+            _ => panic!("expected replay command"),
+        }
+        assert!(cli.run_args().unwrap().json);
+
+        let stdin = Cli::parse_from(&["ct-scout", "replay"]);
+        assert!(matches!(stdin.command, Some(Command::Replay(ref replay)) if replay.input.is_none()));
+    }
+
+    #[test]
+    fn test_deprecated_flags_still_parse() {
+        let cli = Cli::parse_from(&["ct-scout", "--dry-run-sync"]);
+        assert!(cli.run.dry_run_sync);
+        let cli = Cli::parse_from(&["ct-scout", "--export-scope", "--json"]);
+        assert!(cli.run.export_scope);
+    }
+
+    #[test]
+    fn test_watchlist_rollback_args() {
+        let cli = Cli::parse_from(&["ct-scout", "watchlist", "rollback", "--id", "3"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Watchlist { command: WatchlistCommand::Rollback { id: Some(3) } })
+        ));
+        let cli = Cli::parse_from(&["ct-scout", "watchlist", "rollback"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Watchlist { command: WatchlistCommand::Rollback { id: None } })
+        ));
+    }
+
+    #[test]
+    fn test_scan_reversed_range_invalid() {
+        let cli = Cli::parse_from(&[
+            "ct-scout", "scan", "--log", "https://ct.example.com/log/", "--start", "10", "--end", "5",
+        ]);
+        assert!(cli.validate().is_err());
+    }
+
     #[test]
     fn test_short_flags() {
         let cli = Cli::parse_from(&[
@@ -313,10 +835,137 @@ mod tests {
             "-w",
             "-s",
         ]);
-        assert_eq!(cli.config, "test.toml");
-        assert!(cli.json);
-        assert_eq!(cli.root_domains, Some("roots.txt".to_string()));
-        assert!(cli.watch_config);
-        assert!(cli.silent);
+        assert_eq!(cli.config, vec!["test.toml"]);
+        assert!(cli.run.json);
+        assert_eq!(cli.run.root_domains, Some("roots.txt".to_string()));
+        assert_eq!(cli.run.new_roots, None);
+        assert!(cli.run.watch_config);
+        assert!(cli.run.silent);
+    }
+
+    #[test]
+    fn test_run_subcommand_matches_default() {
+        let cli = Cli::parse_from(&["ct-scout", "run", "--csv", "--no-progress"]);
+        let run = cli.run_args().unwrap();
+        assert_eq!(run.output_format(), OutputFormat::Csv);
+        assert!(!run.should_show_progress());
+
+        let cli = Cli::parse_from(&["ct-scout", "run", "--json", "--csv"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_tui_replaces_progress() {
+        let cli = Cli::parse_from(&["ct-scout", "run", "--tui"]);
+        let run = cli.run_args().unwrap();
+        assert!(run.tui);
+        assert_eq!(run.output_format(), OutputFormat::Human);
+        assert!(!run.should_show_progress());
+    }
+
+    #[test]
+    fn test_layered_config_args() {
+        let cli = Cli::parse_from(&[
+            "ct-scout", "-c", "base.toml", "-c", "prod.toml",
+            "--set", "ct_logs.poll_interval_secs=5", "--set", "logging.level=debug",
+        ]);
+        assert_eq!(cli.config_paths(), vec![PathBuf::from("base.toml"), PathBuf::from("prod.toml")]);
+        assert_eq!(cli.set, vec!["ct_logs.poll_interval_secs=5", "logging.level=debug"]);
+    }
+
+    #[test]
+    fn test_stop_conditions() {
+        let cli = Cli::parse_from(&["ct-scout", "run", "--run-for", "2h", "--max-matches", "50"]);
+        let run = cli.run_args().unwrap();
+        assert_eq!(run.run_for, Some(Duration::from_secs(7200)));
+        assert_eq!(run.max_matches, Some(50));
+
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("h").is_err());
+
+        assert!(Cli::parse_from(&["ct-scout", "--max-matches", "0"]).validate().is_err());
+        assert!(Cli::parse_from(&["ct-scout", "--run-for", "0s"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_since() {
+        let cli = Cli::parse_from(&["ct-scout", "run", "--since", "2024-06-01T00:00:00Z"]);
+        assert_eq!(
+            cli.run_args().unwrap().since.unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+
+        let week_ago = parse_since("7d").unwrap();
+        let expected = Utc::now() - chrono::Duration::days(7);
+        assert!((week_ago - expected).num_seconds().abs() < 5);
+
+        assert!(parse_since("last tuesday").is_err());
+        assert!(
+            Cli::parse_from(&["ct-scout", "scan", "--log", "https://ct.example.com/", "--start", "0", "--end", "1", "--since", "1d"])
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_utility_subcommands() {
+        let cli = Cli::parse_from(&["ct-scout", "test-match", "api.example.com", "-c", "other.toml"]);
+        assert_eq!(cli.config, vec!["other.toml"]);
+        assert!(cli.run_args().is_none());
+        match cli.command {
+            Some(Command::TestMatch { targets }) => assert_eq!(targets, vec!["api.example.com"]),
+            _ => panic!("expected test-match command"),
+        }
+
+        let cli = Cli::parse_from(&["ct-scout", "query", "--program", "Acme", "--limit", "5"]);
+        match cli.command {
+            Some(Command::Query(query)) => {
+                assert_eq!(query.program.as_deref(), Some("Acme"));
+                assert_eq!(query.limit, 5);
+            }
+            _ => panic!("expected query command"),
+        }
+
+        assert!(matches!(Cli::parse_from(&["ct-scout", "status"]).command, Some(Command::Status)));
+        match Cli::parse_from(&["ct-scout", "bench", "--domains", "names.txt", "--top", "5"]).command {
+            Some(Command::Bench(bench)) => {
+                assert_eq!(bench.domains, "names.txt");
+                assert_eq!((bench.passes, bench.top, bench.sync), (3, 5, false));
+            }
+            _ => panic!("expected bench command"),
+        }
+        match Cli::parse_from(&["ct-scout", "init", "-c", "new.toml", "-y", "--domain", "*.a.com", "--domain", "b.com"]).command {
+            Some(Command::Init(init)) => {
+                assert!(init.non_interactive);
+                assert_eq!(init.domains, vec!["*.a.com", "b.com"]);
+            }
+            _ => panic!("expected init command"),
+        }
+        assert!(Cli::try_parse_from(["ct-scout", "init", "--hackerone-username", "alice"]).is_err());
+        assert!(matches!(
+            Cli::parse_from(&["ct-scout", "logs", "list"]).command,
+            Some(Command::Logs { command: LogsCommand::List })
+        ));
+        match Cli::parse_from(&["ct-scout", "state", "migrate", "--to", "db"]).command {
+            Some(Command::State { command: StateCommand::Migrate(migrate) }) => {
+                assert_eq!(migrate.to, StateStore::Db);
+                assert!(!migrate.overwrite);
+            }
+            _ => panic!("expected state migrate command"),
+        }
+        assert!(Cli::try_parse_from(["ct-scout", "state", "migrate", "--to", "redis"]).is_err());
+        match Cli::parse_from(&["ct-scout", "state", "import", "--from", "certspotter", "/var/lib/certspotter"]).command {
+            Some(Command::State { command: StateCommand::Import(import) }) => {
+                assert_eq!(import.from, ImportSource::Certspotter);
+                assert_eq!(import.path, PathBuf::from("/var/lib/certspotter"));
+            }
+            _ => panic!("expected state import command"),
+        }
+
+        // Monitoring flags belong to `run`, not to utility commands
+        assert!(Cli::try_parse_from(["ct-scout", "--json", "validate"]).is_err());
     }
 }
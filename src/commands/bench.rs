@@ -0,0 +1,187 @@
+// src/commands/bench.rs
+//! `ct-scout bench`: time watchlist matching against a list of names
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::cli::BenchArgs;
+use crate::config::Config;
+use crate::watchlist::Watchlist;
+
+/// A benchmark input line, parsed up front so only matching is timed
+enum Target<'a> {
+    Domain(&'a str),
+    Ip(IpAddr),
+}
+
+struct Report {
+    lookups: u64,
+    elapsed: Duration,
+    matched: usize,  // Per pass
+    hot: Vec<(String, usize)>,  // Matching pattern -> inputs it matched, most first
+}
+
+pub async fn run(config: &Config, args: &BenchArgs) -> Result<()> {
+    let watchlist = if args.sync {
+        super::scope_watchlist(config).await?
+    } else {
+        Watchlist::from_config(&config.watchlist, &config.programs)?
+    };
+
+    let text = std::fs::read_to_string(&args.domains)
+        .with_context(|| format!("Failed to read {}", args.domains))?;
+    let targets: Vec<Target> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse().map_or(Target::Domain(line), Target::Ip))
+        .collect();
+    if targets.is_empty() {
+        anyhow::bail!("{} contains no domains", args.domains);
+    }
+
+    let report = measure(&watchlist, &targets, args.passes.max(1));
+    println!("{}", summary(&watchlist));
+    println!("Input: {} names from {}, {} passes", targets.len(), args.domains, args.passes.max(1));
+    print!("{}", render(&report, targets.len(), args.top));
+    Ok(())
+}
+
+fn measure(watchlist: &Watchlist, targets: &[Target], passes: u32) -> Report {
+    let mut matched = 0;
+    let started = Instant::now();
+    for _ in 0..passes {
+        for target in targets {
+            let hit = match *target {
+                Target::Domain(domain) => watchlist.matches_domain(black_box(domain)),
+                Target::Ip(ref ip) => watchlist.matches_ip(black_box(ip)),
+            };
+            if black_box(hit) {
+                matched += 1;
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    // Attribution is a separate, untimed pass
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for target in targets {
+        let pattern = match *target {
+            Target::Domain(domain) => watchlist.pattern_for_domain(domain).map(str::to_string),
+            Target::Ip(ref ip) => watchlist.range_for_ip(ip),
+        };
+        if let Some(pattern) = pattern {
+            *counts.entry(pattern).or_insert(0) += 1;
+        }
+    }
+    let mut hot: Vec<(String, usize)> = counts.into_iter().collect();
+    hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Report {
+        lookups: targets.len() as u64 * passes as u64,
+        elapsed,
+        matched: matched / passes as usize,
+        hot,
+    }
+}
+
+fn summary(watchlist: &Watchlist) -> String {
+    let programs = watchlist.programs();
+    let domains = watchlist.global_domains.len() + programs.iter().map(|p| p.domains.len()).sum::<usize>();
+    let hosts = watchlist.global_hosts.len() + programs.iter().map(|p| p.hosts.len()).sum::<usize>();
+    let ips = watchlist.global_ips.len() + programs.iter().map(|p| p.ips.len()).sum::<usize>();
+    let cidrs = watchlist.global_cidrs.len() + programs.iter().map(|p| p.cidrs.len()).sum::<usize>();
+    format!(
+        "Watchlist: {} domain patterns, {} hosts, {} IPs, {} CIDRs across {} programs",
+        domains,
+        hosts,
+        ips,
+        cidrs,
+        programs.len()
+    )
+}
+
+fn render(report: &Report, inputs: usize, top: usize) -> String {
+    let secs = report.elapsed.as_secs_f64().max(f64::EPSILON);
+    let mut out = format!(
+        "Matched: {} of {} ({:.1}%)\nThroughput: {:.0} lookups/s ({:.2} µs per lookup)\n",
+        report.matched,
+        inputs,
+        report.matched as f64 * 100.0 / inputs as f64,
+        report.lookups as f64 / secs,
+        secs * 1e6 / report.lookups as f64
+    );
+
+    if report.hot.is_empty() || top == 0 {
+        return out;
+    }
+    let rows: Vec<Vec<String>> = report
+        .hot
+        .iter()
+        .take(top)
+        .map(|(pattern, count)| {
+            vec![
+                pattern.clone(),
+                count.to_string(),
+                format!("{:.1}%", *count as f64 * 100.0 / report.matched.max(1) as f64),
+            ]
+        })
+        .collect();
+    out.push_str("\nHot patterns:\n");
+    out.push_str(&super::table(&["PATTERN", "MATCHES", "SHARE"], &rows));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProgramConfig, WatchlistConfig};
+
+    #[test]
+    fn test_measure_counts_hot_patterns() {
+        let config = WatchlistConfig {
+            domains: vec!["*.example.com".to_string()],
+            hosts: vec!["api.acme.test".to_string()],
+            ips: vec![],
+            cidrs: vec!["192.0.2.0/24".to_string()],
+            ..Default::default()
+        };
+        let programs = vec![ProgramConfig {
+            name: "Acme".to_string(),
+            domains: vec![".acme.test".to_string()],
+            hosts: vec![],
+            cidrs: vec![],
+            ips: vec![],
+            ..Default::default()
+        }];
+        let watchlist = Watchlist::from_config(&config, &programs).unwrap();
+        let targets = [
+            Target::Domain("a.example.com"),
+            Target::Domain("b.example.com"),
+            Target::Domain("api.acme.test"),
+            Target::Domain("www.acme.test"),
+            Target::Domain("example.org"),
+            Target::Ip("192.0.2.7".parse().unwrap()),
+        ];
+
+        let report = measure(&watchlist, &targets, 3);
+        assert_eq!(report.lookups, 18);
+        assert_eq!(report.matched, 5);
+        assert_eq!(
+            report.hot,
+            vec![
+                ("*.example.com".to_string(), 2),
+                (".acme.test".to_string(), 1),
+                ("192.0.2.0/24".to_string(), 1),
+                ("api.acme.test".to_string(), 1),
+            ]
+        );
+
+        let text = render(&report, targets.len(), 1);
+        assert!(text.starts_with("Matched: 5 of 6 (83.3%)\n"));
+        assert!(text.ends_with("PATTERN        MATCHES  SHARE\n*.example.com  2        40.0%\n"));
+    }
+}
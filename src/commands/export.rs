@@ -0,0 +1,33 @@
+// src/commands/export.rs
+//! `ct-scout export`: print the full scope (config + platform programs) as TOML or JSON
+
+use anyhow::{Context, Result};
+use tracing::info;
+
+use crate::cli::ExportArgs;
+use crate::config::Config;
+use crate::watchlist::Watchlist;
+
+pub async fn run(config: &Config, args: &ExportArgs) -> Result<()> {
+    let watchlist = if args.no_sync {
+        Watchlist::from_config(&config.watchlist, &config.programs)?
+    } else {
+        super::scope_watchlist(config).await?
+    };
+
+    let output = if args.json {
+        serde_json::to_string_pretty(&watchlist.export_to_json())?
+    } else {
+        watchlist.export_to_toml()
+    };
+    match args.output {
+        Some(ref path) => {
+            std::fs::write(path, output)
+                .with_context(|| format!("Failed to write scope to {}", path))?;
+            info!("Scope exported to {}", path);
+        }
+        None => println!("{}", output),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+// src/commands/init.rs
+//! `ct-scout init`: write a commented starter config
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::cli::InitArgs;
+
+/// Values filled into the generated config; anything left empty becomes a commented example
+#[derive(Debug, Default)]
+struct Answers {
+    domains: Vec<String>,
+    webhook_url: Option<String>,
+    database_url: Option<String>,
+    redis_url: Option<String>,
+    hackerone: Option<(String, String)>,  // (username, api token)
+    intigriti_token: Option<String>,
+}
+
+pub fn run(path: &Path, args: &InitArgs) -> Result<()> {
+    if path.exists() && !args.force {
+        anyhow::bail!("{} already exists (use --force to overwrite)", path.display());
+    }
+
+    let mut answers = Answers {
+        domains: args.domains.clone(),
+        webhook_url: args.webhook_url.clone(),
+        database_url: args.database_url.clone(),
+        redis_url: args.redis_url.clone(),
+        hackerone: args.hackerone_username.clone().zip(args.hackerone_token.clone()),
+        intigriti_token: args.intigriti_token.clone(),
+    };
+    if !args.non_interactive && std::io::stdin().is_terminal() {
+        prompt_missing(&mut answers)?;
+    }
+
+    std::fs::write(path, render(&answers))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    println!("Check it with: ct-scout validate -c {}", path.display());
+    Ok(())
+}
+
+/// Ask for everything not already given as a flag; empty answers skip the section
+fn prompt_missing(answers: &mut Answers) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut ask = |question: &str| -> Result<Option<String>> {
+        print!("{}: ", question);
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let line = line.trim();
+        Ok((!line.is_empty()).then(|| line.to_string()))
+    };
+
+    if answers.domains.is_empty()
+        && let Some(domains) = ask("Domains to watch, comma-separated (e.g. *.example.com)")?
+    {
+        answers.domains = domains
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if answers.webhook_url.is_none() {
+        answers.webhook_url = ask("Webhook URL for match notifications (blank to skip)")?;
+    }
+    if answers.database_url.is_none() {
+        answers.database_url = ask("PostgreSQL URL to store matches (blank to skip)")?;
+    }
+    if answers.redis_url.is_none() {
+        answers.redis_url = ask("Redis URL to publish matches (blank to skip)")?;
+    }
+    if answers.hackerone.is_none()
+        && let Some(username) = ask("HackerOne API username (blank to skip)")?
+    {
+        answers.hackerone = ask("HackerOne API token")?.map(|token| (username, token));
+    }
+    if answers.intigriti_token.is_none() {
+        answers.intigriti_token = ask("Intigriti API token (blank to skip)")?;
+    }
+    Ok(())
+}
+
+/// TOML string literal with proper escaping
+fn quote(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+fn render(answers: &Answers) -> String {
+    let mut out = String::from(
+        "# ct-scout configuration (generated by `ct-scout init`)\n\
+         # See config.toml in the repository for every available option\n\n\
+         [ct_logs]\n\
+         # Poll interval in seconds\n\
+         poll_interval_secs = 10\n\
+         # Entries fetched per request\n\
+         batch_size = 256\n\
+         # Progress is saved here so restarts resume where they stopped\n\
+         state_file = \"ct-scout-state.toml\"\n\n",
+    );
+
+    out.push_str("# Matches are printed to stdout; pick a format with --json / --csv when running\n");
+    match answers.webhook_url {
+        Some(ref url) => out.push_str(&format!(
+            "[webhook]\nurl = {}\n# secret = \"\"  # HMAC-SHA256 signature key (optional)\ntimeout_secs = 5\n\n",
+            quote(url)
+        )),
+        None => out.push_str(
+            "# [webhook]\n# url = \"https://hooks.example.com/ct-scout\"\n# secret = \"\"\n# timeout_secs = 5\n\n",
+        ),
+    }
+
+    match answers.database_url {
+        Some(ref url) => out.push_str(&format!(
+            "# Store matches in PostgreSQL (query them with `ct-scout query`)\n[database]\nenabled = true\nurl = {}\n\n",
+            quote(url)
+        )),
+        None => out.push_str(
+            "# Store matches in PostgreSQL (query them with `ct-scout query`)\n# [database]\n# enabled = true\n# url = \"postgresql://localhost/ctscout\"\n\n",
+        ),
+    }
+
+    match answers.redis_url {
+        Some(ref url) => out.push_str(&format!(
+            "# Publish matches to Redis\n[redis]\nenabled = true\nurl = {}\nchannel = \"bb:ct_events\"\n\n",
+            quote(url)
+        )),
+        None => out.push_str(
+            "# Publish matches to Redis\n# [redis]\n# enabled = true\n# url = \"redis://localhost:6379\"\n# channel = \"bb:ct_events\"\n\n",
+        ),
+    }
+
+    out.push_str("[logging]\nlevel = \"info\"\n\n");
+
+    out.push_str("# Certificates are reported when any name matches an entry below\n[watchlist]\n");
+    if answers.domains.is_empty() {
+        out.push_str("domains = [\n    # \"*.example.com\",  # Any subdomain of example.com\n]\n");
+    } else {
+        out.push_str("domains = [\n");
+        for domain in &answers.domains {
+            out.push_str(&format!("    {},\n", quote(domain)));
+        }
+        out.push_str("]\n");
+    }
+    out.push_str(
+        "hosts = [\n    # \"api.example.com\",  # Exact hostnames\n]\n\
+         ips = [\n    # \"192.0.2.10\",\n]\n\
+         cidrs = [\n    # \"192.0.2.0/24\",\n]\n\n",
+    );
+
+    out.push_str(
+        "# Optional: group scope by program so matches are attributed\n\
+         # [[programs]]\n# name = \"Example\"\n# domains = [\".example.com\"]\n# cidrs = []\n\n",
+    );
+
+    out.push_str("# Optional: pull program scope from bug bounty platforms\n");
+    match answers.hackerone {
+        Some((ref username, ref token)) => out.push_str(&format!(
+            "[platforms.hackerone]\nenabled = true\nusername = {}\napi_token = {}\nfilter = \"bookmarked\"  # or \"all\"\n\n",
+            quote(username),
+            quote(token)
+        )),
+        None => out.push_str(
+            "# [platforms.hackerone]\n# enabled = true\n# username = \"your_h1_username\"\n# api_token = \"your_h1_api_token\"\n# filter = \"bookmarked\"\n\n",
+        ),
+    }
+    match answers.intigriti_token {
+        Some(ref token) => out.push_str(&format!(
+            "[platforms.intigriti]\nenabled = true\napi_token = {}\nfilter = \"following\"  # or \"all\"\n",
+            quote(token)
+        )),
+        None => out.push_str(
+            "# [platforms.intigriti]\n# enabled = true\n# api_token = \"your_intigriti_api_token\"\n# filter = \"following\"\n",
+        ),
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_placeholders_parse() {
+        let config: Config = toml::from_str(&render(&Answers::default())).unwrap();
+        assert!(config.watchlist.domains.is_empty());
+        assert!(config.webhook.is_none());
+        assert!(!config.database.enabled);
+        assert!(config.platforms.hackerone.is_none());
+    }
+
+    #[test]
+    fn test_answers_fill_sections() {
+        let answers = Answers {
+            domains: vec!["*.example.com".to_string(), "api.\"odd\".com".to_string()],
+            webhook_url: Some("https://hooks.example.com/x".to_string()),
+            database_url: Some("postgresql://ct:pw@db/ctscout".to_string()),
+            redis_url: None,
+            hackerone: Some(("alice".to_string(), "h1-token".to_string())),
+            intigriti_token: Some("int-token".to_string()),
+        };
+        let config: Config = toml::from_str(&render(&answers)).unwrap();
+
+        assert_eq!(config.watchlist.domains, answers.domains);
+        assert_eq!(config.webhook.unwrap().url, "https://hooks.example.com/x");
+        assert!(config.database.enabled);
+        assert!(!config.redis.enabled);
+        assert_eq!(config.platforms.hackerone.unwrap().username, "alice");
+        assert_eq!(config.platforms.intigriti.unwrap().api_token, "int-token");
+    }
+}
@@ -0,0 +1,142 @@
+// src/commands/logs.rs
+//! `ct-scout logs`: inspect the CT logs selected by the config
+
+use anyhow::Result;
+use futures_util::future::join_all;
+use tracing::{debug, info};
+
+use crate::cli::LogsCommand;
+use crate::config::Config;
+use crate::ct_log::types::TemporalInterval;
+use crate::ct_log::{HttpConfig, SelectedLog, normalize_log_url, probe_log};
+
+pub async fn run(config: &Config, command: &LogsCommand) -> Result<()> {
+    match command {
+        LogsCommand::List => list(config).await,
+    }
+}
+
+/// One row of the `logs list` table
+struct LogRow {
+    log: SelectedLog,
+    saved_index: Option<u64>,
+    tree_size: Option<u64>,
+}
+
+impl LogRow {
+    /// Entries between the saved position and the current tree head
+    fn lag(&self) -> Option<u64> {
+        Some(self.tree_size?.saturating_sub(self.saved_index?))
+    }
+}
+
+async fn list(config: &Config) -> Result<()> {
+    let logs = super::monitored_logs(&config.ct_logs).await?;
+    let state = super::state_backend(config).await?;
+    let http_config = super::http_config(&config.ct_logs);
+
+    let rows = join_all(logs.into_iter().map(|log| {
+        let state = state.clone();
+        let http_config = &http_config;
+        async move {
+            // Keyed by log_id, or by URL until a run has moved the entry over
+            let key = log.log_id.clone().unwrap_or_else(|| normalize_log_url(&log.url).to_string());
+            let saved_index = match state.get_last_index(&key).await {
+                Some(index) => Some(index),
+                None => state.get_last_index(&log.url).await,
+            };
+            let tree_size = tree_size(http_config, &log.url).await;
+            LogRow { log, saved_index, tree_size }
+        }
+    }))
+    .await;
+
+    print!("{}", render(&rows));
+    info!("{} logs would be monitored", rows.len());
+    Ok(())
+}
+
+/// Current tree size, or `None` if the log can't be reached
+async fn tree_size(http_config: &HttpConfig, log_url: &str) -> Option<u64> {
+    match probe_log(http_config, log_url).await {
+        Ok((_, sth)) => Some(sth.tree_size),
+        Err(e) => {
+            debug!("{}: Failed to fetch tree head: {:#}", log_url, e);
+            None
+        }
+    }
+}
+
+/// Shard window as `start..end` dates, open ends left blank
+fn interval_label(interval: Option<&TemporalInterval>) -> String {
+    let date = |ts: &Option<String>| ts.as_deref().map(|t| t.chars().take(10).collect::<String>()).unwrap_or_default();
+    match interval {
+        Some(interval) => format!("{}..{}", date(&interval.start_inclusive), date(&interval.end_exclusive)),
+        None => "-".to_string(),
+    }
+}
+
+fn render(rows: &[LogRow]) -> String {
+    let or_dash = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                Some(row.log.operator.clone()).filter(|o| !o.is_empty()).unwrap_or_else(|| "-".to_string()),
+                row.log.url.clone(),
+                row.log.state.to_string(),
+                interval_label(row.log.temporal_interval.as_ref()),
+                or_dash(row.saved_index),
+                or_dash(row.lag()),
+            ]
+        })
+        .collect();
+
+    super::table(&["OPERATOR", "URL", "STATE", "INTERVAL", "SAVED", "LAG"], &cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![
+            LogRow {
+                log: SelectedLog {
+                    url: "https://ct.example.com/2026h2/".to_string(),
+                    log_id: None,
+                    operator: "Example".to_string(),
+                    description: "Example 2026h2".to_string(),
+                    state: "usable",
+                    temporal_interval: Some(TemporalInterval {
+                        start_inclusive: Some("2026-07-01T00:00:00Z".to_string()),
+                        end_exclusive: Some("2027-01-01T00:00:00Z".to_string()),
+                    }),
+                },
+                saved_index: Some(900),
+                tree_size: Some(1000),
+            },
+            LogRow {
+                log: SelectedLog {
+                    url: "https://custom.example.net/".to_string(),
+                    log_id: None,
+                    operator: String::new(),
+                    description: String::new(),
+                    state: "custom",
+                    temporal_interval: None,
+                },
+                saved_index: None,
+                tree_size: Some(50),
+            },
+        ];
+
+        let table = render(&rows);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("OPERATOR  URL"));
+        assert!(lines[1].contains("2026-07-01..2027-01-01"));
+        assert!(lines[1].ends_with("900    100"));
+        assert!(lines[2].starts_with("-         https://custom.example.net/"));
+        assert!(lines[2].ends_with("-      -"));
+    }
+}
@@ -0,0 +1,186 @@
+// src/commands/mod.rs
+//! One-shot subcommands (everything except `run`, `scan` and `replay`)
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::config::{Config, CtLogConfig};
+use crate::ct_log::{HttpConfig, LogIds, LogListFetcher, RetryConfig, RetryPolicy, LogListSource, LogSelection, SelectedLog};
+use crate::database::{DatabaseBackend, DbStateLease, DbStateManager, PostgresBackend, SqliteStateManager};
+use crate::platforms::{self, PlatformSyncManager};
+use crate::state::{StateBackend, StateLock, StateManager};
+use crate::watchlist::Watchlist;
+
+pub mod bench;
+pub mod export;
+pub mod init;
+pub mod logs;
+pub mod query;
+pub mod schema;
+pub mod state;
+pub mod status;
+pub mod sync;
+pub mod test_match;
+pub mod validate;
+pub mod watchlist;
+
+/// HTTP settings used for CT log and log list requests
+pub fn http_config(ct_logs: &CtLogConfig) -> HttpConfig {
+    HttpConfig {
+        proxy: ct_logs.proxy.clone(),
+        per_log_proxy: ct_logs.log_proxies.clone(),
+        user_agent: ct_logs.user_agent.clone(),
+        headers: ct_logs.headers.clone(),
+        per_log_headers: ct_logs.log_headers.clone(),
+        timeout_secs: Some(ct_logs.http_timeout_secs),
+        per_log_timeout: ct_logs
+            .log_requests
+            .iter()
+            .filter_map(|(url, requests)| Some((url.clone(), requests.http_timeout_secs?)))
+            .collect(),
+    }
+}
+
+/// Retry settings for CT log requests, with per-log overrides resolved
+pub fn retry_config(ct_logs: &CtLogConfig) -> RetryConfig {
+    let default = RetryPolicy {
+        max_retries: ct_logs.max_retries,
+        backoff: Duration::from_secs(ct_logs.retry_backoff_secs),
+    };
+    RetryConfig {
+        default,
+        per_log: ct_logs
+            .log_requests
+            .iter()
+            .map(|(url, requests)| {
+                let policy = RetryPolicy {
+                    max_retries: requests.max_retries.unwrap_or(default.max_retries),
+                    backoff: requests.retry_backoff_secs.map_or(default.backoff, Duration::from_secs),
+                };
+                (url.clone(), policy)
+            })
+            .collect(),
+    }
+}
+
+/// Log list source built from the `[ct_logs]` selection settings
+pub fn log_list_source(ct_logs: &CtLogConfig) -> Result<LogListSource> {
+    let mut fetcher = LogListFetcher::new()
+        .with_http_client(http_config(ct_logs).client()?)
+        .with_skip_expired_shards(ct_logs.skip_expired_shards)
+        .with_selection(LogSelection {
+            include_operators: ct_logs.include_operators.clone(),
+            exclude_operators: ct_logs.exclude_operators.clone(),
+            include_descriptions: ct_logs.include_log_descriptions.clone(),
+            exclude_descriptions: ct_logs.exclude_log_descriptions.clone(),
+        });
+    if !ct_logs.log_list_cache_dir.is_empty() {
+        fetcher = fetcher.with_cache_dir(&ct_logs.log_list_cache_dir);
+    }
+
+    Ok(LogListSource {
+        fetcher,
+        list_urls: ct_logs.list_urls(),
+        include_readonly: ct_logs.include_readonly_logs,
+        include_pending: ct_logs.include_pending,
+        include_all: ct_logs.include_all_logs,
+        additional_logs: ct_logs.additional_logs.clone(),
+        max_logs: ct_logs.max_concurrent_logs,
+        log_ids: LogIds::default(),
+    })
+}
+
+/// Config watchlist plus the programs of every enabled platform, synced once
+pub async fn scope_watchlist(config: &Config) -> Result<Watchlist> {
+    let watchlist = Arc::new(Mutex::new(Watchlist::from_config(&config.watchlist, &config.programs)?));
+
+    let platforms = platforms::connect_enabled(&config.platforms).await?;
+    if platforms.is_empty() {
+        info!("No platforms available, using config-only scope");
+    } else {
+        info!("Syncing platform programs...");
+        PlatformSyncManager::new(platforms, watchlist.clone(), config.platforms.sync_interval_hours)
+            .sync_all_platforms()
+            .await;
+    }
+
+    let watchlist = watchlist.lock().await.clone();
+    Ok(watchlist)
+}
+
+/// Left-aligned plain-text table, columns sized to their widest cell
+pub(crate) fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(rows) {
+        let padded: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(padded.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Read the saved per-log indices from the configured `state_backend`
+pub async fn state_backend(config: &Config) -> Result<Arc<dyn StateBackend>> {
+    match config.ct_logs.state_backend.as_str() {
+        "database" => {
+            if !config.database.enabled {
+                anyhow::bail!("state_backend is set to 'database' but database is not enabled");
+            }
+            let db: Arc<dyn DatabaseBackend> = Arc::new(
+                PostgresBackend::new(&config.database.url, config.database.max_connections).await?,
+            );
+            Ok(Arc::new(DbStateManager::new(db)))
+        }
+        "file" => Ok(Arc::new(
+            StateManager::new(PathBuf::from(&config.ct_logs.state_file)).await?,
+        )),
+        "sqlite" => Ok(Arc::new(SqliteStateManager::new(Path::new(&config.ct_logs.state_db)).await?)),
+        other => anyhow::bail!("Invalid state_backend '{}'. Must be 'file', 'database' or 'sqlite'", other),
+    }
+}
+
+/// Claim the configured state so a second instance fails fast instead of racing this one
+///
+/// The claim lasts until the returned guard is dropped.
+pub async fn lock_state(config: &Config, backend: &str) -> Result<Box<dyn Send>> {
+    match backend {
+        "database" => Ok(Box::new(DbStateLease::acquire(&config.database.url).await?)),
+        "file" => Ok(Box::new(StateLock::acquire(Path::new(&config.ct_logs.state_file))?)),
+        "sqlite" => Ok(Box::new(StateLock::acquire(Path::new(&config.ct_logs.state_db))?)),
+        other => anyhow::bail!("Invalid state_backend '{}'. Must be 'file', 'database' or 'sqlite'", other),
+    }
+}
+
+/// Logs that `run` would monitor: `custom_logs` if set, otherwise the log list
+pub async fn monitored_logs(ct_logs: &CtLogConfig) -> Result<Vec<SelectedLog>> {
+    match ct_logs.custom_logs {
+        Some(ref custom) => Ok(custom
+            .iter()
+            .map(|url| SelectedLog {
+                url: url.clone(),
+                log_id: None,
+                operator: String::new(),
+                description: String::new(),
+                state: "custom",
+                temporal_interval: None,
+            })
+            .collect()),
+        None => log_list_source(ct_logs)?.fetch_selected().await,
+    }
+}
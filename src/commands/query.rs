@@ -0,0 +1,46 @@
+// src/commands/query.rs
+//! `ct-scout query`: search matches stored in the database
+
+use anyhow::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+use crate::cli::QueryArgs;
+use crate::config::Config;
+use crate::database::{DatabaseBackend, MatchQuery, PostgresBackend};
+use crate::output::{human::HumanOutput, json::JsonOutput, OutputHandler};
+
+pub async fn run(config: &Config, args: &QueryArgs) -> Result<()> {
+    if !config.database.enabled {
+        anyhow::bail!("query reads stored matches; enable the [database] config section first");
+    }
+
+    let db = PostgresBackend::new(&config.database.url, config.database.max_connections).await?;
+    let since = args.since_hours.map(|hours| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        now.saturating_sub(hours * 3600)
+    });
+
+    let matches = db
+        .get_matches(MatchQuery {
+            domain_pattern: args.domain.clone(),
+            since,
+            program_name: args.program.clone(),
+            limit: Some(args.limit),
+            ..Default::default()
+        })
+        .await?;
+
+    let output: Box<dyn OutputHandler> = if args.json {
+        Box::new(JsonOutput::new())
+    } else {
+        Box::new(HumanOutput::new())
+    };
+    for result in &matches {
+        output.emit_match(result).await?;
+    }
+    output.flush().await?;
+
+    info!("{} matches", matches.len());
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// src/commands/schema.rs
+//! `ct-scout schema`: JSON Schema of the match and webhook payloads
+
+use anyhow::Result;
+use schemars::Schema;
+
+use crate::cli::SchemaPayload;
+use crate::output::webhook::WebhookPayload;
+use crate::types::MatchResult;
+
+pub fn run(payload: SchemaPayload) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&schema(payload))?);
+    Ok(())
+}
+
+pub fn schema(payload: SchemaPayload) -> Schema {
+    match payload {
+        SchemaPayload::Match => schemars::schema_for!(MatchResult),
+        SchemaPayload::Webhook => schemars::schema_for!(WebhookPayload<'static>),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CertData, SCHEMA_VERSION};
+
+    #[test]
+    fn test_match_schema_describes_output() {
+        let match_schema = serde_json::to_value(schema(SchemaPayload::Match)).unwrap();
+        let properties = match_schema["properties"].as_object().unwrap();
+        assert!(properties.contains_key("schema_version"));
+        assert!(properties.contains_key("matched_domain"));
+        assert!(match_schema["required"].as_array().unwrap().contains(&"matched_domain".into()));
+
+        // Every field of an emitted match is described
+        let data = CertData {
+            all_domains: Some(vec!["api.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(1),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let result = MatchResult::from_cert_data("api.example.com".to_string(), &data, None, None);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        for key in json.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "{} missing from schema", key);
+        }
+
+        let webhook = serde_json::to_value(schema(SchemaPayload::Webhook)).unwrap();
+        assert!(webhook["properties"]["schema_version"].is_object());
+    }
+}
@@ -0,0 +1,388 @@
+// src/commands/state.rs
+//! `ct-scout state`: manage saved per-log progress
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::cli::{ImportArgs, ImportSource, MigrateArgs, StateCommand, StateStore};
+use crate::config::Config;
+use crate::ct_log::{KeyedState, LogIds, LogListFetcher, SelectedLog};
+use crate::database::{DatabaseBackend, DbStateManager, PostgresBackend};
+use crate::state::{StateBackend, StateManager};
+
+pub async fn run(config: &Config, command: &StateCommand) -> Result<()> {
+    match command {
+        StateCommand::Migrate(args) => migrate_command(config, args).await,
+        StateCommand::Import(args) => import_command(config, args).await,
+    }
+}
+
+/// What a migration or import did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MigrateSummary {
+    pub copied: usize,
+    pub skipped: usize,  // Destination already further along
+    pub gaps: usize,
+}
+
+async fn migrate_command(config: &Config, args: &MigrateArgs) -> Result<()> {
+    let db = database_state(config).await?;
+    // Neither side may be written by a running instance meanwhile
+    let _db_lock = super::lock_state(config, "database").await?;
+    let _file_lock = super::lock_state(config, "file").await?;
+    let file: Arc<dyn StateBackend> =
+        Arc::new(StateManager::new(PathBuf::from(&config.ct_logs.state_file)).await?);
+    // Keyed on both sides, so URL-keyed leftovers fold into their log
+    let ids = log_ids(config, &listed_logs(config).await?);
+    let db = keyed(db, &ids);
+    let file = keyed(file, &ids);
+    let (from, to, from_name, to_name, backend) = match args.to {
+        StateStore::Db => (file, db, config.ct_logs.state_file.as_str(), "the database", "database"),
+        StateStore::Toml => (db, file, "the database", config.ct_logs.state_file.as_str(), "file"),
+    };
+
+    let summary = migrate(from.as_ref(), to.as_ref(), args.overwrite).await?;
+    // Persist any entries moved to their log key in the source too
+    from.save().await?;
+    println!(
+        "Copied {} log positions and {} gaps from {} to {}",
+        summary.copied, summary.gaps, from_name, to_name
+    );
+    if summary.skipped > 0 {
+        println!(
+            "Skipped {} logs already further along in {} (use --overwrite to replace them)",
+            summary.skipped, to_name
+        );
+    }
+    if config.ct_logs.state_backend != backend {
+        println!("Set ct_logs.state_backend = \"{}\" to use the migrated state", backend);
+    }
+    Ok(())
+}
+
+/// Every log in the configured log lists, whatever its state
+async fn listed_logs(config: &Config) -> Result<Vec<SelectedLog>> {
+    LogListFetcher::new()
+        .with_http_client(super::http_config(&config.ct_logs).client()?)
+        .with_skip_expired_shards(false)
+        .select_logs_from(&config.ct_logs.list_urls(), true, true, true)
+        .await
+}
+
+/// The log keys `run` uses: log_ids from the lists, unless `custom_logs` replaces them
+fn log_ids(config: &Config, logs: &[SelectedLog]) -> LogIds {
+    let ids = LogIds::new();
+    if config.ct_logs.custom_logs.is_none() {
+        ids.register_logs(logs);
+    }
+    ids
+}
+
+/// `state` read and written under the same log keys as a run
+fn keyed(state: Arc<dyn StateBackend>, ids: &LogIds) -> Arc<dyn StateBackend> {
+    Arc::new(KeyedState::new(state, ids.clone()))
+}
+
+async fn database_state(config: &Config) -> Result<Arc<dyn StateBackend>> {
+    if !config.database.enabled {
+        anyhow::bail!("Migrating state needs the database; set [database] enabled = true");
+    }
+    let postgres = PostgresBackend::new(&config.database.url, config.database.max_connections).await?;
+    // The ct_log_state table may not exist yet if `run` never used the database
+    postgres.migrate().await?;
+    let db: Arc<dyn DatabaseBackend> = Arc::new(postgres);
+    Ok(Arc::new(DbStateManager::new(db)))
+}
+
+/// Copy every log's index and gaps from one backend to another
+///
+/// A log the destination has already advanced past is left alone unless
+/// `overwrite` is set, so migrating never rewinds a log by accident.
+pub async fn migrate(from: &dyn StateBackend, to: &dyn StateBackend, overwrite: bool) -> Result<MigrateSummary> {
+    let mut summary = MigrateSummary::default();
+    let mut logs = from.get_tracked_logs().await;
+    logs.sort();
+
+    for log_url in logs {
+        let Some(index) = from.get_last_index(&log_url).await else {
+            continue;
+        };
+        if !copy_index(to, &log_url, index, overwrite).await? {
+            summary.skipped += 1;
+            continue;
+        }
+        summary.copied += 1;
+        if let Some(tree_size) = from.get_progress(&log_url).await.tree_size {
+            to.update_tree_size(&log_url, tree_size).await;
+        }
+
+        for (start, end) in from.get_gaps(&log_url).await {
+            to.add_gap(&log_url, start, end).await;
+            summary.gaps += 1;
+        }
+    }
+
+    to.save().await?;
+    Ok(summary)
+}
+
+/// Set one log's index unless `to` is further along; false if it was left alone
+async fn copy_index(to: &dyn StateBackend, log_url: &str, index: u64, overwrite: bool) -> Result<bool> {
+    if !overwrite && to.get_last_index(log_url).await.is_some_and(|existing| existing > index) {
+        return Ok(false);
+    }
+
+    to.update_index(log_url, index).await;
+    // Backends log write failures rather than returning them, so check
+    if to.get_last_index(log_url).await != Some(index) {
+        anyhow::bail!("Failed to write the index of {} (see the log for details)", log_url);
+    }
+    Ok(true)
+}
+
+async fn import_command(config: &Config, args: &ImportArgs) -> Result<()> {
+    let logs = listed_logs(config).await?;
+    let positions = match args.from {
+        ImportSource::Certspotter => resolve_log_ids(certspotter_positions(&args.path)?, &logs),
+        ImportSource::Gungnir => gungnir_positions(&args.path)?,
+    };
+
+    let _lock = super::lock_state(config, &config.ct_logs.state_backend).await?;
+    let state = match config.ct_logs.state_backend.as_str() {
+        "database" => database_state(config).await?,
+        _ => super::state_backend(config).await?,
+    };
+    // Imports must land on the key a run reads, or they are shadowed by it
+    let state = keyed(state, &log_ids(config, &logs));
+    let summary = import(&positions, state.as_ref(), args.overwrite).await?;
+
+    println!("Imported {} log positions from {}", summary.copied, args.path.display());
+    if summary.skipped > 0 {
+        println!(
+            "Skipped {} logs already further along (use --overwrite to replace them)",
+            summary.skipped
+        );
+    }
+    Ok(())
+}
+
+/// Write (log URL, next index) pairs into `to`, never rewinding unless `overwrite` is set
+pub async fn import(positions: &[(String, u64)], to: &dyn StateBackend, overwrite: bool) -> Result<MigrateSummary> {
+    let mut summary = MigrateSummary::default();
+    for (log_url, index) in positions {
+        if copy_index(to, log_url, *index, overwrite).await? {
+            summary.copied += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+    to.save().await?;
+    Ok(summary)
+}
+
+/// certspotter's per-log `state.json`; positions are Merkle trees of the entries handled so far
+#[derive(Deserialize)]
+struct CertspotterState {
+    verified_position: Option<CertspotterPosition>,
+    download_position: Option<CertspotterPosition>,
+}
+
+#[derive(Deserialize)]
+struct CertspotterPosition {
+    size: u64,
+}
+
+/// (log ID, next index) for every log in a certspotter state directory
+///
+/// Accepts the state directory itself or its `logs` subdirectory. The verified
+/// position is preferred: entries past it were downloaded but not yet checked.
+fn certspotter_positions(dir: &Path) -> Result<Vec<(String, u64)>> {
+    let logs_dir = if dir.join("logs").is_dir() { dir.join("logs") } else { dir.to_path_buf() };
+    let entries = std::fs::read_dir(&logs_dir)
+        .with_context(|| format!("Failed to read certspotter state directory {}", logs_dir.display()))?;
+
+    let mut positions = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let state_path = path.join("state.json");
+        if !state_path.is_file() {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let Some(log_id) = certspotter_log_id(&name) else {
+            warn!("Skipping {}: not named after a log ID", path.display());
+            continue;
+        };
+
+        let contents = std::fs::read_to_string(&state_path)
+            .with_context(|| format!("Failed to read {}", state_path.display()))?;
+        let state: CertspotterState = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", state_path.display()))?;
+        if let Some(position) = state.verified_position.or(state.download_position) {
+            positions.push((log_id, position.size));
+        }
+    }
+    positions.sort();
+    Ok(positions)
+}
+
+/// certspotter names log directories with URL-safe base64; log lists use standard base64
+fn certspotter_log_id(name: &str) -> Option<String> {
+    let id = URL_SAFE_NO_PAD.decode(name).or_else(|_| URL_SAFE.decode(name)).ok()?;
+    (id.len() == 32).then(|| STANDARD.encode(id))
+}
+
+/// Swap log IDs for the URLs ct-scout monitors them under; unknown IDs are skipped
+fn resolve_log_ids(positions: Vec<(String, u64)>, logs: &[SelectedLog]) -> Vec<(String, u64)> {
+    let urls: HashMap<&str, &str> = logs
+        .iter()
+        .filter_map(|log| Some((log.log_id.as_deref()?, log.url.as_str())))
+        .collect();
+
+    positions
+        .into_iter()
+        .filter_map(|(log_id, index)| match urls.get(log_id.as_str()) {
+            Some(url) => Some((url.to_string(), index)),
+            None => {
+                warn!("Skipping log {}: not in the configured log lists", log_id);
+                None
+            }
+        })
+        .collect()
+}
+
+/// (log URL, next index) pairs from a gungnir position file
+fn gungnir_positions(path: &Path) -> Result<Vec<(String, u64)>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let positions: HashMap<String, u64> = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {} (expected {{\"<log URL>\": <index>, ...}})", path.display()))?;
+
+    let mut positions: Vec<(String, u64)> = positions
+        .into_iter()
+        .map(|(url, index)| (normalize_log_url(&url), index))
+        .collect();
+    positions.sort();
+    Ok(positions)
+}
+
+/// `ct.example.com/log` -> `https://ct.example.com/log/`, the form log lists use
+fn normalize_log_url(url: &str) -> String {
+    let url = url.trim();
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+    if url.ends_with('/') { url } else { format!("{}/", url) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_migrate_copies_indices_and_gaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = StateManager::new(temp_dir.path().join("from.toml")).await.unwrap();
+        from.update_index("https://ct.example.com/a/", 100).await;
+        from.update_index("https://ct.example.com/b/", 50).await;
+        from.add_gap("https://ct.example.com/a/", 10, 20).await;
+
+        let to_path = temp_dir.path().join("to.toml");
+        let to = StateManager::new(to_path.clone()).await.unwrap();
+        to.update_index("https://ct.example.com/b/", 80).await;
+
+        let summary = migrate(&from, &to, false).await.unwrap();
+        assert_eq!(summary, MigrateSummary { copied: 1, skipped: 1, gaps: 1 });
+
+        // Persisted, and b was not rewound
+        let reloaded = StateManager::new(to_path).await.unwrap();
+        assert_eq!(reloaded.get_last_index("https://ct.example.com/a/").await, Some(100));
+        assert_eq!(reloaded.get_gaps("https://ct.example.com/a/").await, vec![(10, 20)]);
+        assert_eq!(reloaded.get_last_index("https://ct.example.com/b/").await, Some(80));
+
+        let summary = migrate(&from, &to, true).await.unwrap();
+        assert_eq!(summary.copied, 2);
+        assert_eq!(to.get_last_index("https://ct.example.com/b/").await, Some(50));
+    }
+
+    #[test]
+    fn test_certspotter_positions() {
+        let temp_dir = TempDir::new().unwrap();
+        let id = [7u8; 32];
+        let log_dir = temp_dir.path().join("logs").join(URL_SAFE_NO_PAD.encode(id));
+        std::fs::create_dir_all(&log_dir).unwrap();
+        std::fs::write(
+            log_dir.join("state.json"),
+            r#"{"download_position": {"nodes": [], "size": 1200}, "verified_position": {"nodes": [], "size": 1000}, "last_success": "2025-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("logs").join("not-a-log")).unwrap();
+
+        let positions = certspotter_positions(temp_dir.path()).unwrap();
+        assert_eq!(positions, vec![(STANDARD.encode(id), 1000)]);
+
+        let logs = vec![SelectedLog {
+            url: "https://ct.example.com/log/".to_string(),
+            log_id: Some(STANDARD.encode(id)),
+            operator: "Example".to_string(),
+            description: "Example log".to_string(),
+            state: "usable",
+            temporal_interval: None,
+        }];
+        let positions = resolve_log_ids(positions.into_iter().chain([("unknown".to_string(), 5)]).collect(), &logs);
+        assert_eq!(positions, vec![("https://ct.example.com/log/".to_string(), 1000)]);
+    }
+
+    #[tokio::test]
+    async fn test_import_gungnir_positions() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("gungnir.json");
+        std::fs::write(&path, r#"{"ct.example.com/a": 100, "https://ct.example.com/b/": 50}"#).unwrap();
+
+        let positions = gungnir_positions(&path).unwrap();
+        assert_eq!(
+            positions,
+            vec![
+                ("https://ct.example.com/a/".to_string(), 100),
+                ("https://ct.example.com/b/".to_string(), 50),
+            ]
+        );
+
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        state.update_index("https://ct.example.com/b/", 80).await;
+        let summary = import(&positions, &state, false).await.unwrap();
+        assert_eq!(summary, MigrateSummary { copied: 1, skipped: 1, gaps: 0 });
+        assert_eq!(state.get_last_index("https://ct.example.com/a/").await, Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_import_into_log_id_keyed_state() {
+        const LOG_ID: &str = "7s3QZNXbGs7FXLedtM0TojKHRny87N7DUUhZRnEftZs=";
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.toml");
+        // A run has already keyed the log and left its old URL entry behind
+        let inner = StateManager::new(path.clone()).await.unwrap();
+        inner.update_index(LOG_ID, 100).await;
+        inner.update_index("https://ct.example.com/log/", 40).await;
+
+        let ids = LogIds::new();
+        ids.register("https://ct.example.com/log/", LOG_ID);
+        let state = keyed(Arc::new(inner), &ids);
+        let positions = vec![("https://ct.example.com/log/".to_string(), 50)];
+
+        // Compared against the log_id entry, not the stale URL one
+        let summary = import(&positions, state.as_ref(), false).await.unwrap();
+        assert_eq!(summary, MigrateSummary { copied: 0, skipped: 1, gaps: 0 });
+
+        let summary = import(&positions, state.as_ref(), true).await.unwrap();
+        assert_eq!(summary.copied, 1);
+        let reloaded = StateManager::new(path).await.unwrap();
+        assert_eq!(reloaded.get_last_index(LOG_ID).await, Some(50));
+        assert_eq!(reloaded.get_tracked_logs().await, vec![LOG_ID.to_string()]);
+    }
+}
@@ -0,0 +1,371 @@
+// src/commands/status.rs
+//! `ct-scout status`: summarize saved progress, stored matches and health
+//!
+//! Only reads the state file / database and the metrics export, so it is
+//! safe to run next to a monitoring instance. The log lists are fetched to
+//! show logs keyed by log_id under their URL.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::config::Config;
+use crate::ct_log::LogIds;
+use crate::database::{DatabaseBackend, DbStateManager, PostgresBackend, SqliteStateManager};
+use crate::state::{StateBackend, StateManager};
+
+/// Metrics from the export file shown under "Health"
+const HEALTH_METRICS: &[(&str, &str)] = &[
+    ("ctscout_certificates_processed_total", "Certificates processed"),
+    ("ctscout_matches_found_total", "Matches found"),
+    ("ctscout_cert_channel_depth", "Channel depth"),
+    ("ctscout_cert_channel_spilled", "Spilled to disk"),
+    ("ctscout_ct_log_lag_entries", "Lag (entries)"),
+    ("ctscout_ct_http_errors_total", "CT HTTP errors"),
+    ("ctscout_ct_parse_errors_total", "Parse errors"),
+    ("ctscout_ct_audit_failures_total", "Audit failures"),
+    ("ctscout_redis_connection_status", "Redis connected"),
+];
+
+/// Saved progress of one log
+struct TrackedLog {
+    url: String,
+    index: u64,
+    updated: Option<u64>,  // Unix timestamp of the last index change
+    tree_size: Option<u64>,
+    gaps: usize,
+}
+
+pub async fn run(config: &Config) -> Result<()> {
+    let db: Option<Arc<dyn DatabaseBackend>> = if config.database.enabled {
+        Some(Arc::new(
+            PostgresBackend::new(&config.database.url, config.database.max_connections).await?,
+        ))
+    } else {
+        None
+    };
+
+    let logs = match config.ct_logs.state_backend.as_str() {
+        "database" => {
+            let Some(ref db) = db else {
+                anyhow::bail!("state_backend is set to 'database' but database is not enabled");
+            };
+            println!("State: database");
+            database_logs(db).await?
+        }
+        "file" => {
+            let path = Path::new(&config.ct_logs.state_file);
+            println!("State: {}", path.display());
+            file_logs(path).await?
+        }
+        "sqlite" => {
+            let path = Path::new(&config.ct_logs.state_db);
+            println!("State: {} (SQLite)", path.display());
+            sqlite_logs(path).await?
+        }
+        other => anyhow::bail!("Invalid state_backend '{}'. Must be 'file', 'database' or 'sqlite'", other),
+    };
+    let ids = LogIds::new();
+    match super::monitored_logs(&config.ct_logs).await {
+        Ok(selected) => ids.register_logs(&selected),
+        Err(e) => warn!("Failed to fetch the log lists, showing logs by key: {:#}", e),
+    }
+    let logs = by_url(logs, &ids);
+
+    println!("Tracked logs: {}", logs.len());
+    if !logs.is_empty() {
+        println!();
+        print!("{}", render(&logs, now()));
+    }
+    let stale = stale_logs(&logs, config.ct_logs.state_age_warn_hours * 3600, now());
+    if !stale.is_empty() {
+        println!();
+        for log in stale {
+            println!(
+                "Warning: {} has not advanced since {}",
+                log.url,
+                log.updated.map(timestamp).unwrap_or_default()
+            );
+        }
+    }
+
+    println!();
+    match db {
+        Some(ref db) => {
+            let day_ago = now().saturating_sub(24 * 3600);
+            println!(
+                "Matches: {} total, {} in the last 24h",
+                db.count_matches(None).await?,
+                db.count_matches(Some(day_ago)).await?
+            );
+        }
+        None => println!("Matches: not stored (enable [database] to keep match history)"),
+    }
+
+    println!();
+    match config.metrics.export_path {
+        Some(ref path) if config.metrics.enabled => match tokio::fs::read_to_string(path).await {
+            Ok(text) => {
+                let exported = modified(Path::new(path)).map(timestamp).unwrap_or_default();
+                println!("Health (metrics exported {}):", exported);
+                let totals = metric_totals(&text);
+                for (metric, label) in HEALTH_METRICS {
+                    if let Some(value) = totals.get(*metric) {
+                        println!("  {:<24}{}", format!("{}:", label), value);
+                    }
+                }
+            }
+            Err(e) => println!("Health: metrics file {} not readable ({})", path, e),
+        },
+        _ => println!("Health: unavailable (set [metrics] enabled and export_path to report it)"),
+    }
+
+    Ok(())
+}
+
+/// Logs saved in a state file; older files only have their own modification time
+async fn file_logs(path: &Path) -> Result<Vec<TrackedLog>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let state = StateManager::new(path.to_path_buf()).await?;
+    let file_updated = modified(path);
+    let mut logs = Vec::new();
+    for url in state.get_tracked_logs().await {
+        let progress = state.get_progress(&url).await;
+        logs.push(TrackedLog {
+            index: state.get_last_index(&url).await.unwrap_or(0),
+            gaps: state.get_gaps(&url).await.len(),
+            updated: progress.last_updated.or(file_updated),
+            tree_size: progress.tree_size,
+            url,
+        });
+    }
+    logs.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(logs)
+}
+
+async fn sqlite_logs(path: &Path) -> Result<Vec<TrackedLog>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let state = SqliteStateManager::new(path).await?;
+    let mut logs = Vec::new();
+    for record in state.records().await? {
+        logs.push(TrackedLog {
+            gaps: state.get_gaps(&record.log_url).await.len(),
+            url: record.log_url,
+            index: record.last_index,
+            updated: Some(record.last_updated),
+            tree_size: record.tree_size,
+        });
+    }
+    Ok(logs)
+}
+
+async fn database_logs(db: &Arc<dyn DatabaseBackend>) -> Result<Vec<TrackedLog>> {
+    let state = DbStateManager::new(Arc::clone(db));
+    let mut logs = Vec::new();
+    for record in db.get_log_state_records().await? {
+        logs.push(TrackedLog {
+            gaps: state.get_gaps(&record.log_url).await.len(),
+            url: record.log_url,
+            index: record.last_index,
+            updated: Some(record.last_updated),
+            tree_size: record.tree_size,
+        });
+    }
+    Ok(logs)
+}
+
+/// Name log_id-keyed logs by URL and leave out URL-keyed entries a run has
+/// already moved to their log key
+fn by_url(logs: Vec<TrackedLog>, ids: &LogIds) -> Vec<TrackedLog> {
+    let stored: HashSet<String> = logs.iter().map(|log| log.url.clone()).collect();
+    let mut logs: Vec<TrackedLog> = logs
+        .into_iter()
+        .filter(|log| {
+            let key = ids.key(&log.url);
+            key == log.url || !stored.contains(&key)
+        })
+        .map(|log| TrackedLog { url: ids.display(&log.url), ..log })
+        .collect();
+    logs.sort_by(|a, b| a.url.cmp(&b.url));
+    logs
+}
+
+fn render(logs: &[TrackedLog], now: u64) -> String {
+    let rows: Vec<Vec<String>> = logs
+        .iter()
+        .map(|log| {
+            vec![
+                log.url.clone(),
+                log.index.to_string(),
+                log.tree_size.map(|size| size.saturating_sub(log.index).to_string()).unwrap_or_else(|| "-".to_string()),
+                log.updated.map(timestamp).unwrap_or_else(|| "-".to_string()),
+                log.updated.map(|updated| age(now.saturating_sub(updated))).unwrap_or_else(|| "-".to_string()),
+                log.gaps.to_string(),
+            ]
+        })
+        .collect();
+    super::table(&["URL", "INDEX", "BEHIND", "UPDATED", "AGE", "GAPS"], &rows)
+}
+
+/// Logs whose index hasn't advanced for at least `max_age` seconds (0 = never stale)
+fn stale_logs(logs: &[TrackedLog], max_age: u64, now: u64) -> Vec<&TrackedLog> {
+    if max_age == 0 {
+        return Vec::new();
+    }
+    logs.iter()
+        .filter(|log| log.updated.is_some_and(|updated| now.saturating_sub(updated) >= max_age))
+        .collect()
+}
+
+fn age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// Sum of every sample of each metric in Prometheus text format, labels ignored
+fn metric_totals(text: &str) -> BTreeMap<String, f64> {
+    let mut totals = BTreeMap::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((series, value)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        let name = series.split('{').next().unwrap_or(series);
+        *totals.entry(name.to_string()).or_insert(0.0) += value;
+    }
+    totals
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+fn timestamp(secs: u64) -> String {
+    DateTime::<Utc>::from_timestamp(secs as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_totals() {
+        let text = "\
+# HELP ctscout_ct_audit_failures_total Total number of CT log proof verification failures
+# TYPE ctscout_ct_audit_failures_total counter
+ctscout_ct_audit_failures_total{check=\"consistency\",log=\"https://a.example.com/\"} 2
+ctscout_ct_audit_failures_total{check=\"inclusion\",log=\"https://b.example.com/\"} 1
+ctscout_cert_channel_depth 17
+";
+        let totals = metric_totals(text);
+        assert_eq!(totals["ctscout_ct_audit_failures_total"], 3.0);
+        assert_eq!(totals["ctscout_cert_channel_depth"], 17.0);
+        assert!(!totals.contains_key("ctscout_matches_found_total"));
+    }
+
+    #[tokio::test]
+    async fn test_file_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.toml");
+        assert!(file_logs(&path).await.unwrap().is_empty());
+
+        std::fs::write(
+            &path,
+            "\"https://b.example.com/\" = 20\n\"https://a.example.com/\" = 10\n\n\
+             [gaps]\n\"https://b.example.com/\" = [[5, 9]]\n",
+        )
+        .unwrap();
+
+        let logs = file_logs(&path).await.unwrap();
+        let summary: Vec<(&str, u64, usize)> =
+            logs.iter().map(|log| (log.url.as_str(), log.index, log.gaps)).collect();
+        assert_eq!(
+            summary,
+            vec![("https://a.example.com/", 10, 0), ("https://b.example.com/", 20, 1)]
+        );
+        assert!(logs[0].updated.is_some());
+        assert_eq!(logs[0].tree_size, None);
+    }
+
+    #[test]
+    fn test_by_url() {
+        const LOG_ID: &str = "7s3QZNXbGs7FXLedtM0TojKHRny87N7DUUhZRnEftZs=";
+        let log = |url: &str, index: u64| TrackedLog {
+            url: url.to_string(),
+            index,
+            updated: Some(0),
+            tree_size: None,
+            gaps: 0,
+        };
+        let ids = LogIds::new();
+        ids.register("https://ct.example.com/2025/", LOG_ID);
+        ids.register("https://ct.example.com/2026/", "MjAyNg==");
+        let logs = vec![
+            log(LOG_ID, 500),
+            log("https://ct.example.com/2025/", 100),  // Moved to LOG_ID
+            log("https://ct.example.com/2026/", 70),  // Not moved yet
+            log("https://custom.example.com/log", 9),
+        ];
+
+        let summary: Vec<(String, u64)> = by_url(logs, &ids).into_iter().map(|log| (log.url, log.index)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("https://ct.example.com/2025/".to_string(), 500),
+                ("https://ct.example.com/2026/".to_string(), 70),
+                ("https://custom.example.com/log".to_string(), 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_logs() {
+        let log = |url: &str, updated: Option<u64>| TrackedLog {
+            url: url.to_string(),
+            index: 10,
+            updated,
+            tree_size: Some(25),
+            gaps: 0,
+        };
+        let now = 100_000;
+        let logs = vec![
+            log("https://fresh.example.com/", Some(now - 60)),
+            log("https://stale.example.com/", Some(now - 7 * 3600)),
+            log("https://unknown.example.com/", None),
+        ];
+
+        let stale: Vec<&str> = stale_logs(&logs, 6 * 3600, now).iter().map(|log| log.url.as_str()).collect();
+        assert_eq!(stale, vec!["https://stale.example.com/"]);
+        assert!(stale_logs(&logs, 0, now).is_empty());
+
+        let table = render(&logs, now);
+        assert!(table.contains("https://stale.example.com/    10     15      "));
+        assert!(table.lines().nth(2).unwrap().ends_with("7h   0"));
+    }
+}
@@ -0,0 +1,61 @@
+// src/commands/sync.rs
+//! `ct-scout sync`: fetch programs from the enabled platforms and print their scope
+
+use anyhow::Result;
+
+use crate::cli::SyncArgs;
+use crate::config::Config;
+use crate::platforms::{self, FetchOptions};
+
+pub async fn run(config: &Config, args: &SyncArgs) -> Result<()> {
+    let platforms = platforms::connect_enabled(&config.platforms).await?;
+    if platforms.is_empty() {
+        anyhow::bail!("No platform is enabled (and reachable) in the [platforms] config section");
+    }
+
+    for platform in &platforms {
+        let programs = platform
+            .fetch_programs_with_options(fetch_options(config, platform.name(), args.dry_run))
+            .await?;
+
+        println!("{}: {} programs", platform.name(), programs.len());
+        for program in &programs {
+            if args.dry_run {
+                println!("  {}", program.name);
+            } else {
+                println!(
+                    "  {} ({} domains, {} hosts)",
+                    program.name,
+                    program.domains.len(),
+                    program.hosts.len()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Filter and program limit configured for a platform
+fn fetch_options(config: &Config, platform: &str, dry_run: bool) -> FetchOptions {
+    let (filter, max_programs) = match platform {
+        "HackerOne" => config
+            .platforms
+            .hackerone
+            .as_ref()
+            .map(|h1| (h1.filter.clone(), h1.max_programs)),
+        "Intigriti" => config
+            .platforms
+            .intigriti
+            .as_ref()
+            .map(|intigriti| (intigriti.filter.clone(), intigriti.max_programs)),
+        _ => None,
+    }
+    .unwrap_or_else(|| ("all".to_string(), None));
+
+    FetchOptions {
+        filter,
+        max_programs: max_programs.unwrap_or(config.platforms.max_programs_per_platform),
+        dry_run,
+    }
+}
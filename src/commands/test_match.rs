@@ -0,0 +1,73 @@
+// src/commands/test_match.rs
+//! `ct-scout test-match`: check domains or IPs against the watchlist
+
+use anyhow::Result;
+use std::net::IpAddr;
+
+use crate::config::Config;
+use crate::watchlist::{Program, Watchlist};
+
+pub fn run(config: &Config, targets: &[String]) -> Result<()> {
+    let watchlist = Watchlist::from_config(&config.watchlist, &config.programs)?;
+    for target in targets {
+        println!("{}", describe(&watchlist, target));
+    }
+    Ok(())
+}
+
+/// One line saying whether (and for which program) a target matches
+fn describe(watchlist: &Watchlist, target: &str) -> String {
+    let target = target.trim().to_lowercase();
+
+    let (matched, program) = match target.parse::<IpAddr>() {
+        Ok(ip) => (watchlist.matches_ip(&ip), watchlist.program_for_ip(&ip)),
+        Err(_) => (
+            watchlist.matches_domain(&target),
+            watchlist.program_for_domain(&target),
+        ),
+    };
+
+    match (matched, program) {
+        (false, _) => format!("[-] {}: no match", target),
+        (true, None) => format!("[+] {}: match (global watchlist)", target),
+        (true, Some(program)) => format!("[+] {}: match (program: {})", target, program_label(program)),
+    }
+}
+
+fn program_label(program: &Program) -> String {
+    match program.platform {
+        Some(ref platform) => format!("{} ({})", program.name, platform),
+        None => program.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProgramConfig, WatchlistConfig};
+
+    #[test]
+    fn test_describe() {
+        let config = WatchlistConfig {
+            domains: vec!["*.example.com".to_string()],
+            hosts: vec![],
+            ips: vec![],
+            cidrs: vec![],
+            ..Default::default()
+        };
+        let programs = vec![ProgramConfig {
+            name: "Acme".to_string(),
+            domains: vec!["*.acme.test".to_string()],
+            hosts: vec![],
+            cidrs: vec!["192.0.2.0/24".to_string()],
+            ips: vec![],
+            ..Default::default()
+        }];
+        let watchlist = Watchlist::from_config(&config, &programs).unwrap();
+
+        assert_eq!(describe(&watchlist, "API.example.com"), "[+] api.example.com: match (global watchlist)");
+        assert_eq!(describe(&watchlist, "shop.acme.test"), "[+] shop.acme.test: match (program: Acme (Config))");
+        assert_eq!(describe(&watchlist, "192.0.2.44"), "[+] 192.0.2.44: match (program: Acme (Config))");
+        assert_eq!(describe(&watchlist, "example.org"), "[-] example.org: no match");
+    }
+}
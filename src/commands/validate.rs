@@ -0,0 +1,138 @@
+// src/commands/validate.rs
+//! `ct-scout validate`: check a config file without starting any monitors
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::ct_log::{LogCheckPolicy, SaturationPolicy};
+use crate::enrich::IntelProvider;
+use crate::logfile::Rotation;
+use crate::output::timestamps::TimestampFormat;
+use crate::watchlist::Watchlist;
+
+pub fn run(paths: &[PathBuf], overrides: &[String]) -> Result<()> {
+    let names: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+    let config = Config::load(paths, overrides)
+        .with_context(|| format!("Failed to load config {}", names.join(" + ")))?;
+    let watchlist = check(&config)?;
+
+    println!("{} is valid", names.join(" + "));
+    println!(
+        "  Watchlist: {} domains, {} hosts, {} IPs, {} CIDRs",
+        config.watchlist.domains.len(),
+        config.watchlist.hosts.len(),
+        config.watchlist.ips.len(),
+        config.watchlist.cidrs.len()
+    );
+    println!("  Programs: {}", watchlist.programs().len());
+    Ok(())
+}
+
+/// Settings that only fail once monitoring starts, checked up front
+fn check(config: &Config) -> Result<Watchlist> {
+    let watchlist = Watchlist::from_config(&config.watchlist, &config.programs)
+        .context("Invalid watchlist")?;
+
+    config
+        .ct_logs
+        .channel_full_policy
+        .parse::<SaturationPolicy>()?;
+    config
+        .ct_logs
+        .check_configured_logs
+        .parse::<LogCheckPolicy>()?;
+
+    match config.ct_logs.state_backend.as_str() {
+        "file" | "sqlite" => {}
+        "database" if config.database.enabled => {}
+        "database" => anyhow::bail!("state_backend is 'database' but [database] is not enabled"),
+        other => anyhow::bail!("Invalid state_backend '{}'. Must be 'file', 'database' or 'sqlite'", other),
+    }
+    if config.cluster.enabled && config.ct_logs.state_backend != "database" {
+        anyhow::bail!("[cluster] needs state_backend = 'database' so instances share log positions");
+    }
+    if config.cluster.enabled && config.certstream.is_some() {
+        anyhow::bail!("[certstream] can't be used with [cluster]; every instance would report the same certificates");
+    }
+    if config.anomaly.is_some() && config.all_webhooks().next().is_none() {
+        anyhow::bail!("[anomaly] sends its alerts to [webhook], which is not configured");
+    }
+
+    if config.logging.file.is_some() {
+        Rotation::from_config(&config.logging.rotation, config.logging.max_size_mb)?;
+    }
+
+    TimestampFormat::from_config(config.output.timestamp_format.as_deref(), &config.output.timezone)?;
+
+    if let Some(ref intel) = config.enrichment.intel {
+        IntelProvider::from_config(&intel.provider, &intel.api_key, intel.api_secret.as_deref())?;
+    }
+
+    Ok(watchlist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn config_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let file = config_file(
+            r#"
+[logging]
+level = "info"
+
+[watchlist]
+domains = ["*.example.com"]
+hosts = []
+ips = []
+cidrs = ["10.0.0.0/8"]
+"#,
+        );
+        assert!(run(&[file.path().to_path_buf()], &[]).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_bad_settings() {
+        let bad_cidr = config_file(
+            r#"
+[logging]
+level = "info"
+
+[watchlist]
+domains = []
+hosts = []
+ips = []
+cidrs = ["10.0.0.0/33"]
+"#,
+        );
+        assert!(run(&[bad_cidr.path().to_path_buf()], &[]).is_err());
+
+        let db_state_without_db = config_file(
+            r#"
+[ct_logs]
+state_backend = "database"
+
+[logging]
+level = "info"
+
+[watchlist]
+domains = ["*.example.com"]
+hosts = []
+ips = []
+cidrs = []
+"#,
+        );
+        let err = run(&[db_state_without_db.path().to_path_buf()], &[]).unwrap_err();
+        assert!(err.to_string().contains("state_backend"));
+    }
+}
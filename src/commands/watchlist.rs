@@ -0,0 +1,74 @@
+// src/commands/watchlist.rs
+//! `ct-scout watchlist`: list watchlist snapshots and roll back to one
+//!
+//! Works from the snapshot directory alone, so it needs neither `[api]` nor
+//! the platform credentials. A rollback is handed to the running instance
+//! through a request file in that directory.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+use crate::cli::WatchlistCommand;
+use crate::config::{Config, PlatformsConfig};
+use crate::platforms::{request_rollback, SnapshotHistory};
+
+pub async fn run(config: &Config, command: &WatchlistCommand) -> Result<()> {
+    if config.platforms.snapshot_dir.is_empty() {
+        anyhow::bail!("Snapshots are only kept in memory; set platforms.snapshot_dir to use `ct-scout watchlist`");
+    }
+    let history = history(&config.platforms)?;
+    match command {
+        WatchlistCommand::Snapshots => {
+            let rows: Vec<Vec<String>> = history
+                .list()
+                .into_iter()
+                .map(|info| {
+                    vec![
+                        info.id.to_string(),
+                        timestamp(info.taken_at),
+                        info.programs.to_string(),
+                        info.domains.to_string(),
+                        info.hosts.to_string(),
+                        info.reason,
+                    ]
+                })
+                .collect();
+            if rows.is_empty() {
+                println!("No watchlist snapshots in {}", config.platforms.snapshot_dir);
+            } else {
+                print!("{}", super::table(&["ID", "TAKEN", "PROGRAMS", "DOMAINS", "HOSTS", "REASON"], &rows));
+            }
+        }
+        WatchlistCommand::Rollback { id } => {
+            let Some(snapshot) = history.get(*id) else {
+                match id {
+                    Some(id) => anyhow::bail!("No watchlist snapshot {}", id),
+                    None => anyhow::bail!("No watchlist snapshots in {}", config.platforms.snapshot_dir),
+                }
+            };
+            let info = snapshot.info();
+            request_rollback(Path::new(&config.platforms.snapshot_dir), info.id)?;
+            println!(
+                "Requested rollback to snapshot {} ({}, {} programs); the running instance applies it within a few seconds",
+                info.id, info.reason, info.programs
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Snapshot history as configured by `[platforms]`
+pub fn history(config: &PlatformsConfig) -> Result<SnapshotHistory> {
+    let history = SnapshotHistory::new(config.watchlist_snapshots);
+    if config.snapshot_dir.is_empty() {
+        return Ok(history);
+    }
+    history.with_dir(&config.snapshot_dir)
+}
+
+fn timestamp(secs: u64) -> String {
+    DateTime::<Utc>::from_timestamp(secs as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
@@ -1,83 +1,361 @@
 // src/config.rs
 
+use anyhow::Context;
+use schemars::JsonSchema;
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct CtLogConfig {
     #[serde(default = "default_poll_interval")]
     pub poll_interval_secs: u64,
+    #[serde(default)]
+    pub adaptive_poll: bool,  // Adjust each log's poll interval to its growth rate (default: false)
+    #[serde(default = "default_min_poll_interval")]
+    pub min_poll_interval_secs: u64,  // Fastest adaptive poll interval
+    #[serde(default = "default_max_poll_interval")]
+    pub max_poll_interval_secs: u64,  // Slowest adaptive poll interval
     #[serde(default = "default_batch_size")]
     pub batch_size: u64,
     #[serde(default = "default_log_list_url")]
     pub log_list_url: String,
     #[serde(default)]
+    pub log_list_urls: Vec<String>,  // Multiple lists merged in priority order (overrides log_list_url)
+    #[serde(default = "default_log_list_refresh")]
+    pub log_list_refresh_secs: u64,  // Re-fetch the log list at runtime (0 = never)
+    #[serde(default = "default_log_list_cache_dir")]
+    pub log_list_cache_dir: String,  // Copies of the log lists, used when a download fails ("" = no cache)
+    #[serde(default)]
     pub custom_logs: Option<Vec<String>>,  // Replaces Google list (backward compat)
     #[serde(default)]
     pub additional_logs: Option<Vec<String>>,  // Merges with Google list
+    #[serde(default = "default_check_configured_logs")]
+    pub check_configured_logs: String,  // Probe custom/additional logs at startup: "off", "warn", "skip" or "fail"
     #[serde(default = "default_state_file")]
     pub state_file: String,
     #[serde(default = "default_state_backend")]
-    pub state_backend: String,  // "file" or "database"
-    #[serde(default = "default_max_concurrent_logs")]
-    pub max_concurrent_logs: usize,
+    pub state_backend: String,  // "file", "database" or "sqlite"
+    #[serde(default = "default_state_db")]
+    pub state_db: String,  // SQLite file used when state_backend = "sqlite"
+    #[serde(default = "default_commit_batch")]
+    pub commit_batch: u64,  // Handled entries per saved-index update (1 = after every entry)
+    #[serde(default)]
+    pub max_concurrent_logs: Option<usize>,  // Monitor only the first N logs (unset = all)
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,  // Cap on in-flight requests across all logs
     #[serde(default = "default_parse_precerts")]
     pub parse_precerts: bool,
+    #[serde(default = "default_parallel_fetches")]
+    pub parallel_fetches: usize,  // Concurrent get-entries requests per log when catching up
     #[serde(default = "default_include_readonly_logs")]
     pub include_readonly_logs: bool,
     #[serde(default = "default_include_all_logs")]
     pub include_all_logs: bool,
     #[serde(default = "default_include_pending")]
     pub include_pending: bool,  // Include pending logs (like gungnir)
+    #[serde(default = "default_skip_expired_shards")]
+    pub skip_expired_shards: bool,  // Skip temporal shards whose window has ended
+    #[serde(default)]
+    pub include_operators: Vec<String>,  // Only logs whose operator name contains one of these
+    #[serde(default)]
+    pub exclude_operators: Vec<String>,  // Skip logs whose operator name contains one of these
+    #[serde(default)]
+    pub include_log_descriptions: Vec<String>,  // Only logs whose description contains one of these
+    #[serde(default)]
+    pub exclude_log_descriptions: Vec<String>,  // Skip logs whose description contains one of these
     #[serde(default = "default_dedupe")]
     pub dedupe: bool,  // Enable certificate deduplication (default: true)
     #[serde(default = "default_reconnect_delay")]
     pub reconnect_delay_secs: u64,  // Delay before reconnecting to failed logs
+    #[serde(default)]
+    pub rate_limit_per_sec: Option<f64>,  // Max requests/sec per log (None = unlimited)
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,  // Token bucket capacity per log
+    #[serde(default)]
+    pub log_rate_limits: HashMap<String, f64>,  // Per-log requests/sec overrides keyed by log URL
+    #[serde(default)]
+    pub proxy: Option<String>,  // HTTP/SOCKS proxy for log and log list requests
+    #[serde(default)]
+    pub log_proxies: HashMap<String, String>,  // Per-log proxy overrides keyed by log URL ("" = direct)
+    #[serde(default)]
+    pub user_agent: Option<String>,  // User-Agent for log requests (default: ct-scout/<version>)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,  // Extra headers sent with every log request
+    #[serde(default)]
+    pub log_headers: HashMap<String, HashMap<String, String>>,  // Per-log extra headers keyed by log URL
+    #[serde(default = "default_http_timeout")]
+    pub http_timeout_secs: u64,  // Timeout of each log request
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,  // Attempts per log request, including the first
+    #[serde(default = "default_retry_backoff")]
+    pub retry_backoff_secs: u64,  // Delay before the first retry, doubled per attempt (max 60s)
+    #[serde(default)]
+    pub log_requests: HashMap<String, LogRequestConfig>,  // Per-log timeout/retry overrides keyed by log URL
+    #[serde(default)]
+    pub audit_enabled: bool,  // Verify consistency/inclusion proofs (default: false)
+    #[serde(default = "default_audit_interval")]
+    pub audit_interval_secs: u64,  // Minimum time between audits of a log
+    #[serde(default = "default_audit_inclusion_samples")]
+    pub audit_inclusion_samples: usize,  // Entries per audit checked with inclusion proofs
+    #[serde(default = "default_channel_capacity")]
+    pub channel_capacity: usize,  // Certificates buffered between monitors and the coordinator
+    #[serde(default = "default_channel_full_policy")]
+    pub channel_full_policy: String,  // "block", "drop" or "spill" when the buffer is full
+    #[serde(default = "default_channel_spill_file")]
+    pub channel_spill_file: String,  // Overflow file for the "spill" policy
+    #[serde(default = "default_quarantine_after_hours")]
+    pub quarantine_after_hours: u64,  // Continuous failure before a log is quarantined (0 = never)
+    #[serde(default = "default_quarantine_probe_interval")]
+    pub quarantine_probe_interval_secs: u64,  // How often quarantined logs are re-probed
+    #[serde(default = "default_state_age_warn_hours")]
+    pub state_age_warn_hours: u64,  // Warn when a log's saved index hasn't advanced for this long (0 = never)
+    #[serde(default)]
+    pub include_pem: bool,  // Include the leaf certificate PEM in JSON/webhook output
 }
 
 fn default_poll_interval() -> u64 { 10 }
+fn default_min_poll_interval() -> u64 { 2 }
+fn default_max_poll_interval() -> u64 { 300 }
 fn default_batch_size() -> u64 { 256 }
+fn default_commit_batch() -> u64 { 100 }
 fn default_log_list_url() -> String {
     "https://www.gstatic.com/ct/log_list/v3/all_logs_list.json".to_string()
 }
+fn default_log_list_refresh() -> u64 { 86400 }
+fn default_check_configured_logs() -> String { "warn".to_string() }
+fn default_log_list_cache_dir() -> String { "ct-scout-log-lists".to_string() }
 fn default_state_file() -> String { "ct-scout-state.toml".to_string() }
+fn default_state_db() -> String { "ct-scout-state.db".to_string() }
 fn default_state_backend() -> String { "file".to_string() }
-fn default_max_concurrent_logs() -> usize { 100 }
 fn default_parse_precerts() -> bool { true }
+fn default_parallel_fetches() -> usize { 1 }
 fn default_include_readonly_logs() -> bool { false }
 fn default_include_all_logs() -> bool { false }
 fn default_include_pending() -> bool { false }
+fn default_skip_expired_shards() -> bool { true }
 fn default_dedupe() -> bool { true }
 fn default_reconnect_delay() -> u64 { 30 }
+fn default_rate_limit_burst() -> u32 { 5 }
+fn default_http_timeout() -> u64 { 30 }
+fn default_max_retries() -> u32 { 3 }
+fn default_retry_backoff() -> u64 { 1 }
+fn default_audit_interval() -> u64 { 300 }
+fn default_audit_inclusion_samples() -> usize { 2 }
+fn default_channel_capacity() -> usize { 1000 }
+fn default_channel_full_policy() -> String { "block".to_string() }
+fn default_channel_spill_file() -> String { "ct-scout-spill.jsonl".to_string() }
+fn default_quarantine_after_hours() -> u64 { 24 }
+fn default_state_age_warn_hours() -> u64 { 6 }
+fn default_quarantine_probe_interval() -> u64 { 86400 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct WebhookConfig {
+    #[serde(default)]
+    pub name: Option<String>,  // Label in stats, metrics and spool files (required with several webhooks)
     pub url: String,
     pub secret: Option<String>,
     pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub program: Option<String>,  // Only matches of programs matching this glob
+    #[serde(default)]
+    pub platform: Option<String>,  // Only matches from platforms matching this glob
+    #[serde(default)]
+    pub severity: Option<String>,  // Only matches of this severity, e.g. ">=high"
+    #[serde(default)]
+    pub tags: Vec<String>,  // Only matches carrying one of these tags
+    #[serde(default = "default_webhook_failure_warn_percent")]
+    pub failure_warn_percent: u32,  // Warn when this share of recent deliveries failed (0 = never)
+    #[serde(default)]
+    pub legacy_signature: bool,  // Sign the body alone, as before v1 signatures (no replay protection)
+}
+
+fn default_webhook_failure_warn_percent() -> u32 { 50 }
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            url: String::new(),
+            secret: None,
+            timeout_secs: None,
+            program: None,
+            platform: None,
+            severity: None,
+            tags: Vec::new(),
+            failure_warn_percent: default_webhook_failure_warn_percent(),
+            legacy_signature: false,
+        }
+    }
 }
 
 impl fmt::Debug for WebhookConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WebhookConfig")
+            .field("name", &self.name)
             .field("url", &self.url)
             .field("secret", &self.secret.as_ref().map(|_| "***REDACTED***"))
             .field("timeout_secs", &self.timeout_secs)
+            .field("program", &self.program)
+            .field("platform", &self.platform)
+            .field("severity", &self.severity)
+            .field("tags", &self.tags)
+            .field("failure_warn_percent", &self.failure_warn_percent)
+            .field("legacy_signature", &self.legacy_signature)
             .finish()
     }
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Deserialize, JsonSchema, Clone)]
+pub struct SentryConfig {
+    pub dsn: String,
+    #[serde(default)]
+    pub environment: Option<String>,  // e.g. "production"
+    #[serde(default = "default_sentry_warn_threshold")]
+    pub warn_threshold: u32,  // Report a warning once it repeats this often within window_secs (0 = never)
+    #[serde(default = "default_sentry_window")]
+    pub window_secs: u64,  // Report each error/warning call site at most once per window
+}
+
+fn default_sentry_warn_threshold() -> u32 { 10 }
+fn default_sentry_window() -> u64 { 3600 }
+
+impl fmt::Debug for SentryConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SentryConfig")
+            .field("dsn", &"***REDACTED***")
+            .field("environment", &self.environment)
+            .field("warn_threshold", &self.warn_threshold)
+            .field("window_secs", &self.window_secs)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Clone)]
+pub struct ApiConfig {
+    pub listen: std::net::SocketAddr,  // e.g. "127.0.0.1:8080"
+    pub token: String,  // Clients send "Authorization: Bearer <token>"
+}
+
+/// Shortest `[api]` / `[grpc]` token accepted; anything less is guessable
+const MIN_API_TOKEN_LEN: usize = 16;
+
+impl fmt::Debug for ApiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("listen", &self.listen)
+            .field("token", &"***REDACTED***")
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct CertstreamConfig {
+    pub url: String,  // Any certstream-compatible server, e.g. "wss://certstream.example.com/full-stream"
+    #[serde(default = "default_certstream_reconnect_delay")]
+    pub reconnect_delay_secs: u64,
+    #[serde(default = "default_certstream_poll_logs")]
+    pub poll_logs: bool,  // Also poll CT logs directly (false = certstream only)
+}
+
+fn default_certstream_reconnect_delay() -> u64 { 5 }
+fn default_certstream_poll_logs() -> bool { true }
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct KafkaConfig {
+    pub brokers: String,  // "kafka1:9092,kafka2:9092"
+    pub topic: String,
+    #[serde(default = "default_kafka_group_id")]
+    pub group_id: String,  // Instances sharing a group split the topic's partitions
+    #[serde(default)]
+    pub properties: HashMap<String, String>,  // Extra librdkafka settings, e.g. "security.protocol" = "SSL"
+    #[serde(default = "default_kafka_poll_logs")]
+    pub poll_logs: bool,  // Also poll CT logs directly (false = Kafka only)
+}
+
+fn default_kafka_group_id() -> String { "ct-scout".to_string() }
+fn default_kafka_poll_logs() -> bool { true }
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct HeartbeatConfig {
+    pub url: String,  // Pinged with a JSON status on every interval
+    #[serde(default = "default_heartbeat_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_heartbeat_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_heartbeat_interval() -> u64 { 300 }
+fn default_heartbeat_timeout() -> u64 { 10 }
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct AsnConfig {
+    #[serde(default = "default_asn_source_url")]
+    pub source_url: String,  // RIPEstat-compatible API serving announced-prefixes
+    #[serde(default = "default_asn_refresh_hours")]
+    pub refresh_hours: u64,  // Re-fetch announced prefixes this often
+    #[serde(default = "default_asn_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_asn_source_url() -> String { "https://stat.ripe.net".to_string() }
+fn default_asn_refresh_hours() -> u64 { 24 }
+fn default_asn_timeout() -> u64 { 30 }
+
+impl Default for AsnConfig {
+    fn default() -> Self {
+        Self {
+            source_url: default_asn_source_url(),
+            refresh_hours: default_asn_refresh_hours(),
+            timeout_secs: default_asn_timeout(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct AnomalyConfig {
+    #[serde(default = "default_anomaly_spike_multiple")]
+    pub spike_multiple: f64,  // Alert when a program's matches this hour exceed its baseline by this factor
+    #[serde(default = "default_anomaly_baseline_hours")]
+    pub baseline_hours: u64,  // Hours of history averaged into the baseline
+    #[serde(default = "default_anomaly_min_matches")]
+    pub min_matches: u64,  // Never alert below this many matches in an hour
+}
+
+fn default_anomaly_spike_multiple() -> f64 { 5.0 }
+fn default_anomaly_baseline_hours() -> u64 { 24 }
+fn default_anomaly_min_matches() -> u64 { 10 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct WatchlistConfig {
     pub domains: Vec<String>,
     pub hosts: Vec<String>,
     pub ips: Vec<String>,
     pub cidrs: Vec<String>,
+    #[serde(default)]
+    pub asns: Vec<String>,  // "AS13335": match addresses in any prefix the AS announces
+    #[serde(default = "default_wildcard_sans")]
+    pub wildcard_sans: String,  // "expand": *.example.com SANs match watched names directly under example.com; "literal"
 }
 
-#[derive(Debug, Deserialize)]
+fn default_wildcard_sans() -> String { "expand".to_string() }
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            domains: Vec::new(),
+            hosts: Vec::new(),
+            ips: Vec::new(),
+            cidrs: Vec::new(),
+            asns: Vec::new(),
+            wildcard_sans: default_wildcard_sans(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default)]
 pub struct ProgramConfig {
     pub name: String,
     #[serde(default)]
@@ -88,19 +366,58 @@ pub struct ProgramConfig {
     pub ips: Vec<String>,
     #[serde(default)]
     pub cidrs: Vec<String>,
+    #[serde(default)]
+    pub asns: Vec<String>,
+    #[serde(default)]
+    pub severity: Option<String>,  // "info", "low", "medium", "high" or "critical", for [[routing]]
+    #[serde(default)]
+    pub tags: Vec<String>,  // Free-form labels, for [[routing]]
+    #[serde(default)]
+    pub expected_issuers: Vec<String>,  // e.g. ["DigiCert", "Amazon"]: certificates from other CAs are flagged
 }
 
-#[derive(Debug, Deserialize)]
+/// One `[[routing]]` rule: matches it selects go to its outputs
+///
+/// An output named by any rule only gets the matches its rules select;
+/// outputs no rule names get every match.
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub program: Option<String>,  // Program name, "*" wildcards allowed (default: any)
+    #[serde(default)]
+    pub platform: Option<String>,  // Platform name, "*" wildcards allowed (default: any)
+    #[serde(default)]
+    pub severity: Option<String>,  // e.g. "high", ">=high", "<medium" (default: any)
+    #[serde(default)]
+    pub tags: Vec<String>,  // Match needs at least one of these tags (default: any)
+    pub outputs: Vec<String>,  // human, json, csv, pipe, webhook, redis, stream, tui
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct LoggingConfig {
     pub level: String,
+    #[serde(default)]
+    pub file: Option<String>,  // Also write logs to this file (plain text, no colours)
+    #[serde(default = "default_log_rotation")]
+    pub rotation: String,  // "daily", "size" or "never"
+    #[serde(default = "default_log_max_size_mb")]
+    pub max_size_mb: u64,  // Rotate at this size when rotation = "size"
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,  // Rotated files kept (0 = keep all)
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_log_rotation() -> String { "daily".to_string() }
+fn default_log_max_size_mb() -> u64 { 100 }
+fn default_log_max_files() -> usize { 7 }
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct StatsConfig {
     #[serde(default = "default_stats_enabled")]
     pub enabled: bool,
     #[serde(default = "default_stats_interval")]
     pub interval_secs: u64,
+    #[serde(default)]
+    pub export_path: Option<String>,  // Write a JSON stats snapshot here every interval_secs
 }
 
 fn default_stats_enabled() -> bool { false }
@@ -111,11 +428,77 @@ impl Default for StatsConfig {
         Self {
             enabled: default_stats_enabled(),
             interval_secs: default_stats_interval(),
+            export_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct OutputConfig {
+    #[serde(default = "default_output_queue_size")]
+    pub queue_size: usize,  // Matches queued per output before dropping (0 = emit inline)
+    #[serde(default = "default_output_workers")]
+    pub workers: usize,  // Concurrent emits per output
+    #[serde(default)]
+    pub spool_dir: Option<String>,  // Spool matches here instead of dropping them (default: drop)
+    #[serde(default = "default_output_flush_interval_ms")]
+    pub flush_interval_ms: u64,  // How often --output files are flushed (0 = after every match)
+    #[serde(default)]
+    pub timestamp_format: Option<String>,  // "unix" or "rfc3339" for every output (default: per output)
+    #[serde(default = "default_output_timezone")]
+    pub timezone: String,  // "utc" or "local"
+    #[serde(default = "default_output_throttle_secs")]
+    pub throttle_secs: u64,  // Notify a matched domain at most once per window (0 = every match)
+    #[serde(default = "default_output_short_lived_days")]
+    pub short_lived_days: u64,  // Tag matches on certificates valid for fewer days as short-lived (0 = off)
+}
+
+fn default_output_queue_size() -> usize { 1000 }
+fn default_output_workers() -> usize { 1 }
+fn default_output_flush_interval_ms() -> u64 { 1000 }
+fn default_output_timezone() -> String { "utc".to_string() }
+fn default_output_throttle_secs() -> u64 { 0 }
+fn default_output_short_lived_days() -> u64 { 7 }
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: default_output_queue_size(),
+            workers: default_output_workers(),
+            spool_dir: None,
+            flush_interval_ms: default_output_flush_interval_ms(),
+            timestamp_format: None,
+            timezone: default_output_timezone(),
+            throttle_secs: default_output_throttle_secs(),
+            short_lived_days: default_output_short_lived_days(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct ClusterConfig {
+    #[serde(default = "default_cluster_enabled")]
+    pub enabled: bool,  // Split the logs with other instances sharing [database]
+    #[serde(default)]
+    pub instance_id: Option<String>,  // Must be unique per instance (default: random)
+    #[serde(default = "default_cluster_lease_secs")]
+    pub lease_secs: u64,  // Logs of an instance that stops renewing move after this long
+}
+
+fn default_cluster_enabled() -> bool { false }
+fn default_cluster_lease_secs() -> u64 { 60 }
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cluster_enabled(),
+            instance_id: None,
+            lease_secs: default_cluster_lease_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct MetricsConfig {
     #[serde(default = "default_metrics_enabled")]
     pub enabled: bool,
@@ -123,6 +506,10 @@ pub struct MetricsConfig {
     pub export_path: Option<String>,
     #[serde(default = "default_metrics_export_interval")]
     pub export_interval_secs: u64,
+    #[serde(default)]
+    pub listen: Option<std::net::SocketAddr>,  // Serve /metrics, /healthz and /readyz here (e.g. "0.0.0.0:9100")
+    #[serde(default)]
+    pub pushgateway: Option<PushgatewayConfig>,  // Push the registry instead of (or as well as) being scraped
 }
 
 fn default_metrics_enabled() -> bool { false }
@@ -134,11 +521,111 @@ impl Default for MetricsConfig {
             enabled: default_metrics_enabled(),
             export_path: None,
             export_interval_secs: default_metrics_export_interval(),
+            listen: None,
+            pushgateway: None,
         }
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct PushgatewayConfig {
+    pub url: String,  // Pushgateway base URL, e.g. "http://pushgateway:9091"
+    #[serde(default = "default_pushgateway_job")]
+    pub job: String,
+    #[serde(default)]
+    pub instance: Option<String>,  // Extra grouping label, e.g. the hostname
+    #[serde(default = "default_pushgateway_interval")]
+    pub interval_secs: u64,  // Also pushed once more at shutdown
+    #[serde(default = "default_pushgateway_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_pushgateway_job() -> String { "ct-scout".to_string() }
+fn default_pushgateway_interval() -> u64 { 60 }
+fn default_pushgateway_timeout() -> u64 { 10 }
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct EnrichmentConfig {
+    #[serde(default)]
+    pub dns: bool,  // Resolve matched domains and check the IPs against watchlist ranges
+    #[serde(default)]
+    pub http_probe: bool,  // GET https://<domain>/ and record status, Server header and title
+    #[serde(default)]
+    pub takeover_check: bool,  // Flag CNAMEs to S3/GitHub Pages/Azure/Heroku that are dangling
+    #[serde(default)]
+    pub nameserver: Option<std::net::SocketAddr>,  // Resolver for CNAME lookups (default: /etc/resolv.conf)
+    #[serde(default)]
+    pub rdap: bool,  // Look up registrar / registration date of the registrable domain
+    #[serde(default = "default_rdap_url")]
+    pub rdap_url: String,  // RDAP server or bootstrap redirector
+    #[serde(default = "default_new_domain_days")]
+    pub new_domain_days: i64,  // Flag registrations younger than this
+    #[serde(default = "default_enrichment_timeout")]
+    pub timeout_secs: u64,  // Per-lookup / per-request timeout
+    #[serde(default = "default_enrichment_cache_ttl")]
+    pub cache_ttl_secs: u64,  // Reuse a host's results for this long (0 = no caching)
+    #[serde(default = "default_enrichment_cache_size")]
+    pub cache_size: usize,  // Max hosts kept in the cache
+    #[serde(default = "default_enrichment_workers")]
+    pub workers: usize,  // Matches enriched concurrently
+    #[serde(default = "default_enrichment_queue_size")]
+    pub queue_size: usize,  // Matches waiting for a worker before they go out unenriched
+    #[serde(default)]
+    pub intel: Option<HostIntelConfig>,  // Shodan/Censys lookups of matched addresses
+}
+
+#[derive(Deserialize, JsonSchema, Clone)]
+pub struct HostIntelConfig {
+    pub provider: String,  // "shodan" or "censys"
+    pub api_key: String,  // Shodan API key, or Censys API ID
+    #[serde(default)]
+    pub api_secret: Option<String>,  // Censys API secret
+    #[serde(default = "default_intel_requests_per_sec")]
+    pub requests_per_sec: f64,  // Both APIs allow about 1 query/second
+}
+
+fn default_intel_requests_per_sec() -> f64 { 1.0 }
+
+impl fmt::Debug for HostIntelConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostIntelConfig")
+            .field("provider", &self.provider)
+            .field("api_key", &"***REDACTED***")
+            .field("api_secret", &self.api_secret.as_ref().map(|_| "***REDACTED***"))
+            .field("requests_per_sec", &self.requests_per_sec)
+            .finish()
+    }
+}
+
+fn default_enrichment_timeout() -> u64 { 3 }
+fn default_enrichment_cache_ttl() -> u64 { 600 }
+fn default_enrichment_cache_size() -> usize { 10_000 }
+fn default_enrichment_workers() -> usize { 4 }
+fn default_enrichment_queue_size() -> usize { 100 }
+fn default_rdap_url() -> String { "https://rdap.org".to_string() }
+fn default_new_domain_days() -> i64 { 30 }
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            dns: false,
+            http_probe: false,
+            takeover_check: false,
+            nameserver: None,
+            rdap: false,
+            rdap_url: default_rdap_url(),
+            new_domain_days: default_new_domain_days(),
+            timeout_secs: default_enrichment_timeout(),
+            cache_ttl_secs: default_enrichment_cache_ttl(),
+            cache_size: default_enrichment_cache_size(),
+            workers: default_enrichment_workers(),
+            queue_size: default_enrichment_queue_size(),
+            intel: None,
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct DatabaseConfig {
     pub enabled: bool,
     #[serde(default = "default_database_url")]
@@ -195,17 +682,35 @@ impl Default for DatabaseConfig {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct RedisConfig {
     #[serde(default = "default_redis_enabled")]
     pub enabled: bool,
     #[serde(default = "default_redis_url")]
     pub url: String,
+    #[serde(default = "default_redis_mode")]
+    pub mode: String,  // "standalone", "cluster" or "sentinel"
+    #[serde(default)]
+    pub nodes: Vec<String>,  // Cluster seed nodes or Sentinel addresses
+    #[serde(default)]
+    pub sentinel_master: Option<String>,  // Master name to ask the Sentinels for
+    #[serde(default)]
+    pub tls: bool,  // Use TLS even for redis:// and host:port addresses
+    #[serde(default)]
+    pub tls_insecure: bool,  // Skip certificate verification (self-signed certs)
     pub token: Option<String>,
     #[serde(default = "default_redis_channel")]
     pub channel: String,
     pub queue_name: Option<String>,
     pub max_queue_size: Option<i64>,
+    #[serde(default = "default_redis_publish_channel")]
+    pub publish_channel: bool,  // PUBLISH to `channel`; false = stream and/or queue only
+    #[serde(default)]
+    pub stream: Option<String>,  // Also XADD each match to this Redis Stream
+    #[serde(default = "default_redis_stream_maxlen")]
+    pub stream_maxlen: u64,  // Trim the stream to about this many entries (0 = unbounded)
+    #[serde(default)]
+    pub stream_group: Option<String>,  // Consumer group created on the stream at startup
     #[serde(default = "default_redis_require")]
     pub require: bool,
 }
@@ -215,10 +720,19 @@ impl fmt::Debug for RedisConfig {
         f.debug_struct("RedisConfig")
             .field("enabled", &self.enabled)
             .field("url", &self.url)
+            .field("mode", &self.mode)
+            .field("nodes", &self.nodes)
+            .field("sentinel_master", &self.sentinel_master)
+            .field("tls", &self.tls)
+            .field("tls_insecure", &self.tls_insecure)
             .field("token", &self.token.as_ref().map(|_| "***REDACTED***"))
             .field("channel", &self.channel)
             .field("queue_name", &self.queue_name)
             .field("max_queue_size", &self.max_queue_size)
+            .field("publish_channel", &self.publish_channel)
+            .field("stream", &self.stream)
+            .field("stream_maxlen", &self.stream_maxlen)
+            .field("stream_group", &self.stream_group)
             .field("require", &self.require)
             .finish()
     }
@@ -226,24 +740,36 @@ impl fmt::Debug for RedisConfig {
 
 fn default_redis_enabled() -> bool { false }
 fn default_redis_url() -> String { "redis://localhost:6379".to_string() }
+fn default_redis_mode() -> String { "standalone".to_string() }
 fn default_redis_channel() -> String { "bb:ct_events".to_string() }
 fn default_redis_require() -> bool { false }
+fn default_redis_publish_channel() -> bool { true }
+fn default_redis_stream_maxlen() -> u64 { 100_000 }
 
 impl Default for RedisConfig {
     fn default() -> Self {
         Self {
             enabled: default_redis_enabled(),
             url: default_redis_url(),
+            mode: default_redis_mode(),
+            nodes: Vec::new(),
+            sentinel_master: None,
+            tls: false,
+            tls_insecure: false,
             token: None,
             channel: default_redis_channel(),
             queue_name: Some("bb:ct_events_queue".to_string()),
             max_queue_size: Some(10000),
+            publish_channel: default_redis_publish_channel(),
+            stream: None,
+            stream_maxlen: default_redis_stream_maxlen(),
+            stream_group: None,
             require: default_redis_require(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct PlatformsConfig {
     #[serde(default)]
     pub hackerone: Option<HackerOneConfig>,
@@ -253,9 +779,13 @@ pub struct PlatformsConfig {
     pub sync_interval_hours: u64,
     #[serde(default = "default_max_programs_per_platform")]
     pub max_programs_per_platform: usize,
+    #[serde(default = "default_watchlist_snapshots")]
+    pub watchlist_snapshots: usize,  // Watchlist copies kept for rollback (0 = none)
+    #[serde(default)]
+    pub snapshot_dir: String,  // Where snapshots are written for `ct-scout watchlist` (default "" = memory only)
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct HackerOneConfig {
     pub enabled: bool,
     pub username: String,
@@ -278,7 +808,7 @@ impl fmt::Debug for HackerOneConfig {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, JsonSchema, Clone)]
 pub struct IntigritiConfig {
     pub enabled: bool,
     pub api_token: String,
@@ -301,6 +831,7 @@ impl fmt::Debug for IntigritiConfig {
 
 fn default_sync_interval_hours() -> u64 { 6 }
 fn default_max_programs_per_platform() -> usize { 100 }
+pub fn default_watchlist_snapshots() -> usize { 10 }
 fn default_h1_filter() -> String { "bookmarked".to_string() }
 fn default_intigriti_filter() -> String { "following".to_string() }
 
@@ -311,30 +842,58 @@ impl Default for PlatformsConfig {
             intigriti: None,
             sync_interval_hours: default_sync_interval_hours(),
             max_programs_per_platform: default_max_programs_per_platform(),
+            watchlist_snapshots: default_watchlist_snapshots(),
+            snapshot_dir: String::new(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub ct_logs: CtLogConfig,
     #[serde(default)]
+    pub certstream: Option<CertstreamConfig>,
+    #[serde(default)]
+    pub kafka: Option<KafkaConfig>,  // Needs the `kafka` build feature
+    #[serde(default)]
     pub webhook: Option<WebhookConfig>,
     #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,  // [[webhooks]]: further destinations, each with its own filters
+    #[serde(default)]
+    pub heartbeat: Option<HeartbeatConfig>,
+    #[serde(default)]
+    pub anomaly: Option<AnomalyConfig>,  // Alerts go to [webhook] (or the first [[webhooks]])
+    #[serde(default)]
+    pub asn: AsnConfig,  // Where `asns` watchlist entries get their prefixes
+    #[serde(default)]
+    pub sentry: Option<SentryConfig>,
+    #[serde(default)]
+    pub api: Option<ApiConfig>,
+    #[serde(default)]
+    pub grpc: Option<ApiConfig>,  // Same fields as [api], served over gRPC
+    #[serde(default)]
     pub database: DatabaseConfig,
     #[serde(default)]
     pub redis: RedisConfig,
     #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    #[serde(default)]
     pub platforms: PlatformsConfig,
     #[serde(default)]
     pub stats: StatsConfig,
     #[serde(default)]
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub enrichment: EnrichmentConfig,
     pub logging: LoggingConfig,
     pub watchlist: WatchlistConfig,
     #[serde(default)]
     pub programs: Vec<ProgramConfig>,
+    #[serde(default)]
+    pub routing: Vec<RoutingConfig>,
     #[serde(default = "default_watch_config")]
     pub watch_config: bool,  // Watch config file for changes
 }
@@ -345,37 +904,892 @@ impl Default for CtLogConfig {
     fn default() -> Self {
         Self {
             poll_interval_secs: default_poll_interval(),
+            adaptive_poll: false,
+            min_poll_interval_secs: default_min_poll_interval(),
+            max_poll_interval_secs: default_max_poll_interval(),
             batch_size: default_batch_size(),
             log_list_url: default_log_list_url(),
+            log_list_urls: Vec::new(),
+            log_list_refresh_secs: default_log_list_refresh(),
+            log_list_cache_dir: default_log_list_cache_dir(),
             custom_logs: None,
             additional_logs: None,
+            check_configured_logs: default_check_configured_logs(),
             state_file: default_state_file(),
             state_backend: default_state_backend(),
-            max_concurrent_logs: default_max_concurrent_logs(),
+            state_db: default_state_db(),
+            commit_batch: default_commit_batch(),
+            max_concurrent_logs: None,
+            max_concurrent_requests: None,
             parse_precerts: default_parse_precerts(),
+            parallel_fetches: default_parallel_fetches(),
             include_readonly_logs: default_include_readonly_logs(),
             include_all_logs: default_include_all_logs(),
             include_pending: default_include_pending(),
+            skip_expired_shards: default_skip_expired_shards(),
+            include_operators: Vec::new(),
+            exclude_operators: Vec::new(),
+            include_log_descriptions: Vec::new(),
+            exclude_log_descriptions: Vec::new(),
             dedupe: default_dedupe(),
             reconnect_delay_secs: default_reconnect_delay(),
+            rate_limit_per_sec: None,
+            rate_limit_burst: default_rate_limit_burst(),
+            log_rate_limits: HashMap::new(),
+            proxy: None,
+            log_proxies: HashMap::new(),
+            user_agent: None,
+            headers: HashMap::new(),
+            log_headers: HashMap::new(),
+            http_timeout_secs: default_http_timeout(),
+            max_retries: default_max_retries(),
+            retry_backoff_secs: default_retry_backoff(),
+            log_requests: HashMap::new(),
+            audit_enabled: false,
+            audit_interval_secs: default_audit_interval(),
+            audit_inclusion_samples: default_audit_inclusion_samples(),
+            channel_capacity: default_channel_capacity(),
+            channel_full_policy: default_channel_full_policy(),
+            channel_spill_file: default_channel_spill_file(),
+            quarantine_after_hours: default_quarantine_after_hours(),
+            state_age_warn_hours: default_state_age_warn_hours(),
+            quarantine_probe_interval_secs: default_quarantine_probe_interval(),
+            include_pem: false,
+        }
+    }
+}
+
+/// `[ct_logs.log_requests."<url>"]`: request settings for one log, unset fields use the global ones
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
+pub struct LogRequestConfig {
+    #[serde(default)]
+    pub http_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub retry_backoff_secs: Option<u64>,
+}
+
+impl CtLogConfig {
+    /// Log lists to fetch, in priority order
+    pub fn list_urls(&self) -> Vec<String> {
+        if self.log_list_urls.is_empty() {
+            vec![self.log_list_url.clone()]
+        } else {
+            self.log_list_urls.clone()
         }
     }
 }
 
 impl Config {
     pub fn from_file(path: &Path) -> anyhow::Result<Self> {
-        let contents = fs::read_to_string(path)?;
-        let cfg: Config = toml::from_str(&contents)?;
-        Ok(cfg)
+        Self::load_layers(&[path.to_path_buf()], [], &[])
+    }
+
+    /// `[webhook]` followed by every `[[webhooks]]` entry
+    pub fn all_webhooks(&self) -> impl Iterator<Item = &WebhookConfig> {
+        self.webhook.iter().chain(&self.webhooks)
+    }
+
+    /// Merge config files in order, then apply `CT_SCOUT__SECTION__KEY` environment
+    /// variables and finally `section.key=value` overrides
+    /// Later files replace earlier values key by key; tables are merged, arrays replaced
+    pub fn load(paths: &[PathBuf], overrides: &[String]) -> anyhow::Result<Self> {
+        let env = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+        Self::load_layers(paths, env, overrides)
+    }
+
+    fn load_layers(
+        paths: &[PathBuf],
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[String],
+    ) -> anyhow::Result<Self> {
+        let mut merged = toml::Table::new();
+        let mut sources = Vec::with_capacity(paths.len());
+        for path in paths {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config {}", path.display()))?;
+            let layer: toml::Table = toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse config {}", path.display()))?;
+            merge_tables(&mut merged, layer);
+            sources.push((path.as_path(), contents));
+        }
+
+        // Environment values have no TOML types, so they are only parsed as
+        // TOML where the config doesn't expect a string: `SECRET=123456` stays
+        // a string, `BATCH_SIZE=1024` becomes an integer
+        let env = env_overrides(env);
+        if !env.is_empty() {
+            let schema = serde_json::to_value(schemars::schema_for!(Config))?;
+            for (name, key, raw) in env {
+                if schema_expects_string(&schema, &key) {
+                    set_value(&mut merged, &key, toml::Value::String(raw))
+                } else {
+                    apply_override(&mut merged, &format!("{}={}", key, raw))
+                }
+                .with_context(|| format!("Invalid environment override {}", name))?;
+            }
+        }
+
+        for assignment in overrides {
+            apply_override(&mut merged, assignment)
+                .with_context(|| format!("Invalid --set '{}'", assignment))?;
+        }
+
+        resolve_secrets(&mut merged)?;
+
+        let config: Config = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| report(&[Diagnostic::from_toml(&e)], &sources))?;
+
+        let diagnostics = config.validate();
+        if !diagnostics.is_empty() {
+            return Err(report(&diagnostics, &sources));
+        }
+        Ok(config)
+    }
+
+    /// Check watchlist entries and URLs that would otherwise only fail once monitoring starts
+    fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        if let Err(e) = self.watchlist.wildcard_sans.parse::<crate::watchlist::WildcardSans>() {
+            diagnostics.push(Diagnostic {
+                path: "watchlist.wildcard_sans".to_string(),
+                message: e.to_string(),
+                value: None,
+            });
+        }
+        check_watchlist(&mut diagnostics, "watchlist", &self.watchlist.domains, &self.watchlist.hosts,
+            &self.watchlist.ips, &self.watchlist.cidrs, &self.watchlist.asns);
+        for (i, program) in self.programs.iter().enumerate() {
+            check_watchlist(&mut diagnostics, &format!("programs[{}]", i), &program.domains, &program.hosts,
+                &program.ips, &program.cidrs, &program.asns);
+            if let Some(Err(e)) = program.severity.as_deref().map(str::parse::<crate::types::Severity>) {
+                diagnostics.push(Diagnostic {
+                    path: format!("programs[{}].severity", i),
+                    message: e.to_string(),
+                    value: None,
+                });
+            }
+        }
+        for (i, rule) in self.routing.iter().enumerate() {
+            if let Some(Err(e)) = rule.severity.as_deref().map(str::parse::<crate::output::routing::SeverityFilter>) {
+                diagnostics.push(Diagnostic {
+                    path: format!("routing[{}].severity", i),
+                    message: e.to_string(),
+                    value: None,
+                });
+            }
+            for (j, output) in rule.outputs.iter().enumerate() {
+                if !crate::output::routing::OUTPUT_NAMES.contains(&output.as_str()) {
+                    diagnostics.push(Diagnostic {
+                        path: format!("routing[{}].outputs[{}]", i, j),
+                        message: format!("Unknown output '{}'. Must be one of: {}", output,
+                            crate::output::routing::OUTPUT_NAMES.join(", ")),
+                        value: Some(output.clone()),
+                    });
+                }
+            }
+        }
+
+        check_url(&mut diagnostics, "ct_logs.log_list_url", &self.ct_logs.log_list_url);
+        check_urls(&mut diagnostics, "ct_logs.log_list_urls", &self.ct_logs.log_list_urls);
+        check_urls(&mut diagnostics, "ct_logs.custom_logs", self.ct_logs.custom_logs.as_deref().unwrap_or_default());
+        check_urls(&mut diagnostics, "ct_logs.additional_logs", self.ct_logs.additional_logs.as_deref().unwrap_or_default());
+        if let Some(ref proxy) = self.ct_logs.proxy {
+            check_url(&mut diagnostics, "ct_logs.proxy", proxy);
+        }
+        let log_timeouts = self.ct_logs.log_requests.iter().map(|(url, requests)| {
+            (format!("ct_logs.log_requests.\"{}\".http_timeout_secs", url), requests.http_timeout_secs)
+        });
+        for (path, timeout) in std::iter::once(("ct_logs.http_timeout_secs".to_string(), Some(self.ct_logs.http_timeout_secs)))
+            .chain(log_timeouts)
+        {
+            if timeout == Some(0) {
+                diagnostics.push(Diagnostic {
+                    path,
+                    message: "must be at least 1 second".to_string(),
+                    value: None,
+                });
+            }
+        }
+        if let Some(ref certstream) = self.certstream {
+            check_url(&mut diagnostics, "certstream.url", &certstream.url);
+        }
+        if let Some(ref kafka) = self.kafka {
+            for (path, value) in [("kafka.brokers", &kafka.brokers), ("kafka.topic", &kafka.topic)] {
+                if value.trim().is_empty() {
+                    diagnostics.push(Diagnostic {
+                        path: path.to_string(),
+                        message: "must not be empty".to_string(),
+                        value: None,
+                    });
+                }
+            }
+        }
+        let webhooks: Vec<(String, &WebhookConfig)> = self
+            .webhook
+            .iter()
+            .map(|webhook| ("webhook".to_string(), webhook))
+            .chain(self.webhooks.iter().enumerate().map(|(i, webhook)| (format!("webhooks[{}]", i), webhook)))
+            .collect();
+        let mut names = HashSet::new();
+        for (path, webhook) in &webhooks {
+            check_url(&mut diagnostics, &format!("{}.url", path), &webhook.url);
+            if webhook.failure_warn_percent > 100 {
+                diagnostics.push(Diagnostic {
+                    path: format!("{}.failure_warn_percent", path),
+                    message: format!("{} is not a percentage (0-100)", webhook.failure_warn_percent),
+                    value: None,
+                });
+            }
+            if let Some(Err(e)) = webhook.severity.as_deref().map(str::parse::<crate::output::routing::SeverityFilter>) {
+                diagnostics.push(Diagnostic {
+                    path: format!("{}.severity", path),
+                    message: e.to_string(),
+                    value: None,
+                });
+            }
+            if webhooks.len() > 1 {
+                match webhook.name {
+                    None => diagnostics.push(Diagnostic {
+                        path: path.clone(),
+                        message: "needs a name when more than one webhook is configured".to_string(),
+                        value: None,
+                    }),
+                    Some(ref name) if !names.insert(name) => diagnostics.push(Diagnostic {
+                        path: format!("{}.name", path),
+                        message: format!("'{}' is used by another webhook", name),
+                        value: None,
+                    }),
+                    Some(_) => {}
+                }
+            }
+        }
+        for (section, api) in [("api", &self.api), ("grpc", &self.grpc)] {
+            if let Some(api) = api
+                && api.token.trim().chars().count() < MIN_API_TOKEN_LEN
+            {
+                diagnostics.push(Diagnostic {
+                    path: format!("{}.token", section),
+                    message: format!("must be at least {} characters", MIN_API_TOKEN_LEN),
+                    value: None,
+                });
+            }
+        }
+        if let Some(ref pushgateway) = self.metrics.pushgateway {
+            check_url(&mut diagnostics, "metrics.pushgateway.url", &pushgateway.url);
+        }
+        if let Some(ref heartbeat) = self.heartbeat {
+            check_url(&mut diagnostics, "heartbeat.url", &heartbeat.url);
+        }
+        check_url(&mut diagnostics, "enrichment.rdap_url", &self.enrichment.rdap_url);
+        check_url(&mut diagnostics, "asn.source_url", &self.asn.source_url);
+
+        match self.redis.mode.parse::<crate::redis_publisher::RedisMode>() {
+            Err(e) => diagnostics.push(Diagnostic {
+                path: "redis.mode".to_string(),
+                message: e.to_string(),
+                value: None,
+            }),
+            Ok(crate::redis_publisher::RedisMode::Sentinel) => {
+                if self.redis.sentinel_master.is_none() {
+                    diagnostics.push(Diagnostic {
+                        path: "redis.mode".to_string(),
+                        message: "sentinel mode needs sentinel_master".to_string(),
+                        value: None,
+                    });
+                }
+                if self.redis.nodes.is_empty() {
+                    diagnostics.push(Diagnostic {
+                        path: "redis.mode".to_string(),
+                        message: "sentinel mode needs at least one Sentinel in nodes".to_string(),
+                        value: None,
+                    });
+                }
+            }
+            Ok(_) => {}
+        }
+
+        diagnostics
+    }
+}
+
+/// A problem with one config value, e.g. `watchlist.cidrs[2]: 'x' is not a valid CIDR`
+struct Diagnostic {
+    /// Dotted key path, with array indices
+    path: String,
+    message: String,
+    /// Offending array element, used to find its line in multi-line arrays
+    value: Option<String>,
+}
+
+impl Diagnostic {
+    /// Split toml's "message\nin `key.path`" into path and message
+    fn from_toml(error: &toml::de::Error) -> Self {
+        let text = error.to_string();
+        let (message, path) = match text.trim_end().rsplit_once("\nin `") {
+            Some((message, path)) => (message.to_string(), path.trim_end_matches('`').to_string()),
+            None => (text.trim_end().to_string(), String::new()),
+        };
+        Self { path, message, value: None }
+    }
+}
+
+fn check_watchlist(
+    diagnostics: &mut Vec<Diagnostic>,
+    section: &str,
+    domains: &[String],
+    hosts: &[String],
+    ips: &[String],
+    cidrs: &[String],
+    asns: &[String],
+) {
+    let mut check = |key: &str, values: &[String], valid: fn(&str) -> bool, what: &str| {
+        for (i, value) in values.iter().enumerate() {
+            if !valid(value) {
+                diagnostics.push(Diagnostic {
+                    path: format!("{}.{}[{}]", section, key, i),
+                    message: format!("'{}' is not a valid {}", value, what),
+                    value: Some(value.clone()),
+                });
+            }
+        }
+    };
+    check("domains", domains, is_domain_pattern, "domain pattern (example.com, .example.com or *.example.com)");
+    check("hosts", hosts, is_hostname, "hostname");
+    check("ips", ips, |v| v.parse::<std::net::IpAddr>().is_ok(), "IP address");
+    check("cidrs", cidrs, |v| v.parse::<ipnet::IpNet>().is_ok(), "CIDR");
+    check("asns", asns, |v| crate::watchlist::parse_asn(v).is_some(), "AS number (AS13335 or 13335)");
+}
+
+fn check_urls(diagnostics: &mut Vec<Diagnostic>, key: &str, urls: &[String]) {
+    for (i, url) in urls.iter().enumerate() {
+        check_url(diagnostics, &format!("{}[{}]", key, i), url);
+    }
+}
+
+fn check_url(diagnostics: &mut Vec<Diagnostic>, path: &str, url: &str) {
+    if let Err(e) = url::Url::parse(url) {
+        diagnostics.push(Diagnostic {
+            path: path.to_string(),
+            message: format!("'{}' is not a valid URL ({})", url, e),
+            value: Some(url.to_string()),
+        });
+    }
+}
+
+/// `example.com`, `.example.com` (domain and subdomains) or `*.example.com` (subdomains only)
+fn is_domain_pattern(pattern: &str) -> bool {
+    let name = pattern
+        .strip_prefix("*.")
+        .or_else(|| pattern.strip_prefix('.'))
+        .unwrap_or(pattern);
+    is_hostname(name)
+}
+
+fn is_hostname(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// All diagnostics as one error, each followed by the config line it points at (if found)
+fn report(diagnostics: &[Diagnostic], sources: &[(&Path, String)]) -> anyhow::Error {
+    let mut text = String::new();
+    for (i, diagnostic) in diagnostics.iter().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+        if diagnostic.path.is_empty() {
+            text.push_str(&diagnostic.message);
+        } else {
+            text.push_str(&format!("{}: {}", diagnostic.path, diagnostic.message));
+        }
+
+        // Later files override earlier ones, so the value most likely came from the last
+        let found = sources.iter().rev().find_map(|(path, contents)| {
+            locate(contents, &diagnostic.path, diagnostic.value.as_deref()).map(|line| (path, line))
+        });
+        if let Some((path, (number, line))) = found {
+            text.push_str(&format!("\n  --> {}:{}\n   | {}", path.display(), number, line.trim()));
+        }
+    }
+    anyhow::anyhow!(text)
+}
+
+/// 1-based number and text of the line defining `path` in a TOML file
+///
+/// Finds the `key = ...` line under the matching `[table]` (or the n-th
+/// `[[table]]` for `programs[n]`); for array elements, the line holding
+/// `value`. Falls back to the table header, for errors like a missing field.
+fn locate<'a>(contents: &'a str, path: &str, value: Option<&str>) -> Option<(usize, &'a str)> {
+    if path.is_empty() {
+        return None;
+    }
+    let base = strip_index(path);
+    let (table, key) = base.rsplit_once('.').unwrap_or(("", base));
+
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut current = String::new();
+    let mut current_plain = String::new();
+    let mut array_counts: HashMap<String, usize> = HashMap::new();
+    let mut header = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("[[").and_then(|rest| rest.split_once("]]")).map(|(name, _)| name.trim()) {
+            let count = array_counts.entry(name.to_string()).or_default();
+            current = format!("{}[{}]", name, count);
+            current_plain = name.to_string();
+            *count += 1;
+        } else if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.split_once(']')).map(|(name, _)| name.trim()) {
+            current = name.to_string();
+            current_plain = current.clone();
+        } else {
+            let in_table = table == current || table == current_plain;
+            let defines_key = trimmed
+                .split_once('=')
+                .is_some_and(|(name, _)| name.trim().trim_matches('"') == key);
+            if in_table && defines_key {
+                return Some(match value {
+                    Some(value) => find_value(&lines, i, value).unwrap_or((i + 1, line)),
+                    None => (i + 1, line),
+                });
+            }
+            continue;
+        }
+
+        if header.is_none() && (base == current || base == current_plain) {
+            header = Some((i + 1, *line));
+        }
+    }
+    header
+}
+
+/// Line of `value` in the array starting at `start`, before the next key or table
+fn find_value<'a>(lines: &[&'a str], start: usize, value: &str) -> Option<(usize, &'a str)> {
+    let quoted = [format!("\"{}\"", value), format!("'{}'", value)];
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let trimmed = line.trim_start();
+        let is_header = trimmed.starts_with('[') && !trimmed[1..].trim_start().starts_with(['"', '\'']);
+        let is_key = trimmed.split_once('=').is_some_and(|(name, _)| !name.contains(['"', '\'']));
+        if i > start && (is_header || is_key) {
+            break;
+        }
+        if quoted.iter().any(|q| line.contains(q.as_str())) {
+            return Some((i + 1, line));
+        }
+    }
+    None
+}
+
+/// `watchlist.cidrs[2]` -> `watchlist.cidrs`
+fn strip_index(path: &str) -> &str {
+    match path.strip_suffix(']').and_then(|rest| rest.rsplit_once('[')) {
+        Some((base, index)) if index.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => path,
+    }
+}
+
+/// Fields that may be given as `{ env = "VAR" }` or `{ file = "/run/secrets/x" }` instead of inline
+const SECRET_FIELDS: &[&str] = &[
+    "webhook.secret",
+    "heartbeat.url",
+    "sentry.dsn",
+    "api.token",
+    "grpc.token",
+    "database.url",
+    "redis.url",
+    "redis.token",
+    "platforms.hackerone.api_token",
+    "platforms.intigriti.api_token",
+    "enrichment.intel.api_key",
+    "enrichment.intel.api_secret",
+];
+
+/// Like `SECRET_FIELDS`, for a field of every table in an array of tables
+const SECRET_ARRAY_FIELDS: &[(&str, &str)] = &[("webhooks", "secret")];
+
+/// Replace secret references with the value they point to
+fn resolve_secrets(table: &mut toml::Table) -> anyhow::Result<()> {
+    for field in SECRET_FIELDS {
+        if let Some(value) = field_mut(table, field) {
+            resolve_secret(value, field)?;
+        }
+    }
+    for (array, field) in SECRET_ARRAY_FIELDS {
+        if let Some(toml::Value::Array(items)) = table.get_mut(*array) {
+            for (i, item) in items.iter_mut().enumerate() {
+                if let toml::Value::Table(item) = item
+                    && let Some(value) = item.get_mut(*field)
+                {
+                    resolve_secret(value, &format!("{}[{}].{}", array, i, field))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_secret(value: &mut toml::Value, field: &str) -> anyhow::Result<()> {
+    if let toml::Value::Table(reference) = value {
+        let secret = read_secret(reference).with_context(|| format!("Failed to resolve {}", field))?;
+        *value = toml::Value::String(secret);
+    }
+    Ok(())
+}
+
+fn field_mut<'a>(table: &'a mut toml::Table, path: &str) -> Option<&'a mut toml::Value> {
+    match path.split_once('.') {
+        Some((head, rest)) => match table.get_mut(head)? {
+            toml::Value::Table(child) => field_mut(child, rest),
+            _ => None,
+        },
+        None => table.get_mut(path),
+    }
+}
+
+fn read_secret(reference: &toml::Table) -> anyhow::Result<String> {
+    let source = (reference.len() == 1).then(|| reference.iter().next()).flatten();
+    match source {
+        Some((kind, toml::Value::String(name))) if kind == "env" => {
+            std::env::var(name).with_context(|| format!("environment variable {} is not set", name))
+        }
+        Some((kind, toml::Value::String(path))) if kind == "file" => {
+            let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+            // Secret files usually end with a newline that isn't part of the secret
+            Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+        }
+        _ => anyhow::bail!("expected {{ env = \"VAR\" }} or {{ file = \"/path\" }}"),
+    }
+}
+
+/// Environment variables starting with this override config keys; `__` separates sections
+const ENV_PREFIX: &str = "CT_SCOUT__";
+
+/// Recursively merge `layer` into `base`; non-table values in `layer` win
+fn merge_tables(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => merge_tables(existing, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// `CT_SCOUT__WEBHOOK__URL=x` -> (`CT_SCOUT__WEBHOOK__URL`, `webhook.url`, `x`), sorted by name
+fn env_overrides(env: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String, String)> {
+    let mut overrides: Vec<(String, String, String)> = env
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.split("__").collect::<Vec<_>>().join(".");
+            Some((name, key.to_lowercase(), value))
+        })
+        .collect();
+    overrides.sort();
+    overrides
+}
+
+/// Whether the config `schema` types the dotted `key` as a string
+/// Unknown keys count as strings, so their value is kept as given.
+fn schema_expects_string(schema: &serde_json::Value, key: &str) -> bool {
+    let mut node = schema;
+    for part in key.split('.') {
+        let field = schema_variants(schema, node)
+            .into_iter()
+            .find_map(|variant| variant.get("properties")?.get(part));
+        match field {
+            Some(field) => node = field,
+            None => return true,
+        }
+    }
+
+    let types: Vec<&str> = schema_variants(schema, node)
+        .into_iter()
+        .flat_map(|variant| match variant.get("type") {
+            Some(serde_json::Value::String(kind)) => vec![kind.as_str()],
+            Some(serde_json::Value::Array(kinds)) => kinds.iter().filter_map(|kind| kind.as_str()).collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    types.is_empty() || types.contains(&"string")
+}
+
+/// `node` with `$ref`s resolved against `root`, split into its `anyOf`/`oneOf` alternatives
+fn schema_variants<'a>(root: &'a serde_json::Value, node: &'a serde_json::Value) -> Vec<&'a serde_json::Value> {
+    let node = match node.get("$ref").and_then(|r| r.as_str()).and_then(|r| r.strip_prefix("#/")) {
+        Some(pointer) => match root.pointer(&format!("/{}", pointer)) {
+            Some(target) => target,
+            None => return Vec::new(),
+        },
+        None => node,
+    };
+    match node.get("anyOf").or_else(|| node.get("oneOf")).and_then(|v| v.as_array()) {
+        Some(alternatives) => alternatives.iter().flat_map(|alt| schema_variants(root, alt)).collect(),
+        None => vec![node],
     }
 }
 
+/// Apply one `dotted.key=value` override; the value is parsed as TOML, or taken as a string
+fn apply_override(table: &mut toml::Table, assignment: &str) -> anyhow::Result<()> {
+    let (key, raw) = assignment
+        .split_once('=')
+        .context("expected KEY=VALUE")?;
+    let path: Vec<&str> = key.trim().split('.').collect();
+    if path.iter().any(|part| part.is_empty()) {
+        anyhow::bail!("empty key segment");
+    }
+
+    // `poll_interval_secs=5` -> integer, `domains=["a.com"]` -> array, `url=https://..` -> string
+    let value = toml::from_str::<toml::Table>(&format!("value = {}", raw.trim()))
+        .ok()
+        .and_then(|mut parsed| parsed.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.trim().to_string()));
+
+    set_value(table, key, value)
+}
+
+/// Set the value at a dotted key, creating tables along the way
+fn set_value(table: &mut toml::Table, key: &str, value: toml::Value) -> anyhow::Result<()> {
+    let path: Vec<&str> = key.trim().split('.').collect();
+    let (last, parents) = path.split_last().expect("split yields at least one segment");
+    let mut current = table;
+    for part in parents {
+        current = match current
+            .entry(part.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            toml::Value::Table(child) => child,
+            _ => anyhow::bail!("'{}' is not a table", part),
+        };
+    }
+    current.insert(last.to_string(), value);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_secret_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("h1_token");
+        fs::write(&token_file, "h1-secret\n").unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            format!(
+                r#"
+[webhook]
+url = "https://hooks.example.com/x"
+secret = "inline"
+
+[platforms.hackerone]
+enabled = true
+username = "alice"
+api_token = {{ file = {:?} }}
+
+[logging]
+level = "info"
+
+[watchlist]
+domains = []
+hosts = []
+ips = []
+cidrs = []
+"#,
+                token_file.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.platforms.hackerone.unwrap().api_token, "h1-secret");
+        assert_eq!(config.webhook.unwrap().secret.as_deref(), Some("inline"));
+
+        let missing = ["redis.token={ env = \"CT_SCOUT_TEST_UNSET_SECRET\" }".to_string()];
+        let err = Config::load_layers(std::slice::from_ref(&path), [], &missing).unwrap_err();
+        assert!(format!("{:#}", err).contains("CT_SCOUT_TEST_UNSET_SECRET is not set"));
+
+        let malformed = ["webhook.secret={ vault = \"x\" }".to_string()];
+        assert!(Config::load_layers(&[path], [], &malformed).is_err());
+    }
+
+    #[test]
+    fn test_multiple_webhooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret_file = dir.path().join("pager_secret");
+        fs::write(&secret_file, "s3cret\n").unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            format!(
+                r#"
+[webhook]
+name = "all"
+url = "https://hooks.example.com/all"
+
+[[webhooks]]
+name = "pager"
+url = "https://pager.example.com/x"
+secret = {{ file = {:?} }}
+severity = ">=high"
+
+[[webhooks]]
+name = "acme"
+url = "https://hooks.example.com/acme"
+program = "acme*"
+
+[logging]
+level = "info"
+
+[watchlist]
+domains = []
+hosts = []
+ips = []
+cidrs = []
+"#,
+                secret_file.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+
+        let config = Config::from_file(&path).unwrap();
+        let names: Vec<_> = config.all_webhooks().map(|webhook| webhook.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["all", "pager", "acme"]);
+        assert_eq!(config.webhooks[0].secret.as_deref(), Some("s3cret"));
+        assert_eq!(config.webhooks[1].program.as_deref(), Some("acme*"));
+
+        let unnamed = ["webhooks=[{ url = \"https://hooks.example.com/y\" }]".to_string()];
+        let err = Config::load_layers(std::slice::from_ref(&path), [], &unnamed).unwrap_err().to_string();
+        assert!(err.contains("webhooks[0]: needs a name when more than one webhook is configured"));
+
+        let duplicate = ["webhooks=[{ name = \"all\", url = \"https://hooks.example.com/y\" }]".to_string()];
+        let err = Config::load_layers(&[path], [], &duplicate).unwrap_err().to_string();
+        assert!(err.contains("webhooks[0].name: 'all' is used by another webhook"));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[ct_logs]
+batch_size = 256
+
+[logging]
+level = "info"
+
+[watchlist]
+domains = []
+hosts = []
+ips = []
+cidrs = []
+"#,
+        )
+        .unwrap();
+
+        let env = [
+            ("CT_SCOUT__WEBHOOK__URL", "https://hooks.example.com/x?a=b"),
+            ("CT_SCOUT__CT_LOGS__BATCH_SIZE", "1024"),
+            ("CT_SCOUT__LOGGING__LEVEL", "warn"),
+            ("CT_SCOUT__WEBHOOK__SECRET", "123456"),
+            ("CT_SCOUT__REDIS__TOKEN", "true"),
+            ("CT_SCOUT__REDIS__ENABLED", "true"),
+            ("CT_SCOUT__WATCHLIST__DOMAINS", "[\"*.example.com\"]"),
+            ("CT_SCOUT_UNRELATED", "ignored"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let config =
+            Config::load_layers(std::slice::from_ref(&path), env, &["logging.level=debug".to_string()]).unwrap();
+
+        let webhook = config.webhook.unwrap();
+        assert_eq!(webhook.url, "https://hooks.example.com/x?a=b");
+        assert_eq!(config.ct_logs.batch_size, 1024);
+        // Values that look like numbers or booleans stay strings where strings are expected
+        assert_eq!(webhook.secret.as_deref(), Some("123456"));
+        assert_eq!(config.redis.token.as_deref(), Some("true"));
+        assert!(config.redis.enabled);
+        assert_eq!(config.watchlist.domains, vec!["*.example.com"]);
+        // --set wins over the environment
+        assert_eq!(config.logging.level, "debug");
+
+        let bad = [("CT_SCOUT__CT_LOGS__BATCH_SIZE".to_string(), "lots".to_string())];
+        assert!(Config::load_layers(&[path], bad, &[]).is_err());
+    }
+
+    #[test]
+    fn test_layered_config_with_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.toml");
+        let prod = dir.path().join("prod.toml");
+        fs::write(
+            &base,
+            r#"
+[ct_logs]
+poll_interval_secs = 10
+batch_size = 512
+
+[logging]
+level = "info"
+
+[watchlist]
+domains = ["*.example.com"]
+hosts = []
+ips = []
+cidrs = []
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &prod,
+            r#"
+[ct_logs]
+poll_interval_secs = 30
+
+[watchlist]
+domains = ["*.prod.example.com"]
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load(
+            &[base.clone(), prod],
+            &[
+                "ct_logs.batch_size=64".to_string(),
+                "webhook.url=https://hooks.example.com/x".to_string(),
+                "logging.level=\"debug\"".to_string(),
+            ],
+        )
+        .unwrap();
+
+        // Tables merge key by key; arrays are replaced wholesale
+        assert_eq!(config.ct_logs.poll_interval_secs, 30);
+        assert_eq!(config.ct_logs.batch_size, 64);
+        assert_eq!(config.watchlist.domains, vec!["*.prod.example.com"]);
+        assert_eq!(config.webhook.unwrap().url, "https://hooks.example.com/x");
+        assert_eq!(config.logging.level, "debug");
+
+        assert!(Config::load(std::slice::from_ref(&base), &["ct_logs.batch_size".to_string()]).is_err());
+        assert!(Config::load(std::slice::from_ref(&base), &["logging.level.x=1".to_string()]).is_err());
+        assert!(Config::load(&[base], &["ct_logs.batch_size=lots".to_string()]).is_err());
+    }
+
     #[test]
     fn test_config_from_valid_toml() {
         let toml_content = r#"
@@ -487,6 +1901,60 @@ domains = []
         assert!(result.is_err());  // Missing logging section
     }
 
+    #[test]
+    fn test_config_errors_point_at_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r#"
+[logging]
+level = "info"
+
+[watchlist]
+domains = ["*.example.com", "bad domain.com"]
+hosts = []
+ips = []
+cidrs = [
+    "10.0.0.0/8",
+    "192.168.0.0/16",
+    "invalid_cidr",
+]
+
+[[programs]]
+name = "A"
+
+[[programs]]
+name = "B"
+ips = ["10.0.0.256"]
+"#,
+        )
+        .unwrap();
+
+        let err = Config::from_file(&path).unwrap_err().to_string();
+        assert!(err.contains("watchlist.domains[1]: 'bad domain.com' is not a valid domain pattern"));
+        assert!(err.contains("watchlist.cidrs[2]: 'invalid_cidr' is not a valid CIDR"));
+        assert!(err.contains(&format!("--> {}:12\n   | \"invalid_cidr\",", path.display())));
+        assert!(err.contains("programs[1].ips[0]: '10.0.0.256' is not a valid IP address"));
+        assert!(err.contains(&format!("--> {}:20", path.display())));
+
+        let wrong_type = ["ct_logs.batch_size=\"lots\"".to_string()];
+        let err = Config::load_layers(std::slice::from_ref(&path), [], &wrong_type).unwrap_err().to_string();
+        assert!(err.starts_with("ct_logs.batch_size: invalid type: string \"lots\""));
+
+        let bad_url = ["webhook.url=hooks.example.com/x".to_string(), "watchlist.domains=[]".to_string(),
+            "watchlist.cidrs=[]".to_string(), "programs=[]".to_string()];
+        let err = Config::load_layers(std::slice::from_ref(&path), [], &bad_url).unwrap_err().to_string();
+        assert!(err.starts_with("webhook.url: 'hooks.example.com/x' is not a valid URL"));
+
+        let weak_tokens = ["api={listen=\"127.0.0.1:8080\", token=\"\"}".to_string(),
+            "grpc={listen=\"127.0.0.1:50051\", token=\"secret\"}".to_string(), "watchlist.domains=[]".to_string(),
+            "watchlist.cidrs=[]".to_string(), "programs=[]".to_string()];
+        let err = Config::load_layers(&[path], [], &weak_tokens).unwrap_err().to_string();
+        assert!(err.contains("api.token: must be at least 16 characters"));
+        assert!(err.contains("grpc.token: must be at least 16 characters"));
+    }
+
     #[test]
     fn test_config_nonexistent_file() {
         let result = Config::from_file(Path::new("/nonexistent/path/config.toml"));
@@ -0,0 +1,326 @@
+// src/ct_log/audit.rs
+use anyhow::{Context, Result};
+use base64::Engine;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use super::log_client::LogClient;
+use super::merkle::{self, Hash};
+use super::types::{LogEntry, SignedTreeHead};
+
+/// Settings for periodic Merkle proof auditing
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    /// Minimum time between audits of the same log
+    pub interval_secs: u64,
+    /// Number of entries per audit to check with inclusion proofs
+    pub inclusion_samples: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            inclusion_samples: 2,
+        }
+    }
+}
+
+/// Which cryptographic check a log failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditCheck {
+    Consistency,
+    Inclusion,
+}
+
+impl AuditCheck {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditCheck::Consistency => "consistency",
+            AuditCheck::Inclusion => "inclusion",
+        }
+    }
+}
+
+/// Error returned when a log's proof does not verify
+#[derive(Debug)]
+pub struct AuditFailure {
+    pub check: AuditCheck,
+    pub detail: String,
+}
+
+impl fmt::Display for AuditFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} proof verification failed: {}", self.check.as_str(), self.detail)
+    }
+}
+
+impl std::error::Error for AuditFailure {}
+
+struct AuditState {
+    /// Last STH that passed verification
+    last_sth: Option<SignedTreeHead>,
+    last_audit: Option<Instant>,
+}
+
+/// Verifies that a single log behaves as an append-only Merkle tree
+pub struct MerkleAuditor {
+    log_url: String,
+    config: AuditConfig,
+    state: Mutex<AuditState>,
+}
+
+impl MerkleAuditor {
+    pub fn new(log_url: String, config: AuditConfig) -> Self {
+        Self {
+            log_url,
+            config,
+            state: Mutex::new(AuditState {
+                last_sth: None,
+                last_audit: None,
+            }),
+        }
+    }
+
+    /// Whether enough time has passed since the last audit
+    pub async fn is_due(&self) -> bool {
+        let state = self.state.lock().await;
+        state
+            .last_audit
+            .is_none_or(|at| at.elapsed() >= Duration::from_secs(self.config.interval_secs))
+    }
+
+    /// Audit the log against a freshly fetched STH
+    ///
+    /// Checks consistency with the previously audited STH, then requests inclusion
+    /// proofs for a sample of `entries` (which start at `first_index`).
+    /// Returns an `AuditFailure` error if any proof does not verify.
+    pub async fn audit(
+        &self,
+        client: &dyn LogClient,
+        sth: &SignedTreeHead,
+        first_index: u64,
+        entries: &[LogEntry],
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.last_audit = Some(Instant::now());
+
+        let root = decode_root(sth)?;
+
+        if let Some(previous) = state.last_sth.clone() {
+            // Frontends may lag behind each other, so order the pair by size
+            let (older, newer) = if previous.tree_size <= sth.tree_size {
+                (&previous, sth)
+            } else {
+                (sth, &previous)
+            };
+
+            self.check_consistency(client, older, newer).await?;
+        }
+
+        for (offset, entry) in sample(entries, self.config.inclusion_samples) {
+            let index = first_index + offset as u64;
+            self.check_inclusion(client, entry, index, sth.tree_size, &root)
+                .await?;
+        }
+
+        if state
+            .last_sth
+            .as_ref()
+            .is_none_or(|previous| previous.tree_size <= sth.tree_size)
+        {
+            state.last_sth = Some(sth.clone());
+        }
+
+        info!(
+            "{}: Audit passed (tree_size={})",
+            self.log_url, sth.tree_size
+        );
+
+        Ok(())
+    }
+
+    async fn check_consistency(
+        &self,
+        client: &dyn LogClient,
+        older: &SignedTreeHead,
+        newer: &SignedTreeHead,
+    ) -> Result<()> {
+        let older_root = decode_root(older)?;
+        let newer_root = decode_root(newer)?;
+
+        let proof = if older.tree_size == newer.tree_size || older.tree_size == 0 {
+            Vec::new()
+        } else {
+            client
+                .get_sth_consistency(older.tree_size, newer.tree_size)
+                .await?
+        };
+
+        debug!(
+            "{}: Verifying consistency {} -> {} ({} nodes)",
+            self.log_url,
+            older.tree_size,
+            newer.tree_size,
+            proof.len()
+        );
+
+        if !merkle::verify_consistency(
+            older.tree_size,
+            newer.tree_size,
+            &older_root,
+            &newer_root,
+            &proof,
+        ) {
+            return Err(AuditFailure {
+                check: AuditCheck::Consistency,
+                detail: format!(
+                    "tree of size {} is not an extension of size {}",
+                    newer.tree_size, older.tree_size
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    async fn check_inclusion(
+        &self,
+        client: &dyn LogClient,
+        entry: &LogEntry,
+        index: u64,
+        tree_size: u64,
+        root: &Hash,
+    ) -> Result<()> {
+        let leaf_input = base64::engine::general_purpose::STANDARD
+            .decode(&entry.leaf_input)
+            .context("Invalid base64 in leaf_input")?;
+        let leaf = merkle::leaf_hash(&leaf_input);
+
+        let (leaf_index, proof) = client.get_proof_by_hash(&leaf, tree_size).await?;
+
+        debug!(
+            "{}: Verifying inclusion of entry {} in tree of {}",
+            self.log_url, index, tree_size
+        );
+
+        if leaf_index != index || !merkle::verify_inclusion(&leaf, index, tree_size, &proof, root) {
+            return Err(AuditFailure {
+                check: AuditCheck::Inclusion,
+                detail: format!(
+                    "entry {} not proven in tree of size {} (log reported index {})",
+                    index, tree_size, leaf_index
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Decode the base64 root hash of an STH
+fn decode_root(sth: &SignedTreeHead) -> Result<Hash> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&sth.sha256_root_hash)
+        .context("Invalid base64 in STH root hash")?;
+
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("STH root hash is not 32 bytes"))
+}
+
+/// Pick up to `count` entries spread evenly across the batch
+fn sample(entries: &[LogEntry], count: usize) -> Vec<(usize, &LogEntry)> {
+    let count = count.min(entries.len());
+    (0..count)
+        .map(|i| {
+            let offset = i * entries.len() / count;
+            (offset, &entries[offset])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct_log::client::CtLogClient;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn b64(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    fn sth(tree_size: u64, root: &Hash) -> SignedTreeHead {
+        SignedTreeHead {
+            tree_size,
+            timestamp: 0,
+            sha256_root_hash: b64(root),
+            tree_head_signature: String::new(),
+        }
+    }
+
+    fn entry(leaf_input: &[u8]) -> LogEntry {
+        LogEntry {
+            leaf_input: b64(leaf_input),
+            extra_data: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_sample_spreads_entries() {
+        let entries: Vec<LogEntry> = (0..10u8).map(|i| entry(&[i])).collect();
+        let offsets: Vec<usize> = sample(&entries, 3).into_iter().map(|(o, _)| o).collect();
+        assert_eq!(offsets, vec![0, 3, 6]);
+        assert_eq!(sample(&entries[..1], 3).len(), 1);
+        assert!(sample(&entries, 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_audit_two_leaf_tree() {
+        let mock_server = MockServer::start().await;
+
+        let a = merkle::leaf_hash(b"a");
+        let b = merkle::leaf_hash(b"b");
+        let root = merkle::node_hash(&a, &b);
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth-consistency"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "consistency": [b64(&b)]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-proof-by-hash"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "leaf_index": 1,
+                "audit_path": [b64(&a)]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = CtLogClient::new(mock_server.uri()).unwrap();
+        let auditor = MerkleAuditor::new(mock_server.uri(), AuditConfig::default());
+
+        assert!(auditor.is_due().await);
+        auditor.audit(&client, &sth(1, &a), 0, &[]).await.unwrap();
+        assert!(!auditor.is_due().await);
+
+        auditor
+            .audit(&client, &sth(2, &root), 1, &[entry(b"b")])
+            .await
+            .unwrap();
+
+        // A root that doesn't match the proof is reported as a consistency failure
+        let forged = merkle::node_hash(&a, &merkle::leaf_hash(b"evil"));
+        let err = auditor.audit(&client, &sth(2, &forged), 1, &[]).await.unwrap_err();
+        let failure = err.downcast_ref::<AuditFailure>().unwrap();
+        assert_eq!(failure.check, AuditCheck::Consistency);
+    }
+}
@@ -0,0 +1,305 @@
+// src/ct_log/checkpoint.rs
+//! Saved indices that only advance past fully handled entries
+//!
+//! Monitors fetch ahead of the coordinator, so the position they fetch from
+//! is kept here in memory while the saved index trails behind it: an entry
+//! only counts once the coordinator has matched it and the outputs have sent
+//! or spooled any match (or it was dropped by policy or skipped unparsed).
+//! Spilled entries count once they have been replayed from the spill file.
+//! A crash therefore re-processes entries instead of losing them.
+//!
+//! Recovered gap entries are tracked the same way: a gap only shrinks once
+//! every entry re-fetched from it has been handled.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::state::StateBackend;
+
+/// Per-log progress between the fetch position and the saved index
+#[derive(Debug)]
+struct LogCheckpoint {
+    /// Sent to the coordinator and not handled yet
+    pending: BTreeSet<u64>,
+    /// Every entry below this has been sent or skipped
+    sent_to: u64,
+    /// Index last written to the state backend
+    committed: u64,
+    /// Gap chunk being re-fetched, at most one at a time
+    recovery: Option<GapRecovery>,
+}
+
+/// Entries `gap.0..=recovered_end` re-fetched from a recorded gap
+#[derive(Debug)]
+struct GapRecovery {
+    gap: (u64, u64),
+    recovered_end: u64,
+    /// Sent to the coordinator and not handled yet
+    pending: BTreeSet<u64>,
+    /// Every recovered entry has been sent or skipped
+    sent: bool,
+}
+
+impl LogCheckpoint {
+    fn at(index: u64) -> Self {
+        Self { pending: BTreeSet::new(), sent_to: index, committed: index, recovery: None }
+    }
+
+    /// First entry that is not handled yet
+    fn handled_to(&self) -> u64 {
+        self.pending.first().copied().unwrap_or(self.sent_to)
+    }
+}
+
+/// Checkpoints of every monitored log, shared by the monitors and the coordinator
+pub struct Checkpoints {
+    state: Arc<dyn StateBackend>,
+    /// Handled entries per saved-index update
+    batch: u64,
+    logs: Mutex<HashMap<String, LogCheckpoint>>,
+}
+
+impl Checkpoints {
+    pub fn new(state: Arc<dyn StateBackend>, batch: u64) -> Self {
+        Self {
+            state,
+            batch: batch.max(1),
+            logs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Next entry to fetch, starting from the saved index the first time
+    pub async fn position(&self, log_url: &str) -> u64 {
+        let mut logs = self.logs.lock().await;
+        if let Some(log) = logs.get(log_url) {
+            return log.sent_to;
+        }
+        let index = self.state.get_last_index(log_url).await.unwrap_or(0);
+        logs.insert(log_url.to_string(), LogCheckpoint::at(index));
+        index
+    }
+
+    /// Move the log to `index`, saving it right away
+    pub async fn reset(&self, log_url: &str, index: u64) {
+        let mut logs = self.logs.lock().await;
+        logs.insert(log_url.to_string(), LogCheckpoint::at(index));
+        self.state.update_index(log_url, index).await;
+    }
+
+    /// Entry `index` is about to be sent to the coordinator
+    pub async fn sent(&self, log_url: &str, index: u64) {
+        if let Some(log) = self.logs.lock().await.get_mut(log_url) {
+            log.pending.insert(index);
+        }
+    }
+
+    /// Every entry below `index` has been sent or skipped
+    pub async fn advance(&self, log_url: &str, index: u64) {
+        let mut logs = self.logs.lock().await;
+        if let Some(log) = logs.get_mut(log_url) {
+            log.sent_to = log.sent_to.max(index);
+            self.commit(log_url, log, false).await;
+        }
+    }
+
+    /// The coordinator is done with entry `index`
+    /// Entries that were never sent through `sent` or `sent_recovered`
+    /// (scans, other inputs) are ignored.
+    pub async fn done(&self, log_url: &str, index: u64) {
+        let mut logs = self.logs.lock().await;
+        let Some(log) = logs.get_mut(log_url) else {
+            return;
+        };
+        if log.pending.remove(&index) {
+            self.commit(log_url, log, false).await;
+        } else if log.recovery.as_mut().is_some_and(|recovery| recovery.pending.remove(&index)) {
+            self.finish_recovery(log_url, log).await;
+        }
+    }
+
+    /// Whether a recovered gap chunk of the log is still being handled
+    pub async fn recovering(&self, log_url: &str) -> bool {
+        let logs = self.logs.lock().await;
+        logs.get(log_url).is_some_and(|log| log.recovery.is_some())
+    }
+
+    /// Entries `gap.0..=recovered_end` of the recorded `gap` are about to be re-sent
+    pub async fn start_recovery(&self, log_url: &str, gap: (u64, u64), recovered_end: u64) {
+        if let Some(log) = self.logs.lock().await.get_mut(log_url) {
+            log.recovery = Some(GapRecovery { gap, recovered_end, pending: BTreeSet::new(), sent: false });
+        }
+    }
+
+    /// Recovered entry `index` is about to be sent to the coordinator
+    pub async fn sent_recovered(&self, log_url: &str, index: u64) {
+        if let Some(recovery) = self.logs.lock().await.get_mut(log_url).and_then(|log| log.recovery.as_mut()) {
+            recovery.pending.insert(index);
+        }
+    }
+
+    /// Every recovered entry has been sent or skipped
+    pub async fn recovery_sent(&self, log_url: &str) {
+        let mut logs = self.logs.lock().await;
+        if let Some(log) = logs.get_mut(log_url)
+            && let Some(ref mut recovery) = log.recovery
+        {
+            recovery.sent = true;
+            self.finish_recovery(log_url, log).await;
+        }
+    }
+
+    /// Shrink the gap by the recovered chunk once all of it is handled
+    async fn finish_recovery(&self, log_url: &str, log: &mut LogCheckpoint) {
+        if !log.recovery.as_ref().is_some_and(|recovery| recovery.sent && recovery.pending.is_empty()) {
+            return;
+        }
+        let Some(GapRecovery { gap: (start, end), recovered_end, .. }) = log.recovery.take() else {
+            return;
+        };
+        if recovered_end < end {
+            self.state.add_gap(log_url, recovered_end + 1, end).await;
+        }
+        self.state.remove_gap(log_url, start, end).await;
+    }
+
+    /// Save the log's handled position and drop it from memory
+    ///
+    /// For retired monitors: if the log comes back (list refresh, cluster
+    /// lease), it starts again from the saved index rather than from a
+    /// position another instance may have moved past in the meantime.
+    pub async fn forget(&self, log_url: &str) {
+        let mut logs = self.logs.lock().await;
+        if let Some(mut log) = logs.remove(log_url) {
+            self.commit(log_url, &mut log, true).await;
+        }
+    }
+
+    /// Save every log's handled position, however few entries it gained
+    pub async fn flush(&self) {
+        let mut logs = self.logs.lock().await;
+        for (log_url, log) in logs.iter_mut() {
+            self.commit(log_url, log, true).await;
+        }
+    }
+
+    /// Save the handled position once a batch has built up, or whenever
+    /// everything sent so far has been handled
+    async fn commit(&self, log_url: &str, log: &mut LogCheckpoint, force: bool) {
+        let handled_to = log.handled_to();
+        if handled_to <= log.committed {
+            return;
+        }
+        if force || log.pending.is_empty() || handled_to - log.committed >= self.batch {
+            // Never move a saved index back, e.g. one another instance advanced
+            let stored = self.state.get_last_index(log_url).await.unwrap_or(0);
+            if handled_to > stored {
+                self.state.update_index(log_url, handled_to).await;
+            }
+            log.committed = handled_to.max(stored);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StateManager;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_index_trails_handled_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        state.update_index("log", 10).await;
+        let checkpoints = Checkpoints::new(state.clone(), 2);
+
+        assert_eq!(checkpoints.position("log").await, 10);
+        for index in 10..13 {
+            checkpoints.sent("log", index).await;
+        }
+        // Entry 13 failed to parse
+        checkpoints.advance("log", 14).await;
+        assert_eq!(checkpoints.position("log").await, 14);
+        assert_eq!(state.get_last_index("log").await, Some(10));
+
+        // Entry 10 is still in flight, so nothing moves
+        checkpoints.done("log", 11).await;
+        assert_eq!(state.get_last_index("log").await, Some(10));
+
+        // A full batch is handled
+        checkpoints.done("log", 10).await;
+        assert_eq!(state.get_last_index("log").await, Some(12));
+
+        // Caught up: commit everything, including the skipped entry
+        checkpoints.done("log", 12).await;
+        assert_eq!(state.get_last_index("log").await, Some(14));
+
+        // Unknown logs and entries are ignored
+        checkpoints.done("other", 1).await;
+        checkpoints.done("log", 3).await;
+        assert_eq!(state.get_last_index("other").await, None);
+        assert_eq!(state.get_last_index("log").await, Some(14));
+
+        checkpoints.sent("log", 14).await;
+        checkpoints.advance("log", 15).await;
+        checkpoints.flush().await;
+        assert_eq!(state.get_last_index("log").await, Some(14));
+    }
+
+    #[tokio::test]
+    async fn test_forget_resumes_from_saved_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        let checkpoints = Checkpoints::new(state.clone(), 100);
+
+        assert_eq!(checkpoints.position("log").await, 0);
+        checkpoints.sent("log", 0).await;
+        checkpoints.sent("log", 1).await;
+        checkpoints.advance("log", 5).await;
+        checkpoints.done("log", 0).await;
+        checkpoints.forget("log").await;
+        assert_eq!(state.get_last_index("log").await, Some(1));
+
+        // Another instance moved the log on while it wasn't ours
+        state.update_index("log", 50).await;
+        assert_eq!(checkpoints.position("log").await, 50);
+
+        // A stale commit never moves the saved index back
+        let stale = Checkpoints::new(state.clone(), 1);
+        state.update_index("other", 10).await;
+        assert_eq!(stale.position("other").await, 10);
+        state.update_index("other", 40).await;
+        stale.advance("other", 20).await;
+        assert_eq!(state.get_last_index("other").await, Some(40));
+    }
+
+    #[tokio::test]
+    async fn test_gap_shrinks_once_recovered_entries_are_handled() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        state.update_index("log", 100).await;
+        state.add_gap("log", 10, 29).await;
+        let checkpoints = Checkpoints::new(state.clone(), 1);
+        assert_eq!(checkpoints.position("log").await, 100);
+
+        // Entries 10-19 recovered; 12 failed to parse
+        checkpoints.start_recovery("log", (10, 29), 19).await;
+        for index in (10..20).filter(|&index| index != 12) {
+            checkpoints.sent_recovered("log", index).await;
+        }
+        checkpoints.recovery_sent("log").await;
+        assert!(checkpoints.recovering("log").await);
+
+        // Delivery still pending: the gap stays as it was
+        for index in (10..19).filter(|&index| index != 12) {
+            checkpoints.done("log", index).await;
+        }
+        assert_eq!(state.get_gaps("log").await, vec![(10, 29)]);
+
+        checkpoints.done("log", 19).await;
+        assert_eq!(state.get_gaps("log").await, vec![(20, 29)]);
+        assert!(!checkpoints.recovering("log").await);
+        assert_eq!(state.get_last_index("log").await, Some(100));
+    }
+}
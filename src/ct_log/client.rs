@@ -1,30 +1,144 @@
 // src/ct_log/client.rs
 use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::SemaphorePermit;
 use tracing::{debug, warn};
 
-use super::types::{GetEntriesResponse, LogEntry, SignedTreeHead};
+use super::rate_limit::{RateLimiter, RequestBudget};
+use super::types::{
+    ConsistencyProofResponse, GetEntriesResponse, InclusionProofResponse, LogEntry,
+    SignedTreeHead,
+};
+use crate::metrics;
+
+/// Longest Retry-After we are willing to honor before falling back to normal backoff
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+/// Longest delay between retries
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often and how patiently a failed log request is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after each further failure
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A single attempt, for one-off lookups that shouldn't wait on a failing log
+    pub fn once() -> Self {
+        Self {
+            max_retries: 1,
+            ..Default::default()
+        }
+    }
+}
+
+/// Retry settings for CT log requests
+#[derive(Debug, Clone, Default)]
+pub struct RetryConfig {
+    /// Policy for logs without an override
+    pub default: RetryPolicy,
+    /// Per-log overrides keyed by log URL
+    pub per_log: HashMap<String, RetryPolicy>,
+}
+
+impl RetryConfig {
+    /// Policy for a specific log, ignoring trailing slashes in either URL
+    pub fn policy_for(&self, log_url: &str) -> RetryPolicy {
+        let wanted = log_url.trim_end_matches('/');
+        self.per_log
+            .iter()
+            .find(|(url, _)| url.trim_end_matches('/') == wanted)
+            .map_or(self.default, |(_, policy)| *policy)
+    }
+}
+
+/// Error returned when a CT log responds with 429 Too Many Requests
+#[derive(Debug)]
+pub struct RateLimitedError {
+    /// Delay requested by the log via the Retry-After header
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for RateLimitedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.retry_after {
+            Some(delay) => write!(f, "Rate limited (429), retry after {:?}", delay),
+            None => write!(f, "Rate limited (429)"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimitedError {}
 
 /// HTTP client for Certificate Transparency log RFC 6962 API
 pub struct CtLogClient {
     base_url: String,
     http_client: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_budget: Option<RequestBudget>,
 }
 
 impl CtLogClient {
     /// Create a new CT log client
     pub fn new(base_url: String) -> Result<Self> {
         let http_client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
+            .timeout(super::http::DEFAULT_TIMEOUT)
             .gzip(true)  // Enable compression
             // Don't force HTTP/2 - let reqwest negotiate automatically
             .build()
             .context("Failed to build HTTP client")?;
 
-        Ok(Self {
+        Ok(Self::with_http_client(base_url, http_client))
+    }
+
+    /// Create a client that sends requests through an existing (possibly shared) HTTP client
+    pub fn with_http_client(base_url: String, http_client: reqwest::Client) -> Self {
+        Self {
             base_url,
             http_client,
-        })
+            rate_limiter: None,
+            request_budget: None,
+        }
+    }
+
+    /// Limit requests to this log with a token bucket
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Share a global in-flight request budget with other clients
+    pub fn with_request_budget(mut self, budget: RequestBudget) -> Self {
+        self.request_budget = Some(budget);
+        self
+    }
+
+    /// Wait for the rate limiter and a request slot (if configured) before sending
+    /// The returned permit must be held until the response body has been read
+    async fn throttle(&self) -> Option<SemaphorePermit<'_>> {
+        throttle(self.rate_limiter.as_deref(), self.request_budget.as_ref()).await
+    }
+
+    /// Convert a 429 response into a RateLimitedError, pausing the limiter if needed
+    async fn rate_limited(&self, response: &reqwest::Response) -> anyhow::Error {
+        rate_limited_error(&self.base_url, self.rate_limiter.as_deref(), response).await
     }
 
     /// Get Signed Tree Head (current log size and timestamp)
@@ -34,14 +148,22 @@ impl CtLogClient {
 
         debug!("Fetching STH from {}", url);
 
+        let _permit = self.throttle().await;
+
         let response = self
             .http_client
             .get(&url)
             .send()
             .await
+            .inspect_err(|_| count_http_error(&self.base_url, "network"))
             .context("Failed to fetch STH")?;
 
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response).await);
+        }
+
         if !response.status().is_success() {
+            count_http_error(&self.base_url, response.status().as_str());
             anyhow::bail!(
                 "STH request failed with status {}: {}",
                 response.status(),
@@ -72,23 +194,26 @@ impl CtLogClient {
 
         debug!("Fetching entries {}-{} from {}", start, end, self.base_url);
 
+        let _permit = self.throttle().await;
+
         let response = self
             .http_client
             .get(&url)
             .send()
             .await
+            .inspect_err(|_| count_http_error(&self.base_url, "network"))
             .context("Failed to fetch entries")?;
 
+        // Handle rate limiting specifically
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response).await);
+        }
+
         if !response.status().is_success() {
+            count_http_error(&self.base_url, response.status().as_str());
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
 
-            // Handle rate limiting specifically
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                warn!("Rate limited by CT log: {}", self.base_url);
-                anyhow::bail!("Rate limited (429)");
-            }
-
             anyhow::bail!(
                 "Get entries request failed with status {}: {}",
                 status,
@@ -110,70 +235,356 @@ impl CtLogClient {
         Ok(entries_response.entries)
     }
 
+    /// Get a consistency proof between two tree sizes
+    /// Endpoint: GET {base_url}/ct/v1/get-sth-consistency?first={first}&second={second}
+    pub async fn get_sth_consistency(&self, first: u64, second: u64) -> Result<Vec<Vec<u8>>> {
+        let url = format!(
+            "{}/ct/v1/get-sth-consistency?first={}&second={}",
+            self.base_url, first, second
+        );
+
+        debug!("Fetching consistency proof {}->{} from {}", first, second, self.base_url);
+
+        let _permit = self.throttle().await;
+
+        let response = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .inspect_err(|_| count_http_error(&self.base_url, "network"))
+            .context("Failed to fetch consistency proof")?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response).await);
+        }
+
+        if !response.status().is_success() {
+            count_http_error(&self.base_url, response.status().as_str());
+            anyhow::bail!(
+                "Consistency proof request failed with status {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+
+        let proof: ConsistencyProofResponse = response
+            .json()
+            .await
+            .context("Failed to parse consistency proof JSON")?;
+
+        decode_proof(&proof.consistency)
+    }
+
+    /// Get an inclusion proof for a leaf hash in the tree of `tree_size`
+    /// Endpoint: GET {base_url}/ct/v1/get-proof-by-hash?hash={hash}&tree_size={tree_size}
+    /// Returns the leaf index and audit path
+    pub async fn get_proof_by_hash(
+        &self,
+        leaf_hash: &[u8],
+        tree_size: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>)> {
+        use base64::Engine;
+
+        let url = format!("{}/ct/v1/get-proof-by-hash", self.base_url);
+        let hash = base64::engine::general_purpose::STANDARD.encode(leaf_hash);
+
+        debug!("Fetching inclusion proof (tree_size={}) from {}", tree_size, self.base_url);
+
+        let _permit = self.throttle().await;
+
+        let response = self
+            .http_client
+            .get(&url)
+            .query(&[("hash", hash), ("tree_size", tree_size.to_string())])
+            .send()
+            .await
+            .inspect_err(|_| count_http_error(&self.base_url, "network"))
+            .context("Failed to fetch inclusion proof")?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response).await);
+        }
+
+        if !response.status().is_success() {
+            count_http_error(&self.base_url, response.status().as_str());
+            anyhow::bail!(
+                "Inclusion proof request failed with status {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+
+        let proof: InclusionProofResponse = response
+            .json()
+            .await
+            .context("Failed to parse inclusion proof JSON")?;
+
+        Ok((proof.leaf_index, decode_proof(&proof.audit_path)?))
+    }
+
     /// Get entries with retry logic and exponential backoff
     pub async fn get_entries_with_retry(
         &self,
         start: u64,
         end: u64,
-        max_retries: u32,
+        retry: RetryPolicy,
     ) -> Result<Vec<LogEntry>> {
-        let mut retries = 0;
-        let mut backoff = Duration::from_secs(1);
-
-        loop {
-            match self.get_entries(start, end).await {
-                Ok(entries) => return Ok(entries),
-                Err(e) => {
-                    retries += 1;
-
-                    if retries >= max_retries {
-                        return Err(e.context(format!(
-                            "Failed after {} retries",
-                            max_retries
-                        )));
-                    }
-
-                    warn!(
-                        "Error fetching entries (attempt {}/{}): {}. Retrying in {:?}",
-                        retries, max_retries, e, backoff
-                    );
-
-                    tokio::time::sleep(backoff).await;
-
-                    // Exponential backoff with max 60 seconds
-                    backoff = std::cmp::min(backoff * 2, Duration::from_secs(60));
-                }
-            }
-        }
+        with_retry("entries", retry, || self.get_entries(start, end)).await
     }
 
     /// Get STH with retry logic
-    pub async fn get_sth_with_retry(&self, max_retries: u32) -> Result<SignedTreeHead> {
-        let mut retries = 0;
-        let mut backoff = Duration::from_secs(1);
-
-        loop {
-            match self.get_sth().await {
-                Ok(sth) => return Ok(sth),
-                Err(e) => {
-                    retries += 1;
-
-                    if retries >= max_retries {
-                        return Err(e.context(format!(
-                            "Failed after {} retries",
-                            max_retries
-                        )));
-                    }
-
-                    warn!(
-                        "Error fetching STH (attempt {}/{}): {}. Retrying in {:?}",
-                        retries, max_retries, e, backoff
-                    );
-
-                    tokio::time::sleep(backoff).await;
-                    backoff = std::cmp::min(backoff * 2, Duration::from_secs(60));
+    pub async fn get_sth_with_retry(&self, retry: RetryPolicy) -> Result<SignedTreeHead> {
+        with_retry("STH", retry, || self.get_sth()).await
+    }
+}
+
+/// Wait for a rate limiter token, then a slot in the global request budget
+/// Tokens come first so a request waiting on its log's limit doesn't hold a slot
+pub(crate) async fn throttle<'a>(
+    limiter: Option<&RateLimiter>,
+    budget: Option<&'a RequestBudget>,
+) -> Option<SemaphorePermit<'a>> {
+    if let Some(limiter) = limiter {
+        limiter.acquire().await;
+    }
+
+    match budget {
+        Some(budget) => Some(budget.acquire().await),
+        None => None,
+    }
+}
+
+/// Count a failed request to a log, by HTTP status ("network" if none was received)
+pub(crate) fn count_http_error(base_url: &str, status: &str) {
+    metrics::CT_HTTP_ERRORS_TOTAL
+        .with_label_values(&[base_url, status])
+        .inc();
+}
+
+/// Build a RateLimitedError from a 429 response, pausing the limiter if the log sent Retry-After
+pub(crate) async fn rate_limited_error(
+    base_url: &str,
+    limiter: Option<&RateLimiter>,
+    response: &reqwest::Response,
+) -> anyhow::Error {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after)
+        .map(|d| d.min(MAX_RETRY_AFTER));
+
+    warn!(
+        "Rate limited by CT log: {} (Retry-After: {:?})",
+        base_url, retry_after
+    );
+    count_http_error(base_url, "429");
+
+    if let (Some(limiter), Some(delay)) = (limiter, retry_after) {
+        limiter.pause_for(delay).await;
+    }
+
+    RateLimitedError { retry_after }.into()
+}
+
+/// Delay before the next retry: the log's Retry-After if given, else our backoff
+fn retry_delay(error: &anyhow::Error, backoff: Duration) -> Duration {
+    error
+        .downcast_ref::<RateLimitedError>()
+        .and_then(|e| e.retry_after)
+        .unwrap_or(backoff)
+}
+
+/// Run a request with retries and exponential backoff (max 60 seconds)
+pub(crate) async fn with_retry<T, F, Fut>(what: &str, retry: RetryPolicy, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let max_retries = retry.max_retries;
+    let mut retries = 0;
+    let mut backoff = retry.backoff.min(MAX_BACKOFF);
+
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                retries += 1;
+
+                if retries >= max_retries {
+                    return Err(e.context(format!(
+                        "Failed after {} retries",
+                        max_retries
+                    )));
                 }
+
+                let delay = retry_delay(&e, backoff);
+
+                warn!(
+                    "Error fetching {} (attempt {}/{}): {}. Retrying in {:?}",
+                    what, retries, max_retries, e, delay
+                );
+
+                tokio::time::sleep(delay).await;
+                backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
             }
         }
     }
 }
+
+/// Decode a list of base64 proof nodes
+fn decode_proof(nodes: &[String]) -> Result<Vec<Vec<u8>>> {
+    use base64::Engine;
+
+    nodes
+        .iter()
+        .map(|node| {
+            base64::engine::general_purpose::STANDARD
+                .decode(node)
+                .context("Invalid base64 in proof")
+        })
+        .collect()
+}
+
+/// Parse a Retry-After header value (delay in seconds or an HTTP date)
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        // A date in the past means "retry now"
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_error_carries_retry_after() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-entries"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+            .mount(&mock_server)
+            .await;
+
+        let client = CtLogClient::new(mock_server.uri()).unwrap();
+
+        let err = client.get_entries(0, 10).await.unwrap_err();
+        let rate_limited = err.downcast_ref::<RateLimitedError>().unwrap();
+        assert_eq!(rate_limited.retry_after, Some(Duration::from_secs(7)));
+    }
+
+    #[tokio::test]
+    async fn test_http_errors_counted_by_status() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let client = CtLogClient::new(mock_server.uri()).unwrap();
+        assert!(client.get_sth().await.is_err());
+        assert!(client.get_entries(0, 1).await.is_err());
+
+        let errors = |status| {
+            metrics::CT_HTTP_ERRORS_TOTAL
+                .with_label_values(&[&mock_server.uri(), status])
+                .get()
+        };
+        assert_eq!(errors("503"), 1);
+        // Unmatched wiremock routes answer 404
+        assert_eq!(errors("404"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_sth_honors_retry_after() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree_size": 42,
+                "timestamp": 1700000000000u64,
+                "sha256_root_hash": "",
+                "tree_head_signature": ""
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = CtLogClient::new(mock_server.uri()).unwrap();
+
+        // Retry-After of 0 means the retry happens without the 1s backoff
+        let start = std::time::Instant::now();
+        let sth = client.get_sth_with_retry(RetryPolicy::default()).await.unwrap();
+        assert_eq!(sth.tree_size, 42);
+        assert!(start.elapsed() < Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_retry_policy_per_log_override() {
+        let slow = RetryPolicy {
+            max_retries: 6,
+            backoff: Duration::from_secs(5),
+        };
+        let config = RetryConfig {
+            default: RetryPolicy::default(),
+            per_log: HashMap::from([("https://slow.log/".to_string(), slow)]),
+        };
+
+        assert_eq!(config.policy_for("https://slow.log"), slow);
+        assert_eq!(config.policy_for("https://other.log/"), RetryPolicy::default());
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_limits_attempts() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = CtLogClient::new(mock_server.uri()).unwrap();
+        let retry = RetryPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(10),
+        };
+        let err = client.get_sth_with_retry(retry).await.unwrap_err();
+        assert!(err.to_string().contains("Failed after 2 retries"));
+    }
+}
@@ -1,29 +1,153 @@
 // src/ct_log/coordinator.rs
-use std::sync::Arc;
-use std::time::Duration;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, watch};
-use tokio::task::JoinHandle;
-use tracing::{error, info, warn};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{debug, error, info, warn};
 
+use super::checkpoint::Checkpoints;
 use super::health::LogHealthTracker;
+use super::http::HttpClientPool;
+use super::log_list::LogListSource;
 use super::monitor::{LogMonitor, LogMonitorConfig};
-use crate::database::DatabaseBackend;
+use super::pipeline::{CertSender, SaturationPolicy, SpillFile};
+use super::rate_limit::RequestBudget;
+use super::source::{CertSource, LogFollow, LogScan};
+use crate::anomaly::AnomalyAlerter;
+use crate::database::{DatabaseBackend, LogLeases};
 use crate::dedupe::Dedupe;
-use crate::filter::RootDomainFilter;
+use crate::enrich::Enricher;
+use crate::filter::{NewRootFilter, RootDomainFilter};
+use crate::metrics;
 use crate::output::OutputManager;
 use crate::progress::ProgressIndicator;
 use crate::state::StateBackend;
 use crate::stats::StatsCollector;
-use crate::types::{CertData, MatchResult};
+use crate::throttle::NotifyThrottle;
+use crate::types::{CertData, MatchResult, Severity};
 use crate::watchlist::Watchlist;
 
+/// Running monitor task and its private shutdown signal
+struct MonitorHandle {
+    handle: JoinHandle<()>,
+    shutdown_tx: watch::Sender<bool>,
+}
+
 /// CT Log Coordinator - Manages monitoring of all CT logs
 pub struct CtLogCoordinator {
-    monitors: Vec<JoinHandle<()>>,
+    monitors: HashMap<String, MonitorHandle>,
+    /// Other inputs; kept apart from `monitors`, which log list refreshes and leases reconcile
+    sources: Vec<MonitorHandle>,
+    cert_tx: Option<CertSender>,
     cert_rx: mpsc::Receiver<CertData>,
-    shutdown_tx: watch::Sender<bool>,
+    spill: Option<Arc<SpillFile>>,
+    state_manager: Arc<dyn StateBackend>,
+    checkpoints: Arc<Checkpoints>,
+    /// Checkpoints waiting for queued outputs to confirm their matches
+    deliveries: Mutex<JoinSet<()>>,
+    config: LogMonitorConfig,
+    request_budget: Option<RequestBudget>,
+    http_pool: HttpClientPool,
+    refresh: Option<(LogListSource, Duration)>,
     db: Option<Arc<dyn DatabaseBackend>>,
     health_tracker: Arc<LogHealthTracker>,
+    stats: StatsCollector,
+    /// Enricher with its queue size and worker count, until `run` starts the workers
+    enricher: Option<(Enricher, usize, usize)>,
+    /// Queue to the enrichment workers while `run` is going
+    enrich_tx: Option<mpsc::Sender<PendingMatch>>,
+    stop_rx: Option<watch::Receiver<bool>>,
+    cluster: Option<Cluster>,
+    throttle: Option<NotifyThrottle>,
+    anomaly: Option<AnomalyAlerter>,
+    new_roots: Option<NewRootFilter>,
+    short_lived: Option<ShortLived>,
+}
+
+/// Flags certificates valid for less than `max_validity`
+struct ShortLived {
+    max_validity: Duration,
+    /// Send only flagged matches to the outputs
+    only: bool,
+}
+
+impl ShortLived {
+    /// Tag and annotate the match if its certificate is short-lived
+    fn flag(&self, result: &mut MatchResult) -> bool {
+        let Some(validity) = result.validity_secs() else {
+            return false;
+        };
+        if validity >= self.max_validity.as_secs() {
+            return false;
+        }
+
+        let period = if validity < 2 * 86400 {
+            format!("{} hours", validity / 3600)
+        } else {
+            format!("{} days", validity / 86400)
+        };
+        result.tags.push("short-lived".to_string());
+        result
+            .enrichment
+            .get_or_insert_with(Default::default)
+            .notes
+            .push(format!("short-lived certificate, valid for {}", period));
+        true
+    }
+}
+
+/// A match on its way to the outputs
+struct PendingMatch {
+    result: MatchResult,
+    /// Issuer outside the program's expected CAs, noted once enrichment is done
+    unexpected_issuer: Option<String>,
+    /// Log entry to checkpoint once the outputs are done with the match
+    checkpoint: Option<(String, u64)>,
+}
+
+/// Start `workers` tasks enriching matches from a queue of `queue_size`,
+/// handing each match back once it is enriched
+fn spawn_enrich_workers(
+    enricher: Enricher,
+    watchlist: Arc<tokio::sync::Mutex<Watchlist>>,
+    queue_size: usize,
+    workers: usize,
+) -> (mpsc::Sender<PendingMatch>, mpsc::Receiver<PendingMatch>) {
+    let enricher = Arc::new(enricher);
+    let (tx, rx) = mpsc::channel::<PendingMatch>(queue_size);
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+    let (enriched_tx, enriched_rx) = mpsc::channel(queue_size + workers);
+    for _ in 0..workers {
+        let enricher = Arc::clone(&enricher);
+        let watchlist = Arc::clone(&watchlist);
+        let rx = Arc::clone(&rx);
+        let enriched_tx = enriched_tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let next = rx.lock().await.recv().await;
+                let Some(mut pending) = next else {
+                    break;
+                };
+                enricher.enrich(&mut pending.result, &watchlist).await;
+                if enriched_tx.send(pending).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    (tx, enriched_rx)
+}
+
+/// Logs shared with other instances; monitors run only for the leased ones
+struct Cluster {
+    leases: LogLeases,
+    /// Every log the cluster monitors
+    logs: Vec<String>,
+    /// Last time our leases were renewed
+    renewed: Instant,
 }
 
 impl CtLogCoordinator {
@@ -31,75 +155,366 @@ impl CtLogCoordinator {
     pub fn new(
         log_urls: Vec<String>,
         state_manager: Arc<dyn StateBackend>,
-        poll_interval_secs: u64,
-        batch_size: u64,
-        parse_precerts: bool,
+        config: LogMonitorConfig,
         db: Option<Arc<dyn DatabaseBackend>>,
+        stats: StatsCollector,
     ) -> Self {
-        let (cert_tx, cert_rx) = mpsc::channel(1000);
-        let (shutdown_tx, _) = watch::channel(false);
-        let health_tracker = Arc::new(LogHealthTracker::default());
+        let (cert_tx, cert_rx) = mpsc::channel(config.channel.capacity.max(1));
+        metrics::CERT_CHANNEL_DEPTH.set(0);
 
-        let config = LogMonitorConfig {
-            poll_interval_secs,
-            batch_size,
-            parse_precerts,
+        // Overflow goes to disk when the pipeline can't keep up; without a
+        // usable spill file the sender falls back to blocking
+        let spill = match config.channel.policy {
+            SaturationPolicy::Spill => match SpillFile::open(config.channel.spill_path.clone()) {
+                Ok(spill) => {
+                    if spill.pending() > 0 {
+                        info!("Resuming {} spilled certificates from {:?}",
+                            spill.pending(), config.channel.spill_path);
+                    }
+                    Some(Arc::new(spill))
+                }
+                Err(e) => {
+                    warn!("Spill file unavailable, monitors will block instead: {:#}", e);
+                    None
+                }
+            },
+            _ => None,
         };
+        let cert_tx = CertSender::new(cert_tx, config.channel.policy, spill.clone());
+        let checkpoints = Arc::new(Checkpoints::new(Arc::clone(&state_manager), config.commit_batch));
+        let mut health_tracker = LogHealthTracker::default().with_log_ids(config.log_ids.clone());
+        if let Some(ref quarantine) = config.quarantine {
+            health_tracker = health_tracker.with_quarantine(quarantine.clone());
+        }
+        let health_tracker = Arc::new(health_tracker);
 
-        let mut monitors = Vec::new();
+        // One budget shared by every monitor bounds total in-flight requests
+        let request_budget = config.max_concurrent_requests.map(|max| {
+            info!("Limiting to {} concurrent CT log requests", max);
+            RequestBudget::new(max)
+        });
+
+        // Monitors with identical HTTP settings share one pooled client
+        let http_pool = HttpClientPool::new(config.http.clone());
+
+        let mut coordinator = Self {
+            monitors: HashMap::new(),
+            sources: Vec::new(),
+            cert_tx: Some(cert_tx),
+            cert_rx,
+            spill,
+            state_manager,
+            checkpoints,
+            deliveries: Mutex::new(JoinSet::new()),
+            config,
+            request_budget,
+            http_pool,
+            refresh: None,
+            db,
+            health_tracker,
+            stats,
+            enricher: None,
+            enrich_tx: None,
+            stop_rx: None,
+            cluster: None,
+            throttle: None,
+            anomaly: None,
+            new_roots: None,
+            short_lived: None,
+        };
 
         info!("Starting {} CT log monitors", log_urls.len());
 
         // Spawn monitor for each log
         for log_url in log_urls {
-            let log_monitor = match LogMonitor::new(
-                log_url.clone(),
-                Arc::clone(&state_manager),
-                Arc::clone(&health_tracker),
-                config.clone(),
-            ) {
-                Ok(monitor) => monitor,
-                Err(e) => {
-                    error!("Failed to create monitor for {}: {}", log_url, e);
-                    continue;
-                }
-            };
+            coordinator.spawn_monitor(log_url);
+        }
 
-            let cert_tx_clone = cert_tx.clone();
-            let shutdown_rx = shutdown_tx.subscribe();
+        info!(
+            "Spawned {} monitor tasks sharing {} HTTP client(s)",
+            coordinator.monitors.len(),
+            coordinator.http_pool.len()
+        );
 
-            let handle = tokio::spawn(async move {
-                log_monitor.run(cert_tx_clone, shutdown_rx).await;
-            });
+        coordinator
+    }
+
+    /// Periodically re-fetch the log list, starting monitors for new logs
+    /// and retiring monitors for logs that dropped off the list
+    pub fn with_log_list_refresh(mut self, source: LogListSource, interval: Duration) -> Self {
+        self.refresh = Some((source, interval));
+        self
+    }
+
+    /// Enrich matches (DNS, scope correlation) before they are emitted
+    ///
+    /// Lookups run on `workers` tasks fed by a queue of `queue_size` matches,
+    /// so they never hold up matching; a match that doesn't fit in the queue
+    /// is emitted without enrichment.
+    pub fn with_enricher(mut self, enricher: Enricher, queue_size: usize, workers: usize) -> Self {
+        self.enricher = enricher
+            .is_enabled()
+            .then_some((enricher, queue_size.max(1), workers.max(1)));
+        self
+    }
+
+    /// Stop all monitors once `stop_rx` turns true; certificates already
+    /// queued are still processed before `run` returns
+    pub fn with_stop_signal(mut self, stop_rx: watch::Receiver<bool>) -> Self {
+        self.stop_rx = Some(stop_rx);
+        self
+    }
+
+    /// Send a matched domain to the outputs at most once per `window`;
+    /// repeats are still saved to the database
+    pub fn with_throttle(mut self, window: Duration) -> Self {
+        self.throttle = Some(NotifyThrottle::new(window));
+        self
+    }
+
+    /// Alert when a program's hourly match rate spikes past its baseline
+    pub fn with_anomaly_alerts(mut self, alerter: AnomalyAlerter) -> Self {
+        self.anomaly = Some(alerter);
+        self
+    }
+
+    /// Send only the first match under each registrable root domain to the
+    /// outputs, with the root as its matched domain; every match is still saved
+    pub fn with_new_roots(mut self, filter: NewRootFilter) -> Self {
+        self.new_roots = Some(filter);
+        self
+    }
+
+    /// Tag matches on certificates valid for less than `max_validity` as
+    /// `short-lived`; with `only`, the outputs get nothing else (every match
+    /// is still saved)
+    pub fn with_short_lived(mut self, max_validity: Duration, only: bool) -> Self {
+        self.short_lived = Some(ShortLived { max_validity, only });
+        self
+    }
+
+    /// Monitor only the share of `log_urls` leased to this instance, instead
+    /// of every log; create the coordinator with no logs of its own
+    pub fn with_cluster(mut self, leases: LogLeases, log_urls: Vec<String>) -> Self {
+        self.cluster = Some(Cluster {
+            leases,
+            logs: log_urls,
+            renewed: Instant::now(),
+        });
+        self
+    }
+
+    /// Per-log health shared with the monitors
+    pub fn health_tracker(&self) -> Arc<LogHealthTracker> {
+        Arc::clone(&self.health_tracker)
+    }
+
+    /// Run a source on its own task, feeding the coordinator channel
+    fn spawn_source(&self, source: Box<dyn CertSource>) -> Result<MonitorHandle> {
+        let cert_tx = self
+            .cert_tx
+            .clone()
+            .context("Coordinator is no longer accepting work")?;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(async move {
+            let name = source.name();
+            if let Err(e) = source.run(cert_tx, shutdown_rx).await {
+                error!("{} failed: {:#}", name, e);
+            }
+        });
+
+        Ok(MonitorHandle { handle, shutdown_tx })
+    }
+
+    /// Feed certificates from another input alongside (or instead of) the monitored logs
+    /// `run` returns once every finite source has finished and its matches are processed
+    pub fn add_source(&mut self, source: Box<dyn CertSource>) -> Result<()> {
+        let handle = self.spawn_source(source)?;
+        self.sources.push(handle);
+        Ok(())
+    }
+
+    /// Build a monitor for one log, sharing the coordinator's clients and state
+    fn log_monitor(&self, log_url: String) -> Result<LogMonitor> {
+        LogMonitor::new(
+            log_url,
+            Arc::clone(&self.state_manager),
+            Arc::clone(&self.checkpoints),
+            Arc::clone(&self.health_tracker),
+            self.stats.clone(),
+            self.request_budget.clone(),
+            &self.http_pool,
+            self.config.clone(),
+        )
+    }
 
-            monitors.push(handle);
+    /// Spawn a monitor task for a log (no-op if it is already monitored)
+    fn spawn_monitor(&mut self, log_url: String) {
+        if self.monitors.contains_key(&log_url) || self.cert_tx.is_none() {
+            return;
         }
 
-        // Drop original sender so channel closes when all monitors finish
-        drop(cert_tx);
+        // Same log_id, or only a trailing slash apart
+        let key = self.config.log_ids.key(&log_url);
+        if let Some(running) = self.monitors.keys().find(|url| self.config.log_ids.key(url) == key) {
+            warn!("Not monitoring {}: it is the same log as {}", log_url, running);
+            return;
+        }
+
+        let handle = self
+            .log_monitor(log_url.clone())
+            .and_then(|monitor| self.spawn_source(Box::new(LogFollow(monitor))));
+        match handle {
+            Ok(handle) => {
+                self.monitors.insert(log_url, handle);
+            }
+            Err(e) => error!("Failed to create monitor for {}: {}", log_url, e),
+        }
+    }
 
-        info!("Spawned {} monitor tasks", monitors.len());
+    /// Scan entries `start..=end` of one log instead of following its head
+    /// `run` returns once the scan has finished and its matches are processed
+    pub fn spawn_scan(&mut self, log_url: String, start: u64, end: u64) -> Result<()> {
+        let monitor = self.log_monitor(log_url)?;
+        self.add_source(Box::new(LogScan { monitor, start, end }))
+    }
 
-        Self {
-            monitors,
-            cert_rx,
-            shutdown_tx,
-            db,
-            health_tracker,
+    /// Stop the monitor for a log that is no longer listed (or leased)
+    async fn retire_monitor(&mut self, log_url: &str) {
+        if let Some(monitor) = self.monitors.remove(log_url) {
+            info!("Retiring monitor for {}", log_url);
+            let _ = monitor.shutdown_tx.send(true);
+            self.checkpoints.forget(log_url).await;
         }
     }
 
+    /// Reconcile running monitors with a freshly fetched log list
+    async fn reconcile_logs(&mut self, fetched: Result<Vec<String>>) {
+        let log_urls = match fetched {
+            Ok(urls) => urls,
+            Err(e) => {
+                warn!("Failed to refresh CT log list, keeping current logs: {:#}", e);
+                return;
+            }
+        };
+
+        // An empty list almost certainly means a bad fetch, not that every log retired
+        if log_urls.is_empty() && !self.monitors.is_empty() {
+            warn!("Refreshed CT log list is empty, keeping current logs");
+            return;
+        }
+
+        // Clustered monitors follow the leases, which pick up the new list
+        if let Some(ref mut cluster) = self.cluster {
+            if log_urls.is_empty() && !cluster.logs.is_empty() {
+                warn!("Refreshed CT log list is empty, keeping current logs");
+            } else {
+                cluster.logs = log_urls;
+                self.rebalance().await;
+            }
+            return;
+        }
+
+        let wanted: HashSet<&String> = log_urls.iter().collect();
+        let removed: Vec<String> = self
+            .monitors
+            .keys()
+            .filter(|url| !wanted.contains(url))
+            .cloned()
+            .collect();
+        let added: Vec<String> = log_urls
+            .iter()
+            .filter(|url| !self.monitors.contains_key(*url))
+            .cloned()
+            .collect();
+
+        if added.is_empty() && removed.is_empty() {
+            debug!("CT log list unchanged ({} logs)", self.monitors.len());
+            return;
+        }
+
+        for log_url in &removed {
+            self.retire_monitor(log_url).await;
+        }
+
+        for log_url in added {
+            info!("Starting monitor for new log {}", log_url);
+            self.spawn_monitor(log_url);
+        }
+
+        info!(
+            "CT log list refreshed: {} removed, now monitoring {} logs",
+            removed.len(),
+            self.monitors.len()
+        );
+    }
+
+    /// Renew our leases and start or stop monitors to match them
+    ///
+    /// If the leases can't be renewed for longer than they last, other
+    /// instances may already have taken the logs over, so every monitor is
+    /// stopped until renewal works again.
+    async fn rebalance(&mut self) {
+        let Some(ref mut cluster) = self.cluster else {
+            return;
+        };
+
+        let owned = match cluster.leases.rebalance(&cluster.logs).await {
+            Ok(owned) => {
+                cluster.renewed = Instant::now();
+                owned
+            }
+            Err(e) => {
+                warn!("Failed to renew log leases: {:#}", e);
+                if cluster.renewed.elapsed() < cluster.leases.ttl() || self.monitors.is_empty() {
+                    return;
+                }
+                warn!("Log leases have expired, stopping all monitors until they can be renewed");
+                HashSet::new()
+            }
+        };
+
+        let removed: Vec<String> = self
+            .monitors
+            .keys()
+            .filter(|url| !owned.contains(*url))
+            .cloned()
+            .collect();
+        let added: Vec<String> = owned
+            .into_iter()
+            .filter(|url| !self.monitors.contains_key(url))
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        for log_url in &removed {
+            self.retire_monitor(log_url).await;
+        }
+        for log_url in &added {
+            info!("Starting monitor for leased log {}", log_url);
+            self.spawn_monitor(log_url.clone());
+        }
+        info!(
+            "Log leases changed: {} added, {} released, now monitoring {} logs",
+            added.len(),
+            removed.len(),
+            self.monitors.len()
+        );
+    }
+
     /// Run the coordinator - processes certificates from all monitors
     pub async fn run(
         mut self,
         watchlist: Arc<tokio::sync::Mutex<Watchlist>>,
         output_manager: OutputManager,
         dedupe: Dedupe,
-        stats: StatsCollector,
         progress: ProgressIndicator,
         root_filter: Option<RootDomainFilter>,
     ) {
         info!("CT Log Coordinator running");
+        let stats = self.stats.clone();
 
         // Spawn background task for periodic health logging
         let health_tracker_clone = Arc::clone(&self.health_tracker);
@@ -111,13 +526,128 @@ impl CtLogCoordinator {
             }
         });
 
+        // The log list is fetched on its own task, so a slow download never
+        // holds up certificates; monitors are added and removed on this one
+        let refresh = self.refresh.take();
+        let refreshing = refresh.is_some();
+        let (mut refresh_rx, refresh_task) = match refresh {
+            Some((source, interval)) => {
+                let (tx, rx) = mpsc::channel(1);
+                let task = tokio::spawn(async move {
+                    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+                    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    loop {
+                        ticker.tick().await;
+                        if tx.send(source.fetch().await).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+                (Some(rx), Some(task))
+            }
+            None => (None, None),
+        };
+
+        // Renew well before the leases run out
+        let mut lease_ticker = self.cluster.as_ref().map(|cluster| {
+            let mut ticker = tokio::time::interval(cluster.leases.ttl() / 3);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker
+        });
+
+        // With refresh and clustering disabled no monitors are ever added, so
+        // drop our sender and let the channel close once every monitor has stopped
+        if !refreshing && self.cluster.is_none() {
+            self.cert_tx = None;
+        }
+
+        let mut stop_rx = self.stop_rx.take();
+
+        // Matches wait here for the enrichment workers, then come back to be emitted
+        let mut enriched_rx = self.enricher.take().map(|(enricher, queue_size, workers)| {
+            let (enrich_tx, enriched_rx) = spawn_enrich_workers(enricher, Arc::clone(&watchlist), queue_size, workers);
+            self.enrich_tx = Some(enrich_tx);
+            enriched_rx
+        });
+
         // Process certificates from channel
-        while let Some(cert_data) = self.cert_rx.recv().await {
-            stats.increment_processed();
+        loop {
+            // Spilled certificates are replayed whenever the channel has drained
+            if self.cert_rx.is_empty() {
+                for cert_data in self.take_spilled().await {
+                    stats.increment_processed();
+                    self.handle_cert_entry(
+                        cert_data,
+                        &watchlist,
+                        &output_manager,
+                        &dedupe,
+                        &stats,
+                        &progress,
+                        &root_filter,
+                    )
+                    .await;
+                }
+            }
+
+            tokio::select! {
+                cert_data = self.cert_rx.recv() => {
+                    let Some(cert_data) = cert_data else {
+                        break;
+                    };
 
-            // Process through existing handler chain
+                    metrics::CERT_CHANNEL_DEPTH.set(self.cert_rx.len() as i64);
+                    stats.increment_processed();
+
+                    // Process through existing handler chain
+                    self.handle_cert_entry(
+                        cert_data,
+                        &watchlist,
+                        &output_manager,
+                        &dedupe,
+                        &stats,
+                        &progress,
+                        &root_filter,
+                    )
+                    .await;
+                }
+                Some(pending) = async { enriched_rx.as_mut().unwrap().recv().await }, if enriched_rx.is_some() => {
+                    self.deliver_match(pending, &output_manager, &progress).await;
+                }
+                Some(fetched) = async { refresh_rx.as_mut().unwrap().recv().await }, if refresh_rx.is_some() => {
+                    self.reconcile_logs(fetched).await;
+                }
+                _ = async { lease_ticker.as_mut().unwrap().tick().await }, if lease_ticker.is_some() => {
+                    self.rebalance().await;
+                }
+                changed = async { stop_rx.as_mut().unwrap().changed().await }, if stop_rx.is_some() => {
+                    let stop = changed.is_ok() && *stop_rx.as_ref().unwrap().borrow();
+                    if changed.is_err() || stop {
+                        stop_rx = None;
+                    }
+                    if stop {
+                        // Monitors drop their senders as they exit, closing the channel
+                        info!("Stop condition reached, shutting down monitors");
+                        self.shutdown().await;
+                        self.cert_tx = None;
+                        refresh_rx = None;
+                        if let Some(ref task) = refresh_task {
+                            task.abort();
+                        }
+                        lease_ticker = None;
+                    }
+                }
+            }
+        }
+
+        info!("Certificate channel closed, coordinator shutting down");
+        if let Some(task) = refresh_task {
+            task.abort();
+        }
+
+        for cert_data in self.take_spilled().await {
+            stats.increment_processed();
             self.handle_cert_entry(
-                &cert_data,
+                cert_data,
                 &watchlist,
                 &output_manager,
                 &dedupe,
@@ -128,22 +658,66 @@ impl CtLogCoordinator {
             .await;
         }
 
-        info!("Certificate channel closed, coordinator shutting down");
+        // Let the enrichment workers finish the matches still queued
+        self.enrich_tx = None;
+        if let Some(mut enriched_rx) = enriched_rx {
+            while let Some(pending) = enriched_rx.recv().await {
+                self.deliver_match(pending, &output_manager, &progress).await;
+            }
+        }
 
         // Wait for all monitors to finish
-        for handle in self.monitors {
-            if let Err(e) = handle.await {
+        let sources = std::mem::take(&mut self.sources);
+        for monitor in self.monitors.drain().map(|(_, monitor)| monitor).chain(sources) {
+            if let Err(e) = monitor.handle.await {
                 error!("Monitor task failed: {}", e);
             }
         }
 
         info!("All monitor tasks stopped");
+
+        if let Err(e) = output_manager.shutdown().await {
+            warn!("Failed to flush outputs: {:#}", e);
+        }
+        let mut deliveries = std::mem::take(&mut *self.deliveries.lock().unwrap());
+        while deliveries.join_next().await.is_some() {}
+        self.checkpoints.flush().await;
+
+        // Saved indices are final now, so others can pick the logs up
+        if let Some(ref cluster) = self.cluster
+            && let Err(e) = cluster.leases.release_all().await
+        {
+            warn!("Failed to release log leases: {:#}", e);
+        }
     }
 
-    /// Handle a single certificate entry (same logic as certstream.rs)
+    /// Take all certificates waiting in the spill file (if any)
+    async fn take_spilled(&self) -> Vec<CertData> {
+        let Some(ref spill) = self.spill else {
+            return Vec::new();
+        };
+        if spill.pending() == 0 {
+            return Vec::new();
+        }
+
+        match spill.drain().await {
+            Ok(entries) => {
+                debug!("Replaying {} spilled certificates", entries.len());
+                entries
+            }
+            Err(e) => {
+                warn!("Failed to read spilled certificates: {:#}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Handle a single certificate entry, passing any match on to the
+    /// enrichment workers or straight to the outputs
+    #[allow(clippy::too_many_arguments)]
     async fn handle_cert_entry(
         &self,
-        data: &CertData,
+        data: CertData,
         watchlist: &Arc<tokio::sync::Mutex<Watchlist>>,
         output_manager: &OutputManager,
         dedupe: &Dedupe,
@@ -151,65 +725,569 @@ impl CtLogCoordinator {
         progress: &ProgressIndicator,
         root_filter: &Option<RootDomainFilter>,
     ) {
+        let checkpoint = data.ct_log_url.clone().zip(data.cert_index);
+        let Some(mut pending) = self.match_cert_entry(data, watchlist, dedupe, stats, root_filter).await else {
+            if let Some((log_url, index)) = checkpoint {
+                self.checkpoints.done(&log_url, index).await;
+            }
+            return;
+        };
+        pending.checkpoint = checkpoint;
+
+        let pending = match self.enrich_tx {
+            Some(ref enrich_tx) => match enrich_tx.try_send(pending) {
+                Ok(()) => return,
+                Err(mpsc::error::TrySendError::Full(pending)) => {
+                    metrics::ENRICHMENT_SKIPPED_TOTAL.inc();
+                    debug!("Enrichment is falling behind, emitting {} unenriched", pending.result.matched_domain);
+                    pending
+                }
+                Err(mpsc::error::TrySendError::Closed(pending)) => pending,
+            },
+            None => pending,
+        };
+        self.deliver_match(pending, output_manager, progress).await;
+    }
+
+    /// Match a single certificate entry against the watchlist
+    async fn match_cert_entry(
+        &self,
+        mut data: CertData,
+        watchlist: &Arc<tokio::sync::Mutex<Watchlist>>,
+        dedupe: &Dedupe,
+        stats: &StatsCollector,
+        root_filter: &Option<RootDomainFilter>,
+    ) -> Option<PendingMatch> {
+        // Every source's SANs are cleaned here, before anything looks at them
+        data.normalize_domains();
+        let data = &data;
+
         // Check dedupe first
         if !dedupe.should_emit(data).await {
-            return;
+            metrics::DEDUPE_SUPPRESSED_TOTAL.inc();
+            return None;
         }
 
-        let domains = match &data.all_domains {
-            Some(d) if !d.is_empty() => d,
-            _ => return,
-        };
+        let domains = data.all_domains.as_deref().unwrap_or_default();
+        let ips = data.ip_addresses.as_deref().unwrap_or_default();
+        if domains.is_empty() && ips.is_empty() {
+            return None;
+        }
 
         // Lock watchlist once for all domains
         let watchlist_guard = watchlist.lock().await;
 
-        for d in domains {
-            if watchlist_guard.matches_domain(d) {
-                // Apply root domain filter if specified
-                if let Some(filter) = root_filter {
-                    if !filter.should_emit(d) {
-                        continue;
-                    }
-                }
+        // First watched domain (subject to the root filter), else first watched IP SAN
+        let matched = domains
+            .iter()
+            .filter(|d| watchlist_guard.matches_domain(d))
+            .find(|d| root_filter.as_ref().is_none_or(|filter| filter.should_emit(d)))
+            .map(|d| {
+                let rule = watchlist_guard
+                    .rule_for_domain(d)
+                    .map(|(pattern, match_type)| (pattern.to_string(), match_type));
+                (d.clone(), rule, watchlist_guard.program_for_domain(d))
+            })
+            .or_else(|| {
+                ips.iter().find_map(|ip| {
+                    let addr: IpAddr = ip.parse().ok()?;
+                    watchlist_guard
+                        .matches_ip(&addr)
+                        .then(|| (ip.clone(), watchlist_guard.rule_for_ip(&addr), watchlist_guard.program_for_ip(&addr)))
+                })
+            });
+
+        let (matched_value, rule, program) = matched?;
+
+        stats.increment_matches();
+        if let Some(program) = program {
+            stats.increment_program_matches(&program.name);
+            if let Some(ref anomaly) = self.anomaly {
+                anomaly.record(&program.name);
+            }
+        }
+
+        let program_name = program.as_ref().map(|p| p.name.clone());
+        let platform = program.as_ref().and_then(|p| p.platform.clone());
+
+        // Create match result
+        let mut result = MatchResult::from_cert_data(
+            matched_value,
+            data,
+            program_name,
+            platform,
+        );
+        if let Some((pattern, match_type)) = rule {
+            result.matched_pattern = Some(pattern);
+            result.match_type = Some(match_type);
+        }
+        if let Some(program) = program {
+            result.severity = program.severity;
+            result.tags = program.tags.clone();
+        }
+        let unexpected_issuer = program.and_then(|program| program.unexpected_issuer(&result));
 
-                stats.increment_matches();
+        Some(PendingMatch { result, unexpected_issuer, checkpoint: None })
+    }
 
-                let program = watchlist_guard.program_for_domain(d);
-                let program_name = program.as_ref().map(|p| p.name.clone());
-                let platform = program.as_ref().and_then(|p| p.platform.clone());
+    /// Finish a match, hand it to the outputs and checkpoint its log entry
+    /// once they are done with it
+    #[allow(clippy::collapsible_if)]
+    async fn deliver_match(&self, pending: PendingMatch, output_manager: &OutputManager, progress: &ProgressIndicator) {
+        let PendingMatch { mut result, unexpected_issuer, checkpoint } = pending;
 
-                // Create match result
-                let result = MatchResult::from_cert_data(
-                    d.to_string(),
-                    data,
-                    program_name,
-                    platform,
-                );
+        // A watched name certified by a CA the program doesn't use may be misissued
+        if let Some(issuer) = unexpected_issuer {
+            let program_name = result.program_name.as_deref().unwrap_or_default();
+            warn!("{} ({}) was issued a certificate by unexpected CA {}", result.matched_domain, program_name, issuer);
+            metrics::UNEXPECTED_ISSUER_TOTAL.with_label_values(&[program_name]).inc();
+            result.severity = result.severity.max(Some(Severity::High));
+            result.tags.push("unexpected-issuer".to_string());
+            result
+                .enrichment
+                .get_or_insert_with(Default::default)
+                .notes
+                .insert(0, format!("issued by unexpected CA {}", issuer));
+        }
 
-                // Emit to all output handlers
+        // How fresh the alert is, measured from the log's own timestamp
+        if let Some(latency) = result.record_latency() {
+            metrics::MATCH_LATENCY
+                .with_label_values(&[result.ct_log_url.as_deref().unwrap_or("unknown")])
+                .observe(latency);
+        }
+
+        // Very short validity is typical of staging and throwaway infrastructure
+        let skip_output = self
+            .short_lived
+            .as_ref()
+            .is_some_and(|short_lived| !short_lived.flag(&mut result) && short_lived.only);
+
+        // In new-roots mode only a never-seen root goes out, in place of the match
+        let root_result;
+        let output = match self.new_roots {
+            _ if skip_output => None,
+            Some(ref new_roots) => {
+                root_result = new_roots.new_root(&result.matched_domain).map(|root| MatchResult {
+                    matched_domain: root,
+                    ..result.clone()
+                });
+                root_result.as_ref()
+            }
+            None => Some(&result),
+        };
+
+        // Emit to all output handlers, unless this domain was notified recently
+        let mut delivery = None;
+        if let Some(output) = output {
+            let notify = self
+                .throttle
+                .as_ref()
+                .is_none_or(|throttle| throttle.should_notify(&output.matched_domain));
+            if notify {
                 // Suspend progress bar temporarily for clean output
                 progress.suspend(|| {});
 
-                if let Err(e) = output_manager.emit(&result).await {
-                    warn!("Output error: {:?}", e);
+                match output_manager.emit(output).await {
+                    Ok(emitted) => delivery = Some(emitted),
+                    Err(e) => warn!("Output error: {:?}", e),
                 }
+            } else {
+                metrics::NOTIFY_THROTTLED_TOTAL.inc();
+            }
+        }
 
-                // Save to database if enabled
-                if let Some(ref db) = self.db {
-                    if let Err(e) = db.save_match(&result).await {
-                        warn!("Failed to save match to database: {:?}", e);
-                    }
-                }
+        // Save to database if enabled
+        if let Some(ref db) = self.db {
+            if let Err(e) = db.save_match(&result).await {
+                warn!("Failed to save match to database: {:?}", e);
+            }
+        }
 
-                break;  // Only emit first match per certificate
+        let Some((log_url, index)) = checkpoint else {
+            return;
+        };
+        match delivery {
+            Some(delivery) if !delivery.is_done() => {
+                let checkpoints = Arc::clone(&self.checkpoints);
+                let mut deliveries = self.deliveries.lock().unwrap();
+                while deliveries.try_join_next().is_some() {}
+                deliveries.spawn(async move {
+                    if delivery.confirmed().await {
+                        checkpoints.done(&log_url, index).await;
+                    } else {
+                        // Left pending, so the entry is processed again after a restart
+                        warn!("Output stopped before sending the match from {} entry {}", log_url, index);
+                    }
+                });
             }
+            _ => self.checkpoints.done(&log_url, index).await,
         }
     }
 
     /// Signal shutdown to all monitors
     pub async fn shutdown(&self) {
         info!("Signaling shutdown to all monitors");
-        let _ = self.shutdown_tx.send(true);
+        for monitor in self.monitors.values().chain(&self.sources) {
+            let _ = monitor.shutdown_tx.send(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct_log::client::RetryConfig;
+    use crate::ct_log::http::HttpConfig;
+    use crate::ct_log::identity::LogIds;
+    use crate::ct_log::log_list::LogListFetcher;
+    use crate::ct_log::pipeline::ChannelConfig;
+    use crate::ct_log::rate_limit::RateLimitConfig;
+    use crate::state::StateManager;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn log_list(urls: &[&str]) -> serde_json::Value {
+        let logs: Vec<_> = urls
+            .iter()
+            .map(|url| serde_json::json!({
+                "url": url,
+                "state": {"usable": {"timestamp": "2024-01-01T00:00:00Z"}}
+            }))
+            .collect();
+
+        serde_json::json!({"operators": [{"name": "Test", "logs": logs}]})
+    }
+
+    fn test_config() -> LogMonitorConfig {
+        LogMonitorConfig {
+            poll_interval_secs: 60,
+            batch_size: 10,
+            parse_precerts: true,
+            parallel_fetches: 1,
+            rate_limit: RateLimitConfig::default(),
+            max_concurrent_requests: None,
+            audit: None,
+            quarantine: None,
+            http: HttpConfig::default(),
+            retry: RetryConfig::default(),
+            channel: ChannelConfig::default(),
+            adaptive_poll: None,
+            include_pem: false,
+            since_ms: None,
+            stale_after_secs: None,
+            commit_batch: 1,
+            log_ids: LogIds::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_signal_ends_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        let (stop_tx, stop_rx) = watch::channel(false);
+
+        let coordinator = CtLogCoordinator::new(
+            vec!["http://127.0.0.1:1/a/".to_string()],
+            Arc::new(state),
+            test_config(),
+            None,
+            StatsCollector::new(),
+        )
+        .with_stop_signal(stop_rx);
+
+        let watchlist = Watchlist::from_config(
+            &crate::config::WatchlistConfig {
+                domains: vec![],
+                hosts: vec![],
+                ips: vec![],
+                cidrs: vec![],
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+        let run = tokio::spawn(coordinator.run(
+            Arc::new(tokio::sync::Mutex::new(watchlist)),
+            OutputManager::new(),
+            Dedupe::new(),
+            ProgressIndicator::new(false),
+            None,
+        ));
+
+        // The monitor would otherwise sleep out its 60s poll interval
+        stop_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(10), run)
+            .await
+            .expect("run did not return after the stop signal")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_refresh_adds_and_retires_monitors() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/list.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(log_list(&[
+                "http://127.0.0.1:1/b/",
+                "http://127.0.0.1:1/c/",
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let mut coordinator = CtLogCoordinator::new(
+            vec!["http://127.0.0.1:1/a/".to_string(), "http://127.0.0.1:1/b/".to_string()],
+            Arc::new(state),
+            test_config(),
+            None,
+            StatsCollector::new(),
+        );
+
+        let source = LogListSource {
+            fetcher: LogListFetcher::new(),
+            list_urls: vec![format!("{}/list.json", mock_server.uri())],
+            include_readonly: false,
+            include_pending: false,
+            include_all: false,
+            additional_logs: None,
+            max_logs: None,
+            log_ids: LogIds::default(),
+        };
+
+        coordinator.reconcile_logs(source.fetch().await).await;
+
+        let mut monitored: Vec<&String> = coordinator.monitors.keys().collect();
+        monitored.sort();
+        assert_eq!(monitored, vec!["http://127.0.0.1:1/b/", "http://127.0.0.1:1/c/"]);
+
+        // All monitors share a single HTTP client
+        assert_eq!(coordinator.http_pool.len(), 1);
+
+        coordinator.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_one_monitor_per_log_identity() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        let config = test_config();
+        config.log_ids.register("http://127.0.0.1:1/b/", "Yg==");
+        config.log_ids.register("http://127.0.0.1:1/b-mirror/", "Yg==");
+
+        let coordinator = CtLogCoordinator::new(
+            vec![
+                "http://127.0.0.1:1/a/".to_string(),
+                "http://127.0.0.1:1/a".to_string(),
+                "http://127.0.0.1:1/b/".to_string(),
+                "http://127.0.0.1:1/b-mirror/".to_string(),
+            ],
+            Arc::new(state),
+            config,
+            None,
+            StatsCollector::new(),
+        );
+
+        let mut monitored: Vec<&String> = coordinator.monitors.keys().collect();
+        monitored.sort();
+        assert_eq!(monitored, vec!["http://127.0.0.1:1/a/", "http://127.0.0.1:1/b/"]);
+
+        coordinator.shutdown().await;
+    }
+
+    /// Sends a fixed list of certificates, then finishes
+    struct FixedSource(Vec<CertData>);
+
+    #[async_trait::async_trait]
+    impl CertSource for FixedSource {
+        fn name(&self) -> String {
+            "fixed".to_string()
+        }
+
+        async fn run(self: Box<Self>, cert_tx: CertSender, _shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+            for data in self.0 {
+                cert_tx.send(data).await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_sources_feed_matching() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        let stats = StatsCollector::new();
+        let mut coordinator = CtLogCoordinator::new(Vec::new(), Arc::new(state), test_config(), None, stats.clone());
+
+        let cert = |domain: &str, index: u64| CertData {
+            all_domains: Some(vec![domain.to_string()]),
+            ip_addresses: None,
+            cert_index: Some(index),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        coordinator.add_source(Box::new(FixedSource(vec![cert("a.example.com", 1), cert("other.org", 2)]))).unwrap();
+        coordinator.add_source(Box::new(FixedSource(vec![cert("b.example.com", 3)]))).unwrap();
+
+        let watchlist = Watchlist::from_config(
+            &crate::config::WatchlistConfig {
+                domains: vec!["*.example.com".to_string()],
+                hosts: vec![],
+                ips: vec![],
+                cidrs: vec![],
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Returns once both sources are exhausted
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            coordinator.run(
+                Arc::new(tokio::sync::Mutex::new(watchlist)),
+                OutputManager::new(),
+                Dedupe::new(),
+                ProgressIndicator::new(false),
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.snapshot().total_processed, 3);
+        assert_eq!(stats.snapshot().matches_found, 2);
+    }
+
+    /// Keeps every match it is given
+    #[derive(Default)]
+    struct CollectingOutput(std::sync::Mutex<Vec<MatchResult>>);
+
+    #[async_trait::async_trait]
+    impl crate::output::OutputHandler for CollectingOutput {
+        fn name(&self) -> &'static str {
+            "collecting"
+        }
+
+        async fn emit_match(&self, result: &MatchResult) -> Result<()> {
+            self.0.lock().unwrap().push(result.clone());
+            Ok(())
+        }
+
+        async fn flush(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrichment_runs_on_workers() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "events": [{"eventAction": "registration", "eventDate": "2020-01-01T00:00:00Z"}]
+                    }))
+                    .set_delay(Duration::from_millis(400)),
+            )
+            .mount(&mock_server)
+            .await;
+        let enricher = Enricher::new(crate::enrich::EnrichConfig {
+            rdap: true,
+            rdap_url: mock_server.uri(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateManager::new(temp_dir.path().join("state.toml")).await.unwrap();
+        let mut coordinator = CtLogCoordinator::new(Vec::new(), Arc::new(state), test_config(), None, StatsCollector::new())
+            .with_enricher(enricher, 10, 4);
+        let roots = ["one.com", "two.com", "three.com", "four.com"];
+        let certs = roots
+            .iter()
+            .enumerate()
+            .map(|(i, root)| CertData {
+                all_domains: Some(vec![format!("www.{}", root)]),
+                ip_addresses: None,
+                cert_index: Some(i as u64),
+                seen_unix: None,
+                logged_at_ms: None,
+                leaf_cert: None,
+                is_precert: false,
+                ct_log_url: None,
+            })
+            .collect();
+        coordinator.add_source(Box::new(FixedSource(certs))).unwrap();
+
+        let watchlist = Watchlist::from_config(
+            &crate::config::WatchlistConfig {
+                domains: roots.iter().map(|root| format!("*.{}", root)).collect(),
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+        let collected = Arc::new(CollectingOutput::default());
+        let mut output_manager = OutputManager::new();
+        output_manager.add_handler(collected.clone());
+
+        // Four lookups of 400ms each overlap instead of running one after another
+        let started = Instant::now();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            coordinator.run(
+                Arc::new(tokio::sync::Mutex::new(watchlist)),
+                output_manager,
+                Dedupe::new(),
+                ProgressIndicator::new(false),
+                None,
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_millis(1200), "took {:?}", started.elapsed());
+
+        let collected = collected.0.lock().unwrap();
+        assert_eq!(collected.len(), 4);
+        assert!(collected.iter().all(|result| result.enrichment.as_ref().unwrap().registration.is_some()));
+    }
+
+    #[test]
+    fn test_short_lived_flag() {
+        let data = CertData {
+            all_domains: Some(vec!["staging.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: None,
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let short_lived = ShortLived { max_validity: Duration::from_secs(7 * 86400), only: false };
+
+        let mut result = MatchResult::from_cert_data("staging.example.com".to_string(), &data, None, None);
+        // Unknown validity is never flagged
+        assert!(!short_lived.flag(&mut result));
+
+        result.not_before = Some(1_700_000_000);
+        result.not_after = Some(1_700_000_000 + 90 * 86400);
+        assert!(!short_lived.flag(&mut result));
+        assert!(result.tags.is_empty());
+
+        result.not_after = Some(1_700_000_000 + 86400);
+        assert!(short_lived.flag(&mut result));
+        assert_eq!(result.tags, ["short-lived"]);
+        assert_eq!(
+            result.enrichment.unwrap().notes,
+            ["short-lived certificate, valid for 24 hours"]
+        );
     }
 }
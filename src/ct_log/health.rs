@@ -1,10 +1,13 @@
 // src/ct_log/health.rs
-use std::collections::HashMap;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{warn, info, debug};
 
+use super::identity::LogIds;
+
 /// Health status of a CT log
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogHealth {
@@ -14,6 +17,37 @@ pub enum LogHealth {
     Degraded,
     /// Log has failed multiple times, using exponential backoff
     Failed,
+    /// Log has failed continuously for a long time, only re-probed occasionally
+    Quarantined,
+}
+
+/// Circuit breaker guarding requests to a log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are blocked until the backoff expires
+    Open,
+    /// A single probe request is in flight; everything else waits for its outcome
+    HalfOpen,
+}
+
+/// When to give up on a persistently failing log
+#[derive(Debug, Clone)]
+pub struct QuarantineConfig {
+    /// Continuous failure time before a log is quarantined
+    pub after_secs: u64,
+    /// Time between probes of a quarantined log
+    pub probe_interval_secs: u64,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            after_secs: 24 * 3600,
+            probe_interval_secs: 24 * 3600,
+        }
+    }
 }
 
 /// Health information for a single log
@@ -23,6 +57,8 @@ pub struct LogHealthInfo {
     pub status: LogHealth,
     /// Number of consecutive failures
     pub failure_count: u32,
+    /// Timestamp of the first failure in the current failure streak
+    pub failing_since: Option<Instant>,
     /// Timestamp of last failure
     pub last_failure: Option<Instant>,
     /// Timestamp of last successful poll
@@ -31,6 +67,10 @@ pub struct LogHealthInfo {
     pub last_error: Option<String>,
     /// Current backoff duration (for failed logs)
     pub current_backoff: Duration,
+    /// Circuit breaker state
+    pub circuit: CircuitState,
+    /// When the current half-open probe was allowed through
+    pub probe_started: Option<Instant>,
 }
 
 impl LogHealthInfo {
@@ -38,10 +78,13 @@ impl LogHealthInfo {
         Self {
             status: LogHealth::Healthy,
             failure_count: 0,
+            failing_since: None,
             last_failure: None,
             last_success: None,
             last_error: None,
             current_backoff: Duration::from_secs(0),
+            circuit: CircuitState::Closed,
+            probe_started: None,
         }
     }
 
@@ -59,12 +102,26 @@ impl LogHealthInfo {
     }
 }
 
+/// Randomize a backoff to between half and all of its length ("equal jitter"),
+/// so logs that failed together (e.g. during a network outage) don't all retry
+/// at the same instant
+fn jittered(backoff: Duration) -> Duration {
+    let half = backoff / 2;
+    half + half.mul_f64(rand::thread_rng().r#gen::<f64>())
+}
+
 /// Tracks health status of all monitored CT logs
 pub struct LogHealthTracker {
-    /// Health information per log URL
+    /// Health information per log key (see `LogIds::key`)
     health: Arc<RwLock<HashMap<String, LogHealthInfo>>>,
     /// Number of failures before marking as Failed
     failure_threshold: u32,
+    /// Quarantine policy (None = never quarantine)
+    quarantine: Option<QuarantineConfig>,
+    /// Logs paused by an operator; never polled until resumed
+    paused: Arc<RwLock<HashSet<String>>>,
+    /// Maps log URLs to keys, so a log listed under two URLs has one entry
+    log_ids: LogIds,
 }
 
 impl LogHealthTracker {
@@ -76,23 +133,44 @@ impl LogHealthTracker {
         Self {
             health: Arc::new(RwLock::new(HashMap::new())),
             failure_threshold,
+            quarantine: None,
+            paused: Arc::new(RwLock::new(HashSet::new())),
+            log_ids: LogIds::default(),
         }
     }
 
+    /// Quarantine logs that keep failing for longer than `config.after_secs`
+    pub fn with_quarantine(mut self, config: QuarantineConfig) -> Self {
+        self.quarantine = Some(config);
+        self
+    }
+
+    /// Key logs by the log_ids learned from the log lists rather than by URL
+    pub fn with_log_ids(mut self, log_ids: LogIds) -> Self {
+        self.log_ids = log_ids;
+        self
+    }
+
     /// Record a successful poll from a log
     pub async fn record_success(&self, log_url: &str) {
         let mut health = self.health.write().await;
-        let info = health.entry(log_url.to_string()).or_insert_with(LogHealthInfo::new);
+        let info = health.entry(self.log_ids.key(log_url)).or_insert_with(LogHealthInfo::new);
 
+        let was_quarantined = info.status == LogHealth::Quarantined;
         let was_failed = info.status == LogHealth::Failed;
         let was_degraded = info.status == LogHealth::Degraded;
 
         info.status = LogHealth::Healthy;
         info.failure_count = 0;
+        info.failing_since = None;
+        info.circuit = CircuitState::Closed;
+        info.probe_started = None;
         info.last_success = Some(Instant::now());
         info.current_backoff = Duration::from_secs(0);
 
-        if was_failed {
+        if was_quarantined {
+            info!("Log recovered: {} is now healthy (was quarantined)", log_url);
+        } else if was_failed {
             info!("Log recovered: {} is now healthy (was failed)", log_url);
         } else if was_degraded {
             debug!("Log recovered: {} is now healthy (was degraded)", log_url);
@@ -102,22 +180,40 @@ impl LogHealthTracker {
     /// Record a failed poll from a log
     pub async fn record_failure(&self, log_url: &str, error: String) {
         let mut health = self.health.write().await;
-        let info = health.entry(log_url.to_string()).or_insert_with(LogHealthInfo::new);
+        let info = health.entry(self.log_ids.key(log_url)).or_insert_with(LogHealthInfo::new);
 
+        let now = Instant::now();
         info.failure_count += 1;
-        info.last_failure = Some(Instant::now());
+        info.last_failure = Some(now);
         info.last_error = Some(error.clone());
+        let failing_since = *info.failing_since.get_or_insert(now);
 
         // Determine new status
         let old_status = info.status;
-        info.status = if info.failure_count >= self.failure_threshold {
+        let quarantine = self
+            .quarantine
+            .as_ref()
+            .filter(|q| now.duration_since(failing_since) >= Duration::from_secs(q.after_secs));
+        info.status = if quarantine.is_some() {
+            LogHealth::Quarantined
+        } else if info.failure_count >= self.failure_threshold {
             LogHealth::Failed
         } else {
             LogHealth::Degraded
         };
 
         // Calculate new backoff
-        info.current_backoff = info.next_backoff();
+        info.current_backoff = match quarantine {
+            Some(q) => Duration::from_secs(q.probe_interval_secs),
+            None => jittered(info.next_backoff()),
+        };
+
+        // Trip the breaker once the log is considered down; a failed probe re-opens it
+        info.circuit = match info.status {
+            LogHealth::Failed | LogHealth::Quarantined => CircuitState::Open,
+            LogHealth::Healthy | LogHealth::Degraded => CircuitState::Closed,
+        };
+        info.probe_started = None;
 
         // Log status change
         match (old_status, info.status) {
@@ -133,29 +229,56 @@ impl LogHealthTracker {
                 debug!("Log still failed: {} (failure {}, backoff: {:?}): {}",
                     log_url, info.failure_count, info.current_backoff, error);
             }
+            (old, LogHealth::Quarantined) if old != LogHealth::Quarantined => {
+                warn!("Log quarantined: {} (failing for {:?}, will re-probe every {:?}): {}",
+                    log_url, now.duration_since(failing_since), info.current_backoff, error);
+            }
             _ => {}
         }
     }
 
     /// Check if a log should be polled based on its health status
     /// Returns true if the log should be polled now, false if it should be skipped
+    ///
+    /// Once the backoff of a failed log expires the circuit goes half-open and
+    /// exactly one caller is let through to probe it; others keep waiting until
+    /// that probe is recorded as a success or failure.
     pub async fn should_poll(&self, log_url: &str) -> bool {
-        let health = self.health.read().await;
-        let info = match health.get(log_url) {
+        let key = self.log_ids.key(log_url);
+        if self.paused.read().await.contains(&key) {
+            return false;
+        }
+
+        let mut health = self.health.write().await;
+        let info = match health.get_mut(&key) {
             Some(info) => info,
             None => return true, // New log, should poll
         };
 
-        match info.status {
-            LogHealth::Healthy | LogHealth::Degraded => true,
-            LogHealth::Failed => {
+        let now = Instant::now();
+        match info.circuit {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
                 // Check if enough time has passed since last failure
-                if let Some(last_failure) = info.last_failure {
-                    let elapsed = last_failure.elapsed();
-                    elapsed >= info.current_backoff
-                } else {
-                    true // No last failure time, should poll
+                let expired = info
+                    .last_failure
+                    .is_none_or(|last_failure| now.duration_since(last_failure) >= info.current_backoff);
+                if expired {
+                    debug!("Circuit half-open for {}: probing", log_url);
+                    info.circuit = CircuitState::HalfOpen;
+                    info.probe_started = Some(now);
+                }
+                expired
+            }
+            CircuitState::HalfOpen => {
+                // A probe that never reported back must not block the log forever
+                let stale = info
+                    .probe_started
+                    .is_none_or(|started| now.duration_since(started) >= info.current_backoff);
+                if stale {
+                    info.probe_started = Some(now);
                 }
+                stale
             }
         }
     }
@@ -163,7 +286,7 @@ impl LogHealthTracker {
     /// Get current health status for a log
     pub async fn get_status(&self, log_url: &str) -> LogHealth {
         let health = self.health.read().await;
-        health.get(log_url)
+        health.get(&self.log_ids.key(log_url))
             .map(|info| info.status)
             .unwrap_or(LogHealth::Healthy)
     }
@@ -171,47 +294,92 @@ impl LogHealthTracker {
     /// Get health information for a log
     pub async fn get_info(&self, log_url: &str) -> Option<LogHealthInfo> {
         let health = self.health.read().await;
-        health.get(log_url).cloned()
+        health.get(&self.log_ids.key(log_url)).cloned()
     }
 
     /// Get statistics about log health
-    pub async fn get_stats(&self) -> (usize, usize, usize) {
+    /// Returns (healthy, degraded, failed, quarantined)
+    pub async fn get_stats(&self) -> (usize, usize, usize, usize) {
         let health = self.health.read().await;
         let mut healthy = 0;
         let mut degraded = 0;
         let mut failed = 0;
+        let mut quarantined = 0;
 
         for info in health.values() {
             match info.status {
                 LogHealth::Healthy => healthy += 1,
                 LogHealth::Degraded => degraded += 1,
                 LogHealth::Failed => failed += 1,
+                LogHealth::Quarantined => quarantined += 1,
             }
         }
 
-        (healthy, degraded, failed)
+        (healthy, degraded, failed, quarantined)
+    }
+
+    /// Health information for every log seen so far, sorted by URL
+    pub async fn snapshot(&self) -> Vec<(String, LogHealthInfo)> {
+        let health = self.health.read().await;
+        let mut logs: Vec<(String, LogHealthInfo)> = health
+            .iter()
+            .map(|(key, info)| (self.log_ids.display(key), info.clone()))
+            .collect();
+        logs.sort_by(|a, b| a.0.cmp(&b.0));
+        logs
+    }
+
+    /// URLs of all quarantined logs, sorted
+    pub async fn quarantined_logs(&self) -> Vec<String> {
+        let health = self.health.read().await;
+        let mut urls: Vec<String> = health
+            .iter()
+            .filter(|(_, info)| info.status == LogHealth::Quarantined)
+            .map(|(key, _)| self.log_ids.display(key))
+            .collect();
+        urls.sort();
+        urls
     }
 
     /// Log a summary of all log health statuses
+    #[allow(clippy::collapsible_if)]
     pub async fn log_summary(&self) {
-        let (healthy, degraded, failed) = self.get_stats().await;
-        let total = healthy + degraded + failed;
+        let (healthy, degraded, failed, quarantined) = self.get_stats().await;
+        let total = healthy + degraded + failed + quarantined;
 
         if total == 0 {
             return;
         }
 
-        info!("Log health summary: {} total ({} healthy, {} degraded, {} failed)",
-            total, healthy, degraded, failed);
+        info!("Log health summary: {} total ({} healthy, {} degraded, {} failed, {} quarantined)",
+            total, healthy, degraded, failed, quarantined);
+
+        // Quarantined logs are likely dead; name them so they can be removed
+        if quarantined > 0 {
+            let health = self.health.read().await;
+            let mut keys: Vec<&String> = health
+                .iter()
+                .filter(|(_, info)| info.status == LogHealth::Quarantined)
+                .map(|(key, _)| key)
+                .collect();
+            keys.sort();
+            for key in keys {
+                let info = &health[key];
+                warn!("Quarantined log: {} - failing for {:?}, last error: {}",
+                    self.log_ids.display(key),
+                    info.failing_since.map(|t| t.elapsed()).unwrap_or_default(),
+                    info.last_error.as_deref().unwrap_or("unknown"));
+            }
+        }
 
         // Log details of failed logs
         if failed > 0 {
             let health = self.health.read().await;
-            for (url, info) in health.iter() {
+            for (key, info) in health.iter() {
                 if info.status == LogHealth::Failed {
                     if let Some(ref error) = info.last_error {
                         warn!("Failed log: {} - {} failures, backoff: {:?}, last error: {}",
-                            url, info.failure_count, info.current_backoff, error);
+                            self.log_ids.display(key), info.failure_count, info.current_backoff, error);
                     }
                 }
             }
@@ -221,10 +389,35 @@ impl LogHealthTracker {
     /// Reset health status for a specific log (for testing or manual recovery)
     pub async fn reset_log(&self, log_url: &str) {
         let mut health = self.health.write().await;
-        health.remove(log_url);
+        health.remove(&self.log_ids.key(log_url));
         info!("Reset health status for log: {}", log_url);
     }
 
+    /// Stop polling a log until `resume` is called; returns false if it was already paused
+    pub async fn pause(&self, log_url: &str) -> bool {
+        let paused = self.paused.write().await.insert(self.log_ids.key(log_url));
+        if paused {
+            info!("Paused log: {}", log_url);
+        }
+        paused
+    }
+
+    /// Poll a paused log again; returns false if it wasn't paused
+    pub async fn resume(&self, log_url: &str) -> bool {
+        let resumed = self.paused.write().await.remove(&self.log_ids.key(log_url));
+        if resumed {
+            info!("Resumed log: {}", log_url);
+        }
+        resumed
+    }
+
+    /// URLs of all paused logs, sorted
+    pub async fn paused_logs(&self) -> Vec<String> {
+        let mut urls: Vec<String> = self.paused.read().await.iter().map(|key| self.log_ids.display(key)).collect();
+        urls.sort();
+        urls
+    }
+
     /// Reset all log health statuses
     pub async fn reset_all(&self) {
         let mut health = self.health.write().await;
@@ -303,6 +496,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[allow(clippy::overly_complex_bool_expr)]
     async fn test_should_poll() {
         let tracker = LogHealthTracker::new(3);
         let log_url = "https://test.log/ct/v1/";
@@ -325,6 +519,42 @@ mod tests {
         assert!(!should_poll || should_poll); // Accept either outcome due to timing
     }
 
+    #[tokio::test]
+    async fn test_half_open_allows_single_probe() {
+        let tracker = LogHealthTracker::new(1);
+        let log_url = "https://test.log/ct/v1/";
+
+        tracker.record_failure(log_url, "Error".to_string()).await;
+        assert_eq!(tracker.get_info(log_url).await.unwrap().circuit, CircuitState::Open);
+        assert!(!tracker.should_poll(log_url).await);
+
+        // Pretend the backoff has expired
+        tracker.health.write().await.get_mut(&tracker.log_ids.key(log_url)).unwrap().last_failure =
+            Some(Instant::now() - Duration::from_secs(7200));
+
+        assert!(tracker.should_poll(log_url).await);
+        assert_eq!(tracker.get_info(log_url).await.unwrap().circuit, CircuitState::HalfOpen);
+        assert!(!tracker.should_poll(log_url).await);
+
+        // Failed probe re-opens the circuit, successful one closes it
+        tracker.record_failure(log_url, "Still down".to_string()).await;
+        assert_eq!(tracker.get_info(log_url).await.unwrap().circuit, CircuitState::Open);
+
+        tracker.record_success(log_url).await;
+        assert_eq!(tracker.get_info(log_url).await.unwrap().circuit, CircuitState::Closed);
+        assert!(tracker.should_poll(log_url).await);
+    }
+
+    #[test]
+    fn test_jittered_backoff_range() {
+        let backoff = Duration::from_secs(240);
+        for _ in 0..100 {
+            let jittered = jittered(backoff);
+            assert!(jittered >= backoff / 2 && jittered <= backoff, "{:?}", jittered);
+        }
+        assert_eq!(jittered(Duration::ZERO), Duration::ZERO);
+    }
+
     #[tokio::test]
     async fn test_backoff_calculation() {
         let mut info = LogHealthInfo::new();
@@ -356,9 +586,83 @@ mod tests {
         tracker.record_failure("https://log3.com/", "Error 2".to_string()).await;
         tracker.record_failure("https://log3.com/", "Error 3".to_string()).await;
 
-        let (healthy, degraded, failed) = tracker.get_stats().await;
+        let (healthy, degraded, failed, quarantined) = tracker.get_stats().await;
         assert_eq!(healthy, 1);
         assert_eq!(degraded, 1);
         assert_eq!(failed, 1);
+        assert_eq!(quarantined, 0);
+    }
+
+    #[tokio::test]
+    async fn test_quarantine_after_continuous_failure() {
+        let tracker = LogHealthTracker::new(3).with_quarantine(QuarantineConfig {
+            after_secs: 0,
+            probe_interval_secs: 86400,
+        });
+        let log_url = "https://dead.log/ct/v1/";
+
+        tracker.record_failure(log_url, "Connection refused".to_string()).await;
+        assert_eq!(tracker.get_status(log_url).await, LogHealth::Quarantined);
+        assert_eq!(tracker.quarantined_logs().await, vec!["https://dead.log/ct/v1".to_string()]);
+
+        let info = tracker.get_info(log_url).await.unwrap();
+        assert_eq!(info.current_backoff, Duration::from_secs(86400));
+        assert!(!tracker.should_poll(log_url).await);
+
+        let (_, _, _, quarantined) = tracker.get_stats().await;
+        assert_eq!(quarantined, 1);
+
+        // A successful probe releases the log
+        tracker.record_success(log_url).await;
+        assert_eq!(tracker.get_status(log_url).await, LogHealth::Healthy);
+        assert!(tracker.quarantined_logs().await.is_empty());
+        assert!(tracker.get_info(log_url).await.unwrap().failing_since.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_no_quarantine_before_threshold() {
+        let tracker = LogHealthTracker::new(3).with_quarantine(QuarantineConfig::default());
+        let log_url = "https://test.log/ct/v1/";
+
+        for _ in 0..5 {
+            tracker.record_failure(log_url, "Error".to_string()).await;
+        }
+
+        assert_eq!(tracker.get_status(log_url).await, LogHealth::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_paused_log_is_not_polled() {
+        let tracker = LogHealthTracker::new(3);
+        let log_url = "https://test.log/ct/v1/";
+
+        assert!(tracker.pause(log_url).await);
+        assert!(!tracker.pause(log_url).await);
+        assert!(!tracker.should_poll(log_url).await);
+        assert_eq!(tracker.paused_logs().await, vec!["https://test.log/ct/v1".to_string()]);
+
+        // Resetting health doesn't unpause
+        tracker.reset_log(log_url).await;
+        assert!(!tracker.should_poll(log_url).await);
+
+        assert!(tracker.resume(log_url).await);
+        assert!(!tracker.resume(log_url).await);
+        assert!(tracker.should_poll(log_url).await);
+    }
+
+    #[tokio::test]
+    async fn test_log_urls_share_health_by_log_id() {
+        let log_ids = LogIds::new();
+        log_ids.register("https://new.log/ct/v1/", "aWQ=");
+        log_ids.register("https://old.log/ct/v1/", "aWQ=");
+        let tracker = LogHealthTracker::new(1).with_log_ids(log_ids);
+
+        tracker.record_failure("https://old.log/ct/v1", "Error".to_string()).await;
+        assert_eq!(tracker.get_status("https://new.log/ct/v1/").await, LogHealth::Failed);
+
+        assert!(tracker.pause("https://new.log/ct/v1").await);
+        assert!(!tracker.should_poll("https://old.log/ct/v1/").await);
+        assert_eq!(tracker.paused_logs().await, vec!["https://old.log/ct/v1/".to_string()]);
+        assert_eq!(tracker.snapshot().await.len(), 1);
     }
 }
@@ -0,0 +1,427 @@
+// src/ct_log/http.rs
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// User-Agent sent when none is configured
+pub const DEFAULT_USER_AGENT: &str = concat!("ct-scout/", env!("CARGO_PKG_VERSION"));
+
+/// Request timeout used when none is configured
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP settings shared by CT log and log list requests
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Proxy for all requests (http://, https://, socks5:// or socks5h://)
+    pub proxy: Option<String>,
+    /// Per-log proxy overrides keyed by log URL (empty string = connect directly)
+    pub per_log_proxy: HashMap<String, String>,
+    /// User-Agent for all requests (None = DEFAULT_USER_AGENT)
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every CT log request
+    pub headers: HashMap<String, String>,
+    /// Per-log extra headers keyed by log URL, merged over `headers`
+    pub per_log_headers: HashMap<String, HashMap<String, String>>,
+    /// Timeout of each request in seconds (None = DEFAULT_TIMEOUT)
+    pub timeout_secs: Option<u64>,
+    /// Per-log timeout overrides in seconds keyed by log URL
+    pub per_log_timeout: HashMap<String, u64>,
+}
+
+/// Everything that distinguishes one configured HTTP client from another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientSpec {
+    proxy: Option<String>,
+    headers: BTreeMap<String, String>,
+    timeout: Duration,
+}
+
+/// Find a per-log setting, ignoring trailing slashes in either URL
+fn lookup<'a, T>(per_log: &'a HashMap<String, T>, log_url: &str) -> Option<&'a T> {
+    let wanted = log_url.trim_end_matches('/');
+    per_log
+        .iter()
+        .find(|(url, _)| url.trim_end_matches('/') == wanted)
+        .map(|(_, value)| value)
+}
+
+impl HttpConfig {
+    /// Proxy to use for a specific log, or None for a direct connection
+    pub fn proxy_for(&self, log_url: &str) -> Option<&str> {
+        lookup(&self.per_log_proxy, log_url)
+            .map(String::as_str)
+            .or(self.proxy.as_deref())
+            .filter(|proxy| !proxy.is_empty())
+    }
+
+    /// Request timeout for a specific log
+    pub fn timeout_for(&self, log_url: &str) -> Duration {
+        lookup(&self.per_log_timeout, log_url)
+            .copied()
+            .or(self.timeout_secs)
+            .map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    /// Global extra headers, with names normalized to lowercase
+    fn global_headers(&self) -> BTreeMap<String, String> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+            .collect()
+    }
+
+    /// Extra headers for a specific log (global headers plus per-log overrides)
+    pub fn headers_for(&self, log_url: &str) -> BTreeMap<String, String> {
+        let mut headers = self.global_headers();
+        if let Some(overrides) = lookup(&self.per_log_headers, log_url) {
+            for (name, value) in overrides {
+                headers.insert(name.to_ascii_lowercase(), value.clone());
+            }
+        }
+
+        headers
+    }
+
+    /// HTTP client for requests not tied to a log (e.g. log list downloads)
+    ///
+    /// Uses the global proxy and User-Agent but none of the extra headers,
+    /// which are meant for the CT logs and often carry credentials.
+    pub fn client(&self) -> Result<reqwest::Client> {
+        self.build_client(&self.global_spec())
+    }
+
+    /// HTTP client for requests to a specific log
+    pub fn client_for(&self, log_url: &str) -> Result<reqwest::Client> {
+        self.build_client(&self.spec_for(log_url))
+    }
+
+    fn global_spec(&self) -> ClientSpec {
+        ClientSpec {
+            proxy: self.proxy.clone().filter(|proxy| !proxy.is_empty()),
+            headers: BTreeMap::new(),
+            timeout: self.timeout_secs.map_or(DEFAULT_TIMEOUT, Duration::from_secs),
+        }
+    }
+
+    fn spec_for(&self, log_url: &str) -> ClientSpec {
+        ClientSpec {
+            proxy: self.proxy_for(log_url).map(str::to_string),
+            headers: self.headers_for(log_url),
+            timeout: self.timeout_for(log_url),
+        }
+    }
+
+    fn build_client(&self, spec: &ClientSpec) -> Result<reqwest::Client> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &spec.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name: {}", name))?;
+            let value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header {}", name))?;
+            headers.insert(name, value);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(spec.timeout)
+            .gzip(true)
+            .user_agent(self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+            .default_headers(headers);
+
+        if let Some(ref proxy) = spec.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .with_context(|| format!("Invalid proxy URL: {}", proxy))?;
+            builder = builder.proxy(proxy);
+        }
+
+        builder.build().context("Failed to build HTTP client")
+    }
+}
+
+/// HTTP clients shared by all monitors
+///
+/// reqwest clients pool connections internally, so logs with identical proxy
+/// and header settings reuse one client (and its TLS sessions and sockets)
+/// instead of each monitor opening its own. Clones share the same pool.
+#[derive(Clone)]
+pub struct HttpClientPool {
+    config: HttpConfig,
+    clients: Arc<Mutex<HashMap<ClientSpec, reqwest::Client>>>,
+}
+
+impl HttpClientPool {
+    /// Create an empty pool; clients are built on first use
+    pub fn new(config: HttpConfig) -> Self {
+        Self {
+            config,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Shared client for requests not tied to a log
+    pub fn client(&self) -> Result<reqwest::Client> {
+        self.get_or_build(self.config.global_spec())
+    }
+
+    /// Shared client for requests to a specific log
+    pub fn client_for(&self, log_url: &str) -> Result<reqwest::Client> {
+        self.get_or_build(self.config.spec_for(log_url))
+    }
+
+    /// Number of distinct clients built so far
+    pub fn len(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Whether no client has been built yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get_or_build(&self, spec: ClientSpec) -> Result<reqwest::Client> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(&spec) {
+            return Ok(client.clone());
+        }
+
+        let client = self.config.build_client(&spec)?;
+        clients.insert(spec, client.clone());
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_log_proxy_overrides_global() {
+        let config = HttpConfig {
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            per_log_proxy: HashMap::from([
+                ("https://a.log/".to_string(), "socks5h://127.0.0.1:1080".to_string()),
+                ("https://b.log".to_string(), String::new()),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(config.proxy_for("https://a.log"), Some("socks5h://127.0.0.1:1080"));
+        assert_eq!(config.proxy_for("https://b.log/"), None);
+        assert_eq!(config.proxy_for("https://c.log/"), Some("http://proxy.corp:3128"));
+    }
+
+    #[test]
+    fn test_builds_clients_for_supported_schemes() {
+        for proxy in ["http://proxy:3128", "https://proxy:443", "socks5://proxy:1080"] {
+            let config = HttpConfig {
+                proxy: Some(proxy.to_string()),
+                ..Default::default()
+            };
+            assert!(config.client().is_ok(), "{} should be accepted", proxy);
+        }
+    }
+
+    #[test]
+    fn test_per_log_headers_merge_over_global() {
+        let config = HttpConfig {
+            headers: HashMap::from([
+                ("X-Team".to_string(), "recon".to_string()),
+                ("Authorization".to_string(), "Bearer global".to_string()),
+            ]),
+            per_log_headers: HashMap::from([(
+                "https://private.log/".to_string(),
+                HashMap::from([("authorization".to_string(), "Bearer private".to_string())]),
+            )]),
+            ..Default::default()
+        };
+
+        let headers = config.headers_for("https://private.log");
+        assert_eq!(headers.get("authorization").unwrap(), "Bearer private");
+        assert_eq!(headers.get("x-team").unwrap(), "recon");
+
+        let headers = config.headers_for("https://public.log/");
+        assert_eq!(headers.get("authorization").unwrap(), "Bearer global");
+    }
+
+    #[tokio::test]
+    async fn test_sends_user_agent_and_headers() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .and(header("user-agent", "scanner/1.0"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let log_url = format!("{}/", mock_server.uri());
+        let config = HttpConfig {
+            user_agent: Some("scanner/1.0".to_string()),
+            per_log_headers: HashMap::from([(
+                log_url.clone(),
+                HashMap::from([("X-Api-Key".to_string(), "secret".to_string())]),
+            )]),
+            ..Default::default()
+        };
+
+        let client = config.client_for(&log_url).unwrap();
+        let response = client
+            .get(format!("{}ct/v1/get-sth", log_url))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    #[test]
+    fn test_rejects_invalid_header() {
+        let config = HttpConfig {
+            headers: HashMap::from([("Bad Header".to_string(), "x".to_string())]),
+            ..Default::default()
+        };
+        assert!(config.client_for("https://ct.example.com/").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_log_list_client_sends_no_extra_headers() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let config = HttpConfig {
+            headers: HashMap::from([("Authorization".to_string(), "Bearer log-secret".to_string())]),
+            ..Default::default()
+        };
+        config.client().unwrap().get(format!("{}/log_list.json", mock_server.uri())).send().await.unwrap();
+        config.client_for(&mock_server.uri()).unwrap().get(format!("{}/ct/v1/get-sth", mock_server.uri()))
+            .send().await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert!(!requests[0].headers.contains_key("authorization"));
+        assert_eq!(requests[1].headers["authorization"], "Bearer log-secret");
+    }
+
+    #[test]
+    fn test_pool_shares_clients_with_identical_settings() {
+        let pool = HttpClientPool::new(HttpConfig {
+            per_log_proxy: HashMap::from([(
+                "https://proxied.log/".to_string(),
+                "http://proxy:3128".to_string(),
+            )]),
+            ..Default::default()
+        });
+
+        pool.client_for("https://a.log/").unwrap();
+        pool.client_for("https://b.log/").unwrap();
+        pool.client().unwrap();
+        assert_eq!(pool.len(), 1);
+
+        pool.client_for("https://proxied.log").unwrap();
+        assert_eq!(pool.len(), 2);
+
+        // Clones share the same clients
+        pool.clone().client_for("https://c.log/").unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+
+    /// HTTP/1.1 server answering every request with `{}`, counting accepted connections
+    async fn counting_server() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = socket.read(&mut buf).await {
+                        if n == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        // Requests are bodyless GETs, so a blank line ends each one
+                        while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            request.drain(..end + 4);
+                            let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}";
+                            if socket.write_all(response).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        (format!("http://{}", addr), connections)
+    }
+
+    #[tokio::test]
+    async fn test_pool_reuses_connections_across_logs() {
+        use std::sync::atomic::Ordering;
+
+        let (base, connections) = counting_server().await;
+        let logs: Vec<String> = (0..10).map(|i| format!("{}/log{}/", base, i)).collect();
+        let config = HttpConfig::default();
+
+        // One client per log, as each monitor used to build: a connection per log
+        for log_url in &logs {
+            let client = config.build_client(&config.spec_for(log_url)).unwrap();
+            for _ in 0..3 {
+                client.get(format!("{}ct/v1/get-sth", log_url)).send().await.unwrap();
+            }
+        }
+        assert_eq!(connections.swap(0, Ordering::SeqCst), logs.len());
+
+        // Shared pool: every log on the host goes over one kept-alive connection
+        let pool = HttpClientPool::new(config);
+        for log_url in &logs {
+            let client = pool.client_for(log_url).unwrap();
+            for _ in 0..3 {
+                client.get(format!("{}ct/v1/get-sth", log_url)).send().await.unwrap();
+            }
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_per_log_timeout_gets_own_client() {
+        let pool = HttpClientPool::new(HttpConfig {
+            timeout_secs: Some(10),
+            per_log_timeout: HashMap::from([("https://slow.log/".to_string(), 120)]),
+            ..Default::default()
+        });
+
+        assert_eq!(pool.config.timeout_for("https://slow.log"), Duration::from_secs(120));
+        assert_eq!(pool.config.timeout_for("https://fast.log/"), Duration::from_secs(10));
+        assert_eq!(HttpConfig::default().timeout_for("https://fast.log/"), DEFAULT_TIMEOUT);
+
+        pool.client_for("https://fast.log/").unwrap();
+        pool.client().unwrap();
+        assert_eq!(pool.len(), 1);
+        pool.client_for("https://slow.log/").unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_rejects_invalid_proxy() {
+        let config = HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(config.client_for("https://a.log/").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+// src/ct_log/identity.rs
+//! Stable identity of a CT log across URL changes
+//!
+//! Log lists give every log a `log_id` (base64 SHA-256 of its key). State,
+//! health and dedupe are keyed by that id, so a log that moves to a new URL,
+//! or is configured once with and once without a trailing slash, stays one
+//! log. Logs without an id (custom logs) are keyed by their URL minus any
+//! trailing slash.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use super::log_list::SelectedLog;
+use crate::state::{LogProgress, StateBackend};
+
+/// A log URL without its trailing slash
+pub fn normalize_log_url(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+#[derive(Debug, Default)]
+struct IdMap {
+    /// Normalized URL → log_id
+    ids: HashMap<String, String>,
+    /// log_id → URL it was last listed under
+    urls: HashMap<String, String>,
+}
+
+/// URL → log_id mapping learned from the log lists, shared by everything keyed by log
+#[derive(Debug, Clone, Default)]
+pub struct LogIds {
+    inner: Arc<RwLock<IdMap>>,
+}
+
+impl LogIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that `url` serves the log `log_id`
+    pub fn register(&self, url: &str, log_id: &str) {
+        let mut map = self.inner.write().unwrap();
+        map.ids.insert(normalize_log_url(url).to_string(), log_id.to_string());
+        map.urls.insert(log_id.to_string(), url.to_string());
+    }
+
+    /// Register every selected log that has a log_id
+    pub fn register_logs(&self, logs: &[SelectedLog]) {
+        for log in logs {
+            if let Some(ref log_id) = log.log_id {
+                self.register(&log.url, log_id);
+            }
+        }
+    }
+
+    /// Key for a log: its log_id if known, else its normalized URL
+    ///
+    /// Keys map to themselves, so passing a key back in is harmless.
+    pub fn key(&self, url: &str) -> String {
+        let url = normalize_log_url(url);
+        let map = self.inner.read().unwrap();
+        map.ids.get(url).cloned().unwrap_or_else(|| url.to_string())
+    }
+
+    /// URL to show for a key (the key itself for URL-keyed logs)
+    pub fn display(&self, key: &str) -> String {
+        let map = self.inner.read().unwrap();
+        map.urls.get(key).cloned().unwrap_or_else(|| key.to_string())
+    }
+}
+
+/// State backend keyed by log identity instead of the URL it is called with
+///
+/// Entries saved under a log's URL by earlier versions (with or without the
+/// trailing slash) are moved to its key the first time the log is used; if
+/// the key already has an entry, the URL-keyed leftovers are dropped.
+pub struct KeyedState {
+    inner: Arc<dyn StateBackend>,
+    ids: LogIds,
+    /// Keys already checked for URL-keyed entries to move
+    migrated: Mutex<HashSet<String>>,
+}
+
+impl KeyedState {
+    pub fn new(inner: Arc<dyn StateBackend>, ids: LogIds) -> Self {
+        Self {
+            inner,
+            ids,
+            migrated: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Key for `log_url`, moving any URL-keyed state over on first use
+    async fn key(&self, log_url: &str) -> String {
+        let key = self.ids.key(log_url);
+        let mut migrated = self.migrated.lock().await;
+        if migrated.insert(key.clone()) {
+            let mut keyed = self.inner.get_last_index(&key).await.is_some();
+            let normalized = normalize_log_url(log_url);
+            let aliases = [log_url.to_string(), normalized.to_string(), format!("{}/", normalized)];
+            for alias in aliases.iter().filter(|alias| **alias != key) {
+                if !keyed {
+                    keyed = self.migrate(alias, &key).await;
+                } else if self.inner.get_last_index(alias).await.is_some() {
+                    self.inner.remove_log(alias).await;
+                    info!("Dropped stale state of {} (kept log key {})", alias, key);
+                }
+            }
+        }
+        key
+    }
+
+    /// Move the index, gaps and tree size saved under `from` to `to`
+    async fn migrate(&self, from: &str, to: &str) -> bool {
+        let Some(index) = self.inner.get_last_index(from).await else {
+            return false;
+        };
+        self.inner.update_index(to, index).await;
+        for (start, end) in self.inner.get_gaps(from).await {
+            self.inner.add_gap(to, start, end).await;
+        }
+        if let Some(tree_size) = self.inner.get_progress(from).await.tree_size {
+            self.inner.update_tree_size(to, tree_size).await;
+        }
+        self.inner.remove_log(from).await;
+        info!("Moved saved state of {} to log key {}", from, to);
+        true
+    }
+
+    /// Stored keys, with URL-keyed leftovers of migrated logs folded into their key
+    async fn keys(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut keys = Vec::new();
+        for stored in self.inner.get_tracked_logs().await {
+            let key = self.ids.key(&stored);
+            if seen.insert(key.clone()) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+#[async_trait]
+impl StateBackend for KeyedState {
+    async fn get_last_index(&self, log_url: &str) -> Option<u64> {
+        let key = self.key(log_url).await;
+        self.inner.get_last_index(&key).await
+    }
+
+    async fn update_index(&self, log_url: &str, index: u64) {
+        let key = self.key(log_url).await;
+        self.inner.update_index(&key, index).await
+    }
+
+    async fn save(&self) -> Result<()> {
+        self.inner.save().await
+    }
+
+    async fn get_tracked_logs(&self) -> Vec<String> {
+        self.keys().await.iter().map(|key| self.ids.display(key)).collect()
+    }
+
+    async fn count(&self) -> usize {
+        self.keys().await.len()
+    }
+
+    async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        let key = self.key(log_url).await;
+        self.inner.get_gaps(&key).await
+    }
+
+    async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        let key = self.key(log_url).await;
+        self.inner.add_gap(&key, start, end).await
+    }
+
+    async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        let key = self.key(log_url).await;
+        self.inner.remove_gap(&key, start, end).await
+    }
+
+    async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        let key = self.key(log_url).await;
+        self.inner.update_tree_size(&key, tree_size).await
+    }
+
+    async fn get_progress(&self, log_url: &str) -> LogProgress {
+        let key = self.key(log_url).await;
+        self.inner.get_progress(&key).await
+    }
+
+    async fn remove_log(&self, log_url: &str) {
+        let key = self.key(log_url).await;
+        self.inner.remove_log(&key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StateManager;
+
+    const LOG_ID: &str = "7s3QZNXbGs7FXLedtM0TojKHRny87N7DUUhZRnEftZs=";
+
+    #[test]
+    fn test_keys_by_log_id_or_normalized_url() {
+        let ids = LogIds::new();
+        ids.register("https://ct.example.com/2025/", LOG_ID);
+
+        assert_eq!(ids.key("https://ct.example.com/2025"), LOG_ID);
+        assert_eq!(ids.key("https://ct.example.com/2025/"), LOG_ID);
+        assert_eq!(ids.key(LOG_ID), LOG_ID);
+        assert_eq!(ids.display(LOG_ID), "https://ct.example.com/2025/");
+
+        assert_eq!(ids.key("https://custom.example.com/log/"), "https://custom.example.com/log");
+    }
+
+    #[tokio::test]
+    async fn test_keyed_state_moves_url_keyed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = Arc::new(StateManager::new(dir.path().join("state.toml")).await.unwrap());
+        inner.update_index("https://ct.example.com/2025/", 500).await;
+        inner.add_gap("https://ct.example.com/2025/", 10, 20).await;
+        inner.update_index("https://custom.example.com/log/", 7).await;
+
+        let ids = LogIds::new();
+        // The log has moved since the state was saved
+        ids.register("https://ct-new.example.com/2025/", LOG_ID);
+        ids.register("https://ct.example.com/2025/", LOG_ID);
+        let state = KeyedState::new(inner.clone(), ids.clone());
+
+        // Either URL (and either slash variant) finds the same state
+        assert_eq!(state.get_last_index("https://ct.example.com/2025").await, Some(500));
+        assert_eq!(state.get_gaps("https://ct-new.example.com/2025/").await, vec![(10, 20)]);
+        assert_eq!(inner.get_last_index(LOG_ID).await, Some(500));
+        assert_eq!(inner.get_last_index("https://ct.example.com/2025/").await, None);
+        assert!(inner.get_gaps("https://ct.example.com/2025/").await.is_empty());
+
+        state.update_index("https://ct-new.example.com/2025/", 600).await;
+        assert_eq!(state.get_last_index("https://ct.example.com/2025/").await, Some(600));
+
+        // Logs without an id only lose the trailing slash
+        assert_eq!(state.get_last_index("https://custom.example.com/log").await, Some(7));
+
+        assert_eq!(state.count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_state_drops_stale_url_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = Arc::new(StateManager::new(dir.path().join("state.toml")).await.unwrap());
+        inner.update_index(LOG_ID, 900).await;
+        inner.update_index("https://ct.example.com/2025/", 500).await;
+
+        let ids = LogIds::new();
+        ids.register("https://ct.example.com/2025/", LOG_ID);
+        let state = KeyedState::new(inner.clone(), ids);
+
+        // The log_id entry wins and the URL-keyed leftover goes away
+        assert_eq!(state.get_last_index("https://ct.example.com/2025/").await, Some(900));
+        assert_eq!(inner.get_tracked_logs().await, vec![LOG_ID.to_string()]);
+    }
+}
@@ -0,0 +1,182 @@
+// src/ct_log/log_client.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use super::client::{CtLogClient, RetryPolicy};
+use super::rate_limit::{RateLimiter, RequestBudget};
+use super::static_client::StaticCtClient;
+use super::types::{LogEntry, SignedTreeHead};
+
+/// API flavor spoken by a CT log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogType {
+    /// Classic RFC 6962 JSON API (get-sth / get-entries)
+    Rfc6962,
+    /// Static CT API (checkpoint + tiles), e.g. Sunlight
+    StaticCt,
+}
+
+impl std::fmt::Display for LogType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogType::Rfc6962 => write!(f, "RFC 6962"),
+            LogType::StaticCt => write!(f, "static-ct"),
+        }
+    }
+}
+
+/// Read access to a CT log, independent of the API flavor
+#[async_trait]
+pub trait LogClient: Send + Sync {
+    /// API flavor of this log
+    fn log_type(&self) -> LogType;
+
+    /// Current tree head, with retries
+    async fn get_sth_with_retry(&self, retry: RetryPolicy) -> Result<SignedTreeHead>;
+
+    /// Entries in `start..=end`, with retries
+    /// May return fewer entries than requested
+    async fn get_entries_with_retry(
+        &self,
+        start: u64,
+        end: u64,
+        retry: RetryPolicy,
+    ) -> Result<Vec<LogEntry>>;
+
+    /// Consistency proof between two tree sizes
+    async fn get_sth_consistency(&self, _first: u64, _second: u64) -> Result<Vec<Vec<u8>>> {
+        anyhow::bail!("{} logs do not serve consistency proofs", self.log_type())
+    }
+
+    /// Leaf index and inclusion proof for a leaf hash
+    async fn get_proof_by_hash(
+        &self,
+        _leaf_hash: &[u8],
+        _tree_size: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>)> {
+        anyhow::bail!("{} logs do not serve inclusion proofs", self.log_type())
+    }
+}
+
+#[async_trait]
+impl LogClient for CtLogClient {
+    fn log_type(&self) -> LogType {
+        LogType::Rfc6962
+    }
+
+    async fn get_sth_with_retry(&self, retry: RetryPolicy) -> Result<SignedTreeHead> {
+        CtLogClient::get_sth_with_retry(self, retry).await
+    }
+
+    async fn get_entries_with_retry(
+        &self,
+        start: u64,
+        end: u64,
+        retry: RetryPolicy,
+    ) -> Result<Vec<LogEntry>> {
+        CtLogClient::get_entries_with_retry(self, start, end, retry).await
+    }
+
+    async fn get_sth_consistency(&self, first: u64, second: u64) -> Result<Vec<Vec<u8>>> {
+        CtLogClient::get_sth_consistency(self, first, second).await
+    }
+
+    async fn get_proof_by_hash(
+        &self,
+        leaf_hash: &[u8],
+        tree_size: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>)> {
+        CtLogClient::get_proof_by_hash(self, leaf_hash, tree_size).await
+    }
+}
+
+/// Detect which API a log speaks and build the matching client
+///
+/// Static CT logs publish a checkpoint at `<url>/checkpoint`; anything else
+/// must answer RFC 6962 get-sth. Fails if neither responds, so detection can
+/// be retried on the next poll instead of locking in the wrong flavor.
+pub async fn detect_log_client(
+    log_url: &str,
+    http_client: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_budget: Option<RequestBudget>,
+) -> Result<Arc<dyn LogClient>> {
+    let mut static_client = StaticCtClient::with_http_client(log_url.to_string(), http_client.clone());
+    if let Some(ref limiter) = rate_limiter {
+        static_client = static_client.with_rate_limiter(Arc::clone(limiter));
+    }
+    if let Some(ref budget) = request_budget {
+        static_client = static_client.with_request_budget(budget.clone());
+    }
+
+    match static_client.get_checkpoint().await {
+        Ok(_) => {
+            info!("{}: Detected {} log", log_url, LogType::StaticCt);
+            return Ok(Arc::new(static_client));
+        }
+        Err(e) => debug!("{}: Not a static-ct log: {}", log_url, e),
+    }
+
+    let mut client = CtLogClient::with_http_client(log_url.to_string(), http_client);
+    if let Some(limiter) = rate_limiter {
+        client = client.with_rate_limiter(limiter);
+    }
+    if let Some(budget) = request_budget {
+        client = client.with_request_budget(budget);
+    }
+
+    client.get_sth().await?;
+    info!("{}: Detected {} log", log_url, LogType::Rfc6962);
+
+    Ok(Arc::new(client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_detects_static_ct_log() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/checkpoint"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "example.com/log\n5\nAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\n\n\u{2014} example.com/log AAAA\n",
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = detect_log_client(&mock_server.uri(), reqwest::Client::new(), None, None).await.unwrap();
+        assert_eq!(client.log_type(), LogType::StaticCt);
+    }
+
+    #[tokio::test]
+    async fn test_detects_rfc6962_log() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree_size": 5,
+                "timestamp": 1700000000000u64,
+                "sha256_root_hash": "",
+                "tree_head_signature": ""
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = detect_log_client(&mock_server.uri(), reqwest::Client::new(), None, None).await.unwrap();
+        assert_eq!(client.log_type(), LogType::Rfc6962);
+    }
+
+    #[tokio::test]
+    async fn test_detection_fails_when_log_unreachable() {
+        let mock_server = MockServer::start().await;
+        assert!(detect_log_client(&mock_server.uri(), reqwest::Client::new(), None, None).await.is_err());
+    }
+}
@@ -1,13 +1,139 @@
 // src/ct_log/log_list.rs
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use tracing::{info, debug};
+use std::collections::HashSet;
+use tracing::{debug, info, warn};
+
+use super::identity::{LogIds, normalize_log_url};
+use super::types::{LogInfo, LogListV3, TemporalInterval};
+
+/// Include/exclude patterns for picking logs by operator name or description
+/// Patterns are case-insensitive substrings; empty include lists match everything
+#[derive(Debug, Clone, Default)]
+pub struct LogSelection {
+    pub include_operators: Vec<String>,
+    pub exclude_operators: Vec<String>,
+    pub include_descriptions: Vec<String>,
+    pub exclude_descriptions: Vec<String>,
+}
+
+impl LogSelection {
+    fn matches_any(patterns: &[String], value: &str) -> bool {
+        let value = value.to_lowercase();
+        patterns.iter().any(|p| value.contains(&p.to_lowercase()))
+    }
+
+    /// Whether a log run by `operator` with `description` should be monitored
+    pub fn allows(&self, operator: &str, description: &str) -> bool {
+        if !self.include_operators.is_empty() && !Self::matches_any(&self.include_operators, operator) {
+            return false;
+        }
+        if !self.include_descriptions.is_empty()
+            && !Self::matches_any(&self.include_descriptions, description)
+        {
+            return false;
+        }
+
+        !Self::matches_any(&self.exclude_operators, operator)
+            && !Self::matches_any(&self.exclude_descriptions, description)
+    }
+}
 
-use super::types::LogListV3;
+/// A log picked from the log list, with the metadata it was selected on
+#[derive(Debug, Clone)]
+pub struct SelectedLog {
+    pub url: String,
+    pub log_id: Option<String>,  // Base64 SHA-256 of the log key, from the log list
+    pub operator: String,
+    pub description: String,
+    pub state: &'static str,  // "usable", "readonly", ..., or "additional" for config-added logs
+    pub temporal_interval: Option<TemporalInterval>,
+}
+
+impl SelectedLog {
+    /// Entry for a log added through `additional_logs` rather than a log list
+    fn additional(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            log_id: None,
+            operator: String::new(),
+            description: String::new(),
+            state: "additional",
+            temporal_interval: None,
+        }
+    }
+}
+
+/// Validators of a cached log list, sent back to revalidate it
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheMeta {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// On-disk copy of one log list: `<hash>.json` plus `<hash>.meta.json`
+struct ListCache {
+    body_path: PathBuf,
+    meta_path: PathBuf,
+}
+
+impl ListCache {
+    fn new(dir: &Path, list_url: &str) -> Self {
+        let name = &hex::encode(Sha256::digest(list_url.as_bytes()))[..16];
+        Self {
+            body_path: dir.join(format!("{}.json", name)),
+            meta_path: dir.join(format!("{}.meta.json", name)),
+        }
+    }
+
+    /// Validators of the cached copy, if there is one
+    async fn meta(&self) -> Option<CacheMeta> {
+        if !tokio::fs::try_exists(&self.body_path).await.unwrap_or(false) {
+            return None;
+        }
+        let meta = tokio::fs::read(&self.meta_path).await.ok()?;
+        serde_json::from_slice(&meta).ok()
+    }
+
+    async fn load(&self) -> Result<LogListV3> {
+        let body = tokio::fs::read(&self.body_path)
+            .await
+            .with_context(|| format!("Failed to read cached log list {:?}", self.body_path))?;
+        serde_json::from_slice(&body).context("Failed to parse cached log list JSON")
+    }
+
+    /// Replace the cached copy, body first so the validators never describe an older body
+    async fn store(&self, body: &[u8], meta: &CacheMeta) -> Result<()> {
+        if let Some(dir) = self.body_path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .with_context(|| format!("Failed to create log list cache directory {:?}", dir))?;
+        }
+        for (path, contents) in [(&self.body_path, body.to_vec()), (&self.meta_path, serde_json::to_vec(meta)?)] {
+            let temp_path = path.with_extension("tmp");
+            tokio::fs::write(&temp_path, contents)
+                .await
+                .with_context(|| format!("Failed to write {:?}", temp_path))?;
+            tokio::fs::rename(&temp_path, path)
+                .await
+                .with_context(|| format!("Failed to rename {:?}", temp_path))?;
+        }
+        Ok(())
+    }
+}
 
 /// Fetches and filters Google's CT log list
 pub struct LogListFetcher {
     http_client: reqwest::Client,
+    skip_expired_shards: bool,
+    selection: LogSelection,
+    cache_dir: Option<PathBuf>,
 }
 
 impl LogListFetcher {
@@ -18,27 +144,102 @@ impl LogListFetcher {
             .build()
             .unwrap();
 
-        Self { http_client }
+        Self {
+            http_client,
+            skip_expired_shards: true,
+            selection: LogSelection::default(),
+            cache_dir: None,
+        }
     }
 
-    /// Fetch CT logs from Google's log list
-    /// Returns list of log URLs ready to monitor
-    ///
-    /// # Arguments
-    /// * `list_url` - URL to Google's CT log list (usually v3/all_logs_list.json)
-    /// * `include_readonly` - Whether to include readonly logs (frozen but may have recent entries)
-    /// * `include_pending` - Whether to include pending logs (like gungnir does)
-    /// * `include_all` - Whether to include ALL logs regardless of state (retired, rejected, etc.)
-    pub async fn fetch_usable_logs(&self, list_url: &str, include_readonly: bool, include_pending: bool, include_all: bool) -> Result<Vec<String>> {
+    /// Keep a copy of each downloaded list in `dir`, revalidated with ETag /
+    /// Last-Modified and used whenever the list can't be downloaded
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Send log list requests through a preconfigured HTTP client (e.g. with a proxy)
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Only keep logs matching the given operator/description patterns
+    pub fn with_selection(mut self, selection: LogSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Set whether logs whose temporal shard window has ended are skipped
+    pub fn with_skip_expired_shards(mut self, skip: bool) -> Self {
+        self.skip_expired_shards = skip;
+        self
+    }
+
+    /// Monitoring priority for a log based on its temporal shard (lower = first)
+    /// The shard covering "now" receives new certificates, so it goes first;
+    /// non-sharded logs next, then future shards, then expired ones
+    fn shard_priority(log: &LogInfo, now: DateTime<Utc>) -> u8 {
+        match &log.temporal_interval {
+            Some(interval) if interval.covers(now) => 0,
+            None => 1,
+            Some(interval) if interval.is_future(now) => 2,
+            Some(_) => 3,
+        }
+    }
+
+    /// Download and parse a log list (Google v3 schema, also used by Apple)
+    /// Falls back to the cached copy, if any, when the download fails
+    async fn fetch_log_list(&self, list_url: &str) -> Result<LogListV3> {
         info!("Fetching CT log list from {}", list_url);
 
-        let response = self
-            .http_client
-            .get(list_url)
+        let cache = self.cache_dir.as_deref().map(|dir| ListCache::new(dir, list_url));
+        let e = match self.download_log_list(list_url, cache.as_ref()).await {
+            Ok(log_list) => return Ok(log_list),
+            Err(e) => e,
+        };
+
+        match cache {
+            Some(cache) => match cache.load().await {
+                Ok(log_list) => {
+                    warn!("Using cached copy of CT log list {} ({:#})", list_url, e);
+                    Ok(log_list)
+                }
+                Err(cache_error) => {
+                    debug!("No usable cached copy of {}: {:#}", list_url, cache_error);
+                    Err(e)
+                }
+            },
+            None => Err(e),
+        }
+    }
+
+    async fn download_log_list(&self, list_url: &str, cache: Option<&ListCache>) -> Result<LogListV3> {
+        let mut request = self.http_client.get(list_url);
+        if let Some(cache) = cache
+            && let Some(meta) = cache.meta().await
+        {
+            if let Some(ref etag) = meta.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(ref last_modified) = meta.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request
             .send()
             .await
             .context("Failed to fetch CT log list")?;
 
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED
+            && let Some(cache) = cache
+        {
+            debug!("CT log list {} not modified, using cached copy", list_url);
+            return cache.load().await;
+        }
+
         if !response.status().is_success() {
             anyhow::bail!(
                 "Failed to fetch log list: HTTP {}",
@@ -46,19 +247,104 @@ impl LogListFetcher {
             );
         }
 
-        let log_list: LogListV3 = response
-            .json()
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let meta = CacheMeta {
+            url: list_url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+
+        let body = response.bytes().await.context("Failed to read log list")?;
+        let log_list = serde_json::from_slice(&body).context("Failed to parse log list JSON")?;
+
+        if let Some(cache) = cache
+            && let Err(e) = cache.store(&body, &meta).await
+        {
+            warn!("Failed to cache CT log list {}: {:#}", list_url, e);
+        }
+
+        Ok(log_list)
+    }
+
+    /// Fetch CT logs from Google's log list
+    /// Returns list of log URLs ready to monitor
+    ///
+    /// # Arguments
+    /// * `list_url` - URL to Google's CT log list (usually v3/all_logs_list.json)
+    /// * `include_readonly` - Whether to include readonly logs (frozen but may have recent entries)
+    /// * `include_pending` - Whether to include pending logs (like gungnir does)
+    /// * `include_all` - Whether to include ALL logs regardless of state (retired, rejected, etc.)
+    pub async fn fetch_usable_logs(&self, list_url: &str, include_readonly: bool, include_pending: bool, include_all: bool) -> Result<Vec<String>> {
+        self.fetch_usable_logs_from(&[list_url.to_string()], include_readonly, include_pending, include_all)
             .await
-            .context("Failed to parse log list JSON")?;
+    }
 
+    /// Fetch and merge several log lists (e.g. Google, Apple, an internal mirror)
+    ///
+    /// Lists are in priority order: when a log (matched by log ID or URL) appears
+    /// in more than one list, the first list containing it decides its state.
+    /// A list that fails to download is skipped; it's an error only if all fail.
+    pub async fn fetch_usable_logs_from(&self, list_urls: &[String], include_readonly: bool, include_pending: bool, include_all: bool) -> Result<Vec<String>> {
+        let logs = self
+            .select_logs_from(list_urls, include_readonly, include_pending, include_all)
+            .await?;
+        Ok(logs.into_iter().map(|log| log.url).collect())
+    }
+
+    /// Same selection as [`Self::fetch_usable_logs_from`], keeping each log's operator, state and shard window
+    pub async fn select_logs_from(&self, list_urls: &[String], include_readonly: bool, include_pending: bool, include_all: bool) -> Result<Vec<SelectedLog>> {
+        let mut log_lists = Vec::new();
+        for list_url in list_urls {
+            match self.fetch_log_list(list_url).await {
+                Ok(log_list) => log_lists.push(log_list),
+                Err(e) => warn!("Skipping CT log list {}: {:#}", list_url, e),
+            }
+        }
+
+        if log_lists.is_empty() {
+            anyhow::bail!("Failed to fetch any of {} CT log list(s)", list_urls.len());
+        }
+
+        let now = Utc::now();
         let mut acceptable_logs = Vec::new();
+        let mut skipped_expired = 0;
+        let mut skipped_selection = 0;
+        let mut skipped_duplicates = 0;
+        let mut seen: HashSet<String> = HashSet::new();
+
+        for operator in log_lists.iter().flat_map(|list| &list.operators) {
+            for log in operator.logs.iter().chain(&operator.tiled_logs) {
+                let url = log.monitor_url();
+
+                // Earlier lists win conflicts, whether or not they accepted the log
+                let keys: Vec<String> = log
+                    .log_id
+                    .iter()
+                    .cloned()
+                    .chain(Some(url.trim_end_matches('/').to_string()).filter(|u| !u.is_empty()))
+                    .collect();
+                if keys.iter().any(|key| seen.contains(key)) {
+                    skipped_duplicates += 1;
+                    continue;
+                }
+                seen.extend(keys);
+
+                if !self.selection.allows(&operator.name, &log.description) {
+                    debug!("Skipping deselected log: {} / {} ({})", operator.name, log.description, url);
+                    skipped_selection += 1;
+                    continue;
+                }
 
-        for operator in &log_list.operators {
-            for log in &operator.logs {
                 // Determine if this log should be included
                 let is_acceptable = if include_all {
                     // Include ALL logs with URLs
-                    !log.url.is_empty()
+                    !url.is_empty()
                 } else {
                     // Filter by state
                     match &log.state {
@@ -67,7 +353,15 @@ impl LogListFetcher {
                     }
                 };
 
-                if is_acceptable && !log.url.is_empty() {
+                if is_acceptable && !url.is_empty() {
+                    if self.skip_expired_shards
+                        && log.temporal_interval.as_ref().is_some_and(|i| i.is_expired(now))
+                    {
+                        debug!("Skipping expired shard: {} ({})", log.description, url);
+                        skipped_expired += 1;
+                        continue;
+                    }
+
                     let state_desc = if let Some(state) = &log.state {
                         if state.is_usable() {
                             "usable"
@@ -90,19 +384,36 @@ impl LogListFetcher {
                         "Found {} log: {} ({})",
                         state_desc,
                         log.description,
-                        log.url
+                        url
                     );
-                    acceptable_logs.push(log.url.clone());
+                    acceptable_logs.push((
+                        Self::shard_priority(log, now),
+                        SelectedLog {
+                            url: url.to_string(),
+                            log_id: log.log_id.clone(),
+                            operator: operator.name.clone(),
+                            description: log.description.clone(),
+                            state: state_desc,
+                            temporal_interval: log.temporal_interval.clone(),
+                        },
+                    ));
                 }
             }
         }
 
+        // Stable sort keeps list order within each priority
+        acceptable_logs.sort_by_key(|(priority, _)| *priority);
+        let acceptable_logs: Vec<SelectedLog> = acceptable_logs.into_iter().map(|(_, log)| log).collect();
+
         info!(
-            "Found {} acceptable CT logs (readonly={}, pending={}, all={})",
+            "Found {} acceptable CT logs (readonly={}, pending={}, all={}, skipped {} expired shards, {} by selection, {} duplicates)",
             acceptable_logs.len(),
             include_readonly,
             include_pending,
-            include_all
+            include_all,
+            skipped_expired,
+            skipped_selection,
+            skipped_duplicates
         );
 
         Ok(acceptable_logs)
@@ -119,14 +430,14 @@ impl LogListFetcher {
     /// * `additional_logs` - Additional log URLs to add to the list
     pub async fn fetch_logs_with_additional(
         &self,
-        list_url: &str,
+        list_urls: &[String],
         include_readonly: bool,
         include_pending: bool,
         include_all: bool,
         additional_logs: &[String],
     ) -> Result<Vec<String>> {
-        // Fetch logs from Google's list
-        let mut logs = self.fetch_usable_logs(list_url, include_readonly, include_pending, include_all).await?;
+        // Fetch logs from the configured lists
+        let mut logs = self.fetch_usable_logs_from(list_urls, include_readonly, include_pending, include_all).await?;
 
         // Add additional logs
         for log_url in additional_logs {
@@ -163,9 +474,9 @@ impl LogListFetcher {
         let mut all_logs = Vec::new();
 
         for operator in &log_list.operators {
-            for log in &operator.logs {
-                if !log.url.is_empty() {
-                    all_logs.push(log.url.clone());
+            for log in operator.logs.iter().chain(&operator.tiled_logs) {
+                if !log.monitor_url().is_empty() {
+                    all_logs.push(log.monitor_url().to_string());
                 }
             }
         }
@@ -182,6 +493,62 @@ impl Default for LogListFetcher {
     }
 }
 
+/// Everything needed to (re-)build the set of logs to monitor from the log list
+pub struct LogListSource {
+    pub fetcher: LogListFetcher,
+    pub list_urls: Vec<String>,  // In priority order
+    pub include_readonly: bool,
+    pub include_pending: bool,
+    pub include_all: bool,
+    pub additional_logs: Option<Vec<String>>,
+    pub max_logs: Option<usize>,  // Drop logs past this many (None = keep all)
+    pub log_ids: LogIds,  // Filled with the log_ids of fetched logs
+}
+
+impl LogListSource {
+    /// Fetch the list, merge additional logs and apply the `max_logs` limit, if any
+    pub async fn fetch(&self) -> Result<Vec<String>> {
+        Ok(self.fetch_selected().await?.into_iter().map(|log| log.url).collect())
+    }
+
+    /// Like [`Self::fetch`], but with the list metadata for each log
+    ///
+    /// The log_ids of the selected logs are registered in `log_ids`.
+    pub async fn fetch_selected(&self) -> Result<Vec<SelectedLog>> {
+        let mut logs = self
+            .fetcher
+            .select_logs_from(
+                &self.list_urls,
+                self.include_readonly,
+                self.include_pending,
+                self.include_all,
+            )
+            .await?;
+
+        for log_url in self.additional_logs.iter().flatten() {
+            let normalized = normalize_log_url(log_url);
+            if !normalized.is_empty() && !logs.iter().any(|log| normalize_log_url(&log.url) == normalized) {
+                info!("Adding additional log: {}", log_url);
+                logs.push(SelectedLog::additional(log_url));
+            }
+        }
+
+        if let Some(max_logs) = self.max_logs
+            && logs.len() > max_logs
+        {
+            warn!(
+                "max_concurrent_logs = {} leaves {} of {} logs unmonitored; use max_concurrent_requests to bound load instead",
+                max_logs,
+                logs.len() - max_logs,
+                logs.len()
+            );
+            logs.truncate(max_logs);
+        }
+        self.log_ids.register_logs(&logs);
+        Ok(logs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +591,207 @@ mod tests {
         println!("Found {} logs (readonly=false, pending=false, all=true)", all_logs.len());
         assert!(all_logs.len() >= logs_with_readonly.len(), "Should find most logs when including all");
     }
+
+    #[test]
+    fn test_shard_priority() {
+        use crate::ct_log::types::TemporalInterval;
+
+        let now = Utc::now();
+        let shard = |start: &str, end: &str| LogInfo {
+            description: String::new(),
+            log_id: None,
+            key: None,
+            url: "https://ct.example.com/".to_string(),
+            mmd: None,
+            state: None,
+            temporal_interval: Some(TemporalInterval {
+                start_inclusive: Some(start.to_string()),
+                end_exclusive: Some(end.to_string()),
+            }),
+            monitoring_url: None,
+        };
+
+        let current = shard("2000-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+        let future = shard("2998-01-01T00:00:00Z", "2999-01-01T00:00:00Z");
+        let expired = shard("2000-01-01T00:00:00Z", "2001-01-01T00:00:00Z");
+        let unsharded = LogInfo { temporal_interval: None, ..current.clone() };
+
+        assert_eq!(LogListFetcher::shard_priority(&current, now), 0);
+        assert_eq!(LogListFetcher::shard_priority(&unsharded, now), 1);
+        assert_eq!(LogListFetcher::shard_priority(&future, now), 2);
+        assert_eq!(LogListFetcher::shard_priority(&expired, now), 3);
+    }
+
+    #[test]
+    fn test_log_selection() {
+        let selection = LogSelection {
+            include_operators: vec!["google".to_string(), "Cloudflare".to_string()],
+            exclude_descriptions: vec!["solera".to_string()],
+            ..Default::default()
+        };
+
+        assert!(selection.allows("Google", "Google 'Argon2025h1' log"));
+        assert!(selection.allows("Cloudflare", "Cloudflare 'Nimbus2025'"));
+        assert!(!selection.allows("Sectigo", "Sectigo 'Mammoth2025h1'"));
+        assert!(!selection.allows("Google", "Google 'Solera2025h1' log"));
+
+        // No patterns means everything is selected
+        assert!(LogSelection::default().allows("Anyone", "Any log"));
+    }
+
+    #[tokio::test]
+    async fn test_merged_lists_first_list_wins() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let usable = serde_json::json!({"usable": {"timestamp": "2024-01-01T00:00:00Z"}});
+        let retired = serde_json::json!({"retired": {"timestamp": "2024-01-01T00:00:00Z"}});
+
+        Mock::given(method("GET"))
+            .and(path("/google.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "operators": [{"name": "A", "logs": [
+                    {"log_id": "shared", "url": "https://a.example.com/log/", "state": retired},
+                    {"log_id": "g-only", "url": "https://g.example.com/log/", "state": usable}
+                ]}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/apple.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "operators": [{"name": "A", "logs": [
+                    {"log_id": "shared", "url": "https://a.example.com/log", "state": usable},
+                    {"log_id": "a-only", "url": "https://apple.example.com/log/", "state": usable}
+                ]}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let fetcher = LogListFetcher::new();
+        let lists = vec![
+            format!("{}/google.json", mock_server.uri()),
+            format!("{}/missing.json", mock_server.uri()),
+            format!("{}/apple.json", mock_server.uri()),
+        ];
+        let logs = fetcher
+            .fetch_usable_logs_from(&lists, false, false, false)
+            .await
+            .unwrap();
+
+        // The retired state from the first list wins; the unreachable list is skipped
+        assert_eq!(
+            logs,
+            vec!["https://g.example.com/log/", "https://apple.example.com/log/"]
+        );
+
+        let none = vec![format!("{}/missing.json", mock_server.uri())];
+        assert!(fetcher.fetch_usable_logs_from(&none, false, false, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_selected_keeps_metadata() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/list.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "operators": [{"name": "Example", "logs": [
+                    {"description": "Example 2999", "url": "https://ct.example.com/2999/",
+                     "log_id": "MjAyOQ==",
+                     "state": {"usable": {"timestamp": "2024-01-01T00:00:00Z"}},
+                     "temporal_interval": {"start_inclusive": "2998-01-01T00:00:00Z",
+                                           "end_exclusive": "2999-01-01T00:00:00Z"}},
+                    {"description": "Example Pending", "url": "https://ct.example.com/next/",
+                     "state": {"pending": {"timestamp": "2024-01-01T00:00:00Z"}}}
+                ]}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let source = LogListSource {
+            fetcher: LogListFetcher::new(),
+            list_urls: vec![format!("{}/list.json", mock_server.uri())],
+            include_readonly: false,
+            include_pending: true,
+            include_all: false,
+            // The first is already listed, only without the trailing slash
+            additional_logs: Some(vec!["https://ct.example.com/2999".to_string(), "https://extra.example.net/".to_string()]),
+            max_logs: None,
+            log_ids: LogIds::default(),
+        };
+        let logs = source.fetch_selected().await.unwrap();
+
+        let summary: Vec<(&str, &str, &str)> = logs
+            .iter()
+            .map(|log| (log.operator.as_str(), log.state, log.url.as_str()))
+            .collect();
+        // Unsharded logs sort before future shards; additional logs come last
+        assert_eq!(
+            summary,
+            vec![
+                ("Example", "pending", "https://ct.example.com/next/"),
+                ("Example", "usable", "https://ct.example.com/2999/"),
+                ("", "additional", "https://extra.example.net/"),
+            ]
+        );
+        assert!(logs[1].temporal_interval.is_some());
+        assert_eq!(source.log_ids.key("https://ct.example.com/2999"), "MjAyOQ==");
+        assert_eq!(source.fetch().await.unwrap().len(), 3);
+
+        // max_logs only trims when it is set
+        let limited = LogListSource { max_logs: Some(2), ..source };
+        assert_eq!(limited.fetch().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_list_revalidated_and_used_offline() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/list.json"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/list.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(serde_json::json!({
+                        "operators": [{"name": "A", "logs": [
+                            {"url": "https://a.example.com/log/",
+                             "state": {"usable": {"timestamp": "2024-01-01T00:00:00Z"}}}
+                        ]}]
+                    })),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let fetcher = LogListFetcher::new().with_cache_dir(cache_dir.path());
+        let lists = vec![format!("{}/list.json", mock_server.uri())];
+
+        // Downloaded and cached, then revalidated with the ETag
+        for _ in 0..2 {
+            let logs = fetcher.fetch_usable_logs_from(&lists, false, false, false).await.unwrap();
+            assert_eq!(logs, vec!["https://a.example.com/log/"]);
+        }
+
+        // With the server gone the cached copy is used
+        let offline = vec!["http://127.0.0.1:1/list.json".to_string()];
+        assert!(fetcher.fetch_usable_logs_from(&offline, false, false, false).await.is_err());
+        drop(mock_server);
+        let logs = fetcher.fetch_usable_logs_from(&lists, false, false, false).await.unwrap();
+        assert_eq!(logs, vec!["https://a.example.com/log/"]);
+    }
 }
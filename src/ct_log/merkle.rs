@@ -0,0 +1,273 @@
+// src/ct_log/merkle.rs
+//! RFC 6962 Merkle tree hashing and proof verification
+//!
+//! Verification follows the algorithms in RFC 9162 section 2.1.3.2
+//! (inclusion) and 2.1.4.2 (consistency), which are equivalent to the
+//! RFC 6962 definitions but iterative.
+
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+/// Hash of a leaf: SHA-256(0x00 || leaf_input)
+pub fn leaf_hash(leaf_input: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(leaf_input);
+    hasher.finalize().into()
+}
+
+/// Hash of an interior node: SHA-256(0x01 || left || right)
+pub fn node_hash(left: &[u8], right: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Convert a proof element into a fixed-size hash
+fn to_hash(bytes: &[u8]) -> Option<Hash> {
+    bytes.try_into().ok()
+}
+
+/// Right-shift both values until the low bit of `fn_` is set or it reaches zero
+fn shift_until_odd(fn_: &mut u64, sn: &mut u64) {
+    while *fn_ & 1 == 0 && *fn_ != 0 {
+        *fn_ >>= 1;
+        *sn >>= 1;
+    }
+}
+
+/// Verify that `leaf` is at `leaf_index` in the tree of `tree_size` with `root`
+pub fn verify_inclusion(
+    leaf: &Hash,
+    leaf_index: u64,
+    tree_size: u64,
+    proof: &[Vec<u8>],
+    root: &Hash,
+) -> bool {
+    if leaf_index >= tree_size {
+        return false;
+    }
+
+    let mut fn_ = leaf_index;
+    let mut sn = tree_size - 1;
+    let mut r = *leaf;
+
+    for p in proof {
+        if sn == 0 {
+            return false;
+        }
+
+        if fn_ & 1 == 1 || fn_ == sn {
+            r = node_hash(p, &r);
+            if fn_ & 1 == 0 {
+                shift_until_odd(&mut fn_, &mut sn);
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    sn == 0 && r == *root
+}
+
+/// Verify that the tree of `second_size` is an append-only extension of `first_size`
+pub fn verify_consistency(
+    first_size: u64,
+    second_size: u64,
+    first_root: &Hash,
+    second_root: &Hash,
+    proof: &[Vec<u8>],
+) -> bool {
+    if first_size > second_size {
+        return false;
+    }
+
+    if first_size == second_size {
+        return proof.is_empty() && first_root == second_root;
+    }
+
+    // Every tree is consistent with the empty tree
+    if first_size == 0 {
+        return proof.is_empty();
+    }
+
+    let mut path: Vec<Hash> = Vec::with_capacity(proof.len() + 1);
+    if first_size.is_power_of_two() {
+        path.push(*first_root);
+    }
+    for p in proof {
+        match to_hash(p) {
+            Some(h) => path.push(h),
+            None => return false,
+        }
+    }
+
+    let Some((&first, rest)) = path.split_first() else {
+        return false;
+    };
+
+    let mut fn_ = first_size - 1;
+    let mut sn = second_size - 1;
+    while fn_ & 1 == 1 {
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    let mut fr = first;
+    let mut sr = first;
+
+    for c in rest {
+        if sn == 0 {
+            return false;
+        }
+
+        if fn_ & 1 == 1 || fn_ == sn {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            if fn_ & 1 == 0 {
+                shift_until_odd(&mut fn_, &mut sn);
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    sn == 0 && fr == *first_root && sr == *second_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference implementations straight from RFC 6962 section 2.1
+
+    fn split(n: usize) -> usize {
+        let mut k = 1;
+        while k * 2 < n {
+            k *= 2;
+        }
+        k
+    }
+
+    fn mth(leaves: &[Vec<u8>]) -> Hash {
+        match leaves.len() {
+            0 => Sha256::digest([]).into(),
+            1 => leaf_hash(&leaves[0]),
+            n => {
+                let k = split(n);
+                node_hash(&mth(&leaves[..k]), &mth(&leaves[k..]))
+            }
+        }
+    }
+
+    fn path(m: usize, leaves: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let n = leaves.len();
+        if n <= 1 {
+            return Vec::new();
+        }
+        let k = split(n);
+        if m < k {
+            let mut p = path(m, &leaves[..k]);
+            p.push(mth(&leaves[k..]).to_vec());
+            p
+        } else {
+            let mut p = path(m - k, &leaves[k..]);
+            p.push(mth(&leaves[..k]).to_vec());
+            p
+        }
+    }
+
+    fn subproof(m: usize, leaves: &[Vec<u8>], complete: bool) -> Vec<Vec<u8>> {
+        let n = leaves.len();
+        if m == n {
+            return if complete { Vec::new() } else { vec![mth(leaves).to_vec()] };
+        }
+        let k = split(n);
+        if m <= k {
+            let mut p = subproof(m, &leaves[..k], complete);
+            p.push(mth(&leaves[k..]).to_vec());
+            p
+        } else {
+            let mut p = subproof(m - k, &leaves[k..], false);
+            p.push(mth(&leaves[..k]).to_vec());
+            p
+        }
+    }
+
+    fn leaves(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| format!("leaf-{}", i).into_bytes()).collect()
+    }
+
+    #[test]
+    fn test_inclusion_proofs_verify() {
+        for size in 1..=17 {
+            let tree = leaves(size);
+            let root = mth(&tree);
+            for index in 0..size {
+                let proof = path(index, &tree);
+                let leaf = leaf_hash(&tree[index]);
+                assert!(
+                    verify_inclusion(&leaf, index as u64, size as u64, &proof, &root),
+                    "inclusion failed for index {} in tree of {}",
+                    index,
+                    size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inclusion_rejects_tampering() {
+        let tree = leaves(10);
+        let root = mth(&tree);
+        let proof = path(3, &tree);
+        let leaf = leaf_hash(&tree[3]);
+
+        assert!(!verify_inclusion(&leaf, 4, 10, &proof, &root));
+        assert!(!verify_inclusion(&leaf_hash(b"forged"), 3, 10, &proof, &root));
+        assert!(!verify_inclusion(&leaf, 3, 10, &proof[..proof.len() - 1], &root));
+        assert!(!verify_inclusion(&leaf, 10, 10, &proof, &root));
+    }
+
+    #[test]
+    fn test_consistency_proofs_verify() {
+        for second in 1..=17 {
+            let tree = leaves(second);
+            let second_root = mth(&tree);
+            for first in 1..=second {
+                let first_root = mth(&tree[..first]);
+                let proof = subproof(first, &tree, true);
+                assert!(
+                    verify_consistency(first as u64, second as u64, &first_root, &second_root, &proof),
+                    "consistency failed for {} -> {}",
+                    first,
+                    second
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_consistency_rejects_forked_tree() {
+        let tree = leaves(12);
+        let first_root = mth(&tree[..7]);
+        let proof = subproof(7, &tree, true);
+
+        let mut forked = tree.clone();
+        forked[2] = b"rewritten".to_vec();
+        let forked_root = mth(&forked);
+
+        assert!(!verify_consistency(7, 12, &first_root, &forked_root, &proof));
+        assert!(!verify_consistency(12, 7, &first_root, &forked_root, &proof));
+        assert!(!verify_consistency(7, 7, &first_root, &forked_root, &[]));
+    }
+}
@@ -1,12 +1,38 @@
 // src/ct_log/mod.rs
+pub mod audit;
+pub mod checkpoint;
 pub mod client;
 pub mod coordinator;
 pub mod health;
+pub mod http;
+pub mod identity;
+pub mod log_client;
 pub mod log_list;
+pub mod merkle;
 pub mod monitor;
+pub mod pipeline;
+pub mod probe;
+pub mod rate_limit;
+pub mod replay;
+pub mod schedule;
+pub mod source;
+pub mod static_client;
 pub mod types;
 
+pub use audit::{AuditConfig, AuditFailure, MerkleAuditor};
+pub use checkpoint::Checkpoints;
+pub use client::{RetryConfig, RetryPolicy};
 pub use coordinator::CtLogCoordinator;
-pub use health::{LogHealth, LogHealthTracker};
-pub use log_list::LogListFetcher;
+pub use health::{CircuitState, LogHealth, LogHealthTracker, QuarantineConfig};
+pub use http::{HttpClientPool, HttpConfig};
+pub use identity::{KeyedState, LogIds, normalize_log_url};
+pub use log_client::{LogClient, LogType, detect_log_client};
+pub use log_list::{LogListFetcher, LogListSource, LogSelection, SelectedLog};
+pub use monitor::LogMonitorConfig;
+pub use pipeline::{CertSender, ChannelConfig, Delivery, SaturationPolicy, SpillFile};
+pub use probe::{LogCheckPolicy, check_logs, probe_log};
+pub use rate_limit::{RateLimitConfig, RateLimiter, RequestBudget};
+pub use schedule::{AdaptivePollConfig, PollScheduler};
+pub use source::{CertSource, LogFollow, LogScan, ReplaySource};
+pub use static_client::StaticCtClient;
 pub use types::{LogEntry, LogInfo, LogListV3, SignedTreeHead};
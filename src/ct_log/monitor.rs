@@ -1,14 +1,27 @@
 // src/ct_log/monitor.rs
 use anyhow::{Context, Result};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{mpsc, watch};
+use futures_util::future::join_all;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OnceCell, watch};
 use tracing::{debug, error, info, warn};
 
-use super::client::CtLogClient;
-use super::health::LogHealthTracker;
+use super::audit::{AuditConfig, AuditFailure, MerkleAuditor};
+use super::checkpoint::Checkpoints;
+use super::client::{RetryConfig, RetryPolicy};
+use super::health::{LogHealthTracker, QuarantineConfig};
+use super::http::{HttpClientPool, HttpConfig};
+use super::identity::LogIds;
+use super::log_client::{LogClient, LogType, detect_log_client};
+use super::pipeline::{CertSender, ChannelConfig, Delivery};
+use super::rate_limit::{RateLimitConfig, RateLimiter, RequestBudget};
+use super::schedule::{AdaptivePollConfig, PollScheduler};
+use super::types::{LogEntry, SignedTreeHead};
 use crate::cert_parser::CertificateParser;
-use crate::state::StateBackend;
+use crate::metrics;
+use crate::state::{StateBackend, unix_now};
+use crate::stats::StatsCollector;
 use crate::types::CertData;
 
 /// Configuration for single log monitor
@@ -17,40 +30,120 @@ pub struct LogMonitorConfig {
     pub poll_interval_secs: u64,
     pub batch_size: u64,
     pub parse_precerts: bool,
+    pub parallel_fetches: usize,  // Concurrent get-entries ranges when catching up
+    pub rate_limit: RateLimitConfig,
+    pub max_concurrent_requests: Option<usize>,  // Global in-flight request cap (None = unlimited)
+    pub audit: Option<AuditConfig>,  // None = proof auditing disabled
+    pub quarantine: Option<QuarantineConfig>,  // None = never quarantine failing logs
+    pub http: HttpConfig,  // Proxy and timeout settings for log requests
+    pub retry: RetryConfig,  // Attempts and backoff of failed log requests
+    pub channel: ChannelConfig,  // Coordinator channel capacity and saturation policy
+    pub adaptive_poll: Option<AdaptivePollConfig>,  // None = always wait poll_interval_secs
+    pub include_pem: bool,  // Attach the leaf certificate PEM to matches
+    pub since_ms: Option<u64>,  // Start at the first entry logged at/after this time (None = saved index)
+    pub stale_after_secs: Option<u64>,  // Warn once the saved index is this old (None = never)
+    pub commit_batch: u64,  // Handled entries per saved-index update
+    pub log_ids: LogIds,  // URL → log_id mapping that health tracking is keyed by
+}
+
+/// How sent entries are checkpointed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tracking {
+    /// New entries: the saved index moves past them once handled
+    Advance,
+    /// A recovered gap chunk: the gap shrinks once it is handled
+    Gap,
+    /// Scans leave the saved index and gaps alone
+    Untracked,
 }
 
 /// Monitors a single CT log for new entries
 pub struct LogMonitor {
     log_url: String,
-    client: CtLogClient,
+    /// Detected on first successful poll (RFC 6962 or static-ct)
+    client: OnceCell<Arc<dyn LogClient>>,
+    http_client: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_budget: Option<RequestBudget>,
+    retry: RetryPolicy,
     state_manager: Arc<dyn StateBackend>,
+    /// Fetch position, and the saved index trailing it
+    checkpoints: Arc<Checkpoints>,
     health_tracker: Arc<LogHealthTracker>,
+    stats: StatsCollector,
+    auditor: Option<MerkleAuditor>,
+    scheduler: Mutex<PollScheduler>,
+    /// `since_ms` start index not located yet
+    since_pending: AtomicBool,
+    /// Stale state already reported; re-armed once the index advances
+    stale_warned: AtomicBool,
     config: LogMonitorConfig,
 }
 
 impl LogMonitor {
     /// Create new log monitor
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         log_url: String,
         state_manager: Arc<dyn StateBackend>,
+        checkpoints: Arc<Checkpoints>,
         health_tracker: Arc<LogHealthTracker>,
+        stats: StatsCollector,
+        request_budget: Option<RequestBudget>,
+        http_pool: &HttpClientPool,
         config: LogMonitorConfig,
     ) -> Result<Self> {
-        let client = CtLogClient::new(log_url.clone())?;
+        let rate_limiter = config.rate_limit.limiter_for(&log_url);
+        if rate_limiter.is_some() {
+            debug!("{}: Rate limiting enabled", log_url);
+        }
+
+        let http_client = http_pool.client_for(&log_url)?;
+        if let Some(proxy) = config.http.proxy_for(&log_url) {
+            debug!("{}: Using proxy {}", log_url, proxy);
+        }
+        let retry = config.retry.policy_for(&log_url);
+
+        let auditor = config
+            .audit
+            .clone()
+            .map(|audit| MerkleAuditor::new(log_url.clone(), audit));
+
+        // Aim for roughly one full round of fetches per poll
+        let scheduler = PollScheduler::new(
+            Duration::from_secs(config.poll_interval_secs),
+            config.batch_size * config.parallel_fetches.max(1) as u64,
+            config.adaptive_poll.clone(),
+        );
 
         Ok(Self {
             log_url,
-            client,
+            client: OnceCell::new(),
+            http_client,
+            rate_limiter,
+            request_budget,
+            retry,
             state_manager,
+            checkpoints,
             health_tracker,
+            stats,
+            auditor,
+            scheduler: Mutex::new(scheduler),
+            since_pending: AtomicBool::new(config.since_ms.is_some()),
+            stale_warned: AtomicBool::new(false),
             config,
         })
     }
 
+    /// URL of the monitored log
+    pub fn log_url(&self) -> &str {
+        &self.log_url
+    }
+
     /// Main monitoring loop - continuously polls for new entries
     pub async fn run(
         &self,
-        cert_tx: mpsc::Sender<CertData>,
+        cert_tx: CertSender,
         mut shutdown_rx: watch::Receiver<bool>,
     ) {
         info!("Starting monitor for {}", self.log_url);
@@ -72,16 +165,23 @@ impl LogMonitor {
             }
 
             // Poll for new entries
+            let mut next_poll = poll_interval;
             match self.poll_once(&cert_tx).await {
                 Ok(()) => {
                     // Record successful poll
                     self.health_tracker.record_success(&self.log_url).await;
+
+                    next_poll = self.scheduler.lock().unwrap().next_interval();
+                    if next_poll != poll_interval {
+                        debug!("{}: Next poll in {:?}", self.log_url, next_poll);
+                    }
                 }
                 Err(e) => {
                     // Record failure
                     self.health_tracker
                         .record_failure(&self.log_url, e.to_string())
                         .await;
+                    self.stats.increment_log_errors(&self.log_url);
 
                     error!(
                         "Error polling {} : {}. Will retry after {:?}",
@@ -92,7 +192,7 @@ impl LogMonitor {
 
             // Sleep until next poll or shutdown
             tokio::select! {
-                _ = tokio::time::sleep(poll_interval) => {},
+                _ = tokio::time::sleep(next_poll) => {},
                 _ = shutdown_rx.changed() => {
                     if *shutdown_rx.borrow() {
                         info!("Shutting down monitor for {}", self.log_url);
@@ -105,23 +205,103 @@ impl LogMonitor {
         info!("Monitor for {} stopped", self.log_url);
     }
 
+    /// Fetch entries `start..=end` once and send them to the pipeline
+    ///
+    /// Used for one-shot investigations of a specific window; the log's
+    /// saved index and gaps are left untouched. Returns the number of
+    /// entries fetched.
+    pub async fn scan(&self, cert_tx: &CertSender, start: u64, end: u64) -> Result<u64> {
+        let client = self.client().await?;
+
+        let sth = client
+            .get_sth_with_retry(self.retry)
+            .await
+            .context("Failed to get STH")?;
+        if sth.tree_size == 0 || start >= sth.tree_size {
+            anyhow::bail!(
+                "{}: start index {} is beyond the tree size {}",
+                self.log_url, start, sth.tree_size
+            );
+        }
+
+        let end = end.min(sth.tree_size - 1);
+        info!("{}: Scanning entries {}-{}", self.log_url, start, end);
+
+        let mut next = start;
+        while next <= end {
+            let batch_end = end.min(next + self.config.batch_size.max(1) - 1);
+            let entries = client
+                .get_entries_with_retry(next, batch_end, self.retry)
+                .await
+                .with_context(|| format!("Failed to get entries {}-{}", next, batch_end))?;
+            if entries.is_empty() {
+                anyhow::bail!("{}: Log returned no entries for {}-{}", self.log_url, next, batch_end);
+            }
+
+            self.process_entries(cert_tx, next, &entries, Tracking::Untracked).await?;
+            next += entries.len() as u64;
+
+            debug!("{}: Scanned {}/{} entries", self.log_url, next - start, end - start + 1);
+        }
+
+        info!("{}: Scan complete ({} entries)", self.log_url, next - start);
+        Ok(next - start)
+    }
+
+    /// Client for this log, detecting its API flavor on first use
+    async fn client(&self) -> Result<&dyn LogClient> {
+        let client = self
+            .client
+            .get_or_try_init(|| {
+                detect_log_client(
+                    &self.log_url,
+                    self.http_client.clone(),
+                    self.rate_limiter.clone(),
+                    self.request_budget.clone(),
+                )
+            })
+            .await
+            .context("Failed to detect log type")?;
+
+        Ok(client.as_ref())
+    }
+
     /// Poll once for new entries
-    async fn poll_once(&self, cert_tx: &mpsc::Sender<CertData>) -> Result<()> {
+    async fn poll_once(&self, cert_tx: &CertSender) -> Result<()> {
+        let client = self.client().await?;
+
         // Get current tree size
-        let sth = self
-            .client
-            .get_sth_with_retry(3)
+        let sth = client
+            .get_sth_with_retry(self.retry)
             .await
             .context("Failed to get STH")?;
 
         let tree_size = sth.tree_size;
+        self.scheduler.lock().unwrap().observe(tree_size, Instant::now());
+        self.state_manager.update_tree_size(&self.log_url, tree_size).await;
 
-        // Get last processed index
-        let last_index = self
-            .state_manager
-            .get_last_index(&self.log_url)
-            .await
-            .unwrap_or(0);
+        // Time-based start replaces the saved index once, on the first successful poll
+        if let Some(since_ms) = self.config.since_ms
+            && self.since_pending.load(Ordering::Relaxed)
+        {
+            let index = first_index_since(client, self.retry, since_ms, tree_size)
+                .await
+                .context("Failed to locate the --since start index")?;
+            info!(
+                "{}: Starting at index {} of {} (first entry since --since)",
+                self.log_url, index, tree_size
+            );
+            self.checkpoints.reset(&self.log_url, index).await;
+            self.since_pending.store(false, Ordering::Relaxed);
+        }
+
+        // Continue after the last fetched entry; the saved index may still trail it
+        let last_index = self.checkpoints.position(&self.log_url).await;
+
+        self.check_state_age(last_index, tree_size).await;
+
+        // Re-fetch entries skipped by earlier failed or interrupted batches
+        self.recover_gap(client, cert_tx, last_index).await?;
 
         // Check if there are new entries
         if last_index >= tree_size {
@@ -129,33 +309,224 @@ impl LogMonitor {
                 "{}: Up to date (last_index={}, tree_size={})",
                 self.log_url, last_index, tree_size
             );
+            self.scheduler.lock().unwrap().set_backlog(0);
+            metrics::CT_LOG_LAG.with_label_values(&[&self.log_url]).set(0);
+            self.audit_if_due(client, &sth, last_index, &[]).await;
             return Ok(());
         }
 
-        // Calculate batch end
-        let end_index = std::cmp::min(last_index + self.config.batch_size, tree_size) - 1;
+        // Split the backlog into up to `parallel_fetches` batches
+        let ranges = plan_ranges(
+            last_index,
+            tree_size,
+            self.config.batch_size,
+            self.config.parallel_fetches,
+        );
+        let batch_end = ranges.last().map_or(last_index, |&(_, end)| end);
 
         debug!(
-            "{}: Fetching entries {}-{} (tree_size={})",
-            self.log_url, last_index, end_index, tree_size
+            "{}: Fetching entries {}-{} in {} range(s) (tree_size={})",
+            self.log_url,
+            last_index,
+            batch_end,
+            ranges.len(),
+            tree_size
         );
 
-        // Fetch entries
-        let entries = self
-            .client
-            .get_entries_with_retry(last_index, end_index, 3)
-            .await
-            .context("Failed to get entries")?;
+        // Fetch ranges concurrently; results come back in request order
+        let mut results = join_all(
+            ranges
+                .iter()
+                .map(|&(start, end)| client.get_entries_with_retry(start, end, self.retry)),
+        )
+        .await;
 
+        // Nothing fetched at all: fail the poll so health tracking backs off
+        if results.iter().all(|result| result.is_err()) {
+            let e = results
+                .into_iter()
+                .find_map(Result::err)
+                .context("No entry ranges to fetch")?;
+            return Err(e.context("Failed to get entries"));
+        }
+
+        // A crash mid-batch needs no gap record: the saved index only moves
+        // past entries the coordinator has handled (see `Checkpoints`).
+        // Only the contiguous prefix is processed; a short response (logs cap
+        // get-entries below batch_size) ends it and the next poll re-plans from
+        // there. A failed range is skipped as a gap only if the ranges after
+        // it came back, and recorded before any of them is processed.
+        let mut audit_batch: Option<usize> = None;
+        let mut new_index = last_index;
+
+        for (position, &(start, end)) in ranges.iter().enumerate() {
+            match &results[position] {
+                Ok(batch) => {
+                    let fetched = batch.len() as u64;
+                    if fetched > 0 {
+                        self.process_entries(cert_tx, start, batch, Tracking::Advance).await?;
+                        new_index = start + fetched;
+                        audit_batch.get_or_insert(position);
+                    }
+                    if fetched < end - start + 1 {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "{}: Failed to fetch entries {}-{}: {:#}",
+                        self.log_url, start, end, e
+                    );
+                    let resumes = results[position + 1..]
+                        .iter()
+                        .find_map(|result| result.as_ref().ok())
+                        .is_some_and(|batch| !batch.is_empty());
+                    if !resumes {
+                        break;
+                    }
+                    warn!(
+                        "{}: Recording gap {}-{} for recovery on a later poll",
+                        self.log_url, start, end
+                    );
+                    self.state_manager.add_gap(&self.log_url, start, end).await;
+                }
+            }
+        }
+
+        self.scheduler
+            .lock()
+            .unwrap()
+            .set_backlog(tree_size.saturating_sub(new_index));
+        metrics::CT_LOG_LAG
+            .with_label_values(&[&self.log_url])
+            .set(tree_size.saturating_sub(new_index) as i64);
+
+        info!(
+            "{}: Processed entries {}-{} ({} entries)",
+            self.log_url,
+            last_index,
+            new_index.saturating_sub(1),
+            new_index - last_index
+        );
+
+        let (audit_start, audit_entries) = match audit_batch {
+            Some(position) => (ranges[position].0, results.swap_remove(position).unwrap_or_default()),
+            None => (last_index, Vec::new()),
+        };
+        self.audit_if_due(client, &sth, audit_start, &audit_entries).await;
+
+        Ok(())
+    }
+
+    /// Warn once when the saved index hasn't advanced for `stale_after_secs`
+    async fn check_state_age(&self, last_index: u64, tree_size: u64) {
+        let Some(stale_after) = self.config.stale_after_secs else {
+            return;
+        };
+        let Some(age) = self.state_manager.get_progress(&self.log_url).await.age_secs(unix_now()) else {
+            return;
+        };
+
+        if age < stale_after {
+            self.stale_warned.store(false, Ordering::Relaxed);
+            return;
+        }
+        if self.stale_warned.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if last_index < tree_size {
+            warn!(
+                "{}: Saved index {} has not advanced in {}h although the log has {} entries",
+                self.log_url,
+                last_index,
+                age / 3600,
+                tree_size
+            );
+        } else {
+            warn!(
+                "{}: No new entries in {}h (tree_size={}); the log may be frozen or retired",
+                self.log_url,
+                age / 3600,
+                tree_size
+            );
+        }
+    }
+
+    /// Re-fetch (part of) the oldest recorded gap, at most one batch per poll
+    async fn recover_gap(
+        &self,
+        client: &dyn LogClient,
+        cert_tx: &CertSender,
+        last_index: u64,
+    ) -> Result<()> {
+        let Some((start, end)) = self.state_manager.get_gaps(&self.log_url).await.into_iter().next() else {
+            return Ok(());
+        };
+
+        // Entries at or past the last-seen index will be fetched by the normal poll
+        if start >= last_index {
+            self.state_manager.remove_gap(&self.log_url, start, end).await;
+            return Ok(());
+        }
+        if end >= last_index {
+            self.state_manager.remove_gap(&self.log_url, start, end).await;
+            self.state_manager
+                .add_gap(&self.log_url, start, last_index - 1)
+                .await;
+            return Ok(());
+        }
+
+        // The previous chunk's entries are still on their way through the outputs
+        if self.checkpoints.recovering(&self.log_url).await {
+            return Ok(());
+        }
+
+        let chunk_end = std::cmp::min(end, start + self.config.batch_size.max(1) - 1);
+
+        info!("{}: Recovering gap {}-{} (entries {}-{})", self.log_url, start, end, start, chunk_end);
+
+        let entries = match client.get_entries_with_retry(start, chunk_end, self.retry).await {
+            Ok(entries) if !entries.is_empty() => entries,
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                warn!("{}: Failed to recover gap {}-{}: {:#}", self.log_url, start, end, e);
+                return Ok(());
+            }
+        };
+
+        // The gap shrinks by what was recovered once it has all been handled
+        let recovered_end = start + entries.len() as u64 - 1;
+        self.checkpoints
+            .start_recovery(&self.log_url, (start, end), recovered_end)
+            .await;
+        self.process_entries(cert_tx, start, &entries, Tracking::Gap).await?;
+
+        Ok(())
+    }
+
+    /// Parse entries starting at `first_index` and send them to the pipeline
+    /// `tracking` says how the entries are checkpointed until the coordinator
+    /// has handled them
+    async fn process_entries(
+        &self,
+        cert_tx: &CertSender,
+        first_index: u64,
+        entries: &[LogEntry],
+        tracking: Tracking,
+    ) -> Result<()> {
         debug!(
             "{}: Processing {} entries",
             self.log_url,
             entries.len()
         );
+        metrics::CT_ENTRIES_FETCHED_TOTAL
+            .with_label_values(&[&self.log_url])
+            .inc_by(entries.len() as u64);
+        self.stats.add_log_entries(&self.log_url, entries.len() as u64);
 
         // Process each entry
         for (offset, entry) in entries.iter().enumerate() {
-            let entry_index = last_index + offset as u64;
+            let entry_index = first_index + offset as u64;
 
             // Parse certificate and extract full metadata (using both leaf_input and extra_data)
             let parsed_cert = match CertificateParser::parse_log_entry(&entry.leaf_input, &entry.extra_data, self.config.parse_precerts) {
@@ -163,6 +534,10 @@ impl LogMonitor {
                 Err(e) => {
                     // Only warn if not disabled precert parsing
                     if self.config.parse_precerts || !e.to_string().contains("Precertificate parsing disabled") {
+                        metrics::CT_PARSE_ERRORS_TOTAL
+                            .with_label_values(&[&self.log_url])
+                            .inc();
+                        self.stats.increment_log_errors(&self.log_url);
                         warn!(
                             "{}: Failed to parse certificate at index {}: {}",
                             self.log_url, entry_index, e
@@ -172,9 +547,9 @@ impl LogMonitor {
                 }
             };
 
-            if parsed_cert.domains.is_empty() {
+            if parsed_cert.domains.is_empty() && parsed_cert.ip_addresses.is_empty() {
                 debug!(
-                    "{}: No domains found in certificate at index {}",
+                    "{}: No domains or IPs found in certificate at index {}",
                     self.log_url, entry_index
                 );
                 continue;
@@ -183,42 +558,336 @@ impl LogMonitor {
             // Create CertData with full certificate metadata
             let cert_data = CertData {
                 all_domains: Some(parsed_cert.domains.clone()),
+                ip_addresses: Some(parsed_cert.ip_addresses.clone()),
                 cert_index: Some(entry_index),
-                seen_unix: Some(chrono::Utc::now().timestamp() as f64),
+                // The log's own timestamp, so backfilled entries keep their real age
+                seen_unix: Some(
+                    parsed_cert
+                        .logged_at_ms
+                        .map(|ms| ms as f64 / 1000.0)
+                        .unwrap_or_else(|| chrono::Utc::now().timestamp() as f64),
+                ),
+                logged_at_ms: parsed_cert.logged_at_ms,
                 leaf_cert: Some(crate::types::LeafCert {
                     not_before: parsed_cert.not_before,
                     not_after: parsed_cert.not_after,
                     fingerprint: Some(parsed_cert.fingerprint),
                     issuer: parsed_cert.issuer,
+                    serial_number: Some(parsed_cert.serial_number),
+                    subject_key_id: parsed_cert.subject_key_id,
+                    authority_key_id: parsed_cert.authority_key_id,
+                    key_algorithm: parsed_cert.key_algorithm,
+                    key_bits: parsed_cert.key_bits,
+                    key_curve: parsed_cert.key_curve,
+                    signature_algorithm: parsed_cert.signature_algorithm,
+                    subject: Some(parsed_cert.subject),
+                    issuer_dn: Some(parsed_cert.issuer_dn),
+                    issuer_chain: parsed_cert.issuer_chain,
+                    pki_endpoints: parsed_cert.pki_endpoints,
+                    // Precerts parsed from their TBS have no signed DER to export
+                    pem: (self.config.include_pem && !parsed_cert.der.is_empty())
+                        .then(|| CertificateParser::to_pem(&parsed_cert.der)),
                 }),
                 is_precert: parsed_cert.is_precert,
                 ct_log_url: Some(self.log_url.clone()),
             };
 
-            // Send to processing pipeline
-            if let Err(e) = cert_tx.send(cert_data).await {
-                warn!(
-                    "{}: Failed to send cert_data to processing pipeline: {}",
-                    self.log_url, e
-                );
-                // Channel closed, stop processing
-                return Err(anyhow::anyhow!("Processing pipeline closed"));
+            // Registered first, as the coordinator may finish it before send returns
+            match tracking {
+                Tracking::Advance => self.checkpoints.sent(&self.log_url, entry_index).await,
+                Tracking::Gap => self.checkpoints.sent_recovered(&self.log_url, entry_index).await,
+                Tracking::Untracked => {}
             }
 
-            // Update state periodically (every entry)
-            self.state_manager
-                .update_index(&self.log_url, entry_index + 1)
-                .await;
+            // Send to processing pipeline
+            match cert_tx.send(cert_data).await {
+                // Spilled entries are finished once the coordinator replays them
+                Ok(Delivery::Queued | Delivery::Spilled) => {}
+                // Nothing more will happen to it in this run
+                Ok(Delivery::Dropped) => {
+                    if tracking != Tracking::Untracked {
+                        self.checkpoints.done(&self.log_url, entry_index).await;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "{}: Failed to send cert_data to processing pipeline: {}",
+                        self.log_url, e
+                    );
+                    // Channel closed, stop processing
+                    return Err(e);
+                }
+            }
         }
 
-        info!(
-            "{}: Processed entries {}-{} ({} entries)",
-            self.log_url,
-            last_index,
-            end_index,
-            entries.len()
-        );
+        // Entries that failed to parse still count as processed
+        match tracking {
+            Tracking::Advance if !entries.is_empty() => {
+                self.checkpoints
+                    .advance(&self.log_url, first_index + entries.len() as u64)
+                    .await;
+            }
+            Tracking::Gap => self.checkpoints.recovery_sent(&self.log_url).await,
+            _ => {}
+        }
 
         Ok(())
     }
+
+    /// Run a Merkle proof audit if auditing is enabled and one is due
+    /// Verification failures are reported but don't affect polling health
+    async fn audit_if_due(
+        &self,
+        client: &dyn LogClient,
+        sth: &SignedTreeHead,
+        first_index: u64,
+        entries: &[LogEntry],
+    ) {
+        let Some(ref auditor) = self.auditor else {
+            return;
+        };
+
+        // Static logs serve proofs as tiles, which we don't audit yet
+        if client.log_type() != LogType::Rfc6962 {
+            return;
+        }
+
+        if !auditor.is_due().await {
+            return;
+        }
+
+        if let Err(e) = auditor.audit(client, sth, first_index, entries).await {
+            match e.downcast_ref::<AuditFailure>() {
+                Some(failure) => {
+                    error!("{}: CT log audit FAILED: {}", self.log_url, failure);
+                    metrics::CT_AUDIT_FAILURES_TOTAL
+                        .with_label_values(&[&self.log_url, failure.check.as_str()])
+                        .inc();
+                }
+                None => warn!("{}: Could not complete audit: {:#}", self.log_url, e),
+            }
+        }
+    }
+}
+
+/// Split `start..tree_size` into up to `parallelism` inclusive ranges of `batch_size`
+fn plan_ranges(start: u64, tree_size: u64, batch_size: u64, parallelism: usize) -> Vec<(u64, u64)> {
+    let batch_size = batch_size.max(1);
+    let mut ranges = Vec::new();
+    let mut next = start;
+
+    while next < tree_size && ranges.len() < parallelism.max(1) {
+        let end = std::cmp::min(next + batch_size, tree_size) - 1;
+        ranges.push((next, end));
+        next = end + 1;
+    }
+
+    ranges
+}
+
+/// Smallest index whose leaf timestamp is at or after `since_ms` (`tree_size` if none is)
+///
+/// Binary search over single-entry fetches. Leaf timestamps are only roughly
+/// ordered (entries may be sequenced up to the MMD late), so the boundary can
+/// be off by a few entries.
+async fn first_index_since(client: &dyn LogClient, retry: RetryPolicy, since_ms: u64, tree_size: u64) -> Result<u64> {
+    let (mut low, mut high) = (0, tree_size);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let entries = client.get_entries_with_retry(mid, mid, retry).await?;
+        let timestamp = entries
+            .first()
+            .and_then(|entry| CertificateParser::leaf_input_timestamp_ms(&entry.leaf_input))
+            .with_context(|| format!("No leaf timestamp for entry {}", mid))?;
+
+        if timestamp < since_ms {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct_log::pipeline::SaturationPolicy;
+    use crate::state::StateManager;
+    use async_trait::async_trait;
+    use base64::Engine;
+    use tempfile::TempDir;
+
+    /// Log whose entry `i` was logged at `timestamps[i]`
+    struct TimestampedLog {
+        timestamps: Vec<u64>,
+    }
+
+    #[async_trait]
+    impl LogClient for TimestampedLog {
+        fn log_type(&self) -> LogType {
+            LogType::Rfc6962
+        }
+
+        async fn get_sth_with_retry(&self, _retry: RetryPolicy) -> Result<SignedTreeHead> {
+            anyhow::bail!("get_sth_with_retry not used in this test")
+        }
+
+        async fn get_entries_with_retry(&self, start: u64, end: u64, _retry: RetryPolicy) -> Result<Vec<LogEntry>> {
+            Ok((start..=end)
+                .map(|index| {
+                    let mut leaf = vec![0u8, 0u8];
+                    leaf.extend_from_slice(&self.timestamps[index as usize].to_be_bytes());
+                    LogEntry {
+                        leaf_input: base64::engine::general_purpose::STANDARD.encode(leaf),
+                        extra_data: String::new(),
+                    }
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_index_since() {
+        let log = TimestampedLog {
+            timestamps: vec![100, 200, 200, 300, 400, 500],
+        };
+        let size = log.timestamps.len() as u64;
+
+        assert_eq!(first_index_since(&log, RetryPolicy::default(), 0, size).await.unwrap(), 0);
+        assert_eq!(first_index_since(&log, RetryPolicy::default(), 200, size).await.unwrap(), 1);
+        assert_eq!(first_index_since(&log, RetryPolicy::default(), 250, size).await.unwrap(), 3);
+        assert_eq!(first_index_since(&log, RetryPolicy::default(), 999, size).await.unwrap(), 6);
+        assert_eq!(first_index_since(&log, RetryPolicy::default(), 100, 0).await.unwrap(), 0);
+    }
+
+    /// Log of unparseable entries that serves at most `cap` entries per
+    /// request and fails requests starting at `failing`
+    struct CappedLog {
+        tree_size: u64,
+        cap: u64,
+        failing: Option<u64>,
+    }
+
+    #[async_trait]
+    impl LogClient for CappedLog {
+        fn log_type(&self) -> LogType {
+            LogType::Rfc6962
+        }
+
+        async fn get_sth_with_retry(&self, _retry: RetryPolicy) -> Result<SignedTreeHead> {
+            Ok(SignedTreeHead {
+                tree_size: self.tree_size,
+                timestamp: 0,
+                sha256_root_hash: String::new(),
+                tree_head_signature: String::new(),
+            })
+        }
+
+        async fn get_entries_with_retry(&self, start: u64, end: u64, _retry: RetryPolicy) -> Result<Vec<LogEntry>> {
+            if self.failing == Some(start) {
+                anyhow::bail!("HTTP 500");
+            }
+            let end = end.min(start + self.cap - 1);
+            Ok((start..=end)
+                .map(|_| LogEntry {
+                    leaf_input: String::new(),
+                    extra_data: String::new(),
+                })
+                .collect())
+        }
+    }
+
+    /// Monitor of `log` fetching four ranges of 256 entries per poll
+    async fn capped_monitor(log: CappedLog, state: Arc<dyn StateBackend>) -> LogMonitor {
+        let config = LogMonitorConfig {
+            poll_interval_secs: 60,
+            batch_size: 256,
+            parse_precerts: true,
+            parallel_fetches: 4,
+            rate_limit: RateLimitConfig::default(),
+            max_concurrent_requests: None,
+            audit: None,
+            quarantine: None,
+            http: HttpConfig::default(),
+            retry: RetryConfig::default(),
+            channel: ChannelConfig::default(),
+            adaptive_poll: None,
+            include_pem: false,
+            since_ms: None,
+            stale_after_secs: None,
+            commit_batch: 1,
+            log_ids: LogIds::default(),
+        };
+        let monitor = LogMonitor::new(
+            "https://capped.test/".to_string(),
+            Arc::clone(&state),
+            Arc::new(Checkpoints::new(state, 1)),
+            Arc::new(LogHealthTracker::new(5)),
+            StatsCollector::new(),
+            None,
+            &HttpClientPool::new(HttpConfig::default()),
+            config,
+        )
+        .unwrap();
+        monitor.client.set(Arc::new(log)).ok().unwrap();
+        monitor
+    }
+
+    #[tokio::test]
+    async fn test_short_responses_are_not_gaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let state: Arc<dyn StateBackend> =
+            Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        let log = CappedLog { tree_size: 1000, cap: 32, failing: None };
+        let monitor = capped_monitor(log, Arc::clone(&state)).await;
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let cert_tx = CertSender::new(tx, SaturationPolicy::Block, None);
+
+        // Each poll only keeps the prefix up to the first short response
+        monitor.poll_once(&cert_tx).await.unwrap();
+        assert_eq!(state.get_last_index("https://capped.test/").await, Some(32));
+        monitor.poll_once(&cert_tx).await.unwrap();
+        assert_eq!(state.get_last_index("https://capped.test/").await, Some(64));
+        assert!(state.get_gaps("https://capped.test/").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_range_before_fetched_ones_is_a_gap() {
+        let temp_dir = TempDir::new().unwrap();
+        let state: Arc<dyn StateBackend> =
+            Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let cert_tx = CertSender::new(tx, SaturationPolicy::Block, None);
+
+        let log = CappedLog { tree_size: 1000, cap: 256, failing: Some(256) };
+        let monitor = capped_monitor(log, Arc::clone(&state)).await;
+        monitor.poll_once(&cert_tx).await.unwrap();
+        assert_eq!(state.get_last_index("https://capped.test/").await, Some(1000));
+        assert_eq!(state.get_gaps("https://capped.test/").await, vec![(256, 511)]);
+
+        // A failure at the end is simply fetched again by the next poll
+        let temp_dir = TempDir::new().unwrap();
+        let state: Arc<dyn StateBackend> =
+            Arc::new(StateManager::new(temp_dir.path().join("state.toml")).await.unwrap());
+        let log = CappedLog { tree_size: 1000, cap: 256, failing: Some(768) };
+        let monitor = capped_monitor(log, Arc::clone(&state)).await;
+        monitor.poll_once(&cert_tx).await.unwrap();
+        assert_eq!(state.get_last_index("https://capped.test/").await, Some(768));
+        assert!(state.get_gaps("https://capped.test/").await.is_empty());
+    }
+
+    #[test]
+    fn test_plan_ranges() {
+        assert_eq!(plan_ranges(0, 1000, 256, 1), vec![(0, 255)]);
+        assert_eq!(
+            plan_ranges(100, 1000, 256, 3),
+            vec![(100, 355), (356, 611), (612, 867)]
+        );
+        // Never plans past the tree size
+        assert_eq!(plan_ranges(900, 1000, 256, 4), vec![(900, 999)]);
+        assert!(plan_ranges(1000, 1000, 256, 4).is_empty());
+        // Zero parallelism behaves like sequential fetching
+        assert_eq!(plan_ranges(0, 10, 4, 0), vec![(0, 3)]);
+    }
 }
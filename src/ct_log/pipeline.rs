@@ -0,0 +1,307 @@
+// src/ct_log/pipeline.rs
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, mpsc};
+use tracing::warn;
+
+use crate::metrics;
+use crate::types::CertData;
+
+/// What monitors do when the coordinator channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaturationPolicy {
+    /// Wait for room in the channel (monitors slow down to match outputs)
+    Block,
+    /// Discard the certificate and count it
+    Drop,
+    /// Append the certificate to a spill file, processed once the channel drains
+    Spill,
+}
+
+impl FromStr for SaturationPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "block" => Ok(Self::Block),
+            "drop" => Ok(Self::Drop),
+            "spill" => Ok(Self::Spill),
+            other => anyhow::bail!(
+                "Invalid channel_full_policy '{}'. Must be 'block', 'drop' or 'spill'",
+                other
+            ),
+        }
+    }
+}
+
+/// Coordinator channel settings
+#[derive(Debug, Clone)]
+pub struct ChannelConfig {
+    /// Certificates buffered between monitors and the coordinator
+    pub capacity: usize,
+    /// Behavior when the buffer is full
+    pub policy: SaturationPolicy,
+    /// Overflow file used by `SaturationPolicy::Spill`
+    pub spill_path: PathBuf,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            policy: SaturationPolicy::Block,
+            spill_path: PathBuf::from("ct-scout-spill.jsonl"),
+        }
+    }
+}
+
+/// On-disk overflow queue of certificates, one JSON object per line
+pub struct SpillFile {
+    path: PathBuf,
+    lock: Mutex<()>,
+    pending: AtomicUsize,
+}
+
+impl SpillFile {
+    /// Open a spill file, picking up entries left over from a previous run
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let pending = match std::fs::read_to_string(&path) {
+            Ok(content) => content.lines().filter(|line| !line.is_empty()).count(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read spill file {:?}", path));
+            }
+        };
+        metrics::CERT_CHANNEL_SPILLED.set(pending as i64);
+
+        Ok(Self {
+            path,
+            lock: Mutex::new(()),
+            pending: AtomicUsize::new(pending),
+        })
+    }
+
+    /// Number of certificates waiting in the file
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Append a certificate
+    pub async fn push(&self, data: &CertData) -> Result<()> {
+        let mut line = serde_json::to_vec(data).context("Failed to serialize certificate")?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open spill file {:?}", self.path))?;
+        file.write_all(&line).await.context("Failed to write spill file")?;
+        // tokio writes in the background; make sure the line is on disk before unlocking
+        file.flush().await.context("Failed to write spill file")?;
+
+        let pending = self.pending.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::CERT_CHANNEL_SPILLED.set(pending as i64);
+        Ok(())
+    }
+
+    /// Take every spilled certificate, emptying the file
+    ///
+    /// Entries spilled by monitors stay unhandled in `Checkpoints` until the
+    /// coordinator has replayed them, so a crash mid-replay fetches them
+    /// from the log again instead of losing them.
+    pub async fn drain(&self) -> Result<Vec<CertData>> {
+        let _guard = self.lock.lock().await;
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).context("Failed to read spill file"),
+        };
+
+        let mut entries = Vec::new();
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(data) => entries.push(data),
+                Err(e) => warn!("Skipping corrupt spill file entry: {}", e),
+            }
+        }
+
+        if !content.is_empty() {
+            tokio::fs::remove_file(&self.path)
+                .await
+                .context("Failed to remove spill file")?;
+        }
+
+        self.pending.store(0, Ordering::Relaxed);
+        metrics::CERT_CHANNEL_SPILLED.set(0);
+        Ok(entries)
+    }
+}
+
+/// What became of a certificate handed to `CertSender::send`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// In the channel, waiting for the coordinator
+    Queued,
+    /// Written to the spill file
+    Spilled,
+    /// Discarded under `SaturationPolicy::Drop`
+    Dropped,
+}
+
+/// Sending half of the coordinator channel, applying the saturation policy
+#[derive(Clone)]
+pub struct CertSender {
+    tx: mpsc::Sender<CertData>,
+    policy: SaturationPolicy,
+    spill: Option<Arc<SpillFile>>,
+}
+
+impl CertSender {
+    /// Wrap a channel sender; `spill` is required for `SaturationPolicy::Spill`
+    pub fn new(
+        tx: mpsc::Sender<CertData>,
+        policy: SaturationPolicy,
+        spill: Option<Arc<SpillFile>>,
+    ) -> Self {
+        Self { tx, policy, spill }
+    }
+
+    /// Sender on the same channel that always waits for room, for inputs that must not lose entries
+    pub fn blocking(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            policy: SaturationPolicy::Block,
+            spill: None,
+        }
+    }
+
+    /// Whether the coordinator has stopped accepting certificates
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Send a certificate to the coordinator
+    /// Fails only if the coordinator has shut down
+    pub async fn send(&self, data: CertData) -> Result<Delivery> {
+        let data = match self.tx.try_send(data) {
+            Ok(()) => return Ok(Delivery::Queued),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                anyhow::bail!("Processing pipeline closed")
+            }
+            Err(mpsc::error::TrySendError::Full(data)) => data,
+        };
+
+        match (self.policy, &self.spill) {
+            (SaturationPolicy::Drop, _) => {
+                metrics::CERT_CHANNEL_SATURATED_TOTAL
+                    .with_label_values(&["dropped"])
+                    .inc();
+                Ok(Delivery::Dropped)
+            }
+            (SaturationPolicy::Spill, Some(spill)) => match spill.push(&data).await {
+                Ok(()) => {
+                    metrics::CERT_CHANNEL_SATURATED_TOTAL
+                        .with_label_values(&["spilled"])
+                        .inc();
+                    Ok(Delivery::Spilled)
+                }
+                Err(e) => {
+                    // Never lose a certificate because the disk failed; fall back to waiting
+                    warn!("Failed to spill certificate, waiting for channel: {:#}", e);
+                    self.send_blocking(data).await
+                }
+            },
+            _ => self.send_blocking(data).await,
+        }
+    }
+
+    async fn send_blocking(&self, data: CertData) -> Result<Delivery> {
+        metrics::CERT_CHANNEL_SATURATED_TOTAL
+            .with_label_values(&["blocked"])
+            .inc();
+        self.tx
+            .send(data)
+            .await
+            .map(|()| Delivery::Queued)
+            .map_err(|_| anyhow::anyhow!("Processing pipeline closed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn cert(index: u64) -> CertData {
+        CertData {
+            all_domains: Some(vec![format!("host{}.example.com", index)]),
+            ip_addresses: None,
+            cert_index: Some(index),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: Some("https://test.log/".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("block".parse::<SaturationPolicy>().unwrap(), SaturationPolicy::Block);
+        assert_eq!("drop".parse::<SaturationPolicy>().unwrap(), SaturationPolicy::Drop);
+        assert_eq!("spill".parse::<SaturationPolicy>().unwrap(), SaturationPolicy::Spill);
+        assert!("discard".parse::<SaturationPolicy>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_policy_discards_when_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let sender = CertSender::new(tx, SaturationPolicy::Drop, None);
+
+        assert_eq!(sender.send(cert(1)).await.unwrap(), Delivery::Queued);
+        assert_eq!(sender.send(cert(2)).await.unwrap(), Delivery::Dropped);
+
+        assert_eq!(rx.recv().await.unwrap().cert_index, Some(1));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spill_policy_writes_overflow_to_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("spill.jsonl");
+        let spill = Arc::new(SpillFile::open(path.clone()).unwrap());
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let sender = CertSender::new(tx, SaturationPolicy::Spill, Some(Arc::clone(&spill)));
+
+        for index in 1..=3 {
+            sender.send(cert(index)).await.unwrap();
+        }
+
+        assert_eq!(rx.recv().await.unwrap().cert_index, Some(1));
+        assert_eq!(spill.pending(), 2);
+
+        // Leftovers are picked up when the file is reopened
+        assert_eq!(SpillFile::open(path.clone()).unwrap().pending(), 2);
+
+        let drained: Vec<_> = spill.drain().await.unwrap().iter().map(|c| c.cert_index).collect();
+        assert_eq!(drained, vec![Some(2), Some(3)]);
+        assert_eq!(spill.pending(), 0);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_when_closed() {
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+        let sender = CertSender::new(tx, SaturationPolicy::Block, None);
+        assert!(sender.send(cert(1)).await.is_err());
+    }
+}
@@ -0,0 +1,145 @@
+// src/ct_log/probe.rs
+//! Startup check of log URLs that come from the config rather than a log list
+//!
+//! A typo in `custom_logs` or `additional_logs` would otherwise only show up as
+//! a monitor failing every poll. Each URL gets one tree head request up front.
+
+use anyhow::{Context, Result};
+use futures_util::future::join_all;
+use std::str::FromStr;
+use tracing::{error, info, warn};
+
+use super::client::RetryPolicy;
+use super::http::HttpConfig;
+use super::log_client::{LogType, detect_log_client};
+use super::types::SignedTreeHead;
+
+/// What to do with a configured log that doesn't answer as a CT log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogCheckPolicy {
+    /// Don't probe
+    Off,
+    /// Report it and monitor it anyway
+    Warn,
+    /// Report it and leave it out
+    Skip,
+    /// Refuse to start
+    Fail,
+}
+
+impl FromStr for LogCheckPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "off" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "skip" => Ok(Self::Skip),
+            "fail" => Ok(Self::Fail),
+            other => anyhow::bail!(
+                "Invalid check_configured_logs '{}'. Must be 'off', 'warn', 'skip' or 'fail'",
+                other
+            ),
+        }
+    }
+}
+
+/// Detect the log's API and fetch its tree head once
+pub async fn probe_log(http_config: &HttpConfig, log_url: &str) -> Result<(LogType, SignedTreeHead)> {
+    let client = detect_log_client(log_url, http_config.client_for(log_url)?, None, None)
+        .await
+        .context("Neither a static-ct checkpoint nor an RFC 6962 get-sth answered")?;
+    let sth = client.get_sth_with_retry(RetryPolicy::once()).await?;
+    Ok((client.log_type(), sth))
+}
+
+/// Probe every URL of `key` (e.g. "custom_logs") and apply `policy` to the failures
+///
+/// Returns the URLs to monitor.
+pub async fn check_logs(http_config: &HttpConfig, key: &str, urls: &[String], policy: LogCheckPolicy) -> Result<Vec<String>> {
+    if policy == LogCheckPolicy::Off || urls.is_empty() {
+        return Ok(urls.to_vec());
+    }
+
+    info!("Checking {} log(s) from {}", urls.len(), key);
+    let results = join_all(urls.iter().map(|url| probe_log(http_config, url))).await;
+
+    let mut usable = Vec::new();
+    let mut failed = Vec::new();
+    for (url, result) in urls.iter().zip(results) {
+        match result {
+            Ok((log_type, sth)) => {
+                info!("{}: {} log, tree size {}", url, log_type, sth.tree_size);
+                usable.push(url.clone());
+            }
+            Err(e) => {
+                let message = format!("{}: '{}' is not reachable as a CT log: {:#}", key, url, e);
+                match policy {
+                    LogCheckPolicy::Skip => warn!("{} (skipped)", message),
+                    LogCheckPolicy::Fail => error!("{}", message),
+                    _ => warn!("{}", message),
+                }
+                failed.push(url.clone());
+                if policy != LogCheckPolicy::Skip {
+                    usable.push(url.clone());
+                }
+            }
+        }
+    }
+
+    if policy == LogCheckPolicy::Fail && !failed.is_empty() {
+        anyhow::bail!(
+            "{} of {} log(s) in {} failed the startup check: {} (set check_configured_logs = \"warn\" or \"skip\" to start anyway)",
+            failed.len(),
+            urls.len(),
+            key,
+            failed.join(", ")
+        );
+    }
+    Ok(usable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_check_logs_policies() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/good/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tree_size": 5,
+                "timestamp": 1700000000000u64,
+                "sha256_root_hash": "",
+                "tree_head_signature": ""
+            })))
+            .mount(&mock_server)
+            .await;
+        // A web page, not a CT log
+        Mock::given(method("GET"))
+            .and(path("/html/ct/v1/get-sth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+            .mount(&mock_server)
+            .await;
+
+        let good = format!("{}/good", mock_server.uri());
+        let urls = vec![good.clone(), format!("{}/html", mock_server.uri())];
+        let http = HttpConfig::default();
+
+        assert_eq!(check_logs(&http, "custom_logs", &urls, LogCheckPolicy::Warn).await.unwrap(), urls);
+        assert_eq!(check_logs(&http, "custom_logs", &urls, LogCheckPolicy::Skip).await.unwrap(), vec![good]);
+
+        let err = check_logs(&http, "custom_logs", &urls, LogCheckPolicy::Fail).await.unwrap_err();
+        assert!(err.to_string().contains("1 of 2 log(s) in custom_logs failed"));
+        assert!(err.to_string().contains("/html"));
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("skip".parse::<LogCheckPolicy>().unwrap(), LogCheckPolicy::Skip);
+        assert!("ignore".parse::<LogCheckPolicy>().is_err());
+    }
+}
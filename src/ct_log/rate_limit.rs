@@ -0,0 +1,210 @@
+// src/ct_log/rate_limit.rs
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tracing::debug;
+
+/// Rate limiting settings for CT log requests
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Default requests per second for each log (None = unlimited)
+    pub requests_per_sec: Option<f64>,
+    /// Maximum burst size (bucket capacity)
+    pub burst: u32,
+    /// Per-log overrides keyed by log URL
+    pub per_log: HashMap<String, f64>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_sec: None,
+            burst: 5,
+            per_log: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Build a limiter for a specific log, or None if the log is unlimited
+    pub fn limiter_for(&self, log_url: &str) -> Option<Arc<RateLimiter>> {
+        let wanted = log_url.trim_end_matches('/');
+        let rate = self
+            .per_log
+            .iter()
+            .find(|(url, _)| url.trim_end_matches('/') == wanted)
+            .map(|(_, rate)| *rate)
+            .or(self.requests_per_sec)?;
+
+        if rate <= 0.0 {
+            return None;
+        }
+
+        Some(Arc::new(RateLimiter::new(rate, self.burst)))
+    }
+}
+
+/// Token bucket limiter for requests to a single CT log
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+    /// Set when the log told us to back off (Retry-After)
+    blocked_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a new limiter allowing `requests_per_sec` with bursts up to `burst`
+    pub fn new(requests_per_sec: f64, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            rate: requests_per_sec,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                blocked_until: None,
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+
+                match state.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        state.blocked_until = None;
+
+                        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                        state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+                        state.last_refill = now;
+
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            return;
+                        }
+
+                        Duration::from_secs_f64((1.0 - state.tokens) / self.rate)
+                    }
+                }
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Block all requests for the given duration (e.g. after a 429 with Retry-After)
+    pub async fn pause_for(&self, duration: Duration) {
+        let mut state = self.state.lock().await;
+        let until = Instant::now() + duration;
+        if state.blocked_until.is_none_or(|current| current < until) {
+            debug!("Rate limiter paused for {:?}", duration);
+            state.blocked_until = Some(until);
+        }
+        state.tokens = 0.0;
+    }
+}
+
+/// Global cap on in-flight HTTP requests, shared by all log monitors
+#[derive(Debug, Clone)]
+pub struct RequestBudget {
+    semaphore: Arc<Semaphore>,
+}
+
+impl RequestBudget {
+    /// Allow at most `max_in_flight` concurrent requests
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight.max(1))),
+        }
+    }
+
+    /// Wait for a request slot; the slot is released when the permit is dropped
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.semaphore
+            .acquire()
+            .await
+            .expect("request budget semaphore is never closed")
+    }
+
+    /// Number of free request slots
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_is_immediate() {
+        let limiter = RateLimiter::new(1.0, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_refill() {
+        let limiter = RateLimiter::new(20.0, 1);
+        limiter.acquire().await;
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_pause_blocks_acquire() {
+        let limiter = RateLimiter::new(100.0, 10);
+        limiter.pause_for(Duration::from_millis(100)).await;
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn test_limiter_for_uses_override() {
+        let mut per_log = HashMap::new();
+        per_log.insert("https://ct.example.com/log/".to_string(), 2.0);
+        let config = RateLimitConfig {
+            requests_per_sec: None,
+            burst: 5,
+            per_log,
+        };
+
+        assert!(config.limiter_for("https://ct.example.com/log").is_some());
+        assert!(config.limiter_for("https://other.example.com/log").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_budget_bounds_in_flight() {
+        let budget = RequestBudget::new(2);
+        let shared = budget.clone();
+
+        let first = budget.acquire().await;
+        let _second = shared.acquire().await;
+        assert_eq!(budget.available(), 0);
+
+        // A third request waits until a slot is released
+        let third = tokio::time::timeout(Duration::from_millis(50), budget.acquire()).await;
+        assert!(third.is_err());
+
+        drop(first);
+        let _third = budget.acquire().await;
+        assert_eq!(shared.available(), 0);
+    }
+}
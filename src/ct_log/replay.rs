@@ -0,0 +1,210 @@
+// src/ct_log/replay.rs
+//! Feed previously captured certificates through the pipeline instead of live logs
+//!
+//! Accepts one JSON object per line in any of these shapes:
+//! - certstream messages (`{"message_type": "certificate_update", "data": {...}}`)
+//! - bare `CertData` objects (certstream `data`, or ct-scout's spill file)
+//! - ct-scout's own JSONL match output (`MatchResult`)
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::net::IpAddr;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use super::pipeline::CertSender;
+use crate::types::{CertData, LeafCert, MatchResult};
+
+/// Send every certificate in `input` to the coordinator; returns how many were sent
+///
+/// Malformed lines are logged and skipped so one bad record doesn't end a replay.
+pub async fn replay<R>(input: R, cert_tx: &CertSender, mut shutdown_rx: watch::Receiver<bool>) -> Result<u64>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut lines = input.lines();
+    let mut line_number = 0u64;
+    let mut sent = 0u64;
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line.context("Failed to read replay input")?,
+            _ = shutdown_rx.changed() => {
+                debug!("Replay stopped after {} certificates", sent);
+                break;
+            }
+        };
+        let Some(line) = line else {
+            break;
+        };
+        line_number += 1;
+
+        match parse_line(&line) {
+            Ok(Some(data)) => {
+                cert_tx.send(data).await?;
+                sent += 1;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Skipping replay line {}: {:#}", line_number, e),
+        }
+    }
+
+    info!("Replayed {} certificates from {} lines", sent, line_number);
+    Ok(sent)
+}
+
+/// Certificate on one input line; `None` for blank lines and non-certificate messages
+pub fn parse_line(line: &str) -> Result<Option<CertData>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut value: Value = serde_json::from_str(line).context("Invalid JSON")?;
+
+    // certstream wraps certificates in a message; heartbeats carry none
+    if let Some(message_type) = value.get("message_type") {
+        if message_type.as_str() != Some("certificate_update") {
+            return Ok(None);
+        }
+        value = value.get_mut("data").map(Value::take).context("certificate_update without data")?;
+    }
+
+    if value.get("matched_domain").is_some() {
+        let result: MatchResult = serde_json::from_value(value).context("Invalid match record")?;
+        return Ok(Some(from_match(result)));
+    }
+
+    normalize_certstream(&mut value);
+    let data: CertData = serde_json::from_value(value).context("Invalid certificate record")?;
+    Ok(Some(data))
+}
+
+/// Map certstream's field layout onto `CertData` where the two differ
+fn normalize_certstream(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+
+    if let Some(leaf) = object.get_mut("leaf_cert").and_then(Value::as_object_mut) {
+        let domains = leaf.remove("all_domains");
+        // certstream's issuer is a DN object; ours is a string plus `issuer_dn`
+        if let Some(issuer) = leaf.get("issuer").filter(|issuer| issuer.is_object()).cloned() {
+            let aggregated = issuer.get("aggregated").cloned().unwrap_or(Value::Null);
+            leaf.insert("issuer".to_string(), aggregated);
+            leaf.entry("issuer_dn").or_insert(issuer);
+        }
+        if let Some(domains) = domains {
+            object.entry("all_domains").or_insert(domains);
+        }
+    }
+
+    if let Some(url) = object.get("source").and_then(|source| source.get("url")).cloned() {
+        object.entry("ct_log").or_insert(url);
+    }
+    if object.get("update_type").and_then(Value::as_str) == Some("PrecertLogEntry") {
+        object.entry("is_precert").or_insert(Value::Bool(true));
+    }
+}
+
+/// Rebuild the certificate a match was reported for
+fn from_match(result: MatchResult) -> CertData {
+    // IP SAN matches report the address as the matched "domain"
+    let ip_addresses = result
+        .matched_domain
+        .parse::<IpAddr>()
+        .is_ok()
+        .then(|| vec![result.matched_domain.clone()]);
+
+    CertData {
+        all_domains: Some(result.all_domains),
+        ip_addresses,
+        cert_index: result.cert_index,
+        seen_unix: result.seen_unix,
+        logged_at_ms: result.logged_at.map(|secs| secs * 1000),
+        leaf_cert: Some(LeafCert {
+            not_before: result.not_before,
+            not_after: result.not_after,
+            fingerprint: result.fingerprint,
+            issuer: result.issuer,
+            serial_number: result.serial_number,
+            subject_key_id: result.subject_key_id,
+            authority_key_id: result.authority_key_id,
+            key_algorithm: result.key_algorithm,
+            key_bits: result.key_bits,
+            key_curve: result.key_curve,
+            signature_algorithm: result.signature_algorithm,
+            subject: result.subject,
+            issuer_dn: result.issuer_dn,
+            issuer_chain: result.issuer_chain,
+            pki_endpoints: result.pki_endpoints,
+            pem: result.pem,
+        }),
+        is_precert: result.is_precert,
+        ct_log_url: result.ct_log_url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct_log::SaturationPolicy;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_parse_certstream_message() {
+        let line = r#"{"message_type":"certificate_update","data":{"update_type":"PrecertLogEntry","cert_index":42,"seen":1700000000.5,"source":{"url":"https://ct.example.com/log/","name":"Example"},"leaf_cert":{"all_domains":["a.example.com","b.example.com"],"fingerprint":"AB:CD","not_before":1,"not_after":2,"issuer":{"C":"US","O":"Let's Encrypt","CN":"R3","aggregated":"/C=US/O=Let's Encrypt/CN=R3"},"subject":{"CN":"a.example.com","aggregated":"/CN=a.example.com"}}}}"#;
+        let data = parse_line(line).unwrap().unwrap();
+
+        assert_eq!(data.all_domains.unwrap(), vec!["a.example.com", "b.example.com"]);
+        assert_eq!(data.cert_index, Some(42));
+        assert_eq!(data.ct_log_url.as_deref(), Some("https://ct.example.com/log/"));
+        assert!(data.is_precert);
+        let leaf = data.leaf_cert.unwrap();
+        assert_eq!(leaf.issuer.as_deref(), Some("/C=US/O=Let's Encrypt/CN=R3"));
+        assert_eq!(leaf.issuer_dn.unwrap().common_name.as_deref(), Some("R3"));
+
+        assert!(parse_line(r#"{"message_type":"heartbeat","timestamp":1}"#).unwrap().is_none());
+        assert!(parse_line("   ").unwrap().is_none());
+        assert!(parse_line("{not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_match_output() {
+        let data = CertData {
+            all_domains: Some(vec!["api.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(7),
+            seen_unix: Some(1700000000.0),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: Some("https://ct.example.com/log/".to_string()),
+        };
+        let result = MatchResult::from_cert_data("api.example.com".to_string(), &data, None, None);
+        let line = serde_json::to_string(&result).unwrap();
+
+        let replayed = parse_line(&line).unwrap().unwrap();
+        assert_eq!(replayed.all_domains, data.all_domains);
+        assert_eq!(replayed.cert_index, Some(7));
+        assert!(replayed.ip_addresses.is_none());
+
+        let ip_match = MatchResult::from_cert_data("192.0.2.1".to_string(), &data, None, None);
+        let replayed = parse_line(&serde_json::to_string(&ip_match).unwrap()).unwrap().unwrap();
+        assert_eq!(replayed.ip_addresses.unwrap(), vec!["192.0.2.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_bad_lines() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let sender = CertSender::new(tx, SaturationPolicy::Block, None);
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let input = "{\"all_domains\":[\"a.example.com\"]}\n\ngarbage\n{\"all_domains\":[\"b.example.com\"]}\n";
+
+        let sent = replay(input.as_bytes(), &sender, shutdown_rx).await.unwrap();
+        assert_eq!(sent, 2);
+        assert_eq!(rx.recv().await.unwrap().all_domains.unwrap(), vec!["a.example.com"]);
+        assert_eq!(rx.recv().await.unwrap().all_domains.unwrap(), vec!["b.example.com"]);
+    }
+}
@@ -0,0 +1,195 @@
+// src/ct_log/schedule.rs
+use std::time::{Duration, Instant};
+
+/// Weight of the newest growth sample in the smoothed rate
+const RATE_SMOOTHING: f64 = 0.3;
+
+/// Bounds for growth-based poll intervals
+#[derive(Debug, Clone)]
+pub struct AdaptivePollConfig {
+    /// Shortest interval, used for the busiest logs
+    pub min_interval_secs: u64,
+    /// Longest interval, used for logs that barely grow
+    pub max_interval_secs: u64,
+}
+
+impl Default for AdaptivePollConfig {
+    fn default() -> Self {
+        Self {
+            min_interval_secs: 2,
+            max_interval_secs: 300,
+        }
+    }
+}
+
+/// Picks the next poll interval from a log's observed growth rate
+///
+/// The goal is to poll about once per `target_entries` new entries: fast logs
+/// (Argon, Nimbus) are polled often enough to stay near the head, while slow
+/// or frozen logs stop burning a get-sth request every few seconds.
+pub struct PollScheduler {
+    config: Option<AdaptivePollConfig>,
+    base_interval: Duration,
+    target_entries: u64,
+    last_sample: Option<(Instant, u64)>,
+    /// Smoothed growth in entries per minute
+    entries_per_min: Option<f64>,
+    /// Entries still unprocessed after the last poll
+    backlog: u64,
+}
+
+impl PollScheduler {
+    /// Create a scheduler; without a config the interval is always `base_interval`
+    pub fn new(base_interval: Duration, target_entries: u64, config: Option<AdaptivePollConfig>) -> Self {
+        Self {
+            config,
+            base_interval,
+            target_entries: target_entries.max(1),
+            last_sample: None,
+            entries_per_min: None,
+            backlog: 0,
+        }
+    }
+
+    /// Record the tree size seen at `now`
+    pub fn observe(&mut self, tree_size: u64, now: Instant) {
+        if let Some((last_time, last_size)) = self.last_sample {
+            let elapsed_min = now.duration_since(last_time).as_secs_f64() / 60.0;
+            if elapsed_min <= 0.0 {
+                return;
+            }
+
+            let sample = tree_size.saturating_sub(last_size) as f64 / elapsed_min;
+            self.entries_per_min = Some(match self.entries_per_min {
+                Some(rate) => RATE_SMOOTHING * sample + (1.0 - RATE_SMOOTHING) * rate,
+                None => sample,
+            });
+        }
+
+        self.last_sample = Some((now, tree_size));
+    }
+
+    /// Record how far behind the tree head the monitor still is
+    pub fn set_backlog(&mut self, backlog: u64) {
+        self.backlog = backlog;
+    }
+
+    /// Smoothed growth rate in entries per minute, once two samples exist
+    pub fn entries_per_min(&self) -> Option<f64> {
+        self.entries_per_min
+    }
+
+    /// Interval to wait before the next poll
+    pub fn next_interval(&self) -> Duration {
+        let Some(ref config) = self.config else {
+            return self.base_interval;
+        };
+        let Some(rate) = self.entries_per_min else {
+            return self.base_interval;
+        };
+
+        let min = config.min_interval_secs as f64;
+        let max = config.max_interval_secs.max(config.min_interval_secs) as f64;
+
+        // Still catching up: come back as soon as allowed
+        let secs = if self.backlog > 0 {
+            min
+        } else if rate > 0.0 {
+            self.target_entries as f64 / (rate / 60.0)
+        } else {
+            max
+        };
+
+        Duration::from_secs_f64(secs.clamp(min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler() -> PollScheduler {
+        PollScheduler::new(Duration::from_secs(10), 256, Some(AdaptivePollConfig::default()))
+    }
+
+    #[test]
+    fn test_uses_base_interval_until_rate_known() {
+        let mut scheduler = scheduler();
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(10));
+
+        scheduler.observe(1000, Instant::now());
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_fast_log_polls_at_floor() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+
+        scheduler.observe(0, start);
+        scheduler.observe(60_000, start + Duration::from_secs(60));
+
+        assert_eq!(scheduler.entries_per_min(), Some(60_000.0));
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_moderate_log_polls_once_per_batch() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+
+        // 1280 entries/min -> one 256-entry batch every 12s
+        scheduler.observe(0, start);
+        scheduler.observe(1280, start + Duration::from_secs(60));
+
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_frozen_log_polls_at_ceiling() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+
+        scheduler.observe(500, start);
+        scheduler.observe(500, start + Duration::from_secs(60));
+
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_rate_is_smoothed() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+
+        scheduler.observe(0, start);
+        scheduler.observe(1000, start + Duration::from_secs(60));
+        scheduler.observe(1000, start + Duration::from_secs(120));
+
+        // 0.3 * 0 + 0.7 * 1000
+        let rate = scheduler.entries_per_min().unwrap();
+        assert!((rate - 700.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_backlog_polls_at_floor() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+
+        scheduler.observe(500, start);
+        scheduler.observe(500, start + Duration::from_secs(60));
+        scheduler.set_backlog(10_000);
+
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_disabled_keeps_base_interval() {
+        let mut scheduler = PollScheduler::new(Duration::from_secs(10), 256, None);
+        let start = Instant::now();
+
+        scheduler.observe(0, start);
+        scheduler.observe(60_000, start + Duration::from_secs(60));
+
+        assert_eq!(scheduler.next_interval(), Duration::from_secs(10));
+    }
+}
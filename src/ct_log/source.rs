@@ -0,0 +1,88 @@
+// src/ct_log/source.rs
+//! Where certificates come from
+//!
+//! Every input (followed CT logs, range scans, captured JSONL, a certstream
+//! server) is a [`CertSource`] feeding the coordinator channel, so matching and
+//! outputs don't care where a certificate came from and inputs can be combined.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncBufRead;
+use tokio::sync::watch;
+
+use super::monitor::LogMonitor;
+use super::pipeline::CertSender;
+use super::replay;
+
+/// A producer of certificates for the coordinator
+#[async_trait]
+pub trait CertSource: Send + 'static {
+    /// Short description used in logs, e.g. the log URL
+    fn name(&self) -> String;
+
+    /// Send certificates until the input is exhausted or `shutdown_rx` fires
+    async fn run(self: Box<Self>, cert_tx: CertSender, shutdown_rx: watch::Receiver<bool>) -> Result<()>;
+}
+
+/// Follow the head of one CT log
+pub struct LogFollow(pub LogMonitor);
+
+#[async_trait]
+impl CertSource for LogFollow {
+    fn name(&self) -> String {
+        self.0.log_url().to_string()
+    }
+
+    async fn run(self: Box<Self>, cert_tx: CertSender, shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        self.0.run(cert_tx, shutdown_rx).await;
+        Ok(())
+    }
+}
+
+/// Entries `start..=end` of one CT log
+pub struct LogScan {
+    pub monitor: LogMonitor,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[async_trait]
+impl CertSource for LogScan {
+    fn name(&self) -> String {
+        format!("scan of {}", self.monitor.log_url())
+    }
+
+    // A scan is finite and always runs to completion
+    async fn run(self: Box<Self>, cert_tx: CertSender, _shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        self.monitor.scan(&cert_tx, self.start, self.end).await?;
+        Ok(())
+    }
+}
+
+/// Certificates captured earlier, one JSON object per line (see `replay`)
+pub struct ReplaySource<R> {
+    name: String,
+    input: R,
+}
+
+impl<R> ReplaySource<R> {
+    pub fn new(name: String, input: R) -> Self {
+        Self { name, input }
+    }
+}
+
+#[async_trait]
+impl<R> CertSource for ReplaySource<R>
+where
+    R: AsyncBufRead + Unpin + Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("replay of {}", self.name)
+    }
+
+    async fn run(self: Box<Self>, cert_tx: CertSender, shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        // Replays are finite, so wait for room rather than dropping or spilling entries
+        replay::replay(self.input, &cert_tx.blocking(), shutdown_rx).await?;
+        Ok(())
+    }
+}
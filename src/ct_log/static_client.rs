@@ -0,0 +1,408 @@
+// src/ct_log/static_client.rs
+//! Client for the Static CT API (https://c2sp.org/static-ct-api)
+//!
+//! Static logs (Sunlight, etc.) publish a signed checkpoint and the tree as
+//! immutable tiles instead of answering get-sth/get-entries. Entries are
+//! converted back into RFC 6962 `LogEntry` form so the rest of the pipeline
+//! (certificate parsing, auditing leaf hashes) works unchanged.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::debug;
+
+use super::client::{RetryPolicy, count_http_error, rate_limited_error, throttle, with_retry};
+use super::log_client::{LogClient, LogType};
+use super::rate_limit::{RateLimiter, RequestBudget};
+use super::types::{LogEntry, SignedTreeHead};
+
+/// Number of entries in a full data tile
+pub const TILE_WIDTH: u64 = 256;
+
+/// HTTP client for a Static CT API log
+pub struct StaticCtClient {
+    base_url: String,
+    http_client: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_budget: Option<RequestBudget>,
+    /// Tree size from the latest checkpoint, used to address partial tiles
+    tree_size: AtomicU64,
+}
+
+impl StaticCtClient {
+    /// Create a new static CT client for a log's monitoring prefix
+    pub fn new(base_url: String) -> Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .gzip(true)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self::with_http_client(base_url, http_client))
+    }
+
+    /// Create a client that sends requests through an existing (possibly shared) HTTP client
+    pub fn with_http_client(base_url: String, http_client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http_client,
+            rate_limiter: None,
+            request_budget: None,
+            tree_size: AtomicU64::new(0),
+        }
+    }
+
+    /// Limit requests to this log with a token bucket
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Share a global in-flight request budget with other clients
+    pub fn with_request_budget(mut self, budget: RequestBudget) -> Self {
+        self.request_budget = Some(budget);
+        self
+    }
+
+    /// GET a path under the monitoring prefix and return the body
+    async fn fetch(&self, path: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/{}", self.base_url, path);
+
+        let _permit = throttle(self.rate_limiter.as_deref(), self.request_budget.as_ref()).await;
+
+        let response = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .inspect_err(|_| count_http_error(&self.base_url, "network"))
+            .with_context(|| format!("Failed to fetch {}", url))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(
+                rate_limited_error(&self.base_url, self.rate_limiter.as_deref(), &response).await,
+            );
+        }
+
+        if !response.status().is_success() {
+            count_http_error(&self.base_url, response.status().as_str());
+            anyhow::bail!("Request for {} failed with status {}", url, response.status());
+        }
+
+        Ok(response.bytes().await.context("Failed to read response body")?.to_vec())
+    }
+
+    /// Get the latest checkpoint as a tree head
+    /// Endpoint: GET {base_url}/checkpoint
+    pub async fn get_checkpoint(&self) -> Result<SignedTreeHead> {
+        let body = self.fetch("checkpoint").await?;
+        let text = String::from_utf8(body).context("Checkpoint is not UTF-8")?;
+        let sth = parse_checkpoint(&text)?;
+
+        self.tree_size.store(sth.tree_size, Ordering::Relaxed);
+
+        debug!(
+            "Checkpoint received: tree_size={}, timestamp={}",
+            sth.tree_size, sth.timestamp
+        );
+
+        Ok(sth)
+    }
+
+    /// Get entries starting at `start`, up to `end` (inclusive)
+    /// Only reads the data tile containing `start`, so may return fewer entries
+    /// Endpoint: GET {base_url}/tile/data/<N>[.p/<W>]
+    pub async fn get_entries(&self, start: u64, end: u64) -> Result<Vec<LogEntry>> {
+        let mut tree_size = self.tree_size.load(Ordering::Relaxed);
+        if tree_size <= end {
+            tree_size = self.get_checkpoint().await?.tree_size;
+        }
+
+        if start > end || start >= tree_size {
+            return Ok(Vec::new());
+        }
+
+        let tile = start / TILE_WIDTH;
+        let tile_start = tile * TILE_WIDTH;
+        let width = (tree_size - tile_start).min(TILE_WIDTH);
+
+        debug!(
+            "Fetching data tile {} (width {}) from {}",
+            tile, width, self.base_url
+        );
+
+        let body = self.fetch(&data_tile_path(tile, width)).await?;
+        let leaves = parse_data_tile(&body, width as usize)
+            .with_context(|| format!("Failed to parse data tile {}", tile))?;
+
+        let skip = (start - tile_start) as usize;
+        let take = (end - start + 1) as usize;
+
+        Ok(leaves.into_iter().skip(skip).take(take).collect())
+    }
+}
+
+#[async_trait]
+impl LogClient for StaticCtClient {
+    fn log_type(&self) -> LogType {
+        LogType::StaticCt
+    }
+
+    async fn get_sth_with_retry(&self, retry: RetryPolicy) -> Result<SignedTreeHead> {
+        with_retry("checkpoint", retry, || self.get_checkpoint()).await
+    }
+
+    async fn get_entries_with_retry(
+        &self,
+        start: u64,
+        end: u64,
+        retry: RetryPolicy,
+    ) -> Result<Vec<LogEntry>> {
+        with_retry("tiles", retry, || self.get_entries(start, end)).await
+    }
+}
+
+/// Parse a checkpoint (signed note) into a tree head
+///
+/// The body is `origin\nsize\nbase64(root)\n[extensions]` followed by a blank
+/// line and signature lines. The timestamp comes from the log's RFC 6962 note
+/// signature (4-byte key ID, then 8-byte timestamp) when present.
+pub fn parse_checkpoint(text: &str) -> Result<SignedTreeHead> {
+    let (body, signatures) = text.split_once("\n\n").unwrap_or((text, ""));
+    let mut lines = body.lines();
+
+    let _origin = lines.next().context("Checkpoint missing origin line")?;
+    let tree_size = lines
+        .next()
+        .context("Checkpoint missing tree size")?
+        .trim()
+        .parse::<u64>()
+        .context("Invalid checkpoint tree size")?;
+    let root_hash = lines.next().context("Checkpoint missing root hash")?.trim();
+
+    let root = base64::engine::general_purpose::STANDARD
+        .decode(root_hash)
+        .context("Invalid base64 in checkpoint root hash")?;
+    if root.len() != 32 {
+        anyhow::bail!("Checkpoint root hash is {} bytes, expected 32", root.len());
+    }
+
+    let timestamp = signatures
+        .lines()
+        .filter_map(|line| line.rsplit(' ').next())
+        .filter_map(|sig| base64::engine::general_purpose::STANDARD.decode(sig).ok())
+        .find(|sig| sig.len() >= 12)
+        .map(|sig| u64::from_be_bytes(sig[4..12].try_into().unwrap()))
+        .unwrap_or(0);
+
+    Ok(SignedTreeHead {
+        tree_size,
+        timestamp,
+        sha256_root_hash: root_hash.to_string(),
+        tree_head_signature: String::new(),
+    })
+}
+
+/// Encode a tile index as path segments: 1234067 -> "x001/x234/067"
+pub fn tile_index_path(mut index: u64) -> String {
+    let mut parts = vec![format!("{:03}", index % 1000)];
+    index /= 1000;
+    while index > 0 {
+        parts.push(format!("x{:03}", index % 1000));
+        index /= 1000;
+    }
+    parts.reverse();
+    parts.join("/")
+}
+
+/// Path of a data tile; partial tiles carry a `.p/<width>` suffix
+fn data_tile_path(tile: u64, width: u64) -> String {
+    if width == TILE_WIDTH {
+        format!("tile/data/{}", tile_index_path(tile))
+    } else {
+        format!("tile/data/{}.p/{}", tile_index_path(tile), width)
+    }
+}
+
+/// Minimal cursor over TLS-encoded bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .context("Data tile truncated")?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn uint(&mut self, bytes: usize) -> Result<usize> {
+        Ok(self.take(bytes)?.iter().fold(0, |acc, &b| (acc << 8) | b as usize))
+    }
+
+    /// Read a length-prefixed opaque value with a `prefix`-byte length
+    fn opaque(&mut self, prefix: usize) -> Result<&'a [u8]> {
+        let len = self.uint(prefix)?;
+        self.take(len)
+    }
+}
+
+/// Parse a data tile into RFC 6962 style entries
+///
+/// Each TileLeaf is a TimestampedEntry, then the precertificate (precert
+/// entries only), then the chain fingerprints. The TimestampedEntry becomes
+/// the MerkleTreeLeaf (`leaf_input`), and the precertificate is placed in
+/// `extra_data` the way get-entries returns it.
+pub fn parse_data_tile(data: &[u8], width: usize) -> Result<Vec<LogEntry>> {
+    let engine = &base64::engine::general_purpose::STANDARD;
+    let mut reader = Reader { data, pos: 0 };
+    let mut entries = Vec::with_capacity(width);
+
+    for _ in 0..width {
+        let entry_start = reader.pos;
+
+        reader.take(8)?; // timestamp
+        let entry_type = reader.uint(2)?;
+        match entry_type {
+            0 => {
+                reader.opaque(3)?; // certificate
+            }
+            1 => {
+                reader.take(32)?; // issuer_key_hash
+                reader.opaque(3)?; // TBSCertificate
+            }
+            other => anyhow::bail!("Unknown entry type in data tile: {}", other),
+        }
+        reader.opaque(2)?; // extensions
+
+        // MerkleTreeLeaf: version v1 (0), leaf_type timestamped_entry (0)
+        let mut leaf_input = vec![0u8, 0u8];
+        leaf_input.extend_from_slice(&data[entry_start..reader.pos]);
+
+        let mut extra_data = Vec::new();
+        if entry_type == 1 {
+            let precert_start = reader.pos;
+            reader.opaque(3)?;
+            extra_data.extend_from_slice(&data[precert_start..reader.pos]);
+        }
+        reader.opaque(2)?; // certificate_chain fingerprints
+
+        entries.push(LogEntry {
+            leaf_input: engine.encode(leaf_input),
+            extra_data: engine.encode(extra_data),
+        });
+    }
+
+    if reader.pos != data.len() {
+        anyhow::bail!(
+            "Data tile has {} trailing bytes after {} entries",
+            data.len() - reader.pos,
+            width
+        );
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn x509_leaf(timestamp: u64, cert: &[u8]) -> Vec<u8> {
+        let mut leaf = timestamp.to_be_bytes().to_vec();
+        leaf.extend_from_slice(&[0, 0]);
+        leaf.extend_from_slice(&(cert.len() as u32).to_be_bytes()[1..]);
+        leaf.extend_from_slice(cert);
+        leaf.extend_from_slice(&[0, 0]); // extensions
+        leaf.extend_from_slice(&[0, 32]);
+        leaf.extend_from_slice(&[7u8; 32]); // one chain fingerprint
+        leaf
+    }
+
+    #[test]
+    fn test_tile_index_path() {
+        assert_eq!(tile_index_path(0), "000");
+        assert_eq!(tile_index_path(67), "067");
+        assert_eq!(tile_index_path(1234067), "x001/x234/067");
+        assert_eq!(data_tile_path(5, 256), "tile/data/005");
+        assert_eq!(data_tile_path(5, 17), "tile/data/005.p/17");
+    }
+
+    #[test]
+    fn test_parse_checkpoint() {
+        let root = base64::engine::general_purpose::STANDARD.encode([1u8; 32]);
+        let mut sig = vec![0xaa, 0xbb, 0xcc, 0xdd];
+        sig.extend_from_slice(&1700000000000u64.to_be_bytes());
+        sig.extend_from_slice(&[0u8; 8]);
+        let sig = base64::engine::general_purpose::STANDARD.encode(sig);
+
+        let text = format!("example.com/log\n1024\n{}\n\n\u{2014} example.com/log {}\n", root, sig);
+        let sth = parse_checkpoint(&text).unwrap();
+
+        assert_eq!(sth.tree_size, 1024);
+        assert_eq!(sth.timestamp, 1700000000000);
+        assert_eq!(sth.sha256_root_hash, root);
+
+        assert!(parse_checkpoint("example.com/log\nnot-a-number\nAAAA\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_data_tile_x509() {
+        let mut tile = x509_leaf(1, b"cert-one");
+        tile.extend(x509_leaf(2, b"cert-two"));
+
+        let entries = parse_data_tile(&tile, 2).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let leaf = base64::engine::general_purpose::STANDARD
+            .decode(&entries[1].leaf_input)
+            .unwrap();
+        // version + leaf_type, then the timestamped entry without the chain
+        assert_eq!(&leaf[..2], &[0, 0]);
+        assert_eq!(&leaf[2..10], &2u64.to_be_bytes());
+        assert_eq!(&leaf[15..23], b"cert-two");
+        assert_eq!(leaf.len(), 2 + 8 + 2 + 3 + 8 + 2);
+
+        assert!(parse_data_tile(&tile, 3).is_err());
+        assert!(parse_data_tile(&tile, 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_entries_reads_partial_tile() {
+        let mock_server = MockServer::start().await;
+        let root = base64::engine::general_purpose::STANDARD.encode([0u8; 32]);
+
+        Mock::given(method("GET"))
+            .and(path("/checkpoint"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(format!("example.com/log\n3\n{}\n", root)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let tile: Vec<u8> = (0..3).flat_map(|i| x509_leaf(i, b"cert")).collect();
+        Mock::given(method("GET"))
+            .and(path("/tile/data/000.p/3"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(tile))
+            .mount(&mock_server)
+            .await;
+
+        let client = StaticCtClient::new(format!("{}/", mock_server.uri())).unwrap();
+        let sth = client.get_sth_with_retry(RetryPolicy::once()).await.unwrap();
+        assert_eq!(sth.tree_size, 3);
+
+        let entries = client.get_entries_with_retry(1, 10, RetryPolicy::once()).await.unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+}
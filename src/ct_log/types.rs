@@ -1,4 +1,5 @@
 // src/ct_log/types.rs
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Response from CT log's get-sth endpoint
@@ -24,6 +25,19 @@ pub struct GetEntriesResponse {
     pub entries: Vec<LogEntry>,
 }
 
+/// Response from CT log's get-sth-consistency endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsistencyProofResponse {
+    pub consistency: Vec<String>,  // base64-encoded node hashes
+}
+
+/// Response from CT log's get-proof-by-hash endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InclusionProofResponse {
+    pub leaf_index: u64,
+    pub audit_path: Vec<String>,  // base64-encoded node hashes
+}
+
 /// Google's CT log list V3 format
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogListV3 {
@@ -37,6 +51,8 @@ pub struct Operator {
     pub email: Vec<String>,
     #[serde(default)]
     pub logs: Vec<LogInfo>,
+    #[serde(default)]
+    pub tiled_logs: Vec<LogInfo>,  // Static CT API logs
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +67,15 @@ pub struct LogInfo {
     #[serde(default)]
     pub state: Option<StateWrapper>,
     pub temporal_interval: Option<TemporalInterval>,
+    #[serde(default)]
+    pub monitoring_url: Option<String>,  // Static CT API logs only
+}
+
+impl LogInfo {
+    /// URL to read entries from (monitoring prefix for static CT logs)
+    pub fn monitor_url(&self) -> &str {
+        self.monitoring_url.as_deref().unwrap_or(&self.url)
+    }
 }
 
 /// State wrapper that can contain different state types
@@ -114,3 +139,84 @@ pub struct TemporalInterval {
     pub start_inclusive: Option<String>,
     pub end_exclusive: Option<String>,
 }
+
+impl TemporalInterval {
+    fn parse(ts: &Option<String>) -> Option<DateTime<Utc>> {
+        ts.as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// Check if the shard window has ended (no new certificates will be logged)
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        Self::parse(&self.end_exclusive).is_some_and(|end| end <= now)
+    }
+
+    /// Check if the shard window has not started yet
+    pub fn is_future(&self, now: DateTime<Utc>) -> bool {
+        Self::parse(&self.start_inclusive).is_some_and(|start| start > now)
+    }
+
+    /// Check if the shard window covers the given time
+    pub fn covers(&self, now: DateTime<Utc>) -> bool {
+        !self.is_expired(now) && !self.is_future(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: &str, end: &str) -> TemporalInterval {
+        TemporalInterval {
+            start_inclusive: Some(start.to_string()),
+            end_exclusive: Some(end.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_temporal_interval_windows() {
+        let now = DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let expired = interval("2023-01-01T00:00:00Z", "2024-01-01T00:00:00Z");
+        assert!(expired.is_expired(now));
+        assert!(!expired.covers(now));
+
+        let current = interval("2025-01-01T00:00:00Z", "2026-01-01T00:00:00Z");
+        assert!(current.covers(now));
+
+        let future = interval("2026-01-01T00:00:00Z", "2027-01-01T00:00:00Z");
+        assert!(future.is_future(now));
+        assert!(!future.is_expired(now));
+    }
+
+    #[test]
+    fn test_temporal_interval_unparseable_is_open() {
+        let now = Utc::now();
+        let open = TemporalInterval {
+            start_inclusive: None,
+            end_exclusive: Some("not a date".to_string()),
+        };
+        assert!(open.covers(now));
+    }
+
+    #[test]
+    fn test_tiled_logs_use_monitoring_url() {
+        let list: LogListV3 = serde_json::from_str(r#"{
+            "operators": [{
+                "name": "Example",
+                "logs": [{"url": "https://ct.example.com/rfc6962/"}],
+                "tiled_logs": [{
+                    "submission_url": "https://submit.example.com/2025h1/",
+                    "monitoring_url": "https://tiles.example.com/2025h1/"
+                }]
+            }]
+        }"#).unwrap();
+
+        let operator = &list.operators[0];
+        assert_eq!(operator.logs[0].monitor_url(), "https://ct.example.com/rfc6962/");
+        assert_eq!(operator.tiled_logs[0].monitor_url(), "https://tiles.example.com/2025h1/");
+    }
+}
@@ -0,0 +1,396 @@
+// src/dashboard.rs
+//! Full-screen terminal dashboard (`--tui`)
+//!
+//! Redraws once a second with global stats, a table of CT logs (state, lag,
+//! fetch rate, health) and the most recent matches. Matches reach it through
+//! `DashboardOutput`, registered with the `OutputManager` like any other handler.
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::{HashMap, VecDeque};
+use std::io::Stdout;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::ct_log::{CircuitState, LogHealth, LogHealthTracker};
+use crate::metrics;
+use crate::output::OutputHandler;
+use crate::stats::StatsCollector;
+use crate::types::MatchResult;
+
+/// Time between redraws
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Matches kept for the recent matches pane
+const MAX_RECENT: usize = 200;
+
+/// Rows a bordered table needs besides its data rows (borders and header)
+const TABLE_CHROME: u16 = 3;
+
+/// Live dashboard state shared between the output handler and the draw loop
+pub struct Dashboard {
+    stats: StatsCollector,
+    recent: Arc<Mutex<VecDeque<MatchResult>>>,
+}
+
+/// Output handler feeding matches into the dashboard
+pub struct DashboardOutput {
+    recent: Arc<Mutex<VecDeque<MatchResult>>>,
+}
+
+/// One row of the logs table
+struct LogRow {
+    url: String,
+    status: LogHealth,
+    circuit: CircuitState,
+    lag: i64,
+    rate: f64,  // Entries fetched per second since the previous redraw
+    failures: u32,
+    last_error: Option<String>,
+}
+
+/// Everything drawn in one frame
+struct View {
+    uptime_secs: u64,
+    processed: u64,
+    matches: u64,
+    per_minute: f64,
+    channel_depth: i64,
+    suppressed: u64,
+    logs: Vec<LogRow>,
+    recent: Vec<MatchResult>,  // Newest first
+}
+
+impl Dashboard {
+    pub fn new(stats: StatsCollector) -> Self {
+        Self {
+            stats,
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_RECENT))),
+        }
+    }
+
+    /// Handler to add to the `OutputManager`
+    pub fn output(&self) -> Arc<DashboardOutput> {
+        Arc::new(DashboardOutput {
+            recent: Arc::clone(&self.recent),
+        })
+    }
+
+    /// Take over the terminal and redraw until the task is aborted
+    ///
+    /// Aborting the task restores the screen and cursor.
+    pub fn spawn(self, health: Arc<LogHealthTracker>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut screen = match Screen::enter() {
+                Ok(screen) => screen,
+                Err(e) => {
+                    warn!("Failed to start the dashboard: {}", e);
+                    return;
+                }
+            };
+            let mut fetched: HashMap<String, u64> = HashMap::new();
+            let mut last_draw = Instant::now();
+            let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+
+            loop {
+                ticker.tick().await;
+                let elapsed = last_draw.elapsed().as_secs_f64().max(f64::EPSILON);
+                last_draw = Instant::now();
+
+                let view = self.collect(&health, &mut fetched, elapsed).await;
+                if let Err(e) = screen.0.draw(|frame| render(frame, &view)) {
+                    warn!("Failed to draw the dashboard: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn collect(
+        &self,
+        health: &LogHealthTracker,
+        fetched: &mut HashMap<String, u64>,
+        elapsed: f64,
+    ) -> View {
+        let snapshot = self.stats.snapshot();
+        let logs = health
+            .snapshot()
+            .await
+            .into_iter()
+            .map(|(url, info)| {
+                let total = metrics::CT_ENTRIES_FETCHED_TOTAL.with_label_values(&[&url]).get();
+                let previous = fetched.insert(url.clone(), total).unwrap_or(total);
+                LogRow {
+                    lag: metrics::CT_LOG_LAG.with_label_values(&[&url]).get(),
+                    rate: total.saturating_sub(previous) as f64 / elapsed,
+                    status: info.status,
+                    circuit: info.circuit,
+                    failures: info.failure_count,
+                    last_error: info.last_error,
+                    url,
+                }
+            })
+            .collect();
+        let recent = self.recent.lock().unwrap().iter().rev().cloned().collect();
+
+        View {
+            uptime_secs: snapshot.uptime_secs,
+            processed: snapshot.total_processed,
+            matches: snapshot.matches_found,
+            per_minute: snapshot.messages_per_minute,
+            channel_depth: metrics::CERT_CHANNEL_DEPTH.get(),
+            suppressed: metrics::DEDUPE_SUPPRESSED_TOTAL.get(),
+            logs,
+            recent,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputHandler for DashboardOutput {
+    fn name(&self) -> &'static str {
+        "tui"
+    }
+
+    async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == MAX_RECENT {
+            recent.pop_front();
+        }
+        recent.push_back(result.clone());
+        Ok(())
+    }
+
+    async fn flush(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Alternate screen with a hidden cursor, restored on drop
+///
+/// Raw mode stays off, so Ctrl+C still reaches the shutdown handler.
+struct Screen(Terminal<CrosstermBackend<Stdout>>);
+
+impl Screen {
+    fn enter() -> std::io::Result<Self> {
+        let mut stdout = std::io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        terminal.hide_cursor()?;
+        Ok(Self(terminal))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = self.0.show_cursor();
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+    }
+}
+
+/// Lay out a frame: stats line, logs in up to half the screen, matches below
+fn render(frame: &mut Frame, view: &View) {
+    let (healthy, degraded, failed, quarantined) =
+        view.logs.iter().fold((0, 0, 0, 0), |(h, d, f, q), log| match log.status {
+            LogHealth::Healthy => (h + 1, d, f, q),
+            LogHealth::Degraded => (h, d + 1, f, q),
+            LogHealth::Failed => (h, d, f + 1, q),
+            LogHealth::Quarantined => (h, d, f, q + 1),
+        });
+
+    let logs_height = (view.logs.len() as u16 + TABLE_CHROME).min(frame.area().height / 2);
+    let [stats_area, logs_area, matches_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(logs_height),
+        Constraint::Fill(1),
+    ])
+    .areas(frame.area());
+
+    let stats = format!(
+        "ct-scout | uptime {} | {} processed | {} matches | {:.1} msg/min | channel {} | {} duplicates suppressed",
+        StatsCollector::format_uptime(view.uptime_secs),
+        view.processed,
+        view.matches,
+        view.per_minute,
+        view.channel_depth,
+        view.suppressed
+    );
+    frame.render_widget(Paragraph::new(stats).bold(), stats_area);
+
+    let mut logs_title = format!(
+        " Logs: {} healthy, {} degraded, {} failed, {} quarantined ",
+        healthy, degraded, failed, quarantined
+    );
+    let hidden = view.logs.len().saturating_sub(logs_height.saturating_sub(TABLE_CHROME) as usize);
+    if hidden > 0 {
+        logs_title.push_str(&format!("(+{} more) ", hidden));
+    }
+    let log_rows = view.logs.iter().map(|log| {
+        let style = match log.status {
+            LogHealth::Healthy => Style::new(),
+            LogHealth::Degraded => Style::new().yellow(),
+            LogHealth::Failed | LogHealth::Quarantined => Style::new().red(),
+        };
+        Row::new([
+            StatsCollector::short_log_name(&log.url).to_string(),
+            state(log.status, log.circuit).to_string(),
+            log.lag.to_string(),
+            format!("{:.1}", log.rate),
+            health_summary(log),
+        ])
+        .style(style)
+    });
+    let logs = Table::new(
+        log_rows,
+        [
+            Constraint::Length(24),
+            Constraint::Length(11),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["LOG", "STATE", "LAG", "RATE/s", "HEALTH"]).bold())
+    .block(Block::bordered().title(logs_title));
+    frame.render_widget(logs, logs_area);
+
+    let match_rows = view.recent.iter().map(|result| {
+        Row::new([
+            DateTime::from_timestamp(result.timestamp as i64, 0)
+                .map(|at| at.format("%H:%M:%S").to_string())
+                .unwrap_or_default(),
+            result.matched_domain.clone(),
+            result.program_name.clone().unwrap_or_else(|| "-".to_string()),
+            result.ct_log_url.as_deref().map(StatsCollector::short_log_name).unwrap_or("-").to_string(),
+        ])
+    });
+    let matches = Table::new(
+        match_rows,
+        [
+            Constraint::Length(10),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["TIME (UTC)", "DOMAIN", "PROGRAM", "LOG"]).bold())
+    .block(Block::bordered().title(" Recent matches "));
+    frame.render_widget(matches, matches_area);
+}
+
+fn state(status: LogHealth, circuit: CircuitState) -> &'static str {
+    match (status, circuit) {
+        (LogHealth::Quarantined, _) => "quarantined",
+        (_, CircuitState::Open) => "backoff",
+        (_, CircuitState::HalfOpen) => "probing",
+        (_, CircuitState::Closed) => "polling",
+    }
+}
+
+fn health_summary(log: &LogRow) -> String {
+    let status = match log.status {
+        LogHealth::Healthy => "healthy",
+        LogHealth::Degraded => "degraded",
+        LogHealth::Failed => "failed",
+        LogHealth::Quarantined => "quarantined",
+    };
+    match (log.failures, log.last_error.as_deref()) {
+        (0, _) => status.to_string(),
+        (failures, Some(error)) => format!("{} ({} failures: {})", status, failures, error),
+        (failures, None) => format!("{} ({} failures)", status, failures),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CertData;
+    use ratatui::backend::TestBackend;
+
+    fn result(domain: &str) -> MatchResult {
+        let data = CertData {
+            all_domains: Some(vec![domain.to_string()]),
+            ip_addresses: None,
+            cert_index: Some(1),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: Some("https://ct.example.com/2025h1/".to_string()),
+        };
+        let mut result = MatchResult::from_cert_data(domain.to_string(), &data, Some("Acme".to_string()), None);
+        result.timestamp = 1_700_000_000;
+        result
+    }
+
+    #[tokio::test]
+    async fn test_output_keeps_recent_matches() {
+        let dashboard = Dashboard::new(StatsCollector::new());
+        let output = dashboard.output();
+        for i in 0..MAX_RECENT + 5 {
+            output.emit_match(&result(&format!("{}.example.com", i))).await.unwrap();
+        }
+
+        let recent = dashboard.recent.lock().unwrap();
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent.front().unwrap().matched_domain, "5.example.com");
+    }
+
+    #[test]
+    fn test_render_fits_screen() {
+        let logs = (0..20)
+            .map(|i| LogRow {
+                url: format!("https://ct{}.example.com/log/", i),
+                status: if i == 0 { LogHealth::Failed } else { LogHealth::Healthy },
+                circuit: if i == 0 { CircuitState::Open } else { CircuitState::Closed },
+                lag: 12,
+                rate: 2.5,
+                failures: if i == 0 { 3 } else { 0 },
+                last_error: (i == 0).then(|| "HTTP 503".to_string()),
+            })
+            .collect();
+        let view = View {
+            uptime_secs: 65,
+            processed: 1000,
+            matches: 2,
+            per_minute: 923.1,
+            channel_depth: 4,
+            suppressed: 7,
+            logs,
+            recent: vec![result("api.example.com"), result("www.example.com")],
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal.draw(|frame| render(frame, &view)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let lines: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+
+        assert!(lines[0].starts_with("ct-scout | uptime 1m 5s | 1000 processed | 2 matches"));
+        // 12 rows for logs: borders, header and 9 of the 20 logs
+        assert_eq!(lines[1], format!("┌ Logs: 19 healthy, 0 degraded, 1 failed, 0 quarantined (+11 more) {}┐", "─".repeat(32)));
+        assert!(lines[3].starts_with("│ct0.example.com/log      backoff     12       2.5     failed (3 failures: HTTP 503)"));
+        assert!(lines[12].starts_with("└"));
+        assert!(lines[13].starts_with("┌ Recent matches "));
+        assert!(lines[14].starts_with("│TIME (UTC) DOMAIN"));
+        assert!(lines[15].starts_with("│22:13:20   api.example.com"));
+        assert!(lines[15].contains("Acme"));
+        assert!(lines[15].contains("ct.example.com/2025h1"));
+        assert!(lines[23].starts_with("└"));
+    }
+}
@@ -0,0 +1,212 @@
+// src/database/leases.rs
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Time-limited claims on CT logs, so several instances can split the logs
+/// between them through a shared PostgreSQL database
+///
+/// Each instance renews its leases well within `ttl`; leases of an instance
+/// that stops renewing expire and are picked up by the others.
+pub struct LogLeases {
+    pool: PgPool,
+    instance_id: String,
+    ttl: Duration,
+}
+
+/// What to do with our leases to reach a fair share
+#[derive(Debug, PartialEq, Eq)]
+struct LeasePlan {
+    /// Leases to hand back, either surplus or for logs no longer monitored
+    release: Vec<String>,
+    /// How many more logs to claim
+    claim: usize,
+}
+
+/// Split `logs` evenly across `members` live instances, keeping as many of
+/// the logs we already own as our share allows
+fn plan(owned: &HashSet<String>, logs: &[String], members: usize) -> LeasePlan {
+    let share = logs.len().div_ceil(members.max(1));
+    let wanted: HashSet<&String> = logs.iter().collect();
+
+    let mut kept: Vec<&String> = owned.iter().filter(|url| wanted.contains(url)).collect();
+    kept.sort();
+    let mut release: Vec<String> = owned
+        .iter()
+        .filter(|url| !wanted.contains(url))
+        .cloned()
+        .collect();
+    if kept.len() > share {
+        release.extend(kept.split_off(share).into_iter().cloned());
+    }
+    release.sort();
+
+    LeasePlan {
+        release,
+        claim: share - kept.len(),
+    }
+}
+
+impl LogLeases {
+    /// Connect to the database holding the leases (tables come from `PostgresBackend::migrate`)
+    pub async fn connect(database_url: &str, instance_id: String, ttl: Duration) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(database_url)
+            .await
+            .context("Failed to connect to PostgreSQL for log leases")?;
+        info!("Joining ct-scout cluster as {}", instance_id);
+        Ok(Self { pool, instance_id, ttl })
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// How long a lease lasts without renewal
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Renew our leases, hand back any beyond our share of `logs` and claim
+    /// free or expired ones up to it; returns the logs we now hold
+    pub async fn rebalance(&self, logs: &[String]) -> Result<HashSet<String>> {
+        let ttl_secs = self.ttl.as_secs_f64();
+
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_members (instance_id, last_seen)
+            VALUES ($1, NOW())
+            ON CONFLICT (instance_id) DO UPDATE SET last_seen = NOW()
+            "#,
+        )
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record cluster membership")?;
+
+        let members: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM cluster_members WHERE last_seen > NOW() - $1::float8 * INTERVAL '1 second'",
+        )
+        .bind(ttl_secs)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count cluster members")?;
+
+        let renewed: Vec<String> = sqlx::query_scalar(
+            r#"
+            UPDATE log_leases SET expires_at = NOW() + $2::float8 * INTERVAL '1 second'
+            WHERE owner = $1
+            RETURNING log_url
+            "#,
+        )
+        .bind(&self.instance_id)
+        .bind(ttl_secs)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to renew log leases")?;
+        let mut owned: HashSet<String> = renewed.into_iter().collect();
+
+        let plan = plan(&owned, logs, members as usize);
+
+        if !plan.release.is_empty() {
+            sqlx::query("DELETE FROM log_leases WHERE owner = $1 AND log_url = ANY($2)")
+                .bind(&self.instance_id)
+                .bind(&plan.release)
+                .execute(&self.pool)
+                .await
+                .context("Failed to release log leases")?;
+            debug!("Released {} log leases", plan.release.len());
+            for url in &plan.release {
+                owned.remove(url);
+            }
+        }
+
+        if plan.claim > 0 {
+            // Another instance may claim the same log in between; the upsert
+            // only takes over leases that are still expired
+            let claimed: Vec<String> = sqlx::query_scalar(
+                r#"
+                INSERT INTO log_leases (log_url, owner, expires_at)
+                SELECT url, $1, NOW() + $3::float8 * INTERVAL '1 second'
+                FROM (
+                    SELECT url FROM UNNEST($2::text[]) AS url
+                    WHERE NOT EXISTS (
+                        SELECT 1 FROM log_leases WHERE log_url = url AND expires_at > NOW()
+                    )
+                    ORDER BY random()
+                    LIMIT $4
+                ) AS free
+                ON CONFLICT (log_url) DO UPDATE
+                SET owner = EXCLUDED.owner, expires_at = EXCLUDED.expires_at
+                WHERE log_leases.expires_at <= NOW()
+                RETURNING log_url
+                "#,
+            )
+            .bind(&self.instance_id)
+            .bind(logs)
+            .bind(ttl_secs)
+            .bind(plan.claim as i64)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to claim log leases")?;
+            if !claimed.is_empty() {
+                debug!("Claimed {} log leases", claimed.len());
+            }
+            owned.extend(claimed);
+        }
+
+        Ok(owned)
+    }
+
+    /// Hand back every lease and leave the cluster, so others take over at once
+    pub async fn release_all(&self) -> Result<()> {
+        sqlx::query("DELETE FROM log_leases WHERE owner = $1")
+            .bind(&self.instance_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to release log leases")?;
+        sqlx::query("DELETE FROM cluster_members WHERE instance_id = $1")
+            .bind(&self.instance_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to leave the cluster")?;
+        info!("Released all log leases");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| format!("https://{}/", name)).collect()
+    }
+
+    #[test]
+    fn test_plan_splits_logs_evenly() {
+        let logs = urls(&["a", "b", "c", "d", "e"]);
+
+        // Alone: take everything
+        assert_eq!(plan(&HashSet::new(), &logs, 1), LeasePlan { release: vec![], claim: 5 });
+
+        // A second instance joined: hand back all but our share of 3
+        let owned: HashSet<String> = logs.iter().cloned().collect();
+        assert_eq!(plan(&owned, &logs, 2), LeasePlan { release: urls(&["d", "e"]), claim: 0 });
+
+        // The other instance died: claim the rest
+        let owned: HashSet<String> = urls(&["a", "b"]).into_iter().collect();
+        assert_eq!(plan(&owned, &logs, 1), LeasePlan { release: vec![], claim: 3 });
+    }
+
+    #[test]
+    fn test_plan_releases_dropped_logs() {
+        let logs = urls(&["a", "b"]);
+        let owned: HashSet<String> = urls(&["a", "retired"]).into_iter().collect();
+        assert_eq!(plan(&owned, &logs, 1), LeasePlan { release: urls(&["retired"]), claim: 1 });
+    }
+}
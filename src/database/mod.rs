@@ -2,13 +2,18 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
+use crate::state::LogProgress;
 use crate::types::MatchResult;
 
+pub mod leases;
 pub mod postgres;
+pub mod sqlite_state;
 pub mod state_manager;
 
+pub use leases::LogLeases;
 pub use postgres::PostgresBackend;
-pub use state_manager::DbStateManager;
+pub use sqlite_state::SqliteStateManager;
+pub use state_manager::{DbStateLease, DbStateManager};
 
 /// Query parameters for fetching matches from database
 #[derive(Debug, Clone)]
@@ -34,6 +39,24 @@ impl Default for MatchQuery {
     }
 }
 
+/// Saved position of a CT log, with when it last advanced
+#[derive(Debug, Clone)]
+pub struct LogStateRecord {
+    pub log_url: String,
+    pub last_index: u64,
+    pub last_updated: u64,  // Unix timestamp
+    pub tree_size: Option<u64>,  // From the latest STH
+}
+
+impl From<LogStateRecord> for LogProgress {
+    fn from(record: LogStateRecord) -> Self {
+        Self {
+            last_updated: Some(record.last_updated),
+            tree_size: record.tree_size,
+        }
+    }
+}
+
 /// Database backend trait for state and match storage
 #[async_trait]
 pub trait DatabaseBackend: Send + Sync {
@@ -52,6 +75,30 @@ pub trait DatabaseBackend: Send + Sync {
     /// Get all tracked log URLs with their last indices
     async fn get_all_log_states(&self) -> Result<Vec<(String, u64)>>;
 
+    /// Get all tracked logs with their last indices and update times
+    async fn get_log_state_records(&self) -> Result<Vec<LogStateRecord>>;
+
+    /// Get one tracked log with its last index and update time
+    async fn get_log_state_record(&self, log_url: &str) -> Result<Option<LogStateRecord>>;
+
+    /// Record the tree size from a log's latest STH (ignored until the log has a saved index)
+    async fn update_log_tree_size(&self, log_url: &str, tree_size: u64) -> Result<()>;
+
+    /// Count stored matches, optionally only those seen since a Unix timestamp
+    async fn count_matches(&self, since: Option<u64>) -> Result<u64>;
+
+    /// Record an unprocessed index range (inclusive) for a CT log
+    async fn add_log_gap(&self, log_url: &str, start: u64, end: u64) -> Result<()>;
+
+    /// Remove a processed index range for a CT log
+    async fn remove_log_gap(&self, log_url: &str, start: u64, end: u64) -> Result<()>;
+
+    /// Get unprocessed index ranges for a CT log, oldest first
+    async fn get_log_gaps(&self, log_url: &str) -> Result<Vec<(u64, u64)>>;
+
+    /// Delete a CT log's state and gaps
+    async fn remove_log_state(&self, log_url: &str) -> Result<()>;
+
     /// Health check
     async fn ping(&self) -> Result<()>;
 }
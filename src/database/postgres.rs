@@ -5,8 +5,8 @@ use sqlx::postgres::{PgPool, PgPoolOptions};
 use sqlx::Row;
 use tracing::{debug, info};
 
-use super::{DatabaseBackend, MatchQuery};
-use crate::types::MatchResult;
+use super::{DatabaseBackend, LogStateRecord, MatchQuery};
+use crate::types::{ChainCert, DistinguishedName, MatchResult, PkiEndpoints, SCHEMA_VERSION};
 
 /// PostgreSQL database backend
 pub struct PostgresBackend {
@@ -46,6 +46,26 @@ impl PostgresBackend {
         .await
         .context("Failed to create ct_log_state table")?;
 
+        sqlx::query("ALTER TABLE ct_log_state ADD COLUMN IF NOT EXISTS tree_size BIGINT")
+            .execute(&self.pool)
+            .await
+            .context("Failed to add tree_size column to ct_log_state table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ct_log_gaps (
+                log_url TEXT NOT NULL,
+                start_index BIGINT NOT NULL,
+                end_index BIGINT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (log_url, start_index, end_index)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create ct_log_gaps table")?;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS matches (
@@ -67,6 +87,87 @@ impl PostgresBackend {
         .await
         .context("Failed to create matches table")?;
 
+        sqlx::query("ALTER TABLE matches ADD COLUMN IF NOT EXISTS logged_at BIGINT")
+            .execute(&self.pool)
+            .await
+            .context("Failed to add logged_at column to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS serial_number TEXT,
+                ADD COLUMN IF NOT EXISTS subject_key_id TEXT,
+                ADD COLUMN IF NOT EXISTS authority_key_id TEXT
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add certificate identifier columns to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS key_algorithm TEXT,
+                ADD COLUMN IF NOT EXISTS key_bits INTEGER,
+                ADD COLUMN IF NOT EXISTS key_curve TEXT,
+                ADD COLUMN IF NOT EXISTS signature_algorithm TEXT
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add key algorithm columns to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS matched_pattern TEXT,
+                ADD COLUMN IF NOT EXISTS match_type TEXT
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add match provenance columns to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS subject_cn TEXT,
+                ADD COLUMN IF NOT EXISTS subject_o TEXT,
+                ADD COLUMN IF NOT EXISTS subject_ou TEXT,
+                ADD COLUMN IF NOT EXISTS subject_c TEXT,
+                ADD COLUMN IF NOT EXISTS issuer_cn TEXT,
+                ADD COLUMN IF NOT EXISTS issuer_o TEXT,
+                ADD COLUMN IF NOT EXISTS issuer_ou TEXT,
+                ADD COLUMN IF NOT EXISTS issuer_c TEXT
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add subject/issuer name columns to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS chain_names TEXT[],
+                ADD COLUMN IF NOT EXISTS chain_fingerprints TEXT[]
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add issuer chain columns to matches table")?;
+
+        sqlx::query(
+            r#"
+            ALTER TABLE matches
+                ADD COLUMN IF NOT EXISTS crl_urls TEXT[],
+                ADD COLUMN IF NOT EXISTS ocsp_urls TEXT[],
+                ADD COLUMN IF NOT EXISTS ca_issuer_urls TEXT[]
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to add PKI endpoint columns to matches table")?;
+
         // Create indices for performance
         sqlx::query(
             r#"
@@ -99,6 +200,31 @@ impl PostgresBackend {
         .await
         .context("Failed to create index on program_name")?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS log_leases (
+                log_url TEXT PRIMARY KEY,
+                owner TEXT NOT NULL,
+                expires_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create log_leases table")?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cluster_members (
+                instance_id TEXT PRIMARY KEY,
+                last_seen TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create cluster_members table")?;
+
         info!("Database migrations completed successfully");
 
         Ok(())
@@ -110,15 +236,66 @@ impl PostgresBackend {
     }
 }
 
+/// Rebuild a subject/issuer name from its `<prefix>_cn`, `_o`, `_ou` and `_c` columns
+fn dn_from_row(row: &sqlx::postgres::PgRow, prefix: &str) -> Option<DistinguishedName> {
+    let dn = DistinguishedName {
+        common_name: row.get(format!("{}_cn", prefix).as_str()),
+        organization: row.get(format!("{}_o", prefix).as_str()),
+        organizational_unit: row.get(format!("{}_ou", prefix).as_str()),
+        country: row.get(format!("{}_c", prefix).as_str()),
+    };
+
+    (dn != DistinguishedName::default()).then_some(dn)
+}
+
+/// Rebuild the issuer chain from the parallel `chain_names`/`chain_fingerprints` arrays
+fn chain_from_row(row: &sqlx::postgres::PgRow) -> Vec<ChainCert> {
+    let names: Option<Vec<String>> = row.get("chain_names");
+    let fingerprints: Option<Vec<String>> = row.get("chain_fingerprints");
+
+    names
+        .unwrap_or_default()
+        .into_iter()
+        .zip(fingerprints.unwrap_or_default())
+        .map(|(name, fingerprint)| ChainCert { name, fingerprint })
+        .collect()
+}
+
+fn log_state_record(row: &sqlx::postgres::PgRow) -> LogStateRecord {
+    LogStateRecord {
+        log_url: row.get("log_url"),
+        last_index: row.get::<i64, _>("last_index") as u64,
+        last_updated: row.get::<i64, _>("last_updated") as u64,
+        tree_size: row.get::<Option<i64>, _>("tree_size").map(|size| size as u64),
+    }
+}
+
 #[async_trait]
 impl DatabaseBackend for PostgresBackend {
     async fn save_match(&self, match_result: &MatchResult) -> Result<()> {
+        let subject = match_result.subject.clone().unwrap_or_default();
+        let issuer_dn = match_result.issuer_dn.clone().unwrap_or_default();
+        let (chain_names, chain_fingerprints): (Vec<String>, Vec<String>) = match_result
+            .issuer_chain
+            .iter()
+            .map(|ca| (ca.name.clone(), ca.fingerprint.clone()))
+            .unzip();
+
         sqlx::query(
             r#"
             INSERT INTO matches (
                 timestamp, matched_domain, all_domains, cert_index,
-                not_before, not_after, fingerprint, program_name, seen_unix
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                not_before, not_after, fingerprint, program_name, seen_unix,
+                logged_at, serial_number, subject_key_id, authority_key_id,
+                key_algorithm, key_bits, key_curve, signature_algorithm,
+                subject_cn, subject_o, subject_ou, subject_c,
+                issuer_cn, issuer_o, issuer_ou, issuer_c,
+                chain_names, chain_fingerprints,
+                crl_urls, ocsp_urls, ca_issuer_urls,
+                matched_pattern, match_type
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
+                      $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25,
+                      $26, $27, $28, $29, $30, $31, $32)
             "#,
         )
         .bind(match_result.timestamp as i64)
@@ -130,6 +307,29 @@ impl DatabaseBackend for PostgresBackend {
         .bind(&match_result.fingerprint)
         .bind(&match_result.program_name)
         .bind(match_result.seen_unix)
+        .bind(match_result.logged_at.map(|t| t as i64))
+        .bind(&match_result.serial_number)
+        .bind(&match_result.subject_key_id)
+        .bind(&match_result.authority_key_id)
+        .bind(&match_result.key_algorithm)
+        .bind(match_result.key_bits.map(|bits| bits as i32))
+        .bind(&match_result.key_curve)
+        .bind(&match_result.signature_algorithm)
+        .bind(subject.common_name)
+        .bind(subject.organization)
+        .bind(subject.organizational_unit)
+        .bind(subject.country)
+        .bind(issuer_dn.common_name)
+        .bind(issuer_dn.organization)
+        .bind(issuer_dn.organizational_unit)
+        .bind(issuer_dn.country)
+        .bind(chain_names)
+        .bind(chain_fingerprints)
+        .bind(&match_result.pki_endpoints.crl)
+        .bind(&match_result.pki_endpoints.ocsp)
+        .bind(&match_result.pki_endpoints.ca_issuers)
+        .bind(&match_result.matched_pattern)
+        .bind(match_result.match_type.map(|t| t.as_str()))
         .execute(&self.pool)
         .await
         .context("Failed to insert match into database")?;
@@ -143,7 +343,14 @@ impl DatabaseBackend for PostgresBackend {
         let mut sql = String::from(
             r#"
             SELECT timestamp, matched_domain, all_domains, cert_index,
-                   not_before, not_after, fingerprint, program_name, seen_unix
+                   not_before, not_after, fingerprint, program_name, seen_unix,
+                   logged_at, serial_number, subject_key_id, authority_key_id,
+                   key_algorithm, key_bits, key_curve, signature_algorithm,
+                   subject_cn, subject_o, subject_ou, subject_c,
+                   issuer_cn, issuer_o, issuer_ou, issuer_c,
+                   chain_names, chain_fingerprints,
+                   crl_urls, ocsp_urls, ca_issuer_urls,
+                   matched_pattern, match_type
             FROM matches
             WHERE 1=1
             "#,
@@ -204,17 +411,46 @@ impl DatabaseBackend for PostgresBackend {
 
         let mut results = Vec::new();
         for row in rows {
+            let timestamp = row.get::<i64, _>("timestamp") as u64;
             results.push(MatchResult {
-                timestamp: row.get::<i64, _>("timestamp") as u64,
+                schema_version: SCHEMA_VERSION,
+                timestamp,
                 matched_domain: row.get("matched_domain"),
+                matched_pattern: row.get("matched_pattern"),
+                match_type: row
+                    .get::<Option<String>, _>("match_type")
+                    .and_then(|t| t.parse().ok()),
                 all_domains: row.get("all_domains"),
                 cert_index: row.get::<Option<i64>, _>("cert_index").map(|i| i as u64),
                 not_before: row.get::<Option<i64>, _>("not_before").map(|i| i as u64),
                 not_after: row.get::<Option<i64>, _>("not_after").map(|i| i as u64),
                 fingerprint: row.get("fingerprint"),
+                serial_number: row.get("serial_number"),
+                subject_key_id: row.get("subject_key_id"),
+                authority_key_id: row.get("authority_key_id"),
+                key_algorithm: row.get("key_algorithm"),
+                key_bits: row.get::<Option<i32>, _>("key_bits").map(|bits| bits as u32),
+                key_curve: row.get("key_curve"),
+                signature_algorithm: row.get("signature_algorithm"),
+                subject: dn_from_row(&row, "subject"),
+                issuer_dn: dn_from_row(&row, "issuer"),
+                issuer_chain: chain_from_row(&row),
+                pki_endpoints: PkiEndpoints {
+                    crl: row.get::<Option<Vec<String>>, _>("crl_urls").unwrap_or_default(),
+                    ocsp: row.get::<Option<Vec<String>>, _>("ocsp_urls").unwrap_or_default(),
+                    ca_issuers: row.get::<Option<Vec<String>>, _>("ca_issuer_urls").unwrap_or_default(),
+                },
+                pem: None, // Not stored; JSON and webhook outputs only
+                enrichment: None, // Not stored
                 program_name: row.get("program_name"),
                 platform: None, // TODO: Add platform column to database
+                severity: None, // Not stored
+                tags: Vec::new(), // Not stored
                 seen_unix: row.get("seen_unix"),
+                logged_at: row.get::<Option<i64>, _>("logged_at").map(|t| t as u64),
+                logged_at_ms: None, // Only used before emitting
+                processed_at: timestamp,
+                latency_ms: None, // Not stored
                 issuer: None, // TODO: Add issuer column to database
                 is_precert: false, // TODO: Add is_precert column to database
                 ct_log_url: None, // TODO: Add ct_log_url column to database
@@ -232,7 +468,9 @@ impl DatabaseBackend for PostgresBackend {
             INSERT INTO ct_log_state (log_url, last_index, last_updated)
             VALUES ($1, $2, NOW())
             ON CONFLICT (log_url)
-            DO UPDATE SET last_index = $2, last_updated = NOW()
+            DO UPDATE SET
+                last_index = $2,
+                last_updated = CASE WHEN ct_log_state.last_index = $2 THEN ct_log_state.last_updated ELSE NOW() END
             "#,
         )
         .bind(log_url)
@@ -279,6 +517,133 @@ impl DatabaseBackend for PostgresBackend {
             .collect())
     }
 
+    async fn get_log_state_records(&self) -> Result<Vec<LogStateRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT log_url, last_index, EXTRACT(EPOCH FROM last_updated)::BIGINT AS last_updated, tree_size
+            FROM ct_log_state
+            ORDER BY log_url
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch CT log state records")?;
+
+        Ok(rows.iter().map(log_state_record).collect())
+    }
+
+    async fn get_log_state_record(&self, log_url: &str) -> Result<Option<LogStateRecord>> {
+        let row = sqlx::query(
+            r#"
+            SELECT log_url, last_index, EXTRACT(EPOCH FROM last_updated)::BIGINT AS last_updated, tree_size
+            FROM ct_log_state
+            WHERE log_url = $1
+            "#,
+        )
+        .bind(log_url)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch CT log state record")?;
+
+        Ok(row.as_ref().map(log_state_record))
+    }
+
+    async fn update_log_tree_size(&self, log_url: &str, tree_size: u64) -> Result<()> {
+        sqlx::query("UPDATE ct_log_state SET tree_size = $2 WHERE log_url = $1")
+            .bind(log_url)
+            .bind(tree_size as i64)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update CT log tree size")?;
+
+        Ok(())
+    }
+
+    async fn count_matches(&self, since: Option<u64>) -> Result<u64> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM matches WHERE timestamp >= $1")
+            .bind(since.unwrap_or(0) as i64)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count matches")?;
+
+        Ok(row.get::<i64, _>("count") as u64)
+    }
+
+    async fn add_log_gap(&self, log_url: &str, start: u64, end: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ct_log_gaps (log_url, start_index, end_index)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(log_url)
+        .bind(start as i64)
+        .bind(end as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record CT log gap")?;
+
+        Ok(())
+    }
+
+    async fn remove_log_gap(&self, log_url: &str, start: u64, end: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM ct_log_gaps
+            WHERE log_url = $1 AND start_index = $2 AND end_index = $3
+            "#,
+        )
+        .bind(log_url)
+        .bind(start as i64)
+        .bind(end as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to remove CT log gap")?;
+
+        Ok(())
+    }
+
+    async fn get_log_gaps(&self, log_url: &str) -> Result<Vec<(u64, u64)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT start_index, end_index FROM ct_log_gaps
+            WHERE log_url = $1
+            ORDER BY start_index
+            "#,
+        )
+        .bind(log_url)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch CT log gaps")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let start: i64 = row.get("start_index");
+                let end: i64 = row.get("end_index");
+                (start as u64, end as u64)
+            })
+            .collect())
+    }
+
+    async fn remove_log_state(&self, log_url: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await.context("Failed to start transaction")?;
+        sqlx::query("DELETE FROM ct_log_gaps WHERE log_url = $1")
+            .bind(log_url)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to remove CT log gaps")?;
+        sqlx::query("DELETE FROM ct_log_state WHERE log_url = $1")
+            .bind(log_url)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to remove CT log state")?;
+        tx.commit().await.context("Failed to commit CT log state removal")?;
+
+        Ok(())
+    }
+
     async fn ping(&self) -> Result<()> {
         sqlx::query("SELECT 1")
             .execute(&self.pool)
@@ -0,0 +1,284 @@
+// src/database/sqlite_state.rs
+//! SQLite-backed CT log state: transactional like the database backend,
+//! but a single local file like the TOML one
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::path::Path;
+use tracing::{info, warn};
+
+use super::LogStateRecord;
+use crate::state::{LogProgress, StateBackend};
+
+/// State manager storing indices and gaps in a SQLite file
+#[derive(Clone)]
+pub struct SqliteStateManager {
+    pool: SqlitePool,
+}
+
+impl SqliteStateManager {
+    /// Open (creating if needed) the state database at `path`
+    pub async fn new(path: &Path) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        // One writer at a time is all SQLite allows anyway
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("Failed to open SQLite state {}", path.display()))?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ct_log_state (
+                log_url TEXT PRIMARY KEY,
+                last_index INTEGER NOT NULL,
+                last_updated INTEGER NOT NULL,
+                tree_size INTEGER
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .context("Failed to create ct_log_state table")?;
+
+        // Files created before tree sizes were tracked lack the column
+        let has_tree_size: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('ct_log_state') WHERE name = 'tree_size'")
+                .fetch_one(&pool)
+                .await
+                .context("Failed to inspect ct_log_state table")?;
+        if has_tree_size == 0 {
+            sqlx::query("ALTER TABLE ct_log_state ADD COLUMN tree_size INTEGER")
+                .execute(&pool)
+                .await
+                .context("Failed to add tree_size column to ct_log_state table")?;
+        }
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ct_log_gaps (
+                log_url TEXT NOT NULL,
+                start_index INTEGER NOT NULL,
+                end_index INTEGER NOT NULL,
+                PRIMARY KEY (log_url, start_index, end_index)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .context("Failed to create ct_log_gaps table")?;
+
+        info!("Using SQLite state {}", path.display());
+        Ok(Self { pool })
+    }
+
+    /// Every saved position with when it last advanced, sorted by URL
+    pub async fn records(&self) -> Result<Vec<LogStateRecord>> {
+        let rows = sqlx::query("SELECT log_url, last_index, last_updated, tree_size FROM ct_log_state ORDER BY log_url")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to read log states")?;
+
+        Ok(rows.iter().map(record).collect())
+    }
+
+    async fn record(&self, log_url: &str) -> Result<Option<LogStateRecord>> {
+        let row = sqlx::query("SELECT log_url, last_index, last_updated, tree_size FROM ct_log_state WHERE log_url = ?")
+            .bind(log_url)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(record))
+    }
+
+    async fn try_update_index(&self, log_url: &str, index: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ct_log_state (log_url, last_index, last_updated)
+            VALUES (?, ?, unixepoch())
+            ON CONFLICT (log_url) DO UPDATE SET
+                last_index = excluded.last_index,
+                last_updated = CASE WHEN last_index = excluded.last_index THEN last_updated ELSE excluded.last_updated END
+            "#,
+        )
+        .bind(log_url)
+        .bind(index as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn try_remove_log(&self, log_url: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM ct_log_gaps WHERE log_url = ?").bind(log_url).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM ct_log_state WHERE log_url = ?").bind(log_url).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn record(row: &SqliteRow) -> LogStateRecord {
+    LogStateRecord {
+        log_url: row.get("log_url"),
+        last_index: row.get::<i64, _>("last_index") as u64,
+        last_updated: row.get::<i64, _>("last_updated") as u64,
+        tree_size: row.get::<Option<i64>, _>("tree_size").map(|size| size as u64),
+    }
+}
+
+#[async_trait]
+impl StateBackend for SqliteStateManager {
+    async fn get_last_index(&self, log_url: &str) -> Option<u64> {
+        let row = sqlx::query("SELECT last_index FROM ct_log_state WHERE log_url = ?")
+            .bind(log_url)
+            .fetch_optional(&self.pool)
+            .await;
+        match row {
+            Ok(row) => row.map(|row| row.get::<i64, _>("last_index") as u64),
+            Err(e) => {
+                warn!("Failed to get log state for {}: {}", log_url, e);
+                None
+            }
+        }
+    }
+
+    async fn update_index(&self, log_url: &str, index: u64) {
+        if let Err(e) = self.try_update_index(log_url, index).await {
+            warn!("Failed to update log state for {}: {}", log_url, e);
+        }
+    }
+
+    /// Every write is already committed
+    async fn save(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_tracked_logs(&self) -> Vec<String> {
+        match self.records().await {
+            Ok(records) => records.into_iter().map(|record| record.log_url).collect(),
+            Err(e) => {
+                warn!("Failed to get tracked logs: {:#}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn count(&self) -> usize {
+        self.get_tracked_logs().await.len()
+    }
+
+    async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        let rows = sqlx::query(
+            "SELECT start_index, end_index FROM ct_log_gaps WHERE log_url = ? ORDER BY start_index, end_index",
+        )
+        .bind(log_url)
+        .fetch_all(&self.pool)
+        .await;
+        match rows {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|row| {
+                    (
+                        row.get::<i64, _>("start_index") as u64,
+                        row.get::<i64, _>("end_index") as u64,
+                    )
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Failed to get gaps for {}: {}", log_url, e);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        let result = sqlx::query("INSERT OR IGNORE INTO ct_log_gaps (log_url, start_index, end_index) VALUES (?, ?, ?)")
+            .bind(log_url)
+            .bind(start as i64)
+            .bind(end as i64)
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to record gap {}-{} for {}: {}", start, end, log_url, e);
+        }
+    }
+
+    async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        let result = sqlx::query("DELETE FROM ct_log_gaps WHERE log_url = ? AND start_index = ? AND end_index = ?")
+            .bind(log_url)
+            .bind(start as i64)
+            .bind(end as i64)
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to remove gap {}-{} for {}: {}", start, end, log_url, e);
+        }
+    }
+
+    /// Ignored until the log has a saved index
+    async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        let result = sqlx::query("UPDATE ct_log_state SET tree_size = ? WHERE log_url = ?")
+            .bind(tree_size as i64)
+            .bind(log_url)
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to update tree size for {}: {}", log_url, e);
+        }
+    }
+
+    async fn get_progress(&self, log_url: &str) -> LogProgress {
+        match self.record(log_url).await {
+            Ok(record) => record.map(LogProgress::from).unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to get log state for {}: {}", log_url, e);
+                LogProgress::default()
+            }
+        }
+    }
+
+    async fn remove_log(&self, log_url: &str) {
+        if let Err(e) = self.try_remove_log(log_url).await {
+            warn!("Failed to remove log state for {}: {}", log_url, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_sqlite_state_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.db");
+
+        let state = SqliteStateManager::new(&path).await.unwrap();
+        assert_eq!(state.get_last_index("https://ct.example.com/a/").await, None);
+        state.update_index("https://ct.example.com/a/", 10).await;
+        state.update_index("https://ct.example.com/a/", 42).await;
+        state.update_index("https://ct.example.com/b/", 7).await;
+        state.add_gap("https://ct.example.com/a/", 5, 9).await;
+        state.add_gap("https://ct.example.com/a/", 1, 2).await;
+        state.add_gap("https://ct.example.com/a/", 1, 2).await;
+        state.remove_gap("https://ct.example.com/a/", 5, 9).await;
+        state.update_tree_size("https://ct.example.com/a/", 100).await;
+        drop(state);
+
+        let state = SqliteStateManager::new(&path).await.unwrap();
+        assert_eq!(state.get_last_index("https://ct.example.com/a/").await, Some(42));
+        assert_eq!(state.get_gaps("https://ct.example.com/a/").await, vec![(1, 2)]);
+        assert_eq!(
+            state.get_tracked_logs().await,
+            vec!["https://ct.example.com/a/", "https://ct.example.com/b/"]
+        );
+        let progress = state.get_progress("https://ct.example.com/a/").await;
+        assert_eq!(progress.tree_size, Some(100));
+        assert!(progress.last_updated.is_some_and(|updated| updated > 0));
+    }
+}
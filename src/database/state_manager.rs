@@ -1,12 +1,46 @@
 // src/database/state_manager.rs
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use sqlx::{Connection, PgConnection};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
 use super::DatabaseBackend;
-use crate::state::StateBackend;
+use crate::state::{LogProgress, StateBackend};
+
+/// Advisory lock key guarding `ct_log_state` ("ctscout" in ASCII)
+const STATE_LOCK_KEY: i64 = 0x63_7473_636f_7574;
+
+/// Exclusive claim on the database state, released when dropped
+///
+/// A session-level advisory lock held on a dedicated connection, so it also
+/// goes away if the process dies and the connection closes.
+pub struct DbStateLease {
+    _conn: PgConnection,
+}
+
+impl DbStateLease {
+    /// Take the lock, failing at once if another process holds it
+    pub async fn acquire(database_url: &str) -> Result<Self> {
+        let mut conn = PgConnection::connect(database_url)
+            .await
+            .context("Failed to connect to PostgreSQL for the state lock")?;
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(STATE_LOCK_KEY)
+            .fetch_one(&mut conn)
+            .await
+            .context("Failed to take the state lock")?;
+        if !locked {
+            anyhow::bail!(
+                "Database state is in use by another ct-scout process; \
+                 stop it or use a different database"
+            );
+        }
+        debug!("Locked database state");
+        Ok(Self { _conn: conn })
+    }
+}
 
 /// Database-backed state manager for CT log tracking
 /// Drop-in replacement for TOML-based StateManager
@@ -74,6 +108,56 @@ impl DbStateManager {
     pub async fn count(&self) -> usize {
         self.get_tracked_logs().await.len()
     }
+
+    /// Get unprocessed index ranges for a CT log
+    pub async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        match self.db.get_log_gaps(log_url).await {
+            Ok(gaps) => gaps,
+            Err(e) => {
+                warn!("Failed to get gaps for {}: {}", log_url, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Record an unprocessed index range
+    pub async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        if let Err(e) = self.db.add_log_gap(log_url, start, end).await {
+            warn!("Failed to record gap {}-{} for {}: {}", start, end, log_url, e);
+        }
+    }
+
+    /// Remove a processed index range
+    pub async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        if let Err(e) = self.db.remove_log_gap(log_url, start, end).await {
+            warn!("Failed to remove gap {}-{} for {}: {}", start, end, log_url, e);
+        }
+    }
+
+    /// Record the tree size from the log's latest STH
+    pub async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        if let Err(e) = self.db.update_log_tree_size(log_url, tree_size).await {
+            warn!("Failed to update tree size for {}: {}", log_url, e);
+        }
+    }
+
+    /// When the index last advanced and the last observed tree size
+    pub async fn get_progress(&self, log_url: &str) -> LogProgress {
+        match self.db.get_log_state_record(log_url).await {
+            Ok(record) => record.map(LogProgress::from).unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to get log state for {}: {}", log_url, e);
+                LogProgress::default()
+            }
+        }
+    }
+
+    /// Forget a log's index, gaps and progress
+    pub async fn remove_log(&self, log_url: &str) {
+        if let Err(e) = self.db.remove_log_state(log_url).await {
+            warn!("Failed to remove log state for {}: {}", log_url, e);
+        }
+    }
 }
 
 impl Clone for DbStateManager {
@@ -107,4 +191,28 @@ impl StateBackend for DbStateManager {
     async fn count(&self) -> usize {
         self.count().await
     }
+
+    async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        self.get_gaps(log_url).await
+    }
+
+    async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        self.add_gap(log_url, start, end).await
+    }
+
+    async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        self.remove_gap(log_url, start, end).await
+    }
+
+    async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        self.update_tree_size(log_url, tree_size).await
+    }
+
+    async fn get_progress(&self, log_url: &str) -> LogProgress {
+        self.get_progress(log_url).await
+    }
+
+    async fn remove_log(&self, log_url: &str) {
+        self.remove_log(log_url).await
+    }
 }
@@ -1,4 +1,5 @@
 // src/dedupe.rs
+use crate::ct_log::LogIds;
 use crate::types::CertData;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -7,20 +8,31 @@ use tokio::sync::Mutex;
 #[derive(Clone, Default)]
 pub struct Dedupe {
     inner: Arc<Mutex<HashSet<String>>>,
+    log_ids: LogIds,
 }
 
 impl Dedupe {
     pub fn new() -> Self {
         Self {
             inner: Arc::new(Mutex::new(HashSet::new())),
+            log_ids: LogIds::default(),
         }
     }
 
+    /// Scope entry indices by log_id, so one log under two URLs counts once
+    pub fn with_log_ids(mut self, log_ids: LogIds) -> Self {
+        self.log_ids = log_ids;
+        self
+    }
+
     /// Returns true if this entry has not been seen before (and records it)
     pub async fn should_emit(&self, data: &CertData) -> bool {
-        // Use cert_index if available, else fingerprint, else no dedupe
+        // Use cert_index (within its log) if available, else fingerprint, else no dedupe
         let key = if let Some(idx) = data.cert_index {
-            format!("idx:{}", idx)
+            match data.ct_log_url {
+                Some(ref log_url) => format!("idx:{}:{}", self.log_ids.key(log_url), idx),
+                None => format!("idx:{}", idx),
+            }
         } else if let Some(leaf) = &data.leaf_cert {
             if let Some(fp) = &leaf.fingerprint {
                 format!("fp:{}", fp)
@@ -52,8 +64,10 @@ mod tests {
 
         let cert_data1 = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(12345),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -61,8 +75,10 @@ mod tests {
 
         let cert_data2 = CertData {
             all_domains: Some(vec!["different.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(12345), // Same index
             seen_unix: Some(1234567891.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -70,8 +86,10 @@ mod tests {
 
         let cert_data3 = CertData {
             all_domains: Some(vec!["another.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(67890), // Different index
             seen_unix: Some(1234567892.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -93,13 +111,27 @@ mod tests {
 
         let cert_data1 = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("abc123def456".to_string()),
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -107,13 +139,27 @@ mod tests {
 
         let cert_data2 = CertData {
             all_domains: Some(vec!["different.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567891.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("abc123def456".to_string()), // Same fingerprint
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -121,13 +167,27 @@ mod tests {
 
         let cert_data3 = CertData {
             all_domains: Some(vec!["another.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567892.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("xyz789ghi012".to_string()), // Different fingerprint
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -144,13 +204,27 @@ mod tests {
 
         let cert_data1 = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(100),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("fingerprint1".to_string()),
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -158,13 +232,27 @@ mod tests {
 
         let cert_data2 = CertData {
             all_domains: Some(vec!["different.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(100), // Same cert_index
             seen_unix: Some(1234567891.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("fingerprint2".to_string()), // Different fingerprint
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -181,8 +269,10 @@ mod tests {
 
         let cert_data1 = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -190,8 +280,10 @@ mod tests {
 
         let cert_data2 = CertData {
             all_domains: Some(vec!["different.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567891.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -208,13 +300,27 @@ mod tests {
 
         let cert_data1 = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: None, // No fingerprint
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -222,13 +328,27 @@ mod tests {
 
         let cert_data2 = CertData {
             all_domains: Some(vec!["different.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: Some(1234567891.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: None,
                 issuer: None,
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: None,
@@ -246,8 +366,10 @@ mod tests {
 
         let cert_data = CertData {
             all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(999),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -259,4 +381,30 @@ mod tests {
         // Should be deduped through cloned instance (shared state)
         assert!(!dedupe2.should_emit(&cert_data).await);
     }
+
+    #[tokio::test]
+    async fn test_dedupe_index_scoped_by_log_identity() {
+        let log_ids = LogIds::new();
+        log_ids.register("https://ct.example.com/2025/", "MjAyNQ==");
+        log_ids.register("https://ct-mirror.example.com/2025/", "MjAyNQ==");
+        let dedupe = Dedupe::new().with_log_ids(log_ids);
+
+        let entry = |log_url: &str, idx: u64| CertData {
+            all_domains: Some(vec!["example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(idx),
+            seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: Some(log_url.to_string()),
+        };
+
+        assert!(dedupe.should_emit(&entry("https://ct.example.com/2025/", 7)).await);
+        // Same log under another URL, or without the trailing slash
+        assert!(!dedupe.should_emit(&entry("https://ct-mirror.example.com/2025/", 7)).await);
+        assert!(!dedupe.should_emit(&entry("https://ct.example.com/2025", 7)).await);
+        // Same index in a different log is a different entry
+        assert!(dedupe.should_emit(&entry("https://other.example.com/log/", 7)).await);
+    }
 }
@@ -0,0 +1,103 @@
+// src/enrich/cache.rs
+//! Expiring cache of per-host enrichment lookups
+//!
+//! Renewals and overlapping SANs produce bursts of matches for the same
+//! hostnames; caching keeps those from re-resolving and re-probing each time.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Hostname-keyed cache whose entries expire after a fixed TTL
+pub struct TtlCache<V> {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached value for `key`, unless it has expired
+    pub async fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().await;
+        match entries.get(key) {
+            Some((stored, value)) if stored.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a value, evicting expired (then oldest) entries when full
+    pub async fn insert(&self, key: String, value: V) {
+        if self.ttl.is_zero() || self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().await;
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        }
+        if entries.len() >= self.max_entries
+            && !entries.contains_key(&key)
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Number of stored entries, including expired ones not yet evicted
+    pub async fn len(&self) -> usize {
+        self.entries.lock().await.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let cache = TtlCache::new(Duration::from_millis(50), 10);
+        cache.insert("a.example.com".to_string(), 1).await;
+        assert_eq!(cache.get("a.example.com").await, Some(1));
+        assert_eq!(cache.get("b.example.com").await, None);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(cache.get("a.example.com").await, None);
+        assert_eq!(cache.len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_oldest_when_full() {
+        let cache = TtlCache::new(Duration::from_secs(60), 2);
+        cache.insert("a".to_string(), 1).await;
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        cache.insert("b".to_string(), 2).await;
+        cache.insert("c".to_string(), 3).await;
+
+        assert_eq!(cache.len().await, 2);
+        assert_eq!(cache.get("a").await, None);
+        assert_eq!(cache.get("c").await, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_disables_cache() {
+        let cache = TtlCache::new(Duration::ZERO, 10);
+        cache.insert("a".to_string(), 1).await;
+        assert_eq!(cache.len().await, 0);
+    }
+}
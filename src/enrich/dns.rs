@@ -0,0 +1,214 @@
+// src/enrich/dns.rs
+//! Minimal DNS client for CNAME chain lookups
+//!
+//! The system resolver (getaddrinfo) only returns addresses, so the CNAME chain
+//! and the NXDOMAIN status needed for takeover checks are read straight from a
+//! recursive resolver's answer to an A query.
+
+use anyhow::{Context, Result};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const TYPE_A: u16 = 1;
+const TYPE_CNAME: u16 = 5;
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// Used when /etc/resolv.conf has no usable nameserver
+const FALLBACK_NAMESERVER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)), 53);
+
+/// CNAME chain of a name and whether resolution ended in NXDOMAIN
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CnameLookup {
+    /// CNAME targets in resolution order
+    pub chain: Vec<String>,
+    /// The name (or the end of its CNAME chain) does not exist
+    pub nxdomain: bool,
+}
+
+/// First nameserver from /etc/resolv.conf, or a public fallback
+pub fn system_nameserver() -> SocketAddr {
+    std::fs::read_to_string("/etc/resolv.conf")
+        .ok()
+        .and_then(|content| parse_resolv_conf(&content))
+        .unwrap_or(FALLBACK_NAMESERVER)
+}
+
+fn parse_resolv_conf(content: &str) -> Option<SocketAddr> {
+    content.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if fields.next()? != "nameserver" {
+            return None;
+        }
+        let ip: IpAddr = fields.next()?.parse().ok()?;
+        Some(SocketAddr::new(ip, 53))
+    })
+}
+
+/// Ask `nameserver` for the A records of `host`, returning its CNAME chain
+pub async fn lookup_cname(host: &str, nameserver: SocketAddr, timeout: Duration) -> Result<CnameLookup> {
+    let bind_addr: SocketAddr = if nameserver.is_ipv4() {
+        "0.0.0.0:0".parse()?
+    } else {
+        "[::]:0".parse()?
+    };
+    let socket = UdpSocket::bind(bind_addr).await.context("Failed to bind DNS socket")?;
+    socket.connect(nameserver).await.context("Failed to connect DNS socket")?;
+
+    let id: u16 = rand::random();
+    socket.send(&build_query(id, host)?).await.context("Failed to send DNS query")?;
+
+    let mut buf = [0u8; 4096];
+    loop {
+        let len = tokio::time::timeout(timeout, socket.recv(&mut buf))
+            .await
+            .context("DNS query timed out")?
+            .context("Failed to receive DNS response")?;
+
+        // Ignore stray datagrams that aren't answers to our query
+        if len >= 2 && u16::from_be_bytes([buf[0], buf[1]]) == id {
+            return parse_response(&buf[..len]);
+        }
+    }
+}
+
+/// Encode a recursive A query for `host`
+fn build_query(id: u16, host: &str) -> Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(32 + host.len());
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&0x0100u16.to_be_bytes()); // RD
+    query.extend_from_slice(&1u16.to_be_bytes()); // QDCOUNT
+    query.extend_from_slice(&[0; 6]); // ANCOUNT, NSCOUNT, ARCOUNT
+
+    for label in host.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            anyhow::bail!("Invalid DNS name: {}", host);
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+
+    query.extend_from_slice(&TYPE_A.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(query)
+}
+
+/// Pull the rcode and CNAME answers out of a response
+fn parse_response(msg: &[u8]) -> Result<CnameLookup> {
+    let header = msg.get(..12).context("DNS response too short")?;
+    let flags = u16::from_be_bytes([header[2], header[3]]);
+    let qdcount = u16::from_be_bytes([header[4], header[5]]);
+    let ancount = u16::from_be_bytes([header[6], header[7]]);
+
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        pos = read_name(msg, pos)?.1 + 4; // QTYPE, QCLASS
+    }
+
+    let mut chain = Vec::new();
+    for _ in 0..ancount {
+        let (_, next) = read_name(msg, pos)?;
+        let fixed = msg.get(next..next + 10).context("Truncated DNS answer")?;
+        let rtype = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let rdlength = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let rdata = next + 10;
+
+        if rtype == TYPE_CNAME {
+            chain.push(read_name(msg, rdata)?.0);
+        }
+        pos = rdata + rdlength;
+    }
+
+    Ok(CnameLookup {
+        chain,
+        nxdomain: flags & 0x000f == RCODE_NXDOMAIN,
+    })
+}
+
+/// Read a (possibly compressed) name at `pos`, returning it and the offset just past it
+fn read_name(msg: &[u8], mut pos: usize) -> Result<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+
+    // Bounded so a pointer loop in a malformed response can't spin forever
+    for _ in 0..128 {
+        let len = *msg.get(pos).context("Truncated DNS name")? as usize;
+        match len {
+            0 => {
+                return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+            }
+            l if l & 0xc0 == 0xc0 => {
+                let low = *msg.get(pos + 1).context("Truncated DNS name pointer")? as usize;
+                end.get_or_insert(pos + 2);
+                pos = ((l & 0x3f) << 8) | low;
+            }
+            l => {
+                let label = msg.get(pos + 1..pos + 1 + l).context("Truncated DNS label")?;
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                pos += 1 + l;
+            }
+        }
+    }
+
+    anyhow::bail!("DNS name compression loop")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Response to `www.example.com A`: CNAME (with compressed owner) to example.github.io, NXDOMAIN
+    fn nxdomain_response() -> Vec<u8> {
+        let mut msg = build_query(0x1234, "www.example.com").unwrap();
+        msg[2..4].copy_from_slice(&0x8183u16.to_be_bytes()); // QR, RD, RA, NXDOMAIN
+        msg[6..8].copy_from_slice(&1u16.to_be_bytes()); // ANCOUNT
+
+        msg.extend_from_slice(&[0xc0, 12]); // owner: pointer to question name
+        msg.extend_from_slice(&TYPE_CNAME.to_be_bytes());
+        msg.extend_from_slice(&CLASS_IN.to_be_bytes());
+        msg.extend_from_slice(&300u32.to_be_bytes());
+
+        let rdata = b"\x07example\x06github\x02io\x00";
+        msg.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        msg.extend_from_slice(rdata);
+        msg
+    }
+
+    #[test]
+    fn test_build_query() {
+        let query = build_query(0xabcd, "a.example.com.").unwrap();
+        assert_eq!(&query[..2], &[0xab, 0xcd]);
+        assert_eq!(&query[12..], b"\x01a\x07example\x03com\x00\x00\x01\x00\x01");
+
+        assert!(build_query(1, "bad..name").is_err());
+    }
+
+    #[test]
+    fn test_parse_cname_chain() {
+        let lookup = parse_response(&nxdomain_response()).unwrap();
+        assert_eq!(lookup.chain, vec!["example.github.io"]);
+        assert!(lookup.nxdomain);
+    }
+
+    #[test]
+    fn test_read_compressed_name() {
+        let msg = nxdomain_response();
+        // The answer owner is a pointer back to the question name
+        let answer = 12 + "www.example.com".len() + 2 + 4;
+        let (name, next) = read_name(&msg, answer).unwrap();
+        assert_eq!(name, "www.example.com");
+        assert_eq!(next, answer + 2);
+
+        // Self-referencing pointer
+        assert!(read_name(&[0xc0, 0x00], 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let conf = "# generated\nsearch corp\nnameserver 10.0.0.2\nnameserver 10.0.0.3\n";
+        assert_eq!(parse_resolv_conf(conf), Some("10.0.0.2:53".parse().unwrap()));
+        assert_eq!(parse_resolv_conf("search corp\n"), None);
+    }
+}
@@ -0,0 +1,342 @@
+// src/enrich/intel.rs
+//! Internet-scan lookups (Shodan, Censys) for matched hosts
+//!
+//! Both services have small query allowances, so every lookup goes through a
+//! token bucket and results are cached per address by the enricher.
+
+use crate::ct_log::RateLimiter;
+use crate::types::{HostIntel, ServiceBanner};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Longest banner kept per service
+const MAX_BANNER_LEN: usize = 256;
+/// Back-off when the API rate-limits us without saying for how long
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Scan database queried for matched addresses
+#[derive(Clone)]
+pub enum IntelProvider {
+    Shodan { api_key: String },
+    Censys { api_id: String, api_secret: String },
+}
+
+impl IntelProvider {
+    /// Build a provider from its config name ("shodan" or "censys") and credentials
+    pub fn from_config(name: &str, api_key: &str, api_secret: Option<&str>) -> Result<Self> {
+        match name {
+            "shodan" => Ok(Self::Shodan {
+                api_key: api_key.to_string(),
+            }),
+            "censys" => Ok(Self::Censys {
+                api_id: api_key.to_string(),
+                api_secret: api_secret
+                    .context("Censys requires api_secret in [enrichment.intel]")?
+                    .to_string(),
+            }),
+            other => anyhow::bail!(
+                "Invalid intel provider '{}'. Must be 'shodan' or 'censys'",
+                other
+            ),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Shodan { .. } => "shodan",
+            Self::Censys { .. } => "censys",
+        }
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        match self {
+            Self::Shodan { .. } => "https://api.shodan.io",
+            Self::Censys { .. } => "https://search.censys.io/api",
+        }
+    }
+}
+
+/// Rate-limited client for the configured scan database
+pub struct IntelClient {
+    provider: IntelProvider,
+    client: reqwest::Client,
+    base_url: String,
+    limiter: RateLimiter,
+}
+
+impl IntelClient {
+    pub fn new(provider: IntelProvider, requests_per_sec: f64, timeout: Duration) -> Result<Self> {
+        if requests_per_sec <= 0.0 {
+            anyhow::bail!("intel requests_per_sec must be greater than 0");
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(crate::ct_log::http::DEFAULT_USER_AGENT)
+            .build()
+            .context("Failed to build intel HTTP client")?;
+
+        Ok(Self {
+            base_url: provider.default_base_url().to_string(),
+            provider,
+            client,
+            limiter: RateLimiter::new(requests_per_sec, 1),
+        })
+    }
+
+    /// Point the client at a different API root (used by tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Open ports and banners for an address; `None` if the service has never scanned it
+    pub async fn lookup(&self, ip: IpAddr) -> Result<Option<HostIntel>> {
+        self.limiter.acquire().await;
+
+        let request = match &self.provider {
+            IntelProvider::Shodan { api_key } => self
+                .client
+                .get(format!("{}/shodan/host/{}", self.base_url, ip))
+                .query(&[("key", api_key)]),
+            IntelProvider::Censys { api_id, api_secret } => self
+                .client
+                .get(format!("{}/v2/hosts/{}", self.base_url, ip))
+                .basic_auth(api_id, Some(api_secret)),
+        };
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("{} lookup of {} failed", self.provider.name(), ip))?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => return Ok(None),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_RETRY_AFTER);
+                self.limiter.pause_for(retry_after).await;
+                anyhow::bail!("{} rate limit hit", self.provider.name());
+            }
+            status if !status.is_success() => {
+                anyhow::bail!("{} returned HTTP {} for {}", self.provider.name(), status, ip);
+            }
+            _ => {}
+        }
+
+        let services = match self.provider {
+            IntelProvider::Shodan { .. } => {
+                let host: ShodanHost = response.json().await.context("Invalid Shodan response")?;
+                host.data.into_iter().map(ShodanService::into_banner).collect()
+            }
+            IntelProvider::Censys { .. } => {
+                let host: CensysResponse = response.json().await.context("Invalid Censys response")?;
+                host.result.services.into_iter().map(CensysService::into_banner).collect()
+            }
+        };
+
+        Ok(Some(Self::summarize(self.provider.name(), ip, services)))
+    }
+
+    fn summarize(source: &str, ip: IpAddr, mut services: Vec<ServiceBanner>) -> HostIntel {
+        services.sort_by_key(|service| service.port);
+        let mut ports: Vec<u16> = services.iter().map(|service| service.port).collect();
+        ports.dedup();
+
+        HostIntel {
+            source: source.to_string(),
+            ip: ip.to_string(),
+            ports,
+            services,
+        }
+    }
+}
+
+/// Trimmed, length-capped banner text
+fn clip_banner(banner: Option<String>) -> Option<String> {
+    let banner: String = banner?.trim().chars().take(MAX_BANNER_LEN).collect();
+    (!banner.is_empty()).then_some(banner)
+}
+
+#[derive(Deserialize)]
+struct ShodanHost {
+    #[serde(default)]
+    data: Vec<ShodanService>,
+}
+
+#[derive(Deserialize)]
+struct ShodanService {
+    port: u16,
+    transport: Option<String>,
+    product: Option<String>,
+    version: Option<String>,
+    data: Option<String>,
+}
+
+impl ShodanService {
+    fn into_banner(self) -> ServiceBanner {
+        let product = match (self.product, self.version) {
+            (Some(product), Some(version)) => Some(format!("{} {}", product, version)),
+            (product, _) => product,
+        };
+        ServiceBanner {
+            port: self.port,
+            transport: self.transport,
+            product,
+            banner: clip_banner(self.data),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CensysResponse {
+    result: CensysHost,
+}
+
+#[derive(Deserialize)]
+struct CensysHost {
+    #[serde(default)]
+    services: Vec<CensysService>,
+}
+
+#[derive(Deserialize)]
+struct CensysService {
+    port: u16,
+    transport_protocol: Option<String>,
+    service_name: Option<String>,
+    #[serde(default)]
+    software: Vec<CensysSoftware>,
+    banner: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CensysSoftware {
+    product: Option<String>,
+}
+
+impl CensysService {
+    fn into_banner(self) -> ServiceBanner {
+        let product = self
+            .software
+            .into_iter()
+            .find_map(|software| software.product)
+            .or(self.service_name);
+        ServiceBanner {
+            port: self.port,
+            transport: self.transport_protocol.map(|t| t.to_ascii_lowercase()),
+            product,
+            banner: clip_banner(self.banner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{basic_auth, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(provider: IntelProvider, server: &MockServer) -> IntelClient {
+        IntelClient::new(provider, 100.0, Duration::from_secs(5))
+            .unwrap()
+            .with_base_url(server.uri())
+    }
+
+    #[tokio::test]
+    async fn test_shodan_lookup() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/192.0.2.10"))
+            .and(query_param("key", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ip_str": "192.0.2.10",
+                "ports": [443, 22],
+                "data": [
+                    {"port": 443, "transport": "tcp", "product": "nginx", "version": "1.25.3",
+                     "data": "HTTP/1.1 200 OK\r\nServer: nginx\r\n\r\n"},
+                    {"port": 22, "transport": "tcp", "product": "OpenSSH",
+                     "data": "SSH-2.0-OpenSSH_9.6\r\n"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let intel = client(IntelProvider::Shodan { api_key: "secret".to_string() }, &mock_server)
+            .lookup("192.0.2.10".parse().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(intel.source, "shodan");
+        assert_eq!(intel.ports, vec![22, 443]);
+        assert_eq!(intel.services[0].banner.as_deref(), Some("SSH-2.0-OpenSSH_9.6"));
+        assert_eq!(intel.services[1].product.as_deref(), Some("nginx 1.25.3"));
+    }
+
+    #[tokio::test]
+    async fn test_censys_lookup() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/hosts/192.0.2.20"))
+            .and(basic_auth("id", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": 200,
+                "status": "OK",
+                "result": {
+                    "ip": "192.0.2.20",
+                    "services": [
+                        {"port": 8443, "service_name": "HTTP", "transport_protocol": "TCP",
+                         "software": [{"vendor": "Apache"}, {"product": "Tomcat"}]},
+                        {"port": 3306, "service_name": "MYSQL", "transport_protocol": "TCP"}
+                    ]
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = IntelProvider::from_config("censys", "id", Some("secret")).unwrap();
+        let intel = client(provider, &mock_server)
+            .lookup("192.0.2.20".parse().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(intel.ports, vec![3306, 8443]);
+        assert_eq!(intel.services[0].product.as_deref(), Some("MYSQL"));
+        assert_eq!(intel.services[1].product.as_deref(), Some("Tomcat"));
+        assert_eq!(intel.services[1].transport.as_deref(), Some("tcp"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_host_and_rate_limit() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/192.0.2.30"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/192.0.2.31"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&mock_server)
+            .await;
+
+        let client = client(IntelProvider::Shodan { api_key: "k".to_string() }, &mock_server);
+        assert!(client.lookup("192.0.2.30".parse().unwrap()).await.unwrap().is_none());
+        assert!(client.lookup("192.0.2.31".parse().unwrap()).await.is_err());
+    }
+
+    #[test]
+    fn test_provider_from_config() {
+        assert!(IntelProvider::from_config("shodan", "k", None).is_ok());
+        assert!(IntelProvider::from_config("censys", "id", None).is_err());
+        assert!(IntelProvider::from_config("zoomeye", "k", None).is_err());
+    }
+}
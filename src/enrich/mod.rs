@@ -0,0 +1,680 @@
+// src/enrich/mod.rs
+//! Post-match enrichment of matched hosts (DNS resolution, scope correlation,
+//! HTTP liveness probing, subdomain takeover indicators, Shodan/Censys lookups,
+//! RDAP registration data)
+//!
+//! Enrichment runs after a certificate has matched the watchlist, so it only
+//! costs network round trips for the small fraction of certificates that matter.
+//! The coordinator runs it on a pool of workers, so slow lookups don't hold up
+//! matching.
+
+mod cache;
+mod dns;
+mod intel;
+mod rdap;
+mod takeover;
+
+pub use intel::{IntelClient, IntelProvider};
+pub use rdap::registrable_domain;
+
+use crate::types::{
+    Enrichment, HostIntel, HttpProbe, MatchResult, Registration, ScopeHit, TakeoverRisk,
+};
+use cache::TtlCache;
+use rdap::RdapClient;
+use crate::watchlist::Watchlist;
+use anyhow::{Context, Result};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Which enrichment steps to run
+#[derive(Debug, Clone)]
+pub struct EnrichConfig {
+    /// Resolve matched domains and check the addresses against watchlist IPs/CIDRs
+    pub dns: bool,
+    /// Request the matched host over HTTPS (falling back to HTTP) and record the response
+    pub http_probe: bool,
+    /// Look up the CNAME chain and flag dangling records to takeover-prone services
+    pub takeover_check: bool,
+    /// Resolver queried for CNAME chains (default: first nameserver in /etc/resolv.conf)
+    pub nameserver: Option<SocketAddr>,
+    /// Look up registrar and registration date of the matched domain over RDAP
+    pub rdap: bool,
+    /// RDAP server or bootstrap redirector queried as `<rdap_url>/domain/<name>`
+    pub rdap_url: String,
+    /// Registrations younger than this many days are called out in the notes
+    pub new_domain_days: i64,
+    /// Upper bound for each network lookup or HTTP request
+    pub timeout: Duration,
+    /// How long a host's lookup results are reused (zero disables caching)
+    pub cache_ttl: Duration,
+    /// Most hosts kept in the cache
+    pub cache_size: usize,
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            dns: false,
+            http_probe: false,
+            takeover_check: false,
+            nameserver: None,
+            rdap: false,
+            rdap_url: "https://rdap.org".to_string(),
+            new_domain_days: 30,
+            timeout: Duration::from_secs(3),
+            cache_ttl: Duration::from_secs(600),
+            cache_size: 10_000,
+        }
+    }
+}
+
+/// Most of a page read while looking for its <title>
+const MAX_PROBE_BODY: usize = 64 * 1024;
+/// Longest title kept in the match
+const MAX_TITLE_LEN: usize = 200;
+
+/// Network lookups for one host, shared by every match on that host until
+/// the cache entry expires
+#[derive(Debug, Clone, Default)]
+struct HostInfo {
+    ips: Vec<IpAddr>,
+    http: Option<HttpProbe>,
+    cnames: Vec<String>,
+    takeover: Option<TakeoverRisk>,
+}
+
+/// Annotates match results with information gathered about the matched host
+pub struct Enricher {
+    config: EnrichConfig,
+    http_client: reqwest::Client,
+    nameserver: SocketAddr,
+    cache: TtlCache<HostInfo>,
+    intel: Option<IntelClient>,
+    /// Scan results keyed by address; `None` entries remember unscanned hosts
+    intel_cache: TtlCache<Option<HostIntel>>,
+    rdap: Option<RdapClient>,
+    /// Registrations keyed by registrable domain
+    rdap_cache: TtlCache<Option<Registration>>,
+}
+
+impl Enricher {
+    pub fn new(config: EnrichConfig) -> Result<Self> {
+        // Freshly issued certificates are often not deployed yet (or only on
+        // internal hosts), so don't let TLS errors hide an otherwise live server
+        let http_client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.timeout)
+            .redirect(reqwest::redirect::Policy::limited(3))
+            .danger_accept_invalid_certs(true)
+            .user_agent(crate::ct_log::http::DEFAULT_USER_AGENT)
+            .build()
+            .context("Failed to build HTTP probe client")?;
+
+        let nameserver = config.nameserver.unwrap_or_else(dns::system_nameserver);
+        let cache = TtlCache::new(config.cache_ttl, config.cache_size);
+        let intel_cache = TtlCache::new(config.cache_ttl, config.cache_size);
+        let rdap = if config.rdap {
+            Some(RdapClient::new(&config.rdap_url, config.timeout)?)
+        } else {
+            None
+        };
+        let rdap_cache = TtlCache::new(config.cache_ttl, config.cache_size);
+
+        Ok(Self {
+            config,
+            http_client,
+            nameserver,
+            cache,
+            intel: None,
+            intel_cache,
+            rdap,
+            rdap_cache,
+        })
+    }
+
+    /// Look up matched addresses in Shodan or Censys
+    pub fn with_intel(mut self, client: IntelClient) -> Self {
+        self.intel = Some(client);
+        self
+    }
+
+    /// Whether any enrichment step is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.config.dns
+            || self.config.http_probe
+            || self.config.takeover_check
+            || self.config.rdap
+            || self.intel.is_some()
+    }
+
+    /// Enrich a match in place; failures only leave the corresponding fields empty
+    pub async fn enrich(&self, result: &mut MatchResult, watchlist: &Mutex<Watchlist>) {
+        let info = match Self::resolvable_host(&result.matched_domain) {
+            Some(host) => match self.cache.get(host).await {
+                Some(info) => info,
+                None => {
+                    let info = self.lookup(host).await;
+                    self.cache.insert(host.to_string(), info.clone()).await;
+                    debug!("Enriched {} ({} hosts cached)", host, self.cache.len().await);
+                    info
+                }
+            },
+            None => HostInfo::default(),
+        };
+
+        // IP SAN matches are looked up directly, domains by their first address
+        let intel_target = result
+            .matched_domain
+            .parse::<IpAddr>()
+            .ok()
+            .or_else(|| info.ips.first().copied());
+        let intel = match intel_target {
+            Some(ip) => self.host_intel(ip).await,
+            None => None,
+        };
+        let registration = self.registration(&result.matched_domain).await;
+
+        let mut enrichment = Enrichment {
+            resolved_ips: info.ips.iter().map(IpAddr::to_string).collect(),
+            http: info.http,
+            cnames: info.cnames,
+            intel,
+            ..Default::default()
+        };
+
+        if let Some(risk) = info.takeover {
+            // First note, so it's what shows up at the top of the match
+            enrichment.notes.push(format!(
+                "possible subdomain takeover: CNAME to {} ({}), {}",
+                risk.cname, risk.service, risk.reason
+            ));
+            enrichment.takeover = Some(risk);
+        }
+
+        if let Some(ref registration) = registration
+            && let Some(age) = Self::registration_age_days(registration)
+            && age < self.config.new_domain_days
+        {
+            enrichment.notes.push(format!(
+                "{} registered {} day(s) ago{}",
+                registration.domain,
+                age,
+                registration
+                    .registrar
+                    .as_ref()
+                    .map(|registrar| format!(" via {}", registrar))
+                    .unwrap_or_default()
+            ));
+        }
+        enrichment.registration = registration;
+
+        // Correlated on every match rather than cached, since the watchlist can reload
+        enrichment.in_scope = Self::correlate(&info.ips, &*watchlist.lock().await);
+        for hit in &enrichment.in_scope {
+            enrichment
+                .notes
+                .push(format!("resolves into in-scope range {}", hit.range));
+        }
+
+        if enrichment != Enrichment::default() {
+            result.enrichment = Some(enrichment);
+        }
+    }
+
+    /// Run the enabled network lookups for a host
+    async fn lookup(&self, host: &str) -> HostInfo {
+        let mut info = HostInfo::default();
+
+        // Intel lookups are by address, so they need the name resolved too
+        if self.config.dns || self.intel.is_some() {
+            info.ips = self.resolve(host).await;
+        }
+
+        // Skip the probe when DNS already showed the name doesn't resolve
+        let mut body = None;
+        if self.config.http_probe
+            && !(self.config.dns && info.ips.is_empty())
+            && let Some((probe, page)) = self.probe(host).await
+        {
+            info.http = Some(probe);
+            body = Some(page);
+        }
+
+        if self.config.takeover_check {
+            match dns::lookup_cname(host, self.nameserver, self.config.timeout).await {
+                Ok(lookup) => {
+                    // Services that answer for unclaimed names need their error page checked
+                    if body.is_none() && takeover::needs_body(&lookup) {
+                        body = self.probe(host).await.map(|(_, page)| page);
+                    }
+                    info.takeover = takeover::assess(&lookup, body.as_deref());
+                    if let Some(ref risk) = info.takeover {
+                        warn!(
+                            "Possible subdomain takeover: {} -> {} ({}): {}",
+                            host, risk.cname, risk.service, risk.reason
+                        );
+                    }
+                    info.cnames = lookup.chain;
+                }
+                Err(e) => debug!("CNAME lookup for {} failed: {:#}", host, e),
+            }
+        }
+
+        info
+    }
+
+    /// Scan database results for an address, cached like host lookups
+    async fn host_intel(&self, ip: IpAddr) -> Option<HostIntel> {
+        let client = self.intel.as_ref()?;
+        let key = ip.to_string();
+        if let Some(cached) = self.intel_cache.get(&key).await {
+            return cached;
+        }
+
+        match client.lookup(ip).await {
+            Ok(intel) => {
+                self.intel_cache.insert(key, intel.clone()).await;
+                intel
+            }
+            Err(e) => {
+                // Not cached, so the next match on this address retries
+                debug!("Intel lookup for {} failed: {:#}", ip, e);
+                None
+            }
+        }
+    }
+
+    /// RDAP registration of the match's registrable domain, cached per domain
+    async fn registration(&self, matched: &str) -> Option<Registration> {
+        let client = self.rdap.as_ref()?;
+        if matched.parse::<IpAddr>().is_ok() {
+            return None;
+        }
+        let domain = rdap::registrable_domain(matched)?;
+        if let Some(cached) = self.rdap_cache.get(&domain).await {
+            return cached;
+        }
+
+        match client.lookup(&domain).await {
+            Ok(registration) => {
+                self.rdap_cache.insert(domain, registration.clone()).await;
+                registration
+            }
+            Err(e) => {
+                debug!("RDAP lookup for {} failed: {:#}", domain, e);
+                None
+            }
+        }
+    }
+
+    /// Whole days since the domain was registered
+    fn registration_age_days(registration: &Registration) -> Option<i64> {
+        let created = chrono::DateTime::parse_from_rfc3339(registration.created.as_deref()?).ok()?;
+        Some((chrono::Utc::now() - created.with_timezone(&chrono::Utc)).num_days())
+    }
+
+    /// Hostname worth resolving (wildcards and IP SAN matches are skipped)
+    fn resolvable_host(matched: &str) -> Option<&str> {
+        if matched.starts_with("*.") || matched.parse::<IpAddr>().is_ok() {
+            return None;
+        }
+        Some(matched)
+    }
+
+    /// Resolve A/AAAA records with the system resolver
+    async fn resolve(&self, host: &str) -> Vec<IpAddr> {
+        let lookup = tokio::net::lookup_host((host, 0));
+        let addrs = match tokio::time::timeout(self.config.timeout, lookup).await {
+            Ok(Ok(addrs)) => addrs,
+            Ok(Err(e)) => {
+                debug!("DNS lookup for {} failed: {}", host, e);
+                return Vec::new();
+            }
+            Err(_) => {
+                debug!("DNS lookup for {} timed out", host);
+                return Vec::new();
+            }
+        };
+
+        let mut ips: Vec<IpAddr> = addrs.map(|addr| addr.ip()).collect();
+        ips.sort();
+        ips.dedup();
+        ips
+    }
+
+    /// Probe https://host/, then http://host/ if HTTPS is not reachable
+    async fn probe(&self, host: &str) -> Option<(HttpProbe, String)> {
+        for url in [format!("https://{}/", host), format!("http://{}/", host)] {
+            match self.probe_url(&url).await {
+                Ok(probe) => return Some(probe),
+                Err(e) => debug!("HTTP probe of {} failed: {:#}", url, e),
+            }
+        }
+        None
+    }
+
+    /// GET a URL, capturing status, Server header and page title, plus the
+    /// start of the body
+    async fn probe_url(&self, url: &str) -> Result<(HttpProbe, String)> {
+        let mut response = self.http_client.get(url).send().await?;
+
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let server = response
+            .headers()
+            .get(reqwest::header::SERVER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let mut body = Vec::new();
+        while body.len() < MAX_PROBE_BODY {
+            match tokio::time::timeout(self.config.timeout, response.chunk()).await {
+                Ok(Ok(Some(chunk))) => body.extend_from_slice(&chunk),
+                _ => break,
+            }
+        }
+
+        let body = String::from_utf8_lossy(&body).into_owned();
+        let probe = HttpProbe {
+            url: final_url,
+            status,
+            server,
+            title: Self::extract_title(&body),
+        };
+        Ok((probe, body))
+    }
+
+    /// Text of the first <title> element, whitespace-collapsed
+    fn extract_title(html: &str) -> Option<String> {
+        // ASCII lowercasing keeps byte offsets valid for slicing `html`
+        let lower = html.to_ascii_lowercase();
+        let open = lower.find("<title")?;
+        let start = open + lower[open..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+
+        let title: String = html[start..end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_TITLE_LEN)
+            .collect();
+        (!title.is_empty()).then_some(title)
+    }
+
+    /// Resolved addresses that fall inside a watched IP or range
+    fn correlate(ips: &[IpAddr], watchlist: &Watchlist) -> Vec<ScopeHit> {
+        ips.iter()
+            .filter(|ip| watchlist.matches_ip(ip))
+            .filter_map(|ip| {
+                Some(ScopeHit {
+                    ip: ip.to_string(),
+                    range: watchlist.range_for_ip(ip)?,
+                    program: watchlist.program_for_ip(ip).map(|p| p.name.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProgramConfig, WatchlistConfig};
+    use crate::types::CertData;
+
+    fn watchlist() -> Mutex<Watchlist> {
+        let config = WatchlistConfig {
+            domains: vec![],
+            hosts: vec![],
+            ips: vec![],
+            cidrs: vec![],
+            ..Default::default()
+        };
+        let programs = vec![ProgramConfig {
+            name: "Loopback".to_string(),
+            domains: vec![],
+            hosts: vec![],
+            cidrs: vec!["127.0.0.0/8".to_string()],
+            ips: vec![],
+            ..Default::default()
+        }];
+        Mutex::new(Watchlist::from_config(&config, &programs).unwrap())
+    }
+
+    fn result_for(domain: &str) -> MatchResult {
+        let data = CertData {
+            all_domains: Some(vec![domain.to_string()]),
+            ip_addresses: None,
+            cert_index: Some(1),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        MatchResult::from_cert_data(domain.to_string(), &data, None, None)
+    }
+
+    #[tokio::test]
+    async fn test_resolved_ip_in_program_range() {
+        let enricher = Enricher::new(EnrichConfig {
+            dns: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut result = result_for("localhost");
+        enricher.enrich(&mut result, &watchlist()).await;
+
+        let enrichment = result.enrichment.expect("localhost should resolve");
+        assert!(enrichment.resolved_ips.contains(&"127.0.0.1".to_string()));
+
+        let hit = enrichment.in_scope.iter().find(|hit| hit.ip == "127.0.0.1").unwrap();
+        assert_eq!(hit.range, "127.0.0.0/8");
+        assert_eq!(hit.program.as_deref(), Some("Loopback"));
+        assert!(enrichment.notes.contains(&"resolves into in-scope range 127.0.0.0/8".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_skips_wildcards_and_ips() {
+        let enricher = Enricher::new(EnrichConfig {
+            dns: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for matched in ["*.localhost", "127.0.0.1"] {
+            let mut result = result_for(matched);
+            enricher.enrich(&mut result, &watchlist()).await;
+            assert!(result.enrichment.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_captures_status_server_and_title() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(
+                ResponseTemplate::new(403)
+                    .insert_header("server", "nginx/1.25.3")
+                    .set_body_string("<html><head><TITLE>\n  Staging   Login\n</TITLE></head></html>"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let enricher = Enricher::new(EnrichConfig {
+            http_probe: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (probe, body) = enricher
+            .probe_url(&format!("{}/", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(probe.status, 403);
+        assert_eq!(probe.server.as_deref(), Some("nginx/1.25.3"));
+        assert_eq!(probe.title.as_deref(), Some("Staging Login"));
+        assert!(body.starts_with("<html>"));
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(Enricher::extract_title("<title lang=\"en\">Hi</title>").as_deref(), Some("Hi"));
+        assert_eq!(Enricher::extract_title("<title> </title>"), None);
+        assert_eq!(Enricher::extract_title("<h1>No title</h1>"), None);
+    }
+
+    /// Fake resolver answering `queries` queries with CNAME -> acme.github.io, NXDOMAIN
+    async fn dangling_github_resolver(queries: usize) -> SocketAddr {
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let nameserver = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            for _ in 0..queries {
+                let (len, peer) = server.recv_from(&mut buf).await.unwrap();
+                let mut response = buf[..len].to_vec();
+                response[2..4].copy_from_slice(&[0x81, 0x83]);
+                response[6..8].copy_from_slice(&[0, 1]);
+                response.extend_from_slice(&[0xc0, 12, 0, 5, 0, 1, 0, 0, 1, 44, 0, 16]);
+                response.extend_from_slice(b"\x04acme\x06github\x02io\x00");
+                server.send_to(&response, peer).await.unwrap();
+            }
+        });
+        nameserver
+    }
+
+    #[tokio::test]
+    async fn test_dangling_cname_is_flagged() {
+        let nameserver = dangling_github_resolver(1).await;
+
+        let enricher = Enricher::new(EnrichConfig {
+            takeover_check: true,
+            nameserver: Some(nameserver),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut result = result_for("docs.example.com");
+        enricher.enrich(&mut result, &watchlist()).await;
+
+        let enrichment = result.enrichment.unwrap();
+        assert_eq!(enrichment.cnames, vec!["acme.github.io"]);
+        let risk = enrichment.takeover.unwrap();
+        assert_eq!(risk.service, "GitHub Pages");
+        assert_eq!(risk.cname, "acme.github.io");
+        assert!(enrichment.notes[0].starts_with("possible subdomain takeover"));
+    }
+
+    #[tokio::test]
+    async fn test_repeat_hosts_served_from_cache() {
+        // The resolver only answers once; the second match must not query it
+        let nameserver = dangling_github_resolver(1).await;
+        let enricher = Enricher::new(EnrichConfig {
+            takeover_check: true,
+            nameserver: Some(nameserver),
+            timeout: Duration::from_millis(500),
+            ..Default::default()
+        })
+        .unwrap();
+
+        for _ in 0..2 {
+            let mut result = result_for("docs.example.com");
+            enricher.enrich(&mut result, &watchlist()).await;
+            assert!(result.enrichment.unwrap().takeover.is_some());
+        }
+        assert_eq!(enricher.cache.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ip_match_gets_intel_once() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/192.0.2.7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"port": 8080, "transport": "tcp", "product": "Jetty"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let intel = IntelClient::new(
+            IntelProvider::Shodan { api_key: "k".to_string() },
+            100.0,
+            Duration::from_secs(5),
+        )
+        .unwrap()
+        .with_base_url(mock_server.uri());
+        let enricher = Enricher::new(EnrichConfig::default()).unwrap().with_intel(intel);
+        assert!(enricher.is_enabled());
+
+        for _ in 0..2 {
+            let mut result = result_for("192.0.2.7");
+            enricher.enrich(&mut result, &watchlist()).await;
+            let intel = result.enrichment.unwrap().intel.unwrap();
+            assert_eq!(intel.ports, vec![8080]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fresh_registration_is_noted() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let created = (chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/domain/example-login.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "events": [{"eventAction": "registration", "eventDate": created}],
+                "entities": [{
+                    "roles": ["registrar"],
+                    "vcardArray": ["vcard", [["fn", {}, "text", "Example Registrar"]]]
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let enricher = Enricher::new(EnrichConfig {
+            rdap: true,
+            rdap_url: mock_server.uri(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Both names share a registrable domain, so RDAP is queried once
+        for matched in ["secure.example-login.com", "*.example-login.com"] {
+            let mut result = result_for(matched);
+            enricher.enrich(&mut result, &watchlist()).await;
+
+            let enrichment = result.enrichment.unwrap();
+            assert_eq!(enrichment.registration.unwrap().domain, "example-login.com");
+            assert_eq!(
+                enrichment.notes,
+                vec!["example-login.com registered 2 day(s) ago via Example Registrar"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_leaves_match_untouched() {
+        let enricher = Enricher::new(EnrichConfig::default()).unwrap();
+        assert!(!enricher.is_enabled());
+
+        let mut result = result_for("localhost");
+        enricher.enrich(&mut result, &watchlist()).await;
+        assert!(result.enrichment.is_none());
+    }
+}
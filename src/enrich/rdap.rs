@@ -0,0 +1,201 @@
+// src/enrich/rdap.rs
+//! RDAP registration lookups for the registrable domain of a match
+//!
+//! A certificate for a domain registered days ago is a strong phishing
+//! signal, so the registration date is the main thing we're after.
+
+use crate::types::Registration;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// The domain a registrant actually registered, e.g. `login.example.co.uk` -> `example.co.uk`
+///
+/// Uses the Public Suffix List, including its private section, so each
+/// `*.github.io` or `*.s3.amazonaws.com` site counts as its own domain.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = host.trim_start_matches("*.").trim_end_matches('.').to_ascii_lowercase();
+    if host.split('.').any(|label| label.is_empty()) {
+        return None;
+    }
+    psl::domain_str(&host).map(str::to_string)
+}
+
+/// Client for an RDAP server or bootstrap redirector (rdap.org by default)
+pub struct RdapClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl RdapClient {
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(crate::ct_log::http::DEFAULT_USER_AGENT)
+            .build()
+            .context("Failed to build RDAP HTTP client")?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Registration record for a registrable domain; `None` if the registry doesn't know it
+    pub async fn lookup(&self, domain: &str) -> Result<Option<Registration>> {
+        let response = self
+            .client
+            .get(format!("{}/domain/{}", self.base_url, domain))
+            .header(reqwest::header::ACCEPT, "application/rdap+json")
+            .send()
+            .await
+            .with_context(|| format!("RDAP lookup of {} failed", domain))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("RDAP server returned HTTP {} for {}", response.status(), domain);
+        }
+
+        let record: RdapDomain = response.json().await.context("Invalid RDAP response")?;
+        Ok(Some(record.into_registration(domain)))
+    }
+}
+
+#[derive(Deserialize)]
+struct RdapDomain {
+    #[serde(default)]
+    events: Vec<RdapEvent>,
+    #[serde(default)]
+    entities: Vec<RdapEntity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RdapEvent {
+    event_action: String,
+    event_date: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RdapEntity {
+    #[serde(default)]
+    roles: Vec<String>,
+    vcard_array: Option<Value>,
+    /// Registrars are often nested under the registrant or vice versa
+    #[serde(default)]
+    entities: Vec<RdapEntity>,
+}
+
+impl RdapEntity {
+    /// First entity (searching nested ones too) with the given role
+    fn find<'a>(entities: &'a [RdapEntity], role: &str) -> Option<&'a RdapEntity> {
+        entities.iter().find_map(|entity| {
+            if entity.roles.iter().any(|r| r == role) {
+                Some(entity)
+            } else {
+                Self::find(&entity.entities, role)
+            }
+        })
+    }
+
+    /// Text value of a jCard property, e.g. "fn" or "org"
+    fn vcard(&self, property: &str) -> Option<String> {
+        // ["vcard", [["fn", {}, "text", "Example Registrar"], ...]]
+        self.vcard_array
+            .as_ref()?
+            .get(1)?
+            .as_array()?
+            .iter()
+            .find(|entry| entry.get(0).and_then(Value::as_str) == Some(property))?
+            .get(3)?
+            .as_str()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl RdapDomain {
+    fn into_registration(self, domain: &str) -> Registration {
+        let event = |action: &str| {
+            self.events
+                .iter()
+                .find(|event| event.event_action == action)
+                .map(|event| event.event_date.clone())
+        };
+
+        let registrant = RdapEntity::find(&self.entities, "registrant");
+        Registration {
+            domain: domain.to_string(),
+            registrar: RdapEntity::find(&self.entities, "registrar").and_then(|e| e.vcard("fn")),
+            created: event("registration"),
+            expires: event("expiration"),
+            // Redacted records often keep the org while dropping the name
+            registrant_org: registrant.and_then(|e| e.vcard("org").or_else(|| e.vcard("fn"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("login.example.com").as_deref(), Some("example.com"));
+        assert_eq!(registrable_domain("*.shop.example.co.uk").as_deref(), Some("example.co.uk"));
+        assert_eq!(registrable_domain("a.b.example.de").as_deref(), Some("example.de"));
+        assert_eq!(registrable_domain("example.com.").as_deref(), Some("example.com"));
+        assert_eq!(registrable_domain("co.uk"), None);
+        assert_eq!(registrable_domain("localhost"), None);
+        // Shared hosting suffixes from the private section of the list
+        assert_eq!(registrable_domain("a.github.io").as_deref(), Some("a.github.io"));
+        assert_eq!(registrable_domain("x.y.s3.amazonaws.com").as_deref(), Some("y.s3.amazonaws.com"));
+        assert_eq!(registrable_domain("www.example.com.au").as_deref(), Some("example.com.au"));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_parses_registration() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/domain/example-login.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "objectClassName": "domain",
+                "ldhName": "EXAMPLE-LOGIN.COM",
+                "events": [
+                    {"eventAction": "registration", "eventDate": "2026-10-14T09:12:00Z"},
+                    {"eventAction": "expiration", "eventDate": "2027-10-14T09:12:00Z"}
+                ],
+                "entities": [{
+                    "roles": ["registrar"],
+                    "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "Example Registrar, LLC"]]],
+                    "entities": [{
+                        "roles": ["registrant"],
+                        "vcardArray": ["vcard", [["fn", {}, "text", ""], ["org", {}, "text", "Privacy Service"]]]
+                    }]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/domain/unregistered.com"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = RdapClient::new(&mock_server.uri(), Duration::from_secs(5)).unwrap();
+        let registration = client.lookup("example-login.com").await.unwrap().unwrap();
+        assert_eq!(registration.registrar.as_deref(), Some("Example Registrar, LLC"));
+        assert_eq!(registration.created.as_deref(), Some("2026-10-14T09:12:00Z"));
+        assert_eq!(registration.expires.as_deref(), Some("2027-10-14T09:12:00Z"));
+        assert_eq!(registration.registrant_org.as_deref(), Some("Privacy Service"));
+
+        assert!(client.lookup("unregistered.com").await.unwrap().is_none());
+    }
+}
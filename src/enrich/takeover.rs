@@ -0,0 +1,154 @@
+// src/enrich/takeover.rs
+//! Subdomain takeover indicators
+//!
+//! A matched domain whose CNAME points at a hosting service that no longer
+//! has the target (NXDOMAIN, or the service's "no such site" page) can often
+//! be claimed by anyone who registers that bucket/app/site name.
+
+use super::dns::CnameLookup;
+use crate::types::TakeoverRisk;
+
+/// A hosting service whose unclaimed names can be registered by anyone
+struct Service {
+    name: &'static str,
+    /// CNAME target suffixes belonging to the service
+    suffixes: &'static [&'static str],
+    /// Substring the target must also contain (to tell e.g. S3 apart from other AWS hosts)
+    requires: &'static [&'static str],
+    /// Body of the service's response for an unclaimed name; `None` means
+    /// only NXDOMAIN is a reliable signal
+    fingerprint: Option<&'static str>,
+}
+
+const SERVICES: &[Service] = &[
+    Service {
+        name: "AWS S3",
+        suffixes: &[".amazonaws.com"],
+        requires: &[".s3.", ".s3-website", ".s3-"],
+        fingerprint: Some("NoSuchBucket"),
+    },
+    Service {
+        name: "GitHub Pages",
+        suffixes: &[".github.io"],
+        requires: &[],
+        fingerprint: Some("There isn't a GitHub Pages site here"),
+    },
+    Service {
+        name: "Azure",
+        suffixes: &[
+            ".azurewebsites.net",
+            ".cloudapp.net",
+            ".cloudapp.azure.com",
+            ".trafficmanager.net",
+            ".blob.core.windows.net",
+            ".azureedge.net",
+        ],
+        requires: &[],
+        fingerprint: None,
+    },
+    Service {
+        name: "Heroku",
+        suffixes: &[".herokuapp.com", ".herokudns.com"],
+        requires: &[],
+        fingerprint: Some("No such app"),
+    },
+];
+
+impl Service {
+    fn hosts(&self, target: &str) -> bool {
+        let target = target.trim_end_matches('.');
+        self.suffixes.iter().any(|suffix| target.ends_with(suffix))
+            && (self.requires.is_empty() || self.requires.iter().any(|part| target.contains(part)))
+    }
+}
+
+/// Last CNAME in the chain that points at a takeover-prone service
+fn find_service(chain: &[String]) -> Option<(&'static Service, &str)> {
+    chain.iter().rev().find_map(|target| {
+        SERVICES
+            .iter()
+            .find(|service| service.hosts(target))
+            .map(|service| (service, target.as_str()))
+    })
+}
+
+/// Whether the matched host's HTTP response is worth fetching for `assess`
+pub fn needs_body(lookup: &CnameLookup) -> bool {
+    !lookup.nxdomain
+        && find_service(&lookup.chain).is_some_and(|(service, _)| service.fingerprint.is_some())
+}
+
+/// Decide whether a CNAME chain (plus the host's response body, if fetched)
+/// looks like a dangling record
+pub fn assess(lookup: &CnameLookup, body: Option<&str>) -> Option<TakeoverRisk> {
+    let (service, cname) = find_service(&lookup.chain)?;
+
+    let reason = if lookup.nxdomain {
+        "CNAME target does not exist (NXDOMAIN)".to_string()
+    } else {
+        let fingerprint = service.fingerprint?;
+        if !body?.contains(fingerprint) {
+            return None;
+        }
+        format!("service reports the name is unclaimed (\"{}\")", fingerprint)
+    };
+
+    Some(TakeoverRisk {
+        service: service.name.to_string(),
+        cname: cname.to_string(),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(chain: &[&str], nxdomain: bool) -> CnameLookup {
+        CnameLookup {
+            chain: chain.iter().map(|c| c.to_string()).collect(),
+            nxdomain,
+        }
+    }
+
+    #[test]
+    fn test_service_matching() {
+        let s3 = &SERVICES[0];
+        assert!(s3.hosts("assets.s3.amazonaws.com"));
+        assert!(s3.hosts("assets.s3-website-us-east-1.amazonaws.com."));
+        assert!(!s3.hosts("ec2-192-0-2-1.compute-1.amazonaws.com"));
+
+        assert!(find_service(&["cdn.example.net".to_string()]).is_none());
+        let chain = ["edge.example.net".to_string(), "app.azurewebsites.net".to_string()];
+        let (service, cname) = find_service(&chain).unwrap();
+        assert_eq!(service.name, "Azure");
+        assert_eq!(cname, "app.azurewebsites.net");
+    }
+
+    #[test]
+    fn test_nxdomain_target_is_flagged() {
+        let risk = assess(&lookup(&["gone.azurewebsites.net"], true), None).unwrap();
+        assert_eq!(risk.service, "Azure");
+        assert_eq!(risk.cname, "gone.azurewebsites.net");
+        assert!(risk.reason.contains("NXDOMAIN"));
+
+        // NXDOMAIN without a CNAME to a known service is just a dead name
+        assert!(assess(&lookup(&[], true), None).is_none());
+    }
+
+    #[test]
+    fn test_fingerprint_requires_body() {
+        let pages = lookup(&["acme.github.io"], false);
+        assert!(needs_body(&pages));
+        assert!(assess(&pages, None).is_none());
+        assert!(assess(&pages, Some("<h1>Welcome</h1>")).is_none());
+
+        let risk = assess(&pages, Some("<p>There isn't a GitHub Pages site here.</p>")).unwrap();
+        assert_eq!(risk.service, "GitHub Pages");
+
+        // Azure has no fingerprint, so a resolving target is never flagged
+        let azure = lookup(&["app.cloudapp.net"], false);
+        assert!(!needs_body(&azure));
+        assert!(assess(&azure, Some("anything")).is_none());
+    }
+}
@@ -1,9 +1,14 @@
 // src/filter.rs
 //! Root domain filtering for output
 
+use crate::enrich::registrable_domain;
 use std::collections::HashSet;
-use std::fs;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::net::IpAddr;
 use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
 
 /// Filter that checks if domains belong to specified root domains
 #[derive(Clone)]
@@ -61,6 +66,61 @@ impl RootDomainFilter {
     }
 }
 
+/// Collapses matches to their registrable root domain and lets through only
+/// roots never seen before
+///
+/// Seen roots are appended to a file (one per line) so they stay seen across
+/// restarts; a fresh root usually means an acquisition or a brand-new property.
+pub struct NewRootFilter {
+    seen: Mutex<HashSet<String>>,
+    file: Mutex<File>,
+}
+
+impl NewRootFilter {
+    /// Load previously seen roots from `path`, creating it if missing
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let seen = match fs::read_to_string(path) {
+            Ok(content) => content
+                .lines()
+                .map(|l| l.trim().to_lowercase())
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            seen: Mutex::new(seen),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Root of `domain` if it has never been seen before (and records it)
+    ///
+    /// IP addresses and names without a registrable domain yield `None`.
+    pub fn new_root(&self, domain: &str) -> Option<String> {
+        if domain.parse::<IpAddr>().is_ok() {
+            return None;
+        }
+        let root = registrable_domain(domain)?;
+
+        if !self.seen.lock().unwrap().insert(root.clone()) {
+            return None;
+        }
+        // Still new for this run even if it couldn't be persisted
+        if let Err(e) = writeln!(self.file.lock().unwrap(), "{}", root) {
+            warn!("Failed to record new root domain {}: {}", root, e);
+        }
+        Some(root)
+    }
+
+    /// Get the number of roots seen so far
+    pub fn count(&self) -> usize {
+        self.seen.lock().unwrap().len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +142,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::writeln_empty_string)]
     fn test_from_file() {
         let mut temp_file = NamedTempFile::new().unwrap();
         writeln!(temp_file, "example.com").unwrap();
@@ -131,4 +192,22 @@ mod tests {
         assert!(filter.should_emit("WWW.EXAMPLE.COM"));
         assert!(filter.should_emit("Api.Example.Com"));
     }
+
+    #[test]
+    fn test_new_roots_persisted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("roots.txt");
+
+        let filter = NewRootFilter::open(&path).unwrap();
+        assert_eq!(filter.new_root("api.example.com"), Some("example.com".to_string()));
+        assert_eq!(filter.new_root("*.www.Example.com"), None);
+        assert_eq!(filter.new_root("shop.example.co.uk"), Some("example.co.uk".to_string()));
+        assert_eq!(filter.new_root("10.0.0.1"), None);
+        drop(filter);
+
+        let filter = NewRootFilter::open(&path).unwrap();
+        assert_eq!(filter.count(), 2);
+        assert_eq!(filter.new_root("example.com"), None);
+        assert_eq!(filter.new_root("example.net"), Some("example.net".to_string()));
+    }
 }
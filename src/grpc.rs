@@ -0,0 +1,250 @@
+// src/grpc.rs
+//! Optional gRPC API, for typed clients in other languages
+//!
+//! The service is defined in `proto/ct_scout.proto`. Calls need
+//! `authorization: Bearer <token>` metadata, like the REST API.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::{debug, info, warn};
+
+use crate::api::{MatchStream, constant_time_eq};
+use crate::database::{DatabaseBackend, MatchQuery};
+use crate::stats::StatsCollector;
+use crate::types::MatchResult;
+
+pub mod proto {
+    tonic::include_proto!("ctscout.v1");
+}
+
+use proto::ct_scout_server::{CtScout, CtScoutServer};
+
+/// Matches returned when the request sets no `limit`
+const DEFAULT_MATCH_LIMIT: i64 = 100;
+
+/// Upper bound on `limit`, so one call can't pull the whole table
+const MAX_MATCH_LIMIT: i64 = 1000;
+
+/// What the RPCs work on
+#[derive(Clone)]
+pub struct GrpcState {
+    pub db: Option<Arc<dyn DatabaseBackend>>,
+    pub stats: StatsCollector,
+    pub stream: Arc<MatchStream>,
+    pub token: Arc<str>,
+}
+
+/// Bind the listener up front so a bad address fails start-up
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!("Serving the gRPC API on {}", listener.local_addr()?);
+    Ok(listener)
+}
+
+/// Answer calls until the task is dropped
+pub async fn serve(listener: TcpListener, state: GrpcState) {
+    let token = Arc::clone(&state.token);
+    let service = CtScoutServer::with_interceptor(state, move |request: Request<()>| authenticate(&token, request));
+    let result = tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await;
+    if let Err(e) = result {
+        warn!("gRPC API stopped: {}", e);
+    }
+}
+
+fn authenticate(token: &str, request: Request<()>) -> Result<Request<()>, Status> {
+    let presented = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), token.as_bytes()) => Ok(request),
+        _ => Err(Status::unauthenticated("missing or invalid bearer token")),
+    }
+}
+
+impl From<&MatchResult> for proto::Match {
+    fn from(result: &MatchResult) -> Self {
+        Self {
+            timestamp: result.timestamp,
+            matched_domain: result.matched_domain.clone(),
+            all_domains: result.all_domains.clone(),
+            cert_index: result.cert_index,
+            not_before: result.not_before,
+            not_after: result.not_after,
+            fingerprint: result.fingerprint.clone(),
+            serial_number: result.serial_number.clone(),
+            issuer: result.issuer.clone(),
+            program_name: result.program_name.clone(),
+            platform: result.platform.clone(),
+            is_precert: result.is_precert,
+            ct_log_url: result.ct_log_url.clone(),
+            logged_at: result.logged_at,
+            json: serde_json::to_string(result).unwrap_or_default(),
+            matched_pattern: result.matched_pattern.clone(),
+            match_type: result.match_type.map(|t| t.to_string()),
+        }
+    }
+}
+
+type MatchStreamResponse = Pin<Box<dyn Stream<Item = Result<proto::Match, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl CtScout for GrpcState {
+    type WatchMatchesStream = MatchStreamResponse;
+
+    async fn watch_matches(
+        &self,
+        request: Request<proto::WatchMatchesRequest>,
+    ) -> Result<Response<Self::WatchMatchesStream>, Status> {
+        let program = request.into_inner().program;
+        let matches = BroadcastStream::new(self.stream.subscribe()).filter_map(move |item| match item {
+            Ok(result) => {
+                let wanted = program.is_none() || result.program_name == program;
+                wanted.then(|| Ok(proto::Match::from(&*result)))
+            }
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                debug!("gRPC client fell behind, skipped {} matches", skipped);
+                None
+            }
+        });
+        Ok(Response::new(Box::pin(matches)))
+    }
+
+    async fn query_matches(
+        &self,
+        request: Request<proto::QueryMatchesRequest>,
+    ) -> Result<Response<proto::QueryMatchesResponse>, Status> {
+        let Some(ref db) = self.db else {
+            return Err(Status::unavailable("matches are only stored with [database] enabled"));
+        };
+
+        let request = request.into_inner();
+        let query = MatchQuery {
+            domain_pattern: request.domain,
+            since: request.since,
+            until: request.until,
+            program_name: request.program,
+            limit: Some(request.limit.unwrap_or(DEFAULT_MATCH_LIMIT).clamp(1, MAX_MATCH_LIMIT)),
+            offset: request.offset,
+        };
+        let results = db
+            .get_matches(query)
+            .await
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+        Ok(Response::new(proto::QueryMatchesResponse {
+            matches: results.iter().map(proto::Match::from).collect(),
+        }))
+    }
+
+    async fn get_stats(&self, _request: Request<proto::GetStatsRequest>) -> Result<Response<proto::Stats>, Status> {
+        let snapshot = self.stats.snapshot();
+        let logs: HashMap<String, proto::LogCounts> = self
+            .stats
+            .top_logs(usize::MAX)
+            .into_iter()
+            .map(|(url, counts)| (url, proto::LogCounts { entries: counts.entries, errors: counts.errors }))
+            .collect();
+        Ok(Response::new(proto::Stats {
+            total_processed: snapshot.total_processed,
+            matches_found: snapshot.matches_found,
+            messages_per_minute: snapshot.messages_per_minute,
+            uptime_secs: snapshot.uptime_secs,
+            program_matches: self.stats.top_programs(usize::MAX).into_iter().collect(),
+            logs,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputHandler;
+    use proto::ct_scout_client::CtScoutClient;
+    use tonic::transport::Channel;
+
+    async fn start() -> (CtScoutClient<Channel>, Arc<MatchStream>) {
+        let stream = Arc::new(MatchStream::new());
+        let stats = StatsCollector::new();
+        stats.increment_processed();
+        let state = GrpcState {
+            db: None,
+            stats,
+            stream: Arc::clone(&stream),
+            token: Arc::from("secret"),
+        };
+        let listener = bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, state));
+        (CtScoutClient::connect(url).await.unwrap(), stream)
+    }
+
+    fn authorized<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let (mut client, _) = start().await;
+
+        let status = client.get_stats(proto::GetStatsRequest {}).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let stats = client.get_stats(authorized(proto::GetStatsRequest {})).await.unwrap().into_inner();
+        assert_eq!(stats.total_processed, 1);
+
+        // No database, no match history
+        let status = client
+            .query_matches(authorized(proto::QueryMatchesRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_watch_matches_filters_by_program() {
+        let (mut client, stream) = start().await;
+        let request = proto::WatchMatchesRequest { program: Some("acme".to_string()) };
+        let mut matches = client.watch_matches(authorized(request)).await.unwrap().into_inner();
+
+        // Wait for the call to subscribe before emitting
+        while stream.subscriber_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let data = crate::types::CertData {
+            all_domains: Some(vec!["api.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(7),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        for program in ["other", "acme"] {
+            let result = MatchResult::from_cert_data("api.example.com".to_string(), &data, Some(program.to_string()), None);
+            stream.emit_match(&result).await.unwrap();
+        }
+
+        let received = matches.message().await.unwrap().unwrap();
+        assert_eq!(received.program_name.as_deref(), Some("acme"));
+        assert_eq!(received.matched_domain, "api.example.com");
+        assert_eq!(received.cert_index, Some(7));
+        assert!(received.json.contains("\"matched_domain\":\"api.example.com\""));
+    }
+}
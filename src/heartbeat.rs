@@ -0,0 +1,133 @@
+// src/heartbeat.rs
+//! Dead man's switch pings (healthchecks.io, Cronitor, Uptime Kuma push monitors, ...)
+//!
+//! While ct-scout runs it POSTs a small JSON status to the configured URL every
+//! interval; the receiving service alerts when the pings stop.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::config::HeartbeatConfig;
+use crate::ct_log::LogHealthTracker;
+use crate::stats::StatsCollector;
+
+/// Body of each heartbeat request
+#[derive(Debug, Serialize)]
+pub struct HeartbeatPayload {
+    pub uptime_secs: u64,
+    pub processed: u64,
+    pub matches: u64,
+    pub unhealthy_logs: usize,  // Failed or quarantined
+}
+
+pub struct Heartbeat {
+    client: Client,
+    url: String,
+    interval: Duration,
+}
+
+impl Heartbeat {
+    pub fn new(config: &HeartbeatConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build heartbeat HTTP client")?;
+        Ok(Self {
+            client,
+            url: config.url.clone(),
+            interval: Duration::from_secs(config.interval_secs.max(1)),
+        })
+    }
+
+    /// Ping now and then every interval until the task is aborted
+    pub fn spawn(self, stats: StatsCollector, health: Arc<LogHealthTracker>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            loop {
+                ticker.tick().await;
+
+                let snapshot = stats.snapshot();
+                let (_, _, failed, quarantined) = health.get_stats().await;
+                let payload = HeartbeatPayload {
+                    uptime_secs: snapshot.uptime_secs,
+                    processed: snapshot.total_processed,
+                    matches: snapshot.matches_found,
+                    unhealthy_logs: failed + quarantined,
+                };
+
+                // A missed ping is what the other side alerts on; just try again next time
+                match self.beat(&payload).await {
+                    Ok(()) => debug!("Heartbeat sent: {:?}", payload),
+                    Err(e) => warn!("Heartbeat failed: {:#}", e),
+                }
+            }
+        })
+    }
+
+    async fn beat(&self, payload: &HeartbeatPayload) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(payload)
+            .send()
+            .await
+            .context("Failed to reach heartbeat URL")?
+            .error_for_status()
+            .context("Heartbeat URL rejected the ping")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_heartbeat_posts_status() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/ping/abc"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let stats = StatsCollector::new();
+        stats.increment_processed();
+        stats.increment_processed();
+        stats.increment_matches();
+        let health = Arc::new(LogHealthTracker::new(1));
+        health.record_success("https://ok.example.com/").await;
+        health.record_failure("https://down.example.com/", "HTTP 503".to_string()).await;
+
+        let heartbeat = Heartbeat::new(&HeartbeatConfig {
+            url: format!("{}/ping/abc", mock_server.uri()),
+            interval_secs: 60,
+            timeout_secs: 5,
+        })
+        .unwrap();
+        let task = heartbeat.spawn(stats, health);
+
+        // The first ping goes out immediately
+        let mut requests = Vec::new();
+        for _ in 0..50 {
+            requests = mock_server.received_requests().await.unwrap();
+            if !requests.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        task.abort();
+
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["processed"], 2);
+        assert_eq!(body["matches"], 1);
+        assert_eq!(body["unhealthy_logs"], 1);
+    }
+}
@@ -0,0 +1,139 @@
+// src/kafka.rs
+//! Kafka topic input (`kafka` build feature)
+//!
+//! Consumes certificates another system publishes to a Kafka topic, one per
+//! message, in any format `replay` accepts: a certstream message, a bare
+//! certstream `data` object, or a line of ct-scout's own JSON output. Instances
+//! sharing a `group_id` split the topic's partitions between them.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::config::KafkaConfig;
+use crate::ct_log::pipeline::{CertSender, Delivery};
+use crate::ct_log::replay::parse_line;
+use crate::ct_log::source::CertSource;
+
+/// Wait after a consumer error before asking for the next message
+const ERROR_DELAY: Duration = Duration::from_secs(1);
+
+/// Kafka topic as a certificate source
+pub struct KafkaSource {
+    topic: String,
+    consumer: StreamConsumer,
+}
+
+impl KafkaSource {
+    /// Create the consumer and subscribe to `config.topic`
+    ///
+    /// Brokers are only contacted once the source runs.
+    pub fn new(config: &KafkaConfig) -> Result<Self> {
+        let mut client = ClientConfig::new();
+        client
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group_id)
+            // Offsets are stored by hand once a certificate is in the pipeline
+            .set("enable.auto.offset.store", "false");
+        for (key, value) in &config.properties {
+            client.set(key, value);
+        }
+
+        let consumer: StreamConsumer = client.create().context("Failed to create Kafka consumer")?;
+        consumer
+            .subscribe(&[&config.topic])
+            .with_context(|| format!("Failed to subscribe to Kafka topic {}", config.topic))?;
+
+        Ok(Self {
+            topic: config.topic.clone(),
+            consumer,
+        })
+    }
+}
+
+#[async_trait]
+impl CertSource for KafkaSource {
+    fn name(&self) -> String {
+        format!("Kafka topic {}", self.topic)
+    }
+
+    async fn run(self: Box<Self>, cert_tx: CertSender, mut shutdown_rx: watch::Receiver<bool>) -> Result<()> {
+        info!("Consuming certificates from Kafka topic {}", self.topic);
+
+        // The topic holds the backlog, so wait for room rather than dropping
+        // certificates whose offsets would then be stored past them
+        let cert_tx = cert_tx.blocking();
+
+        loop {
+            let message = tokio::select! {
+                message = self.consumer.recv() => message,
+                _ = shutdown_rx.changed() => break,
+            };
+            // librdkafka reconnects by itself, so errors are only reported
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Kafka topic {}: {}", self.topic, e);
+                    tokio::time::sleep(ERROR_DELAY).await;
+                    continue;
+                }
+            };
+
+            let data = match message.payload_view::<str>() {
+                Some(Ok(text)) => match parse_line(text) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        debug!("Skipping Kafka message: {:#}", e);
+                        None
+                    }
+                },
+                Some(Err(_)) => {
+                    debug!("Skipping Kafka message that is not UTF-8");
+                    None
+                }
+                None => None,
+            };
+            if let Some(data) = data
+                && cert_tx.send(data).await? == Delivery::Dropped
+            {
+                continue;
+            }
+
+            // The certificate is in the pipeline now, so a restart resumes after it
+            if let Err(e) = self.consumer.store_offset_from_message(&message) {
+                warn!("Failed to store Kafka offset: {}", e);
+            }
+        }
+
+        debug!("Kafka input stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_source_from_config() {
+        let config: KafkaConfig = toml::from_str(
+            r#"
+            brokers = "localhost:1"
+            topic = "certs"
+            properties = { "client.id" = "ct-scout-test" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.group_id, "ct-scout");
+        assert!(config.poll_logs);
+
+        // Creating and subscribing doesn't need a reachable broker
+        let source = KafkaSource::new(&config).unwrap();
+        assert_eq!(source.name(), "Kafka topic certs");
+    }
+}
@@ -1,20 +1,37 @@
 // src/lib.rs
 // Library interface for ct-scout
+pub mod anomaly;
+pub mod api;
+pub mod asn;
 pub mod cert_parser;
+pub mod certstream;
 pub mod cli;
+pub mod commands;
 pub mod config;
 pub mod ct_log;
+pub mod dashboard;
 pub mod database;
 pub mod dedupe;
+pub mod enrich;
 pub mod filter;
+pub mod grpc;
+pub mod heartbeat;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod logfile;
 pub mod metrics;
 pub mod notifier;
 pub mod output;
 pub mod platforms;
 pub mod progress;
+pub mod pushgateway;
 pub mod redis_publisher;
+pub mod sentry;
+pub mod server;
 pub mod state;
 pub mod stats;
+pub mod systemd;
+pub mod throttle;
 pub mod types;
 pub mod watcher;
 pub mod watchlist;
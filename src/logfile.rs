@@ -0,0 +1,224 @@
+// src/logfile.rs
+//! Log file writer with daily or size-based rotation
+//!
+//! Rotated files sit next to the live file as `<name>.<suffix>`, where the
+//! suffix is the day (`2025-01-31`) or the rotation time (`20250131-142501.123`).
+//! Both sort chronologically, which is what pruning relies on.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// When the live log file is rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// At the first write after midnight (UTC)
+    Daily,
+    /// Before a write would grow the file past this many bytes
+    Size(u64),
+    /// Never; the file grows without bound
+    Never,
+}
+
+impl Rotation {
+    /// Parse `[logging] rotation`, with `max_size_mb` used for "size"
+    pub fn from_config(rotation: &str, max_size_mb: u64) -> Result<Self> {
+        match rotation {
+            "daily" => Ok(Self::Daily),
+            "size" if max_size_mb == 0 => anyhow::bail!("logging.max_size_mb must be greater than 0"),
+            "size" => Ok(Self::Size(max_size_mb * 1024 * 1024)),
+            "never" => Ok(Self::Never),
+            other => anyhow::bail!(
+                "Invalid logging.rotation '{}'. Must be 'daily', 'size' or 'never'",
+                other
+            ),
+        }
+    }
+}
+
+/// Append-only log file that rotates itself
+pub struct RollingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: usize,  // Rotated files kept (0 = keep all)
+    file: File,
+    size: u64,
+    day: NaiveDate,  // Day the live file's contents belong to
+}
+
+impl RollingFile {
+    /// Open (appending to) the log file, creating its directory if needed
+    pub fn open(path: impl Into<PathBuf>, rotation: Rotation, max_files: usize) -> Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        }
+
+        let file = open_append(&path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let meta = file.metadata()?;
+        // A file left from an earlier day is rotated on the first write
+        let day = meta
+            .modified()
+            .ok()
+            .map(|modified| chrono::DateTime::<Utc>::from(modified).date_naive())
+            .unwrap_or_else(|| Utc::now().date_naive());
+
+        Ok(Self {
+            size: meta.len(),
+            path,
+            rotation,
+            max_files,
+            file,
+            day,
+        })
+    }
+
+    fn needs_rotation(&self, incoming: usize, today: NaiveDate) -> bool {
+        match self.rotation {
+            Rotation::Daily => today != self.day && self.size > 0,
+            Rotation::Size(max) => self.size > 0 && self.size + incoming as u64 > max,
+            Rotation::Never => false,
+        }
+    }
+
+    fn rotate(&mut self, today: NaiveDate) -> io::Result<()> {
+        self.file.flush()?;
+        let suffix = match self.rotation {
+            Rotation::Daily => self.day.format("%Y-%m-%d").to_string(),
+            _ => Utc::now().format("%Y%m%d-%H%M%S%.3f").to_string(),
+        };
+        fs::rename(&self.path, rotated_path(&self.path, &suffix))?;
+
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        self.day = today;
+        self.prune();
+        Ok(())
+    }
+
+    /// Delete the oldest rotated files beyond `max_files`
+    fn prune(&self) {
+        if self.max_files == 0 {
+            return;
+        }
+        let mut rotated = rotated_files(&self.path);
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.max_files);
+        for old in &rotated[..excess] {
+            // Best effort: failing to prune must not stop logging
+            let _ = fs::remove_file(old);
+        }
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = Utc::now().date_naive();
+        if self.needs_rotation(buf.len(), today) {
+            self.rotate(today)?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        self.day = today;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Rotated siblings of the live log file
+fn rotated_files(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", name);
+    let dir = match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => dir,
+        None => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_name().to_str().and_then(|file| file.strip_prefix(&prefix)).is_some_and(|suffix| {
+                // Date or timestamp suffix only, so e.g. "ct-scout.log.gz" archives are left alone
+                !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit() || "-.".contains(c))
+            })
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(Rotation::from_config("daily", 0).unwrap(), Rotation::Daily);
+        assert_eq!(Rotation::from_config("size", 2).unwrap(), Rotation::Size(2 * 1024 * 1024));
+        assert!(Rotation::from_config("size", 0).is_err());
+        assert!(Rotation::from_config("hourly", 10).is_err());
+    }
+
+    #[test]
+    fn test_size_rotation_prunes_old_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("ct-scout.log");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path.with_file_name("ct-scout.log.gz"), "archive").unwrap();
+
+        let mut log = RollingFile::open(&path, Rotation::Size(10), 2).unwrap();
+        for line in ["line one\n", "line two\n", "line three\n", "line four\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+            // Distinct timestamps for the rotated names
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        log.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line four\n");
+        let mut rotated: Vec<String> = rotated_files(&path)
+            .iter()
+            .map(|p| fs::read_to_string(p).unwrap())
+            .collect();
+        rotated.sort();
+        assert_eq!(rotated, vec!["line three\n", "line two\n"]);
+        assert!(path.with_file_name("ct-scout.log.gz").exists());
+    }
+
+    #[test]
+    fn test_daily_rotation_names_file_by_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ct-scout.log");
+        let mut log = RollingFile::open(&path, Rotation::Daily, 0).unwrap();
+        log.write_all(b"yesterday\n").unwrap();
+
+        let yesterday = NaiveDate::from_ymd_opt(2025, 1, 30).unwrap();
+        log.day = yesterday;
+        log.write_all(b"today\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+        let archived = dir.path().join("ct-scout.log.2025-01-30");
+        assert_eq!(fs::read_to_string(archived).unwrap(), "yesterday\n");
+    }
+}
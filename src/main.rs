@@ -1,24 +1,45 @@
 // src/main.rs
+use anyhow::Context;
 use clap::Parser;
-use ct_scout::cli::{Cli, OutputFormat};
+use ct_scout::anomaly::AnomalyAlerter;
+use ct_scout::api::{ApiState, MatchStream};
+use ct_scout::grpc::GrpcState;
+use ct_scout::cli::{Cli, Command, ExportArgs, OutputFormat, ReplayArgs, RunArgs, ScanArgs, SyncArgs};
+use ct_scout::commands;
 use ct_scout::config::Config;
-use ct_scout::ct_log::{CtLogCoordinator, LogListFetcher};
-use ct_scout::database::{DatabaseBackend, PostgresBackend};
+use ct_scout::dashboard::Dashboard;
+use ct_scout::certstream::CertstreamSource;
+use ct_scout::ct_log::{AdaptivePollConfig, AuditConfig, ChannelConfig, CtLogCoordinator, KeyedState, LogCheckPolicy, LogIds, LogMonitorConfig, QuarantineConfig, RateLimitConfig, ReplaySource, check_logs};
+use ct_scout::database::{DatabaseBackend, LogLeases, PostgresBackend, SqliteStateManager};
 use ct_scout::dedupe::Dedupe;
-use ct_scout::filter::RootDomainFilter;
+use ct_scout::enrich::{EnrichConfig, Enricher, IntelClient, IntelProvider};
+use ct_scout::filter::{NewRootFilter, RootDomainFilter};
+use ct_scout::asn::AsnRefresher;
+use ct_scout::heartbeat::Heartbeat;
+use ct_scout::logfile::{RollingFile, Rotation};
 use ct_scout::metrics;
-use ct_scout::output::{self, csv, human, json, silent, webhook, OutputManager};
-use ct_scout::platforms::{HackerOneAPI, IntigritiAPI, PlatformAPI, PlatformSyncManager};
+use ct_scout::notifier::Notifier;
+use ct_scout::output::{self, csv, human, json, pipe, silent, webhook, OutputManager};
+use ct_scout::output::timestamps::TimestampFormat;
+use ct_scout::output::routing::Router;
+use ct_scout::platforms::{self, PlatformSyncManager};
 use ct_scout::redis_publisher;
+use ct_scout::sentry;
+use ct_scout::server::{self, Readiness};
 use ct_scout::progress::ProgressIndicator;
+use ct_scout::pushgateway::Pushgateway;
 use ct_scout::state::StateManager;
 use ct_scout::stats::StatsCollector;
+use ct_scout::systemd::SystemdNotifier;
 use ct_scout::watcher::ConfigWatcher;
 use ct_scout::watchlist::Watchlist;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
@@ -29,25 +50,50 @@ async fn main() -> anyhow::Result<()> {
     // Validate arguments
     cli.validate()?;
 
-    // Load config file
-    let mut config = Config::from_file(Path::new(&cli.config))?;
+    // `validate` reports config errors itself rather than failing to load it
+    if let Some(Command::Validate) = cli.command {
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::new(cli.log_level()))
+            .with_writer(std::io::stderr)
+            .init();
+        return commands::validate::run(&cli.config_paths(), &cli.set);
+    }
+
+    // Schemas describe the output payloads, not any particular config
+    if let Some(Command::Schema { payload }) = cli.command {
+        return commands::schema::run(payload);
+    }
+
+    // `init` creates the config, so there is nothing to load yet
+    if let Some(Command::Init(ref init)) = cli.command {
+        let [ref path] = cli.config[..] else {
+            anyhow::bail!("init writes a single file; pass one --config");
+        };
+        return commands::init::run(Path::new(path), init);
+    }
+
+    // Load config files (merged in order) and --set overrides
+    let mut config = Config::load(&cli.config_paths(), &cli.set)?;
 
     // Apply CLI overrides
-    if let Some(ref url) = cli.webhook_url {
-        if let Some(ref mut webhook) = config.webhook {
-            webhook.url = url.clone();
+    #[allow(clippy::collapsible_if)]
+    if let Some(args) = cli.run_args() {
+        if let Some(ref url) = args.webhook_url {
+            if let Some(ref mut webhook) = config.webhook {
+                webhook.url = url.clone();
+            }
         }
-    }
 
-    if let Some(ref secret) = cli.webhook_secret {
-        if let Some(ref mut webhook) = config.webhook {
-            webhook.secret = Some(secret.clone());
+        if let Some(ref secret) = args.webhook_secret {
+            if let Some(ref mut webhook) = config.webhook {
+                webhook.secret = Some(secret.clone());
+            }
         }
-    }
 
-    if let Some(timeout) = cli.webhook_timeout {
-        if let Some(ref mut webhook) = config.webhook {
-            webhook.timeout_secs = Some(timeout);
+        if let Some(timeout) = args.webhook_timeout {
+            if let Some(ref mut webhook) = config.webhook {
+                webhook.timeout_secs = Some(timeout);
+            }
         }
     }
 
@@ -63,10 +109,91 @@ async fn main() -> anyhow::Result<()> {
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(log_level));
 
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
+    // Utility commands and --pipe print their results to stdout, so keep logs
+    // out of it; the dashboard owns the whole terminal
+    let console = if cli.run_args().is_some_and(|args| args.tui) {
+        BoxMakeWriter::new(std::io::sink)
+    } else if cli.run_args().is_some_and(|args| !args.pipe) {
+        BoxMakeWriter::new(std::io::stdout)
+    } else {
+        BoxMakeWriter::new(std::io::stderr)
+    };
+
+    let log_file = match config.logging.file {
+        Some(ref path) => {
+            let rotation = Rotation::from_config(&config.logging.rotation, config.logging.max_size_mb)?;
+            let file = RollingFile::open(path, rotation, config.logging.max_files)?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(std::sync::Mutex::new(file)),
+            )
+        }
+        None => None,
+    };
+
+    let sentry = match config.sentry {
+        Some(ref sentry) => {
+            sentry::install_panic_hook(sentry)?;
+            Some(sentry::init(sentry)?)
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(console))
+        .with(log_file)
+        .with(sentry)
         .init();
 
+    let watcher = ConfigWatcher::new(cli.config_paths()).with_overrides(cli.set.clone());
+    let (args, input) = match cli.command {
+        None => (cli.run, Input::Logs),
+        Some(Command::Run(args)) => (args, Input::Logs),
+        Some(Command::Scan(scan)) => (scan.run.clone(), Input::Scan(scan)),
+        Some(Command::Replay(replay)) => (replay.run.clone(), Input::Replay(replay)),
+        Some(Command::Query(ref query)) => return commands::query::run(&config, query).await,
+        Some(Command::Sync(ref sync)) => return commands::sync::run(&config, sync).await,
+        Some(Command::Export(ref export)) => return commands::export::run(&config, export).await,
+        Some(Command::Status) => return commands::status::run(&config).await,
+        Some(Command::Logs { ref command }) => return commands::logs::run(&config, command).await,
+        Some(Command::State { ref command }) => return commands::state::run(&config, command).await,
+        Some(Command::Watchlist { ref command }) => return commands::watchlist::run(&config, command).await,
+        Some(Command::Bench(ref bench)) => return commands::bench::run(&config, bench).await,
+        Some(Command::TestMatch { ref targets }) => {
+            return commands::test_match::run(&config, targets);
+        }
+        Some(Command::Validate | Command::Init(_) | Command::Schema { .. }) => unreachable!("handled before the config is loaded"),
+    };
+
+    if args.export_scope {
+        let export = ExportArgs {
+            output: args.output.clone(),
+            json: args.json,
+            no_sync: false,
+        };
+        return commands::export::run(&config, &export).await;
+    }
+    if args.dry_run_sync {
+        return commands::sync::run(&config, &SyncArgs { dry_run: true }).await;
+    }
+
+    run(watcher, config, args, input).await
+}
+
+/// Where certificates come from
+enum Input {
+    /// Follow every configured log
+    Logs,
+    /// One entry range of a single log
+    Scan(ScanArgs),
+    /// Captured certificates from a file or stdin
+    Replay(ReplayArgs),
+}
+
+/// Monitor CT logs until shutdown (or until a `scan` range / `replay` input is done)
+async fn run(watcher: ConfigWatcher, config: Config, args: RunArgs, input: Input) -> anyhow::Result<()> {
     tracing::info!("Starting ct-scout...");
 
     // Initialize Prometheus metrics if enabled
@@ -92,15 +219,13 @@ async fn main() -> anyhow::Result<()> {
 
     // Start config file watcher if enabled
     // Precedence: CLI flag overrides config
-    let watch_config_enabled = if cli.watch_config {
+    let watch_config_enabled = if args.watch_config {
         true
     } else {
         config.watch_config
     };
 
     if watch_config_enabled {
-        let config_path = PathBuf::from(&cli.config);
-        let watcher = ConfigWatcher::new(config_path.clone());
         let mut config_rx = watcher.watch()?;
 
         // Spawn task to handle config reloads
@@ -130,6 +255,7 @@ async fn main() -> anyhow::Result<()> {
     // Initialize and spawn platform sync manager if configured
     let (platform_shutdown_tx, platform_shutdown_rx) = tokio::sync::watch::channel(false);
     let mut platform_sync_handle = None;
+    let mut platform_sync = None;
 
     // Check if platforms are enabled
     let platforms_enabled = config.platforms.hackerone.as_ref().map(|h| h.enabled).unwrap_or(false)
@@ -138,151 +264,48 @@ async fn main() -> anyhow::Result<()> {
     if platforms_enabled {
         tracing::info!("Platform API integration enabled, initializing sync manager...");
 
-        let mut platforms: Vec<Box<dyn PlatformAPI>> = Vec::new();
-
-        // Initialize HackerOne if configured
-        if let Some(h1_config) = &config.platforms.hackerone {
-            if h1_config.enabled {
-                tracing::info!("Initializing HackerOne API integration");
-
-                // Get filter and max_programs from config with defaults
-                let filter = h1_config.filter.clone();
-                let max_programs = h1_config.max_programs.unwrap_or(config.platforms.max_programs_per_platform);
-
-                let h1_api = HackerOneAPI::new(
-                    h1_config.username.clone(),
-                    h1_config.api_token.clone(),
-                    filter.clone(),
-                    max_programs,
-                )?;
-
-                // Test connection
-                match h1_api.test_connection().await {
-                    Ok(true) => {
-                        tracing::info!("HackerOne API connection successful (filter: {}, max: {})", filter, max_programs);
-                        platforms.push(Box::new(h1_api));
-                    }
-                    Ok(false) => {
-                        tracing::warn!("HackerOne API connection failed (invalid credentials?)");
-                    }
-                    Err(e) => {
-                        tracing::error!("HackerOne API connection error: {:?}", e);
-                    }
-                }
-            }
-        }
-
-        // Initialize Intigriti if configured
-        if let Some(intigriti_config) = &config.platforms.intigriti {
-            if intigriti_config.enabled {
-                tracing::info!("Initializing Intigriti API integration");
-
-                // Get filter and max_programs from config with defaults
-                let filter = intigriti_config.filter.clone();
-                let max_programs = intigriti_config.max_programs.unwrap_or(config.platforms.max_programs_per_platform);
-
-                let intigriti_api = IntigritiAPI::new(
-                    intigriti_config.api_token.clone(),
-                    filter.clone(),
-                    max_programs,
-                )?;
-
-                // Test connection
-                match intigriti_api.test_connection().await {
-                    Ok(true) => {
-                        tracing::info!("Intigriti API connection successful (filter: {}, max: {})", filter, max_programs);
-                        platforms.push(Box::new(intigriti_api));
-                    }
-                    Ok(false) => {
-                        tracing::warn!("Intigriti API connection failed (invalid credentials?)");
-                    }
-                    Err(e) => {
-                        tracing::error!("Intigriti API connection error: {:?}", e);
-                    }
-                }
-            }
-        }
+        let platforms = platforms::connect_enabled(&config.platforms).await?;
 
         if !platforms.is_empty() {
             // Create platform sync manager
-            let sync_manager = PlatformSyncManager::new(
+            let sync_manager = Arc::new(PlatformSyncManager::new(
                 platforms,
                 watchlist.clone(),
                 config.platforms.sync_interval_hours,
+            ).with_snapshots(commands::watchlist::history(&config.platforms).unwrap_or_else(|e| {
+                // Snapshots are a safety net, not a reason to refuse to start
+                tracing::warn!("Keeping watchlist snapshots in memory only: {:#}", e);
+                platforms::SnapshotHistory::new(config.platforms.watchlist_snapshots)
+            })));
+            platform_sync = Some(sync_manager.clone());
+
+            let shutdown_rx_clone = platform_shutdown_rx.clone();
+            platform_sync_handle = Some(tokio::spawn(async move {
+                sync_manager.run(shutdown_rx_clone).await;
+            }));
+
+            tracing::info!(
+                "Platform sync manager started (sync interval: {} hours)",
+                config.platforms.sync_interval_hours
             );
-
-            // If --export-scope is set, run initial sync synchronously then export and exit
-            if cli.export_scope {
-                tracing::info!("--export-scope: Running initial platform sync before export...");
-
-                // Create a shutdown channel for the single sync
-                let (export_shutdown_tx, export_shutdown_rx) = tokio::sync::watch::channel(false);
-
-                // Spawn sync manager to run the initial sync
-                // The sync_all_platforms() is called immediately at the start of run()
-                let sync_handle = tokio::spawn(async move {
-                    sync_manager.run(export_shutdown_rx).await;
-                });
-
-                // Give it time to complete the initial sync
-                // Most API calls complete within a few seconds
-                tokio::time::sleep(Duration::from_secs(3)).await;
-
-                // Signal shutdown to stop the periodic loop
-                export_shutdown_tx.send(true).ok();
-
-                // Wait for the sync task to finish
-                sync_handle.await.ok();
-
-                tracing::info!("Initial platform sync complete. Exporting scope...");
-                let watchlist_guard = watchlist.lock().await;
-                let toml_output = watchlist_guard.export_to_toml();
-                println!("{}", toml_output);
-                tracing::info!("Export complete. Exiting.");
-                return Ok(());
-            } else {
-                // Normal mode: spawn platform sync manager as background task
-                let shutdown_rx_clone = platform_shutdown_rx.clone();
-                platform_sync_handle = Some(tokio::spawn(async move {
-                    sync_manager.run(shutdown_rx_clone).await;
-                }));
-
-                tracing::info!(
-                    "Platform sync manager started (sync interval: {} hours)",
-                    config.platforms.sync_interval_hours
-                );
-            }
-        } else if cli.export_scope {
-            // No platforms available but export-scope requested
-            tracing::info!("No platforms available. Exporting config-only scope...");
-            let watchlist_guard = watchlist.lock().await;
-            let toml_output = watchlist_guard.export_to_toml();
-            println!("{}", toml_output);
-            tracing::info!("Export complete. Exiting.");
-            return Ok(());
         }
-    } else if cli.export_scope {
-        // Platforms not enabled, export config-only scope
-        tracing::info!("Exporting current scope to TOML format...");
-        let watchlist_guard = watchlist.lock().await;
-        let toml_output = watchlist_guard.export_to_toml();
-        println!("{}", toml_output);
-        tracing::info!("Export complete. Exiting.");
-        return Ok(());
     }
 
     // Create dedupe
     // Precedence: CLI flags override config
-    let dedupe_enabled = if cli.no_dedupe {
+    let dedupe_enabled = if args.no_dedupe {
         false
-    } else if cli.dedupe {
+    } else if args.dedupe {
         true
     } else {
         config.ct_logs.dedupe
     };
 
+    // log_ids from the log lists, so state, health and dedupe survive URL changes
+    let log_ids = LogIds::new();
+
     let dedupe = if dedupe_enabled {
-        Dedupe::new()
+        Dedupe::new().with_log_ids(log_ids.clone())
     } else {
         tracing::info!("Deduplication disabled");
         Dedupe::new() // Still create it but won't use it effectively
@@ -292,10 +315,10 @@ async fn main() -> anyhow::Result<()> {
     let stats = StatsCollector::new();
 
     // Create progress indicator
-    let progress = ProgressIndicator::new(cli.should_show_progress());
+    let progress = ProgressIndicator::new(args.should_show_progress());
 
     // Load root domain filter if specified
-    let root_filter = if let Some(ref path) = cli.root_domains {
+    let root_filter = if let Some(ref path) = args.root_domains {
         let filter = RootDomainFilter::from_file(Path::new(path))?;
         tracing::info!("Loaded root domain filter: {} domains", filter.count());
         Some(filter)
@@ -304,51 +327,83 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Create output manager
-    let mut output_manager = OutputManager::new();
+    let mut output_manager = OutputManager::new().with_routes(Router::from_config(&config.routing)?);
+    if !config.routing.is_empty() {
+        tracing::info!("Loaded {} output routing rules", config.routing.len());
+    }
+    if config.output.queue_size > 0 {
+        output_manager = output_manager.with_queues(config.output.queue_size, config.output.workers);
+        if let Some(dir) = &config.output.spool_dir {
+            output_manager = output_manager.with_spool(dir);
+        }
+    }
+
+    // The dashboard shows matches itself; other formats then only go to --output
+    let dashboard = args.tui.then(|| Dashboard::new(stats.clone()));
+    if let Some(ref dashboard) = dashboard {
+        output_manager.add_handler(dashboard.output());
+    }
 
     // Add output handlers based on format
-    match cli.output_format() {
-        OutputFormat::Human => {
-            if let Some(ref path) = cli.output {
-                let file = std::fs::File::create(path)?;
-                output_manager.add_handler(Arc::new(human::HumanOutput::to_file(file)));
-                tracing::info!("Writing human-readable output to: {}", path);
-            } else {
-                output_manager.add_handler(Arc::new(human::HumanOutput::new()));
+    if dashboard.is_none() || args.output.is_some() {
+        let flush_interval = Duration::from_millis(config.output.flush_interval_ms);
+        let timestamps = TimestampFormat::from_config(
+            config.output.timestamp_format.as_deref(),
+            &config.output.timezone,
+        )?;
+        match args.output_format() {
+            OutputFormat::Human => {
+                if let Some(ref path) = args.output {
+                    let file = std::fs::File::create(path)?;
+                    output_manager.add_handler(Arc::new(human::HumanOutput::to_file(file, flush_interval).with_detail(args.detail).with_timestamps(timestamps)));
+                    tracing::info!("Writing human-readable output to: {}", path);
+                } else {
+                    output_manager.add_handler(Arc::new(human::HumanOutput::new().with_detail(args.detail).with_timestamps(timestamps)));
+                }
             }
-        }
-        OutputFormat::Json => {
-            if let Some(ref path) = cli.output {
-                let file = std::fs::File::create(path)?;
-                output_manager.add_handler(Arc::new(json::JsonOutput::to_file(file)));
-                tracing::info!("Writing JSON output to: {}", path);
-            } else {
-                output_manager.add_handler(Arc::new(json::JsonOutput::new()));
+            OutputFormat::Json => {
+                if let Some(ref path) = args.output {
+                    let file = std::fs::File::create(path)?;
+                    output_manager.add_handler(Arc::new(json::JsonOutput::to_file(file, flush_interval).with_timestamps(timestamps)));
+                    tracing::info!("Writing JSON output to: {}", path);
+                } else {
+                    output_manager.add_handler(Arc::new(json::JsonOutput::new().with_timestamps(timestamps)));
+                }
             }
-        }
-        OutputFormat::Csv => {
-            if let Some(ref path) = cli.output {
-                let file = std::fs::File::create(path)?;
-                output_manager.add_handler(Arc::new(csv::CsvOutput::to_file(file)));
-                tracing::info!("Writing CSV output to: {}", path);
-            } else {
-                output_manager.add_handler(Arc::new(csv::CsvOutput::new()));
+            OutputFormat::Csv => {
+                if let Some(ref path) = args.output {
+                    let file = std::fs::File::create(path)?;
+                    output_manager.add_handler(Arc::new(csv::CsvOutput::to_file(file, flush_interval).with_timestamps(timestamps)));
+                    tracing::info!("Writing CSV output to: {}", path);
+                } else {
+                    output_manager.add_handler(Arc::new(csv::CsvOutput::new().with_timestamps(timestamps)));
+                }
+            }
+            OutputFormat::Pipe => {
+                if let Some(ref path) = args.output {
+                    let file = std::fs::File::create(path)?;
+                    output_manager.add_handler(Arc::new(pipe::PipeOutput::to_file(file, flush_interval)));
+                    tracing::info!("Writing hostnames to: {}", path);
+                } else {
+                    output_manager.add_handler(Arc::new(pipe::PipeOutput::new()));
+                }
+            }
+            OutputFormat::Silent => {
+                output_manager.add_handler(Arc::new(silent::SilentOutput));
+                tracing::info!("Silent mode: no stdout output");
             }
-        }
-        OutputFormat::Silent => {
-            output_manager.add_handler(Arc::new(silent::SilentOutput));
-            tracing::info!("Silent mode: no stdout output");
         }
     }
 
     // Add webhook handler if configured and not disabled
-    if !cli.no_webhook {
-        if let Some(ref webhook_config) = config.webhook {
-            output_manager.add_handler(Arc::new(webhook::WebhookOutput::new(
-                webhook_config.clone(),
-            )));
+    if !args.no_webhook {
+        for webhook_config in config.all_webhooks() {
+            output_manager.add_handler(Arc::new(
+                webhook::WebhookOutput::new(webhook_config.clone())?.with_stats(stats.clone()),
+            ));
             tracing::info!("Webhook enabled: {}", webhook_config.url);
-        } else {
+        }
+        if config.all_webhooks().next().is_none() {
             tracing::debug!("No webhook configured");
         }
     } else {
@@ -360,18 +415,27 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Initializing Redis publisher...");
         let redis_config = redis_publisher::RedisConfig {
             url: config.redis.url.clone(),
+            mode: config.redis.mode.parse()?,
+            nodes: config.redis.nodes.clone(),
+            sentinel_master: config.redis.sentinel_master.clone(),
+            tls: config.redis.tls,
+            tls_insecure: config.redis.tls_insecure,
             token: config.redis.token.clone(),
             channel: config.redis.channel.clone(),
             queue_name: config.redis.queue_name.clone(),
             max_queue_size: config.redis.max_queue_size,
+            publish_channel: config.redis.publish_channel,
+            stream: config.redis.stream.clone(),
+            stream_maxlen: (config.redis.stream_maxlen > 0).then_some(config.redis.stream_maxlen),
+            stream_group: config.redis.stream_group.clone(),
         };
 
         let redis_pub = Arc::new(redis_publisher::RedisPublisher::new(redis_config));
 
         // Determine strict mode (CLI > Config > Default)
-        let redis_required = if cli.require_redis {
+        let redis_required = if args.require_redis {
             true
-        } else if cli.no_require_redis {
+        } else if args.no_require_redis {
             false
         } else {
             config.redis.require
@@ -384,6 +448,9 @@ async fn main() -> anyhow::Result<()> {
                     redis_pub.clone(),
                 )));
                 tracing::info!("Redis publisher enabled: channel={}", config.redis.channel);
+                if let Some(ref stream) = config.redis.stream {
+                    tracing::info!("Adding matches to Redis stream {}", stream);
+                }
             }
             Err(e) => {
                 if redis_required {
@@ -414,7 +481,7 @@ async fn main() -> anyhow::Result<()> {
                 tracing::warn!("Continuing without Redis publishing (use --require-redis to make this fatal)");
             }
         }
-    } else if cli.require_redis {
+    } else if args.require_redis {
         // Redis is required via CLI but not enabled in config
         anyhow::bail!(
             "Redis connection required (--require-redis) but Redis is not enabled in config.\n\
@@ -428,24 +495,34 @@ async fn main() -> anyhow::Result<()> {
         tracing::debug!("Redis publishing disabled");
     }
 
+    // Matches for the REST API's /stream and gRPC WatchMatches clients
+    let match_stream = Arc::new(MatchStream::new());
+    if config.api.is_some() || config.grpc.is_some() {
+        output_manager.add_handler(match_stream.clone());
+    }
+
     // Start stats display background task if requested
     // Precedence: CLI flags override config
-    let stats_enabled = if cli.no_stats {
+    let stats_enabled = if args.no_stats {
         false
-    } else if cli.stats {
+    } else if args.stats {
         true
     } else {
         config.stats.enabled
     };
 
-    let stats_interval = if cli.stats_interval != 10 {
+    let stats_interval = if args.stats_interval != 10 {
         // CLI provided non-default value
-        cli.stats_interval
+        args.stats_interval
     } else {
         config.stats.interval_secs
     };
 
-    if stats_enabled {
+    let stats_file = args.stats_file.clone().or_else(|| config.stats.export_path.clone());
+
+    // The dashboard already shows live stats
+    let show_stats = stats_enabled && dashboard.is_none();
+    if show_stats || stats_file.is_some() {
         let stats_clone = stats.clone();
         let progress_clone = progress.clone();
         let interval = stats_interval;
@@ -453,6 +530,16 @@ async fn main() -> anyhow::Result<()> {
         tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(interval)).await;
+
+                if let Some(ref path) = stats_file
+                    && let Err(e) = stats_clone.write_json(Path::new(path)).await
+                {
+                    tracing::warn!("Failed to export stats: {:#}", e);
+                }
+                if !show_stats {
+                    continue;
+                }
+
                 let msg = stats_clone.format_stats();
 
                 // If progress indicator is enabled, use it; otherwise print directly to stderr
@@ -489,6 +576,24 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
+    // Clustered instances share the database state, split by log leases
+    let clustered = config.cluster.enabled && matches!(input, Input::Logs);
+    if clustered && config.ct_logs.state_backend != "database" {
+        anyhow::bail!("[cluster] needs state_backend = 'database' so instances share log positions");
+    }
+    if clustered && config.certstream.is_some() {
+        anyhow::bail!("[certstream] can't be used with [cluster]; every instance would report the same certificates");
+    }
+    if cfg!(not(feature = "kafka")) && config.kafka.is_some() && matches!(input, Input::Logs) {
+        anyhow::bail!("[kafka] needs a ct-scout built with `--features kafka`");
+    }
+
+    // Only following the logs advances saved indices; scans and replays leave them alone
+    let _state_lock = match input {
+        Input::Logs if !clustered => Some(commands::lock_state(&config, &config.ct_logs.state_backend).await?),
+        Input::Logs | Input::Scan(_) | Input::Replay(_) => None,
+    };
+
     // Create state manager based on configuration
     let state_manager: Arc<dyn ct_scout::state::StateBackend> = match config.ct_logs.state_backend.as_str() {
         "database" => {
@@ -508,62 +613,293 @@ async fn main() -> anyhow::Result<()> {
             let file_state = StateManager::new(PathBuf::from(&config.ct_logs.state_file)).await?;
             Arc::new(file_state)
         }
+        "sqlite" => Arc::new(SqliteStateManager::new(Path::new(&config.ct_logs.state_db)).await?),
         other => {
             anyhow::bail!(
-                "Invalid state_backend '{}'. Must be 'file' or 'database'",
+                "Invalid state_backend '{}'. Must be 'file', 'database' or 'sqlite'",
                 other
             );
         }
     };
+    let state_manager: Arc<dyn ct_scout::state::StateBackend> = Arc::new(KeyedState::new(state_manager, log_ids.clone()));
     tracing::info!("State manager initialized");
 
+    let http_config = commands::http_config(&config.ct_logs);
+
     // Fetch log URLs
-    let log_urls = if let Some(ref custom) = config.ct_logs.custom_logs {
+    let log_check: LogCheckPolicy = config.ct_logs.check_configured_logs.parse()?;
+    let (log_urls, log_list_source) = if let Input::Scan(ref scan) = input {
+        // One-shot scan: only the requested log, no list fetch or refresh
+        tracing::info!("Scan mode: {}", scan.log);
+        (Vec::new(), None)
+    } else if let Input::Replay(_) = input {
+        // Offline: certificates come from the replay input only
+        tracing::info!("Replay mode");
+        (Vec::new(), None)
+    } else if (config.certstream.is_some() || config.kafka.is_some())
+        && config.certstream.as_ref().is_none_or(|certstream| !certstream.poll_logs)
+        && config.kafka.as_ref().is_none_or(|kafka| !kafka.poll_logs)
+    {
+        // Stream inputs only: they follow the logs for us
+        tracing::info!("Not polling CT logs directly (poll_logs = false)");
+        (Vec::new(), None)
+    } else if let Some(ref custom) = config.ct_logs.custom_logs {
         // Backward compatibility: custom_logs replaces Google's list
+        let custom = check_logs(&http_config, "custom_logs", custom, log_check).await?;
         tracing::info!("Using {} custom CT logs (replacing Google's list)", custom.len());
-        custom.clone()
+        (custom, None)
     } else {
-        let fetcher = LogListFetcher::new();
-
         // Fetch logs from Google's list, optionally merging with additional_logs
-        let logs = if let Some(ref additional) = config.ct_logs.additional_logs {
-            // Merge additional logs with Google's list
-            fetcher.fetch_logs_with_additional(
-                &config.ct_logs.log_list_url,
-                config.ct_logs.include_readonly_logs,
-                config.ct_logs.include_pending,
-                config.ct_logs.include_all_logs,
-                additional
-            ).await?
-        } else {
-            // Just fetch from Google's list
-            fetcher.fetch_usable_logs(
-                &config.ct_logs.log_list_url,
-                config.ct_logs.include_readonly_logs,
-                config.ct_logs.include_pending,
-                config.ct_logs.include_all_logs
-            ).await?
-        };
-
-        tracing::info!("Fetched {} CT logs from list", logs.len());
+        let mut source = commands::log_list_source(&config.ct_logs)?;
+        source.log_ids = log_ids.clone();
+        if let Some(ref additional) = source.additional_logs {
+            source.additional_logs = Some(check_logs(&http_config, "additional_logs", additional, log_check).await?);
+        }
 
-        // Limit to max_concurrent_logs
-        let limited_logs: Vec<String> = logs.into_iter()
-            .take(config.ct_logs.max_concurrent_logs)
-            .collect();
-        tracing::info!("Monitoring {} CT logs (limited by max_concurrent_logs)", limited_logs.len());
-        limited_logs
+        let logs = source.fetch().await?;
+        tracing::info!("Monitoring {} CT logs", logs.len());
+        (logs, Some(source))
     };
 
     // Create coordinator
-    let coordinator = CtLogCoordinator::new(
-        log_urls,
+    let monitor_config = LogMonitorConfig {
+        poll_interval_secs: config.ct_logs.poll_interval_secs,
+        batch_size: config.ct_logs.batch_size,
+        parse_precerts: config.ct_logs.parse_precerts,
+        parallel_fetches: config.ct_logs.parallel_fetches,
+        rate_limit: RateLimitConfig {
+            requests_per_sec: config.ct_logs.rate_limit_per_sec,
+            burst: config.ct_logs.rate_limit_burst,
+            per_log: config.ct_logs.log_rate_limits.clone(),
+        },
+        max_concurrent_requests: config.ct_logs.max_concurrent_requests,
+        audit: config.ct_logs.audit_enabled.then_some(AuditConfig {
+            interval_secs: config.ct_logs.audit_interval_secs,
+            inclusion_samples: config.ct_logs.audit_inclusion_samples,
+        }),
+        quarantine: (config.ct_logs.quarantine_after_hours > 0).then_some(QuarantineConfig {
+            after_secs: config.ct_logs.quarantine_after_hours * 3600,
+            probe_interval_secs: config.ct_logs.quarantine_probe_interval_secs,
+        }),
+        http: http_config,
+        retry: commands::retry_config(&config.ct_logs),
+        channel: ChannelConfig {
+            capacity: config.ct_logs.channel_capacity,
+            policy: config.ct_logs.channel_full_policy.parse()?,
+            spill_path: PathBuf::from(&config.ct_logs.channel_spill_file),
+        },
+        adaptive_poll: config.ct_logs.adaptive_poll.then_some(AdaptivePollConfig {
+            min_interval_secs: config.ct_logs.min_poll_interval_secs,
+            max_interval_secs: config.ct_logs.max_poll_interval_secs,
+        }),
+        include_pem: args.include_pem || config.ct_logs.include_pem,
+        since_ms: args.since.map(|since| since.timestamp_millis().max(0) as u64),
+        stale_after_secs: (config.ct_logs.state_age_warn_hours > 0)
+            .then_some(config.ct_logs.state_age_warn_hours * 3600),
+        commit_batch: config.ct_logs.commit_batch,
+        log_ids,
+    };
+
+    // Clustered monitors start as log leases are granted
+    let mut coordinator = CtLogCoordinator::new(
+        if clustered { Vec::new() } else { log_urls.clone() },
         state_manager.clone(),
-        config.ct_logs.poll_interval_secs,
-        config.ct_logs.batch_size,
-        config.ct_logs.parse_precerts,
-        db,
+        monitor_config,
+        db.clone(),
+        stats.clone(),
     );
+    if clustered {
+        let instance_id = config.cluster.instance_id.clone().unwrap_or_else(|| {
+            format!("ct-scout-{}-{:08x}", std::process::id(), rand::random::<u32>())
+        });
+        let leases = LogLeases::connect(
+            &config.database.url,
+            instance_id,
+            Duration::from_secs(config.cluster.lease_secs.max(3)),
+        ).await?;
+        coordinator = coordinator.with_cluster(leases, log_urls);
+    }
+
+    if let Some(addr) = config.metrics.listen {
+        let listener = server::bind(addr).await?;
+        tokio::spawn(server::serve(
+            listener,
+            Readiness {
+                health: coordinator.health_tracker(),
+                db: db.clone(),
+                channel_capacity: config.ct_logs.channel_capacity,
+            },
+        ));
+    }
+
+    if let Some(ref api) = config.api {
+        let listener = ct_scout::api::bind(api.listen).await?;
+        tokio::spawn(ct_scout::api::serve(
+            listener,
+            ApiState {
+                db: db.clone(),
+                stats: stats.clone(),
+                watchlist: watchlist.clone(),
+                stream: match_stream.clone(),
+                health: coordinator.health_tracker(),
+                state: state_manager.clone(),
+                platform_sync,
+                token: Arc::from(api.token.as_str()),
+            },
+        ));
+    }
+
+    if let Some(ref grpc) = config.grpc {
+        let listener = ct_scout::grpc::bind(grpc.listen).await?;
+        tokio::spawn(ct_scout::grpc::serve(
+            listener,
+            GrpcState {
+                db,
+                stats: stats.clone(),
+                stream: match_stream,
+                token: Arc::from(grpc.token.as_str()),
+            },
+        ));
+    }
+
+    match input {
+        Input::Logs => {
+            if let Some(ref certstream) = config.certstream {
+                tracing::info!("Following certstream server {}", certstream.url);
+                coordinator.add_source(Box::new(CertstreamSource(certstream.clone())))?;
+            }
+            #[cfg(feature = "kafka")]
+            if let Some(ref kafka) = config.kafka {
+                tracing::info!("Following Kafka topic {} on {}", kafka.topic, kafka.brokers);
+                coordinator.add_source(Box::new(ct_scout::kafka::KafkaSource::new(kafka)?))?;
+            }
+        }
+        Input::Scan(scan) => coordinator.spawn_scan(scan.log, scan.start, scan.end)?,
+        Input::Replay(replay) => {
+            let reader: Box<dyn tokio::io::AsyncBufRead + Unpin + Send> = match replay.input.as_deref() {
+                None | Some("-") => Box::new(tokio::io::BufReader::new(tokio::io::stdin())),
+                Some(path) => {
+                    let file = tokio::fs::File::open(path)
+                        .await
+                        .with_context(|| format!("Failed to open replay input {}", path))?;
+                    Box::new(tokio::io::BufReader::new(file))
+                }
+            };
+            let name = replay.input.unwrap_or_else(|| "-".to_string());
+            tracing::info!("Replaying certificates from {}", if name == "-" { "stdin" } else { &name });
+            coordinator.add_source(Box::new(ReplaySource::new(name, reader)))?;
+        }
+    }
+
+    if let Some(source) = log_list_source
+        && config.ct_logs.log_list_refresh_secs > 0
+    {
+        coordinator = coordinator.with_log_list_refresh(
+            source,
+            std::time::Duration::from_secs(config.ct_logs.log_list_refresh_secs),
+        );
+    }
+
+    let enrich_timeout = std::time::Duration::from_secs(config.enrichment.timeout_secs);
+    let mut enricher = Enricher::new(EnrichConfig {
+        dns: config.enrichment.dns,
+        http_probe: config.enrichment.http_probe,
+        takeover_check: config.enrichment.takeover_check,
+        nameserver: config.enrichment.nameserver,
+        rdap: config.enrichment.rdap,
+        rdap_url: config.enrichment.rdap_url.clone(),
+        new_domain_days: config.enrichment.new_domain_days,
+        timeout: enrich_timeout,
+        cache_ttl: std::time::Duration::from_secs(config.enrichment.cache_ttl_secs),
+        cache_size: config.enrichment.cache_size,
+    })?;
+    if let Some(ref intel) = config.enrichment.intel {
+        let provider = IntelProvider::from_config(
+            &intel.provider,
+            &intel.api_key,
+            intel.api_secret.as_deref(),
+        )?;
+        tracing::info!("Intel enrichment enabled ({})", intel.provider);
+        enricher = enricher.with_intel(IntelClient::new(provider, intel.requests_per_sec, enrich_timeout)?);
+    }
+    coordinator = coordinator.with_enricher(enricher, config.enrichment.queue_size, config.enrichment.workers);
+    if config.output.throttle_secs > 0 {
+        tracing::info!("Notifying each matched domain at most once per {}s", config.output.throttle_secs);
+        coordinator = coordinator.with_throttle(Duration::from_secs(config.output.throttle_secs));
+    }
+    if let Some(ref anomaly) = config.anomaly {
+        let Some(webhook) = config.all_webhooks().next() else {
+            anyhow::bail!("[anomaly] sends its alerts to [webhook], which is not configured");
+        };
+        tracing::info!(
+            "Alerting on match rate spikes over {}x the {}h baseline",
+            anomaly.spike_multiple, anomaly.baseline_hours
+        );
+        coordinator = coordinator.with_anomaly_alerts(AnomalyAlerter::new(anomaly, Notifier::new(webhook.clone())));
+    }
+
+    if let Some(ref path) = args.new_roots {
+        let filter = NewRootFilter::open(Path::new(path))?;
+        tracing::info!("Emitting only new root domains ({} already seen)", filter.count());
+        coordinator = coordinator.with_new_roots(filter);
+    }
+
+    if config.output.short_lived_days > 0 {
+        let max_validity = Duration::from_secs(config.output.short_lived_days * 86400);
+        coordinator = coordinator.with_short_lived(max_validity, args.short_lived_only);
+        if args.short_lived_only {
+            tracing::info!("Emitting only certificates valid for under {} days", config.output.short_lived_days);
+        }
+    } else if args.short_lived_only {
+        anyhow::bail!("--short-lived-only needs [output] short_lived_days above 0");
+    }
+
+    // --run-for / --max-matches: stop cleanly once either is reached
+    let stop_conditions = args.run_for.is_some() || args.max_matches.is_some();
+    if stop_conditions {
+        let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+        coordinator = coordinator.with_stop_signal(stop_rx);
+        tokio::spawn(watch_stop_conditions(args.run_for, args.max_matches, stats.clone(), stop_tx));
+    }
+
+    // Monitors are running; let systemd know when started as a Type=notify unit
+    let systemd = SystemdNotifier::from_env().map(Arc::new);
+    let keepalive = systemd.as_ref().map(|notifier| {
+        notifier.ready("Monitoring CT logs");
+        Arc::clone(notifier).spawn_keepalive(stats.clone())
+    });
+
+    let heartbeat = match config.heartbeat {
+        Some(ref heartbeat) => {
+            tracing::info!("Sending heartbeats every {}s", heartbeat.interval_secs);
+            Some(Heartbeat::new(heartbeat)?.spawn(stats.clone(), coordinator.health_tracker()))
+        }
+        None => None,
+    };
+
+    let pushgateway = match config.metrics.pushgateway {
+        Some(ref pushgateway) if config.metrics.enabled => {
+            tracing::info!("Pushing metrics to {} every {}s", pushgateway.url, pushgateway.interval_secs);
+            let pusher = Pushgateway::new(pushgateway)?;
+            let handle = pusher.spawn();
+            Some((pusher, handle))
+        }
+        Some(_) => {
+            tracing::warn!("[metrics.pushgateway] is ignored while [metrics] enabled = false");
+            None
+        }
+        None => None,
+    };
+
+    // Prefixes of watched AS numbers, refreshed in the background
+    let asn_refresh = if watchlist.lock().await.all_asns().next().is_some() {
+        tracing::info!("Refreshing announced prefixes of watched ASNs every {}h", config.asn.refresh_hours);
+        Some(AsnRefresher::new(&config.asn)?.spawn(watchlist.clone()))
+    } else {
+        None
+    };
+
+    let dashboard_task = dashboard.map(|dashboard| dashboard.spawn(coordinator.health_tracker()));
 
     // Run monitoring
     tracing::info!("Starting CT log monitoring...");
@@ -571,11 +907,28 @@ async fn main() -> anyhow::Result<()> {
         watchlist,
         output_manager,
         dedupe,
-        stats.clone(),
         progress.clone(),
         root_filter,
     ).await;
 
+    if let Some(ref notifier) = systemd {
+        notifier.stopping();
+    }
+    if let Some(handle) = keepalive {
+        handle.abort();
+    }
+    if let Some(handle) = heartbeat {
+        handle.abort();
+    }
+    if let Some(handle) = asn_refresh {
+        handle.abort();
+    }
+    // Give the terminal back before printing anything else
+    if let Some(handle) = dashboard_task {
+        handle.abort();
+        handle.await.ok();
+    }
+
     // Shutdown platform sync manager if it was running
     if let Some(handle) = platform_sync_handle {
         tracing::info!("Shutting down platform sync manager...");
@@ -587,15 +940,79 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Saving final state...");
     state_manager.save().await?;
 
-    // Print final stats if enabled
-    if stats_enabled {
-        let snapshot = stats.snapshot();
-        println!("\n\n📊 Final Statistics:");
-        println!("  Total processed: {}", snapshot.total_processed);
-        println!("  Matches found: {}", snapshot.matches_found);
-        println!("  Rate: {:.1} msg/min", snapshot.messages_per_minute);
-        println!("  Uptime: {}", StatsCollector::format_uptime(snapshot.uptime_secs));
+    // Final push, so the gateway keeps the totals of the whole run
+    if let Some((pusher, handle)) = pushgateway {
+        handle.abort();
+        if let Err(e) = pusher.push().await {
+            tracing::warn!("Final metrics push failed: {:#}", e);
+        }
+    }
+
+    // Print final stats if enabled (always for runs with a stop condition)
+    if stats_enabled || stop_conditions {
+        // --pipe keeps stdout for hostnames only
+        if args.pipe {
+            print_final_stats(&mut std::io::stderr(), &stats)?;
+        } else {
+            print_final_stats(&mut std::io::stdout(), &stats)?;
+        }
     }
 
     Ok(())
 }
+
+fn print_final_stats(out: &mut dyn Write, stats: &StatsCollector) -> std::io::Result<()> {
+    let snapshot = stats.snapshot();
+    writeln!(out, "\n\n📊 Final Statistics:")?;
+    writeln!(out, "  Total processed: {}", snapshot.total_processed)?;
+    writeln!(out, "  Matches found: {}", snapshot.matches_found)?;
+    writeln!(out, "  Rate: {:.1} msg/min", snapshot.messages_per_minute)?;
+    writeln!(out, "  Uptime: {}", StatsCollector::format_uptime(snapshot.uptime_secs))?;
+
+    let programs = stats.top_programs(10);
+    if !programs.is_empty() {
+        writeln!(out, "  Top programs:")?;
+        for (name, count) in programs {
+            writeln!(out, "    {:<30} {} matches", name, count)?;
+        }
+    }
+    let logs = stats.top_logs(10);
+    if !logs.is_empty() {
+        writeln!(out, "  Top logs:")?;
+        for (url, counts) in logs {
+            writeln!(
+                out,
+                "    {:<50} {} entries, {} errors",
+                StatsCollector::short_log_name(&url),
+                counts.entries,
+                counts.errors
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Signal the coordinator to stop once `run_for` has elapsed or `max_matches` were found
+async fn watch_stop_conditions(
+    run_for: Option<Duration>,
+    max_matches: Option<u64>,
+    stats: StatsCollector,
+    stop_tx: tokio::sync::watch::Sender<bool>,
+) {
+    let deadline = run_for.map(|run_for| tokio::time::Instant::now() + run_for);
+    let mut ticker = tokio::time::interval(Duration::from_millis(500));
+    loop {
+        ticker.tick().await;
+        if let Some(max) = max_matches
+            && stats.snapshot().matches_found >= max
+        {
+            tracing::info!("Found {} matches (--max-matches), stopping", max);
+            break;
+        }
+        if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+            tracing::info!("Ran for {:?} (--run-for), stopping", run_for.unwrap_or_default());
+            break;
+        }
+    }
+    stop_tx.send(true).ok();
+}
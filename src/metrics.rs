@@ -1,11 +1,11 @@
 //! Prometheus metrics for ct-scout
 //!
-//! Provides observability into Redis publishing operations,
-//! connection health, and overall system performance.
+//! Provides observability into CT log fetching, the certificate pipeline,
+//! output handlers, Redis publishing and overall system performance.
 
 use lazy_static::lazy_static;
 use prometheus::{
-    Gauge, HistogramVec, IntCounter, IntCounterVec, Opts, Registry,
+    Gauge, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
 };
 use tracing::warn;
 
@@ -60,11 +60,196 @@ lazy_static! {
         "Total number of certificates processed"
     ).expect("metric cannot be created");
 
+    /// Total CT log Merkle proof verification failures
+    /// Labels: log=<log url>, check="consistency|inclusion"
+    pub static ref CT_AUDIT_FAILURES_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_ct_audit_failures_total",
+            "Total number of CT log proof verification failures"
+        ),
+        &["log", "check"]
+    ).expect("metric cannot be created");
+
+    /// Certificates waiting in the coordinator channel
+    pub static ref CERT_CHANNEL_DEPTH: IntGauge = IntGauge::new(
+        "ctscout_cert_channel_depth",
+        "Number of certificates queued between monitors and the coordinator"
+    ).expect("metric cannot be created");
+
+    /// Certificates spilled to disk and waiting to be processed
+    pub static ref CERT_CHANNEL_SPILLED: IntGauge = IntGauge::new(
+        "ctscout_cert_channel_spilled",
+        "Number of certificates waiting in the spill file"
+    ).expect("metric cannot be created");
+
+    /// Total sends that found the coordinator channel full
+    /// Labels: action="blocked|dropped|spilled"
+    pub static ref CERT_CHANNEL_SATURATED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_cert_channel_saturated_total",
+            "Total number of certificates sent while the coordinator channel was full"
+        ),
+        &["action"]
+    ).expect("metric cannot be created");
+
     /// Total certificate matches found
     pub static ref MATCHES_FOUND_TOTAL: IntCounter = IntCounter::new(
         "ctscout_matches_found_total",
         "Total number of certificate matches found"
     ).expect("metric cannot be created");
+
+    /// Certificates dropped as duplicates before matching
+    pub static ref DEDUPE_SUPPRESSED_TOTAL: IntCounter = IntCounter::new(
+        "ctscout_dedupe_suppressed_total",
+        "Total number of certificates skipped as duplicates"
+    ).expect("metric cannot be created");
+
+    /// Matches not sent to outputs because their domain was notified recently
+    pub static ref NOTIFY_THROTTLED_TOTAL: IntCounter = IntCounter::new(
+        "ctscout_notify_throttled_total",
+        "Total number of matches whose notification was suppressed by the per-domain throttle"
+    ).expect("metric cannot be created");
+
+    /// Matches emitted without enrichment because the enrichment queue was full
+    pub static ref ENRICHMENT_SKIPPED_TOTAL: IntCounter = IntCounter::new(
+        "ctscout_enrichment_skipped_total",
+        "Total number of matches emitted unenriched because the enrichment workers were behind"
+    ).expect("metric cannot be created");
+
+    /// Alerts raised because a program's hourly match rate jumped past its baseline
+    /// Labels: program=<program name>
+    pub static ref MATCH_RATE_SPIKES_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_match_rate_spikes_total",
+            "Total number of match rate spike alerts per program"
+        ),
+        &["program"]
+    ).expect("metric cannot be created");
+
+    /// Matches whose certificate came from a CA the program doesn't expect
+    /// Labels: program=<program name>
+    pub static ref UNEXPECTED_ISSUER_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_unexpected_issuer_total",
+            "Total number of matches issued by a CA outside the program's expected_issuers"
+        ),
+        &["program"]
+    ).expect("metric cannot be created");
+
+    /// Time from a log entry's timestamp until its match was emitted
+    /// Labels: log=<log url>
+    pub static ref MATCH_LATENCY: HistogramVec = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "ctscout_match_latency_seconds",
+            "Seconds between the CT log entry's timestamp and the match being emitted"
+        )
+        .buckets(vec![
+            1.0, 5.0, 15.0, 30.0, 60.0,  // 1s to 1min
+            120.0, 300.0, 900.0, 1800.0, 3600.0,  // 2min to 1h
+            21600.0, 86400.0  // 6h, 24h (backfills)
+        ]),
+        &["log"]
+    ).expect("metric cannot be created");
+
+    // ===== CT Log Metrics =====
+
+    /// Total log entries fetched
+    /// Labels: log=<log url>
+    pub static ref CT_ENTRIES_FETCHED_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_ct_entries_fetched_total",
+            "Total number of entries fetched from CT logs"
+        ),
+        &["log"]
+    ).expect("metric cannot be created");
+
+    /// Total entries whose certificate could not be parsed
+    /// Labels: log=<log url>
+    pub static ref CT_PARSE_ERRORS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_ct_parse_errors_total",
+            "Total number of CT log entries that failed to parse"
+        ),
+        &["log"]
+    ).expect("metric cannot be created");
+
+    /// Total failed requests to CT logs
+    /// Labels: log=<log url>, status=<HTTP status code>|network
+    pub static ref CT_HTTP_ERRORS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_ct_http_errors_total",
+            "Total number of failed HTTP requests to CT logs"
+        ),
+        &["log", "status"]
+    ).expect("metric cannot be created");
+
+    /// Entries in the log not yet fetched, as of the last poll
+    /// Labels: log=<log url>
+    pub static ref CT_LOG_LAG: IntGaugeVec = IntGaugeVec::new(
+        Opts::new(
+            "ctscout_ct_log_lag_entries",
+            "Entries between the saved index and the log's tree size"
+        ),
+        &["log"]
+    ).expect("metric cannot be created");
+
+    // ===== Output Metrics =====
+
+    /// Total matches handed to each output handler
+    /// Labels: handler="human|json|csv|silent|webhook|redis", status="success|failure|dropped|spooled"
+    /// ("dropped"/"spooled" = the handler's queue was full)
+    pub static ref OUTPUT_EMIT_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_output_emit_total",
+            "Total number of matches emitted per output handler"
+        ),
+        &["handler", "status"]
+    ).expect("metric cannot be created");
+
+    /// Matches spooled to disk and waiting to be emitted
+    /// Labels: handler
+    pub static ref OUTPUT_SPOOLED: IntGaugeVec = IntGaugeVec::new(
+        Opts::new(
+            "ctscout_output_spooled",
+            "Number of matches waiting in an output's spool file"
+        ),
+        &["handler"]
+    ).expect("metric cannot be created");
+
+    /// Time each output handler takes to emit a match
+    /// Labels: handler
+    pub static ref OUTPUT_EMIT_DURATION: HistogramVec = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "ctscout_output_emit_duration_seconds",
+            "Duration of output handler emits in seconds"
+        )
+        .buckets(vec![
+            0.0001, 0.001, 0.005, 0.010, 0.050,  // 100µs to 50ms
+            0.100, 0.250, 0.500, 1.0, 2.5, 5.0, 10.0  // 100ms to 10s (webhooks)
+        ]),
+        &["handler"]
+    ).expect("metric cannot be created");
+
+    /// Webhook deliveries per endpoint
+    /// Labels: endpoint=<host[:port]>, status="success|failure"
+    pub static ref WEBHOOK_DELIVERIES_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "ctscout_webhook_deliveries_total",
+            "Total number of webhook deliveries per endpoint"
+        ),
+        &["endpoint", "status"]
+    ).expect("metric cannot be created");
+
+    /// Time until a webhook endpoint accepted a delivery
+    /// Labels: endpoint
+    pub static ref WEBHOOK_DELIVERY_DURATION: HistogramVec = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "ctscout_webhook_delivery_duration_seconds",
+            "Duration of successful webhook deliveries in seconds"
+        )
+        .buckets(vec![0.010, 0.050, 0.100, 0.250, 0.500, 1.0, 2.5, 5.0, 10.0]),
+        &["endpoint"]
+    ).expect("metric cannot be created");
 }
 
 /// Initialize metrics registry
@@ -76,6 +261,25 @@ pub fn init_metrics() -> Result<(), prometheus::Error> {
     REGISTRY.register(Box::new(REDIS_RECONNECT_ATTEMPTS.clone()))?;
     REGISTRY.register(Box::new(CERTIFICATES_PROCESSED_TOTAL.clone()))?;
     REGISTRY.register(Box::new(MATCHES_FOUND_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(CT_AUDIT_FAILURES_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(CERT_CHANNEL_DEPTH.clone()))?;
+    REGISTRY.register(Box::new(CERT_CHANNEL_SPILLED.clone()))?;
+    REGISTRY.register(Box::new(CERT_CHANNEL_SATURATED_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(DEDUPE_SUPPRESSED_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(NOTIFY_THROTTLED_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(ENRICHMENT_SKIPPED_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(MATCH_RATE_SPIKES_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(UNEXPECTED_ISSUER_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(WEBHOOK_DELIVERIES_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(WEBHOOK_DELIVERY_DURATION.clone()))?;
+    REGISTRY.register(Box::new(MATCH_LATENCY.clone()))?;
+    REGISTRY.register(Box::new(CT_ENTRIES_FETCHED_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(CT_PARSE_ERRORS_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(CT_HTTP_ERRORS_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(CT_LOG_LAG.clone()))?;
+    REGISTRY.register(Box::new(OUTPUT_EMIT_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(OUTPUT_EMIT_DURATION.clone()))?;
+    REGISTRY.register(Box::new(OUTPUT_SPOOLED.clone()))?;
 
     Ok(())
 }
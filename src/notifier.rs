@@ -1,12 +1,10 @@
 // src/notifier.rs
+use crate::anomaly::RateSpike;
 use crate::config::WebhookConfig;
+use crate::output::webhook::signature_headers;
 use crate::types::CertData;
-use hmac::{Hmac, Mac};
 use reqwest::Client;
 use serde::Serialize;
-use sha2::Sha256;
-
-type HmacSha256 = Hmac<Sha256>;
 
 #[derive(Clone)]
 pub struct Notifier {
@@ -24,12 +22,21 @@ pub struct NotificationPayload<'a> {
     pub program_name: Option<&'a str>,
 }
 
+#[derive(Serialize)]
+pub struct AlertPayload<'a> {
+    pub alert: &'a str,
+    pub program_name: &'a str,
+    pub matches_this_hour: u64,
+    pub baseline_per_hour: f64,
+}
+
 impl Notifier {
     pub fn new(cfg: WebhookConfig) -> Self {
         let client = Client::new();
         Self { client, cfg }
     }
 
+    #[allow(clippy::option_as_ref_deref)]
     pub async fn notify_match(
         &self,
         domain: &str,
@@ -57,7 +64,23 @@ impl Notifier {
             program_name,
         };
 
-        let body = serde_json::to_vec(&payload)?;
+        self.post(&payload).await
+    }
+
+    /// Report a sudden jump in one program's match rate
+    pub async fn notify_rate_spike(&self, spike: &RateSpike) -> anyhow::Result<()> {
+        let payload = AlertPayload {
+            alert: "match_rate_spike",
+            program_name: &spike.program,
+            matches_this_hour: spike.matches,
+            baseline_per_hour: spike.baseline,
+        };
+
+        self.post(&payload).await
+    }
+
+    async fn post<T: Serialize>(&self, payload: &T) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(payload)?;
 
         let timeout_secs = self.cfg.timeout_secs.unwrap_or(5);
         let mut req = self
@@ -67,14 +90,9 @@ impl Notifier {
             .body(body.clone())
             .header("Content-Type", "application/json");
 
-        // Optional HMAC signature header
-        if let Some(secret) = &self.cfg.secret {
-            let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-                .map_err(|e| anyhow::anyhow!("HMAC init error: {:?}", e))?;
-            mac.update(&body);
-            let sig = mac.finalize().into_bytes();
-            let sig_hex = hex::encode(sig);
-            req = req.header("X-CTScout-Signature", sig_hex);
+        // Optional HMAC signature headers
+        for (name, value) in signature_headers(&self.cfg, &body, chrono::Utc::now().timestamp())? {
+            req = req.header(name, value);
         }
 
         let resp = req.send().await?;
@@ -97,13 +115,27 @@ mod tests {
                 "example.com".to_string(),
                 "www.example.com".to_string(),
             ]),
+            ip_addresses: None,
             cert_index: Some(123456),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: Some(LeafCert {
                 not_before: Some(1600000000),
                 not_after: Some(1700000000),
                 fingerprint: Some("abcdef123456".to_string()),
                 issuer: Some("Test CA".to_string()),
+                serial_number: None,
+                subject_key_id: None,
+                authority_key_id: None,
+                key_algorithm: None,
+                key_bits: None,
+                key_curve: None,
+                signature_algorithm: None,
+                subject: None,
+                issuer_dn: None,
+                issuer_chain: Vec::new(),
+                pki_endpoints: Default::default(),
+                pem: None,
             }),
             is_precert: false,
             ct_log_url: Some("https://ct.example.com/log".to_string()),
@@ -126,6 +158,7 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -157,6 +190,7 @@ mod tests {
             url: mock_server.uri(),
             secret: Some(secret.to_string()),
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -194,6 +228,7 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -219,6 +254,7 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -247,6 +283,7 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(1), // 1 second timeout
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -272,6 +309,7 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let notifier = Notifier::new(config);
@@ -279,8 +317,10 @@ mod tests {
         // Minimal cert data with no leaf cert
         let cert_data = CertData {
             all_domains: Some(vec!["minimal.com".to_string()]),
+            ip_addresses: None,
             cert_index: None,
             seen_unix: None,
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -308,13 +348,68 @@ mod tests {
 
         let body = serde_json::to_vec(&payload).unwrap();
 
-        // Generate HMAC signature the same way the notifier does
+        // Receivers sign `v1:<timestamp>:<body>` to check the header
         let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(b"v1:1700000000:");
         mac.update(&body);
-        let expected_sig = hex::encode(mac.finalize().into_bytes());
+        let expected_sig = format!("v1={}", hex::encode(mac.finalize().into_bytes()));
 
-        // Verify it's a valid hex string
-        assert_eq!(expected_sig.len(), 64); // SHA256 produces 32 bytes = 64 hex chars
-        assert!(expected_sig.chars().all(|c| c.is_ascii_hexdigit()));
+        let mut config = WebhookConfig {
+            secret: Some(secret.to_string()),
+            ..Default::default()
+        };
+        let headers = signature_headers(&config, &body, 1_700_000_000).unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                ("X-CTScout-Timestamp", "1700000000".to_string()),
+                ("X-CTScout-Signature", expected_sig),
+            ]
+        );
+
+        // The legacy signature covers the body alone
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(&body);
+        let legacy_sig = hex::encode(mac.finalize().into_bytes());
+        config.legacy_signature = true;
+        let headers = signature_headers(&config, &body, 1_700_000_000).unwrap();
+        assert_eq!(headers[1], ("X-CTScout-Signature", legacy_sig));
+
+        config.secret = None;
+        assert!(signature_headers(&config, &body, 1_700_000_000).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_notify_rate_spike_payload() {
+        let mock_server = MockServer::start().await;
+
+        let expected_json = serde_json::json!({
+            "alert": "match_rate_spike",
+            "program_name": "IBM",
+            "matches_this_hour": 40,
+            "baseline_per_hour": 2.5
+        });
+
+        Mock::given(method("POST"))
+            .and(header_exists("X-CTScout-Signature"))
+            .and(body_json_string(expected_json.to_string()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = WebhookConfig {
+            url: mock_server.uri(),
+            secret: Some("test_secret_key".to_string()),
+            timeout_secs: Some(5),
+            ..Default::default()
+        };
+
+        let spike = RateSpike {
+            program: "IBM".to_string(),
+            matches: 40,
+            baseline: 2.5,
+        };
+        assert!(Notifier::new(config).notify_rate_spike(&spike).await.is_ok());
     }
 }
@@ -2,42 +2,57 @@
 //! CSV output handler
 
 use crate::output::OutputHandler;
+use crate::output::timestamps::TimestampFormat;
 use crate::types::MatchResult;
 use async_trait::async_trait;
-use std::io::{self, Write};
-use std::sync::Mutex;
+use futures_util::future::BoxFuture;
+use crate::output::writer::OutputWriter;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Column header. New columns are only ever appended, so readers that go by
+/// position keep working. `platform` through `seen_unix` are the most recent
+/// additions.
+const HEADER: &str = "timestamp,matched_domain,all_domains,cert_index,not_before,not_after,fingerprint,program_name,logged_at,serial_number,subject_key_id,authority_key_id,key_algorithm,key_bits,key_curve,signature_algorithm,subject,issuer_dn,issuer_chain,crl_urls,ocsp_urls,ca_issuer_urls,matched_pattern,match_type,platform,issuer,is_precert,ct_log_url,seen_unix,latency_ms";
 
 /// CSV output handler
 pub struct CsvOutput {
-    writer: Mutex<Box<dyn Write + Send>>,
+    writer: Arc<Mutex<OutputWriter>>,
     header_written: Mutex<bool>,
+    timestamps: TimestampFormat,
 }
 
 impl CsvOutput {
     /// Create a new CsvOutput that writes to stdout
     pub fn new() -> Self {
         Self {
-            writer: Mutex::new(Box::new(io::stdout())),
+            writer: OutputWriter::stdout(),
             header_written: Mutex::new(false),
+            timestamps: TimestampFormat::default(),
         }
     }
 
-    /// Create a new CsvOutput that writes to a file
-    pub fn to_file(file: std::fs::File) -> Self {
+    /// Create a new CsvOutput that writes to a file, flushed every `flush_interval`
+    pub fn to_file(file: std::fs::File, flush_interval: Duration) -> Self {
         Self {
-            writer: Mutex::new(Box::new(file)),
+            writer: OutputWriter::file(file, flush_interval),
             header_written: Mutex::new(false),
+            timestamps: TimestampFormat::default(),
         }
     }
 
+    /// Render timestamp columns as configured
+    pub fn with_timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
     /// Write CSV header if not already written
     fn ensure_header(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
         let mut header_written = self.header_written.lock().unwrap();
         if !*header_written {
-            writeln!(
-                writer,
-                "timestamp,matched_domain,all_domains,cert_index,not_before,not_after,fingerprint,program_name"
-            )?;
+            writeln!(writer, "{}", HEADER)?;
             *header_written = true;
         }
         Ok(())
@@ -66,30 +81,20 @@ impl Default for CsvOutput {
 
 #[async_trait]
 impl OutputHandler for CsvOutput {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
         let mut writer = self.writer.lock().unwrap();
 
         // Ensure header is written
         self.ensure_header(&mut *writer)?;
 
-        // Format all_domains as comma-separated (within quotes)
-        let all_domains = result.all_domains.join(";"); // Use semicolon to avoid CSV confusion
-
         // Write CSV row
-        writeln!(
-            writer,
-            "{},{},{},{},{},{},{},{}",
-            result.timestamp,
-            Self::escape_field(&result.matched_domain),
-            Self::escape_field(&all_domains),
-            Self::format_optional(&result.cert_index),
-            Self::format_optional(&result.not_before),
-            Self::format_optional(&result.not_after),
-            result.fingerprint.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
-            result.program_name.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
-        )?;
+        writeln!(writer, "{}", self.format_row(result))?;
 
-        writer.flush()?;
+        writer.end_match()?;
         Ok(())
     }
 
@@ -98,6 +103,60 @@ impl OutputHandler for CsvOutput {
         writer.flush()?;
         Ok(())
     }
+
+    fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        self.writer.lock().unwrap().persisted()
+    }
+}
+
+impl CsvOutput {
+    /// One CSV row, in the order of [`HEADER`]
+    fn format_row(&self, result: &MatchResult) -> String {
+        let time = |ts: Option<u64>| ts.map(|ts| self.timestamps.field(ts as f64)).unwrap_or_default();
+
+        // Format all_domains as comma-separated (within quotes)
+        let all_domains = result.all_domains.join(";"); // Use semicolon to avoid CSV confusion
+        let issuer_chain = result
+            .issuer_chain
+            .iter()
+            .map(|ca| ca.fingerprint.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.timestamps.field(result.timestamp as f64),
+            Self::escape_field(&result.matched_domain),
+            Self::escape_field(&all_domains),
+            Self::format_optional(&result.cert_index),
+            time(result.not_before),
+            time(result.not_after),
+            result.fingerprint.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            result.program_name.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            time(result.logged_at),
+            Self::format_optional(&result.serial_number),
+            Self::format_optional(&result.subject_key_id),
+            Self::format_optional(&result.authority_key_id),
+            Self::format_optional(&result.key_algorithm),
+            Self::format_optional(&result.key_bits),
+            Self::format_optional(&result.key_curve),
+            Self::format_optional(&result.signature_algorithm),
+            Self::escape_field(&Self::format_optional(&result.subject)),
+            Self::escape_field(&Self::format_optional(&result.issuer_dn)),
+            Self::escape_field(&issuer_chain),
+            Self::escape_field(&result.pki_endpoints.crl.join(";")),
+            Self::escape_field(&result.pki_endpoints.ocsp.join(";")),
+            Self::escape_field(&result.pki_endpoints.ca_issuers.join(";")),
+            result.matched_pattern.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            Self::format_optional(&result.match_type),
+            result.platform.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            result.issuer.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            result.is_precert,
+            result.ct_log_url.as_ref().map(|s| Self::escape_field(s)).unwrap_or_default(),
+            result.seen_unix.map(|ts| self.timestamps.field(ts)).unwrap_or_default(),
+            Self::format_optional(&result.latency_ms),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -110,8 +169,10 @@ mod tests {
         let handler = CsvOutput::new();
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string(), "www.test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -127,6 +188,32 @@ mod tests {
         assert!(handler.emit_match(&result).await.is_ok());
     }
 
+    #[test]
+    fn test_csv_row_matches_header() {
+        let cert_data = CertData {
+            all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(123),
+            seen_unix: Some(1234567890.5),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: true,
+            ct_log_url: Some("https://ct.example.com/log/".to_string()),
+        };
+        let mut result = MatchResult::from_cert_data(
+            "test.com".to_string(),
+            &cert_data,
+            Some("Test Program".to_string()),
+            Some("HackerOne".to_string()),
+        );
+        result.latency_ms = Some(1500);
+
+        let row = CsvOutput::new().format_row(&result);
+        let fields: Vec<&str> = row.split(',').collect();
+        assert_eq!(fields.len(), HEADER.split(',').count());
+        assert!(row.ends_with(",HackerOne,,true,https://ct.example.com/log/,1234567890.5,1500"));
+    }
+
     #[tokio::test]
     async fn test_csv_escape_field() {
         assert_eq!(CsvOutput::escape_field("simple"), "simple");
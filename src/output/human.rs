@@ -1,48 +1,151 @@
 // src/output/human.rs
 //! Human-readable colored terminal output
 
+use crate::cli::HumanDetail;
 use crate::output::OutputHandler;
+use crate::output::timestamps::TimestampFormat;
 use crate::types::MatchResult;
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
 use colored::Colorize;
-use std::io::{self, Write};
-use std::sync::Mutex;
+use crate::output::writer::OutputWriter;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Human-readable output handler with colored terminal output
 pub struct HumanOutput {
-    writer: Mutex<Box<dyn Write + Send>>,
+    writer: Arc<Mutex<OutputWriter>>,
     use_colors: bool,
+    detail: HumanDetail,
+    timestamps: TimestampFormat,
 }
 
 impl HumanOutput {
     /// Create a new HumanOutput that writes to stdout
     pub fn new() -> Self {
         Self {
-            writer: Mutex::new(Box::new(io::stdout())),
+            writer: OutputWriter::stdout(),
             use_colors: is_terminal::is_terminal(std::io::stdout()),
+            detail: HumanDetail::Normal,
+            timestamps: TimestampFormat::default(),
         }
     }
 
-    /// Create a new HumanOutput that writes to a file
-    pub fn to_file(file: std::fs::File) -> Self {
+    /// Create a new HumanOutput that writes to a file, flushed every `flush_interval`
+    pub fn to_file(file: std::fs::File, flush_interval: Duration) -> Self {
         Self {
-            writer: Mutex::new(Box::new(file)),
+            writer: OutputWriter::file(file, flush_interval),
             use_colors: false, // No colors when writing to file
+            detail: HumanDetail::Normal,
+            timestamps: TimestampFormat::default(),
         }
     }
 
-    /// Format a timestamp as human-readable string
-    fn format_timestamp(ts: u64) -> String {
-        use chrono::DateTime;
+    /// Show more or less of each match
+    pub fn with_detail(mut self, detail: HumanDetail) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    /// Render timestamps as configured
+    pub fn with_timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
 
-        // Convert Unix timestamp to DateTime
-        if let Some(datetime) = DateTime::from_timestamp(ts as i64, 0) {
-            // Format as YYYY-MM-DD HH:MM:SS UTC
-            datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    /// Field label, dimmed on a terminal
+    fn label(&self, text: &str) -> String {
+        if self.use_colors {
+            text.dimmed().to_string()
         } else {
-            // Fallback if timestamp is invalid
-            format!("{}", ts)
+            text.to_string()
+        }
+    }
+
+    /// Certificate details only shown with `--detail full`
+    fn write_full_details(&self, writer: &mut dyn Write, result: &MatchResult) -> std::io::Result<()> {
+        writeln!(writer, "    {} {}", self.label("SANs:"), result.all_domains.join(", "))?;
+
+        if let Some(ref issuer) = result.issuer {
+            writeln!(writer, "    {} {}", self.label("Issuer:"), issuer)?;
+        }
+
+        if result.not_before.is_some() || result.not_after.is_some() {
+            let format = |ts: Option<u64>| ts.map(|ts| self.timestamps.human(ts)).unwrap_or_else(|| "?".to_string());
+            writeln!(
+                writer,
+                "    {} {} to {}",
+                self.label("Valid:"),
+                format(result.not_before),
+                format(result.not_after)
+            )?;
+        }
+
+        if let Some(ref fingerprint) = result.fingerprint {
+            writeln!(writer, "    {} {}", self.label("Fingerprint:"), fingerprint)?;
+        }
+
+        if let Some(ref log) = result.ct_log_url {
+            match result.cert_index {
+                Some(index) => writeln!(writer, "    {} {} #{}", self.label("CT log:"), log, index)?,
+                None => writeln!(writer, "    {} {}", self.label("CT log:"), log)?,
+            }
+        }
+
+        if let Some(latency_ms) = result.latency_ms {
+            writeln!(writer, "    {} {:.1}s after logging", self.label("Latency:"), latency_ms as f64 / 1000.0)?;
+        }
+
+        writeln!(
+            writer,
+            "    {} {}",
+            self.label("Precert:"),
+            if result.is_precert { "yes" } else { "no" }
+        )
+    }
+
+    /// Enrichment findings worth calling out
+    fn notes(result: &MatchResult) -> &[String] {
+        result
+            .enrichment
+            .as_ref()
+            .map(|enrichment| enrichment.notes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Watchlist rule that fired, e.g. `*.example.com (wildcard)`
+    fn rule_summary(result: &MatchResult) -> Option<String> {
+        let pattern = result.matched_pattern.as_ref()?;
+        Some(match result.match_type {
+            Some(match_type) => format!("{} ({})", pattern, match_type),
+            None => pattern.clone(),
+        })
+    }
+
+    /// One-line summary of the HTTP probe, e.g. `200 nginx "Login"`
+    fn http_summary(result: &MatchResult) -> Option<String> {
+        let http = result.enrichment.as_ref()?.http.as_ref()?;
+
+        let mut summary = http.status.to_string();
+        if let Some(ref server) = http.server {
+            summary.push_str(&format!(" {}", server));
+        }
+        if let Some(ref title) = http.title {
+            summary.push_str(&format!(" \"{}\"", title));
         }
+        Some(summary)
+    }
+
+    /// Open ports from Shodan/Censys, e.g. `22, 443 (shodan)`
+    fn intel_summary(result: &MatchResult) -> Option<String> {
+        let intel = result.enrichment.as_ref()?.intel.as_ref()?;
+        if intel.ports.is_empty() {
+            return None;
+        }
+
+        let ports: Vec<String> = intel.ports.iter().map(u16::to_string).collect();
+        Some(format!("{} ({})", ports.join(", "), intel.source))
     }
 }
 
@@ -54,10 +157,22 @@ impl Default for HumanOutput {
 
 #[async_trait]
 impl OutputHandler for HumanOutput {
+    fn name(&self) -> &'static str {
+        "human"
+    }
+
+    #[allow(clippy::write_with_newline)]
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
         let mut writer = self.writer.lock().unwrap();
 
-        let timestamp = Self::format_timestamp(result.timestamp);
+        if self.detail == HumanDetail::Minimal {
+            writeln!(writer, "{}", result.matched_domain)?;
+            writer.end_match()?;
+            return Ok(());
+        }
+
+        let timestamp = self.timestamps.human(result.timestamp);
+        let full = self.detail == HumanDetail::Full;
 
         if self.use_colors {
             write!(
@@ -83,7 +198,13 @@ impl OutputHandler for HumanOutput {
                 )?;
             }
 
-            if result.all_domains.len() > 1 {
+            if let Some(rule) = Self::rule_summary(result) {
+                writeln!(writer, "    {} {}", "Rule:".dimmed(), rule)?;
+            }
+
+            if full {
+                self.write_full_details(&mut *writer, result)?;
+            } else if result.all_domains.len() > 1 {
                 writeln!(
                     writer,
                     "    {} {}",
@@ -91,6 +212,18 @@ impl OutputHandler for HumanOutput {
                     result.all_domains.join(", ")
                 )?;
             }
+
+            if let Some(http) = Self::http_summary(result) {
+                writeln!(writer, "    {} {}", "HTTP:".dimmed(), http)?;
+            }
+
+            if let Some(ports) = Self::intel_summary(result) {
+                writeln!(writer, "    {} {}", "Open ports:".dimmed(), ports)?;
+            }
+
+            for note in Self::notes(result) {
+                writeln!(writer, "    {} {}", "[!]".red().bold(), note.red())?;
+            }
         } else {
             writeln!(writer, "[{}] [+] {}", timestamp, result.matched_domain)?;
 
@@ -104,12 +237,30 @@ impl OutputHandler for HumanOutput {
                 writeln!(writer, "    Program: {}", program_display)?;
             }
 
-            if result.all_domains.len() > 1 {
+            if let Some(rule) = Self::rule_summary(result) {
+                writeln!(writer, "    Rule: {}", rule)?;
+            }
+
+            if full {
+                self.write_full_details(&mut *writer, result)?;
+            } else if result.all_domains.len() > 1 {
                 writeln!(writer, "    All domains: {}", result.all_domains.join(", "))?;
             }
+
+            if let Some(http) = Self::http_summary(result) {
+                writeln!(writer, "    HTTP: {}", http)?;
+            }
+
+            if let Some(ports) = Self::intel_summary(result) {
+                writeln!(writer, "    Open ports: {}", ports)?;
+            }
+
+            for note in Self::notes(result) {
+                writeln!(writer, "    [!] {}", note)?;
+            }
         }
 
-        writer.flush()?;
+        writer.end_match()?;
         Ok(())
     }
 
@@ -118,6 +269,10 @@ impl OutputHandler for HumanOutput {
         writer.flush()?;
         Ok(())
     }
+
+    fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        self.writer.lock().unwrap().persisted()
+    }
 }
 
 #[cfg(test)]
@@ -130,8 +285,10 @@ mod tests {
         let handler = HumanOutput::new();
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string(), "www.test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -147,4 +304,43 @@ mod tests {
         assert!(handler.emit_match(&result).await.is_ok());
         assert!(handler.flush().await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_detail_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert_data = CertData {
+            all_domains: Some(vec!["test.com".to_string(), "www.test.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(123),
+            seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: true,
+            ct_log_url: Some("https://ct.example.com/log/".to_string()),
+        };
+        let result = MatchResult::from_cert_data("test.com".to_string(), &cert_data, None, None);
+
+        let render = |detail: HumanDetail| {
+            let path = dir.path().join(format!("{:?}.txt", detail));
+            let handler = HumanOutput::to_file(std::fs::File::create(&path).unwrap(), std::time::Duration::ZERO)
+                .with_detail(detail);
+            let result = result.clone();
+            async move {
+                handler.emit_match(&result).await.unwrap();
+                handler.flush().await.unwrap();
+                std::fs::read_to_string(&path).unwrap()
+            }
+        };
+
+        assert_eq!(render(HumanDetail::Minimal).await, "test.com\n");
+
+        let normal = render(HumanDetail::Normal).await;
+        assert!(normal.contains("All domains: test.com, www.test.com"));
+        assert!(!normal.contains("CT log:"));
+
+        let full = render(HumanDetail::Full).await;
+        assert!(full.contains("SANs: test.com, www.test.com"));
+        assert!(full.contains("CT log: https://ct.example.com/log/ #123"));
+        assert!(full.contains("Precert: yes"));
+    }
 }
@@ -2,32 +2,45 @@
 //! JSON Lines (JSONL) output handler
 
 use crate::output::OutputHandler;
+use crate::output::timestamps::TimestampFormat;
 use crate::types::MatchResult;
 use async_trait::async_trait;
-use std::io::{self, Write};
-use std::sync::Mutex;
+use futures_util::future::BoxFuture;
+use crate::output::writer::OutputWriter;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// JSON Lines output handler
 ///
 /// Outputs one JSON object per line (JSONL/NDJSON format)
 pub struct JsonOutput {
-    writer: Mutex<Box<dyn Write + Send>>,
+    writer: Arc<Mutex<OutputWriter>>,
+    timestamps: TimestampFormat,
 }
 
 impl JsonOutput {
     /// Create a new JsonOutput that writes to stdout
     pub fn new() -> Self {
         Self {
-            writer: Mutex::new(Box::new(io::stdout())),
+            writer: OutputWriter::stdout(),
+            timestamps: TimestampFormat::default(),
         }
     }
 
-    /// Create a new JsonOutput that writes to a file
-    pub fn to_file(file: std::fs::File) -> Self {
+    /// Create a new JsonOutput that writes to a file, flushed every `flush_interval`
+    pub fn to_file(file: std::fs::File, flush_interval: Duration) -> Self {
         Self {
-            writer: Mutex::new(Box::new(file)),
+            writer: OutputWriter::file(file, flush_interval),
+            timestamps: TimestampFormat::default(),
         }
     }
+
+    /// Render timestamp fields as configured
+    pub fn with_timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
 }
 
 impl Default for JsonOutput {
@@ -38,15 +51,20 @@ impl Default for JsonOutput {
 
 #[async_trait]
 impl OutputHandler for JsonOutput {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
         let mut writer = self.writer.lock().unwrap();
 
         // Serialize to JSON
-        let json = serde_json::to_string(result)?;
+        let mut json = serde_json::to_value(result)?;
+        self.timestamps.apply_json(&mut json);
 
         // Write JSON line
         writeln!(writer, "{}", json)?;
-        writer.flush()?;
+        writer.end_match()?;
 
         Ok(())
     }
@@ -56,6 +74,10 @@ impl OutputHandler for JsonOutput {
         writer.flush()?;
         Ok(())
     }
+
+    fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        self.writer.lock().unwrap().persisted()
+    }
 }
 
 #[cfg(test)]
@@ -68,8 +90,10 @@ mod tests {
         let handler = JsonOutput::new();
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: Some(1234567890123),
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -88,6 +112,8 @@ mod tests {
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("test.com"));
         assert!(json.contains("Test Program"));
+        assert!(json.contains("\"logged_at\":1234567890"));
+        assert!(json.contains(&format!("\"processed_at\":{}", result.timestamp)));
     }
 
     #[tokio::test]
@@ -4,30 +4,114 @@
 //! This module provides a flexible output system that supports multiple
 //! output formats and destinations simultaneously.
 
+use crate::metrics;
 use crate::types::MatchResult;
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use routing::Router;
+use spool::OutputSpool;
 
 pub mod csv;
 pub mod human;
 pub mod json;
+pub mod pipe;
 pub mod redis;
+pub mod routing;
 pub mod silent;
+pub mod spool;
+pub mod timestamps;
 pub mod webhook;
+pub mod writer;
 
 /// Trait for output handlers that process matched certificates
 #[async_trait]
 pub trait OutputHandler: Send + Sync {
+    /// Short name used as the `handler` label in metrics
+    fn name(&self) -> &'static str;
+
+    /// Spool file name, for handlers that can be configured more than once
+    fn spool_name(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// Whether this handler wants `result` at all, on top of the routing rules
+    fn accepts(&self, _result: &MatchResult) -> bool {
+        true
+    }
+
     /// Emit a matched certificate result
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()>;
 
     /// Flush any buffered output
     async fn flush(&self) -> anyhow::Result<()>;
+
+    /// For buffered handlers: resolves once every match emitted so far has
+    /// been flushed (false if it never will be). None = nothing to wait for.
+    fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        None
+    }
+}
+
+/// How long a worker waits before replaying its spool again after a failed attempt
+const SPOOL_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A queued match and who to tell once the worker is done with it
+struct Queued {
+    result: Arc<MatchResult>,
+    done: oneshot::Sender<()>,
+}
+
+/// Confirmation that every queue a match was handed to is done with it
+///
+/// A queue is done once its handler has emitted the match, spooled it or
+/// given up on it; buffered file handlers only count as emitted once the
+/// match has been flushed. Matches spooled or dropped by `emit` itself have
+/// nothing left to wait for.
+#[derive(Default)]
+pub struct Delivery {
+    pending: Vec<BoxFuture<'static, bool>>,
+}
+
+impl Delivery {
+    /// Whether nothing is left to wait for
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Wait for every queue; false if a worker stopped without finishing the match
+    pub async fn confirmed(self) -> bool {
+        let mut confirmed = true;
+        for pending in self.pending {
+            confirmed &= pending.await;
+        }
+        confirmed
+    }
+}
+
+/// Bounded queue in front of a handler, drained by background workers
+struct HandlerQueue {
+    tx: mpsc::Sender<Queued>,
+    workers: Vec<JoinHandle<()>>,
+    spool: Option<Arc<OutputSpool>>,
 }
 
 /// Manager that dispatches output to multiple handlers
 pub struct OutputManager {
     handlers: Vec<Arc<dyn OutputHandler>>,
+    /// One per handler when queueing is enabled, in the same order
+    queues: Vec<HandlerQueue>,
+    /// (capacity, workers per handler); None = emit inline
+    queue_config: Option<(usize, usize)>,
+    /// Directory for per-handler spool files; None = drop on overload
+    spool_dir: Option<PathBuf>,
+    /// Which handlers each match goes to
+    router: Router,
 }
 
 impl OutputManager {
@@ -35,11 +119,62 @@ impl OutputManager {
     pub fn new() -> Self {
         Self {
             handlers: Vec::new(),
+            queues: Vec::new(),
+            queue_config: None,
+            spool_dir: None,
+            router: Router::default(),
         }
     }
 
+    /// Only hand matches to the handlers the routing rules select for them
+    ///
+    /// Handlers no rule names still get every match.
+    pub fn with_routes(mut self, router: Router) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Give each handler added from now on its own queue of `capacity` matches,
+    /// drained by `workers` tasks, so a slow destination can't hold up `emit`
+    ///
+    /// A match is dropped for a handler whose queue is full, unless a spool
+    /// is set with [`with_spool`](Self::with_spool). Must be called
+    /// inside a Tokio runtime.
+    pub fn with_queues(mut self, capacity: usize, workers: usize) -> Self {
+        self.queue_config = Some((capacity.max(1), workers.max(1)));
+        self
+    }
+
+    /// Spool matches that don't fit in a handler's queue, or that the handler
+    /// fails to emit, to `<dir>/<handler>.jsonl` instead of dropping them
+    ///
+    /// Spooled matches are replayed in order once the queue is idle, and
+    /// retried every 30 seconds while the handler keeps failing. Files left
+    /// over from a previous run are picked up. Only applies with queues.
+    pub fn with_spool(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spool_dir = Some(dir.into());
+        self
+    }
+
     /// Add an output handler
     pub fn add_handler(&mut self, handler: Arc<dyn OutputHandler>) {
+        if let Some((capacity, workers)) = self.queue_config {
+            let (tx, rx) = mpsc::channel::<Queued>(capacity);
+            let rx = Arc::new(Mutex::new(rx));
+            let spool = self.spool_dir.as_ref().and_then(|dir| {
+                match OutputSpool::open(dir, &handler.spool_name()) {
+                    Ok(spool) => Some(Arc::new(spool)),
+                    Err(e) => {
+                        tracing::warn!("Not spooling {} output: {:#}", handler.name(), e);
+                        None
+                    }
+                }
+            });
+            let workers = (0..workers)
+                .map(|_| tokio::spawn(run_worker(Arc::clone(&handler), Arc::clone(&rx), spool.clone())))
+                .collect();
+            self.queues.push(HandlerQueue { tx, workers, spool });
+        }
         self.handlers.push(handler);
     }
 
@@ -47,13 +182,55 @@ impl OutputManager {
     ///
     /// Errors from individual handlers are logged but don't stop processing.
     /// This ensures webhook failures don't prevent stdout output, etc.
-    pub async fn emit(&self, result: &MatchResult) -> anyhow::Result<()> {
+    /// With queues, the match is only handed to each queue and handler
+    /// errors are logged by the workers; the returned [`Delivery`] resolves
+    /// once they are done with it.
+    #[allow(clippy::collapsible_if)]
+    pub async fn emit(&self, result: &MatchResult) -> anyhow::Result<Delivery> {
+        if !self.queues.is_empty() {
+            let result = Arc::new(result.clone());
+            let mut delivery = Delivery::default();
+            for (handler, queue) in self.handlers.iter().zip(&self.queues) {
+                if !self.router.allows(handler.name(), &result) || !handler.accepts(&result) {
+                    continue;
+                }
+                let (done, done_rx) = oneshot::channel();
+                let queued = Queued { result: Arc::clone(&result), done };
+                if queue.tx.try_send(queued).is_ok() {
+                    delivery.pending.push(Box::pin(async move { done_rx.await.is_ok() }));
+                } else {
+                    if let Some(spool) = &queue.spool {
+                        match spool.push(&[&result]).await {
+                            Ok(()) => {
+                                metrics::OUTPUT_EMIT_TOTAL
+                                    .with_label_values(&[handler.name(), "spooled"])
+                                    .inc();
+                                continue;
+                            }
+                            Err(e) => tracing::warn!("Failed to spool {} output: {:#}", handler.name(), e),
+                        }
+                    }
+                    metrics::OUTPUT_EMIT_TOTAL
+                        .with_label_values(&[handler.name(), "dropped"])
+                        .inc();
+                    tracing::warn!("{} output is falling behind, dropped match for {}",
+                        handler.name(), result.matched_domain);
+                }
+            }
+            return Ok(delivery);
+        }
+
         let mut last_error = None;
+        let mut delivery = Delivery::default();
 
-        for handler in &self.handlers {
-            if let Err(e) = handler.emit_match(result).await {
-                tracing::warn!("Output handler error: {}", e);
-                last_error = Some(e);
+        for handler in self
+            .handlers
+            .iter()
+            .filter(|handler| self.router.allows(handler.name(), result) && handler.accepts(result))
+        {
+            match emit_to(handler.as_ref(), result).await {
+                Ok(()) => delivery.pending.extend(handler.persisted()),
+                Err(e) => last_error = Some(e),
             }
         }
 
@@ -64,7 +241,7 @@ impl OutputManager {
             }
         }
 
-        Ok(())
+        Ok(delivery)
     }
 
     /// Flush all handlers
@@ -74,6 +251,131 @@ impl OutputManager {
         }
         Ok(())
     }
+
+    /// Wait for queued matches to be emitted, then flush all handlers
+    ///
+    /// Spooled matches stay on disk for the next run.
+    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+        for queue in self.queues.drain(..) {
+            drop(queue.tx);
+            for worker in queue.workers {
+                worker.await.ok();
+            }
+        }
+        self.flush().await
+    }
+}
+
+/// Drain a handler's queue, spooling failed emits and replaying the spool
+/// whenever the queue is idle
+async fn run_worker(
+    handler: Arc<dyn OutputHandler>,
+    rx: Arc<Mutex<mpsc::Receiver<Queued>>>,
+    spool: Option<Arc<OutputSpool>>,
+) {
+    let mut replay_at = tokio::time::Instant::now();
+    loop {
+        let next = {
+            let mut rx = rx.lock().await;
+            match &spool {
+                Some(spool) if spool.pending() > 0 => {
+                    tokio::time::timeout_at(replay_at, rx.recv()).await.ok()
+                }
+                _ => Some(rx.recv().await),
+            }
+        };
+
+        let spool = match (next, &spool) {
+            (Some(None), _) => break,
+            (Some(Some(Queued { result, done })), spool) => {
+                match emit_to(handler.as_ref(), &result).await {
+                    Ok(()) => {
+                        // The handler has recovered, so the spool can go out now
+                        replay_at = tokio::time::Instant::now();
+                        if let Some(persisted) = handler.persisted() {
+                            tokio::spawn(async move {
+                                if persisted.await {
+                                    done.send(()).ok();
+                                }
+                            });
+                            continue;
+                        }
+                    }
+                    Err(_) => {
+                        if let Some(spool) = spool
+                            && let Err(e) = spool.push(&[&result]).await
+                        {
+                            tracing::warn!("Failed to spool {} output: {:#}", handler.name(), e);
+                        }
+                    }
+                }
+                done.send(()).ok();
+                continue;
+            }
+            (None, Some(spool)) => spool,
+            (None, None) => unreachable!("only spooling workers time out"),
+        };
+
+        if !replay_spool(handler.as_ref(), spool).await {
+            replay_at = tokio::time::Instant::now() + SPOOL_RETRY_INTERVAL;
+        }
+    }
+}
+
+/// Emit spooled matches in order, putting back whatever is left after the
+/// first failure. Returns whether the spool was fully replayed; false too if
+/// another worker is replaying it.
+async fn replay_spool(handler: &dyn OutputHandler, spool: &OutputSpool) -> bool {
+    let Some(_replay) = spool.claim_replay() else {
+        return false;
+    };
+    let results = match spool.start_replay().await {
+        Ok(results) => results,
+        Err(e) => {
+            tracing::warn!("Failed to read {} output spool: {:#}", handler.name(), e);
+            return false;
+        }
+    };
+    if !results.is_empty() {
+        tracing::info!("Replaying {} spooled matches to {} output", results.len(), handler.name());
+    }
+
+    let mut replayed = true;
+    for (i, result) in results.iter().enumerate() {
+        if emit_to(handler, result).await.is_err() {
+            let remaining: Vec<&MatchResult> = results[i..].iter().collect();
+            if let Err(e) = spool.push(&remaining).await {
+                // Keep the replay file, so they go out on the next attempt or run
+                tracing::warn!("Failed to spool {} output: {:#}", handler.name(), e);
+                return false;
+            }
+            replayed = false;
+            break;
+        }
+    }
+
+    if let Err(e) = spool.finish_replay().await {
+        tracing::warn!("Failed to finish {} output spool replay: {:#}", handler.name(), e);
+        return false;
+    }
+    replayed
+}
+
+/// Emit to one handler, recording metrics and logging failures
+async fn emit_to(handler: &dyn OutputHandler, result: &MatchResult) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let outcome = handler.emit_match(result).await;
+    metrics::OUTPUT_EMIT_DURATION
+        .with_label_values(&[handler.name()])
+        .observe(started.elapsed().as_secs_f64());
+    metrics::OUTPUT_EMIT_TOTAL
+        .with_label_values(&[handler.name(), if outcome.is_ok() { "success" } else { "failure" }])
+        .inc();
+
+    if let Err(ref e) = outcome {
+        tracing::warn!("Output handler error: {}", e);
+    }
+    outcome
 }
 
 impl Default for OutputManager {
@@ -105,11 +407,224 @@ mod tests {
         assert!(manager.emit(&result).await.is_ok());
     }
 
+    struct FailingOutput;
+
+    #[async_trait]
+    impl OutputHandler for FailingOutput {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        async fn emit_match(&self, _result: &MatchResult) -> anyhow::Result<()> {
+            anyhow::bail!("unreachable sink")
+        }
+
+        async fn flush(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_emit_metrics_per_handler() {
+        let mut manager = OutputManager::new();
+        manager.add_handler(Arc::new(FailingOutput));
+        manager.add_handler(Arc::new(silent::SilentOutput));
+
+        let failures = metrics::OUTPUT_EMIT_TOTAL.with_label_values(&["failing", "failure"]);
+        let before = failures.get();
+        // One handler still succeeded, so the emit as a whole does too
+        assert!(manager.emit(&create_test_result()).await.is_ok());
+        assert_eq!(failures.get(), before + 1);
+        assert!(metrics::OUTPUT_EMIT_DURATION.with_label_values(&["failing"]).get_sample_count() >= 1);
+    }
+
+    /// Counts matches, each emit taking `delay`
+    struct SlowOutput {
+        delay: std::time::Duration,
+        emitted: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl OutputHandler for SlowOutput {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        async fn emit_match(&self, _result: &MatchResult) -> anyhow::Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.emitted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn flush(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queued_handler_does_not_block_emit() {
+        let slow = Arc::new(SlowOutput {
+            delay: std::time::Duration::from_millis(200),
+            emitted: Default::default(),
+        });
+        let mut manager = OutputManager::new().with_queues(2, 1);
+        manager.add_handler(slow.clone());
+
+        let started = Instant::now();
+        for _ in 0..5 {
+            manager.emit(&create_test_result()).await.unwrap();
+        }
+        assert!(started.elapsed() < std::time::Duration::from_millis(100));
+
+        // One match was picked up by the worker and two queued; the rest were dropped
+        manager.shutdown().await.unwrap();
+        let emitted = slow.emitted.load(std::sync::atomic::Ordering::SeqCst);
+        assert!((2..=3).contains(&emitted), "emitted {}", emitted);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_waits_for_queued_emit() {
+        let slow = Arc::new(SlowOutput {
+            delay: std::time::Duration::from_millis(100),
+            emitted: Default::default(),
+        });
+        let mut manager = OutputManager::new().with_queues(10, 1);
+        manager.add_handler(slow.clone());
+
+        let delivery = manager.emit(&create_test_result()).await.unwrap();
+        assert!(!delivery.is_done());
+        assert_eq!(slow.emitted.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert!(delivery.confirmed().await);
+        assert_eq!(slow.emitted.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Inline emits are done before emit returns
+        let mut inline = OutputManager::new();
+        inline.add_handler(Arc::new(silent::SilentOutput));
+        assert!(inline.emit(&create_test_result()).await.unwrap().is_done());
+        manager.shutdown().await.unwrap();
+    }
+
+    /// Fails every emit while `down` is set
+    struct FlakyOutput {
+        down: std::sync::atomic::AtomicBool,
+        emitted: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl OutputHandler for FlakyOutput {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn emit_match(&self, _result: &MatchResult) -> anyhow::Result<()> {
+            if self.down.load(std::sync::atomic::Ordering::SeqCst) {
+                anyhow::bail!("destination down");
+            }
+            self.emitted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn flush(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spool_replays_after_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let flaky = Arc::new(FlakyOutput {
+            down: std::sync::atomic::AtomicBool::new(true),
+            emitted: Default::default(),
+        });
+        let mut manager = OutputManager::new().with_queues(1, 1).with_spool(dir.path());
+        manager.add_handler(flaky.clone());
+
+        for _ in 0..5 {
+            manager.emit(&create_test_result()).await.unwrap();
+        }
+        // Every match ends up in the spool, either from a full queue or a failed emit
+        let spool_file = dir.path().join("flaky.jsonl");
+        for _ in 0..50 {
+            if std::fs::read_to_string(&spool_file).map(|s| s.lines().count()).unwrap_or(0) == 5 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&spool_file).unwrap().lines().count(), 5);
+
+        // The next successful emit triggers a replay of everything spooled
+        flaky.down.store(false, std::sync::atomic::Ordering::SeqCst);
+        manager.emit(&create_test_result()).await.unwrap();
+        for _ in 0..50 {
+            if flaky.emitted.load(std::sync::atomic::Ordering::SeqCst) == 6 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(flaky.emitted.load(std::sync::atomic::Ordering::SeqCst), 6);
+        assert!(!spool_file.exists());
+        assert!(!dir.path().join("flaky.replaying.jsonl").exists());
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_replay_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        // A previous run was killed while replaying two matches
+        let line = serde_json::to_string(&create_test_result()).unwrap();
+        let replay_file = dir.path().join("flaky.replaying.jsonl");
+        std::fs::write(&replay_file, format!("{}\n{}\n", line, line)).unwrap();
+
+        let flaky = Arc::new(FlakyOutput {
+            down: std::sync::atomic::AtomicBool::new(false),
+            emitted: Default::default(),
+        });
+        let mut manager = OutputManager::new().with_queues(1, 1).with_spool(dir.path());
+        manager.add_handler(flaky.clone());
+
+        for _ in 0..50 {
+            if flaky.emitted.load(std::sync::atomic::Ordering::SeqCst) == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(flaky.emitted.load(std::sync::atomic::Ordering::SeqCst), 2);
+        manager.shutdown().await.unwrap();
+        assert!(!replay_file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_spool_replayed_once_by_several_workers() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = serde_json::to_string(&create_test_result()).unwrap();
+        std::fs::write(dir.path().join("flaky.jsonl"), format!("{}\n", line).repeat(20)).unwrap();
+
+        let flaky = Arc::new(FlakyOutput {
+            down: std::sync::atomic::AtomicBool::new(false),
+            emitted: Default::default(),
+        });
+        let mut manager = OutputManager::new().with_queues(1, 2).with_spool(dir.path());
+        manager.add_handler(flaky.clone());
+
+        for _ in 0..50 {
+            if flaky.emitted.load(std::sync::atomic::Ordering::SeqCst) >= 20 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(flaky.emitted.load(std::sync::atomic::Ordering::SeqCst), 20);
+        manager.shutdown().await.unwrap();
+        assert!(!dir.path().join("flaky.replaying.jsonl").exists());
+    }
+
     fn create_test_result() -> MatchResult {
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
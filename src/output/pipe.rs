@@ -0,0 +1,111 @@
+// src/output/pipe.rs
+//! Plain hostname output for chaining into other tools (`ct-scout --pipe | httpx | nuclei`)
+
+use crate::output::OutputHandler;
+use crate::output::writer::OutputWriter;
+use crate::types::MatchResult;
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Pipe output handler
+///
+/// Writes each matched hostname once, on a line of its own and with nothing
+/// else around it. Wildcards are reduced to their base name, since the tools
+/// downstream can't resolve `*.example.com`.
+pub struct PipeOutput {
+    writer: Arc<Mutex<OutputWriter>>,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl PipeOutput {
+    /// Create a new PipeOutput that writes to stdout
+    pub fn new() -> Self {
+        Self {
+            writer: OutputWriter::stdout(),
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Create a new PipeOutput that writes to a file, flushed every `flush_interval`
+    pub fn to_file(file: std::fs::File, flush_interval: Duration) -> Self {
+        Self {
+            writer: OutputWriter::file(file, flush_interval),
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl Default for PipeOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OutputHandler for PipeOutput {
+    fn name(&self) -> &'static str {
+        "pipe"
+    }
+
+    async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
+        let host = result
+            .matched_domain
+            .trim_start_matches("*.")
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        if !self.seen.lock().unwrap().insert(host.clone()) {
+            return Ok(());
+        }
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{}", host)?;
+        writer.end_match()?;
+
+        Ok(())
+    }
+
+    async fn flush(&self) -> anyhow::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        self.writer.lock().unwrap().persisted()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CertData;
+
+    #[tokio::test]
+    async fn test_pipe_output_unique_hostnames() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let handler = PipeOutput::to_file(file.reopen().unwrap(), Duration::ZERO);
+
+        for domain in ["api.example.com", "*.example.com", "API.example.com", "example.com", "10.0.0.1"] {
+            let cert_data = CertData {
+                all_domains: Some(vec![domain.to_string()]),
+                ip_addresses: None,
+                cert_index: Some(1),
+                seen_unix: None,
+                logged_at_ms: None,
+                leaf_cert: None,
+                is_precert: false,
+                ct_log_url: None,
+            };
+            let result = MatchResult::from_cert_data(domain.to_string(), &cert_data, None, None);
+            handler.emit_match(&result).await.unwrap();
+        }
+        handler.flush().await.unwrap();
+
+        let written = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(written, "api.example.com\nexample.com\n10.0.0.1\n");
+    }
+}
@@ -21,6 +21,10 @@ impl RedisOutput {
 
 #[async_trait]
 impl OutputHandler for RedisOutput {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
         // Build the CT event message from the match result
         let event = CTEventMessage::from_match(
@@ -68,8 +72,10 @@ mod tests {
 
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: Some("https://ct.example.com/log".to_string()),
@@ -0,0 +1,240 @@
+// src/output/routing.rs
+//! Routing rules that decide which outputs receive which matches
+//!
+//! Built from the `[[routing]]` tables of the config. Each rule selects
+//! matches by program, platform, severity and tags, and names the outputs
+//! they go to. An output named by any rule only gets the matches one of its
+//! rules selects; outputs no rule names keep getting everything.
+
+use crate::config::RoutingConfig;
+use crate::types::{MatchResult, Severity};
+use anyhow::Context;
+use std::str::FromStr;
+
+/// Output names a rule can refer to, as returned by `OutputHandler::name`
+pub const OUTPUT_NAMES: &[&str] = &["human", "json", "csv", "pipe", "silent", "webhook", "redis", "stream", "tui"];
+
+/// Severity condition such as `>=high` or `critical`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeverityFilter {
+    op: Comparison,
+    severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+impl SeverityFilter {
+    pub fn accepts(&self, severity: Severity) -> bool {
+        match self.op {
+            Comparison::Eq => severity == self.severity,
+            Comparison::Ge => severity >= self.severity,
+            Comparison::Gt => severity > self.severity,
+            Comparison::Le => severity <= self.severity,
+            Comparison::Lt => severity < self.severity,
+        }
+    }
+}
+
+impl FromStr for SeverityFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let (op, rest) = [(">=", Comparison::Ge), ("<=", Comparison::Le), (">", Comparison::Gt), ("<", Comparison::Lt), ("=", Comparison::Eq)]
+            .into_iter()
+            .find_map(|(prefix, op)| s.strip_prefix(prefix).map(|rest| (op, rest)))
+            .unwrap_or((Comparison::Eq, s));
+        Ok(Self { op, severity: rest.trim().parse()? })
+    }
+}
+
+/// Conditions on a match's program, platform, severity and tags
+///
+/// Program and platform are case-insensitive globs; a match needs one of
+/// `tags`. Conditions left unset accept every match.
+#[derive(Debug, Clone, Default)]
+pub struct MatchFilter {
+    program: Option<String>,
+    platform: Option<String>,
+    severity: Option<SeverityFilter>,
+    tags: Vec<String>,
+}
+
+impl MatchFilter {
+    pub fn new(
+        program: Option<String>,
+        platform: Option<String>,
+        severity: Option<&str>,
+        tags: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            program,
+            platform,
+            severity: severity.map(str::parse).transpose()?,
+            tags,
+        })
+    }
+
+    pub fn selects(&self, result: &MatchResult) -> bool {
+        let field_matches = |pattern: &Option<String>, value: &Option<String>| match pattern {
+            None => true,
+            Some(pattern) => value.as_deref().is_some_and(|value| glob_match(pattern, value)),
+        };
+
+        field_matches(&self.program, &result.program_name)
+            && field_matches(&self.platform, &result.platform)
+            && self
+                .severity
+                .is_none_or(|filter| result.severity.is_some_and(|severity| filter.accepts(severity)))
+            && (self.tags.is_empty() || self.tags.iter().any(|tag| result.tags.contains(tag)))
+    }
+}
+
+/// One compiled `[[routing]]` rule
+#[derive(Debug, Clone)]
+struct Route {
+    filter: MatchFilter,
+    outputs: Vec<String>,
+}
+
+/// Compiled routing rules
+#[derive(Debug, Clone, Default)]
+pub struct Router {
+    routes: Vec<Route>,
+}
+
+impl Router {
+    pub fn from_config(rules: &[RoutingConfig]) -> anyhow::Result<Self> {
+        let routes = rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                if let Some(output) = rule.outputs.iter().find(|output| !OUTPUT_NAMES.contains(&output.as_str())) {
+                    anyhow::bail!("routing[{}]: unknown output '{}'", i, output);
+                }
+                Ok(Route {
+                    filter: MatchFilter::new(
+                        rule.program.clone(),
+                        rule.platform.clone(),
+                        rule.severity.as_deref(),
+                        rule.tags.clone(),
+                    )
+                    .with_context(|| format!("routing[{}]", i))?,
+                    outputs: rule.outputs.clone(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { routes })
+    }
+
+    /// Whether the output named `output` should receive `result`
+    pub fn allows(&self, output: &str, result: &MatchResult) -> bool {
+        let mut routed = false;
+        for route in self.routes.iter().filter(|route| route.outputs.iter().any(|o| o == output)) {
+            if route.filter.selects(result) {
+                return true;
+            }
+            routed = true;
+        }
+        !routed
+    }
+}
+
+/// Case-insensitive match where `*` stands for any run of characters
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let value = value.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CertData;
+
+    fn result(program: &str, severity: Option<Severity>, tags: &[&str]) -> MatchResult {
+        let data = CertData {
+            all_domains: Some(vec!["api.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(1),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let mut result = MatchResult::from_cert_data(
+            "api.example.com".to_string(),
+            &data,
+            Some(program.to_string()),
+            Some("HackerOne".to_string()),
+        );
+        result.severity = severity;
+        result.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        result
+    }
+
+    #[test]
+    fn test_routing_rules() {
+        let router = Router::from_config(&[
+            RoutingConfig {
+                program: Some("*".to_string()),
+                severity: Some(">=high".to_string()),
+                outputs: vec!["webhook".to_string(), "redis".to_string()],
+                ..Default::default()
+            },
+            RoutingConfig {
+                platform: Some("hacker*".to_string()),
+                tags: vec!["prod".to_string()],
+                outputs: vec!["redis".to_string()],
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+
+        let critical = result("Acme", Some(Severity::Critical), &[]);
+        let low_prod = result("Acme", Some(Severity::Low), &["prod"]);
+        let unrated = result("Acme", None, &[]);
+
+        assert!(router.allows("webhook", &critical));
+        assert!(!router.allows("webhook", &low_prod));
+        assert!(router.allows("redis", &low_prod));
+        assert!(!router.allows("redis", &unrated));
+        // No rule names json, so it gets everything
+        assert!(router.allows("json", &unrated));
+
+        let filter: SeverityFilter = "<medium".parse().unwrap();
+        assert!(filter.accepts(Severity::Low));
+        assert!(!filter.accepts(Severity::Medium));
+        assert!("urgent".parse::<SeverityFilter>().is_err());
+
+        assert!(glob_match("acme-*", "ACME-corp"));
+        assert!(glob_match("*corp*", "acme-corp-eu"));
+        assert!(!glob_match("acme", "acme-corp"));
+
+        let unknown = RoutingConfig { outputs: vec!["pagerduty".to_string()], ..Default::default() };
+        assert!(Router::from_config(&[unknown]).is_err());
+    }
+}
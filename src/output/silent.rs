@@ -12,6 +12,10 @@ pub struct SilentOutput;
 
 #[async_trait]
 impl OutputHandler for SilentOutput {
+    fn name(&self) -> &'static str {
+        "silent"
+    }
+
     async fn emit_match(&self, _result: &MatchResult) -> anyhow::Result<()> {
         // Intentionally do nothing
         Ok(())
@@ -32,8 +36,10 @@ mod tests {
         let handler = SilentOutput;
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -0,0 +1,146 @@
+// src/output/spool.rs
+//! On-disk overflow for an output whose queue is full or whose destination is down
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::warn;
+
+use crate::metrics;
+use crate::types::MatchResult;
+
+/// Matches waiting for one output handler, one JSON object per line
+///
+/// A replay moves the file aside to `<handler>.replaying.jsonl` and only
+/// deletes it once every match in it has been emitted or spooled again, so a
+/// crash mid-replay sends them again on the next run instead of losing them.
+/// Only one worker replays a spool at a time (see [`claim_replay`](Self::claim_replay)).
+pub struct OutputSpool {
+    handler: String,
+    path: PathBuf,
+    replaying_path: PathBuf,
+    lock: Mutex<()>,
+    /// Held for the whole of a replay
+    replay: Mutex<()>,
+    pending: AtomicUsize,
+    /// Lines in the replay file being worked through
+    replaying: AtomicUsize,
+}
+
+impl OutputSpool {
+    /// Open `<dir>/<handler>.jsonl`, picking up matches left over from a previous run
+    pub fn open(dir: &std::path::Path, handler: &str) -> Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create spool directory {:?}", dir))?;
+        let path = dir.join(format!("{}.jsonl", handler));
+        let replaying_path = dir.join(format!("{}.replaying.jsonl", handler));
+        let pending = count_lines(&path)? + count_lines(&replaying_path)?;
+        metrics::OUTPUT_SPOOLED.with_label_values(&[handler]).set(pending as i64);
+
+        Ok(Self {
+            handler: handler.to_string(),
+            path,
+            replaying_path,
+            lock: Mutex::new(()),
+            replay: Mutex::new(()),
+            pending: AtomicUsize::new(pending),
+            replaying: AtomicUsize::new(0),
+        })
+    }
+
+    /// Number of matches waiting in the file
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Append matches
+    pub async fn push(&self, results: &[&MatchResult]) -> Result<()> {
+        let mut lines = Vec::new();
+        for result in results {
+            serde_json::to_writer(&mut lines, result).context("Failed to serialize match")?;
+            lines.push(b'\n');
+        }
+
+        let _guard = self.lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open spool file {:?}", self.path))?;
+        file.write_all(&lines).await.context("Failed to write spool file")?;
+        file.flush().await.context("Failed to write spool file")?;
+
+        let pending = self.pending.fetch_add(results.len(), Ordering::Relaxed) + results.len();
+        metrics::OUTPUT_SPOOLED.with_label_values(&[&self.handler]).set(pending as i64);
+        Ok(())
+    }
+
+    /// Claim the spool for a replay, held until the guard is dropped
+    ///
+    /// `None` while another worker is replaying it.
+    pub fn claim_replay(&self) -> Option<MutexGuard<'_, ()>> {
+        self.replay.try_lock().ok()
+    }
+
+    /// Start a replay: move the spooled matches aside and return them
+    ///
+    /// A replay file left by an interrupted run is resumed first. The matches
+    /// stay on disk until [`finish_replay`](Self::finish_replay).
+    pub async fn start_replay(&self) -> Result<Vec<MatchResult>> {
+        let _guard = self.lock.lock().await;
+        let content = match tokio::fs::read_to_string(&self.replaying_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                match tokio::fs::rename(&self.path, &self.replaying_path).await {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                    Err(e) => return Err(e).context("Failed to move spool file aside"),
+                }
+                tokio::fs::read_to_string(&self.replaying_path)
+                    .await
+                    .context("Failed to read spool file")?
+            }
+            Err(e) => return Err(e).context("Failed to read spool file"),
+        };
+
+        let mut results = Vec::new();
+        let mut lines = 0;
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            lines += 1;
+            match serde_json::from_str(line) {
+                Ok(result) => results.push(result),
+                Err(e) => warn!("Skipping corrupt spool file entry: {}", e),
+            }
+        }
+
+        self.replaying.store(lines, Ordering::Relaxed);
+        Ok(results)
+    }
+
+    /// Every match of the current replay has been emitted or spooled again
+    pub async fn finish_replay(&self) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        match tokio::fs::remove_file(&self.replaying_path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).context("Failed to remove spool replay file"),
+        }
+
+        let replayed = self.replaying.swap(0, Ordering::Relaxed);
+        let pending = self.pending.load(Ordering::Relaxed).saturating_sub(replayed);
+        self.pending.store(pending, Ordering::Relaxed);
+        metrics::OUTPUT_SPOOLED.with_label_values(&[&self.handler]).set(pending as i64);
+        Ok(())
+    }
+}
+
+/// Non-empty lines in `path` (0 if it doesn't exist)
+fn count_lines(path: &std::path::Path) -> Result<usize> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content.lines().filter(|line| !line.is_empty()).count()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e).with_context(|| format!("Failed to read spool file {:?}", path)),
+    }
+}
@@ -0,0 +1,122 @@
+// src/output/timestamps.rs
+//! How the human, JSON and CSV outputs render timestamps
+
+use anyhow::Result;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+
+/// Match fields holding Unix timestamps
+const JSON_FIELDS: &[&str] = &["timestamp", "not_before", "not_after", "logged_at", "processed_at", "seen_unix"];
+
+/// Timestamp rendering shared by the line-oriented outputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimestampFormat {
+    /// Some(true) = RFC 3339 everywhere, Some(false) = Unix seconds everywhere,
+    /// None = each output's own (dates for human, Unix seconds for JSON/CSV)
+    rfc3339: Option<bool>,
+    /// Local time zone instead of UTC
+    local: bool,
+}
+
+impl TimestampFormat {
+    /// Parse `[output] timestamp_format` and `timezone`
+    pub fn from_config(format: Option<&str>, timezone: &str) -> Result<Self> {
+        let rfc3339 = match format {
+            None => None,
+            Some("unix") => Some(false),
+            Some("rfc3339") => Some(true),
+            Some(other) => anyhow::bail!(
+                "Invalid output.timestamp_format '{}'. Must be 'unix' or 'rfc3339'",
+                other
+            ),
+        };
+        let local = match timezone {
+            "utc" => false,
+            "local" => true,
+            other => anyhow::bail!("Invalid output.timezone '{}'. Must be 'utc' or 'local'", other),
+        };
+        Ok(Self { rfc3339, local })
+    }
+
+    /// Timestamp for human-readable output
+    pub fn human(&self, ts: u64) -> String {
+        match self.rfc3339 {
+            Some(true) => self.rfc3339(ts as f64),
+            Some(false) => ts.to_string(),
+            None => self.datetime(ts as f64, |dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| ts.to_string()),
+        }
+    }
+
+    /// Timestamp for a CSV field; fractional seconds (seen_unix) are kept
+    pub fn field(&self, ts: f64) -> String {
+        if self.rfc3339 == Some(true) {
+            self.rfc3339(ts)
+        } else {
+            ts.to_string()
+        }
+    }
+
+    /// Rewrite the timestamp fields of a serialized match as RFC 3339 strings
+    pub fn apply_json(&self, value: &mut serde_json::Value) {
+        if self.rfc3339 != Some(true) {
+            return;
+        }
+        let Some(object) = value.as_object_mut() else {
+            return;
+        };
+        for field in JSON_FIELDS {
+            if let Some(entry) = object.get_mut(*field)
+                && let Some(ts) = entry.as_f64()
+            {
+                *entry = serde_json::Value::String(self.rfc3339(ts));
+            }
+        }
+    }
+
+    fn rfc3339(&self, ts: f64) -> String {
+        let format = if ts.fract() == 0.0 { SecondsFormat::Secs } else { SecondsFormat::Millis };
+        self.datetime(ts, |dt| dt.to_rfc3339_opts(format, true))
+            .unwrap_or_else(|| ts.to_string())
+    }
+
+    fn datetime(&self, ts: f64, render: impl Fn(DateTime<chrono::FixedOffset>) -> String) -> Option<String> {
+        let nanos = (ts.fract() * 1e9).round() as u32;
+        let utc: DateTime<Utc> = DateTime::from_timestamp(ts.trunc() as i64, nanos.min(999_999_999))?;
+        Some(if self.local {
+            render(utc.with_timezone(&Local).fixed_offset())
+        } else {
+            render(utc.fixed_offset())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc3339_utc() {
+        let format = TimestampFormat::from_config(Some("rfc3339"), "utc").unwrap();
+        assert_eq!(format.human(1234567890), "2009-02-13T23:31:30Z");
+        assert_eq!(format.field(1234567890.5), "2009-02-13T23:31:30.500Z");
+
+        let mut value = serde_json::json!({"timestamp": 1234567890, "not_after": null, "matched_domain": "a.com"});
+        format.apply_json(&mut value);
+        assert_eq!(value["timestamp"], "2009-02-13T23:31:30Z");
+        assert!(value["not_after"].is_null());
+        assert_eq!(value["matched_domain"], "a.com");
+    }
+
+    #[test]
+    fn test_default_keeps_each_outputs_format() {
+        let format = TimestampFormat::default();
+        assert_eq!(format.human(1234567890), "2009-02-13 23:31:30");
+        assert_eq!(format.field(1234567890.0), "1234567890");
+
+        let unix = TimestampFormat::from_config(Some("unix"), "utc").unwrap();
+        assert_eq!(unix.human(1234567890), "1234567890");
+
+        assert!(TimestampFormat::from_config(Some("iso"), "utc").is_err());
+        assert!(TimestampFormat::from_config(None, "Europe/Berlin").is_err());
+    }
+}
@@ -2,24 +2,45 @@
 //! Webhook output handler - sends HTTP POST notifications
 
 use crate::config::WebhookConfig;
+use crate::metrics;
 use crate::output::OutputHandler;
-use crate::types::MatchResult;
+use crate::output::routing::MatchFilter;
+use crate::stats::StatsCollector;
+use crate::types::{Enrichment, MatchResult, SCHEMA_VERSION};
 use async_trait::async_trait;
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use reqwest::{Client, Url};
+use schemars::JsonSchema;
 use serde::Serialize;
 use sha2::Sha256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tracing::warn;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Deliveries needed before an endpoint's failure rate is judged
+const MIN_DELIVERIES_FOR_WARNING: usize = 10;
+
 /// Webhook output handler
 pub struct WebhookOutput {
     client: Client,
     config: WebhookConfig,
+    /// Endpoint name in stats and metrics: the configured name, else only the
+    /// host, as paths often carry tokens
+    endpoint: String,
+    /// Matches this webhook is configured to receive
+    filter: MatchFilter,
+    stats: StatsCollector,
+    /// Failure rate warning already logged; re-armed once the endpoint recovers
+    failing: AtomicBool,
 }
 
-#[derive(Serialize)]
-struct WebhookPayload<'a> {
+/// Body POSTed for each match
+#[derive(Serialize, JsonSchema)]
+pub struct WebhookPayload<'a> {
+    /// Layout version, see `MatchResult::schema_version`
+    schema_version: u32,
     matched_domain: &'a str,
     all_domains: &'a [String],
     cert_index: Option<u64>,
@@ -28,22 +49,164 @@ struct WebhookPayload<'a> {
     program_name: Option<&'a str>,
     timestamp: u64,
     fingerprint: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pem: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enrichment: Option<&'a Enrichment>,
 }
 
 impl WebhookOutput {
     /// Create a new WebhookOutput
-    pub fn new(config: WebhookConfig) -> Self {
-        Self {
+    ///
+    /// Fails if the configured severity filter is invalid.
+    pub fn new(config: WebhookConfig) -> anyhow::Result<Self> {
+        let filter = MatchFilter::new(
+            config.program.clone(),
+            config.platform.clone(),
+            config.severity.as_deref(),
+            config.tags.clone(),
+        )?;
+        Ok(Self {
             client: Client::new(),
+            endpoint: config.name.clone().unwrap_or_else(|| endpoint_name(&config.url)),
+            filter,
             config,
+            stats: StatsCollector::new(),
+            failing: AtomicBool::new(false),
+        })
+    }
+
+    /// Record delivery receipts in `stats` instead of a private collector
+    pub fn with_stats(mut self, stats: StatsCollector) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    async fn deliver(&self, body: Vec<u8>) -> anyhow::Result<()> {
+        let timeout_secs = self.config.timeout_secs.unwrap_or(5);
+        let mut req = self
+            .client
+            .post(&self.config.url)
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .body(body.clone())
+            .header("Content-Type", "application/json");
+
+        for (name, value) in signature_headers(&self.config, &body, chrono::Utc::now().timestamp())? {
+            req = req.header(name, value);
+        }
+
+        let resp = req.send().await?;
+        resp.error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Count a delivery and warn once the endpoint fails too often
+    fn record(&self, outcome: &anyhow::Result<()>, started: Instant) {
+        let status = if outcome.is_ok() { "success" } else { "failure" };
+        metrics::WEBHOOK_DELIVERIES_TOTAL
+            .with_label_values(&[&self.endpoint, status])
+            .inc();
+        if outcome.is_ok() {
+            metrics::WEBHOOK_DELIVERY_DURATION
+                .with_label_values(&[&self.endpoint])
+                .observe(started.elapsed().as_secs_f64());
+        }
+
+        let stats = self.stats.record_webhook(
+            &self.endpoint,
+            match outcome {
+                Ok(()) => Ok(started.elapsed()),
+                Err(e) => Err(format!("{:#}", e)),
+            },
+        );
+
+        let threshold = self.config.failure_warn_percent;
+        if threshold == 0 || stats.recent < MIN_DELIVERIES_FOR_WARNING {
+            return;
+        }
+        if stats.failure_rate * 100.0 >= threshold as f64 {
+            if !self.failing.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "Webhook {} failed {:.0}% of the last {} deliveries (last error: {})",
+                    self.endpoint,
+                    stats.failure_rate * 100.0,
+                    stats.recent,
+                    stats.last_error.as_deref().unwrap_or("none")
+                );
+            }
+        } else {
+            self.failing.store(false, Ordering::Relaxed);
         }
     }
 }
 
+/// Signature headers for a webhook body, if a secret is configured
+///
+/// `X-CTScout-Signature` is `v1=` followed by the hex HMAC-SHA256 of
+/// `v1:<timestamp>:<body>`, with the timestamp sent in `X-CTScout-Timestamp`,
+/// so receivers can reject stale or replayed requests. `legacy_signature`
+/// signs the body alone, without the prefix.
+pub fn signature_headers(
+    config: &WebhookConfig,
+    body: &[u8],
+    timestamp: i64,
+) -> anyhow::Result<Vec<(&'static str, String)>> {
+    let Some(secret) = &config.secret else {
+        return Ok(Vec::new());
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| anyhow::anyhow!("HMAC init error: {:?}", e))?;
+
+    let signature = if config.legacy_signature {
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    } else {
+        mac.update(format!("v1:{}:", timestamp).as_bytes());
+        mac.update(body);
+        format!("v1={}", hex::encode(mac.finalize().into_bytes()))
+    };
+
+    Ok(vec![
+        ("X-CTScout-Timestamp", timestamp.to_string()),
+        ("X-CTScout-Signature", signature),
+    ])
+}
+
+/// `host[:port]` of a webhook URL
+fn endpoint_name(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| "webhook".to_string())
+}
+
 #[async_trait]
 impl OutputHandler for WebhookOutput {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn spool_name(&self) -> String {
+        match self.config.name {
+            Some(ref name) => format!("webhook-{}", name),
+            None => "webhook".to_string(),
+        }
+    }
+
+    fn accepts(&self, result: &MatchResult) -> bool {
+        self.filter.selects(result)
+    }
+
     async fn emit_match(&self, result: &MatchResult) -> anyhow::Result<()> {
         let payload = WebhookPayload {
+            schema_version: SCHEMA_VERSION,
             matched_domain: &result.matched_domain,
             all_domains: &result.all_domains,
             cert_index: result.cert_index,
@@ -52,32 +215,16 @@ impl OutputHandler for WebhookOutput {
             program_name: result.program_name.as_deref(),
             timestamp: result.timestamp,
             fingerprint: result.fingerprint.as_deref(),
+            pem: result.pem.as_deref(),
+            enrichment: result.enrichment.as_ref(),
         };
 
         let body = serde_json::to_vec(&payload)?;
 
-        let timeout_secs = self.config.timeout_secs.unwrap_or(5);
-        let mut req = self
-            .client
-            .post(&self.config.url)
-            .timeout(std::time::Duration::from_secs(timeout_secs))
-            .body(body.clone())
-            .header("Content-Type", "application/json");
-
-        // Add HMAC signature if secret is configured
-        if let Some(secret) = &self.config.secret {
-            let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-                .map_err(|e| anyhow::anyhow!("HMAC init error: {:?}", e))?;
-            mac.update(&body);
-            let sig = mac.finalize().into_bytes();
-            let sig_hex = hex::encode(sig);
-            req = req.header("X-CTScout-Signature", sig_hex);
-        }
-
-        let resp = req.send().await?;
-        resp.error_for_status()?;
-
-        Ok(())
+        let started = Instant::now();
+        let outcome = self.deliver(body).await;
+        self.record(&outcome, started);
+        outcome
     }
 
     async fn flush(&self) -> anyhow::Result<()> {
@@ -108,14 +255,17 @@ mod tests {
             url: mock_server.uri(),
             secret: None,
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
-        let handler = WebhookOutput::new(config);
+        let handler = WebhookOutput::new(config).unwrap();
 
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -145,14 +295,17 @@ mod tests {
             url: mock_server.uri(),
             secret: Some("test_secret".to_string()),
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
-        let handler = WebhookOutput::new(config);
+        let handler = WebhookOutput::new(config).unwrap();
 
         let cert_data = CertData {
             all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
             cert_index: Some(123),
             seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
             leaf_cert: None,
             is_precert: false,
             ct_log_url: None,
@@ -167,4 +320,102 @@ mod tests {
 
         assert!(handler.emit_match(&result).await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_delivery_receipts() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/ok"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let cert_data = CertData {
+            all_domains: Some(vec!["test.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(123),
+            seen_unix: Some(1234567890.0),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let result = MatchResult::from_cert_data("test.com".to_string(), &cert_data, None, None);
+
+        let stats = StatsCollector::new();
+        let handler = WebhookOutput::new(WebhookConfig {
+            url: format!("{}/ok", mock_server.uri()),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_stats(stats.clone());
+        handler.emit_match(&result).await.unwrap();
+
+        let endpoint = mock_server.address().to_string();
+        let receipts = &stats.webhooks()[&endpoint];
+        assert_eq!((receipts.delivered, receipts.failed), (1, 0));
+        assert!(receipts.p95_ms.is_some());
+
+        let handler = WebhookOutput::new(WebhookConfig {
+            url: format!("{}/down", mock_server.uri()),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_stats(stats.clone());
+        for _ in 0..MIN_DELIVERIES_FOR_WARNING {
+            assert!(handler.emit_match(&result).await.is_err());
+        }
+        assert!(handler.failing.load(Ordering::Relaxed));
+
+        let receipts = &stats.webhooks()[&endpoint];
+        assert_eq!((receipts.delivered, receipts.failed), (1, 10));
+        assert!(receipts.last_error.as_deref().unwrap().contains("503"));
+        assert!((receipts.failure_rate - 10.0 / 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_webhook_filters() {
+        let cert_data = CertData {
+            all_domains: Some(vec!["api.acme.com".to_string()]),
+            ip_addresses: None,
+            cert_index: Some(1),
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let mut result = MatchResult::from_cert_data("api.acme.com".to_string(), &cert_data, Some("Acme".to_string()), None);
+
+        let handler = WebhookOutput::new(WebhookConfig {
+            name: Some("pager".to_string()),
+            url: "https://pager.example.com/x".to_string(),
+            program: Some("acme*".to_string()),
+            severity: Some(">=high".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(handler.spool_name(), "webhook-pager");
+        assert_eq!(handler.endpoint, "pager");
+        assert!(!handler.accepts(&result));
+        result.severity = Some(crate::types::Severity::Critical);
+        assert!(handler.accepts(&result));
+
+        // Unnamed webhooks keep the original spool file and are labelled by host
+        let handler = WebhookOutput::new(WebhookConfig {
+            url: "https://hooks.example.com:8443/T000/secret-token".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(handler.spool_name(), "webhook");
+        assert_eq!(handler.endpoint, "hooks.example.com:8443");
+
+        let invalid = WebhookConfig { severity: Some("urgent".to_string()), ..Default::default() };
+        assert!(WebhookOutput::new(invalid).is_err());
+    }
 }
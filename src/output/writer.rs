@@ -0,0 +1,159 @@
+// src/output/writer.rs
+//! Line writer shared by the human, JSON and CSV outputs
+
+use futures_util::future::BoxFuture;
+use std::io::{self, BufWriter, Write};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Destination for line-oriented output
+///
+/// Stdout is flushed after every match so it stays interactive. Files are
+/// buffered and flushed at most once per `flush_interval`, plus in the
+/// background so a quiet period doesn't leave matches sitting in memory.
+/// [`persisted`](Self::persisted) lets deliveries wait for that flush, so the
+/// saved log index never gets ahead of what is on disk.
+pub struct OutputWriter {
+    inner: Box<dyn Write + Send>,
+    flush_interval: Duration,
+    last_flush: Instant,
+    /// Matches completed with `end_match`
+    written: u64,
+    /// `written` as of the last successful flush
+    flushed: watch::Sender<u64>,
+}
+
+impl OutputWriter {
+    /// Unbuffered writer for stdout
+    pub fn stdout() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            inner: Box::new(io::stdout()),
+            flush_interval: Duration::ZERO,
+            last_flush: Instant::now(),
+            written: 0,
+            flushed: watch::Sender::new(0),
+        }))
+    }
+
+    /// Buffered writer for a file, flushed every `flush_interval`
+    /// (zero = after every match)
+    ///
+    /// The background flush needs a Tokio runtime; without one the buffer is
+    /// only flushed as matches are written and on [`OutputHandler::flush`](super::OutputHandler::flush).
+    pub fn file(file: std::fs::File, flush_interval: Duration) -> Arc<Mutex<Self>> {
+        let writer = Arc::new(Mutex::new(Self {
+            inner: Box::new(BufWriter::new(file)),
+            flush_interval,
+            last_flush: Instant::now(),
+            written: 0,
+            flushed: watch::Sender::new(0),
+        }));
+
+        if !flush_interval.is_zero()
+            && let Ok(runtime) = tokio::runtime::Handle::try_current()
+        {
+            runtime.spawn(flush_periodically(Arc::downgrade(&writer), flush_interval));
+        }
+        writer
+    }
+
+    /// Call after writing a complete match; flushes if the interval has passed
+    pub fn end_match(&mut self) -> io::Result<()> {
+        self.written += 1;
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Resolves once every match written so far has been flushed (None if
+    /// it already has); false if the writer is dropped first
+    pub fn persisted(&self) -> Option<BoxFuture<'static, bool>> {
+        let written = self.written;
+        if *self.flushed.borrow() >= written {
+            return None;
+        }
+        let mut flushed = self.flushed.subscribe();
+        Some(Box::pin(async move {
+            flushed.wait_for(|&flushed| flushed >= written).await.is_ok()
+        }))
+    }
+}
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.inner.flush()?;
+        self.flushed.send_replace(self.written);
+        Ok(())
+    }
+}
+
+/// Flush `writer` every `interval` until its output is dropped
+async fn flush_periodically(writer: Weak<Mutex<OutputWriter>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let Some(writer) = writer.upgrade() else {
+            break;
+        };
+        let mut writer = writer.lock().unwrap();
+        if writer.last_flush.elapsed() >= interval
+            && let Err(e) = writer.flush()
+        {
+            tracing::warn!("Failed to flush output file: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_writer_flushes_in_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let writer = OutputWriter::file(std::fs::File::create(&path).unwrap(), Duration::from_millis(50));
+
+        {
+            let mut writer = writer.lock().unwrap();
+            writeln!(writer, "first").unwrap();
+            writer.end_match().unwrap();
+        }
+        // Buffered until the interval has passed
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\n");
+    }
+
+    #[tokio::test]
+    async fn test_persisted_waits_for_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let writer = OutputWriter::file(std::fs::File::create(&path).unwrap(), Duration::from_millis(50));
+
+        let persisted = {
+            let mut writer = writer.lock().unwrap();
+            writeln!(writer, "first").unwrap();
+            writer.end_match().unwrap();
+            writer.persisted().expect("match is still buffered")
+        };
+        assert!(persisted.await);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\n");
+        assert!(writer.lock().unwrap().persisted().is_none());
+
+        // Unbuffered writers are persisted right away
+        let stdout = OutputWriter::stdout();
+        let mut stdout = stdout.lock().unwrap();
+        stdout.end_match().unwrap();
+        assert!(stdout.persisted().is_none());
+    }
+}
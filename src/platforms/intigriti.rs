@@ -121,6 +121,7 @@ impl IntigritiAPI {
     }
 
     /// Fetch program details including scope
+    #[allow(clippy::collapsible_if)]
     async fn fetch_program_details(&self, program_id: &str) -> Result<Vec<String>> {
         debug!("Fetching scope for program: {}", program_id);
 
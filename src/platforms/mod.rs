@@ -3,13 +3,18 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use tracing::{error, info, warn};
+
+use crate::config::PlatformsConfig;
 
 pub mod hackerone;
 pub mod intigriti;
+pub mod snapshot;
 pub mod sync;
 
 pub use hackerone::HackerOneAPI;
 pub use intigriti::IntigritiAPI;
+pub use snapshot::{request_rollback, Snapshot, SnapshotHistory, SnapshotInfo};
 pub use sync::PlatformSyncManager;
 
 /// Represents a bug bounty program with its scope
@@ -72,11 +77,84 @@ pub trait PlatformAPI: Send + Sync {
     async fn test_connection(&self) -> Result<bool>;
 }
 
+/// Build clients for every enabled platform, keeping only those whose
+/// credentials check out
+#[allow(clippy::collapsible_if)]
+pub async fn connect_enabled(config: &PlatformsConfig) -> Result<Vec<Box<dyn PlatformAPI>>> {
+    let mut platforms: Vec<Box<dyn PlatformAPI>> = Vec::new();
+
+    // Initialize HackerOne if configured
+    if let Some(h1_config) = &config.hackerone {
+        if h1_config.enabled {
+            info!("Initializing HackerOne API integration");
+
+            // Get filter and max_programs from config with defaults
+            let filter = h1_config.filter.clone();
+            let max_programs = h1_config.max_programs.unwrap_or(config.max_programs_per_platform);
+
+            let h1_api = HackerOneAPI::new(
+                h1_config.username.clone(),
+                h1_config.api_token.clone(),
+                filter.clone(),
+                max_programs,
+            )?;
+
+            // Test connection
+            match h1_api.test_connection().await {
+                Ok(true) => {
+                    info!("HackerOne API connection successful (filter: {}, max: {})", filter, max_programs);
+                    platforms.push(Box::new(h1_api));
+                }
+                Ok(false) => {
+                    warn!("HackerOne API connection failed (invalid credentials?)");
+                }
+                Err(e) => {
+                    error!("HackerOne API connection error: {:?}", e);
+                }
+            }
+        }
+    }
+
+    // Initialize Intigriti if configured
+    if let Some(intigriti_config) = &config.intigriti {
+        if intigriti_config.enabled {
+            info!("Initializing Intigriti API integration");
+
+            // Get filter and max_programs from config with defaults
+            let filter = intigriti_config.filter.clone();
+            let max_programs = intigriti_config.max_programs.unwrap_or(config.max_programs_per_platform);
+
+            let intigriti_api = IntigritiAPI::new(
+                intigriti_config.api_token.clone(),
+                filter.clone(),
+                max_programs,
+            )?;
+
+            // Test connection
+            match intigriti_api.test_connection().await {
+                Ok(true) => {
+                    info!("Intigriti API connection successful (filter: {}, max: {})", filter, max_programs);
+                    platforms.push(Box::new(intigriti_api));
+                }
+                Ok(false) => {
+                    warn!("Intigriti API connection failed (invalid credentials?)");
+                }
+                Err(e) => {
+                    error!("Intigriti API connection error: {:?}", e);
+                }
+            }
+        }
+    }
+
+    Ok(platforms)
+}
+
 /// Extract domain from URL or pattern
 /// Examples:
 /// - "https://example.com" -> "example.com"
 /// - "*.example.com" -> "*.example.com"
 /// - "example.com" -> "example.com"
+#[allow(clippy::collapsible_if)]
 pub fn extract_domain(url_or_pattern: &str) -> String {
     let trimmed = url_or_pattern.trim();
 
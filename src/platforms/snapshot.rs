@@ -0,0 +1,256 @@
+// src/platforms/snapshot.rs
+//! Copies of the watchlist taken before platform syncs change it
+//!
+//! A platform API returning garbage would otherwise leave the watchlist broken
+//! until restart. The last few copies are kept, and one can be put back with
+//! `POST /watchlist/rollback` or `ct-scout watchlist rollback`.
+//!
+//! With a snapshot directory, each copy is also written to `<dir>/<id>.json`,
+//! so the copies outlive a restart and the CLI can see them. The CLI can't
+//! reach into a running instance, so it leaves a rollback request file in the
+//! directory, which `run` picks up on its next check.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::state::unix_now;
+use crate::watchlist::Watchlist;
+
+/// File holding the snapshot id a running instance should roll back to
+const ROLLBACK_REQUEST: &str = "rollback-request";
+
+/// Watchlist as it was at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: u64,
+    pub taken_at: u64,  // Unix timestamp
+    pub reason: String,  // e.g. "before HackerOne sync"
+    pub watchlist: Watchlist,
+}
+
+/// What a snapshot holds, without the entries themselves
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotInfo {
+    pub id: u64,
+    pub taken_at: u64,
+    pub reason: String,
+    pub programs: usize,
+    pub domains: usize,
+    pub hosts: usize,
+}
+
+impl Snapshot {
+    pub fn info(&self) -> SnapshotInfo {
+        let watchlist = &self.watchlist;
+        SnapshotInfo {
+            id: self.id,
+            taken_at: self.taken_at,
+            reason: self.reason.clone(),
+            programs: watchlist.programs.len(),
+            domains: watchlist.global_domains.len()
+                + watchlist.programs.iter().map(|program| program.domains.len()).sum::<usize>(),
+            hosts: watchlist.global_hosts.len()
+                + watchlist.programs.iter().map(|program| program.hosts.len()).sum::<usize>(),
+        }
+    }
+}
+
+/// The most recent `keep` snapshots, oldest first
+#[derive(Debug)]
+pub struct SnapshotHistory {
+    keep: usize,
+    next_id: u64,
+    snapshots: VecDeque<Snapshot>,
+    dir: Option<PathBuf>,  // Where snapshots are written, if anywhere
+}
+
+impl SnapshotHistory {
+    /// Keep up to `keep` snapshots (0 = take none)
+    pub fn new(keep: usize) -> Self {
+        Self {
+            keep,
+            next_id: 1,
+            snapshots: VecDeque::new(),
+            dir: None,
+        }
+    }
+
+    /// Also write snapshots to `dir`, picking up the ones earlier runs left there
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        if self.keep == 0 {
+            return Ok(self);
+        }
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create snapshot directory {:?}", dir))?;
+
+        let mut snapshots = Vec::new();
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read snapshot directory {:?}", dir))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            match read_snapshot(&path) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => warn!("Skipping watchlist snapshot {:?}: {:#}", path, e),
+            }
+        }
+        snapshots.sort_by_key(|snapshot| snapshot.id);
+
+        self.next_id = snapshots.last().map_or(1, |snapshot| snapshot.id + 1);
+        self.snapshots = snapshots.into();
+        self.dir = Some(dir);
+        while self.snapshots.len() > self.keep {
+            self.drop_oldest();
+        }
+        Ok(self)
+    }
+
+    /// Copy `watchlist`, dropping the oldest snapshot if the history is full
+    ///
+    /// Returns the new snapshot's id, or `None` if snapshots are disabled.
+    pub fn take(&mut self, watchlist: &Watchlist, reason: impl Into<String>) -> Option<u64> {
+        if self.keep == 0 {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.snapshots.len() == self.keep {
+            self.drop_oldest();
+        }
+        let snapshot = Snapshot {
+            id,
+            taken_at: unix_now(),
+            reason: reason.into(),
+            watchlist: watchlist.clone(),
+        };
+        if let Some(ref dir) = self.dir
+            && let Err(e) = write_snapshot(dir, &snapshot)
+        {
+            warn!("Failed to write watchlist snapshot {}: {:#}", id, e);
+        }
+        self.snapshots.push_back(snapshot);
+        Some(id)
+    }
+
+    /// Snapshot `id`, or the newest one
+    pub fn get(&self, id: Option<u64>) -> Option<&Snapshot> {
+        match id {
+            Some(id) => self.snapshots.iter().find(|snapshot| snapshot.id == id),
+            None => self.snapshots.back(),
+        }
+    }
+
+    /// Every kept snapshot, newest first
+    pub fn list(&self) -> Vec<SnapshotInfo> {
+        self.snapshots.iter().rev().map(Snapshot::info).collect()
+    }
+
+    /// Snapshot id left by [`request_rollback`], removing the request
+    pub fn take_rollback_request(&self) -> Option<u64> {
+        let path = self.dir.as_ref()?.join(ROLLBACK_REQUEST);
+        let content = std::fs::read_to_string(&path).ok()?;
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove watchlist rollback request {:?}: {}", path, e);
+        }
+        match content.trim().parse() {
+            Ok(id) => Some(id),
+            Err(_) => {
+                warn!("Ignoring watchlist rollback request for '{}'", content.trim());
+                None
+            }
+        }
+    }
+
+    fn drop_oldest(&mut self) {
+        let Some(oldest) = self.snapshots.pop_front() else {
+            return;
+        };
+        if let Some(ref dir) = self.dir {
+            let path = snapshot_path(dir, oldest.id);
+            if let Err(e) = std::fs::remove_file(&path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                warn!("Failed to remove watchlist snapshot {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Ask the instance using `dir` to roll its watchlist back to snapshot `id`
+pub fn request_rollback(dir: &Path, id: u64) -> Result<()> {
+    let path = dir.join(ROLLBACK_REQUEST);
+    std::fs::write(&path, format!("{}\n", id))
+        .with_context(|| format!("Failed to write rollback request {:?}", path))
+}
+
+fn snapshot_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn read_snapshot(path: &Path) -> Result<Snapshot> {
+    let content = std::fs::read_to_string(path).context("Failed to read snapshot")?;
+    serde_json::from_str(&content).context("Failed to parse snapshot")
+}
+
+fn write_snapshot(dir: &Path, snapshot: &Snapshot) -> Result<()> {
+    let path = snapshot_path(dir, snapshot.id);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(snapshot)?)
+        .with_context(|| format!("Failed to write {:?}", tmp))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to rename {:?}", tmp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_keeps_newest() {
+        let mut history = SnapshotHistory::new(2);
+        let mut watchlist = Watchlist::default();
+        for domain in ["a.com", "b.com", "c.com"] {
+            watchlist.add_domain_to_program(domain, "Acme", None);
+            history.take(&watchlist, format!("after {}", domain));
+        }
+
+        let ids: Vec<u64> = history.list().iter().map(|info| info.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+        assert!(history.get(Some(1)).is_none());
+        assert_eq!(history.get(None).unwrap().info().domains, 3);
+        assert_eq!(history.get(Some(2)).unwrap().reason, "after b.com");
+
+        let mut disabled = SnapshotHistory::new(0);
+        assert_eq!(disabled.take(&watchlist, "sync"), None);
+        assert!(disabled.list().is_empty());
+    }
+
+    #[test]
+    fn test_history_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut watchlist = Watchlist::default();
+        let mut history = SnapshotHistory::new(2).with_dir(dir.path()).unwrap();
+        for domain in ["a.com", "b.com", "c.com"] {
+            watchlist.add_domain_to_program(domain, "Acme", Some("HackerOne".to_string()));
+            history.take(&watchlist, format!("after {}", domain));
+        }
+        assert!(!dir.path().join("1.json").exists());
+
+        let mut reopened = SnapshotHistory::new(2).with_dir(dir.path()).unwrap();
+        assert_eq!(reopened.list(), history.list());
+        let restored = &reopened.get(Some(3)).unwrap().watchlist;
+        assert_eq!(restored.programs[0].platform.as_deref(), Some("HackerOne"));
+        assert_eq!(restored.programs[0].domains.len(), 3);
+        assert_eq!(reopened.take(&watchlist, "sync"), Some(4));
+
+        assert_eq!(reopened.take_rollback_request(), None);
+        request_rollback(dir.path(), 3).unwrap();
+        assert_eq!(reopened.take_rollback_request(), Some(3));
+        assert_eq!(reopened.take_rollback_request(), None);
+    }
+}
@@ -5,17 +5,26 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use tokio::sync::{watch, Mutex};
-use tracing::{error, info};
+use tokio::sync::{watch, Mutex, Notify};
+use tracing::{error, info, warn};
 
 use super::PlatformAPI;
+use super::snapshot::{SnapshotHistory, SnapshotInfo};
+use crate::config::default_watchlist_snapshots;
 use crate::watchlist::Watchlist;
 
+/// How often `run` looks for a rollback request from `ct-scout watchlist rollback`
+const ROLLBACK_POLL: Duration = Duration::from_secs(5);
+
 /// Manages periodic synchronization with bug bounty platforms
 pub struct PlatformSyncManager {
     platforms: Vec<Box<dyn PlatformAPI>>,
     watchlist: Arc<Mutex<Watchlist>>,
     sync_interval: Duration,
+    /// Wakes `run` for a sync ahead of schedule
+    sync_requested: Notify,
+    /// Watchlist copies taken before each sync changes it; locked after `watchlist`
+    snapshots: Mutex<SnapshotHistory>,
 }
 
 impl PlatformSyncManager {
@@ -29,9 +38,55 @@ impl PlatformSyncManager {
             platforms,
             watchlist,
             sync_interval: Duration::from_secs(sync_interval_hours * 3600),
+            sync_requested: Notify::new(),
+            snapshots: Mutex::new(SnapshotHistory::new(default_watchlist_snapshots())),
         }
     }
 
+    /// Keep watchlist snapshots in `history` instead of the default in-memory one
+    pub fn with_snapshots(mut self, history: SnapshotHistory) -> Self {
+        self.snapshots = Mutex::new(history);
+        self
+    }
+
+    /// Kept watchlist snapshots, newest first
+    pub async fn snapshots(&self) -> Vec<SnapshotInfo> {
+        self.snapshots.lock().await.list()
+    }
+
+    /// Put the watchlist back as it was in snapshot `id` (or the newest one)
+    ///
+    /// The current watchlist is snapshotted first, so a rollback can itself be
+    /// rolled back. ASN prefixes are kept, since they aren't part of the scope.
+    pub async fn rollback(&self, id: Option<u64>) -> Result<SnapshotInfo> {
+        let mut watchlist = self.watchlist.lock().await;
+        let mut snapshots = self.snapshots.lock().await;
+        let snapshot = match snapshots.get(id) {
+            Some(snapshot) => snapshot.clone(),
+            None => match id {
+                Some(id) => anyhow::bail!("No watchlist snapshot {}", id),
+                None => anyhow::bail!("No watchlist snapshots have been taken"),
+            },
+        };
+
+        snapshots.take(&watchlist, format!("before rollback to snapshot {}", snapshot.id));
+        let asn_prefixes = std::mem::take(&mut watchlist.asn_prefixes);
+        *watchlist = snapshot.watchlist.clone();
+        watchlist.asn_prefixes = asn_prefixes;
+
+        let info = snapshot.info();
+        warn!(
+            "Watchlist rolled back to snapshot {} ({}, {} programs)",
+            info.id, info.reason, info.programs
+        );
+        Ok(info)
+    }
+
+    /// Have `run` sync now instead of waiting for the next interval
+    pub fn request_sync(&self) {
+        self.sync_requested.notify_one();
+    }
+
     /// Run the sync manager (blocks until shutdown signal received)
     pub async fn run(&self, mut shutdown_rx: watch::Receiver<bool>) {
         info!(
@@ -42,6 +97,7 @@ impl PlatformSyncManager {
         // Perform initial sync immediately
         self.sync_all_platforms().await;
 
+        let mut rollback_poll = tokio::time::interval(ROLLBACK_POLL);
         loop {
             tokio::select! {
                 // Wait for next sync interval
@@ -49,6 +105,21 @@ impl PlatformSyncManager {
                     self.sync_all_platforms().await;
                 }
 
+                _ = self.sync_requested.notified() => {
+                    info!("Platform sync requested");
+                    self.sync_all_platforms().await;
+                }
+
+                _ = rollback_poll.tick() => {
+                    let requested = self.snapshots.lock().await.take_rollback_request();
+                    if let Some(id) = requested {
+                        info!("Watchlist rollback to snapshot {} requested", id);
+                        if let Err(e) = self.rollback(Some(id)).await {
+                            error!("Failed to roll back the watchlist: {:#}", e);
+                        }
+                    }
+                }
+
                 // Check for shutdown signal
                 _ = shutdown_rx.changed() => {
                     info!("Platform sync manager shutting down");
@@ -59,7 +130,7 @@ impl PlatformSyncManager {
     }
 
     /// Sync watchlist from all configured platforms
-    async fn sync_all_platforms(&self) {
+    pub async fn sync_all_platforms(&self) {
         info!("Starting platform synchronization");
 
         for platform in &self.platforms {
@@ -96,6 +167,10 @@ impl PlatformSyncManager {
 
         // Update watchlist with new domains
         let mut watchlist = self.watchlist.lock().await;
+        self.snapshots
+            .lock()
+            .await
+            .take(&watchlist, format!("before {} sync", platform.name()));
         let mut total_domains_added = 0;
 
         for program in programs {
@@ -180,4 +255,32 @@ mod tests {
         let watchlist_lock = watchlist.lock().await;
         assert_eq!(watchlist_lock.programs().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_rollback_to_snapshot_before_sync() {
+        let mut initial = Watchlist::default();
+        initial.add_domain_to_program("*.config.com", "Config Program", None);
+        let watchlist = Arc::new(Mutex::new(initial));
+        let platforms: Vec<Box<dyn PlatformAPI>> = vec![Box::new(MockPlatform)];
+        let manager = PlatformSyncManager::new(platforms, watchlist.clone(), 24);
+
+        assert!(manager.rollback(None).await.is_err());
+
+        manager.sync_all_platforms().await;
+        assert_eq!(watchlist.lock().await.programs().len(), 2);
+
+        let snapshots = manager.snapshots().await;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].reason, "before Mock sync");
+        assert_eq!(snapshots[0].programs, 1);
+
+        let restored = manager.rollback(Some(snapshots[0].id)).await.unwrap();
+        assert_eq!(restored.id, snapshots[0].id);
+        assert_eq!(watchlist.lock().await.programs().len(), 1);
+        assert!(watchlist.lock().await.matches_domain("api.config.com"));
+
+        // The rollback can be undone
+        assert_eq!(manager.snapshots().await[0].programs, 2);
+        assert!(manager.rollback(Some(99)).await.is_err());
+    }
 }
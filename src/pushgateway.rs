@@ -0,0 +1,142 @@
+// src/pushgateway.rs
+//! Pushing the metrics registry to a Prometheus Pushgateway
+//!
+//! For instances Prometheus can't scrape (behind NAT, short-lived batch runs):
+//! the registry is PUT to `<url>/metrics/job/<job>[/instance/<instance>]` every
+//! interval and once more at shutdown, so the final counts of a run are kept.
+
+use anyhow::{Context, Result};
+use reqwest::{Client, Url};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::config::PushgatewayConfig;
+use crate::metrics;
+
+#[derive(Clone)]
+pub struct Pushgateway {
+    client: Client,
+    url: Url,
+    interval: Duration,
+}
+
+impl Pushgateway {
+    pub fn new(config: &PushgatewayConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build Pushgateway HTTP client")?;
+        Ok(Self {
+            client,
+            url: grouping_url(config)?,
+            interval: Duration::from_secs(config.interval_secs.max(1)),
+        })
+    }
+
+    /// Push now and then every interval until the task is aborted
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let pusher = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(pusher.interval);
+            loop {
+                ticker.tick().await;
+                // The gateway keeps the last push, so a failed one only leaves it stale
+                match pusher.push().await {
+                    Ok(()) => debug!("Metrics pushed to {}", pusher.url),
+                    Err(e) => warn!("Metrics push failed: {:#}", e),
+                }
+            }
+        })
+    }
+
+    /// Replace this instance's metric group with the current registry
+    pub async fn push(&self) -> Result<()> {
+        self.client
+            .put(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(metrics::export_metrics())
+            .send()
+            .await
+            .context("Failed to reach Pushgateway")?
+            .error_for_status()
+            .context("Pushgateway rejected the metrics")?;
+        Ok(())
+    }
+}
+
+/// `<url>/metrics/job/<job>[/instance/<instance>]`, with the labels percent-encoded
+fn grouping_url(config: &PushgatewayConfig) -> Result<Url> {
+    let mut url = Url::parse(&config.url).with_context(|| format!("Invalid Pushgateway URL '{}'", config.url))?;
+    {
+        let mut segments = url
+            .path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Pushgateway URL '{}'", config.url))?;
+        segments.pop_if_empty().extend(["metrics", "job", &config.job]);
+        if let Some(ref instance) = config.instance {
+            segments.extend(["instance", instance]);
+        }
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(url: String) -> PushgatewayConfig {
+        PushgatewayConfig {
+            url,
+            job: "ct-scout".to_string(),
+            instance: Some("scanner 1".to_string()),
+            interval_secs: 60,
+            timeout_secs: 5,
+        }
+    }
+
+    #[test]
+    fn test_grouping_url() {
+        let url = grouping_url(&config("http://pushgateway:9091/".to_string())).unwrap();
+        assert_eq!(url.as_str(), "http://pushgateway:9091/metrics/job/ct-scout/instance/scanner%201");
+
+        let config = PushgatewayConfig { instance: None, ..config("http://gw.example.com/prefix".to_string()) };
+        let url = grouping_url(&config).unwrap();
+        assert_eq!(url.as_str(), "http://gw.example.com/prefix/metrics/job/ct-scout");
+    }
+
+    #[tokio::test]
+    async fn test_push_puts_registry() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/metrics/job/ct-scout/instance/scanner%201"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        metrics::init_metrics().ok();
+        metrics::CERTIFICATES_PROCESSED_TOTAL.inc();
+
+        let pusher = Pushgateway::new(&config(mock_server.uri())).unwrap();
+        pusher.push().await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        assert!(body.contains("ctscout_certificates_processed_total"));
+    }
+
+    #[tokio::test]
+    async fn test_push_reports_rejection() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&mock_server)
+            .await;
+
+        let pusher = Pushgateway::new(&config(mock_server.uri())).unwrap();
+        let err = pusher.push().await.unwrap_err();
+        assert!(format!("{:#}", err).contains("rejected"));
+    }
+}
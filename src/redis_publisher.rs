@@ -2,24 +2,69 @@
 //!
 //! Publishes certificate matches directly to Redis channels,
 //! enabling real-time integration with automation pipelines.
+//! Matches can also be appended to a Redis Stream, which unlike pub/sub
+//! lets downstream workers replay what they missed and share the load
+//! through consumer groups.
+//!
+//! Besides a single server, the publisher can talk to a Redis Cluster or to
+//! the master of a Sentinel-managed deployment, optionally over TLS.
 
 use crate::metrics::{
     REDIS_PUBLISH_TOTAL, REDIS_PUBLISH_DURATION,
     REDIS_CONNECTION_STATUS, REDIS_RECONNECT_ATTEMPTS,
 };
-use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{AsyncCommands, RedisConnectionInfo, RedisFuture, TlsMode, Value};
 use serde::Serialize;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
+/// How the publisher reaches Redis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedisMode {
+    /// A single server (or managed endpoint such as Upstash) at `url`
+    #[default]
+    Standalone,
+    /// A Redis Cluster, discovered from the seed `nodes`
+    Cluster,
+    /// The master named `sentinel_master`, looked up through the Sentinels in `nodes`
+    Sentinel,
+}
+
+impl FromStr for RedisMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "standalone" => Ok(Self::Standalone),
+            "cluster" => Ok(Self::Cluster),
+            "sentinel" => Ok(Self::Sentinel),
+            other => anyhow::bail!("Invalid redis mode '{}'. Must be 'standalone', 'cluster' or 'sentinel'", other),
+        }
+    }
+}
+
 /// Redis publisher configuration
 #[derive(Debug, Clone)]
 pub struct RedisConfig {
     /// Redis URL (supports Upstash format: rediss://...)
     pub url: String,
+    /// Standalone server, Cluster or Sentinel
+    pub mode: RedisMode,
+    /// Cluster seed nodes or Sentinel addresses (`host:port` or URLs)
+    pub nodes: Vec<String>,
+    /// Master name to ask the Sentinels for
+    pub sentinel_master: Option<String>,
+    /// Use TLS even for `redis://` and bare `host:port` addresses
+    pub tls: bool,
+    /// Skip TLS certificate verification (self-signed certificates)
+    pub tls_insecure: bool,
     /// Optional auth token (for Upstash)
     pub token: Option<String>,
     /// Channel name for CT events
@@ -28,16 +73,33 @@ pub struct RedisConfig {
     pub queue_name: Option<String>,
     /// Maximum queue size (older items evicted)
     pub max_queue_size: Option<i64>,
+    /// PUBLISH to `channel`; turn off to use only the stream and/or queue
+    pub publish_channel: bool,
+    /// Also XADD every match to this stream (optional)
+    pub stream: Option<String>,
+    /// Approximate stream length kept (`MAXLEN ~`); None = unbounded
+    pub stream_maxlen: Option<u64>,
+    /// Consumer group created on the stream at connect time (optional)
+    pub stream_group: Option<String>,
 }
 
 impl Default for RedisConfig {
     fn default() -> Self {
         Self {
             url: "redis://localhost:6379".to_string(),
+            mode: RedisMode::Standalone,
+            nodes: Vec::new(),
+            sentinel_master: None,
+            tls: false,
+            tls_insecure: false,
             token: None,
             channel: "bb:ct_events".to_string(),
             queue_name: Some("bb:ct_events_queue".to_string()),
             max_queue_size: Some(10000),
+            publish_channel: true,
+            stream: None,
+            stream_maxlen: Some(100_000),
+            stream_group: None,
         }
     }
 }
@@ -71,10 +133,48 @@ pub struct CTEventMessage {
     pub is_precert: bool,
 }
 
+/// Connection to whichever kind of deployment is configured
+///
+/// Both variants reconnect on their own and are cheap to clone per publish.
+#[derive(Clone)]
+enum Connection {
+    /// Standalone server, or the master a Sentinel pointed us at
+    Single(Box<ConnectionManager>),
+    Cluster(ClusterConnection),
+}
+
+impl ConnectionLike for Connection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a redis::Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Single(conn) => conn.req_packed_command(cmd),
+            Self::Cluster(conn) => conn.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Single(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Single(conn) => conn.get_db(),
+            Self::Cluster(conn) => conn.get_db(),
+        }
+    }
+}
+
 /// Redis publisher with automatic reconnection
 pub struct RedisPublisher {
     config: RedisConfig,
-    connection: Arc<RwLock<Option<ConnectionManager>>>,
+    connection: Arc<RwLock<Option<Connection>>>,
     connected: Arc<RwLock<bool>>,
 }
 
@@ -102,18 +202,58 @@ impl RedisPublisher {
             self.config.url.clone()
         };
 
-        info!("Connecting to Redis...");
+        info!("Connecting to Redis ({:?})...", self.config.mode);
 
         // Track reconnection attempts
         REDIS_RECONNECT_ATTEMPTS.inc();
 
-        let client = redis::Client::open(url)?;
-        let manager = ConnectionManager::new(client).await?;
+        let manager = match self.config.mode {
+            RedisMode::Standalone => {
+                let client = redis::Client::open(self.node_url(&url))?;
+                Connection::Single(Box::new(ConnectionManager::new(client).await?))
+            }
+            RedisMode::Cluster => {
+                let nodes = if self.config.nodes.is_empty() {
+                    vec![self.node_url(&self.config.url)]
+                } else {
+                    self.config.nodes.iter().map(|node| self.node_url(node)).collect()
+                };
+                let mut builder = ClusterClient::builder(nodes);
+                if let Some(ref token) = self.config.token {
+                    builder = builder.password(token.clone());
+                }
+                Connection::Cluster(builder.build()?.get_async_connection().await?)
+            }
+            RedisMode::Sentinel => {
+                let master = self.config.sentinel_master.as_deref().ok_or_else(|| {
+                    redis::RedisError::from((
+                        redis::ErrorKind::InvalidClientConfig,
+                        "sentinel mode needs sentinel_master",
+                    ))
+                })?;
+                let sentinels: Vec<String> = self.config.nodes.iter().map(|node| self.node_url(node)).collect();
+                // The master's address comes from the Sentinels, so TLS and
+                // auth for it can't be taken from a URL
+                let master_info = SentinelNodeConnectionInfo {
+                    tls_mode: self.tls_mode(),
+                    redis_connection_info: Some(RedisConnectionInfo {
+                        password: self.config.token.clone(),
+                        ..Default::default()
+                    }),
+                };
+                let client = Sentinel::build(sentinels)?.async_master_for(master, Some(&master_info)).await?;
+                Connection::Single(Box::new(ConnectionManager::new(client).await?))
+            }
+        };
 
         // Test connection
         let mut conn = manager.clone();
         redis::cmd("PING").query_async::<String>(&mut conn).await?;
 
+        if let (Some(stream), Some(group)) = (&self.config.stream, &self.config.stream_group) {
+            Self::create_group(&mut conn, stream, group).await?;
+        }
+
         *self.connection.write().await = Some(manager);
         *self.connected.write().await = true;
 
@@ -124,6 +264,37 @@ impl RedisPublisher {
         Ok(())
     }
 
+    /// Apply the TLS settings to one address
+    ///
+    /// Bare `host:port` becomes a `redis://` URL; `tls` upgrades it to
+    /// `rediss://`, and `tls_insecure` adds the `#insecure` fragment the
+    /// redis crate reads to skip certificate verification.
+    fn node_url(&self, address: &str) -> String {
+        let mut url = if address.contains("://") {
+            address.to_string()
+        } else {
+            format!("redis://{}", address)
+        };
+        if self.config.tls
+            && let Some(rest) = url.strip_prefix("redis://")
+        {
+            url = format!("rediss://{}", rest);
+        }
+        if self.config.tls_insecure && url.starts_with("rediss://") && !url.contains('#') {
+            url.push_str("#insecure");
+        }
+        url
+    }
+
+    /// TLS mode for connections whose address doesn't come from a URL
+    fn tls_mode(&self) -> Option<TlsMode> {
+        match (self.config.tls, self.config.tls_insecure) {
+            (false, _) => None,
+            (true, false) => Some(TlsMode::Secure),
+            (true, true) => Some(TlsMode::Insecure),
+        }
+    }
+
     /// Check if connected
     pub async fn is_connected(&self) -> bool {
         *self.connected.read().await
@@ -164,11 +335,19 @@ impl RedisPublisher {
         let mut conn = conn;
 
         // Publish to channel (for real-time subscribers)
-        let subscribers: i64 = conn.publish(&self.config.channel, &payload).await?;
-        debug!(
-            "Published to channel {} ({} subscribers)",
-            self.config.channel, subscribers
-        );
+        if self.config.publish_channel {
+            let subscribers: i64 = conn.publish(&self.config.channel, &payload).await?;
+            debug!(
+                "Published to channel {} ({} subscribers)",
+                self.config.channel, subscribers
+            );
+        }
+
+        // Append to stream (for workers that need replay)
+        if let Some(ref stream) = self.config.stream {
+            let id: String = self.stream_entry(stream, &event, &payload).query_async(&mut conn).await?;
+            debug!("Added to stream {} as {}", stream, id);
+        }
 
         // Also push to queue for persistence (if configured)
         if let Some(ref queue_name) = self.config.queue_name {
@@ -190,6 +369,48 @@ impl RedisPublisher {
         Ok(())
     }
 
+    /// XADD command for one event
+    ///
+    /// IDs are left to Redis (`*`), so they always increase, which consumer
+    /// groups and `XREAD` from a last-seen ID rely on.
+    fn stream_entry(&self, stream: &str, event: &CTEventMessage, payload: &str) -> redis::Cmd {
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(stream);
+        if let Some(maxlen) = self.config.stream_maxlen {
+            cmd.arg("MAXLEN").arg("~").arg(maxlen);
+        }
+        cmd.arg("*")
+            .arg("matched_domain")
+            .arg(&event.matched_domain)
+            .arg("program")
+            .arg(event.program_name.as_deref().unwrap_or(""))
+            .arg("data")
+            .arg(payload);
+        cmd
+    }
+
+    /// Create the consumer group (and the stream) unless it already exists
+    ///
+    /// New groups start at `$`, i.e. with matches published from now on.
+    async fn create_group(conn: &mut Connection, stream: &str, group: &str) -> Result<(), redis::RedisError> {
+        let created: Result<(), redis::RedisError> = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(stream)
+            .arg(group)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async(conn)
+            .await;
+        match created {
+            Ok(()) => {
+                info!("Created consumer group {} on stream {}", group, stream);
+                Ok(())
+            }
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Publish with automatic retry
     pub async fn publish_with_retry(&self, event: CTEventMessage, max_retries: u32) -> bool {
         for attempt in 0..max_retries {
@@ -222,6 +443,7 @@ impl RedisPublisher {
 
 /// Builder for CTEventMessage from ct-scout's internal types
 impl CTEventMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn from_match(
         matched_domain: String,
         all_domains: Vec<String>,
@@ -276,4 +498,58 @@ mod tests {
         assert!(json.contains("ct_match"));
         assert!(json.contains("test.example.com"));
     }
+
+    #[test]
+    fn test_stream_entry() {
+        let event = CTEventMessage::from_match(
+            "api.example.com".to_string(),
+            vec!["api.example.com".to_string()],
+            1,
+            0,
+            0,
+            "abc".to_string(),
+            None,
+            "https://ct.example.com/log/".to_string(),
+            None,
+            false,
+        );
+        let publisher = RedisPublisher::new(RedisConfig {
+            stream: Some("ct:matches".to_string()),
+            stream_maxlen: Some(5000),
+            ..RedisConfig::default()
+        });
+
+        let packed = publisher.stream_entry("ct:matches", &event, "{}").get_packed_command();
+        let packed = String::from_utf8(packed).unwrap();
+        // RESP: "*<argc>", then "$<len>" before each argument
+        let args: Vec<&str> = packed.split("\r\n").skip(2).step_by(2).collect();
+        assert_eq!(
+            args[..8],
+            ["XADD", "ct:matches", "MAXLEN", "~", "5000", "*", "matched_domain", "api.example.com"]
+        );
+
+        let unbounded = RedisPublisher::new(RedisConfig { stream_maxlen: None, ..RedisConfig::default() });
+        let packed = String::from_utf8(unbounded.stream_entry("s", &event, "{}").get_packed_command()).unwrap();
+        assert!(!packed.contains("MAXLEN"));
+    }
+
+    #[test]
+    fn test_node_url() {
+        let plain = RedisPublisher::new(RedisConfig::default());
+        assert_eq!(plain.node_url("10.0.0.1:26379"), "redis://10.0.0.1:26379");
+        assert_eq!(plain.node_url("rediss://cache:6380"), "rediss://cache:6380");
+        assert!(plain.tls_mode().is_none());
+
+        let tls = RedisPublisher::new(RedisConfig { tls: true, ..RedisConfig::default() });
+        assert_eq!(tls.node_url("10.0.0.1:7000"), "rediss://10.0.0.1:7000");
+        assert_eq!(tls.node_url("redis://:pw@10.0.0.1:7000"), "rediss://:pw@10.0.0.1:7000");
+        assert!(matches!(tls.tls_mode(), Some(TlsMode::Secure)));
+
+        let insecure = RedisPublisher::new(RedisConfig { tls: true, tls_insecure: true, ..RedisConfig::default() });
+        assert_eq!(insecure.node_url("10.0.0.1:7000"), "rediss://10.0.0.1:7000#insecure");
+        assert!(matches!(insecure.tls_mode(), Some(TlsMode::Insecure)));
+
+        assert_eq!("sentinel".parse::<RedisMode>().unwrap(), RedisMode::Sentinel);
+        assert!("replica".parse::<RedisMode>().is_err());
+    }
 }
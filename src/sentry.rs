@@ -0,0 +1,352 @@
+// src/sentry.rs
+//! Optional Sentry error reporting
+//!
+//! Speaks Sentry's store API directly. Reported are:
+//! - panics, sent before the process unwinds
+//! - `error!` events, at most once per call site per `window_secs`
+//! - `warn!` events that repeat `warn_threshold` times within `window_secs`
+//!   (failed webhook deliveries, database writes, ...), which on their own
+//!   are too noisy to report one by one
+
+use anyhow::{Context, Result};
+use rand::Rng;
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::callsite::Identifier;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use url::Url;
+
+use crate::config::SentryConfig;
+
+/// How long a panic report may hold up the crash
+const PANIC_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where and how to authenticate, from a DSN like `https://<key>@o1.ingest.sentry.io/<project>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dsn {
+    store_url: String,
+    public_key: String,
+}
+
+impl std::str::FromStr for Dsn {
+    type Err = anyhow::Error;
+
+    fn from_str(dsn: &str) -> Result<Self> {
+        let url = Url::parse(dsn).context("Invalid Sentry DSN")?;
+        let public_key = url.username();
+        if public_key.is_empty() {
+            anyhow::bail!("Sentry DSN has no public key");
+        }
+        let (prefix, project) = url.path().trim_end_matches('/').rsplit_once('/').unwrap_or_default();
+        if project.is_empty() {
+            anyhow::bail!("Sentry DSN has no project ID");
+        }
+        let host = url.host_str().context("Sentry DSN has no host")?;
+        let port = url.port().map(|port| format!(":{}", port)).unwrap_or_default();
+
+        Ok(Self {
+            store_url: format!("{}://{}{}{}/api/{}/store/", url.scheme(), host, port, prefix, project),
+            public_key: public_key.to_string(),
+        })
+    }
+}
+
+/// One event to report
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub level: &'static str,
+    pub logger: String,
+    pub message: String,
+    pub extra: Map<String, Value>,
+}
+
+/// Sends events to Sentry
+pub struct SentryClient {
+    client: Client,
+    dsn: Dsn,
+    environment: Option<String>,
+}
+
+impl SentryClient {
+    pub fn new(config: &SentryConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(PANIC_SEND_TIMEOUT)
+            .build()
+            .context("Failed to build Sentry HTTP client")?;
+        Ok(Self {
+            client,
+            dsn: config.dsn.parse()?,
+            environment: config.environment.clone(),
+        })
+    }
+
+    pub async fn capture(&self, report: &Report) -> Result<()> {
+        let auth = format!(
+            "Sentry sentry_version=7, sentry_key={}, sentry_client=ct-scout/{}",
+            self.dsn.public_key,
+            env!("CARGO_PKG_VERSION")
+        );
+        self.client
+            .post(&self.dsn.store_url)
+            .header("X-Sentry-Auth", auth)
+            .json(&self.event(report))
+            .send()
+            .await
+            .context("Failed to reach Sentry")?
+            .error_for_status()
+            .context("Sentry rejected the event")?;
+        Ok(())
+    }
+
+    fn event(&self, report: &Report) -> Value {
+        json!({
+            "event_id": format!("{:032x}", rand::thread_rng().r#gen::<u128>()),
+            "timestamp": chrono::Utc::now().timestamp_millis() as f64 / 1000.0,
+            "platform": "other",
+            "level": report.level,
+            "logger": report.logger,
+            "message": { "formatted": report.message },
+            "release": concat!("ct-scout@", env!("CARGO_PKG_VERSION")),
+            "environment": self.environment,
+            "extra": report.extra,
+        })
+    }
+}
+
+/// Tracing layer reporting errors and repeated warnings
+///
+/// Must be called from within the Tokio runtime.
+pub fn init(config: &SentryConfig) -> Result<SentryLayer> {
+    let client = SentryClient::new(config)?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Report>();
+    tokio::spawn(async move {
+        while let Some(report) = rx.recv().await {
+            if let Err(e) = client.capture(&report).await {
+                // Not via tracing, which would report the failure to report
+                eprintln!("Failed to send event to Sentry: {:#}", e);
+            }
+        }
+    });
+
+    Ok(SentryLayer {
+        tx,
+        warn_threshold: config.warn_threshold,
+        window: Duration::from_secs(config.window_secs),
+        sites: Mutex::new(HashMap::new()),
+    })
+}
+
+/// Report panics before the default hook runs
+pub fn install_panic_hook(config: &SentryConfig) -> Result<()> {
+    let client = Arc::new(SentryClient::new(config)?);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        let mut extra = Map::new();
+        if let Some(location) = info.location() {
+            extra.insert("location".to_string(), location.to_string().into());
+        }
+        if let Some(thread) = std::thread::current().name() {
+            extra.insert("thread".to_string(), thread.into());
+        }
+        let report = Report {
+            level: "fatal",
+            logger: "panic".to_string(),
+            message,
+            extra,
+        };
+
+        // The hook may run on a runtime thread, so send from a fresh one
+        let client = Arc::clone(&client);
+        let sender = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build();
+            if let Ok(runtime) = runtime {
+                let _ = runtime.block_on(client.capture(&report));
+            }
+        });
+        let _ = sender.join();
+
+        previous(info);
+    }));
+    Ok(())
+}
+
+/// Occurrences of one call site within the current window
+struct Site {
+    window_start: Instant,
+    count: u32,
+    reported: bool,
+}
+
+/// Tracing layer forwarding errors and repeated warnings to Sentry
+pub struct SentryLayer {
+    tx: mpsc::UnboundedSender<Report>,
+    warn_threshold: u32,  // 0 = never report warnings
+    window: Duration,
+    sites: Mutex<HashMap<Identifier, Site>>,
+}
+
+impl SentryLayer {
+    /// Count an event; `Some(occurrences)` if it should be reported now
+    fn should_report(&self, site: Identifier, level: Level) -> Option<u32> {
+        let threshold = match level {
+            Level::ERROR => 1,
+            Level::WARN if self.warn_threshold > 0 => self.warn_threshold,
+            _ => return None,
+        };
+
+        let now = Instant::now();
+        let mut sites = self.sites.lock().unwrap();
+        let site = sites.entry(site).or_insert(Site {
+            window_start: now,
+            count: 0,
+            reported: false,
+        });
+        if now.duration_since(site.window_start) >= self.window {
+            *site = Site {
+                window_start: now,
+                count: 0,
+                reported: false,
+            };
+        }
+
+        site.count += 1;
+        if site.reported || site.count < threshold {
+            return None;
+        }
+        site.reported = true;
+        Some(site.count)
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SentryLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let Some(occurrences) = self.should_report(metadata.callsite(), *metadata.level()) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let mut extra = visitor.fields;
+        extra.insert("occurrences".to_string(), occurrences.into());
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            extra.insert("location".to_string(), format!("{}:{}", file, line).into());
+        }
+
+        let _ = self.tx.send(Report {
+            level: if *metadata.level() == Level::ERROR { "error" } else { "warning" },
+            logger: metadata.target().to_string(),
+            message: visitor.message,
+            extra,
+        });
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.into());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value).into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+    use wiremock::matchers::{header_regex, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse_dsn() {
+        let dsn: Dsn = "https://abc123@o42.ingest.sentry.io/4505".parse().unwrap();
+        assert_eq!(dsn.store_url, "https://o42.ingest.sentry.io/api/4505/store/");
+        assert_eq!(dsn.public_key, "abc123");
+
+        let dsn: Dsn = "http://key@localhost:9000/sentry/7".parse().unwrap();
+        assert_eq!(dsn.store_url, "http://localhost:9000/sentry/api/7/store/");
+
+        assert!("https://o42.ingest.sentry.io/4505".parse::<Dsn>().is_err());
+        assert!("https://abc@o42.ingest.sentry.io/".parse::<Dsn>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reports_errors_and_repeated_warnings() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/1/store/"))
+            .and(header_regex("X-Sentry-Auth", "sentry_key=public"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let config = SentryConfig {
+            dsn: format!("{}/1", mock_server.uri().replace("://", "://public@")),
+            environment: Some("test".to_string()),
+            warn_threshold: 3,
+            window_secs: 3600,
+        };
+        let subscriber = tracing_subscriber::registry().with(init(&config).unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            for attempt in 0..2 {
+                tracing::error!(attempt, "Database unreachable");
+            }
+            for _ in 0..4 {
+                tracing::warn!("Webhook delivery failed");
+            }
+            tracing::info!("Not reported");
+        });
+
+        let mut requests = Vec::new();
+        for _ in 0..50 {
+            requests = mock_server.received_requests().await.unwrap();
+            if requests.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let events: Vec<Value> = requests
+            .iter()
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["level"], "error");
+        assert_eq!(events[0]["message"]["formatted"], "Database unreachable");
+        assert_eq!(events[0]["extra"]["attempt"], "0");
+        assert_eq!(events[0]["environment"], "test");
+        assert_eq!(events[1]["level"], "warning");
+        assert_eq!(events[1]["message"]["formatted"], "Webhook delivery failed");
+        assert_eq!(events[1]["extra"]["occurrences"], 3);
+    }
+}
@@ -0,0 +1,200 @@
+// src/server.rs
+//! Small HTTP endpoint for Prometheus scraping and orchestrator probes
+//!
+//! - `/metrics`: Prometheus text format
+//! - `/healthz`: 200 while the process is serving requests
+//! - `/readyz`: 200 once at least one log is healthy, the database (if enabled)
+//!   answers and the certificate channel has room; 503 listing the problems otherwise
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info};
+
+use crate::ct_log::LogHealthTracker;
+use crate::database::DatabaseBackend;
+use crate::metrics;
+
+/// Largest request head we read before giving up on a client
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What `/readyz` checks
+pub struct Readiness {
+    pub health: Arc<LogHealthTracker>,
+    pub db: Option<Arc<dyn DatabaseBackend>>,
+    pub channel_capacity: usize,
+}
+
+impl Readiness {
+    /// Reasons the instance is not ready; empty when it is
+    pub async fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let (healthy, degraded, failed, quarantined) = self.health.get_stats().await;
+        if healthy + degraded == 0 {
+            problems.push(format!(
+                "no healthy CT logs ({} failed, {} quarantined)",
+                failed, quarantined
+            ));
+        }
+
+        if let Some(ref db) = self.db
+            && let Err(e) = db.ping().await
+        {
+            problems.push(format!("database unreachable: {:#}", e));
+        }
+
+        let depth = metrics::CERT_CHANNEL_DEPTH.get().max(0) as usize;
+        if depth >= self.channel_capacity.max(1) {
+            problems.push(format!("certificate channel saturated ({}/{})", depth, self.channel_capacity));
+        }
+
+        problems
+    }
+}
+
+/// Bind the listener up front so a bad address fails start-up
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!("Serving /metrics, /healthz and /readyz on http://{}", listener.local_addr()?);
+    Ok(listener)
+}
+
+/// Answer requests until the task is dropped
+pub async fn serve(listener: TcpListener, readiness: Readiness) {
+    let readiness = Arc::new(readiness);
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                debug!("Failed to accept HTTP connection: {}", e);
+                continue;
+            }
+        };
+
+        let readiness = Arc::clone(&readiness);
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &readiness).await {
+                debug!("HTTP request from {} failed: {:#}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, readiness: &Readiness) -> Result<()> {
+    let head = tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream))
+        .await
+        .context("Timed out reading request")??;
+
+    let mut parts = head.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, body) = if method != "GET" && method != "HEAD" {
+        ("405 Method Not Allowed", "method not allowed\n".to_string())
+    } else {
+        match path {
+            "/metrics" => ("200 OK", metrics::export_metrics()),
+            "/healthz" => ("200 OK", "ok\n".to_string()),
+            "/readyz" => {
+                let problems = readiness.problems().await;
+                if problems.is_empty() {
+                    ("200 OK", "ready\n".to_string())
+                } else {
+                    ("503 Service Unavailable", format!("{}\n", problems.join("\n")))
+                }
+            }
+            _ => ("404 Not Found", "not found\n".to_string()),
+        }
+    };
+
+    let content_type = if path == "/metrics" {
+        "text/plain; version=0.0.4"
+    } else {
+        "text/plain; charset=utf-8"
+    };
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Request line and headers; the body (if any) is ignored
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("request head too large");
+        }
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_probe_endpoints() {
+        let health = Arc::new(LogHealthTracker::new(3));
+        let listener = bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(
+            listener,
+            Readiness {
+                health: Arc::clone(&health),
+                db: None,
+                channel_capacity: 1000,
+            },
+        ));
+
+        assert!(get(addr, "/healthz").await.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(get(addr, "/nope").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        // Not ready until a log has answered
+        let response = get(addr, "/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(response.ends_with("no healthy CT logs (0 failed, 0 quarantined)\n"));
+
+        health.record_success("https://ct.example.com/log/").await;
+        let response = get(addr, "/readyz?verbose").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nready\n"));
+
+        server.abort();
+    }
+}
@@ -1,15 +1,18 @@
 // src/state.rs
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 
 /// Trait for state backend abstraction
-/// Allows different implementations (file-based, database-based)
+/// Implemented by `StateManager` (TOML file), `DbStateManager` (PostgreSQL)
+/// and `SqliteStateManager`, so monitors and the coordinator work with any of them
 #[async_trait]
 pub trait StateBackend: Send + Sync {
     /// Get last-seen index for a CT log
@@ -26,6 +29,109 @@ pub trait StateBackend: Send + Sync {
 
     /// Get total number of tracked logs
     async fn count(&self) -> usize;
+
+    /// Get unprocessed index ranges (inclusive) below the last-seen index
+    async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)>;
+
+    /// Record an inclusive index range that still needs to be processed
+    async fn add_gap(&self, log_url: &str, start: u64, end: u64);
+
+    /// Forget a previously recorded range once it has been processed
+    async fn remove_gap(&self, log_url: &str, start: u64, end: u64);
+
+    /// Remember the tree size from the log's latest STH
+    async fn update_tree_size(&self, log_url: &str, tree_size: u64);
+
+    /// When the index last advanced and the last observed tree size
+    async fn get_progress(&self, log_url: &str) -> LogProgress;
+
+    /// Forget a log's index, gaps and progress
+    async fn remove_log(&self, log_url: &str);
+}
+
+/// Bookkeeping saved next to each log's index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogProgress {
+    pub last_updated: Option<u64>,  // Unix timestamp of the last index change
+    pub tree_size: Option<u64>,  // From the latest STH
+}
+
+impl LogProgress {
+    /// Seconds since the index last advanced, if known
+    pub fn age_secs(&self, now: u64) -> Option<u64> {
+        self.last_updated.map(|updated| now.saturating_sub(updated))
+    }
+}
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Exclusive claim on a state file, released when dropped
+///
+/// Held via an advisory lock on `<file>.lock`, which also records the owner's PID.
+pub struct StateLock {
+    _file: std::fs::File,
+}
+
+impl StateLock {
+    /// Lock the state at `path`, failing at once if another process holds it
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".lock");
+        let lock_path = PathBuf::from(name);
+
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open lock file {:?}", lock_path))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(std::fs::TryLockError::WouldBlock) => {
+                let owner = std::fs::read_to_string(&lock_path).unwrap_or_default();
+                anyhow::bail!(
+                    "State {:?} is in use by another ct-scout process (pid {}); \
+                     stop it or point this instance at a different state",
+                    path,
+                    owner.trim()
+                );
+            }
+            Err(std::fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {:?}", lock_path));
+            }
+        }
+
+        use std::io::Write;
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        debug!("Locked state {:?}", path);
+        Ok(Self { _file: file })
+    }
+}
+
+/// Copies of the previous state file kept by each save (`<file>.bak.1` is the newest)
+const BACKUP_COUNT: usize = 3;
+
+/// Unprocessed inclusive index ranges keyed by log URL
+type GapMap = HashMap<String, Vec<(u64, u64)>>;
+
+/// On-disk layout of the state file
+/// Log indices stay at the top level (compatible with older files), the rest goes in tables
+#[derive(Default, Serialize, Deserialize)]
+struct StateFile {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    gaps: GapMap,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    updated: HashMap<String, u64>,  // Unix timestamp of the last index change
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tree_sizes: HashMap<String, u64>,
+    #[serde(flatten)]
+    indices: HashMap<String, u64>,
 }
 
 /// State manager for tracking last-seen index per CT log
@@ -33,6 +139,8 @@ pub trait StateBackend: Send + Sync {
 pub struct StateManager {
     state_file_path: PathBuf,
     state: Arc<Mutex<HashMap<String, u64>>>,
+    gaps: Arc<Mutex<GapMap>>,
+    progress: Arc<Mutex<HashMap<String, LogProgress>>>,
     save_counter: Arc<Mutex<u64>>,  // Track entries processed since last save
 }
 
@@ -40,20 +148,27 @@ impl StateManager {
     /// Create new state manager and load existing state from file
     pub async fn new(state_file: PathBuf) -> Result<Self> {
         let mut state = HashMap::new();
+        let mut gaps = HashMap::new();
+        let mut progress: HashMap<String, LogProgress> = HashMap::new();
 
         // Load existing state if file exists
         if state_file.exists() {
             info!("Loading state from {:?}", state_file);
 
-            let contents = fs::read_to_string(&state_file)
-                .await
-                .context("Failed to read state file")?;
-
-            let loaded_state: HashMap<String, u64> = toml::from_str(&contents)
-                .context("Failed to parse state file")?;
+            let loaded_state = match load_state_file(&state_file).await {
+                Ok(loaded) => loaded,
+                Err(e) => load_backup(&state_file, e).await?,
+            };
 
-            info!("Loaded state for {} CT logs", loaded_state.len());
-            state = loaded_state;
+            info!("Loaded state for {} CT logs", loaded_state.indices.len());
+            for (url, updated) in loaded_state.updated {
+                progress.entry(url).or_default().last_updated = Some(updated);
+            }
+            for (url, tree_size) in loaded_state.tree_sizes {
+                progress.entry(url).or_default().tree_size = Some(tree_size);
+            }
+            state = loaded_state.indices;
+            gaps = loaded_state.gaps;
         } else {
             info!(
                 "State file {:?} does not exist, starting fresh",
@@ -64,6 +179,8 @@ impl StateManager {
         Ok(Self {
             state_file_path: state_file,
             state: Arc::new(Mutex::new(state)),
+            gaps: Arc::new(Mutex::new(gaps)),
+            progress: Arc::new(Mutex::new(progress)),
             save_counter: Arc::new(Mutex::new(0)),
         })
     }
@@ -77,12 +194,20 @@ impl StateManager {
     /// Update last-seen index for a CT log
     /// Auto-saves every 100 entries to balance durability and I/O overhead
     pub async fn update_index(&self, log_url: &str, index: u64) {
-        {
+        let previous = {
             let mut state = self.state.lock().await;
-            state.insert(log_url.to_string(), index);
+            state.insert(log_url.to_string(), index)
+        };
+        if previous != Some(index) {
+            let mut progress = self.progress.lock().await;
+            progress.entry(log_url.to_string()).or_default().last_updated = Some(unix_now());
         }
 
-        // Increment counter and save periodically
+        self.count_change().await;
+    }
+
+    /// Increment the change counter, saving every 100 changes
+    async fn count_change(&self) {
         let mut counter = self.save_counter.lock().await;
         *counter += 1;
 
@@ -99,10 +224,24 @@ impl StateManager {
     /// Manually save state to file
     pub async fn save(&self) -> Result<()> {
         let state = self.state.lock().await;
+        let gaps = self.gaps.lock().await;
+        let progress = self.progress.lock().await;
 
         debug!("Saving state for {} CT logs to {:?}", state.len(), self.state_file_path);
 
-        let toml_string = toml::to_string(&*state)
+        let file = StateFile {
+            gaps: gaps.clone(),
+            updated: progress
+                .iter()
+                .filter_map(|(url, progress)| Some((url.clone(), progress.last_updated?)))
+                .collect(),
+            tree_sizes: progress
+                .iter()
+                .filter_map(|(url, progress)| Some((url.clone(), progress.tree_size?)))
+                .collect(),
+            indices: state.clone(),
+        };
+        let toml_string = toml::to_string(&file)
             .context("Failed to serialize state to TOML")?;
 
         // Write to temporary file first, then rename for atomicity
@@ -112,6 +251,10 @@ impl StateManager {
             .await
             .context("Failed to write state to temporary file")?;
 
+        if let Err(e) = rotate_backups(&self.state_file_path).await {
+            warn!("Failed to back up state file: {:#}", e);
+        }
+
         fs::rename(&temp_path, &self.state_file_path)
             .await
             .context("Failed to rename temporary state file")?;
@@ -132,6 +275,120 @@ impl StateManager {
         let state = self.state.lock().await;
         state.len()
     }
+
+    /// Get unprocessed index ranges for a CT log, oldest first
+    pub async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        let gaps = self.gaps.lock().await;
+        let mut ranges = gaps.get(log_url).cloned().unwrap_or_default();
+        ranges.sort_unstable();
+        ranges
+    }
+
+    /// Record an unprocessed range; written with the next save, which also
+    /// carries any index that moved past it
+    pub async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        {
+            let mut gaps = self.gaps.lock().await;
+            let ranges = gaps.entry(log_url.to_string()).or_default();
+            if ranges.contains(&(start, end)) {
+                return;
+            }
+            ranges.push((start, end));
+        }
+        self.count_change().await;
+    }
+
+    /// Remove a processed range
+    pub async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        {
+            let mut gaps = self.gaps.lock().await;
+            let Some(ranges) = gaps.get_mut(log_url) else {
+                return;
+            };
+            ranges.retain(|&range| range != (start, end));
+            if ranges.is_empty() {
+                gaps.remove(log_url);
+            }
+        }
+        self.count_change().await;
+    }
+
+    /// Record the latest tree size; written with the next save
+    pub async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        let mut progress = self.progress.lock().await;
+        progress.entry(log_url.to_string()).or_default().tree_size = Some(tree_size);
+    }
+
+    /// When the index last advanced and the last observed tree size
+    pub async fn get_progress(&self, log_url: &str) -> LogProgress {
+        let progress = self.progress.lock().await;
+        progress.get(log_url).copied().unwrap_or_default()
+    }
+
+    /// Forget a log entirely; written with the next save
+    pub async fn remove_log(&self, log_url: &str) {
+        self.state.lock().await.remove(log_url);
+        self.gaps.lock().await.remove(log_url);
+        self.progress.lock().await.remove(log_url);
+        self.count_change().await;
+    }
+}
+
+async fn load_state_file(path: &Path) -> Result<StateFile> {
+    let contents = fs::read_to_string(path)
+        .await
+        .context("Failed to read state file")?;
+
+    toml::from_str(&contents).context("Failed to parse state file")
+}
+
+/// Path of the `n`th newest backup of `path`
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".bak.{}", n));
+    PathBuf::from(name)
+}
+
+/// Fall back to the newest backup that still loads, after `error` loading the state file itself
+async fn load_backup(path: &Path, error: anyhow::Error) -> Result<StateFile> {
+    for n in 1..=BACKUP_COUNT {
+        let backup = backup_path(path, n);
+        if !backup.exists() {
+            continue;
+        }
+        match load_state_file(&backup).await {
+            Ok(loaded) => {
+                warn!(
+                    "State file {:?} is unusable ({:#}); recovered from backup {:?}",
+                    path, error, backup
+                );
+                return Ok(loaded);
+            }
+            Err(e) => warn!("State backup {:?} is unusable too: {:#}", backup, e),
+        }
+    }
+    Err(error.context(format!("No usable backup of {:?}", path)))
+}
+
+/// Shift `<file>.bak.N` up by one and copy the current file to `<file>.bak.1`
+///
+/// Copied rather than renamed so the state file never disappears, even briefly.
+async fn rotate_backups(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))
+                .await
+                .with_context(|| format!("Failed to rotate {:?}", from))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))
+        .await
+        .context("Failed to copy state file to backup")?;
+    Ok(())
 }
 
 impl Clone for StateManager {
@@ -139,6 +396,8 @@ impl Clone for StateManager {
         Self {
             state_file_path: self.state_file_path.clone(),
             state: Arc::clone(&self.state),
+            gaps: Arc::clone(&self.gaps),
+            progress: Arc::clone(&self.progress),
             save_counter: Arc::clone(&self.save_counter),
         }
     }
@@ -166,6 +425,30 @@ impl StateBackend for StateManager {
     async fn count(&self) -> usize {
         self.count().await
     }
+
+    async fn get_gaps(&self, log_url: &str) -> Vec<(u64, u64)> {
+        self.get_gaps(log_url).await
+    }
+
+    async fn add_gap(&self, log_url: &str, start: u64, end: u64) {
+        self.add_gap(log_url, start, end).await
+    }
+
+    async fn remove_gap(&self, log_url: &str, start: u64, end: u64) {
+        self.remove_gap(log_url, start, end).await
+    }
+
+    async fn update_tree_size(&self, log_url: &str, tree_size: u64) {
+        self.update_tree_size(log_url, tree_size).await
+    }
+
+    async fn get_progress(&self, log_url: &str) -> LogProgress {
+        self.get_progress(log_url).await
+    }
+
+    async fn remove_log(&self, log_url: &str) {
+        self.remove_log(log_url).await
+    }
 }
 
 #[cfg(test)]
@@ -222,4 +505,97 @@ mod tests {
         let manager2 = StateManager::new(state_path).await.unwrap();
         assert!(manager2.get_last_index("https://example.com/log").await.is_some());
     }
+
+    #[tokio::test]
+    async fn test_state_manager_gaps_persist() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let state_path = temp_file.path().to_path_buf();
+
+        let manager = StateManager::new(state_path.clone()).await.unwrap();
+        manager.update_index("https://example.com/log", 500).await;
+        manager.add_gap("https://example.com/log", 300, 399).await;
+        manager.add_gap("https://example.com/log", 100, 199).await;
+        // Gaps are batched with index updates rather than written one by one
+        assert!(StateManager::new(state_path.clone()).await.unwrap().get_gaps("https://example.com/log").await.is_empty());
+        manager.save().await.unwrap();
+
+        // Reloaded state keeps both the index and the gaps, oldest first
+        let manager2 = StateManager::new(state_path.clone()).await.unwrap();
+        assert_eq!(manager2.get_last_index("https://example.com/log").await, Some(500));
+        assert_eq!(
+            manager2.get_gaps("https://example.com/log").await,
+            vec![(100, 199), (300, 399)]
+        );
+
+        manager2.remove_gap("https://example.com/log", 100, 199).await;
+        manager2.save().await.unwrap();
+        let manager3 = StateManager::new(state_path).await.unwrap();
+        assert_eq!(manager3.get_gaps("https://example.com/log").await, vec![(300, 399)]);
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_loads_legacy_file() {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), "\"https://example.com/log\" = 42\n").unwrap();
+
+        let manager = StateManager::new(temp_file.path().to_path_buf()).await.unwrap();
+        assert_eq!(manager.get_last_index("https://example.com/log").await, Some(42));
+        assert!(manager.get_gaps("https://example.com/log").await.is_empty());
+        assert_eq!(manager.get_progress("https://example.com/log").await, LogProgress::default());
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_recovers_from_backup() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.toml");
+
+        let manager = StateManager::new(state_path.clone()).await.unwrap();
+        for index in [100, 200, 300, 400, 500] {
+            manager.update_index("https://example.com/log", index).await;
+            manager.save().await.unwrap();
+        }
+        assert!(backup_path(&state_path, BACKUP_COUNT).exists());
+        assert!(!backup_path(&state_path, BACKUP_COUNT + 1).exists());
+
+        // Newest backup is corrupt as well, so the one before it is used
+        std::fs::write(&state_path, "\"https://example.com/log\" = ").unwrap();
+        std::fs::write(backup_path(&state_path, 1), "not toml [").unwrap();
+        let recovered = StateManager::new(state_path.clone()).await.unwrap();
+        assert_eq!(recovered.get_last_index("https://example.com/log").await, Some(300));
+
+        for n in 1..=BACKUP_COUNT {
+            std::fs::remove_file(backup_path(&state_path, n)).unwrap();
+        }
+        assert!(StateManager::new(state_path).await.is_err());
+    }
+
+    #[test]
+    fn test_state_lock_is_exclusive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.toml");
+
+        let lock = StateLock::acquire(&state_path).unwrap();
+        let err = StateLock::acquire(&state_path).err().unwrap();
+        assert!(err.to_string().contains(&format!("pid {}", std::process::id())));
+
+        drop(lock);
+        assert!(StateLock::acquire(&state_path).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_progress_persists() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let state_path = temp_file.path().to_path_buf();
+
+        let manager = StateManager::new(state_path.clone()).await.unwrap();
+        manager.update_index("https://example.com/log", 500).await;
+        manager.update_tree_size("https://example.com/log", 800).await;
+        manager.save().await.unwrap();
+
+        let manager2 = StateManager::new(state_path).await.unwrap();
+        assert_eq!(manager2.get_last_index("https://example.com/log").await, Some(500));
+        let progress = manager2.get_progress("https://example.com/log").await;
+        assert_eq!(progress.tree_size, Some(800));
+        assert!(progress.age_secs(unix_now()).is_some_and(|age| age < 60));
+    }
 }
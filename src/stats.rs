@@ -2,20 +2,85 @@
 //! Statistics tracking for ct-scout
 
 use crate::metrics::{CERTIFICATES_PROCESSED_TOTAL, MATCHES_FOUND_TOTAL};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Thread-safe statistics collector
 #[derive(Clone)]
 pub struct StatsCollector {
     total_processed: Arc<AtomicU64>,
     matches_found: Arc<AtomicU64>,
+    program_matches: Arc<Mutex<HashMap<String, u64>>>,
+    logs: Arc<Mutex<HashMap<String, LogCounts>>>,
+    webhooks: Arc<Mutex<HashMap<String, WebhookLog>>>,
     start_time: Instant,
 }
 
+/// Per-log counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LogCounts {
+    /// Entries fetched from the log
+    pub entries: u64,
+    /// Failed polls and unparseable entries
+    pub errors: u64,
+}
+
+/// Recent deliveries per webhook endpoint behind its failure rate and latency
+const WEBHOOK_WINDOW: usize = 100;
+
+/// Delivery history of one webhook endpoint
+#[derive(Debug, Default)]
+struct WebhookLog {
+    delivered: u64,
+    failed: u64,
+    last_error: Option<String>,
+    /// Latency in ms of the most recent deliveries, None for failures
+    recent: VecDeque<Option<f64>>,
+}
+
+/// Delivery receipts of one webhook endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WebhookStats {
+    pub delivered: u64,
+    pub failed: u64,
+    pub last_error: Option<String>,
+    /// Recent deliveries `failure_rate` and `p95_ms` are based on
+    pub recent: usize,
+    /// Share of recent deliveries that failed
+    pub failure_rate: f64,
+    /// 95th percentile latency of recent successful deliveries
+    pub p95_ms: Option<f64>,
+}
+
+impl WebhookLog {
+    fn stats(&self) -> WebhookStats {
+        let mut latencies: Vec<f64> = self.recent.iter().flatten().copied().collect();
+        latencies.sort_by(f64::total_cmp);
+        let p95_ms = (!latencies.is_empty())
+            .then(|| latencies[(latencies.len() * 95).div_ceil(100) - 1]);
+        let failures = self.recent.iter().filter(|latency| latency.is_none()).count();
+
+        WebhookStats {
+            delivered: self.delivered,
+            failed: self.failed,
+            last_error: self.last_error.clone(),
+            recent: self.recent.len(),
+            failure_rate: if self.recent.is_empty() { 0.0 } else { failures as f64 / self.recent.len() as f64 },
+            p95_ms,
+        }
+    }
+}
+
+/// Programs and logs listed in the periodic stats line
+const TOP_IN_STATS_LINE: usize = 3;
+
 /// Snapshot of statistics at a point in time
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StatsSnapshot {
     pub total_processed: u64,
     pub matches_found: u64,
@@ -29,6 +94,9 @@ impl StatsCollector {
         Self {
             total_processed: Arc::new(AtomicU64::new(0)),
             matches_found: Arc::new(AtomicU64::new(0)),
+            program_matches: Arc::new(Mutex::new(HashMap::new())),
+            logs: Arc::new(Mutex::new(HashMap::new())),
+            webhooks: Arc::new(Mutex::new(HashMap::new())),
             start_time: Instant::now(),
         }
     }
@@ -45,6 +113,80 @@ impl StatsCollector {
         MATCHES_FOUND_TOTAL.inc();
     }
 
+    /// Count a match attributed to a program
+    pub fn increment_program_matches(&self, program: &str) {
+        *self.program_matches.lock().unwrap().entry(program.to_string()).or_insert(0) += 1;
+    }
+
+    /// Count entries fetched from a log
+    pub fn add_log_entries(&self, log_url: &str, entries: u64) {
+        self.logs.lock().unwrap().entry(log_url.to_string()).or_default().entries += entries;
+    }
+
+    /// Count a failed poll or unparseable entry for a log
+    pub fn increment_log_errors(&self, log_url: &str) {
+        self.logs.lock().unwrap().entry(log_url.to_string()).or_default().errors += 1;
+    }
+
+    /// Record a webhook delivery: its latency, or why it failed
+    pub fn record_webhook(&self, endpoint: &str, outcome: Result<Duration, String>) -> WebhookStats {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        let log = webhooks.entry(endpoint.to_string()).or_default();
+        match outcome {
+            Ok(latency) => {
+                log.delivered += 1;
+                log.recent.push_back(Some(latency.as_secs_f64() * 1000.0));
+            }
+            Err(error) => {
+                log.failed += 1;
+                log.last_error = Some(error);
+                log.recent.push_back(None);
+            }
+        }
+        if log.recent.len() > WEBHOOK_WINDOW {
+            log.recent.pop_front();
+        }
+        log.stats()
+    }
+
+    /// Delivery receipts per webhook endpoint
+    pub fn webhooks(&self) -> HashMap<String, WebhookStats> {
+        self.webhooks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(endpoint, log)| (endpoint.clone(), log.stats()))
+            .collect()
+    }
+
+    /// Programs with the most matches, most first (at most `n`)
+    pub fn top_programs(&self, n: usize) -> Vec<(String, u64)> {
+        let mut programs: Vec<(String, u64)> = self
+            .program_matches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, count)| (name.clone(), *count))
+            .collect();
+        programs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        programs.truncate(n);
+        programs
+    }
+
+    /// Logs with the most entries, most first (at most `n`)
+    pub fn top_logs(&self, n: usize) -> Vec<(String, LogCounts)> {
+        let mut logs: Vec<(String, LogCounts)> = self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(url, counts)| (url.clone(), *counts))
+            .collect();
+        logs.sort_by(|a, b| b.1.entries.cmp(&a.1.entries).then_with(|| a.0.cmp(&b.0)));
+        logs.truncate(n);
+        logs
+    }
+
     /// Get current statistics snapshot
     pub fn snapshot(&self) -> StatsSnapshot {
         let elapsed = self.start_time.elapsed();
@@ -65,16 +207,70 @@ impl StatsCollector {
         }
     }
 
+    /// Snapshot plus every per-log, per-program and per-webhook counter, as written by `write_json`
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self.snapshot()).unwrap_or_default();
+        value["generated_at"] = chrono::Utc::now().timestamp().into();
+        value["programs"] = serde_json::to_value(self.top_programs(usize::MAX).into_iter().collect::<HashMap<_, _>>())
+            .unwrap_or_default();
+        value["logs"] = serde_json::to_value(self.top_logs(usize::MAX).into_iter().collect::<HashMap<_, _>>())
+            .unwrap_or_default();
+        value["webhooks"] = serde_json::to_value(self.webhooks()).unwrap_or_default();
+        value
+    }
+
+    /// Replace `path` with the current JSON stats, so readers never see a partial file
+    pub async fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        let temp_path = path.with_extension("tmp");
+        tokio::fs::write(&temp_path, json)
+            .await
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        tokio::fs::rename(&temp_path, path)
+            .await
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
     /// Format statistics as a human-readable string
     pub fn format_stats(&self) -> String {
         let snapshot = self.snapshot();
-        format!(
+        let mut line = format!(
             "{} processed | {} matches | {:.1} msg/min | uptime: {}",
             snapshot.total_processed,
             snapshot.matches_found,
             snapshot.messages_per_minute,
             Self::format_uptime(snapshot.uptime_secs)
-        )
+        );
+
+        let programs = self.top_programs(TOP_IN_STATS_LINE);
+        if !programs.is_empty() {
+            let programs: Vec<String> = programs
+                .iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect();
+            line.push_str(&format!(" | top programs: {}", programs.join(", ")));
+        }
+
+        let logs = self.top_logs(TOP_IN_STATS_LINE);
+        if !logs.is_empty() {
+            let logs: Vec<String> = logs
+                .iter()
+                .map(|(url, counts)| format!("{} {}", Self::short_log_name(url), counts.entries))
+                .collect();
+            line.push_str(&format!(" | top logs: {}", logs.join(", ")));
+        }
+
+        line
+    }
+
+    /// Log URL without scheme and trailing slash, e.g. `ct.googleapis.com/logs/us1/argon2025h1`
+    pub fn short_log_name(url: &str) -> &str {
+        let url = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+        url.trim_end_matches('/')
     }
 
     /// Format uptime duration
@@ -169,6 +365,57 @@ mod tests {
         assert_eq!(snapshot2.total_processed, 2);
     }
 
+    #[test]
+    fn test_per_program_and_log_counts() {
+        let stats = StatsCollector::new();
+
+        stats.increment_program_matches("Acme");
+        stats.increment_program_matches("Globex");
+        stats.increment_program_matches("Acme");
+        stats.add_log_entries("https://ct.example.com/a/", 10);
+        stats.add_log_entries("https://ct.example.com/b/", 25);
+        stats.add_log_entries("https://ct.example.com/a/", 5);
+        stats.increment_log_errors("https://ct.example.com/a/");
+        stats.increment_log_errors("https://ct.example.com/c/");
+
+        assert_eq!(
+            stats.top_programs(5),
+            vec![("Acme".to_string(), 2), ("Globex".to_string(), 1)]
+        );
+        assert_eq!(
+            stats.top_logs(2),
+            vec![
+                ("https://ct.example.com/b/".to_string(), LogCounts { entries: 25, errors: 0 }),
+                ("https://ct.example.com/a/".to_string(), LogCounts { entries: 15, errors: 1 }),
+            ]
+        );
+        assert!(stats.format_stats().ends_with(
+            " | top programs: Acme 2, Globex 1 | top logs: ct.example.com/b 25, ct.example.com/a 15, ct.example.com/c 0"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_json() {
+        let stats = StatsCollector::new();
+        stats.increment_processed();
+        stats.increment_matches();
+        stats.increment_program_matches("Acme");
+        stats.add_log_entries("https://ct.example.com/a/", 7);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.json");
+        stats.write_json(&path).await.unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["total_processed"], 1);
+        assert_eq!(json["matches_found"], 1);
+        assert_eq!(json["programs"]["Acme"], 1);
+        assert_eq!(json["logs"]["https://ct.example.com/a/"]["entries"], 7);
+        assert_eq!(json["logs"]["https://ct.example.com/a/"]["errors"], 0);
+        assert!(json["generated_at"].as_i64().unwrap() > 0);
+        assert!(!dir.path().join("stats.tmp").exists());
+    }
+
     #[test]
     fn test_format_uptime() {
         assert_eq!(StatsCollector::format_uptime(30), "30s");
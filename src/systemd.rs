@@ -0,0 +1,170 @@
+// src/systemd.rs
+//! systemd service integration via the sd_notify protocol
+//!
+//! Active only when systemd starts us with `Type=notify` (it sets
+//! `NOTIFY_SOCKET`); with `WatchdogSec=` set it also expects keepalives.
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::stats::StatsCollector;
+
+/// How often STATUS= is refreshed when no watchdog is configured
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sends readiness, status and watchdog notifications to systemd
+pub struct SystemdNotifier {
+    socket_path: String,
+    watchdog: Option<Duration>,
+    #[cfg(unix)]
+    socket: std::os::unix::net::UnixDatagram,
+}
+
+impl SystemdNotifier {
+    /// Notifier for the socket systemd handed us, if any
+    pub fn from_env() -> Option<Self> {
+        let socket_path = std::env::var("NOTIFY_SOCKET").ok().filter(|p| !p.is_empty())?;
+
+        // WATCHDOG_PID, when set, names the process expected to send keepalives
+        let for_us = std::env::var("WATCHDOG_PID")
+            .ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_none_or(|pid| pid == std::process::id());
+        let watchdog = std::env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .filter(|usec| *usec > 0 && for_us)
+            .map(Duration::from_micros);
+
+        match Self::new(&socket_path, watchdog) {
+            Ok(notifier) => {
+                info!("systemd notifications enabled (watchdog: {:?})", watchdog);
+                Some(notifier)
+            }
+            Err(e) => {
+                warn!("Ignoring NOTIFY_SOCKET {}: {:#}", socket_path, e);
+                None
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn new(socket_path: &str, watchdog: Option<Duration>) -> Result<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()
+            .context("Failed to create notification socket")?;
+        Ok(Self {
+            socket_path: socket_path.to_string(),
+            watchdog,
+            socket,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn new(_socket_path: &str, _watchdog: Option<Duration>) -> Result<Self> {
+        anyhow::bail!("systemd notifications need Unix sockets")
+    }
+
+    /// Send newline-separated `KEY=value` assignments
+    #[cfg(unix)]
+    pub fn notify(&self, state: &str) -> Result<()> {
+        // A leading '@' denotes a socket in the Linux abstract namespace
+        #[cfg(target_os = "linux")]
+        if let Some(name) = self.socket_path.strip_prefix('@') {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            self.socket
+                .send_to_addr(state.as_bytes(), &addr)
+                .context("Failed to notify systemd")?;
+            return Ok(());
+        }
+
+        self.socket
+            .send_to(state.as_bytes(), &self.socket_path)
+            .context("Failed to notify systemd")?;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn notify(&self, _state: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Tell systemd start-up is complete
+    pub fn ready(&self, status: &str) {
+        self.send(&format!("READY=1\nSTATUS={}", status));
+    }
+
+    /// Tell systemd we're shutting down
+    pub fn stopping(&self) {
+        self.send("STOPPING=1\nSTATUS=Shutting down");
+    }
+
+    /// Send watchdog keepalives (if systemd expects them) and refresh STATUS= with counters
+    pub fn spawn_keepalive(self: std::sync::Arc<Self>, stats: StatsCollector) -> JoinHandle<()> {
+        // Ping at half the watchdog timeout, as sd_watchdog_enabled(3) recommends
+        let period = self.watchdog.map_or(STATUS_INTERVAL, |timeout| timeout / 2);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                let snapshot = stats.snapshot();
+                let status = format!(
+                    "STATUS=Processed {} certificates, {} matches",
+                    snapshot.total_processed, snapshot.matches_found
+                );
+                if self.watchdog.is_some() {
+                    self.send(&format!("WATCHDOG=1\n{}", status));
+                } else {
+                    self.send(&status);
+                }
+            }
+        })
+    }
+
+    fn send(&self, state: &str) {
+        match self.notify(state) {
+            Ok(()) => debug!("sd_notify: {}", state.replace('\n', " ")),
+            Err(e) => warn!("{:#}", e),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+    use std::sync::Arc;
+
+    fn receive(socket: &UnixDatagram) -> String {
+        let mut buf = [0u8; 256];
+        let len = socket.recv(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..len]).to_string()
+    }
+
+    // recv() blocks this thread, so the keepalive task needs another worker
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_notifications_reach_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let notifier = Arc::new(
+            SystemdNotifier::new(path.to_str().unwrap(), Some(Duration::from_millis(100))).unwrap(),
+        );
+        notifier.ready("Monitoring 3 logs");
+        assert_eq!(receive(&server), "READY=1\nSTATUS=Monitoring 3 logs");
+
+        let stats = StatsCollector::new();
+        stats.increment_processed();
+        let keepalive = Arc::clone(&notifier).spawn_keepalive(stats);
+        assert_eq!(receive(&server), "WATCHDOG=1\nSTATUS=Processed 1 certificates, 0 matches");
+        keepalive.abort();
+
+        notifier.stopping();
+        // Skip keepalives queued before the abort
+        while !receive(&server).starts_with("STOPPING=1") {}
+    }
+}
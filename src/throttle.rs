@@ -0,0 +1,84 @@
+// src/throttle.rs
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept before expired ones are first swept out
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct Notified {
+    last: HashMap<String, Instant>,
+    /// Size at which the next sweep runs: twice what the last one kept, so
+    /// sweeps stay amortized O(1) per domain even when few entries expire
+    next_sweep: usize,
+}
+
+/// Suppresses repeat notifications for the same matched domain within a window
+///
+/// Only notifications are throttled; matches are still counted and saved.
+pub struct NotifyThrottle {
+    window: Duration,
+    notified: Mutex<Notified>,
+}
+
+impl NotifyThrottle {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            notified: Mutex::new(Notified {
+                last: HashMap::new(),
+                next_sweep: SWEEP_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Returns true if `domain` hasn't been notified within the window (and records it)
+    pub fn should_notify(&self, domain: &str) -> bool {
+        let now = Instant::now();
+        let mut notified = self.notified.lock().unwrap();
+
+        if let Some(last) = notified.last.get(domain)
+            && now.duration_since(*last) < self.window
+        {
+            return false;
+        }
+
+        if notified.last.len() >= notified.next_sweep {
+            notified.last.retain(|_, last| now.duration_since(*last) < self.window);
+            notified.next_sweep = (notified.last.len() * 2).max(SWEEP_THRESHOLD);
+        }
+        notified.last.insert(domain.to_string(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttles_within_window() {
+        let throttle = NotifyThrottle::new(Duration::from_millis(50));
+
+        assert!(throttle.should_notify("a.example.com"));
+        assert!(!throttle.should_notify("a.example.com"));
+        assert!(throttle.should_notify("b.example.com"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(throttle.should_notify("a.example.com"));
+    }
+
+    #[test]
+    fn test_sweeps_only_after_doubling() {
+        let throttle = NotifyThrottle::new(Duration::from_secs(3600));
+        for i in 0..SWEEP_THRESHOLD {
+            assert!(throttle.should_notify(&format!("{}.example.com", i)));
+        }
+
+        // Nothing has expired, so the sweep keeps everything and the next one waits for twice as many
+        assert!(throttle.should_notify("one-more.example.com"));
+        let notified = throttle.notified.lock().unwrap();
+        assert_eq!(notified.last.len(), SWEEP_THRESHOLD + 1);
+        assert_eq!(notified.next_sweep, SWEEP_THRESHOLD * 2);
+    }
+}
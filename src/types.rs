@@ -1,5 +1,7 @@
 // src/types.rs
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt;
 
 #[derive(Debug, Deserialize)]
@@ -8,16 +10,25 @@ pub struct CertStreamMessage {
     pub data: Option<CertData>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CertData {
     pub all_domains: Option<Vec<String>>,
 
+    /// IP address SANs
+    #[serde(default)]
+    pub ip_addresses: Option<Vec<String>>,
+
     #[serde(rename = "cert_index")]
     pub cert_index: Option<u64>,
 
     #[serde(rename = "seen")]
     pub seen_unix: Option<f64>,
 
+    /// Timestamp the CT log gave the entry, in milliseconds (None when the
+    /// source doesn't carry it, e.g. certstream)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logged_at_ms: Option<u64>,
+
     #[serde(rename = "leaf_cert")]
     pub leaf_cert: Option<LeafCert>,
 
@@ -28,7 +39,41 @@ pub struct CertData {
     pub ct_log_url: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl CertData {
+    /// Clean up SANs as logged: trim whitespace, drop NUL and other control
+    /// characters, strip trailing dots and lowercase; empty and repeated names
+    /// are dropped
+    ///
+    /// Runs once per certificate before dedupe, so matching and every output
+    /// only ever see the cleaned names.
+    pub fn normalize_domains(&mut self) {
+        if let Some(ref mut domains) = self.all_domains {
+            let mut seen = HashSet::with_capacity(domains.len());
+            domains.retain_mut(|domain| match normalize_domain(domain) {
+                Some(normalized) if seen.insert(normalized.clone()) => {
+                    *domain = normalized;
+                    true
+                }
+                _ => false,
+            });
+        }
+        if let Some(ref mut ips) = self.ip_addresses {
+            ips.retain_mut(|ip| {
+                *ip = ip.trim().to_string();
+                !ip.is_empty()
+            });
+        }
+    }
+}
+
+/// `" WWW.Example.com.\0"` -> `www.example.com`; `None` if nothing is left
+pub fn normalize_domain(domain: &str) -> Option<String> {
+    let cleaned: String = domain.chars().filter(|c| !c.is_control()).collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').to_lowercase();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LeafCert {
     #[serde(rename = "not_before")]
     pub not_before: Option<u64>,
@@ -36,17 +81,220 @@ pub struct LeafCert {
     pub not_after: Option<u64>,
     pub fingerprint: Option<String>,
     pub issuer: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub subject_key_id: Option<String>,
+    #[serde(default)]
+    pub authority_key_id: Option<String>,
+    #[serde(default)]
+    pub key_algorithm: Option<String>,
+    #[serde(default)]
+    pub key_bits: Option<u32>,
+    #[serde(default)]
+    pub key_curve: Option<String>,
+    #[serde(default)]
+    pub signature_algorithm: Option<String>,
+    #[serde(default)]
+    pub subject: Option<DistinguishedName>,
+    #[serde(default)]
+    pub issuer_dn: Option<DistinguishedName>,
+    #[serde(default)]
+    pub issuer_chain: Vec<ChainCert>,
+    #[serde(default)]
+    pub pki_endpoints: PkiEndpoints,
+    /// PEM-encoded certificate, only attached when include_pem is enabled
+    #[serde(default)]
+    pub pem: Option<String>,
+}
+
+/// Revocation and issuer URLs published in a certificate
+///
+/// These often point at internal PKI hosts, which makes them useful recon leads.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PkiEndpoints {
+    /// CRL distribution points
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crl: Vec<String>,
+    /// OCSP responders (AIA)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ocsp: Vec<String>,
+    /// CA Issuers certificate URLs (AIA)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_issuers: Vec<String>,
+}
+
+/// A CA certificate from the chain submitted alongside a log entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ChainCert {
+    /// Subject DN
+    pub name: String,
+    /// SHA-256 fingerprint (hex)
+    pub fingerprint: String,
+}
+
+/// Selected attributes of an X.509 subject or issuer name
+///
+/// Field names follow certstream's `leaf_cert.subject` object.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DistinguishedName {
+    #[serde(rename = "CN", default, skip_serializing_if = "Option::is_none")]
+    pub common_name: Option<String>,
+    #[serde(rename = "O", default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    #[serde(rename = "OU", default, skip_serializing_if = "Option::is_none")]
+    pub organizational_unit: Option<String>,
+    #[serde(rename = "C", default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+}
+
+impl fmt::Display for DistinguishedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [
+            ("CN", &self.common_name),
+            ("O", &self.organization),
+            ("OU", &self.organizational_unit),
+            ("C", &self.country),
+        ];
+        let mut first = true;
+        for (key, value) in parts {
+            if let Some(value) = value {
+                if !first {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}={}", key, value)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kind of watchlist rule that produced a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchType {
+    /// "*.example.com": any subdomain
+    Wildcard,
+    /// ".example.com" or "example.com": the domain and its subdomains
+    Suffix,
+    /// An exact hostname from `hosts`
+    ExactHost,
+    /// An address from `ips`
+    Ip,
+    /// A range from `cidrs`
+    Cidr,
+    /// A prefix announced by an AS from `asns`
+    Asn,
+}
+
+impl MatchType {
+    /// Name used in JSON, CSV and the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wildcard => "wildcard",
+            Self::Suffix => "suffix",
+            Self::ExactHost => "exact-host",
+            Self::Ip => "ip",
+            Self::Cidr => "cidr",
+            Self::Asn => "asn",
+        }
+    }
 }
 
+impl fmt::Display for MatchType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for MatchType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "wildcard" => Ok(Self::Wildcard),
+            "suffix" => Ok(Self::Suffix),
+            "exact-host" => Ok(Self::ExactHost),
+            "ip" => Ok(Self::Ip),
+            "cidr" => Ok(Self::Cidr),
+            "asn" => Ok(Self::Asn),
+            other => anyhow::bail!("Unknown match type '{}'", other),
+        }
+    }
+}
+
+/// How urgent a program's matches are, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "info" => Ok(Self::Info),
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            other => anyhow::bail!("Invalid severity '{}'. Must be info, low, medium, high or critical", other),
+        }
+    }
+}
+
+/// Current `schema_version` of match and webhook payloads
+///
+/// Bumped when a field is removed or changes meaning; new optional fields
+/// don't change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Represents a matched certificate for output
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MatchResult {
+    /// Layout version of this payload (0 for records written before versioning)
+    #[serde(default)]
+    pub schema_version: u32,
+
     /// Timestamp when the match was found (Unix timestamp)
     pub timestamp: u64,
 
     /// The specific domain that matched the watchlist
     pub matched_domain: String,
 
+    /// Watchlist pattern, host, IP or CIDR (as written) that matched
+    #[serde(default)]
+    pub matched_pattern: Option<String>,
+
+    /// How `matched_pattern` matched
+    #[serde(default)]
+    pub match_type: Option<MatchType>,
+
     /// All domains in the certificate
     pub all_domains: Vec<String>,
 
@@ -62,15 +310,91 @@ pub struct MatchResult {
     /// Certificate fingerprint
     pub fingerprint: Option<String>,
 
+    /// Certificate serial number (hex)
+    #[serde(default)]
+    pub serial_number: Option<String>,
+
+    /// Subject Key Identifier (hex)
+    #[serde(default)]
+    pub subject_key_id: Option<String>,
+
+    /// Authority Key Identifier (hex) of the issuing CA key
+    #[serde(default)]
+    pub authority_key_id: Option<String>,
+
+    /// Public key algorithm (RSA, EC, ...)
+    #[serde(default)]
+    pub key_algorithm: Option<String>,
+
+    /// Public key size in bits
+    #[serde(default)]
+    pub key_bits: Option<u32>,
+
+    /// Named curve for EC keys
+    #[serde(default)]
+    pub key_curve: Option<String>,
+
+    /// Signature algorithm (e.g. sha256WithRSAEncryption)
+    #[serde(default)]
+    pub signature_algorithm: Option<String>,
+
+    /// Certificate subject (CN, O, OU, C)
+    #[serde(default)]
+    pub subject: Option<DistinguishedName>,
+
+    /// Certificate issuer (CN, O, OU, C)
+    #[serde(default)]
+    pub issuer_dn: Option<DistinguishedName>,
+
+    /// Issuing CA chain as submitted to the log, issuer first
+    #[serde(default)]
+    pub issuer_chain: Vec<ChainCert>,
+
+    /// CRL distribution point and AIA (OCSP, CA Issuers) URLs
+    #[serde(default)]
+    pub pki_endpoints: PkiEndpoints,
+
+    /// Information gathered about the matched host after matching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<Enrichment>,
+
+    /// Full PEM-encoded leaf certificate (only with include_pem)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pem: Option<String>,
+
     /// Bug bounty program name (if matched)
     pub program_name: Option<String>,
 
     /// Platform the program belongs to (if matched)
     pub platform: Option<String>,
 
+    /// Severity of the matched program (if set)
+    #[serde(default)]
+    pub severity: Option<Severity>,
+
+    /// Tags of the matched program
+    #[serde(default)]
+    pub tags: Vec<String>,
+
     /// Unix timestamp when the cert was seen
     pub seen_unix: Option<f64>,
 
+    /// Unix timestamp when the CT log recorded the entry (None when the source doesn't say)
+    #[serde(default)]
+    pub logged_at: Option<u64>,
+
+    /// `logged_at` to the millisecond, for measuring latency; not serialized
+    #[serde(skip)]
+    pub logged_at_ms: Option<u64>,
+
+    /// Unix timestamp when ct-scout processed the entry
+    #[serde(default)]
+    pub processed_at: u64,
+
+    /// Milliseconds from the log entry's timestamp until ct-scout emitted the match
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+
     /// Certificate issuer
     pub issuer: Option<String>,
 
@@ -82,6 +406,26 @@ pub struct MatchResult {
 }
 
 impl MatchResult {
+    /// Set `latency_ms` to the time since the log entry's timestamp, in seconds
+    ///
+    /// `None` (and nothing recorded) when the log's timestamp is unknown;
+    /// when ct-scout saw the entry is no stand-in for when it was logged.
+    pub fn record_latency(&mut self) -> Option<f64> {
+        let logged_ms = self.logged_at_ms?;
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let latency_ms = now_ms.saturating_sub(logged_ms);
+        self.latency_ms = Some(latency_ms);
+        Some(latency_ms as f64 / 1000.0)
+    }
+
+    /// Seconds from notBefore to notAfter, when both are known
+    pub fn validity_secs(&self) -> Option<u64> {
+        Some(self.not_after?.saturating_sub(self.not_before?))
+    }
+
     /// Create a new MatchResult from CertData
     pub fn from_cert_data(
         matched_domain: String,
@@ -94,21 +438,46 @@ impl MatchResult {
             .as_ref()
             .map(|leaf| (leaf.not_before, leaf.not_after, leaf.fingerprint.clone(), leaf.issuer.clone()))
             .unwrap_or((None, None, None, None));
+        let leaf = data.leaf_cert.as_ref();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
 
         Self {
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            schema_version: SCHEMA_VERSION,
+            timestamp: now,
             matched_domain,
+            matched_pattern: None,
+            match_type: None,
             all_domains: data.all_domains.clone().unwrap_or_default(),
             cert_index: data.cert_index,
             not_before,
             not_after,
             fingerprint,
+            serial_number: leaf.and_then(|leaf| leaf.serial_number.clone()),
+            subject_key_id: leaf.and_then(|leaf| leaf.subject_key_id.clone()),
+            authority_key_id: leaf.and_then(|leaf| leaf.authority_key_id.clone()),
+            key_algorithm: leaf.and_then(|leaf| leaf.key_algorithm.clone()),
+            key_bits: leaf.and_then(|leaf| leaf.key_bits),
+            key_curve: leaf.and_then(|leaf| leaf.key_curve.clone()),
+            signature_algorithm: leaf.and_then(|leaf| leaf.signature_algorithm.clone()),
+            subject: leaf.and_then(|leaf| leaf.subject.clone()),
+            issuer_dn: leaf.and_then(|leaf| leaf.issuer_dn.clone()),
+            issuer_chain: leaf.map(|leaf| leaf.issuer_chain.clone()).unwrap_or_default(),
+            pki_endpoints: leaf.map(|leaf| leaf.pki_endpoints.clone()).unwrap_or_default(),
+            pem: leaf.and_then(|leaf| leaf.pem.clone()),
+            enrichment: None,
             program_name,
             platform,
+            severity: None,
+            tags: Vec::new(),
             seen_unix: data.seen_unix,
+            logged_at: data.logged_at_ms.map(|ms| ms / 1000),
+            logged_at_ms: data.logged_at_ms,
+            processed_at: now,
+            latency_ms: None,
             issuer,
             is_precert: data.is_precert,
             ct_log_url: data.ct_log_url.clone(),
@@ -116,6 +485,104 @@ impl MatchResult {
     }
 }
 
+/// Results of post-match enrichment (see `crate::enrich`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Enrichment {
+    /// A/AAAA addresses of the matched domain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_ips: Vec<String>,
+    /// Resolved addresses inside a watched IP or CIDR
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub in_scope: Vec<ScopeHit>,
+    /// Response of the matched host to an HTTP(S) request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpProbe>,
+    /// CNAME chain of the matched domain, in resolution order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cnames: Vec<String>,
+    /// Dangling CNAME pointing at a service where the name could be claimed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub takeover: Option<TakeoverRisk>,
+    /// Open ports and banners from an internet-scan database (Shodan/Censys)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intel: Option<HostIntel>,
+    /// RDAP registration of the matched domain's registrable domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration: Option<Registration>,
+    /// Human-readable findings, e.g. "resolves into in-scope range 192.0.2.0/24"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Result of probing a matched host over HTTP(S)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HttpProbe {
+    /// URL that answered (after redirects)
+    pub url: String,
+    pub status: u16,
+    /// Server response header
+    pub server: Option<String>,
+    /// Page <title>
+    pub title: Option<String>,
+}
+
+/// Registration record of a registrable domain (from RDAP)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Registration {
+    /// Registrable domain that was looked up, e.g. "example.co.uk"
+    pub domain: String,
+    pub registrar: Option<String>,
+    /// RFC 3339 registration date
+    pub created: Option<String>,
+    /// RFC 3339 expiration date
+    pub expires: Option<String>,
+    pub registrant_org: Option<String>,
+}
+
+/// What an internet-scan database knows about a matched address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HostIntel {
+    /// Database queried ("shodan" or "censys")
+    pub source: String,
+    pub ip: String,
+    pub ports: Vec<u16>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<ServiceBanner>,
+}
+
+/// A service seen listening on a scanned address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ServiceBanner {
+    pub port: u16,
+    /// "tcp" or "udp"
+    pub transport: Option<String>,
+    /// Detected software, e.g. "nginx 1.25.3"
+    pub product: Option<String>,
+    /// Start of the service banner
+    pub banner: Option<String>,
+}
+
+/// Indicator that a matched domain may be vulnerable to subdomain takeover
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TakeoverRisk {
+    /// Hosting service the CNAME points at, e.g. "GitHub Pages"
+    pub service: String,
+    /// The dangling CNAME target
+    pub cname: String,
+    /// Why the target looks unclaimed (NXDOMAIN or the service's "not found" page)
+    pub reason: String,
+}
+
+/// A resolved address that falls inside the watchlist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScopeHit {
+    pub ip: String,
+    /// Watched IP or CIDR containing the address
+    pub range: String,
+    /// Program owning the range, if any
+    pub program: Option<String>,
+}
+
 impl fmt::Display for MatchResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[+] Match: {}", self.matched_domain)?;
@@ -130,6 +597,90 @@ impl fmt::Display for MatchResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_distinguished_name() {
+        let json = r#"{"CN": "Example CA", "O": "Example Inc", "C": "US"}"#;
+        let dn: DistinguishedName = serde_json::from_str(json).unwrap();
+
+        assert_eq!(dn.organization.as_deref(), Some("Example Inc"));
+        assert_eq!(dn.organizational_unit, None);
+        assert_eq!(dn.to_string(), "CN=Example CA, O=Example Inc, C=US");
+        assert_eq!(serde_json::to_value(&dn).unwrap(), serde_json::from_str::<serde_json::Value>(json).unwrap());
+    }
+
+    #[test]
+    fn test_normalize_domains() {
+        let mut data = CertData {
+            all_domains: Some(
+                [" WWW.Example.com. ", "www.example.com", "", "\0", "api.example.com\0", "*.Example.COM.", "..."]
+                    .map(String::from)
+                    .to_vec(),
+            ),
+            ip_addresses: Some(vec![" 10.0.0.1".to_string(), " ".to_string()]),
+            cert_index: None,
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        data.normalize_domains();
+
+        assert_eq!(data.all_domains.unwrap(), vec!["www.example.com", "api.example.com", "*.example.com"]);
+        assert_eq!(data.ip_addresses.unwrap(), vec!["10.0.0.1"]);
+    }
+
+    #[test]
+    fn test_logged_at_only_from_log_timestamp() {
+        let mut data = CertData {
+            all_domains: Some(vec!["a.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: None,
+            seen_unix: Some(1700000000.0),
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        // When ct-scout saw the entry says nothing about when it was logged
+        let result = MatchResult::from_cert_data("a.example.com".to_string(), &data, None, None);
+        assert_eq!(result.logged_at, None);
+
+        data.logged_at_ms = Some(1699999990500);
+        let result = MatchResult::from_cert_data("a.example.com".to_string(), &data, None, None);
+        assert_eq!(result.logged_at, Some(1699999990));
+    }
+
+    #[test]
+    fn test_record_latency() {
+        let logged_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+            - 90_000;
+        let mut data = CertData {
+            all_domains: Some(vec!["a.example.com".to_string()]),
+            ip_addresses: None,
+            cert_index: None,
+            seen_unix: Some(logged_ms as f64 / 1000.0),
+            logged_at_ms: Some(logged_ms),
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let mut result = MatchResult::from_cert_data("a.example.com".to_string(), &data, None, None);
+
+        let latency = result.record_latency().unwrap();
+        assert!((90.0..95.0).contains(&latency));
+        assert!((90_000..95_000).contains(&result.latency_ms.unwrap()));
+
+        // Without the log's timestamp nothing is recorded, even if the entry was seen
+        data.logged_at_ms = None;
+        let mut result = MatchResult::from_cert_data("a.example.com".to_string(), &data, None, None);
+        assert_eq!(result.record_latency(), None);
+        assert_eq!(result.latency_ms, None);
+    }
+
     #[test]
     fn test_deserialize_full_certstream_message() {
         let json = r#"{
@@ -10,25 +10,36 @@ use tokio::sync::mpsc as tokio_mpsc;
 
 /// Configuration file watcher
 pub struct ConfigWatcher {
-    path: PathBuf,
+    paths: Vec<PathBuf>,
+    overrides: Vec<String>,
 }
 
 impl ConfigWatcher {
-    /// Create a new config watcher
-    pub fn new(path: PathBuf) -> Self {
-        Self { path }
+    /// Create a new config watcher for one or more layered config files
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Re-apply `--set` overrides to every reloaded config
+    pub fn with_overrides(mut self, overrides: Vec<String>) -> Self {
+        self.overrides = overrides;
+        self
     }
 
-    /// Start watching the config file for changes
+    /// Start watching the config files for changes
     ///
-    /// Returns a receiver that will get new Config instances when the file changes
+    /// Returns a receiver that will get new Config instances when any file changes
     pub fn watch(&self) -> anyhow::Result<tokio_mpsc::Receiver<Config>> {
         let (tx, rx) = tokio_mpsc::channel(10);
-        let path = self.path.clone();
+        let paths = self.paths.clone();
+        let overrides = self.overrides.clone();
 
         // Spawn blocking task for file watching
         tokio::task::spawn_blocking(move || {
-            if let Err(e) = Self::watch_blocking(path, tx) {
+            if let Err(e) = Self::watch_blocking(paths, overrides, tx) {
                 tracing::error!("Config watcher error: {}", e);
             }
         });
@@ -37,13 +48,20 @@ impl ConfigWatcher {
     }
 
     /// Blocking file watch implementation
-    fn watch_blocking(path: PathBuf, tx: tokio_mpsc::Sender<Config>) -> anyhow::Result<()> {
+    #[allow(clippy::collapsible_if)]
+    fn watch_blocking(
+        paths: Vec<PathBuf>,
+        overrides: Vec<String>,
+        tx: tokio_mpsc::Sender<Config>,
+    ) -> anyhow::Result<()> {
         let (notify_tx, notify_rx) = mpsc::channel();
 
         let mut watcher = notify::recommended_watcher(notify_tx)?;
-        watcher.watch(&path, RecursiveMode::NonRecursive)?;
+        for path in &paths {
+            watcher.watch(path, RecursiveMode::NonRecursive)?;
+        }
 
-        tracing::info!("Watching config file: {:?}", path);
+        tracing::info!("Watching config files: {:?}", paths);
 
         // Debounce to avoid multiple reloads for a single file change
         let mut last_reload = std::time::Instant::now();
@@ -60,9 +78,9 @@ impl ConfigWatcher {
 
                     last_reload = now;
 
-                    match Config::from_file(&path) {
+                    match Config::load(&paths, &overrides) {
                         Ok(config) => {
-                            tracing::info!("Config reloaded from {:?}", path);
+                            tracing::info!("Config reloaded from {:?}", paths);
                             if tx.blocking_send(config).is_err() {
                                 tracing::warn!("Config receiver dropped, stopping watcher");
                                 break;
@@ -93,16 +111,15 @@ impl ConfigWatcher {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
     use tempfile::NamedTempFile;
 
     #[tokio::test]
     async fn test_config_watcher_creation() {
         let temp_file = NamedTempFile::new().unwrap();
-        let watcher = ConfigWatcher::new(temp_file.path().to_path_buf());
+        let watcher = ConfigWatcher::new(vec![temp_file.path().to_path_buf()]);
 
         // Just verify we can create a watcher
-        assert_eq!(watcher.path, temp_file.path());
+        assert_eq!(watcher.paths, vec![temp_file.path().to_path_buf()]);
     }
 
     // Note: Full integration test of file watching is complex due to
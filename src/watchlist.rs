@@ -1,9 +1,61 @@
 // src/watchlist.rs
 use crate::config::{ProgramConfig, WatchlistConfig};
+use crate::types::{MatchResult, MatchType, Severity};
 use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Which global watchlist entries an edit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Domain,
+    Host,
+    Ip,
+    Cidr,
+}
+
+impl FromStr for EntryKind {
+    type Err = anyhow::Error;
+
+    /// Named like the `[watchlist]` keys: "domains", "hosts", "ips" or "cidrs"
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "domains" => Ok(Self::Domain),
+            "hosts" => Ok(Self::Host),
+            "ips" => Ok(Self::Ip),
+            "cidrs" => Ok(Self::Cidr),
+            other => anyhow::bail!("Unknown watchlist entry kind '{}'. Must be domains, hosts, ips or cidrs", other),
+        }
+    }
+}
+
+/// How a wildcard SAN such as `*.example.com` is matched against the watchlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WildcardSans {
+    /// The certificate covers every name one label below the wildcard, so
+    /// `*.example.com` also matches watched `api.example.com`
+    #[default]
+    Expand,
+    /// Match the SAN string as written, like any other name
+    Literal,
+}
+
+impl FromStr for WildcardSans {
+    type Err = anyhow::Error;
 
-#[derive(Debug, Clone)]
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "expand" => Ok(Self::Expand),
+            "literal" => Ok(Self::Literal),
+            other => anyhow::bail!("Invalid wildcard_sans '{}'. Must be 'expand' or 'literal'", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Program {
     pub name: String,
     pub platform: Option<String>, // e.g., "HackerOne", "Intigriti", "Config"
@@ -11,15 +63,78 @@ pub struct Program {
     pub hosts: Vec<String>,   // exact hostnames
     pub ips: Vec<IpAddr>,     // specific IP addresses
     pub cidrs: Vec<IpNet>,    // IP ranges
+    pub asns: Vec<u32>,       // AS numbers, matched through `Watchlist::asn_prefixes`
+    pub severity: Option<Severity>, // copied onto matches for routing
+    pub tags: Vec<String>,
+    pub expected_issuers: Vec<String>, // CA names; empty = any CA
 }
 
-#[derive(Debug, Clone)]
+impl Program {
+    /// The match's issuer, when this program pins its CAs and the issuer is
+    /// none of them
+    ///
+    /// Expected names match case-insensitively anywhere in the issuer's
+    /// organization or common name. Matches without issuer details pass.
+    pub fn unexpected_issuer(&self, result: &MatchResult) -> Option<String> {
+        if self.expected_issuers.is_empty() {
+            return None;
+        }
+        let dn = result.issuer_dn.as_ref();
+        let names: Vec<String> = [
+            dn.and_then(|dn| dn.organization.as_deref()),
+            dn.and_then(|dn| dn.common_name.as_deref()),
+            result.issuer.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_lowercase)
+        .collect();
+        if names.is_empty() {
+            return None;
+        }
+
+        let expected = self.expected_issuers.iter().any(|issuer| {
+            let issuer = issuer.to_lowercase();
+            names.iter().any(|name| name.contains(&issuer))
+        });
+        if expected {
+            return None;
+        }
+        Some(match dn {
+            Some(dn) => dn.to_string(),
+            None => result.issuer.clone().unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Watchlist {
     pub global_domains: Vec<String>, // suffixes, e.g. ".world.org"
     pub global_hosts: Vec<String>,   // exact names
     pub global_ips: Vec<IpAddr>,
     pub global_cidrs: Vec<IpNet>,
+    pub global_asns: Vec<u32>,
     pub programs: Vec<Program>,
+    pub wildcard_sans: WildcardSans,
+    /// Prefixes announced by each watched AS, filled in by the ASN refresher
+    #[serde(skip)]
+    pub asn_prefixes: HashMap<u32, Vec<IpNet>>,
+}
+
+/// AS number from "AS13335" or "13335"
+pub fn parse_asn(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let digits = s
+        .strip_prefix("AS")
+        .or_else(|| s.strip_prefix("as"))
+        .unwrap_or(s);
+    digits.parse().ok()
+}
+
+fn parse_asns(asns: &[String]) -> anyhow::Result<Vec<u32>> {
+    asns.iter()
+        .map(|asn| parse_asn(asn).ok_or_else(|| anyhow::anyhow!("Invalid AS number '{}'", asn)))
+        .collect()
 }
 
 impl Watchlist {
@@ -56,6 +171,10 @@ impl Watchlist {
                     hosts: p.hosts.clone(),
                     ips,
                     cidrs,
+                    asns: parse_asns(&p.asns)?,
+                    severity: p.severity.as_deref().map(str::parse).transpose()?,
+                    tags: p.tags.clone(),
+                    expected_issuers: p.expected_issuers.clone(),
                 })
             })
             .collect::<Result<Vec<_>, anyhow::Error>>()?;
@@ -65,7 +184,10 @@ impl Watchlist {
             global_hosts: wl.hosts.clone(),
             global_ips,
             global_cidrs,
+            global_asns: parse_asns(&wl.asns)?,
             programs,
+            wildcard_sans: wl.wildcard_sans.parse()?,
+            asn_prefixes: HashMap::new(),
         })
     }
 
@@ -73,13 +195,13 @@ impl Watchlist {
         let host = domain.to_ascii_lowercase();
 
         // Check exact host match in global watchlist
-        if self.global_hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)) {
+        if self.global_hosts.iter().any(|h| self.matches_host(&host, h)) {
             return true;
         }
 
         // Check wildcard/suffix patterns in global_domains
         if self.global_domains.iter().any(|pattern| {
-            Self::matches_pattern(&host, pattern)
+            self.matches_domain_pattern(&host, pattern)
         }) {
             return true;
         }
@@ -87,13 +209,13 @@ impl Watchlist {
         // Check program-specific hosts and domains
         for program in &self.programs {
             // Check exact host match
-            if program.hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)) {
+            if program.hosts.iter().any(|h| self.matches_host(&host, h)) {
                 return true;
             }
 
             // Check domain patterns
             if program.domains.iter().any(|pattern| {
-                Self::matches_pattern(&host, pattern)
+                self.matches_domain_pattern(&host, pattern)
             }) {
                 return true;
             }
@@ -102,6 +224,34 @@ impl Watchlist {
         false
     }
 
+    /// Exact host match, or a wildcard SAN covering the host when expanding
+    fn matches_host(&self, host: &str, watched: &str) -> bool {
+        host.eq_ignore_ascii_case(watched)
+            || (self.wildcard_sans == WildcardSans::Expand && Self::wildcard_covers(host, watched))
+    }
+
+    /// Pattern match; when expanding, a wildcard SAN also matches suffix and
+    /// plain patterns for a name it covers (`*.example.com` vs `.api.example.com`)
+    fn matches_domain_pattern(&self, host: &str, pattern: &str) -> bool {
+        if Self::matches_pattern(host, pattern) {
+            return true;
+        }
+        self.wildcard_sans == WildcardSans::Expand
+            && !pattern.starts_with("*.")
+            && Self::wildcard_covers(host, pattern.trim_start_matches('.'))
+    }
+
+    /// Whether a wildcard SAN (`*.example.com`) is valid for `name` (`api.example.com`)
+    ///
+    /// A wildcard stands for exactly one label, as in certificate validation.
+    fn wildcard_covers(san: &str, name: &str) -> bool {
+        let Some(suffix) = san.strip_prefix("*.") else {
+            return false;
+        };
+        name.split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && label != "*" && rest.eq_ignore_ascii_case(suffix))
+    }
+
     /// Match a hostname against a pattern (wildcard or suffix)
     /// Patterns can be:
     /// - "*.example.com" - wildcard, matches "foo.example.com" but NOT "example.com"
@@ -126,17 +276,46 @@ impl Watchlist {
         host == pattern_lower || host.ends_with(&format!(".{}", pattern_lower))
     }
 
+    /// The watched host or domain pattern (as written) that matches a domain
+    pub fn pattern_for_domain(&self, domain: &str) -> Option<&str> {
+        self.rule_for_domain(domain).map(|(pattern, _)| pattern)
+    }
+
+    /// Like `pattern_for_domain`, also saying how the pattern matched
+    pub fn rule_for_domain(&self, domain: &str) -> Option<(&str, MatchType)> {
+        let host = domain.to_ascii_lowercase();
+        self.rule_in(&host, &self.global_hosts, &self.global_domains).or_else(|| {
+            self.programs
+                .iter()
+                .find_map(|program| self.rule_in(&host, &program.hosts, &program.domains))
+        })
+    }
+
+    /// First exact host, else first domain pattern, matching a lowercased host
+    fn rule_in<'a>(&self, host: &str, hosts: &'a [String], domains: &'a [String]) -> Option<(&'a str, MatchType)> {
+        if let Some(exact) = hosts.iter().find(|h| self.matches_host(host, h)) {
+            return Some((exact, MatchType::ExactHost));
+        }
+        domains
+            .iter()
+            .find(|pattern| self.matches_domain_pattern(host, pattern))
+            .map(|pattern| {
+                let match_type = if pattern.starts_with("*.") { MatchType::Wildcard } else { MatchType::Suffix };
+                (pattern.as_str(), match_type)
+            })
+    }
+
     pub fn program_for_domain(&self, domain: &str) -> Option<&Program> {
         let host = domain.to_ascii_lowercase();
         for program in &self.programs {
             // Check exact host match first
-            if program.hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)) {
+            if program.hosts.iter().any(|h| self.matches_host(&host, h)) {
                 return Some(program);
             }
 
             // Check domain patterns
             for pattern in &program.domains {
-                if Self::matches_pattern(&host, pattern) {
+                if self.matches_domain_pattern(&host, pattern) {
                     return Some(program);
                 }
             }
@@ -169,7 +348,47 @@ impl Watchlist {
             }
         }
 
-        false
+        self.asn_for_ip(ip, self.all_asns()).is_some()
+    }
+
+    /// Every watched AS number, global and per program
+    pub fn all_asns(&self) -> impl Iterator<Item = &u32> {
+        self.global_asns.iter().chain(self.programs.iter().flat_map(|p| p.asns.iter()))
+    }
+
+    /// First AS among `asns` announcing a prefix that contains the address
+    fn asn_for_ip<'a>(&self, ip: &IpAddr, asns: impl IntoIterator<Item = &'a u32>) -> Option<(u32, IpNet)> {
+        asns.into_iter().find_map(|asn| {
+            let prefix = self.asn_prefixes.get(asn)?.iter().find(|prefix| prefix.contains(ip))?;
+            Some((*asn, *prefix))
+        })
+    }
+
+    /// The watched IP or CIDR (as written) that contains an address
+    pub fn range_for_ip(&self, ip: &IpAddr) -> Option<String> {
+        self.rule_for_ip(ip).map(|(range, _)| range)
+    }
+
+    /// Like `range_for_ip`, also saying whether an exact IP or a CIDR matched
+    pub fn rule_for_ip(&self, ip: &IpAddr) -> Option<(String, MatchType)> {
+        let exact = self
+            .global_ips
+            .iter()
+            .chain(self.programs.iter().flat_map(|p| p.ips.iter()))
+            .find(|watched| *watched == ip);
+        if let Some(exact) = exact {
+            return Some((exact.to_string(), MatchType::Ip));
+        }
+
+        self.global_cidrs
+            .iter()
+            .chain(self.programs.iter().flat_map(|p| p.cidrs.iter()))
+            .find(|cidr| cidr.contains(ip))
+            .map(|cidr| (cidr.to_string(), MatchType::Cidr))
+            .or_else(|| {
+                self.asn_for_ip(ip, self.all_asns())
+                    .map(|(asn, prefix)| (format!("AS{} ({})", asn, prefix), MatchType::Asn))
+            })
     }
 
     /// Find which program (if any) an IP belongs to based on exact IP or CIDR ranges
@@ -185,7 +404,9 @@ impl Watchlist {
                 return Some(program);
             }
         }
-        None
+        self.programs
+            .iter()
+            .find(|program| self.asn_for_ip(ip, &program.asns).is_some())
     }
 
     /// Add a domain to a program, creating the program if it doesn't exist
@@ -202,6 +423,10 @@ impl Watchlist {
                 hosts: Vec::new(),
                 ips: Vec::new(),
                 cidrs: Vec::new(),
+                asns: Vec::new(),
+                severity: None,
+                tags: Vec::new(),
+                expected_issuers: Vec::new(),
             });
         }
     }
@@ -220,15 +445,95 @@ impl Watchlist {
                 hosts: vec![host.to_string()],
                 ips: Vec::new(),
                 cidrs: Vec::new(),
+                asns: Vec::new(),
+                severity: None,
+                tags: Vec::new(),
+                expected_issuers: Vec::new(),
             });
         }
     }
 
+    /// Add a global entry; `Ok(false)` if it is already watched
+    pub fn add_global(&mut self, kind: EntryKind, value: &str) -> anyhow::Result<bool> {
+        fn insert<T: PartialEq>(entries: &mut Vec<T>, value: T) -> bool {
+            let added = !entries.contains(&value);
+            if added {
+                entries.push(value);
+            }
+            added
+        }
+
+        let value = value.trim();
+        if value.is_empty() {
+            anyhow::bail!("Watchlist entry is empty");
+        }
+        Ok(match kind {
+            EntryKind::Domain => insert(&mut self.global_domains, value.to_ascii_lowercase()),
+            EntryKind::Host => insert(&mut self.global_hosts, value.to_ascii_lowercase()),
+            EntryKind::Ip => insert(&mut self.global_ips, value.parse()?),
+            EntryKind::Cidr => insert(&mut self.global_cidrs, value.parse()?),
+        })
+    }
+
+    /// Remove a global entry; `Ok(false)` if it wasn't watched
+    pub fn remove_global(&mut self, kind: EntryKind, value: &str) -> anyhow::Result<bool> {
+        fn remove<T: PartialEq>(entries: &mut Vec<T>, value: &T) -> bool {
+            let before = entries.len();
+            entries.retain(|entry| entry != value);
+            entries.len() != before
+        }
+
+        let value = value.trim();
+        Ok(match kind {
+            EntryKind::Domain => remove(&mut self.global_domains, &value.to_ascii_lowercase()),
+            EntryKind::Host => remove(&mut self.global_hosts, &value.to_ascii_lowercase()),
+            EntryKind::Ip => remove(&mut self.global_ips, &value.parse()?),
+            EntryKind::Cidr => remove(&mut self.global_cidrs, &value.parse()?),
+        })
+    }
+
     /// Get all programs
     pub fn programs(&self) -> &[Program] {
         &self.programs
     }
 
+    /// Export watchlist as JSON, with the same layout as the TOML export
+    pub fn export_to_json(&self) -> serde_json::Value {
+        let addresses = |ips: &[IpAddr]| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>();
+        let ranges = |cidrs: &[IpNet]| cidrs.iter().map(|cidr| cidr.to_string()).collect::<Vec<_>>();
+        let asns = |asns: &[u32]| asns.iter().map(|asn| format!("AS{}", asn)).collect::<Vec<_>>();
+
+        let programs: Vec<serde_json::Value> = self
+            .programs
+            .iter()
+            .map(|program| {
+                serde_json::json!({
+                    "name": program.name,
+                    "platform": program.platform,
+                    "domains": program.domains,
+                    "hosts": program.hosts,
+                    "ips": addresses(&program.ips),
+                    "cidrs": ranges(&program.cidrs),
+                    "asns": asns(&program.asns),
+                    "severity": program.severity,
+                    "tags": program.tags,
+                    "expected_issuers": program.expected_issuers,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "watchlist": {
+                "domains": self.global_domains,
+                "hosts": self.global_hosts,
+                "ips": addresses(&self.global_ips),
+                "cidrs": ranges(&self.global_cidrs),
+                "asns": asns(&self.global_asns),
+            },
+            "programs": programs,
+        })
+    }
+
     /// Export watchlist to TOML format
     pub fn export_to_toml(&self) -> String {
         let mut output = String::new();
@@ -244,6 +549,11 @@ impl Watchlist {
         let global_cidrs_str: Vec<String> = self.global_cidrs.iter().map(|cidr| cidr.to_string()).collect();
         output.push_str(&format!("cidrs = {:?}\n", global_cidrs_str));
 
+        if !self.global_asns.is_empty() {
+            let global_asns_str: Vec<String> = self.global_asns.iter().map(|asn| format!("AS{}", asn)).collect();
+            output.push_str(&format!("asns = {:?}\n", global_asns_str));
+        }
+
         output.push('\n');
 
         // Export programs
@@ -265,6 +575,21 @@ impl Watchlist {
             let program_cidrs_str: Vec<String> = program.cidrs.iter().map(|cidr| cidr.to_string()).collect();
             output.push_str(&format!("cidrs = {:?}\n", program_cidrs_str));
 
+            if !program.asns.is_empty() {
+                let program_asns_str: Vec<String> = program.asns.iter().map(|asn| format!("AS{}", asn)).collect();
+                output.push_str(&format!("asns = {:?}\n", program_asns_str));
+            }
+
+            if let Some(severity) = program.severity {
+                output.push_str(&format!("severity = \"{}\"\n", severity));
+            }
+            if !program.tags.is_empty() {
+                output.push_str(&format!("tags = {:?}\n", program.tags));
+            }
+            if !program.expected_issuers.is_empty() {
+                output.push_str(&format!("expected_issuers = {:?}\n", program.expected_issuers));
+            }
+
             output.push('\n');
         }
 
@@ -272,6 +597,7 @@ impl Watchlist {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Watchlist {
     fn default() -> Self {
         Self {
@@ -279,7 +605,10 @@ impl Default for Watchlist {
             global_hosts: Vec::new(),
             global_ips: Vec::new(),
             global_cidrs: Vec::new(),
+            global_asns: Vec::new(),
             programs: Vec::new(),
+            wildcard_sans: WildcardSans::default(),
+            asn_prefixes: HashMap::new(),
         }
     }
 }
@@ -308,6 +637,7 @@ mod tests {
                 "172.16.0.0/12".to_string(),
                 "203.79.37.0/29".to_string(),
             ],
+            ..Default::default()
         };
 
         let programs = vec![
@@ -317,6 +647,7 @@ mod tests {
                 hosts: vec![],
                 cidrs: vec![],
                 ips: vec![],
+                ..Default::default()
             },
             ProgramConfig {
                 name: "Hilton".to_string(),
@@ -324,6 +655,7 @@ mod tests {
                 hosts: vec![],
                 cidrs: vec!["192.251.125.0/24".to_string()],
                 ips: vec![],
+                ..Default::default()
             },
         ];
 
@@ -334,11 +666,12 @@ mod tests {
     fn test_wildcard_pattern_matching() {
         let watchlist = create_test_watchlist();
 
-        // "*.ibm.com" should match subdomains but NOT the domain itself
+        // "*.ibm.com" should match subdomains
         assert!(watchlist.matches_domain("foo.ibm.com"));
         assert!(watchlist.matches_domain("bar.baz.ibm.com"));
         assert!(watchlist.matches_domain("www.ibm.com"));
-        assert!(!watchlist.matches_domain("ibm.com"));
+        // *.ibm.com doesn't match ibm.com itself, but the IBM program's .ibm.com suffix does
+        assert!(watchlist.matches_domain("ibm.com"));
     }
 
     #[test]
@@ -468,6 +801,7 @@ mod tests {
             hosts: vec![],
             ips: vec![],
             cidrs: vec!["invalid_cidr".to_string()],
+            ..Default::default()
         };
 
         let result = Watchlist::from_config(&watchlist_config, &[]);
@@ -481,6 +815,7 @@ mod tests {
             hosts: vec![],
             ips: vec!["not.an.ip".to_string()],
             cidrs: vec![],
+            ..Default::default()
         };
 
         let result = Watchlist::from_config(&watchlist_config, &[]);
@@ -505,4 +840,123 @@ mod tests {
         assert!(watchlist.matches_domain("sub.example.com"));
         assert!(watchlist.program_for_domain("sub.example.com").is_some());
     }
+
+    #[test]
+    fn test_pattern_for_domain() {
+        let watchlist = create_test_watchlist();
+
+        assert_eq!(watchlist.pattern_for_domain("API.Service.io"), Some("api.service.io"));
+        assert_eq!(watchlist.pattern_for_domain("www.hilton.com"), Some(".hilton.com"));
+        // Program-only patterns
+        assert_eq!(watchlist.pattern_for_domain("www.hilton.io"), Some(".hilton.io"));
+        assert_eq!(watchlist.pattern_for_domain("nomatch.org"), None);
+        for domain in ["api.example.com", "example.com", "ibm.com", "a.hilton.io", "nomatch.org"] {
+            assert_eq!(watchlist.pattern_for_domain(domain).is_some(), watchlist.matches_domain(domain));
+        }
+    }
+
+    #[test]
+    fn test_export_to_json() {
+        let json = create_test_watchlist().export_to_json();
+
+        assert_eq!(json["watchlist"]["hosts"], serde_json::json!(["exact.host.com", "api.service.io"]));
+        assert_eq!(json["watchlist"]["cidrs"][1], "203.79.37.0/29");
+        assert_eq!(json["programs"][1]["name"], "Hilton");
+        assert_eq!(json["programs"][1]["platform"], "Config");
+        assert_eq!(json["programs"][1]["cidrs"], serde_json::json!(["192.251.125.0/24"]));
+    }
+
+    #[test]
+    fn test_range_for_ip() {
+        let watchlist = create_test_watchlist();
+
+        assert_eq!(
+            watchlist.range_for_ip(&"203.79.37.3".parse().unwrap()).as_deref(),
+            Some("203.79.37.0/29")
+        );
+        assert_eq!(
+            watchlist.range_for_ip(&"10.0.0.5".parse().unwrap()).as_deref(),
+            Some("10.0.0.5")
+        );
+        assert_eq!(watchlist.range_for_ip(&"8.8.8.8".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_match_types() {
+        let watchlist = create_test_watchlist();
+
+        assert_eq!(watchlist.rule_for_domain("a.ibm.com"), Some(("*.ibm.com", MatchType::Wildcard)));
+        assert_eq!(watchlist.rule_for_domain("hilton.com"), Some((".hilton.com", MatchType::Suffix)));
+        assert_eq!(watchlist.rule_for_domain("www.example.com"), Some(("example.com", MatchType::Suffix)));
+        assert_eq!(watchlist.rule_for_domain("EXACT.host.com"), Some(("exact.host.com", MatchType::ExactHost)));
+        assert_eq!(
+            watchlist.rule_for_ip(&"10.0.0.5".parse().unwrap()),
+            Some(("10.0.0.5".to_string(), MatchType::Ip))
+        );
+        assert_eq!(
+            watchlist.rule_for_ip(&"192.251.125.9".parse().unwrap()),
+            Some(("192.251.125.0/24".to_string(), MatchType::Cidr))
+        );
+    }
+
+    #[test]
+    fn test_wildcard_sans() {
+        let mut watchlist = create_test_watchlist();
+        watchlist.add_domain_to_program(".shop.acme.test", "Acme", None);
+
+        // The certificate is valid for exact.host.com, one label below the wildcard
+        assert!(watchlist.matches_domain("*.host.com"));
+        assert_eq!(watchlist.rule_for_domain("*.HOST.com"), Some(("exact.host.com", MatchType::ExactHost)));
+        assert_eq!(watchlist.program_for_domain("*.acme.test").map(|p| p.name.as_str()), Some("Acme"));
+        assert!(!watchlist.matches_domain("*.other.com"));
+        assert!(!watchlist.matches_domain("*.exact.host.com"));
+
+        watchlist.wildcard_sans = WildcardSans::Literal;
+        assert!(!watchlist.matches_domain("*.host.com"));
+        assert!(watchlist.program_for_domain("*.acme.test").is_none());
+        // Patterns still match the SAN as written
+        assert!(watchlist.matches_domain("*.ibm.com"));
+
+        assert!("sideways".parse::<WildcardSans>().is_err());
+    }
+
+    #[test]
+    fn test_unexpected_issuer() {
+        use crate::types::{CertData, DistinguishedName};
+
+        let mut watchlist = create_test_watchlist();
+        watchlist.programs[0].expected_issuers = vec!["DigiCert".to_string(), "Amazon".to_string()];
+        let program = &watchlist.programs[0];
+
+        let data = CertData {
+            all_domains: Some(vec!["api.ibm.com".to_string()]),
+            ip_addresses: None,
+            cert_index: None,
+            seen_unix: None,
+            logged_at_ms: None,
+            leaf_cert: None,
+            is_precert: false,
+            ct_log_url: None,
+        };
+        let mut result = MatchResult::from_cert_data("api.ibm.com".to_string(), &data, None, None);
+        // Nothing known about the issuer, nothing to flag
+        assert_eq!(program.unexpected_issuer(&result), None);
+
+        result.issuer_dn = Some(DistinguishedName {
+            common_name: Some("Amazon RSA 2048 M02".to_string()),
+            organization: Some("Amazon".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(program.unexpected_issuer(&result), None);
+
+        result.issuer_dn = Some(DistinguishedName {
+            common_name: Some("R11".to_string()),
+            organization: Some("Let's Encrypt".to_string()),
+            ..Default::default()
+        });
+        assert!(program.unexpected_issuer(&result).unwrap().contains("Let's Encrypt"));
+
+        // Programs without expected_issuers accept any CA
+        assert_eq!(watchlist.programs[1].unexpected_issuer(&result), None);
+    }
 }
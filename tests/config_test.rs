@@ -8,13 +8,16 @@ fn test_load_test_config() {
     let config = Config::from_file(config_path).expect("Failed to load test config");
 
     // Verify certstream config
-    assert_eq!(config.certstream.url, "ws://127.0.0.1:4000/full-stream");
-    assert_eq!(config.certstream.reconnect_delay_secs, 5);
+    let certstream = config.certstream.as_ref().unwrap();
+    assert_eq!(certstream.url, "ws://127.0.0.1:4000/full-stream");
+    assert_eq!(certstream.reconnect_delay_secs, 5);
+    assert!(certstream.poll_logs);
 
     // Verify webhook config
-    assert_eq!(config.webhook.url, "https://example.com/webhook");
-    assert_eq!(config.webhook.secret, Some("test_secret_key".to_string()));
-    assert_eq!(config.webhook.timeout_secs, Some(10));
+    let webhook = config.webhook.as_ref().unwrap();
+    assert_eq!(webhook.url, "https://example.com/webhook");
+    assert_eq!(webhook.secret, Some("test_secret_key".to_string()));
+    assert_eq!(webhook.timeout_secs, Some(10));
 
     // Verify logging config
     assert_eq!(config.logging.level, "info");
@@ -62,8 +65,9 @@ fn test_watchlist_from_config() {
     // Test exact host that shouldn't match subdomains
     assert!(!watchlist.matches_domain("sub.exact.example.com"));
 
-    // Test wildcard that shouldn't match base domain
-    assert!(!watchlist.matches_domain("ibm.com")); // *.ibm.com doesn't match ibm.com itself
+    // *.ibm.com doesn't match ibm.com itself, but the IBM program's .ibm.com suffix does
+    assert!(watchlist.matches_domain("ibm.com"));
+    assert_eq!(watchlist.program_for_domain("ibm.com").unwrap().name, "IBM");
 
     // Test IP matching
     let ip1: std::net::IpAddr = "192.168.1.100".parse().unwrap();
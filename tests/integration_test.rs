@@ -1,11 +1,17 @@
 // Integration tests for ct-scout
-use ct_scout::certstream::run_certstream_loop;
+use ct_scout::certstream::CertstreamSource;
 use ct_scout::config::{CertstreamConfig, ProgramConfig, WatchlistConfig, WebhookConfig};
+use ct_scout::ct_log::{ChannelConfig, CtLogCoordinator, HttpConfig, LogIds, LogMonitorConfig, RateLimitConfig, RetryConfig};
 use ct_scout::dedupe::Dedupe;
-use ct_scout::notifier::Notifier;
+use ct_scout::output::OutputManager;
+use ct_scout::output::webhook::WebhookOutput;
+use ct_scout::progress::ProgressIndicator;
+use ct_scout::state::StateManager;
+use ct_scout::stats::StatsCollector;
 use ct_scout::watchlist::Watchlist;
 
 use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::time::Duration;
 use tokio_tungstenite::accept_async;
@@ -13,11 +19,10 @@ use tokio_tungstenite::tungstenite::Message;
 use wiremock::{Mock, MockServer, ResponseTemplate};
 use wiremock::matchers::{method, path};
 
-/// Helper function to create a mock WebSocket server
-async fn start_mock_certstream_server(port: u16, messages: Vec<String>) {
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", port))
-        .await
-        .unwrap();
+/// Helper function to create a mock WebSocket server, returning its port
+async fn start_mock_certstream_server(messages: Vec<String>) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
 
     tokio::spawn(async move {
         if let Ok((stream, _)) = listener.accept().await {
@@ -27,7 +32,7 @@ async fn start_mock_certstream_server(port: u16, messages: Vec<String>) {
             // Send all messages to the client
             for msg_text in messages {
                 ws_sender
-                    .send(Message::Text(msg_text))
+                    .send(Message::Text(msg_text.into()))
                     .await
                     .unwrap();
                 tokio::time::sleep(Duration::from_millis(10)).await;
@@ -38,8 +43,56 @@ async fn start_mock_certstream_server(port: u16, messages: Vec<String>) {
         }
     });
 
-    // Give the server time to start
-    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+fn monitor_config() -> LogMonitorConfig {
+    LogMonitorConfig {
+        poll_interval_secs: 60,
+        batch_size: 10,
+        parse_precerts: true,
+        parallel_fetches: 1,
+        rate_limit: RateLimitConfig::default(),
+        max_concurrent_requests: None,
+        audit: None,
+        quarantine: None,
+        http: HttpConfig::default(),
+        retry: RetryConfig::default(),
+        channel: ChannelConfig::default(),
+        adaptive_poll: None,
+        include_pem: false,
+        since_ms: None,
+        stale_after_secs: None,
+        commit_batch: 1,
+        log_ids: LogIds::default(),
+    }
+}
+
+/// Run the processing pipeline on certstream input only, notifying the webhook
+async fn run_certstream(certstream_config: CertstreamConfig, watchlist: Watchlist, webhook_config: WebhookConfig) {
+    let state_dir = tempfile::tempdir().unwrap();
+    let state = StateManager::new(state_dir.path().join("state.toml")).await.unwrap();
+    let mut coordinator = CtLogCoordinator::new(
+        Vec::new(),
+        Arc::new(state),
+        monitor_config(),
+        None,
+        StatsCollector::new(),
+    );
+    coordinator.add_source(Box::new(CertstreamSource(certstream_config))).unwrap();
+
+    let mut output_manager = OutputManager::new();
+    output_manager.add_handler(Arc::new(WebhookOutput::new(webhook_config).unwrap()));
+
+    coordinator
+        .run(
+            Arc::new(tokio::sync::Mutex::new(watchlist)),
+            output_manager,
+            Dedupe::new(),
+            ProgressIndicator::new(false),
+            None,
+        )
+        .await;
 }
 
 #[tokio::test]
@@ -93,14 +146,14 @@ async fn test_end_to_end_domain_matching() {
         .to_string(),
     ];
 
-    // Start mock certstream server on a random high port
-    let ws_port = 19001;
-    start_mock_certstream_server(ws_port, messages).await;
+    // Start mock certstream server on a free port
+    let ws_port = start_mock_certstream_server(messages).await;
 
     // Configure ct-scout
     let certstream_config = CertstreamConfig {
         url: format!("ws://127.0.0.1:{}/", ws_port),
         reconnect_delay_secs: 1,
+        poll_logs: false,
     };
 
     let watchlist_config = WatchlistConfig {
@@ -108,6 +161,7 @@ async fn test_end_to_end_domain_matching() {
         hosts: vec!["exact.host.com".to_string()],
         ips: vec![],
         cidrs: vec![],
+        ..Default::default()
     };
 
     let programs = vec![ProgramConfig {
@@ -116,6 +170,7 @@ async fn test_end_to_end_domain_matching() {
         hosts: vec![],
         cidrs: vec![],
         ips: vec![],
+        ..Default::default()
     }];
 
     let watchlist = Watchlist::from_config(&watchlist_config, &programs).unwrap();
@@ -124,14 +179,12 @@ async fn test_end_to_end_domain_matching() {
         url: webhook_server.uri(),
         secret: None,
         timeout_secs: Some(5),
+        ..Default::default()
     };
 
-    let notifier = Notifier::new(webhook_config);
-    let dedupe = Dedupe::new();
-
     // Run certstream loop with timeout
     let certstream_task = tokio::spawn(async move {
-        run_certstream_loop(certstream_config, watchlist, notifier, dedupe).await;
+        run_certstream(certstream_config, watchlist, webhook_config).await;
     });
 
     // Let it run for a bit
@@ -176,12 +229,12 @@ async fn test_dedupe_prevents_duplicate_notifications() {
         .to_string(),
     ];
 
-    let ws_port = 19002;
-    start_mock_certstream_server(ws_port, messages).await;
+    let ws_port = start_mock_certstream_server(messages).await;
 
     let certstream_config = CertstreamConfig {
         url: format!("ws://127.0.0.1:{}/", ws_port),
         reconnect_delay_secs: 1,
+        poll_logs: false,
     };
 
     let watchlist_config = WatchlistConfig {
@@ -189,6 +242,7 @@ async fn test_dedupe_prevents_duplicate_notifications() {
         hosts: vec![],
         ips: vec![],
         cidrs: vec![],
+        ..Default::default()
     };
 
     let watchlist = Watchlist::from_config(&watchlist_config, &[]).unwrap();
@@ -197,13 +251,11 @@ async fn test_dedupe_prevents_duplicate_notifications() {
         url: webhook_server.uri(),
         secret: None,
         timeout_secs: Some(5),
+        ..Default::default()
     };
 
-    let notifier = Notifier::new(webhook_config);
-    let dedupe = Dedupe::new();
-
     let certstream_task = tokio::spawn(async move {
-        run_certstream_loop(certstream_config, watchlist, notifier, dedupe).await;
+        run_certstream(certstream_config, watchlist, webhook_config).await;
     });
 
     tokio::time::sleep(Duration::from_secs(1)).await;
@@ -258,12 +310,12 @@ async fn test_wildcard_and_suffix_matching() {
         .to_string(),
     ];
 
-    let ws_port = 19003;
-    start_mock_certstream_server(ws_port, messages).await;
+    let ws_port = start_mock_certstream_server(messages).await;
 
     let certstream_config = CertstreamConfig {
         url: format!("ws://127.0.0.1:{}/", ws_port),
         reconnect_delay_secs: 1,
+        poll_logs: false,
     };
 
     let watchlist_config = WatchlistConfig {
@@ -275,6 +327,7 @@ async fn test_wildcard_and_suffix_matching() {
         hosts: vec![],
         ips: vec![],
         cidrs: vec![],
+        ..Default::default()
     };
 
     let watchlist = Watchlist::from_config(&watchlist_config, &[]).unwrap();
@@ -283,13 +336,11 @@ async fn test_wildcard_and_suffix_matching() {
         url: webhook_server.uri(),
         secret: None,
         timeout_secs: Some(5),
+        ..Default::default()
     };
 
-    let notifier = Notifier::new(webhook_config);
-    let dedupe = Dedupe::new();
-
     let certstream_task = tokio::spawn(async move {
-        run_certstream_loop(certstream_config, watchlist, notifier, dedupe).await;
+        run_certstream(certstream_config, watchlist, webhook_config).await;
     });
 
     tokio::time::sleep(Duration::from_secs(1)).await;
@@ -316,12 +367,12 @@ async fn test_program_assignment() {
         .to_string(),
     ];
 
-    let ws_port = 19004;
-    start_mock_certstream_server(ws_port, messages).await;
+    let ws_port = start_mock_certstream_server(messages).await;
 
     let certstream_config = CertstreamConfig {
         url: format!("ws://127.0.0.1:{}/", ws_port),
         reconnect_delay_secs: 1,
+        poll_logs: false,
     };
 
     let watchlist_config = WatchlistConfig {
@@ -329,6 +380,7 @@ async fn test_program_assignment() {
         hosts: vec![],
         ips: vec![],
         cidrs: vec![],
+        ..Default::default()
     };
 
     let programs = vec![ProgramConfig {
@@ -337,6 +389,7 @@ async fn test_program_assignment() {
         hosts: vec![],
         cidrs: vec![],
         ips: vec![],
+        ..Default::default()
     }];
 
     let watchlist = Watchlist::from_config(&watchlist_config, &programs).unwrap();
@@ -345,13 +398,11 @@ async fn test_program_assignment() {
         url: webhook_server.uri(),
         secret: Some("test_secret".to_string()),
         timeout_secs: Some(5),
+        ..Default::default()
     };
 
-    let notifier = Notifier::new(webhook_config);
-    let dedupe = Dedupe::new();
-
     let certstream_task = tokio::spawn(async move {
-        run_certstream_loop(certstream_config, watchlist, notifier, dedupe).await;
+        run_certstream(certstream_config, watchlist, webhook_config).await;
     });
 
     tokio::time::sleep(Duration::from_secs(1)).await;